@@ -6,7 +6,9 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use openvote::cds::{get_example, naive_verify_cds_proofs};
+use openvote::cds::{
+    compute_all_blinding_keys, compute_blinding_key, get_example, naive_verify_cds_proofs,
+};
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use std::time::Duration;
 
@@ -41,5 +43,29 @@ fn cds_bench(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(cds_group, cds_bench);
+const BLINDING_KEY_SIZES: [usize; 3] = [64, 256, 1024];
+
+fn blinding_keys_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cds_blinding_keys");
+    group.sample_size(10);
+
+    for &size in BLINDING_KEY_SIZES.iter() {
+        let (cds, _) = get_example(size);
+
+        group.bench_function(BenchmarkId::new("per_voter", size), |bench| {
+            bench.iter(|| {
+                (0..size)
+                    .map(|i| compute_blinding_key(&cds.voting_keys, i))
+                    .collect::<Vec<_>>()
+            });
+        });
+
+        group.bench_function(BenchmarkId::new("cached_chain", size), |bench| {
+            bench.iter(|| compute_all_blinding_keys(&cds.voting_keys));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(cds_group, cds_bench, blinding_keys_bench);
 criterion_main!(cds_group);
@@ -0,0 +1,17 @@
+fn main() {
+    // protobuf bindings are only generated when the proto feature is enabled
+    #[cfg(feature = "proto")]
+    {
+        println!("cargo:rerun-if-changed=proto/openvote.proto");
+        prost_build::compile_protos(&["proto/openvote.proto"], &["proto/"])
+            .expect("failed to compile protobuf schema");
+    }
+
+    // gRPC service of remote proving workers
+    #[cfg(feature = "prover-service")]
+    {
+        println!("cargo:rerun-if-changed=proto/prover.proto");
+        tonic_build::compile_protos("proto/prover.proto")
+            .expect("failed to compile prover service schema");
+    }
+}
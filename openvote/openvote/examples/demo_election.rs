@@ -0,0 +1,158 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// DEMO ELECTION
+// ================================================================================================
+// Runs a deterministic election of 4 voters through the registration, casting and tallying
+// phases of the aggregator, printing the artifacts of each phase and verifying them as an
+// on-chain verifier would. All randomness is drawn from a seeded RNG, so that every run
+// prints the same artifacts.
+//
+// cargo run --release --example demo_election
+
+use openvote::{
+    aggregator::{
+        cast::{EncryptedVote, VoteCollector},
+        register::{Registration, VoterRegistar},
+        tally::VoteTallier,
+    },
+    cds::format_tracker,
+    merkle::build_merkle_tree_with_rng,
+    schnorr::{random_key_pair_with_rng, sign_message_with_rng},
+    utils::{address::Address, ecc::compress_point},
+    verifier::{
+        encode_encrypted_votes, verify_cast_proof, verify_register_proof, verify_tally_result,
+    },
+};
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use winterfell::{ByteWriter, Serializable};
+
+const SEED: u64 = 42;
+const VOTES: [bool; 4] = [true, false, true, true];
+
+fn main() {
+    let mut rng = ChaCha20Rng::seed_from_u64(SEED);
+    let num_voters = VOTES.len();
+    println!("Demo election of {} voters (seed {})", num_voters, SEED);
+
+    // KEY GENERATION
+    // --------------------------------------------------------------------------------------------
+    let (secret_keys, voting_keys): (Vec<_>, Vec<_>) = (0..num_voters)
+        .map(|_| random_key_pair_with_rng(&mut rng))
+        .unzip();
+    let addresses = (0..num_voters)
+        .map(|_| Address::random_with_rng(&mut rng))
+        .collect::<Vec<Address>>();
+    for (i, (voting_key, address)) in voting_keys.iter().zip(addresses.iter()).enumerate() {
+        println!(
+            "voter {}: address {}, voting key {}",
+            i,
+            address,
+            hex::encode(compress_point(voting_key))
+        );
+    }
+
+    // REGISTRATION
+    // --------------------------------------------------------------------------------------------
+    let (tree, hash_indices) = build_merkle_tree_with_rng(&voting_keys, &mut rng);
+    let mut elg_root_bytes = vec![];
+    Serializable::write_batch_into(&tree.root(), &mut elg_root_bytes);
+    println!("\neligibility root: {}", hex::encode(&elg_root_bytes));
+
+    let mut registar = VoterRegistar::new(tree, num_voters);
+    for i in 0..num_voters {
+        let registration = Registration {
+            voting_key: voting_keys[i],
+            merkle_branch: registar.tree.branch(hash_indices[i]),
+            hash_index: hash_indices[i],
+            signature: sign_message_with_rng(
+                secret_keys[i],
+                &voting_keys[i],
+                addresses[i],
+                &mut rng,
+            ),
+            address: addresses[i],
+            ownership_signature: None,
+        };
+        registar
+            .add_registration(registration)
+            .expect("Registration should be accepted.");
+        println!("registered voter {} at leaf {}", i, hash_indices[i]);
+    }
+
+    let register_proof = registar
+        .get_register_proof()
+        .expect("Failed to generate register proof.");
+    println!("register proof: {} bytes", register_proof.len());
+    assert!(
+        verify_register_proof(&elg_root_bytes, &register_proof).unwrap(),
+        "Register proof should be valid."
+    );
+    println!("register proof verified");
+
+    // CASTING
+    // --------------------------------------------------------------------------------------------
+    let registered_keys = registar.voting_keys.clone();
+    let mut collector = VoteCollector::new(registered_keys.clone());
+    for (i, &vote) in VOTES.iter().enumerate() {
+        let encrypted_vote =
+            EncryptedVote::cast_with_rng(i, secret_keys[i], &registered_keys, vote, &mut rng);
+        encrypted_vote
+            .verify(&registered_keys, &collector.cds_context())
+            .expect("Encrypted vote should be valid.");
+        collector
+            .add_encrypted_vote(encrypted_vote)
+            .expect("Encrypted vote should be accepted.");
+        println!(
+            "\nvoter {} voted {}, tracker {}",
+            i,
+            if vote { "yes" } else { "no" },
+            format_tracker(&collector.tracker_for(i).unwrap())
+        );
+    }
+
+    let cast_proof = collector
+        .get_cast_proof()
+        .expect("Failed to generate cast proof.");
+    println!("cast proof: {} bytes", cast_proof.len());
+    let mut voting_keys_bytes = vec![];
+    voting_keys_bytes.write_u8_slice(&(registered_keys.len() as u32).to_be_bytes());
+    for voting_key in registered_keys.iter() {
+        voting_keys_bytes.write_u8_slice(&compress_point(voting_key));
+    }
+    assert!(
+        verify_cast_proof(&voting_keys_bytes, &cast_proof).unwrap(),
+        "Cast proof should be valid."
+    );
+    println!("cast proof verified");
+
+    // TALLYING
+    // --------------------------------------------------------------------------------------------
+    let encrypted_votes = collector
+        .encrypted_votes
+        .iter()
+        .map(|encrypted_vote| encrypted_vote.unwrap())
+        .collect::<Vec<_>>();
+    let encrypted_votes_bytes = encode_encrypted_votes(&encrypted_votes);
+
+    let mut tallier = VoteTallier::new(encrypted_votes);
+    let tally_result = tallier.tally_votes().expect("Failed to tally votes.");
+    let expected_result = VOTES.iter().filter(|&&vote| vote).count() as u32;
+    assert_eq!(tally_result, expected_result);
+    println!(
+        "\ntally result: {} yes, {} no",
+        tally_result,
+        num_voters as u32 - tally_result
+    );
+    assert!(
+        verify_tally_result(&encrypted_votes_bytes, tally_result).unwrap(),
+        "Tally result should be valid."
+    );
+    println!("tally result verified");
+}
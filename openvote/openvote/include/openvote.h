@@ -0,0 +1,72 @@
+#ifndef OPENVOTE_H
+#define OPENVOTE_H
+
+/* Generated by cbindgen from src/capi. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Status codes returned by the C API
+ */
+typedef enum OpenvoteStatus {
+  /**
+   * The proof or result is valid
+   */
+  OPENVOTE_STATUS_VALID = 0,
+  /**
+   * The proof or result is invalid
+   */
+  OPENVOTE_STATUS_INVALID = 1,
+  /**
+   * The input bytes could not be deserialized
+   */
+  OPENVOTE_STATUS_MALFORMED = 2,
+  /**
+   * A required pointer is null
+   */
+  OPENVOTE_STATUS_NULL_POINTER = 3,
+  /**
+   * The verifier panicked, which no input is expected to cause
+   */
+  OPENVOTE_STATUS_PANIC = 4,
+} OpenvoteStatus;
+
+/**
+ * Verify a register proof against the serialized root of the Merkle tree of eligible voters
+ *
+ * # Safety
+ * `elg_root` and `register_proof` must point to `elg_root_len` and `register_proof_len`
+ * readable bytes.
+ */
+enum OpenvoteStatus openvote_verify_register_proof(const uint8_t *elg_root,
+                                                   size_t elg_root_len,
+                                                   const uint8_t *register_proof,
+                                                   size_t register_proof_len);
+
+/**
+ * Verify a cast proof against the voting keys of registered voters
+ *
+ * # Safety
+ * `voting_keys` and `cast_proof` must point to `voting_keys_len` and `cast_proof_len`
+ * readable bytes.
+ */
+enum OpenvoteStatus openvote_verify_cast_proof(const uint8_t *voting_keys,
+                                               size_t voting_keys_len,
+                                               const uint8_t *cast_proof,
+                                               size_t cast_proof_len);
+
+/**
+ * Verify a tally result against the encrypted votes
+ *
+ * # Safety
+ * `encrypted_votes` must point to `encrypted_votes_len` readable bytes.
+ */
+enum OpenvoteStatus openvote_verify_tally_result(const uint8_t *encrypted_votes,
+                                                 size_t encrypted_votes_len,
+                                                 uint32_t tally_result);
+
+#endif /* OPENVOTE_H */
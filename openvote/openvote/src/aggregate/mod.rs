@@ -0,0 +1,205 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// REGIONAL TALLY
+// ================================================================================================
+// Hierarchical elections run an independent election in each precinct, and combine the precinct
+// tallies into a region-level result. The encrypted votes of a precinct sum to
+// (2 * tally_result - num_votes) * G, so that the sum of the encrypted sums of all precincts is
+// (2 * T - N) * G, where T and N are the sums of the precinct tallies and numbers of votes. The
+// regional tally is proven by the STARK tally proofs of the precincts along with this relation,
+// which anyone can check from the encrypted sums without access to the individual ballots.
+
+use crate::{
+    compat::curve::{AffinePoint, ProjectivePoint, Scalar},
+    utils::rescue::{self, digest_bytes},
+    verifier::{decode_encrypted_votes, verify_tally_proof},
+};
+use winterfell::DeserializationError;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+#[cfg(test)]
+mod tests;
+
+// ERRORS
+// ================================================================================================
+
+/// Errors raised when aggregating precinct tallies, with the index of the offending precinct
+#[derive(Debug, PartialEq)]
+pub enum AggregateError {
+    /// This error occurs when no precinct tally is provided
+    NoPrecincts,
+    /// This error occurs when the encrypted votes or tally proof of a precinct cannot be read
+    Deserialization(usize, DeserializationError),
+    /// This error occurs when the tally proof of a precinct is invalid
+    InvalidTallyProof(usize),
+}
+
+// PRECINCT TALLIES
+// ================================================================================================
+
+/// Tally of a precinct election, as published by its aggregator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrecinctTally {
+    /// Encrypted votes of the precinct, as serialized by encode_encrypted_votes
+    pub encrypted_votes: Vec<u8>,
+    /// Number of "yes" votes of the precinct
+    pub tally_result: u32,
+    /// Tally proof of the precinct, as generated by VoteTallier::get_tally_proof
+    pub tally_proof: Vec<u8>,
+}
+
+/// Summary of a precinct tally within a regional tally
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrecinctSummary {
+    /// Number of "yes" votes of the precinct
+    pub tally_result: u32,
+    /// Number of votes of the precinct
+    pub num_votes: u32,
+    /// Sum of the encrypted votes of the precinct
+    pub encrypted_sum: ProjectivePoint,
+    /// Rescue digest of the tally proof of the precinct
+    pub proof_digest: rescue::Hash,
+}
+
+/// Region-level result combining the tallies of precinct elections
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegionalTally {
+    /// Summaries of the precinct tallies, in the order they were aggregated
+    pub precincts: Vec<PrecinctSummary>,
+    /// Total number of "yes" votes
+    pub tally_result: u64,
+    /// Total number of votes
+    pub num_votes: u64,
+    /// Sum of the encrypted votes of all precincts
+    pub encrypted_sum: ProjectivePoint,
+}
+
+impl RegionalTally {
+    /// Total number of "no" votes
+    pub fn no_votes(&self) -> u64 {
+        self.num_votes - self.tally_result
+    }
+
+    /// Check that the totals are the sums of the precinct tallies, and that the encrypted
+    /// sums of the precincts add up to (2 * tally_result - num_votes) * G. The precinct
+    /// summaries are trusted, see verify_regional_tally to check them against tally proofs.
+    pub fn is_consistent(&self) -> bool {
+        let tally_result: u64 = self.precincts.iter().map(|p| p.tally_result as u64).sum();
+        let num_votes: u64 = self.precincts.iter().map(|p| p.num_votes as u64).sum();
+        let encrypted_sum = self
+            .precincts
+            .iter()
+            .fold(ProjectivePoint::identity(), |sum, p| sum + p.encrypted_sum);
+
+        self.tally_result == tally_result
+            && self.num_votes == num_votes
+            && self.tally_result <= self.num_votes
+            && points_equal(self.encrypted_sum, encrypted_sum)
+            && self.precincts.iter().all(|p| {
+                p.tally_result <= p.num_votes
+                    && points_equal(
+                        p.encrypted_sum,
+                        tally_point(p.tally_result as u64, p.num_votes as u64),
+                    )
+            })
+            && points_equal(self.encrypted_sum, tally_point(tally_result, num_votes))
+    }
+}
+
+// AGGREGATION
+// ================================================================================================
+
+/// Verify the tally proofs of precinct elections and combine their tallies
+pub fn aggregate_precincts(precincts: &[PrecinctTally]) -> Result<RegionalTally, AggregateError> {
+    if precincts.is_empty() {
+        return Err(AggregateError::NoPrecincts);
+    }
+    let mut summaries = Vec::with_capacity(precincts.len());
+    for (index, precinct) in precincts.iter().enumerate() {
+        let encrypted_votes = decode_encrypted_votes(&precinct.encrypted_votes)
+            .map_err(|e| AggregateError::Deserialization(index, e))?;
+        let is_valid = verify_tally_proof(
+            &precinct.encrypted_votes,
+            precinct.tally_result,
+            &precinct.tally_proof,
+        )
+        .map_err(|e| AggregateError::Deserialization(index, e))?;
+        if !is_valid {
+            return Err(AggregateError::InvalidTallyProof(index));
+        }
+        let encrypted_sum =
+            encrypted_votes
+                .iter()
+                .fold(ProjectivePoint::identity(), |sum, encrypted_vote| {
+                    sum + ProjectivePoint::from(AffinePoint::from_raw_coordinates(*encrypted_vote))
+                });
+        summaries.push(PrecinctSummary {
+            tally_result: precinct.tally_result,
+            num_votes: encrypted_votes.len() as u32,
+            encrypted_sum,
+            proof_digest: digest_bytes(&precinct.tally_proof),
+        });
+    }
+
+    Ok(RegionalTally {
+        tally_result: summaries.iter().map(|p| p.tally_result as u64).sum(),
+        num_votes: summaries.iter().map(|p| p.num_votes as u64).sum(),
+        encrypted_sum: summaries
+            .iter()
+            .fold(ProjectivePoint::identity(), |sum, p| sum + p.encrypted_sum),
+        precincts: summaries,
+    })
+}
+
+/// Verify a regional tally against the published precinct tallies, i.e. that each
+/// precinct tally is proven and summarized correctly and that the totals add up
+pub fn verify_regional_tally(region: &RegionalTally, precincts: &[PrecinctTally]) -> bool {
+    match aggregate_precincts(precincts) {
+        Ok(expected) => {
+            region.is_consistent()
+                && region.precincts.len() == expected.precincts.len()
+                && region
+                    .precincts
+                    .iter()
+                    .zip(expected.precincts.iter())
+                    .all(|(p, q)| {
+                        p.tally_result == q.tally_result
+                            && p.num_votes == q.num_votes
+                            && p.proof_digest == q.proof_digest
+                            && points_equal(p.encrypted_sum, q.encrypted_sum)
+                    })
+        }
+        Err(_) => false,
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Sum of the encrypted votes of an election with the given tally, i.e.
+/// (2 * tally_result - num_votes) * G, computed without wrapping around
+fn tally_point(tally_result: u64, num_votes: u64) -> ProjectivePoint {
+    let yes_minus_no = 2 * tally_result as u128;
+    let diff = match yes_minus_no.checked_sub(num_votes as u128) {
+        Some(diff) => Scalar::from(diff as u64),
+        None => -Scalar::from((num_votes as u128 - yes_minus_no) as u64),
+    };
+    ProjectivePoint::generator() * diff
+}
+
+/// Equality of points, including the identity which has no unique projective representation
+fn points_equal(a: ProjectivePoint, b: ProjectivePoint) -> bool {
+    if a.is_identity() || b.is_identity() {
+        a.is_identity() && b.is_identity()
+    } else {
+        a == b
+    }
+}
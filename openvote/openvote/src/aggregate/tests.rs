@@ -0,0 +1,73 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+use crate::aggregator::tally::VoteTallier;
+
+fn precinct_tally(num_votes: usize) -> PrecinctTally {
+    let (mut tallier, _) = VoteTallier::get_example(num_votes);
+    let tally_result = tallier.tally_votes().unwrap();
+    PrecinctTally {
+        encrypted_votes: tallier.encrypted_votes_bytes(),
+        tally_result,
+        tally_proof: tallier.get_tally_proof().unwrap(),
+    }
+}
+
+#[test]
+fn aggregate_test_regional_tally() {
+    let precincts = vec![precinct_tally(2), precinct_tally(4)];
+    let region = aggregate_precincts(&precincts).unwrap();
+
+    assert_eq!(region.precincts.len(), 2);
+    assert_eq!(region.num_votes, 6);
+    assert_eq!(
+        region.tally_result,
+        (precincts[0].tally_result + precincts[1].tally_result) as u64
+    );
+    assert_eq!(region.no_votes(), region.num_votes - region.tally_result);
+    assert!(region.is_consistent());
+    assert!(verify_regional_tally(&region, &precincts));
+
+    // totals must be the sums of the precinct tallies
+    let mut tampered = region.clone();
+    tampered.tally_result += 1;
+    assert!(!tampered.is_consistent());
+    assert!(!verify_regional_tally(&tampered, &precincts));
+
+    // moving a vote between precincts keeps the totals but not the encrypted sums
+    let mut tampered = region.clone();
+    if tampered.precincts[0].tally_result > 0 {
+        tampered.precincts[0].tally_result -= 1;
+        tampered.precincts[1].tally_result += 1;
+    } else {
+        tampered.precincts[0].tally_result += 1;
+        tampered.precincts[1].tally_result -= 1;
+    }
+    assert!(!tampered.is_consistent());
+    assert!(!verify_regional_tally(&tampered, &precincts));
+
+    // the regional tally must cover the same precincts
+    assert!(!verify_regional_tally(&region, &precincts[..1]));
+}
+
+#[test]
+fn aggregate_test_invalid_precinct() {
+    assert_eq!(aggregate_precincts(&[]), Err(AggregateError::NoPrecincts));
+
+    let mut precincts = vec![precinct_tally(2), precinct_tally(2)];
+    precincts[1].tally_result = (precincts[1].tally_result + 1) % 3;
+    assert!(aggregate_precincts(&precincts).is_err());
+
+    let mut precincts = vec![precinct_tally(2)];
+    precincts[0].encrypted_votes.push(0);
+    assert!(matches!(
+        aggregate_precincts(&precincts),
+        Err(AggregateError::Deserialization(0, _))
+    ));
+}
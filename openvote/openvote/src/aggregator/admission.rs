@@ -0,0 +1,172 @@
+use super::{cast::EncryptedVote, register::Registration};
+use crate::utils::address::Address;
+#[cfg(not(feature = "std"))]
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::collections::{BTreeMap, BTreeSet};
+use winterfell::{
+    crypto::{hashers::Blake3_256, Digest, Hasher},
+    math::fields::f63::BaseElement,
+    Serializable,
+};
+
+/// Submission received by a public-facing aggregator
+#[derive(Debug, Clone, Copy)]
+pub enum Submission<'a> {
+    /// Registration submitted during voter registration phase
+    Registration(&'a Registration),
+    /// Encrypted vote submitted during vote casting phase
+    EncryptedVote(&'a EncryptedVote),
+}
+
+impl<'a> Submission<'a> {
+    /// Serialize the submission so that it can be hashed
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Submission::Registration(registration) => registration.to_bytes(),
+            Submission::EncryptedVote(encrypted_vote) => encrypted_vote.to_bytes(),
+        }
+    }
+}
+
+/// Policy consulted before a submission is verified.
+/// It allows an aggregator to cheaply shed garbage submissions
+/// before running expensive verification of Merkle proofs,
+/// Schnorr signatures or CDS proofs.
+pub trait AdmissionPolicy {
+    /// Return true if the submission should be verified and processed
+    fn admit(&mut self, submission: Submission) -> bool;
+
+    /// Called once an admitted submission has passed verification of its Schnorr
+    /// signature or CDS proof, so that its submitter is authenticated.
+    /// The default implementation does nothing.
+    fn record(&mut self, _submission: Submission) {}
+}
+
+/// Policy that admits every submission
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AllowAll;
+
+impl AdmissionPolicy for AllowAll {
+    fn admit(&mut self, _submission: Submission) -> bool {
+        true
+    }
+}
+
+/// Policy that bounds the number of verified submissions per leaf of the eligibility
+/// tree (for registrations) and per voter index (for encrypted votes). Submissions
+/// are only charged once verified, so that nobody can exhaust the quota of another
+/// voter with forged submissions, and at most one counter is kept per eligible voter.
+#[derive(Debug, Clone)]
+pub struct SubmissionQuota {
+    /// Maximum number of submissions per submitter
+    pub max_submissions: usize,
+    registration_counts: BTreeMap<usize, usize>,
+    vote_counts: BTreeMap<usize, usize>,
+}
+
+impl SubmissionQuota {
+    /// Create a quota allowing at most max_submissions
+    /// submissions per submitter
+    pub fn new(max_submissions: usize) -> Self {
+        Self {
+            max_submissions,
+            registration_counts: BTreeMap::new(),
+            vote_counts: BTreeMap::new(),
+        }
+    }
+}
+
+impl AdmissionPolicy for SubmissionQuota {
+    fn admit(&mut self, submission: Submission) -> bool {
+        let count = match submission {
+            Submission::Registration(registration) => {
+                self.registration_counts.get(&registration.hash_index)
+            }
+            Submission::EncryptedVote(encrypted_vote) => {
+                self.vote_counts.get(&encrypted_vote.voter_index())
+            }
+        };
+        count.map_or(true, |&count| count < self.max_submissions)
+    }
+
+    fn record(&mut self, submission: Submission) {
+        let count = match submission {
+            Submission::Registration(registration) => self
+                .registration_counts
+                .entry(registration.hash_index)
+                .or_insert(0),
+            Submission::EncryptedVote(encrypted_vote) => self
+                .vote_counts
+                .entry(encrypted_vote.voter_index())
+                .or_insert(0),
+        };
+        *count += 1;
+    }
+}
+
+/// Policy that only admits registrations from a fixed set
+/// of Ethereum addresses. Encrypted votes are always admitted
+/// since they can only come from registered voters.
+#[derive(Debug, Clone)]
+pub struct Allowlist {
+    addresses: BTreeSet<Address>,
+}
+
+impl Allowlist {
+    /// Create an allowlist from a list of Ethereum addresses
+    pub fn new(addresses: &[Address]) -> Self {
+        Self {
+            addresses: addresses.iter().copied().collect(),
+        }
+    }
+}
+
+impl AdmissionPolicy for Allowlist {
+    fn admit(&mut self, submission: Submission) -> bool {
+        match submission {
+            Submission::Registration(registration) => {
+                self.addresses.contains(&registration.address)
+            }
+            Submission::EncryptedVote(_) => true,
+        }
+    }
+}
+
+/// Policy that only admits submissions whose Blake3 hash starts
+/// with a given number of zero bits. Voters grind the randomness
+/// of their Schnorr signature or CDS proof until the condition holds.
+#[derive(Debug, Clone, Copy)]
+pub struct ProofOfWork {
+    /// Number of leading zero bits required
+    pub difficulty: u32,
+}
+
+impl ProofOfWork {
+    /// Create a proof-of-work policy with the given difficulty
+    pub fn new(difficulty: u32) -> Self {
+        Self { difficulty }
+    }
+
+    /// Return the number of leading zero bits of the hash of a submission
+    pub fn leading_zeros(submission: Submission) -> u32 {
+        let digest = Blake3_256::<BaseElement>::hash(&submission.to_bytes()).as_bytes();
+        let mut leading_zeros = 0;
+        for byte in digest.iter() {
+            leading_zeros += byte.leading_zeros();
+            if *byte != 0 {
+                break;
+            }
+        }
+        leading_zeros
+    }
+}
+
+impl AdmissionPolicy for ProofOfWork {
+    fn admit(&mut self, submission: Submission) -> bool {
+        Self::leading_zeros(submission) >= self.difficulty
+    }
+}
@@ -0,0 +1,268 @@
+use super::cast::{CollectorError, EncryptedVote, VoteCollector};
+use crate::beacon::{
+    local_randomness, Beacon, BeaconError, BeaconOutput, BeaconRng, BATCH_WEIGHTS_DOMAIN,
+};
+use crate::cds::{compute_challenge, verify_cds_proof_projective};
+use crate::compat::curve::{AffinePoint, ProjectivePoint, Scalar};
+use crate::utils::ecc::is_in_prime_subgroup;
+use rand_core::RngCore;
+use winterfell::Serializable;
+
+#[cfg(feature = "concurrent")]
+use rayon::prelude::*;
+
+/// Queue of encrypted votes whose CDS proofs are verified in batch.
+///
+/// The four point equations of every queued CDS proof are combined
+/// with random weights into a single equation, so that the terms
+/// involving the generator are folded into one scalar multiplication
+/// for the whole batch. The weights are drawn from a beacon round
+/// published after the queue was closed, see `close`, and bound to
+/// the queued encrypted votes. When the `concurrent` feature
+/// is enabled, per-ballot work is spread across threads. If the batch
+/// equation does not hold, every proof is verified individually to
+/// find the invalid ones.
+#[derive(Debug, Default, Clone)]
+pub struct CastQueue {
+    pending: Vec<EncryptedVote>,
+    closing_round: Option<u64>,
+}
+
+impl CastQueue {
+    /// Create an empty queue
+    pub fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            closing_round: None,
+        }
+    }
+
+    /// Number of encrypted votes waiting for verification
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Return true if no encrypted vote is waiting for verification
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Queue an encrypted vote for verification. This reopens a closed queue.
+    pub fn push(&mut self, encrypted_vote: EncryptedVote) {
+        self.pending.push(encrypted_vote);
+        self.closing_round = None;
+    }
+
+    /// Close the queue at the latest published round of the beacon, so that the weights
+    /// of the batch equation are drawn from a round which was unknown to voters
+    pub fn close(&mut self, latest_round: u64) {
+        self.closing_round = Some(latest_round);
+    }
+
+    /// Verify all queued encrypted votes and add the valid ones to
+    /// the collector, in the order they were queued.
+    /// Return the result of processing each queued encrypted vote.
+    pub fn flush(&mut self, collector: &mut VoteCollector) -> Vec<Result<(), CollectorError>> {
+        self.flush_with_randomness(collector, &local_randomness())
+    }
+
+    /// Same as `flush`, drawing the weights of the batch equation from the given beacon
+    /// round, which must be later than the round at which the queue was closed
+    pub fn flush_with_beacon<B: Beacon>(
+        &mut self,
+        collector: &mut VoteCollector,
+        beacon: &B,
+        round: u64,
+    ) -> Result<Vec<Result<(), CollectorError>>, BeaconError> {
+        match self.closing_round {
+            Some(closing_round) if round > closing_round => {}
+            _ => return Err(BeaconError::PrematureRound(round)),
+        }
+        let randomness = beacon.randomness(round)?;
+        self.closing_round = None;
+        Ok(self.flush_with_randomness(collector, &randomness))
+    }
+
+    fn flush_with_randomness(
+        &mut self,
+        collector: &mut VoteCollector,
+        randomness: &BeaconOutput,
+    ) -> Vec<Result<(), CollectorError>> {
+        let pending = core::mem::take(&mut self.pending);
+        let num_voters = collector.voting_keys.len();
+
+        let verified = if pending.iter().all(|ev| ev.voter_index < num_voters)
+            && batch_verify_cds_proofs(collector, &pending, randomness)
+        {
+            vec![true; pending.len()]
+        } else {
+            individually_verify_cds_proofs(collector, &pending)
+        };
+
+        pending
+            .into_iter()
+            .zip(verified.into_iter())
+            .map(|(encrypted_vote, is_valid)| {
+                let result = if is_valid {
+                    collector.add_verified_encrypted_vote(encrypted_vote)
+                } else {
+                    collector.record_invalid_ballot(encrypted_vote);
+                    Err(CollectorError::InvalidEncryptedVote)
+                };
+                collector.log_rejection(&encrypted_vote, result)
+            })
+            .collect()
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Verify CDS proofs of all encrypted votes with a single random linear
+/// combination of their equations. Voter indices must be in range.
+fn batch_verify_cds_proofs(
+    collector: &VoteCollector,
+    pending: &[EncryptedVote],
+    randomness: &BeaconOutput,
+) -> bool {
+    // the weights are bound to the encrypted votes, so that they cannot
+    // be chosen to cancel the equations of invalid proofs
+    let context = pending
+        .iter()
+        .flat_map(|encrypted_vote| encrypted_vote.to_bytes())
+        .collect::<Vec<u8>>();
+    let mut rng = BeaconRng::new(randomness, BATCH_WEIGHTS_DOMAIN, &context);
+    let weights = pending
+        .iter()
+        .map(|_| {
+            [
+                random_weight(&mut rng),
+                random_weight(&mut rng),
+                random_weight(&mut rng),
+                random_weight(&mut rng),
+            ]
+        })
+        .collect::<Vec<[Scalar; 4]>>();
+
+    #[cfg(not(feature = "concurrent"))]
+    let partial_sums = pending
+        .iter()
+        .zip(weights.iter())
+        .map(|(encrypted_vote, rho)| weighted_equation(collector, encrypted_vote, rho))
+        .collect::<Vec<Option<(ProjectivePoint, Scalar)>>>();
+
+    #[cfg(feature = "concurrent")]
+    let partial_sums = pending
+        .par_iter()
+        .zip(weights.par_iter())
+        .map(|(encrypted_vote, rho)| weighted_equation(collector, encrypted_vote, rho))
+        .collect::<Vec<Option<(ProjectivePoint, Scalar)>>>();
+
+    let mut sum = ProjectivePoint::identity();
+    let mut generator_coeff = Scalar::zero();
+    for partial_sum in partial_sums {
+        match partial_sum {
+            Some((point, coeff)) => {
+                sum += point;
+                generator_coeff += coeff;
+            }
+            None => return false,
+        }
+    }
+    sum -= ProjectivePoint::generator() * generator_coeff;
+
+    sum.is_identity()
+}
+
+/// Combine the four point equations of a CDS proof with the random weights rho
+///   rho1 * (a1 - r1 * G - d1 * vk)
+/// + rho2 * (b1 - r1 * bk - d1 * (ev + G))
+/// + rho3 * (a2 - r2 * G - d2 * vk)
+/// + rho4 * (b2 - r2 * bk - d2 * (ev - G))
+/// and return the sum without its generator term, together with the
+/// coefficient of the generator. Return None if the challenge is invalid,
+/// or if a supplied point has a component of small order.
+fn weighted_equation(
+    collector: &VoteCollector,
+    encrypted_vote: &EncryptedVote,
+    rho: &[Scalar; 4],
+) -> Option<(ProjectivePoint, Scalar)> {
+    let voter_index = encrypted_vote.voter_index;
+    let voting_key = ProjectivePoint::from(AffinePoint::from_raw_coordinates(
+        collector.voting_keys[voter_index],
+    ));
+    let blinding_key = collector.blinding_keys[voter_index];
+    let [d1, d2, r1, r2] = encrypted_vote.proof_scalars;
+    let [a1, b1, a2, b2] = encrypted_vote.proof_points;
+
+    // random weights only cancel small-order components with some probability,
+    // so that the supplied points are checked to be in the prime-order subgroup
+    if !is_in_prime_subgroup(encrypted_vote.encrypted_vote)
+        || !encrypted_vote
+            .proof_points
+            .iter()
+            .all(|&point| is_in_prime_subgroup(point))
+    {
+        return None;
+    }
+
+    let c_scalar = compute_challenge(
+        voter_index,
+        voting_key,
+        blinding_key,
+        encrypted_vote.encrypted_vote,
+        &encrypted_vote.proof_points,
+        &collector.cds_context(),
+    );
+    if c_scalar != d1 + d2 {
+        return None;
+    }
+
+    let point = a1 * rho[0] + b1 * rho[1] + a2 * rho[2] + b2 * rho[3]
+        - voting_key * (rho[0] * d1 + rho[2] * d2)
+        - blinding_key * (rho[1] * r1 + rho[3] * r2)
+        - encrypted_vote.encrypted_vote * (rho[1] * d1 + rho[3] * d2);
+    let generator_coeff = rho[0] * r1 + rho[1] * d1 + rho[2] * r2 - rho[3] * d2;
+
+    Some((point, generator_coeff))
+}
+
+/// Random 128-bit weight of the batch equation
+fn random_weight<R: RngCore>(rng: &mut R) -> Scalar {
+    Scalar::from(rng.next_u64()) * Scalar::from(1u64 << 32).square() + Scalar::from(rng.next_u64())
+}
+
+/// Verify CDS proofs of all encrypted votes one by one
+fn individually_verify_cds_proofs(
+    collector: &VoteCollector,
+    pending: &[EncryptedVote],
+) -> Vec<bool> {
+    let context = collector.cds_context();
+    let verify = |encrypted_vote: &EncryptedVote| {
+        let voter_index = encrypted_vote.voter_index;
+        if voter_index >= collector.voting_keys.len() {
+            return false;
+        }
+        let voting_key = ProjectivePoint::from(AffinePoint::from_raw_coordinates(
+            collector.voting_keys[voter_index],
+        ));
+        verify_cds_proof_projective(
+            voter_index,
+            voting_key,
+            collector.blinding_keys[voter_index],
+            encrypted_vote.encrypted_vote,
+            &encrypted_vote.proof_points,
+            &encrypted_vote.proof_scalars,
+            &context,
+        )
+        .is_ok()
+    };
+
+    #[cfg(not(feature = "concurrent"))]
+    let verified = pending.iter().map(verify).collect();
+
+    #[cfg(feature = "concurrent")]
+    let verified = pending.par_iter().map(verify).collect();
+
+    verified
+}
@@ -0,0 +1,104 @@
+use super::tally::{TallierError, VoteTallier};
+#[cfg(not(feature = "std"))]
+use alloc::{collections::BTreeMap, vec::Vec};
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+/// Counts of a group of voters in a result breakdown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GroupCount {
+    /// Group label committed in the leaves of the voters of the group
+    pub group: u32,
+    /// Number of votes of the group
+    pub num_votes: u32,
+    /// Number of "yes" votes of the group
+    pub tally_result: u32,
+}
+
+/// Result of an election broken down by group of voters. Only groups with more votes
+/// than the threshold are published, the others are only counted in the totals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultBreakdown {
+    /// Number of votes a group must exceed to be published
+    pub threshold: u32,
+    /// Counts of published groups, by increasing group label
+    pub groups: Vec<GroupCount>,
+    /// Number of suppressed groups
+    pub num_suppressed: usize,
+    /// Total number of votes
+    pub num_votes: u64,
+    /// Total number of "yes" votes
+    pub tally_result: u64,
+}
+
+/// Type that tallies the votes of each group of voters separately, to publish a result
+/// breakdown. Encrypted votes cancel their blinding keys within a group only if the votes
+/// of the group are cast with a VoteCollector of the voting keys of the group, see
+/// VoterRegistar::voting_keys_by_group.
+///
+/// Suppression of small groups is cosmetic: the encrypted votes of every group are public,
+/// so anyone can tally a suppressed group on their own. It offers no privacy to the voters
+/// of small groups.
+#[derive(Debug, Clone)]
+pub struct GroupTallier {
+    /// Number of votes a group must exceed to be published
+    pub threshold: u32,
+    /// Tallier of the votes of each group, by group label
+    pub talliers: BTreeMap<u32, VoteTallier>,
+}
+
+impl GroupTallier {
+    /// Create an object of type GroupTallier publishing groups of more than threshold votes
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            threshold,
+            talliers: BTreeMap::new(),
+        }
+    }
+
+    /// Add the tallier of the votes of a group, and return the tallier
+    /// it replaces if the group was already added
+    pub fn insert(&mut self, group: u32, tallier: VoteTallier) -> Option<VoteTallier> {
+        self.talliers.insert(group, tallier)
+    }
+
+    /// Tally the votes of each group, and leave out of the breakdown the counts of groups
+    /// which do not exceed the threshold. If a single group is left out, the smallest
+    /// published group is left out along with it, so that the counts of the left out group
+    /// cannot be read off the totals of the breakdown.
+    pub fn tally_groups(&mut self) -> Result<ResultBreakdown, TallierError> {
+        let mut counts = Vec::with_capacity(self.talliers.len());
+        for (&group, tallier) in self.talliers.iter_mut() {
+            counts.push(GroupCount {
+                group,
+                num_votes: tallier.encrypted_votes.len() as u32,
+                tally_result: tallier.tally_votes()?,
+            });
+        }
+        let num_votes = counts.iter().map(|count| count.num_votes as u64).sum();
+        let tally_result = counts.iter().map(|count| count.tally_result as u64).sum();
+
+        let (mut groups, suppressed): (Vec<GroupCount>, Vec<GroupCount>) = counts
+            .into_iter()
+            .partition(|count| count.num_votes > self.threshold);
+        let mut num_suppressed = suppressed.len();
+        if num_suppressed == 1 {
+            if let Some((index, _)) = groups
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, count)| count.num_votes)
+            {
+                groups.remove(index);
+                num_suppressed += 1;
+            }
+        }
+
+        Ok(ResultBreakdown {
+            threshold: self.threshold,
+            groups,
+            num_suppressed,
+            num_votes,
+            tally_result,
+        })
+    }
+}
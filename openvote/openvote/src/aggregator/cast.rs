@@ -1,27 +1,275 @@
+pub use crate::cds::CdsVerificationError;
+
 use crate::{
-    cds::{verify_cds_proof, CDSProver},
-    schnorr::projective_to_elements,
-    utils::ecc,
+    backend::{CastBackend, ElectionProver},
+    cds::{
+        compute_blinding_key, compute_tracker, compute_vote_commitment, concat_proof_points,
+        constants::UNBOUND_CONTEXT, election_context, encrypt_vote_and_compute_proof_in_context,
+        read_ballot_signature, read_validity_bitmap, signed_ballot_digest, validity_bitmap_size,
+        verify_cds_proof_projective, write_ballot_signature, write_validity_bitmap,
+        BallotSignature, PublicInputs as CdsPublicInputs, ShardInfo,
+    },
+    chain::{ballot_commitment, PhaseCommitment},
+    compat::curve::{AffinePoint, ProjectivePoint, Scalar},
+    manifest::ElectionManifest,
+    planner::FriConfig,
+    schnorr::{sign_digest, verify_digest_signature},
+    utils::{
+        constant_time::ct_eq_bytes,
+        ecc::{self, projective_to_elements},
+        ordering::KeyOrder,
+        rescue, wire,
+    },
 };
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use core::ops::Range;
+use rand_core::{OsRng, RngCore};
 use winterfell::{
-    math::{
-        curves::curve_f63::{AffinePoint, ProjectivePoint, Scalar},
-        fields::f63::BaseElement,
-        FieldElement,
-    },
-    ByteReader, ByteWriter, Deserializable, DeserializationError, Prover, ProverError,
-    Serializable, SliceReader,
+    math::{fields::f63::BaseElement, FieldElement},
+    ByteReader, ByteWriter, Deserializable, DeserializationError, ProverError, Serializable,
+    SliceReader, StarkProof,
 };
 
-use super::{build_options, constants::*};
+#[cfg(feature = "std")]
+use super::pool::{Job, VerificationPool};
+#[cfg(feature = "std")]
+use crate::utils::checkpoint::CheckpointError;
+#[cfg(feature = "std")]
+use crate::utils::proof_store::{inputs_key, ProofStore, StoreDigest};
+#[cfg(feature = "std")]
+use log::debug;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+use super::{
+    admission::{AdmissionPolicy, AllowAll, Submission},
+    build_options_with_fri,
+    constants::*,
+    rejection::{RejectionLog, RejectionReason, Submitter},
+    status::{ProofRecord, ProofTimer},
+};
+
+/// Number of bytes of the ballot in the slot of each voter of a serialized VoteCollector:
+/// compressed encrypted vote and CDS proof points, followed by the CDS proof scalars
+pub const BYTES_PER_BALLOT: usize =
+    (1 + PROOF_NUM_POINTS) * BYTES_PER_COMPRESSED_POINT + PROOF_NUM_SCALARS * BYTES_PER_SCALAR;
+
+/// Prefix of sealed VoteCollector objects, which also versions their encoding, see
+/// VoteCollector::seal
+pub const SEALED_COLLECTOR_MAGIC: [u8; 4] = *b"ovs1";
+
+/// Number of bytes of the key sealing the ballot slots of a VoteCollector
+pub const SEALING_KEY_SIZE: usize = 32;
+
+/// Number of bytes of the nonce of a sealed ballot slot
+const SLOT_NONCE_SIZE: usize = 12;
+
+/// Number of bytes of the content of a sealed ballot slot: flag, ballot, and signature of
+/// the voter for excluded ballots, padded with zeros
+const BYTES_PER_SLOT_CONTENT: usize = 1 + BYTES_PER_BALLOT + BYTES_PER_SIGNATURE;
+
+/// Number of bytes of the sealed ballot slot of each voter: nonce, then slot content
+/// encrypted with ChaCha20-Poly1305, followed by the authentication tag
+pub const BYTES_PER_SEALED_SLOT: usize = SLOT_NONCE_SIZE + BYTES_PER_SLOT_CONTENT + 16;
 
 /// Encrypted vote submitted by a registered voter
 #[derive(Debug, Clone, Copy)]
 pub struct EncryptedVote {
-    voter_index: usize,
-    encrypted_vote: ProjectivePoint,
-    proof_points: [ProjectivePoint; PROOF_NUM_POINTS],
-    proof_scalars: [Scalar; PROOF_NUM_SCALARS],
+    pub(crate) voter_index: usize,
+    pub(crate) encrypted_vote: ProjectivePoint,
+    pub(crate) proof_points: [ProjectivePoint; PROOF_NUM_POINTS],
+    pub(crate) proof_scalars: [Scalar; PROOF_NUM_SCALARS],
+    pub(crate) signature: Option<BallotSignature>,
+}
+
+impl EncryptedVote {
+    /// Create an encrypted vote submitted by the voter at voter_index
+    pub fn new(
+        voter_index: usize,
+        encrypted_vote: ProjectivePoint,
+        proof_points: [ProjectivePoint; PROOF_NUM_POINTS],
+        proof_scalars: [Scalar; PROOF_NUM_SCALARS],
+    ) -> Self {
+        Self {
+            voter_index,
+            encrypted_vote,
+            proof_points,
+            proof_scalars,
+            signature: None,
+        }
+    }
+
+    /// Attach the signature of the ballot with the voting key of the voter,
+    /// see signed_ballot_digest
+    pub fn with_signature(mut self, signature: BallotSignature) -> Self {
+        self.signature = Some(signature);
+        self
+    }
+
+    /// Signature of the ballot with the voting key of the voter, if any
+    pub fn signature(&self) -> Option<BallotSignature> {
+        self.signature
+    }
+
+    /// Digest of the ballot signed by the voter in the given election context
+    fn signed_digest(&self, context: &[BaseElement; DIGEST_SIZE]) -> rescue::Hash {
+        signed_ballot_digest(
+            self.voter_index,
+            &projective_to_elements(self.encrypted_vote),
+            &concat_proof_points(&self.proof_points),
+            &self.proof_scalars,
+            context,
+        )
+    }
+
+    /// Return true if the ballot is signed with the given voting key in the given
+    /// election context
+    pub(crate) fn is_signed_by(
+        &self,
+        voting_key: [BaseElement; AFFINE_POINT_WIDTH],
+        context: &[BaseElement; DIGEST_SIZE],
+    ) -> bool {
+        self.signature.map_or(false, |signature| {
+            verify_digest_signature(voting_key, &self.signed_digest(context), signature).is_ok()
+        })
+    }
+
+    /// Encrypt the vote of the voter at voter_index with their secret key and
+    /// prove its validity against the list of voting keys of registered voters
+    pub fn cast(
+        voter_index: usize,
+        secret_key: Scalar,
+        voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+        vote: bool,
+    ) -> Self {
+        Self::cast_with_rng(voter_index, secret_key, voting_keys, vote, &mut OsRng)
+    }
+
+    /// Same as `cast`, drawing the randomness of the CDS proof
+    /// from the given random number generator
+    pub fn cast_with_rng<R: RngCore>(
+        voter_index: usize,
+        secret_key: Scalar,
+        voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+        vote: bool,
+        rng: &mut R,
+    ) -> Self {
+        Self::cast_in_context(
+            voter_index,
+            secret_key,
+            voting_keys,
+            vote,
+            &UNBOUND_CONTEXT,
+            rng,
+        )
+    }
+
+    /// Same as `cast`, for an election whose collector is bound to
+    /// the manifest with the given digest
+    pub fn cast_with_manifest(
+        voter_index: usize,
+        secret_key: Scalar,
+        voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+        vote: bool,
+        manifest_digest: &[BaseElement; DIGEST_SIZE],
+    ) -> Self {
+        Self::cast_in_context(
+            voter_index,
+            secret_key,
+            voting_keys,
+            vote,
+            manifest_digest,
+            &mut OsRng,
+        )
+    }
+
+    fn cast_in_context<R: RngCore>(
+        voter_index: usize,
+        secret_key: Scalar,
+        voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+        vote: bool,
+        context: &[BaseElement; DIGEST_SIZE],
+        rng: &mut R,
+    ) -> Self {
+        let voting_key = ecc::mul_generator_constant_time(secret_key);
+        let blinding_key = ProjectivePoint::from(AffinePoint::from_raw_coordinates(
+            compute_blinding_key(voting_keys, voter_index),
+        ));
+        let (encrypted_vote, proof_scalars, proof_points) =
+            encrypt_vote_and_compute_proof_in_context(
+                voter_index,
+                secret_key,
+                voting_key,
+                blinding_key,
+                vote,
+                context,
+                rng,
+            );
+        let encrypted_vote = Self::new(voter_index, encrypted_vote, proof_points, proof_scalars);
+        let signature = sign_digest(secret_key, &encrypted_vote.signed_digest(context));
+        encrypted_vote.with_signature(signature)
+    }
+
+    /// Index of the voter who submitted this encrypted vote
+    pub fn voter_index(&self) -> usize {
+        self.voter_index
+    }
+
+    /// Verify the CDS proof of this encrypted vote against the list of
+    /// voting keys of registered voters and the election context of the
+    /// collector, see VoteCollector::cds_context, so that a voter can check
+    /// their encrypted vote before submitting it.
+    pub fn verify(
+        &self,
+        voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+        context: &[BaseElement; DIGEST_SIZE],
+    ) -> Result<(), CdsVerificationError> {
+        let voting_key = voting_keys
+            .get(self.voter_index)
+            .ok_or(CdsVerificationError::InvalidVoterIndex)?;
+        let blinding_keys = VoteCollector::compute_blinding_keys(voting_keys);
+        let voting_key = ProjectivePoint::from(AffinePoint::from_raw_coordinates(*voting_key));
+        verify_cds_proof_projective(
+            self.voter_index,
+            voting_key,
+            blinding_keys[self.voter_index],
+            self.encrypted_vote,
+            &self.proof_points,
+            &self.proof_scalars,
+            context,
+        )
+    }
+}
+
+impl Serializable for EncryptedVote {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u32(self.voter_index as u32);
+        ecc::write_compressed_points(&projective_to_elements(self.encrypted_vote), target);
+        for &proof_point in self.proof_points.iter() {
+            ecc::write_compressed_points(&projective_to_elements(proof_point), target);
+        }
+        Serializable::write_batch_into(&self.proof_scalars, target);
+    }
+}
+
+impl Deserializable for EncryptedVote {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let voter_index = source.read_u32()? as usize;
+        let encrypted_vote = ecc::read_compressed_point(source)?;
+        let mut points = [BaseElement::ZERO; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH];
+        points.copy_from_slice(&ecc::read_compressed_points(source, PROOF_NUM_POINTS)?);
+        let mut scalars = [Scalar::zero(); PROOF_NUM_SCALARS];
+        scalars.copy_from_slice(&ecc::read_canonical_scalars(source, PROOF_NUM_SCALARS)?);
+        Ok(encrypted_vote_from_elements(
+            voter_index,
+            &encrypted_vote,
+            &points,
+            scalars,
+        ))
+    }
 }
 
 /// Errors raised by VoteCollector
@@ -32,74 +280,107 @@ pub enum CollectorError {
     /// This error occurs when not all voters have submitted
     /// valid encrypted votes
     NotEnoughEncryptedVotes,
+    /// This error occurs when submitted encrypted vote is rejected
+    /// by the admission policy
+    Rejected,
+    /// This error occurs when a commitment is submitted while the
+    /// commit phase is disabled, or when a voter commits twice
+    InvalidCommitment,
+    /// This error occurs when a revealed encrypted vote does not
+    /// match the commitment of the voter
+    InvalidReveal,
+    /// This error occurs when a shard of voters is empty, out of range
+    /// or does not contain a power of two voters
+    InvalidShard,
+    /// This error occurs when the number of registered voters
+    /// is not a power of two greater than 1
+    InvalidNumberOfVoters,
     /// Wrapper for errors raised by CDSProver
     Prover(ProverError),
+    /// Wrapper for errors raised when proving from a checkpoint
+    #[cfg(feature = "std")]
+    Checkpoint(CheckpointError),
+    /// This error occurs when the verification queue is full,
+    /// or when no verification pool is running
+    Busy,
 }
 
 /// Compact public inputs sent to on-chain verifier
-/// to minimize the cost of calldata
+/// to minimize the cost of calldata. Encrypted votes
+/// and CDS proof points are written in compressed form.
+/// The validity of ballots is written as a bitmap, followed by the
+/// CDS proof scalars of invalid ballots and the signatures of their
+/// voters, so that the verifier can check that they were rightfully
+/// excluded.
 #[derive(Debug)]
 pub struct CompactPublicInputs {
     /// encrypted votes
-    encrypted_votes: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+    pub(crate) encrypted_votes: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
     /// truncated CDS proofs
-    cds_proofs: Vec<[BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH]>,
-    /// output of CDS proof validation
-    outputs: Vec<[BaseElement; AFFINE_POINT_WIDTH * 5]>,
+    pub(crate) cds_proofs: Vec<[BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH]>,
+    /// validity flags of ballots
+    pub(crate) validity: Vec<bool>,
+    /// CDS proof scalars of invalid ballots
+    pub(crate) excluded_scalars: Vec<[Scalar; PROOF_NUM_SCALARS]>,
+    /// signatures of invalid ballots by their voters
+    pub(crate) excluded_signatures: Vec<BallotSignature>,
 }
 
 impl Serializable for CompactPublicInputs {
     fn write_into<W: winterfell::ByteWriter>(&self, target: &mut W) {
         target.write_u32(self.encrypted_votes.len() as u32);
         for encrypted_vote in self.encrypted_votes.iter() {
-            Serializable::write_batch_into(encrypted_vote, target);
+            ecc::write_compressed_points(encrypted_vote, target);
         }
         for cds_proof in self.cds_proofs.iter() {
-            Serializable::write_batch_into(cds_proof, target);
+            ecc::write_compressed_points(cds_proof, target);
         }
-        for output in self.outputs.iter() {
-            Serializable::write_batch_into(output, target);
+        write_validity_bitmap(&self.validity, target);
+        for (proof_scalars, signature) in self
+            .excluded_scalars
+            .iter()
+            .zip(self.excluded_signatures.iter())
+        {
+            Serializable::write_batch_into(proof_scalars, target);
+            write_ballot_signature(signature, target);
         }
     }
 }
 
 impl Deserializable for CompactPublicInputs {
     fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
-        let mut encrypted_vote = [BaseElement::ZERO; AFFINE_POINT_WIDTH];
         let mut cds_proof = [BaseElement::ZERO; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH];
-        let mut output = [BaseElement::ZERO; AFFINE_POINT_WIDTH * 5];
 
         let num_proofs = source.read_u32()? as usize;
         let mut encrypted_votes = Vec::with_capacity(num_proofs);
         let mut cds_proofs = Vec::with_capacity(num_proofs);
-        let mut outputs = Vec::with_capacity(num_proofs);
 
         for _ in 0..num_proofs {
-            encrypted_vote
-                .copy_from_slice(&BaseElement::read_batch_from(source, AFFINE_POINT_WIDTH)?);
-            encrypted_votes.push(encrypted_vote);
+            encrypted_votes.push(ecc::read_compressed_point(source)?);
         }
 
         for _ in 0..num_proofs {
-            cds_proof.copy_from_slice(&BaseElement::read_batch_from(
-                source,
-                PROOF_NUM_POINTS * AFFINE_POINT_WIDTH,
-            )?);
+            cds_proof.copy_from_slice(&ecc::read_compressed_points(source, PROOF_NUM_POINTS)?);
             cds_proofs.push(cds_proof);
         }
 
-        for _ in 0..num_proofs {
-            output.copy_from_slice(&BaseElement::read_batch_from(
-                source,
-                AFFINE_POINT_WIDTH * 5,
-            )?);
-            outputs.push(output);
+        let validity = read_validity_bitmap(source, num_proofs)?;
+        let num_excluded = validity.iter().filter(|&&is_valid| !is_valid).count();
+        let mut excluded_scalars = Vec::with_capacity(num_excluded);
+        let mut excluded_signatures = Vec::with_capacity(num_excluded);
+        let mut scalars = [Scalar::zero(); PROOF_NUM_SCALARS];
+        for _ in 0..num_excluded {
+            scalars.copy_from_slice(&ecc::read_canonical_scalars(source, PROOF_NUM_SCALARS)?);
+            excluded_scalars.push(scalars);
+            excluded_signatures.push(read_ballot_signature(source)?);
         }
 
         Ok(Self {
             encrypted_votes,
             cds_proofs,
-            outputs,
+            validity,
+            excluded_scalars,
+            excluded_signatures,
         })
     }
 }
@@ -121,15 +402,57 @@ pub struct VoteCollector {
     /// Scalars in CDS proof
     pub proof_scalars: Vec<Option<[Scalar; PROOF_NUM_SCALARS]>>,
 
+    /// Set to true if voters must commit to their encrypted votes
+    /// before revealing them
+    pub commit_phase: bool,
+    /// Commitments to encrypted votes submitted during commit phase
+    pub commitments: Vec<Option<[u8; BYTES_PER_COMMITMENT]>>,
+
+    /// Set to true if invalid encrypted votes are reported in the cast proof
+    /// instead of being discarded. It is not serialized.
+    pub report_invalid_ballots: bool,
+    /// First invalid encrypted vote of each voter who has not submitted
+    /// a valid one, recorded if report_invalid_ballots is set
+    pub excluded_ballots: Vec<Option<EncryptedVote>>,
+
     /// Number of valid encrypted votes received
     pub num_valid_votes: usize,
+    /// Digest of the election manifest bound into the proofs, if any.
+    /// It is not serialized and must be bound again after restoring the collector.
+    pub manifest_digest: Option<[BaseElement; DIGEST_SIZE]>,
+    /// Digest of the manifest voters cast their ballots against, if the manifest was amended
+    /// since, see amend_manifest. It is not serialized and must be bound again after
+    /// restoring the collector.
+    pub ballot_context: Option<[BaseElement; DIGEST_SIZE]>,
+    /// Commitment to the registration phase the cast proof is chained to, if any. Proofs
+    /// of shards are not chained. It is not serialized and must be bound again after
+    /// restoring the collector.
+    pub register_commitment: Option<PhaseCommitment>,
+    /// Order of the voting keys, see try_new_with_order. Proofs of shards are not bound
+    /// to it. It is not serialized and must be set again after restoring the collector.
+    pub key_order: KeyOrder,
+    /// Field extension of the STARK proofs, see build_options. It is not serialized.
+    pub extension: u8,
+    /// FRI parameters of the STARK proofs, see build_options_with_fri. It is not serialized.
+    pub fri: FriConfig,
+    /// Log of rejected encrypted votes and commitments. It is not serialized.
+    pub rejections: RejectionLog,
     /// Cached proof
     pub serialized_proof: Vec<u8>,
+    /// Record of the last cast proof generated. It is not serialized.
+    pub last_proof: Option<ProofRecord>,
+    /// Pool verifying encrypted votes off the intake loop, if started
+    #[cfg(feature = "std")]
+    pub(crate) verification_pool: Option<VerificationPool>,
 }
 
 impl VoteCollector {
     /// Create an object of type VoteCollector given a list of voting keys
     /// Number of voting_keys must be a power of two.
+    ///
+    /// # Panics
+    /// Panics if the number of voting keys is not a power of two greater than 1,
+    /// see try_new
     pub fn new(voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>) -> Self {
         // compute blinding keys
         let blinding_keys = Self::compute_blinding_keys(&voting_keys);
@@ -141,15 +464,197 @@ impl VoteCollector {
             encrypted_votes: vec![None; num_voters],
             proof_points: vec![None; num_voters],
             proof_scalars: vec![None; num_voters],
+            commit_phase: false,
+            commitments: vec![None; num_voters],
+            report_invalid_ballots: false,
+            excluded_ballots: vec![None; num_voters],
             num_valid_votes: 0,
+            manifest_digest: None,
+            ballot_context: None,
+            register_commitment: None,
+            key_order: KeyOrder::Submission,
+            extension: 1,
+            fri: FriConfig::DEFAULT,
+            rejections: RejectionLog::new(),
             serialized_proof: vec![],
+            last_proof: None,
+            #[cfg(feature = "std")]
+            verification_pool: None,
+        }
+    }
+
+    /// Create an object of type VoteCollector given a list of voting keys, or return
+    /// InvalidNumberOfVoters if their number is not a power of two greater than 1.
+    /// Elections with a single voter cannot be cast, as the vote of the voter
+    /// would not be blinded by any other voting key.
+    pub fn try_new(
+        voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+    ) -> Result<Self, CollectorError> {
+        if voting_keys.len() < 2 || !voting_keys.len().is_power_of_two() {
+            return Err(CollectorError::InvalidNumberOfVoters);
+        }
+        Ok(Self::new(voting_keys))
+    }
+
+    /// Create an object of type VoteCollector as try_new does, listing voting keys in
+    /// the given order. Voting keys are sorted if the order is canonical, which is a
+    /// no-op for the voting keys of a registar using the same order.
+    pub fn try_new_with_order(
+        voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+        key_order: KeyOrder,
+    ) -> Result<Self, CollectorError> {
+        let voting_keys = key_order
+            .permutation(&voting_keys)
+            .iter()
+            .map(|&i| voting_keys[i])
+            .collect();
+        let mut collector = Self::try_new(voting_keys)?;
+        collector.key_order = key_order;
+        Ok(collector)
+    }
+
+    /// Create an object of type VoteCollector given a list of voting keys,
+    /// where voters must commit to their encrypted votes before revealing them.
+    /// Number of voting_keys must be a power of two.
+    pub fn with_commit_phase(voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>) -> Self {
+        let mut collector = Self::new(voting_keys);
+        collector.commit_phase = true;
+        collector
+    }
+
+    /// Tracker of the encrypted vote of a voter, if the voter has submitted a
+    /// valid encrypted vote. Trackers are listed in the order of voters by
+    /// the public inputs of the cast proof, see CastReport::trackers.
+    pub fn tracker_for(&self, voter_index: usize) -> Option<[u8; BYTES_PER_TRACKER]> {
+        let encrypted_vote = self.encrypted_votes.get(voter_index)?.as_ref()?;
+        let proof_points = self.proof_points[voter_index].as_ref()?;
+        Some(compute_tracker(voter_index, encrypted_vote, proof_points))
+    }
+
+    /// Number of voters whose ballot is reported as invalid in the cast proof
+    pub fn num_excluded_ballots(&self) -> usize {
+        self.excluded_ballots
+            .iter()
+            .filter(|ballot| ballot.is_some())
+            .count()
+    }
+
+    /// Which of the CDS verification equations does not hold for the
+    /// excluded ballot of a voter, if the ballot of the voter is excluded
+    pub fn exclusion_reason(&self, voter_index: usize) -> Option<CdsVerificationError> {
+        let excluded_ballot = self.excluded_ballots.get(voter_index)?.as_ref()?;
+        excluded_ballot
+            .verify(&self.voting_keys, &self.cds_context())
+            .err()
+    }
+
+    /// Record an invalid encrypted vote to be reported in the cast proof, if
+    /// invalid ballots are reported, the voter has submitted no ballot yet and
+    /// the encrypted vote matches their commitment during the commit phase
+    pub(crate) fn record_invalid_ballot(&mut self, encrypted_vote: EncryptedVote) {
+        let voter_index = encrypted_vote.voter_index;
+        if self.report_invalid_ballots
+            && voter_index < self.voting_keys.len()
+            && self.encrypted_votes[voter_index].is_none()
+            && self.excluded_ballots[voter_index].is_none()
+            && self.check_reveal(&encrypted_vote).is_ok()
+            && encrypted_vote.is_signed_by(self.voting_keys[voter_index], &self.cds_context())
+        {
+            self.excluded_ballots[voter_index] = Some(encrypted_vote);
+            self.serialized_proof.clear();
+        }
+    }
+
+    /// Return true if every voter has submitted either a valid ballot
+    /// or an invalid one reported in the cast proof
+    fn all_ballots_submitted(&self) -> bool {
+        self.num_valid_votes + self.num_excluded_ballots() == self.voting_keys.len()
+    }
+
+    /// Process new commitment to an encrypted vote submitted by voter.
+    /// Commitments cannot be replaced once submitted.
+    pub fn add_commitment(
+        &mut self,
+        voter_index: usize,
+        commitment: [u8; BYTES_PER_COMMITMENT],
+    ) -> Result<(), CollectorError> {
+        if !self.commit_phase
+            || voter_index >= self.voting_keys.len()
+            || self.commitments[voter_index].is_some()
+        {
+            self.rejections.record(
+                Submitter::Voter(voter_index),
+                RejectionReason::InvalidCommitment,
+                &commitment,
+            );
+            return Err(CollectorError::InvalidCommitment);
+        }
+        self.commitments[voter_index] = Some(commitment);
+        Ok(())
+    }
+
+    /// Bind subsequent cast proofs to the given election manifest. CDS proofs of encrypted
+    /// votes are bound to it as well, so this must be called before voters cast their votes.
+    pub fn bind_manifest(&mut self, manifest: &ElectionManifest) {
+        self.manifest_digest = Some(manifest.digest().to_elements());
+        self.serialized_proof.clear();
+    }
+
+    /// Bind subsequent cast proofs to the given amendment of the election manifest, see
+    /// ElectionManifest::amend. Voters keep casting against the digest of the manifest in
+    /// effect when casting started, so that ballots cast before the amendment stay valid.
+    pub fn amend_manifest(&mut self, manifest: &ElectionManifest) {
+        self.ballot_context = Some(self.cds_context());
+        self.bind_manifest(manifest);
+    }
+
+    /// Chain subsequent cast proofs to the commitment to the registration
+    /// phase, see VoterRegistar::phase_commitment
+    pub fn bind_register_commitment(&mut self, register_commitment: PhaseCommitment) {
+        self.register_commitment = Some(register_commitment);
+        self.serialized_proof.clear();
+    }
+
+    /// Commitment to the vote casting phase, to which the tally proof is chained with
+    /// VoteTallier::bind_ballot_commitment. Return None if the cast proof is not chained
+    /// or if some voters have not submitted a ballot yet.
+    pub fn phase_commitment(&self) -> Option<PhaseCommitment> {
+        let register_commitment = self.register_commitment?;
+        if !self.all_ballots_submitted() {
+            return None;
         }
+        let encrypted_votes = self
+            .encrypted_votes
+            .iter()
+            .flatten()
+            .copied()
+            .collect::<Vec<[BaseElement; AFFINE_POINT_WIDTH]>>();
+        Some(ballot_commitment(&register_commitment, &encrypted_votes))
+    }
+
+    /// Election context bound into the challenges of CDS proofs, i.e. the digest of the
+    /// manifest voters cast against, or the unbound context if no manifest is bound
+    pub fn cds_context(&self) -> [BaseElement; DIGEST_SIZE] {
+        election_context(self.ballot_context.or(self.manifest_digest))
+    }
+
+    /// Generate subsequent cast proofs with the given field extension, see build_options
+    pub fn set_extension(&mut self, extension: u8) {
+        self.extension = extension;
+        self.serialized_proof.clear();
+    }
+
+    /// Generate subsequent cast proofs with the given FRI parameters,
+    /// see build_options_with_fri
+    pub fn set_fri(&mut self, fri: FriConfig) {
+        self.fri = fri;
+        self.serialized_proof.clear();
     }
 
     /// Compute list of blinding keys given list of voting keys
     #[inline]
     pub fn compute_blinding_keys(
-        voting_keys: &Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+        voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
     ) -> Vec<ProjectivePoint> {
         let num_voters = voting_keys.len();
         assert!(num_voters > 1, "Number of voters must be greater than 1.");
@@ -186,7 +691,10 @@ impl VoteCollector {
         Self::read_from(&mut source)
     }
 
-    /// Dump self to an output stream
+    /// Dump self to an output stream, serialized with write_into. The flags and lengths
+    /// of ballot slots reveal which voters have submitted a ballot, so a collector
+    /// stored or sent outside the aggregator while voting is open must be sealed
+    /// instead, see seal, which is the only encoding that hides them.
     pub fn dump_to<W: std::io::Write>(&self, target: &mut W) -> Result<usize, std::io::Error> {
         target.write(&self.to_bytes())
     }
@@ -197,22 +705,209 @@ impl VoteCollector {
         &mut self,
         encrypted_vote: EncryptedVote,
     ) -> Result<(), CollectorError> {
-        // Check CDS proof validation result
+        self.add_encrypted_vote_with_policy(encrypted_vote, &mut AllowAll)
+    }
+
+    /// Process new encrypted vote submitted by voter after consulting
+    /// the admission policy. Rejected encrypted votes are not verified.
+    /// Return Ok if encrypted vote is processed successfully, and
+    /// record the rejection in the rejection log otherwise.
+    pub fn add_encrypted_vote_with_policy<P: AdmissionPolicy>(
+        &mut self,
+        encrypted_vote: EncryptedVote,
+        policy: &mut P,
+    ) -> Result<(), CollectorError> {
+        let result = self.process_encrypted_vote(encrypted_vote, policy);
+        self.log_rejection(&encrypted_vote, result)
+    }
+
+    fn process_encrypted_vote<P: AdmissionPolicy>(
+        &mut self,
+        encrypted_vote: EncryptedVote,
+        policy: &mut P,
+    ) -> Result<(), CollectorError> {
+        if !policy.admit(Submission::EncryptedVote(&encrypted_vote)) {
+            return Err(CollectorError::Rejected);
+        }
+
         let voter_index = encrypted_vote.voter_index;
+        if voter_index >= self.voting_keys.len() {
+            return Err(CollectorError::InvalidEncryptedVote);
+        }
+
+        // Check that the revealed encrypted vote matches the commitment
+        self.check_reveal(&encrypted_vote)?;
+
+        // Check CDS proof validation result
         let voting_key = ProjectivePoint::from(AffinePoint::from_raw_coordinates(
             self.voting_keys[voter_index],
         ));
 
-        if verify_cds_proof(
+        if verify_cds_proof_projective(
             voter_index,
             voting_key,
             self.blinding_keys[voter_index],
             encrypted_vote.encrypted_vote,
             &encrypted_vote.proof_points,
             &encrypted_vote.proof_scalars,
-        ) {
+            &self.cds_context(),
+        )
+        .is_err()
+        {
+            self.record_invalid_ballot(encrypted_vote);
             return Err(CollectorError::InvalidEncryptedVote);
         }
+        policy.record(Submission::EncryptedVote(&encrypted_vote));
+
+        self.add_verified_encrypted_vote(encrypted_vote)
+    }
+
+    /// Start a pool of num_workers threads verifying encrypted votes submitted with
+    /// submit_encrypted_vote, with at most capacity encrypted votes waiting for
+    /// verification. A running pool is replaced once its jobs have completed.
+    #[cfg(feature = "std")]
+    pub fn start_verification_pool(&mut self, num_workers: usize, capacity: usize) {
+        if self.verification_pool.is_some() {
+            self.wait_verified_votes();
+        }
+        self.verification_pool = Some(VerificationPool::new(num_workers, capacity));
+    }
+
+    /// Submit an encrypted vote for verification by the pool without waiting for
+    /// its result, which is returned by poll_verified_votes once available.
+    /// Return Busy if the queue of the pool is full, in which case the encrypted
+    /// vote should be submitted again later.
+    #[cfg(feature = "std")]
+    pub fn submit_encrypted_vote(
+        &mut self,
+        encrypted_vote: EncryptedVote,
+    ) -> Result<(), CollectorError> {
+        let pool = self
+            .verification_pool
+            .as_ref()
+            .ok_or(CollectorError::Busy)?;
+
+        let voter_index = encrypted_vote.voter_index;
+        if voter_index >= self.voting_keys.len() {
+            return self.log_rejection(&encrypted_vote, Err(CollectorError::InvalidEncryptedVote));
+        }
+
+        // Check that the revealed encrypted vote matches the commitment
+        if let Err(error) = self.check_reveal(&encrypted_vote) {
+            return self.log_rejection(&encrypted_vote, Err(error));
+        }
+
+        let job = Job {
+            encrypted_vote,
+            voting_key: ProjectivePoint::from(AffinePoint::from_raw_coordinates(
+                self.voting_keys[voter_index],
+            )),
+            blinding_key: self.blinding_keys[voter_index],
+            context: self.cds_context(),
+        };
+        pool.try_submit(job).map_err(|_| CollectorError::Busy)
+    }
+
+    /// Add encrypted votes verified by the pool since the last call, without blocking.
+    /// Return the voter index and the result of processing each encrypted vote.
+    #[cfg(feature = "std")]
+    pub fn poll_verified_votes(&mut self) -> Vec<(usize, Result<(), CollectorError>)> {
+        self.collect_verified_votes(false)
+    }
+
+    /// Add encrypted votes verified by the pool, blocking until all submitted
+    /// encrypted votes have been verified.
+    /// Return the voter index and the result of processing each encrypted vote.
+    #[cfg(feature = "std")]
+    pub fn wait_verified_votes(&mut self) -> Vec<(usize, Result<(), CollectorError>)> {
+        self.collect_verified_votes(true)
+    }
+
+    /// Number of submitted encrypted votes whose result has not been collected yet
+    #[cfg(feature = "std")]
+    pub fn pending_verifications(&self) -> usize {
+        self.verification_pool
+            .as_ref()
+            .map_or(0, |pool| pool.in_flight())
+    }
+
+    #[cfg(feature = "std")]
+    fn collect_verified_votes(&mut self, wait: bool) -> Vec<(usize, Result<(), CollectorError>)> {
+        let completed = match self.verification_pool.as_ref() {
+            Some(pool) => pool.collect(wait),
+            None => return vec![],
+        };
+        completed
+            .into_iter()
+            .map(|(encrypted_vote, is_valid)| {
+                let voter_index = encrypted_vote.voter_index;
+                let result = if is_valid {
+                    self.add_verified_encrypted_vote(encrypted_vote)
+                } else {
+                    self.record_invalid_ballot(encrypted_vote);
+                    Err(CollectorError::InvalidEncryptedVote)
+                };
+                (voter_index, self.log_rejection(&encrypted_vote, result))
+            })
+            .collect()
+    }
+
+    /// Record the encrypted vote in the rejection log if it was rejected
+    pub(crate) fn log_rejection(
+        &mut self,
+        encrypted_vote: &EncryptedVote,
+        result: Result<(), CollectorError>,
+    ) -> Result<(), CollectorError> {
+        if let Some(reason) = result
+            .as_ref()
+            .err()
+            .and_then(RejectionReason::from_collector_error)
+        {
+            self.rejections.record(
+                Submitter::Voter(encrypted_vote.voter_index),
+                reason,
+                &encrypted_vote.to_bytes(),
+            );
+        }
+        result
+    }
+
+    /// Return true if the encrypted vote matches the commitment of the voter
+    pub(crate) fn matches_commitment(&self, encrypted_vote: &EncryptedVote) -> bool {
+        let voter_index = encrypted_vote.voter_index;
+        match self.commitments.get(voter_index) {
+            Some(Some(commitment)) => {
+                let revealed = compute_vote_commitment(
+                    voter_index,
+                    &projective_to_elements(encrypted_vote.encrypted_vote),
+                    &concat_proof_points(&encrypted_vote.proof_points),
+                );
+                bool::from(ct_eq_bytes(commitment, &revealed))
+            }
+            _ => false,
+        }
+    }
+
+    /// Check that an encrypted vote revealed during the commit phase matches
+    /// the commitment of its voter
+    pub(crate) fn check_reveal(
+        &self,
+        encrypted_vote: &EncryptedVote,
+    ) -> Result<(), CollectorError> {
+        if self.commit_phase && !self.matches_commitment(encrypted_vote) {
+            return Err(CollectorError::InvalidReveal);
+        }
+        Ok(())
+    }
+
+    /// Add encrypted vote whose CDS proof has already been verified, if it
+    /// matches the commitment of its voter during the commit phase
+    pub(crate) fn add_verified_encrypted_vote(
+        &mut self,
+        encrypted_vote: EncryptedVote,
+    ) -> Result<(), CollectorError> {
+        let voter_index = encrypted_vote.voter_index;
+        self.check_reveal(&encrypted_vote)?;
 
         // Return InvalidEncryptedVote if a voter submits twice to
         // avoid information leak
@@ -227,9 +922,10 @@ impl VoteCollector {
 
     /// Generate STARK proofs for verification of encrypted votes
     /// Public inputs and proofs are serialized and returned as
-    /// a single sequenece of bytes
+    /// a single sequenece of bytes. Excluded ballots are flagged
+    /// as invalid in the public inputs.
     pub fn get_cast_proof(&mut self) -> Result<Vec<u8>, CollectorError> {
-        if self.num_valid_votes != self.voting_keys.len() {
+        if !self.all_ballots_submitted() {
             return Err(CollectorError::NotEnoughEncryptedVotes);
         }
 
@@ -237,44 +933,205 @@ impl VoteCollector {
             return Ok(self.serialized_proof.clone());
         }
 
-        let encrypted_votes = self
-            .encrypted_votes
-            .iter()
-            .map(|&x| x.unwrap())
-            .collect::<Vec<[BaseElement; AFFINE_POINT_WIDTH]>>();
-        let proof_points = self
-            .proof_points
-            .iter()
-            .map(|&x| x.unwrap())
-            .collect::<Vec<[BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH]>>();
-        let proof_scalars = self
-            .proof_scalars
-            .iter()
-            .map(|&x| x.unwrap())
-            .collect::<Vec<[Scalar; PROOF_NUM_SCALARS]>>();
+        let timer = ProofTimer::start();
+        let serialized_proof = self.prove_voters(0..self.voting_keys.len(), None)?;
+        self.last_proof = timer.finish(self.voting_keys.len());
+        Ok(serialized_proof)
+    }
 
-        let cds_prover = CDSProver::new(
-            build_options(1),
-            self.voting_keys.clone(),
-            encrypted_votes,
-            proof_points,
-            proof_scalars,
-        );
-        let cds_trace = cds_prover.build_trace();
-        let cds_pub_inputs = cds_prover.get_pub_inputs(&cds_trace);
-        let cds_proof = cds_prover.prove(cds_trace);
-        if cds_proof.is_err() {
-            return Err(CollectorError::Prover(cds_proof.unwrap_err()));
+    /// Generate the cast proof as get_cast_proof does, checkpointing the execution
+    /// trace and the STARK proof in the directory at path. If proving was interrupted,
+    /// calling this method again with the same path resumes from the last checkpoint.
+    #[cfg(feature = "std")]
+    pub fn get_cast_proof_resumable(&mut self, path: &Path) -> Result<Vec<u8>, CollectorError> {
+        if !self.all_ballots_submitted() {
+            return Err(CollectorError::NotEnoughEncryptedVotes);
+        }
+
+        if self.serialized_proof.len() > 0 {
+            return Ok(self.serialized_proof.clone());
+        }
+
+        let timer = ProofTimer::start();
+        let serialized_proof =
+            self.prove_voters_with(0..self.voting_keys.len(), None, |cds_prover| {
+                cds_prover
+                    .prove_resumable(path)
+                    .map_err(CollectorError::Checkpoint)
+            })?;
+        self.last_proof = timer.finish(self.voting_keys.len());
+        Ok(serialized_proof)
+    }
+
+    /// Digest of the inputs of the cast proof, under which the proof is
+    /// recorded in a ProofStore
+    #[cfg(feature = "std")]
+    pub fn proof_key(&self) -> StoreDigest {
+        let mut inputs = self.to_bytes();
+        match self.manifest_digest {
+            Some(manifest_digest) => {
+                inputs.write_u8(1);
+                Serializable::write_batch_into(&manifest_digest, &mut inputs);
+            }
+            None => inputs.write_u8(0),
+        }
+        if let Some(ballot_context) = self.ballot_context {
+            Serializable::write_batch_into(&ballot_context, &mut inputs);
+        }
+        self.key_order.write_into(&mut inputs);
+        match self.register_commitment {
+            Some(register_commitment) => {
+                inputs.write_u8(1);
+                Serializable::write_batch_into(&register_commitment, &mut inputs);
+            }
+            None => inputs.write_u8(0),
+        }
+        inputs.write_u8(self.report_invalid_ballots as u8);
+        inputs.write_u8(self.extension);
+        inputs.write_u32(self.fri.folding_factor() as u32);
+        inputs.write_u32(self.fri.max_remainder_size() as u32);
+        inputs_key(b"cast", &inputs)
+    }
+
+    /// Same as get_cast_proof, reusing the proof found in the store for the
+    /// same ballots, e.g. one generated before the process was restarted.
+    /// New proofs are recorded in the store. Failing to read or write the
+    /// store is not an error, the proof is then generated again.
+    #[cfg(feature = "std")]
+    pub fn get_cast_proof_cached(&mut self, store: &ProofStore) -> Result<Vec<u8>, CollectorError> {
+        if !self.all_ballots_submitted() {
+            return Err(CollectorError::NotEnoughEncryptedVotes);
+        }
+
+        if self.serialized_proof.len() > 0 {
+            return Ok(self.serialized_proof.clone());
         }
-        let cds_proof = cds_proof.unwrap();
+
+        let key = self.proof_key();
+        match store.lookup(&key) {
+            Ok(Some(serialized_proof)) => {
+                self.serialized_proof = serialized_proof.clone();
+                return Ok(serialized_proof);
+            }
+            Ok(None) => {}
+            Err(error) => debug!("Could not read the proof store: {}", error),
+        }
+
+        let serialized_proof = self.get_cast_proof()?;
+        if let Err(error) = store.insert(&key, &serialized_proof) {
+            debug!("Could not write the proof store: {}", error);
+        }
+        self.serialized_proof = serialized_proof.clone();
+        Ok(serialized_proof)
+    }
+
+    /// Prove the CDS relation for the voters in the given range and serialize
+    /// the proof as | compact pub inputs | commitments | STARK proof |
+    pub(crate) fn prove_voters(
+        &self,
+        voters: Range<usize>,
+        shard: Option<ShardInfo>,
+    ) -> Result<Vec<u8>, CollectorError> {
+        self.prove_voters_with(voters, shard, |cds_prover| {
+            cds_prover.prove_phase(()).map_err(CollectorError::Prover)
+        })
+    }
+
+    /// Prove the CDS relation for the voters in the given range with the given
+    /// proving strategy, and serialize the proof as prove_voters does
+    fn prove_voters_with(
+        &self,
+        voters: Range<usize>,
+        shard: Option<ShardInfo>,
+        prove: impl FnOnce(&CastBackend) -> Result<(CdsPublicInputs, StarkProof), CollectorError>,
+    ) -> Result<Vec<u8>, CollectorError> {
+        let mut encrypted_votes = Vec::with_capacity(voters.len());
+        let mut proof_points = Vec::with_capacity(voters.len());
+        let mut proof_scalars = Vec::with_capacity(voters.len());
+        let mut validity = Vec::with_capacity(voters.len());
+        let mut excluded_scalars = Vec::new();
+        let mut excluded_signatures = Vec::new();
+        for voter_index in voters.clone() {
+            match (
+                self.encrypted_votes[voter_index],
+                self.excluded_ballots[voter_index],
+            ) {
+                (Some(encrypted_vote), _) => {
+                    encrypted_votes.push(encrypted_vote);
+                    proof_points.push(self.proof_points[voter_index].unwrap());
+                    proof_scalars.push(self.proof_scalars[voter_index].unwrap());
+                    validity.push(true);
+                }
+                (None, Some(excluded_ballot)) => {
+                    // ballots excluded without the signature of their voter cannot be
+                    // told apart from scalars chosen by the aggregator
+                    let signature = excluded_ballot
+                        .signature
+                        .ok_or(CollectorError::NotEnoughEncryptedVotes)?;
+                    encrypted_votes.push(projective_to_elements(excluded_ballot.encrypted_vote));
+                    proof_points.push(concat_proof_points(&excluded_ballot.proof_points));
+                    proof_scalars.push(excluded_ballot.proof_scalars);
+                    validity.push(false);
+                    excluded_scalars.push(excluded_ballot.proof_scalars);
+                    excluded_signatures.push(signature);
+                }
+                (None, None) => return Err(CollectorError::NotEnoughEncryptedVotes),
+            }
+        }
+
+        let cds_prover = match shard {
+            Some(shard) => CastBackend::new_shard(
+                build_options_with_fri(self.extension, self.fri),
+                shard,
+                &self.voting_keys[voters.clone()],
+                &encrypted_votes,
+                &proof_points,
+                &proof_scalars,
+            ),
+            None => CastBackend::new(
+                build_options_with_fri(self.extension, self.fri),
+                &self.voting_keys[voters.clone()],
+                &encrypted_votes,
+                &proof_points,
+                &proof_scalars,
+            ),
+        };
+        let cds_prover = match self.manifest_digest {
+            Some(manifest_digest) => cds_prover.with_manifest(manifest_digest),
+            None => cds_prover,
+        };
+        let cds_prover = match self.ballot_context {
+            Some(ballot_context) => cds_prover.with_ballot_context(ballot_context),
+            None => cds_prover,
+        };
+        let cds_prover = match (self.register_commitment, shard) {
+            (Some(register_commitment), None) => cds_prover
+                .with_register_commitment(register_commitment)
+                .with_key_order(self.key_order),
+            (None, None) => cds_prover.with_key_order(self.key_order),
+            _ => cds_prover,
+        }
+        .with_validity(validity);
+        let (cds_pub_inputs, cds_proof) = prove(&cds_prover)?;
 
         let compact_pub_inputs = CompactPublicInputs {
             encrypted_votes: cds_pub_inputs.encrypted_votes,
             cds_proofs: cds_pub_inputs.cds_proofs,
-            outputs: cds_pub_inputs.outputs,
+            validity: cds_pub_inputs.validity,
+            excluded_scalars,
+            excluded_signatures,
         };
         let mut serialized_proof = vec![];
         CompactPublicInputs::write_into(&compact_pub_inputs, &mut serialized_proof);
+        // Serialize commitments (if any) between public inputs and STARK proof
+        if self.commit_phase {
+            serialized_proof.write_u32(voters.len() as u32);
+            for commitment in self.commitments[voters].iter() {
+                serialized_proof.write_u8_slice(&commitment.unwrap_or([0u8; BYTES_PER_COMMITMENT]));
+            }
+        } else {
+            serialized_proof.write_u32(0);
+        }
         serialized_proof.write_u8_slice(&cds_proof.to_bytes());
 
         Ok(serialized_proof)
@@ -284,6 +1141,7 @@ impl VoteCollector {
         let voter_index = encrypted_vote.voter_index;
         self.encrypted_votes[voter_index] =
             Some(projective_to_elements(encrypted_vote.encrypted_vote));
+        self.excluded_ballots[voter_index] = None;
 
         let mut points = [BaseElement::ZERO; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH];
         points[..AFFINE_POINT_WIDTH]
@@ -302,6 +1160,7 @@ impl VoteCollector {
 
     #[cfg(test)]
     pub fn get_example(num_proofs: usize) -> Self {
+        use super::build_options;
         use crate::cds::CDSExample;
 
         let (example, _) = CDSExample::new(build_options(1), num_proofs);
@@ -328,18 +1187,29 @@ impl VoteCollector {
             encrypted_votes,
             proof_points,
             proof_scalars,
+            commit_phase: false,
+            commitments: vec![None; num_proofs],
+            report_invalid_ballots: false,
+            excluded_ballots: vec![None; num_proofs],
             num_valid_votes: num_proofs,
+            manifest_digest: None,
+            ballot_context: None,
+            register_commitment: None,
+            key_order: KeyOrder::Submission,
+            extension: 1,
+            fri: FriConfig::DEFAULT,
+            rejections: RejectionLog::new(),
             serialized_proof: vec![],
+            last_proof: None,
+            #[cfg(feature = "std")]
+            verification_pool: None,
         }
     }
 
     #[cfg(test)]
     pub fn get_cast_proof_wrong_pub_inputs(&mut self) -> Result<Vec<u8>, CollectorError> {
-        use rand_core::{OsRng, RngCore};
-
         let mut serialized_proof = self.get_cast_proof()?;
-        let pub_inputs_nbytes =
-            self.num_valid_votes * (2 * 5 * AFFINE_POINT_WIDTH * BYTES_PER_ELEMENT);
+        let pub_inputs_nbytes = self.num_valid_votes * BYTES_PER_CAST_PUB_INPUTS;
         let fault_position = 4 + ((OsRng.next_u32() as usize) % pub_inputs_nbytes);
         serialized_proof[fault_position] ^= 1;
 
@@ -348,70 +1218,185 @@ impl VoteCollector {
 
     #[cfg(test)]
     pub fn get_cast_proof_wrong_stark_proof(&mut self) -> Result<Vec<u8>, CollectorError> {
-        use rand_core::{OsRng, RngCore};
-
         let mut serialized_proof = self.get_cast_proof()?;
-        let pub_inputs_nbytes =
-            self.num_valid_votes * (2 * 5 * AFFINE_POINT_WIDTH * BYTES_PER_ELEMENT);
-        let proof_nbytes = serialized_proof.len() - 4 - pub_inputs_nbytes;
-        let fault_position = 4 + pub_inputs_nbytes + ((OsRng.next_u32() as usize) % proof_nbytes);
+        let pub_inputs_nbytes = self.num_valid_votes * BYTES_PER_CAST_PUB_INPUTS;
+        let commitments_nbytes = if self.commit_phase {
+            4 + self.commitments.len() * BYTES_PER_COMMITMENT
+        } else {
+            4
+        };
+        let excluded_nbytes = validity_bitmap_size(self.voting_keys.len())
+            + self.num_excluded_ballots()
+                * (PROOF_NUM_SCALARS * BYTES_PER_SCALAR + BYTES_PER_SIGNATURE);
+        let proof_offset = 4 + pub_inputs_nbytes + excluded_nbytes + commitments_nbytes;
+        let proof_nbytes = serialized_proof.len() - proof_offset;
+        let fault_position = proof_offset + ((OsRng.next_u32() as usize) % proof_nbytes);
         serialized_proof[fault_position] ^= 1;
 
         Ok(serialized_proof)
     }
 }
 
-impl Serializable for VoteCollector {
-    fn write_into<W: winterfell::ByteWriter>(&self, target: &mut W) {
-        target.write_u32(self.voting_keys.len() as u32);
+impl VoteCollector {
+    /// Serialize the collector with the ballot slot of each voter sealed with
+    /// ChaCha20-Poly1305 under the given key, so that the encoding does not reveal which
+    /// voters have submitted a ballot. Unlike the slots written by write_into, sealed
+    /// slots have the same length and look random whether they hold a valid ballot,
+    /// an excluded one or none.
+    pub fn seal(&self, key: &[u8; SEALING_KEY_SIZE]) -> Vec<u8> {
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+        let mut target = Vec::new();
+        target.write_u8_slice(&SEALED_COLLECTOR_MAGIC);
+        self.write_slots(&mut target, |collector, voter_index, target| {
+            let mut slot = Vec::with_capacity(BYTES_PER_SLOT_CONTENT);
+            collector.write_slot(voter_index, &mut slot);
+            slot.resize(BYTES_PER_SLOT_CONTENT, 0);
+
+            let mut nonce = [0u8; SLOT_NONCE_SIZE];
+            OsRng.fill_bytes(&mut nonce);
+            let payload = Payload {
+                msg: &slot,
+                aad: &slot_associated_data(voter_index),
+            };
+            let sealed = cipher
+                .encrypt(Nonce::from_slice(&nonce), payload)
+                .expect("sealing a ballot slot cannot fail");
+            target.write_u8_slice(&nonce);
+            target.write_u8_slice(&sealed);
+        });
+        target
+    }
+
+    /// Deserialize a collector sealed with the given key, see seal
+    pub fn open_sealed(
+        sealed: &[u8],
+        key: &[u8; SEALING_KEY_SIZE],
+    ) -> Result<Self, DeserializationError> {
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+        let mut source = SliceReader::new(sealed);
+        if source.read_u8_vec(SEALED_COLLECTOR_MAGIC.len())? != SEALED_COLLECTOR_MAGIC {
+            return Err(DeserializationError::InvalidValue(String::from(
+                "Not a sealed vote collector.",
+            )));
+        }
+        let num_voters = wire::read_varint_usize(&mut source)?;
+        let collector = Self::read_slots(&mut source, num_voters, |source, voter_index| {
+            let nonce = source.read_u8_vec(SLOT_NONCE_SIZE)?;
+            let sealed = source.read_u8_vec(BYTES_PER_SEALED_SLOT - SLOT_NONCE_SIZE)?;
+            let payload = Payload {
+                msg: &sealed,
+                aad: &slot_associated_data(voter_index),
+            };
+            let slot = cipher
+                .decrypt(Nonce::from_slice(&nonce), payload)
+                .map_err(|_| {
+                    DeserializationError::InvalidValue(String::from(
+                        "Ballot slot cannot be opened with the given key.",
+                    ))
+                })?;
+            read_slot(&mut SliceReader::new(&slot), voter_index)
+        })?;
+        if source.has_more_bytes() {
+            return Err(DeserializationError::UnconsumedBytes);
+        }
+        Ok(collector)
+    }
+
+    /// Write the ballot slot of a voter: a flag telling whether the voter has submitted
+    /// a valid encrypted vote (1), an excluded invalid one (2), or none (0), followed by
+    /// the ballot, if any, and the signature of the voter for excluded ballots
+    fn write_slot<W: ByteWriter>(&self, voter_index: usize, target: &mut W) {
+        if self.encrypted_votes[voter_index].is_some() {
+            target.write_u8(1);
+            ecc::write_compressed_points(&self.encrypted_votes[voter_index].unwrap(), target);
+            ecc::write_compressed_points(&self.proof_points[voter_index].unwrap(), target);
+            Serializable::write_batch_into(&self.proof_scalars[voter_index].unwrap(), target);
+        } else if let Some((excluded_ballot, signature)) = self.excluded_ballots[voter_index]
+            .and_then(|ballot| ballot.signature.map(|signature| (ballot, signature)))
+        {
+            target.write_u8(2);
+            ecc::write_compressed_points(
+                &projective_to_elements(excluded_ballot.encrypted_vote),
+                target,
+            );
+            ecc::write_compressed_points(
+                &concat_proof_points(&excluded_ballot.proof_points),
+                target,
+            );
+            Serializable::write_batch_into(&excluded_ballot.proof_scalars, target);
+            write_ballot_signature(&signature, target);
+        } else {
+            target.write_u8(0);
+        }
+    }
+
+    /// Write the voting keys and ballot slots of voters, followed by the commitments
+    fn write_slots<W: ByteWriter>(
+        &self,
+        target: &mut W,
+        mut write_slot: impl FnMut(&Self, usize, &mut W),
+    ) {
+        wire::write_varint(target, self.voting_keys.len() as u64);
         for i in 0..self.voting_keys.len() {
-            Serializable::write_batch_into(&self.voting_keys[i], target);
-            if self.encrypted_votes[i].is_some() {
-                Serializable::write_batch_into(&self.encrypted_votes[i].unwrap(), target);
-                Serializable::write_batch_into(&self.proof_points[i].unwrap(), target);
-                Serializable::write_batch_into(&self.proof_scalars[i].unwrap(), target);
-            } else {
-                target.write_u8_slice(&[0u8; BYTES_PER_CDS_PROOF]);
+            ecc::write_compressed_points(&self.voting_keys[i], target);
+            write_slot(self, i, target);
+        }
+        target.write_u8(self.commit_phase as u8);
+        if self.commit_phase {
+            for commitment in self.commitments.iter() {
+                target.write_u8_slice(&commitment.unwrap_or([0u8; BYTES_PER_COMMITMENT]));
             }
         }
     }
-}
 
-impl Deserializable for VoteCollector {
-    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
-        let mut voting_key = [BaseElement::ZERO; AFFINE_POINT_WIDTH];
-        let mut encrypted_vote = [BaseElement::ZERO; AFFINE_POINT_WIDTH];
-        let mut points = [BaseElement::ZERO; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH];
-        let mut scalars = [Scalar::zero(); PROOF_NUM_SCALARS];
+    /// Read the voting keys and ballot slots of voters, followed by the commitments
+    fn read_slots<R: ByteReader>(
+        source: &mut R,
+        num_voters: usize,
+        mut read_slot: impl FnMut(&mut R, usize) -> Result<BallotSlot, DeserializationError>,
+    ) -> Result<Self, DeserializationError> {
         let mut num_valid_votes: usize = 0;
-
-        let num_voters = source.read_u32()? as usize;
         let mut voting_keys = Vec::with_capacity(num_voters);
         let mut encrypted_votes = Vec::with_capacity(num_voters);
         let mut proof_points = Vec::with_capacity(num_voters);
         let mut proof_scalars = Vec::with_capacity(num_voters);
+        let mut excluded_ballots = vec![None; num_voters];
 
-        for _ in 0..num_voters {
-            voting_key.copy_from_slice(&BaseElement::read_batch_from(source, AFFINE_POINT_WIDTH)?);
-            voting_keys.push(voting_key);
-            encrypted_vote
-                .copy_from_slice(&BaseElement::read_batch_from(source, AFFINE_POINT_WIDTH)?);
-            if encrypted_vote.iter().all(|&x| x == BaseElement::ZERO) {
-                // encrypted vote is None
-                encrypted_votes.push(None);
-                proof_points.push(None);
-                proof_scalars.push(None);
-                continue;
+        for (voter_index, excluded_ballot) in excluded_ballots.iter_mut().enumerate() {
+            voting_keys.push(ecc::read_compressed_point(source)?);
+            match read_slot(source, voter_index)? {
+                BallotSlot::Empty => {
+                    encrypted_votes.push(None);
+                    proof_points.push(None);
+                    proof_scalars.push(None);
+                }
+                BallotSlot::Excluded(ballot) => {
+                    // invalid encrypted vote reported in the cast proof
+                    *excluded_ballot = Some(ballot);
+                    encrypted_votes.push(None);
+                    proof_points.push(None);
+                    proof_scalars.push(None);
+                }
+                BallotSlot::Valid(encrypted_vote, points, scalars) => {
+                    num_valid_votes += 1;
+                    encrypted_votes.push(Some(encrypted_vote));
+                    proof_points.push(Some(points));
+                    proof_scalars.push(Some(scalars));
+                }
+            }
+        }
+
+        let commit_phase = source.read_u8()? == 1;
+        let mut commitments = vec![None; num_voters];
+        if commit_phase {
+            for commitment in commitments.iter_mut() {
+                let bytes = source.read_u8_vec(BYTES_PER_COMMITMENT)?;
+                if bytes.iter().any(|&b| b != 0) {
+                    let mut tmp = [0u8; BYTES_PER_COMMITMENT];
+                    tmp.copy_from_slice(&bytes);
+                    *commitment = Some(tmp);
+                }
             }
-            num_valid_votes += 1;
-            points.copy_from_slice(&BaseElement::read_batch_from(
-                source,
-                PROOF_NUM_POINTS * AFFINE_POINT_WIDTH,
-            )?);
-            scalars.copy_from_slice(&Scalar::read_batch_from(source, PROOF_NUM_SCALARS)?);
-            encrypted_votes.push(Some(encrypted_vote));
-            proof_points.push(Some(points));
-            proof_scalars.push(Some(scalars));
         }
 
         let blinding_keys = Self::compute_blinding_keys(&voting_keys);
@@ -422,8 +1407,105 @@ impl Deserializable for VoteCollector {
             encrypted_votes,
             proof_points,
             proof_scalars,
+            commit_phase,
+            commitments,
+            report_invalid_ballots: false,
+            excluded_ballots,
             num_valid_votes,
+            manifest_digest: None,
+            ballot_context: None,
+            register_commitment: None,
+            key_order: KeyOrder::Submission,
+            extension: 1,
+            fri: FriConfig::DEFAULT,
+            rejections: RejectionLog::new(),
             serialized_proof: vec![],
+            last_proof: None,
+            #[cfg(feature = "std")]
+            verification_pool: None,
         })
     }
 }
+
+impl Serializable for VoteCollector {
+    fn write_into<W: winterfell::ByteWriter>(&self, target: &mut W) {
+        // the flags and lengths of ballot slots reveal which voters have submitted,
+        // so this encoding is only meant for trusted storage, see seal and dump_to
+        wire::write_header(target);
+        self.write_slots(target, |collector, voter_index, target| {
+            collector.write_slot(voter_index, target)
+        });
+    }
+}
+
+impl Deserializable for VoteCollector {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let header = wire::read_header(source)?;
+        let num_voters = header.format().read_first_count(header, source)?;
+        Self::read_slots(source, num_voters, read_slot)
+    }
+}
+
+/// Content of the ballot slot of a voter in a serialized VoteCollector
+enum BallotSlot {
+    Empty,
+    Valid(
+        [BaseElement; AFFINE_POINT_WIDTH],
+        [BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH],
+        [Scalar; PROOF_NUM_SCALARS],
+    ),
+    Excluded(EncryptedVote),
+}
+
+/// Read the ballot slot of a voter, see VoteCollector::write_slot
+fn read_slot<R: ByteReader>(
+    source: &mut R,
+    voter_index: usize,
+) -> Result<BallotSlot, DeserializationError> {
+    let flag = source.read_u8()?;
+    if flag == 0 {
+        return Ok(BallotSlot::Empty);
+    }
+    let mut points = [BaseElement::ZERO; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH];
+    let mut scalars = [Scalar::zero(); PROOF_NUM_SCALARS];
+    let encrypted_vote = ecc::read_compressed_point(source)?;
+    points.copy_from_slice(&ecc::read_compressed_points(source, PROOF_NUM_POINTS)?);
+    scalars.copy_from_slice(&ecc::read_canonical_scalars(source, PROOF_NUM_SCALARS)?);
+    Ok(if flag == 2 {
+        BallotSlot::Excluded(
+            encrypted_vote_from_elements(voter_index, &encrypted_vote, &points, scalars)
+                .with_signature(read_ballot_signature(source)?),
+        )
+    } else {
+        BallotSlot::Valid(encrypted_vote, points, scalars)
+    })
+}
+
+/// Data authenticated along with the sealed ballot slot of a voter, which binds the
+/// slot to its position so that sealed slots cannot be swapped
+fn slot_associated_data(voter_index: usize) -> Vec<u8> {
+    let mut aad = SEALED_COLLECTOR_MAGIC.to_vec();
+    aad.write_u32(voter_index as u32);
+    aad
+}
+
+/// Rebuild an encrypted vote from its affine coordinates
+fn encrypted_vote_from_elements(
+    voter_index: usize,
+    encrypted_vote: &[BaseElement; AFFINE_POINT_WIDTH],
+    proof_points: &[BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH],
+    proof_scalars: [Scalar; PROOF_NUM_SCALARS],
+) -> EncryptedVote {
+    let mut points = [ProjectivePoint::identity(); PROOF_NUM_POINTS];
+    let mut point = [BaseElement::ZERO; AFFINE_POINT_WIDTH];
+    for (i, p) in points.iter_mut().enumerate() {
+        point.copy_from_slice(&proof_points[i * AFFINE_POINT_WIDTH..(i + 1) * AFFINE_POINT_WIDTH]);
+        *p = ProjectivePoint::from(AffinePoint::from_raw_coordinates(point));
+    }
+    EncryptedVote::new(
+        voter_index,
+        ProjectivePoint::from(AffinePoint::from_raw_coordinates(*encrypted_vote)),
+        points,
+        proof_scalars,
+    )
+}
@@ -0,0 +1,343 @@
+use crate::{
+    compat::curve::Scalar,
+    manifest::{ElectionManifest, PolicyOutcome, ResultPolicy},
+    schnorr::{sign_digest, verify_digest_signature},
+    utils::{
+        ecc::{
+            mul_generator_constant_time, projective_to_elements, read_canonical_scalar,
+            read_compressed_point, write_compressed_points,
+        },
+        rescue::{self, digest_bytes, Rescue63},
+        wire,
+    },
+    verifier::read_num_registrations,
+};
+use winterfell::{
+    math::{fields::f63::BaseElement, FieldElement},
+    ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable, SliceReader,
+};
+
+use super::{
+    constants::*,
+    tally::{TallierError, VoteTallier},
+};
+
+/// Evaluation of the result policy of the manifest, as certified by the aggregator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PolicyEvaluation {
+    /// Result policy of the manifest
+    pub policy: ResultPolicy,
+    /// Number of voters listed by the register proof, to which the minimum turnout applies
+    pub num_elg_voters: u32,
+    /// Outcome of the election under the policy
+    pub outcome: PolicyOutcome,
+}
+
+/// Compact finalization artifact of an election, signed by the aggregator,
+/// which third parties can check without the full STARK proofs
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultCertificate {
+    /// Number of "yes" votes
+    pub yes_votes: u32,
+    /// Number of "no" votes
+    pub no_votes: u32,
+    /// Rescue digest of the tallied encrypted votes
+    pub votes_commitment: rescue::Hash,
+    /// Rescue digest of the manifest of the election
+    pub manifest_digest: rescue::Hash,
+    /// Rescue digests of the proofs generated during the election
+    pub proof_digests: Vec<rescue::Hash>,
+    /// Public key of the aggregator
+    pub aggregator_key: [BaseElement; AFFINE_POINT_WIDTH],
+    /// Schnorr signature of the certificate by the aggregator
+    pub signature: ([BaseElement; POINT_COORDINATE_WIDTH], Scalar),
+    /// Rescue digest of the content identifiers of the published artifacts, if any
+    pub publication_digest: Option<rescue::Hash>,
+    /// Evaluation of the result policy of the manifest, if any
+    pub policy_evaluation: Option<PolicyEvaluation>,
+}
+
+impl ResultCertificate {
+    /// Reconstruct an object of type Self from a sequence of bytes
+    pub fn from_bytes(source: &[u8]) -> Result<Self, DeserializationError> {
+        let mut source = SliceReader::new(source);
+        Self::read_from(&mut source)
+    }
+
+    /// Total number of tallied votes
+    pub fn num_votes(&self) -> u64 {
+        self.yes_votes as u64 + self.no_votes as u64
+    }
+
+    /// Digest of the certified data, which is what the aggregator signs
+    pub fn digest(&self) -> rescue::Hash {
+        let mut hasher = Rescue63::new();
+        hasher.update(&[
+            BaseElement::from(self.yes_votes),
+            BaseElement::from(self.no_votes),
+        ]);
+        hasher.update(&self.votes_commitment.to_elements());
+        hasher.update(&self.manifest_digest.to_elements());
+        hasher.update(&[BaseElement::from(self.proof_digests.len() as u64)]);
+        hasher.update(rescue::Hash::hashes_as_elements(&self.proof_digests));
+        hasher.update(&self.aggregator_key);
+        if let Some(publication_digest) = self.publication_digest {
+            hasher.update(&publication_digest.to_elements());
+        }
+        if let Some(evaluation) = self.policy_evaluation {
+            hasher.update(&[
+                BaseElement::from(evaluation.policy.min_turnout),
+                BaseElement::from(evaluation.policy.threshold),
+                BaseElement::from(evaluation.num_elg_voters),
+                BaseElement::from(evaluation.outcome.code()),
+            ]);
+        }
+        hasher.finalize()
+    }
+
+    /// Check that the certificate commits to the given encrypted votes
+    pub fn commits_to(&self, encrypted_votes: &[[BaseElement; AFFINE_POINT_WIDTH]]) -> bool {
+        encrypted_votes.len() as u64 == self.num_votes()
+            && commit_encrypted_votes(encrypted_votes) == self.votes_commitment
+    }
+
+    /// Check that the certificate contains the digest of the given proof
+    pub fn contains_proof(&self, proof: &[u8]) -> bool {
+        let proof_digest = digest_bytes(proof);
+        self.proof_digests.iter().any(|&d| d == proof_digest)
+    }
+
+    /// Check that the certificate evaluates the result policy of the given manifest for the
+    /// voters listed by the given register proof, which it certifies, and that its outcome
+    /// follows from the certified tally
+    pub fn follows_policy(&self, manifest: &ElectionManifest, register_proof: &[u8]) -> bool {
+        match (manifest.policy, self.policy_evaluation) {
+            (None, None) => true,
+            (Some(policy), Some(evaluation)) => {
+                evaluation.policy == policy
+                    && self.contains_proof(register_proof)
+                    && read_num_registrations(register_proof).map_or(false, |num_regs| {
+                        num_regs == evaluation.num_elg_voters as usize
+                    })
+                    && self.num_votes() <= evaluation.num_elg_voters as u64
+                    && evaluation.outcome
+                        == policy.evaluate(
+                            evaluation.num_elg_voters,
+                            self.yes_votes,
+                            self.num_votes() as u32,
+                        )
+            }
+            _ => false,
+        }
+    }
+}
+
+impl VoteTallier {
+    /// Tally votes and assemble a certificate of the result of the election of
+    /// the manifest, signed with the secret key of the aggregator and covering
+    /// the given proofs
+    pub fn certify(
+        &mut self,
+        proofs: &[&[u8]],
+        manifest: &ElectionManifest,
+        secret_key: Scalar,
+    ) -> Result<ResultCertificate, TallierError> {
+        self.certify_with_publication(proofs, manifest, None, secret_key)
+    }
+
+    /// Tally votes and assemble a certificate of the result which also evaluates the
+    /// result policy of the manifest, if any, for the voters listed by the register proof.
+    /// The register proof is certified along with the given proofs.
+    pub fn certify_with_policy(
+        &mut self,
+        proofs: &[&[u8]],
+        manifest: &ElectionManifest,
+        register_proof: &[u8],
+        secret_key: Scalar,
+    ) -> Result<ResultCertificate, TallierError> {
+        let num_regs = read_num_registrations(register_proof)
+            .map_err(|_| TallierError::InvalidRegisterProof)?;
+        let policy = manifest.policy.map(|policy| (policy, num_regs as u32));
+        let mut proofs = proofs.to_vec();
+        if !proofs.contains(&register_proof) {
+            proofs.insert(0, register_proof);
+        }
+        self.assemble_certificate(&proofs, manifest, None, policy, secret_key)
+    }
+
+    /// Tally votes and assemble a certificate of the result which also commits
+    /// to the digest of the content identifiers of the published artifacts
+    pub fn certify_with_publication(
+        &mut self,
+        proofs: &[&[u8]],
+        manifest: &ElectionManifest,
+        publication_digest: Option<rescue::Hash>,
+        secret_key: Scalar,
+    ) -> Result<ResultCertificate, TallierError> {
+        self.assemble_certificate(proofs, manifest, publication_digest, None, secret_key)
+    }
+
+    /// Tally votes and assemble a signed certificate, evaluating the given
+    /// result policy for the given number of voters
+    fn assemble_certificate(
+        &mut self,
+        proofs: &[&[u8]],
+        manifest: &ElectionManifest,
+        publication_digest: Option<rescue::Hash>,
+        policy: Option<(ResultPolicy, u32)>,
+        secret_key: Scalar,
+    ) -> Result<ResultCertificate, TallierError> {
+        let yes_votes = self.tally_votes()?;
+        let num_votes = self.encrypted_votes.len() as u32;
+        let mut certificate = ResultCertificate {
+            yes_votes,
+            no_votes: num_votes - yes_votes,
+            votes_commitment: commit_encrypted_votes(&self.encrypted_votes),
+            manifest_digest: manifest.digest(),
+            proof_digests: proofs.iter().map(|proof| digest_bytes(proof)).collect(),
+            aggregator_key: projective_to_elements(mul_generator_constant_time(secret_key)),
+            signature: ([BaseElement::ZERO; POINT_COORDINATE_WIDTH], Scalar::zero()),
+            publication_digest,
+            policy_evaluation: policy.map(|(policy, num_elg_voters)| PolicyEvaluation {
+                policy,
+                num_elg_voters,
+                outcome: policy.evaluate(num_elg_voters, yes_votes, num_votes),
+            }),
+        };
+        certificate.signature = sign_digest(secret_key, &certificate.digest());
+        Ok(certificate)
+    }
+}
+
+/// Verify that a certificate was issued for the election of the given manifest digest,
+/// and signed by the aggregator with the given public key
+pub fn verify_certificate(
+    certificate: &ResultCertificate,
+    manifest_digest: &rescue::Hash,
+    aggregator_key: &[BaseElement; AFFINE_POINT_WIDTH],
+) -> bool {
+    certificate.manifest_digest == *manifest_digest
+        && certificate.aggregator_key == *aggregator_key
+        && verify_digest_signature(
+            *aggregator_key,
+            &certificate.digest(),
+            certificate.signature,
+        )
+        .is_ok()
+}
+
+// SERIALIZATION
+// ================================================================================================
+
+impl Serializable for ResultCertificate {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        wire::write_header(target);
+        wire::write_varint(target, self.yes_votes as u64);
+        wire::write_varint(target, self.no_votes as u64);
+        self.votes_commitment.write_into(target);
+        self.manifest_digest.write_into(target);
+        wire::write_varint(target, self.proof_digests.len() as u64);
+        Serializable::write_batch_into(&self.proof_digests, target);
+        write_compressed_points(&self.aggregator_key, target);
+        Serializable::write_batch_into(&self.signature.0, target);
+        target.write(self.signature.1);
+        if let Some(publication_digest) = self.publication_digest {
+            publication_digest.write_into(target);
+        }
+        if let Some(evaluation) = self.policy_evaluation {
+            wire::write_section_marker(target);
+            evaluation.policy.write_into(target);
+            wire::write_varint(target, evaluation.num_elg_voters as u64);
+            target.write_u8(evaluation.outcome.code());
+        }
+    }
+}
+
+impl Deserializable for ResultCertificate {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        if let wire::WireHeader::Legacy(_) = wire::read_header(source)? {
+            return Err(DeserializationError::InvalidValue(String::from(
+                "Missing wire header.",
+            )));
+        }
+        let yes_votes = read_vote_count(source)?;
+        let no_votes = read_vote_count(source)?;
+        let votes_commitment = rescue::Hash::read_from(source)?;
+        let manifest_digest = rescue::Hash::read_from(source)?;
+        let num_proofs = wire::read_varint_usize(source)?;
+        let proof_digests = rescue::Hash::read_batch_from(source, num_proofs)?;
+        let aggregator_key = read_compressed_point(source)?;
+        let mut signature_r = [BaseElement::ZERO; POINT_COORDINATE_WIDTH];
+        signature_r.copy_from_slice(&BaseElement::read_batch_from(
+            source,
+            POINT_COORDINATE_WIDTH,
+        )?);
+        let signature_s = read_canonical_scalar(source)?;
+        let mut publication_digest = None;
+        let mut policy_evaluation = None;
+        while source.has_more_bytes() {
+            match wire::read_digest_or_marker(source)? {
+                Some(digest) if publication_digest.is_none() && policy_evaluation.is_none() => {
+                    publication_digest = Some(digest)
+                }
+                None if policy_evaluation.is_none() => {
+                    policy_evaluation = Some(read_policy_evaluation(source)?)
+                }
+                _ => {
+                    return Err(DeserializationError::InvalidValue(String::from(
+                        "Unexpected trailing section.",
+                    )))
+                }
+            }
+        }
+
+        Ok(Self {
+            yes_votes,
+            no_votes,
+            votes_commitment,
+            manifest_digest,
+            proof_digests,
+            aggregator_key,
+            signature: (signature_r, signature_s),
+            publication_digest,
+            policy_evaluation,
+        })
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Rescue digest of encrypted votes in affine coordinates
+fn commit_encrypted_votes(encrypted_votes: &[[BaseElement; AFFINE_POINT_WIDTH]]) -> rescue::Hash {
+    let mut hasher = Rescue63::new();
+    for encrypted_vote in encrypted_votes.iter() {
+        hasher.update(encrypted_vote);
+    }
+    hasher.finalize()
+}
+
+fn read_policy_evaluation<R: ByteReader>(
+    source: &mut R,
+) -> Result<PolicyEvaluation, DeserializationError> {
+    let policy = ResultPolicy::read_from(source)?;
+    let num_elg_voters = read_vote_count(source)?;
+    let outcome = PolicyOutcome::from_code(source.read_u8()?).ok_or_else(|| {
+        DeserializationError::InvalidValue(String::from("Invalid policy outcome."))
+    })?;
+    Ok(PolicyEvaluation {
+        policy,
+        num_elg_voters,
+        outcome,
+    })
+}
+
+fn read_vote_count<R: ByteReader>(source: &mut R) -> Result<u32, DeserializationError> {
+    let count = wire::read_varint(source)?;
+    if count > u32::MAX as u64 {
+        return Err(DeserializationError::InvalidValue(String::from(
+            "Vote count does not fit in u32.",
+        )));
+    }
+    Ok(count as u32)
+}
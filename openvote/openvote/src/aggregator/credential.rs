@@ -0,0 +1,168 @@
+use crate::{
+    aggregator::register::{Registration, VoterRegistar},
+    compat::curve::Scalar,
+    merkle::{build_merkle_tree_with_keys, MerkleTree},
+    schnorr::{random_key_pairs, sign_messages},
+    utils::{
+        address::Address,
+        ecc::{mul_generator_constant_time, projective_to_elements, read_canonical_scalar},
+    },
+};
+use sha3::{Digest, Sha3_256};
+use winterfell::{
+    math::fields::f63::BaseElement, ByteReader, ByteWriter, Deserializable, DeserializationError,
+    Serializable, SliceReader,
+};
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use super::constants::*;
+
+/// Prefix of printable credentials, which also versions their encoding
+pub const PRINTABLE_PREFIX: &str = "ovc1:";
+
+/// Number of checksum bytes appended to printable credentials
+pub const CHECKSUM_SIZE: usize = 4;
+
+/// Number of hex characters per group of a printable credential
+const PRINTABLE_GROUP_SIZE: usize = 8;
+
+/// One-time voting credential generated by the election authority for a voter
+/// without a wallet. It holds the secret key of the voter and a registration
+/// bundle signed with it, and is distributed offline, e.g. printed on paper.
+/// As non-crypto voters have no Ethereum address, a random address is assigned.
+#[derive(Debug, Clone)]
+pub struct Credential {
+    /// Secret key of the voter, used to cast the vote
+    pub secret_key: Scalar,
+    /// Registration bundle to submit to the registar
+    pub registration: Registration,
+}
+
+impl Credential {
+    /// Voting key of the credential
+    pub fn voting_key(&self) -> [BaseElement; AFFINE_POINT_WIDTH] {
+        self.registration.voting_key
+    }
+
+    /// Reconstruct an object of type Self from a sequence of bytes
+    pub fn from_bytes(source: &[u8]) -> Result<Self, DeserializationError> {
+        let mut source = SliceReader::new(source);
+        Self::read_from(&mut source)
+    }
+
+    /// Encode the credential as printable text: a versioned prefix followed
+    /// by the hex encoding of the credential and a checksum, in dash-separated groups
+    pub fn to_printable(&self) -> String {
+        let mut bytes = self.to_bytes();
+        let checksum = Sha3_256::digest(&bytes);
+        bytes.extend_from_slice(&checksum[..CHECKSUM_SIZE]);
+        let encoded = hex::encode(bytes);
+        let groups = encoded
+            .as_bytes()
+            .chunks(PRINTABLE_GROUP_SIZE)
+            .map(|group| core::str::from_utf8(group).expect("hex is ASCII"))
+            .collect::<Vec<_>>();
+        format!("{}{}", PRINTABLE_PREFIX, groups.join("-"))
+    }
+
+    /// Decode a credential from its printable text. Whitespace and dashes
+    /// are ignored, so that credentials can be typed in from paper.
+    pub fn from_printable(text: &str) -> Result<Self, DeserializationError> {
+        let text = text.trim();
+        if !text.starts_with(PRINTABLE_PREFIX) {
+            return Err(DeserializationError::InvalidValue(String::from(
+                "Missing credential prefix.",
+            )));
+        }
+        let encoded = text[PRINTABLE_PREFIX.len()..]
+            .chars()
+            .filter(|c| *c != '-' && !c.is_whitespace())
+            .collect::<String>();
+        let bytes = hex::decode(encoded).map_err(|_| {
+            DeserializationError::InvalidValue(String::from("Invalid credential encoding."))
+        })?;
+        if bytes.len() < CHECKSUM_SIZE {
+            return Err(DeserializationError::UnexpectedEOF);
+        }
+        let (bytes, checksum) = bytes.split_at(bytes.len() - CHECKSUM_SIZE);
+        if Sha3_256::digest(bytes)[..CHECKSUM_SIZE] != *checksum {
+            return Err(DeserializationError::InvalidValue(String::from(
+                "Invalid credential checksum.",
+            )));
+        }
+        Self::from_bytes(bytes)
+    }
+}
+
+impl Serializable for Credential {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write(self.secret_key);
+        self.registration.write_into(target);
+    }
+}
+
+impl Deserializable for Credential {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let secret_key = read_canonical_scalar(source)?;
+        let registration = Registration::read_from(source)?;
+        if projective_to_elements(mul_generator_constant_time(secret_key))
+            != registration.voting_key
+        {
+            return Err(DeserializationError::InvalidValue(String::from(
+                "Secret key does not match the voting key of the registration.",
+            )));
+        }
+        Ok(Self {
+            secret_key,
+            registration,
+        })
+    }
+}
+
+/// Batch of one-time credentials, together with the Merkle tree of
+/// eligible voters built from their voting keys
+#[derive(Debug, Clone)]
+pub struct CredentialBatch {
+    /// Merkle tree of eligible voters
+    pub tree: MerkleTree,
+    /// Credentials to distribute, one per voter
+    pub credentials: Vec<Credential>,
+}
+
+impl CredentialBatch {
+    /// Generate num_credentials credentials with fresh key pairs
+    ///
+    /// # Panics
+    /// Panics if num_credentials exceeds the number of leaves of the tree
+    pub fn generate(num_credentials: usize) -> Self {
+        let (secret_keys, voting_keys) = random_key_pairs(num_credentials);
+        let addresses = (0..num_credentials)
+            .map(|_| Address::random())
+            .collect::<Vec<Address>>();
+        let signatures = sign_messages(&voting_keys, &addresses, &secret_keys);
+        let (tree, hash_indices) = build_merkle_tree_with_keys(&voting_keys);
+
+        let credentials = (0..num_credentials)
+            .map(|i| Credential {
+                secret_key: secret_keys[i],
+                registration: Registration {
+                    voting_key: voting_keys[i],
+                    merkle_branch: tree.branch(hash_indices[i]),
+                    hash_index: hash_indices[i],
+                    signature: signatures[i],
+                    address: addresses[i],
+                    ownership_signature: None,
+                },
+            })
+            .collect();
+
+        Self { tree, credentials }
+    }
+
+    /// Create the registar of the election the credentials were generated for
+    pub fn registar(&self) -> VoterRegistar {
+        VoterRegistar::new(self.tree.clone(), self.credentials.len())
+    }
+}
@@ -0,0 +1,422 @@
+use crate::{
+    aggregator::{
+        cast::{CollectorError, VoteCollector},
+        constants::AFFINE_POINT_WIDTH,
+        register::{RegistarError, Registration, VoterRegistar},
+        status::{CollectorStatus, RegistarStatus},
+        tally::VoteTallier,
+    },
+    manifest::{ElectionManifest, ManifestError, ManifestUpdate},
+    merkle::MerkleTree,
+    schnorr::SigningDomain,
+    utils::{ordering::KeyOrder, proof_store::ProofStore},
+};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+use winterfell::{
+    crypto::{hashers::Blake3_256, Digest, Hasher},
+    math::fields::f63::BaseElement,
+    Serializable,
+};
+
+/// Number of bytes of an election identifier
+pub const ELECTION_ID_SIZE: usize = 16;
+
+/// Serialized state of the voter registar, in the namespace of an election
+pub const REGISTRATIONS_FILE: &str = "registrations.dat";
+/// Serialized state of the vote collector, in the namespace of an election
+pub const BALLOTS_FILE: &str = "ballots.dat";
+/// Cached register proof, in the namespace of an election
+pub const REGISTER_PROOF_FILE: &str = "truncated_register_proof.dat";
+/// Cached cast proof, in the namespace of an election
+pub const CAST_PROOF_FILE: &str = "truncated_cast_proof.dat";
+/// Checkpoint directory of the cast proof, in the namespace of an election
+pub const CAST_CHECKPOINT_DIR: &str = "cast_checkpoint";
+/// Content-addressed store of generated proofs, in the namespace of an election
+pub const PROOF_STORE_DIR: &str = "proofs";
+
+/// Identifier of an election, derived from its manifest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ElectionId(pub [u8; ELECTION_ID_SIZE]);
+
+impl ElectionId {
+    /// Identifier of the election configured by the given manifest,
+    /// i.e. the truncated Blake3 digest of the serialized manifest
+    pub fn from_manifest(manifest: &ElectionManifest) -> Self {
+        let digest = Blake3_256::<BaseElement>::hash(&manifest.to_bytes()).as_bytes();
+        let mut id = [0u8; ELECTION_ID_SIZE];
+        id.copy_from_slice(&digest[..ELECTION_ID_SIZE]);
+        Self(id)
+    }
+
+    /// Signing domain binding registrations to this election on the given chain
+    pub fn signing_domain(&self, chain_id: u64) -> SigningDomain {
+        SigningDomain::new(chain_id, self.0)
+    }
+}
+
+impl fmt::Display for ElectionId {
+    /// Hex encoding of the identifier, also used as storage namespace
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+/// Errors raised by Election and ElectionRegistry
+#[derive(Debug)]
+pub enum ElectionError {
+    /// This error occurs when the manifest of a new election or an amendment of it is invalid
+    InvalidManifest(ManifestError),
+    /// This error occurs when an election with the same identifier is already hosted
+    DuplicateElection(ElectionId),
+    /// This error occurs when no election is hosted under the given identifier
+    UnknownElection(ElectionId),
+    /// This error occurs when the registry rejects key reuse and the voting key
+    /// of a registration is already registered in the given election
+    KeyReused(ElectionId),
+    /// This error occurs when an operation is not allowed in the current phase
+    InvalidPhase,
+    /// This error occurs when the storage namespace cannot be read or written
+    Storage(std::io::Error),
+    /// Wrapper for errors raised by VoterRegistar
+    Registar(RegistarError),
+    /// Wrapper for errors raised by VoteCollector
+    Collector(CollectorError),
+}
+
+impl From<std::io::Error> for ElectionError {
+    fn from(error: std::io::Error) -> Self {
+        ElectionError::Storage(error)
+    }
+}
+
+// ELECTION
+// ================================================================================================
+
+/// Status of an election, e.g. for dashboards and readiness probes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ElectionStatus {
+    /// Identifier of the election
+    pub id: ElectionId,
+    /// Status of the registration phase
+    pub registar: RegistarStatus,
+    /// Status of the vote casting phase, once casting started
+    pub collector: Option<CollectorStatus>,
+    /// Number of "yes" votes, once votes are tallied
+    pub tally_result: Option<u32>,
+}
+
+/// Aggregator objects of a single election, together with its manifest and the
+/// directory in which its state and proofs are stored. Phases are entered in order:
+/// the collector is created when casting starts, and the tallier when tallying starts.
+#[derive(Debug)]
+pub struct Election {
+    id: ElectionId,
+    manifest: ElectionManifest,
+    storage: PathBuf,
+    /// Collect and process registrations of voters
+    pub registar: VoterRegistar,
+    /// Collect and process encrypted ballots, once casting started
+    pub collector: Option<VoteCollector>,
+    /// Tally encrypted votes, once tallying started
+    pub tallier: Option<VoteTallier>,
+}
+
+impl Election {
+    /// Identifier of the election
+    pub fn id(&self) -> ElectionId {
+        self.id
+    }
+
+    /// Manifest bound into the proofs of the election
+    pub fn manifest(&self) -> &ElectionManifest {
+        &self.manifest
+    }
+
+    /// Storage namespace of the election
+    pub fn storage(&self) -> &Path {
+        &self.storage
+    }
+
+    /// Status of the election and of the phases it entered
+    pub fn status(&self) -> ElectionStatus {
+        ElectionStatus {
+            id: self.id,
+            registar: self.registar.status(),
+            collector: self.collector.as_ref().map(VoteCollector::status),
+            tally_result: self
+                .tallier
+                .as_ref()
+                .and_then(|tallier| tallier.tally_result),
+        }
+    }
+
+    /// Amend the manifest of the election with an update signed by one of its trustees,
+    /// e.g. to extend casting. Subsequent proofs are bound to the digest of the amended
+    /// manifest, while the identifier of the election is left unchanged.
+    pub fn amend_manifest(&mut self, update: &ManifestUpdate) -> Result<(), ElectionError> {
+        if self.tallier.is_some() {
+            return Err(ElectionError::InvalidPhase);
+        }
+        let manifest = self
+            .manifest
+            .amend(update)
+            .map_err(ElectionError::InvalidManifest)?;
+        self.registar.bind_manifest(&manifest);
+        if let Some(collector) = self.collector.as_mut() {
+            collector.amend_manifest(&manifest);
+        }
+        self.manifest = manifest;
+        Ok(())
+    }
+
+    /// List voting keys in the given order in the proofs of the election. It must be
+    /// called before casting starts, as the indices of voters may change.
+    pub fn set_key_order(&mut self, key_order: KeyOrder) -> Result<(), ElectionError> {
+        if self.collector.is_some() {
+            return Err(ElectionError::InvalidPhase);
+        }
+        self.registar.set_key_order(key_order);
+        Ok(())
+    }
+
+    /// Close registration and create the vote collector
+    /// from the voting keys of registered voters
+    pub fn start_casting(&mut self) -> Result<&mut VoteCollector, ElectionError> {
+        if self.collector.is_some() {
+            return Err(ElectionError::InvalidPhase);
+        }
+        let mut collector = VoteCollector::try_new_with_order(
+            self.registar.voting_keys.clone(),
+            self.registar.key_order,
+        )
+        .map_err(ElectionError::Collector)?;
+        collector.bind_manifest(&self.manifest);
+        Ok(self.collector.insert(collector))
+    }
+
+    /// Close casting and create the vote tallier
+    /// from the encrypted votes of all voters
+    pub fn start_tallying(&mut self) -> Result<&mut VoteTallier, ElectionError> {
+        if self.tallier.is_some() {
+            return Err(ElectionError::InvalidPhase);
+        }
+        let collector = self.collector.as_ref().ok_or(ElectionError::InvalidPhase)?;
+        let encrypted_votes = collector
+            .encrypted_votes
+            .iter()
+            .map(|encrypted_vote| encrypted_vote.ok_or(CollectorError::NotEnoughEncryptedVotes))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(ElectionError::Collector)?;
+        Ok(self.tallier.insert(VoteTallier::new(encrypted_votes)))
+    }
+
+    /// Get the register proof of the election, and store it in its namespace.
+    /// The proof is cached by the registar until a new voter registers, and
+    /// reused from the proof store of the namespace after a restart.
+    pub fn register_proof(&mut self) -> Result<Vec<u8>, ElectionError> {
+        let store = ProofStore::open(&self.storage.join(PROOF_STORE_DIR))?;
+        let register_proof = self
+            .registar
+            .get_register_proof_cached(&store)
+            .map_err(ElectionError::Registar)?;
+        fs::write(self.storage.join(REGISTER_PROOF_FILE), &register_proof)?;
+        Ok(register_proof)
+    }
+
+    /// Get the cast proof of the election, and store it in its namespace.
+    /// Proving is checkpointed in the namespace, so that it resumes
+    /// after the aggregator process is restarted, and a proof already
+    /// recorded in the proof store of the namespace is reused.
+    pub fn cast_proof(&mut self) -> Result<Vec<u8>, ElectionError> {
+        let checkpoint = self.storage.join(CAST_CHECKPOINT_DIR);
+        let store = ProofStore::open(&self.storage.join(PROOF_STORE_DIR))?;
+        let collector = self.collector.as_mut().ok_or(ElectionError::InvalidPhase)?;
+        let key = collector.proof_key();
+        let cast_proof = match store.lookup(&key)? {
+            Some(cast_proof) => cast_proof,
+            None => {
+                let cast_proof = collector
+                    .get_cast_proof_resumable(&checkpoint)
+                    .map_err(ElectionError::Collector)?;
+                store.insert(&key, &cast_proof)?;
+                cast_proof
+            }
+        };
+        fs::write(self.storage.join(CAST_PROOF_FILE), &cast_proof)?;
+        Ok(cast_proof)
+    }
+
+    /// Dump the state of the registar and of the collector, if any, to the namespace
+    pub fn save(&self) -> Result<(), ElectionError> {
+        self.registar
+            .dump_to(&mut File::create(self.storage.join(REGISTRATIONS_FILE))?)?;
+        if let Some(collector) = self.collector.as_ref() {
+            collector.dump_to(&mut File::create(self.storage.join(BALLOTS_FILE))?)?;
+        }
+        Ok(())
+    }
+}
+
+// ELECTION REGISTRY
+// ================================================================================================
+
+/// Elections hosted by a single aggregator process, keyed by their identifier.
+/// Each election stores its state and proofs in its own namespace, i.e. in a
+/// subdirectory of the root named after the identifier of the election.
+#[derive(Debug)]
+pub struct ElectionRegistry {
+    root: PathBuf,
+    elections: BTreeMap<ElectionId, Election>,
+    /// Set to true if a voting key cannot be registered in two elections.
+    /// Blinding keys are derived from the voting keys of all voters, so
+    /// ballots cast with the same key in two elections can be linked.
+    pub reject_key_reuse: bool,
+}
+
+impl ElectionRegistry {
+    /// Create an empty registry storing elections under the directory at root
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            elections: BTreeMap::new(),
+            reject_key_reuse: false,
+        }
+    }
+
+    /// Create an empty registry which rejects registrations whose voting key
+    /// is already registered in another hosted election
+    pub fn without_key_reuse<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            reject_key_reuse: true,
+            ..Self::new(root)
+        }
+    }
+
+    /// Host a new election configured by the given manifest, whose eligible
+    /// voters are the leaves of tree. The manifest is bound into its proofs.
+    pub fn create(
+        &mut self,
+        manifest: ElectionManifest,
+        tree: MerkleTree,
+        num_elg_voters: usize,
+    ) -> Result<&mut Election, ElectionError> {
+        manifest
+            .validate()
+            .map_err(ElectionError::InvalidManifest)?;
+        let id = ElectionId::from_manifest(&manifest);
+        if self.elections.contains_key(&id) {
+            return Err(ElectionError::DuplicateElection(id));
+        }
+
+        let storage = self.root.join(id.to_string());
+        fs::create_dir_all(&storage)?;
+        let mut registar = VoterRegistar::new(tree, num_elg_voters);
+        registar.bind_manifest(&manifest);
+
+        Ok(self.elections.entry(id).or_insert(Election {
+            id,
+            manifest,
+            storage,
+            registar,
+            collector: None,
+            tallier: None,
+        }))
+    }
+
+    /// Process a registration submitted to the given election. If the registry
+    /// rejects key reuse, the voting key must not be registered in another election.
+    pub fn register(
+        &mut self,
+        id: &ElectionId,
+        registration: Registration,
+    ) -> Result<(), ElectionError> {
+        if self.reject_key_reuse {
+            if let Some(&other_id) = self.key_reused_in(id, &registration.voting_key).first() {
+                return Err(ElectionError::KeyReused(other_id));
+            }
+        }
+        self.get_mut(id)?
+            .registar
+            .add_registration(registration)
+            .map_err(ElectionError::Registar)
+    }
+
+    /// Identifiers of the hosted elections other than the given one
+    /// in which the voting key is registered
+    pub fn key_reused_in(
+        &self,
+        id: &ElectionId,
+        voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    ) -> Vec<ElectionId> {
+        self.elections
+            .iter()
+            .filter(|(other_id, election)| {
+                *other_id != id && election.registar.voting_keys.contains(voting_key)
+            })
+            .map(|(&other_id, _)| other_id)
+            .collect()
+    }
+
+    /// Voting keys registered in both elections
+    pub fn shared_voting_keys(
+        &self,
+        id: &ElectionId,
+        other_id: &ElectionId,
+    ) -> Result<Vec<[BaseElement; AFFINE_POINT_WIDTH]>, ElectionError> {
+        let other_keys = &self.get(other_id)?.registar.voting_keys;
+        Ok(self
+            .get(id)?
+            .registar
+            .voting_keys
+            .iter()
+            .filter(|voting_key| other_keys.contains(voting_key))
+            .copied()
+            .collect())
+    }
+
+    /// Election hosted under the given identifier
+    pub fn get(&self, id: &ElectionId) -> Result<&Election, ElectionError> {
+        self.elections
+            .get(id)
+            .ok_or(ElectionError::UnknownElection(*id))
+    }
+
+    /// Mutable election hosted under the given identifier
+    pub fn get_mut(&mut self, id: &ElectionId) -> Result<&mut Election, ElectionError> {
+        self.elections
+            .get_mut(id)
+            .ok_or(ElectionError::UnknownElection(*id))
+    }
+
+    /// Stop hosting the given election. Its namespace is left on disk.
+    pub fn remove(&mut self, id: &ElectionId) -> Result<Election, ElectionError> {
+        self.elections
+            .remove(id)
+            .ok_or(ElectionError::UnknownElection(*id))
+    }
+
+    /// Identifiers of the hosted elections, in increasing order
+    pub fn ids(&self) -> impl Iterator<Item = &ElectionId> {
+        self.elections.keys()
+    }
+
+    /// Number of hosted elections
+    pub fn len(&self) -> usize {
+        self.elections.len()
+    }
+
+    /// Check whether no election is hosted
+    pub fn is_empty(&self) -> bool {
+        self.elections.is_empty()
+    }
+
+    /// Directory under which the namespaces of elections are created
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
@@ -0,0 +1,281 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// REGISTRATION KITS
+// ================================================================================================
+// The registar hands each eligible voter a kit with everything needed to register: the index of
+// their leaf, their Merkle branch and the parameters of the election. Kits are sealed to the
+// voting key of the voter, so that only the holder of the secret key can open them:
+//
+//   R = r * G,  key = SHA3-256(domain, r * vk, R, vk)
+//   sealed kit = | KIT_MAGIC | R (compressed) | ChaCha20-Poly1305(key, kit) |
+//
+// where the magic and R are authenticated as associated data. Each key seals a single kit, so
+// that the nonce is fixed. The ephemeral key R is checked to be in the subgroup generated by G
+// before it is multiplied by the secret key of the voter.
+
+use super::register::VoterRegistar;
+use crate::{
+    compat::curve::{AffinePoint, ProjectivePoint, Scalar},
+    merkle::constants::*,
+    schnorr::SigningDomain,
+    utils::ecc::{
+        compress_point, decompress_point, mul_constant_time, mul_generator_constant_time,
+        projective_to_elements, validate_point, BYTES_PER_COMPRESSED_POINT,
+    },
+};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use rand_core::{OsRng, RngCore};
+use sha3::{Digest, Sha3_256};
+use winterfell::{
+    math::fields::f63::BaseElement, ByteReader, ByteWriter, Deserializable, DeserializationError,
+    Serializable, SliceReader,
+};
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+/// Prefix of sealed registration kits, which also versions their encoding
+pub const KIT_MAGIC: [u8; 4] = *b"ovk2";
+
+/// Number of bytes of the authentication tag of a sealed kit
+pub const KIT_TAG_SIZE: usize = 16;
+
+/// Extension of the files of sealed registration kits
+pub const KIT_FILE_EXTENSION: &str = "kit";
+
+// Domain separating the derivation of the key
+const KEY_DOMAIN: &[u8] = b"openvote-kit-key";
+
+// Nonce of the cipher, which is fixed as each key seals a single kit
+const KIT_NONCE: [u8; 12] = [0u8; 12];
+
+/// Everything an eligible voter needs to register, as distributed by the registar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegistrationKit {
+    /// Index of the leaf of the voting key of the voter
+    pub hash_index: usize,
+    /// Siblings on the path from the leaf to the root
+    pub merkle_branch: [BaseElement; TREE_DEPTH * DIGEST_SIZE],
+    /// Merkle root of tree built from list of eligible voters
+    pub elg_root: [BaseElement; DIGEST_SIZE],
+    /// Number of eligible voters
+    pub num_elg_voters: usize,
+    /// Set to true if voters must prove ownership of their Ethereum address
+    pub require_ownership: bool,
+    /// Chain and election to which registration signatures must be bound
+    pub domain: SigningDomain,
+    /// Digest of the election manifest, if the election is bound to one
+    pub manifest_digest: Option<[BaseElement; DIGEST_SIZE]>,
+}
+
+impl RegistrationKit {
+    /// Seal the kit to the voting key of its voter
+    pub fn seal(&self, voting_key: &[BaseElement; AFFINE_POINT_WIDTH]) -> Vec<u8> {
+        self.seal_with_rng(voting_key, &mut OsRng)
+    }
+
+    /// Seal the kit to the voting key of its voter, drawing the
+    /// ephemeral key from the given random number generator
+    pub fn seal_with_rng<R: RngCore>(
+        &self,
+        voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+        rng: &mut R,
+    ) -> Vec<u8> {
+        let ephemeral_secret = Scalar::random(rng);
+        let ephemeral_key = compress_point(&projective_to_elements(mul_generator_constant_time(
+            ephemeral_secret,
+        )));
+        let shared_point = mul_constant_time(
+            ProjectivePoint::from(AffinePoint::from_raw_coordinates(*voting_key)),
+            ephemeral_secret,
+        );
+        let key = derive_key(&shared_point, &ephemeral_key, voting_key);
+
+        let mut sealed = Vec::with_capacity(KIT_MAGIC.len() + BYTES_PER_COMPRESSED_POINT);
+        sealed.extend_from_slice(&KIT_MAGIC);
+        sealed.extend_from_slice(&ephemeral_key);
+        let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&key))
+            .encrypt(
+                Nonce::from_slice(&KIT_NONCE),
+                Payload {
+                    msg: &self.to_bytes(),
+                    aad: &sealed,
+                },
+            )
+            .expect("encrypting a registration kit cannot fail");
+        sealed.extend_from_slice(&ciphertext);
+        sealed
+    }
+
+    /// Open a sealed kit with the secret key of its voter
+    pub fn open(sealed: &[u8], secret_key: Scalar) -> Result<Self, DeserializationError> {
+        let header_size = KIT_MAGIC.len() + BYTES_PER_COMPRESSED_POINT;
+        if sealed.len() < header_size + KIT_TAG_SIZE {
+            return Err(DeserializationError::UnexpectedEOF);
+        }
+        if sealed[..KIT_MAGIC.len()] != KIT_MAGIC {
+            return Err(invalid("Unknown registration kit version."));
+        }
+        let mut ephemeral_key = [0u8; BYTES_PER_COMPRESSED_POINT];
+        ephemeral_key.copy_from_slice(&sealed[KIT_MAGIC.len()..header_size]);
+        // the ephemeral key is checked before it is multiplied by the secret key,
+        // so that it cannot be used to learn the secret key modulo small orders
+        let ephemeral_point = decompress_point(&ephemeral_key)
+            .and_then(|point| validate_point(&point).ok())
+            .map(ProjectivePoint::from)
+            .filter(|point| !point.is_identity())
+            .ok_or_else(|| invalid("Invalid ephemeral key of registration kit."))?;
+
+        let voting_key = projective_to_elements(mul_generator_constant_time(secret_key));
+        let shared_point = mul_constant_time(ephemeral_point, secret_key);
+        let key = derive_key(&shared_point, &ephemeral_key, &voting_key);
+
+        let plaintext = ChaCha20Poly1305::new(Key::from_slice(&key))
+            .decrypt(
+                Nonce::from_slice(&KIT_NONCE),
+                Payload {
+                    msg: &sealed[header_size..],
+                    aad: &sealed[..header_size],
+                },
+            )
+            .map_err(|_| invalid("Invalid tag of registration kit."))?;
+
+        let mut source = SliceReader::new(&plaintext);
+        let kit = Self::read_from(&mut source)?;
+        if source.has_more_bytes() {
+            return Err(DeserializationError::UnconsumedBytes);
+        }
+        Ok(kit)
+    }
+}
+
+impl Serializable for RegistrationKit {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u32(self.hash_index as u32);
+        Serializable::write_batch_into(&self.merkle_branch, target);
+        Serializable::write_batch_into(&self.elg_root, target);
+        target.write_u32(self.num_elg_voters as u32);
+        target.write_u8(self.require_ownership as u8);
+        self.domain.write_into(target);
+        match self.manifest_digest {
+            Some(manifest_digest) => {
+                target.write_u8(1);
+                Serializable::write_batch_into(&manifest_digest, target);
+            }
+            None => target.write_u8(0),
+        }
+    }
+}
+
+impl Deserializable for RegistrationKit {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let hash_index = source.read_u32()? as usize;
+        let mut merkle_branch = [BaseElement::default(); TREE_DEPTH * DIGEST_SIZE];
+        merkle_branch.copy_from_slice(&BaseElement::read_batch_from(
+            source,
+            TREE_DEPTH * DIGEST_SIZE,
+        )?);
+        let mut elg_root = [BaseElement::default(); DIGEST_SIZE];
+        elg_root.copy_from_slice(&BaseElement::read_batch_from(source, DIGEST_SIZE)?);
+        let num_elg_voters = source.read_u32()? as usize;
+        let require_ownership = match source.read_u8()? {
+            0 => false,
+            1 => true,
+            _ => return Err(invalid("Invalid ownership flag of registration kit.")),
+        };
+        let domain = SigningDomain::read_from(source)?;
+        let manifest_digest = match source.read_u8()? {
+            0 => None,
+            1 => {
+                let mut manifest_digest = [BaseElement::default(); DIGEST_SIZE];
+                manifest_digest
+                    .copy_from_slice(&BaseElement::read_batch_from(source, DIGEST_SIZE)?);
+                Some(manifest_digest)
+            }
+            _ => return Err(invalid("Invalid manifest flag of registration kit.")),
+        };
+        Ok(Self {
+            hash_index,
+            merkle_branch,
+            elg_root,
+            num_elg_voters,
+            require_ownership,
+            domain,
+            manifest_digest,
+        })
+    }
+}
+
+// KIT DISTRIBUTION
+// ================================================================================================
+
+impl VoterRegistar {
+    /// Registration kit of the eligible voter with the given voting key,
+    /// or None if the voting key is not in the tree of eligible voters
+    pub fn registration_kit(
+        &self,
+        voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    ) -> Option<RegistrationKit> {
+        self.tree
+            .find(voting_key)
+            .map(|hash_index| RegistrationKit {
+                hash_index,
+                merkle_branch: self.tree.branch(hash_index),
+                elg_root: self.elg_root,
+                num_elg_voters: self.num_elg_voters,
+                require_ownership: self.require_ownership,
+                domain: self.domain,
+                manifest_digest: self.manifest_digest,
+            })
+    }
+
+    /// Write the sealed registration kit of each eligible voter among the given voting
+    /// keys to `<leaf index>.kit` in the given directory, and return the number of
+    /// files written. Voting keys which are not in the tree are skipped.
+    #[cfg(feature = "std")]
+    pub fn export_registration_kits(
+        &self,
+        voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+        dir: &std::path::Path,
+    ) -> Result<usize, std::io::Error> {
+        std::fs::create_dir_all(dir)?;
+        let mut num_files = 0;
+        for voting_key in voting_keys.iter() {
+            if let Some(kit) = self.registration_kit(voting_key) {
+                let path = dir.join(format!("{}.{}", kit.hash_index, KIT_FILE_EXTENSION));
+                std::fs::write(path, kit.seal(voting_key))?;
+                num_files += 1;
+            }
+        }
+        Ok(num_files)
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn derive_key(
+    shared_point: &ProjectivePoint,
+    ephemeral_key: &[u8; BYTES_PER_COMPRESSED_POINT],
+    voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(KEY_DOMAIN);
+    hasher.update(compress_point(&projective_to_elements(*shared_point)));
+    hasher.update(ephemeral_key);
+    hasher.update(compress_point(voting_key));
+    hasher.finalize().into()
+}
+
+fn invalid(message: &str) -> DeserializationError {
+    DeserializationError::InvalidValue(String::from(message))
+}
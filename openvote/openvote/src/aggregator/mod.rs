@@ -1,11 +1,43 @@
 use self::{cast::VoteCollector, register::VoterRegistar, tally::VoteTallier};
+use crate::compat::proof_options;
+use crate::planner::FriConfig;
 use winterfell::{FieldExtension, HashFunction, ProofOptions};
 
+/// Module for admission policies of submissions
+pub mod admission;
+/// Module for batched verification of encrypted votes
+pub mod batch;
+/// Module for result breakdowns by group of voters
+pub mod breakdown;
 /// Module for vote casting phase
 pub mod cast;
+/// Module for signed certificates of election results
+pub mod certificate;
 pub(crate) mod constants;
+/// Module for one-time voting credentials of voters without a wallet
+pub mod credential;
+/// Module for hosting multiple elections in a single aggregator process
+#[cfg(feature = "std")]
+pub mod election;
+/// Module for sealed registration kits distributed to eligible voters
+pub mod kit;
+/// Module for proofs of ownership of Ethereum addresses
+pub mod ownership;
+/// Module for off-thread verification of encrypted votes
+#[cfg(feature = "std")]
+pub mod pool;
+/// Module for publication of the ballots proven by the cast proof
+pub mod publication;
 /// Module for voter registration phase
 pub mod register;
+/// Module for logs of rejected submissions
+pub mod rejection;
+/// Module for proving vote casting across multiple aggregators
+pub mod shard;
+/// Module for status introspection of aggregator objects
+pub mod status;
+/// Module for state synchronization between aggregator replicas
+pub mod sync;
 /// Module for vote tallying phase
 pub mod tally;
 
@@ -14,21 +46,31 @@ mod tests;
 
 /// Build options to generate all STARK proofs
 pub fn build_options(extension: u8) -> ProofOptions {
-    ProofOptions::new(
+    build_options_with_fri(extension, FriConfig::DEFAULT)
+}
+
+/// Same as build_options, with the given FRI parameters
+pub fn build_options_with_fri(extension: u8, fri: FriConfig) -> ProofOptions {
+    proof_options(
         42,
         8,
         0,
         HashFunction::Blake3_192,
-        match extension {
-            2 => FieldExtension::Quadratic,
-            3 => FieldExtension::Cubic,
-            _ => FieldExtension::None,
-        },
-        4,
-        256,
+        field_extension(extension),
+        fri.folding_factor(),
+        fri.max_remainder_size(),
     )
 }
 
+/// Field extension of the given degree, no extension for unsupported degrees
+pub(crate) fn field_extension(extension: u8) -> FieldExtension {
+    match extension {
+        2 => FieldExtension::Quadratic,
+        3 => FieldExtension::Cubic,
+        _ => FieldExtension::None,
+    }
+}
+
 /// Example for a complete set of aggrgator objects
 #[derive(Debug)]
 pub struct AggregatorExample {
@@ -43,21 +85,18 @@ pub struct AggregatorExample {
 impl AggregatorExample {
     /// Create an instance of type AggregatorExample with random data
     pub fn new(num_voters: usize) -> Self {
-        use self::constants::*;
+        use self::{constants::*, rejection::RejectionLog};
+        use crate::compat::curve::{AffinePoint, ProjectivePoint, Scalar};
+        use crate::utils::address::Address;
         use crate::{
             cds::{concat_proof_points, encrypt_votes_and_compute_proofs, naive_verify_cds_proofs},
-            merkle::build_merkle_tree_from,
-            schnorr::{
-                naive_verify_signatures, projective_to_elements, random_key_pairs, sign_messages,
-            },
+            merkle::build_merkle_tree_with_keys,
+            schnorr::{naive_verify_signatures, random_key_pairs, sign_messages, SigningDomain},
             tally::naive_verify_tally_result,
+            utils::{ecc::projective_to_elements, ordering::KeyOrder},
         };
         use rand_core::{OsRng, RngCore};
-        use web3::types::Address;
-        use winterfell::math::{
-            curves::curve_f63::{AffinePoint, ProjectivePoint, Scalar},
-            fields::f63::BaseElement,
-        };
+        use winterfell::math::fields::f63::BaseElement;
 
         assert!(num_voters > 1, "Number of voters must be greater than 1.");
         assert!(
@@ -77,7 +116,7 @@ impl AggregatorExample {
             &addresses,
             &signatures
         ));
-        let (elg_root, merkle_branches, hash_indices) = build_merkle_tree_from(&voting_keys);
+        let (tree, hash_indices) = build_merkle_tree_with_keys(&voting_keys);
 
         let projective_voting_keys = voting_keys
             .iter()
@@ -134,15 +173,26 @@ impl AggregatorExample {
         assert!(naive_verify_tally_result(&encrypted_votes, tally_result));
 
         let voter_registar = VoterRegistar {
-            elg_root,
+            elg_root: tree.root(),
             num_elg_voters: num_voters,
             voting_keys: voting_keys.clone(),
-            merkle_branches,
+            tree,
             hash_indices,
             signatures,
             addresses,
+            require_ownership: false,
+            ownership_signatures: vec![None; num_voters],
+            manifest_digest: None,
+            domain: SigningDomain::default(),
+            key_order: KeyOrder::Submission,
+            leaf_binding: false,
+            leaf_groups: Vec::new(),
+            extension: 1,
+            fri: FriConfig::DEFAULT,
+            rejections: RejectionLog::new(),
             dirty_flag: true,
             serialized_proof: vec![],
+            last_proof: None,
         };
 
         let wrapped_encrypted_votes = encrypted_votes
@@ -157,13 +207,29 @@ impl AggregatorExample {
             encrypted_votes: wrapped_encrypted_votes,
             proof_points,
             proof_scalars,
+            commit_phase: false,
+            commitments: vec![None; num_voters],
+            report_invalid_ballots: false,
+            excluded_ballots: vec![None; num_voters],
             num_valid_votes: num_voters,
+            manifest_digest: None,
+            ballot_context: None,
+            register_commitment: None,
+            key_order: KeyOrder::Submission,
+            extension: 1,
+            fri: FriConfig::DEFAULT,
+            rejections: RejectionLog::new(),
             serialized_proof: vec![],
+            last_proof: None,
+            #[cfg(feature = "std")]
+            verification_pool: None,
         };
 
         let vote_tallier = VoteTallier {
             tally_result: Some(tally_result),
             encrypted_votes,
+            ballot_commitment: None,
+            extension: 1,
         };
 
         AggregatorExample {
@@ -0,0 +1,89 @@
+use crate::utils::{address::Address, ecc::compress_point};
+use k256::ecdsa::{RecoveryId, Signature, SigningKey, VerifyingKey};
+use sha3::{Digest, Keccak256};
+use winterfell::{math::fields::f63::BaseElement, Serializable};
+
+use super::constants::*;
+
+/// Number of bytes of an Ethereum signature (r, s, v)
+pub const BYTES_PER_OWNERSHIP_SIGNATURE: usize = 65;
+
+/// Prefix of messages signed with the Ethereum personal_sign method, followed by
+/// the length of the compressed voting key and of the digest of the manifest
+const PERSONAL_SIGN_PREFIX: &[u8] = b"\x19Ethereum Signed Message:\n104";
+
+/// Message signed by the key of an Ethereum address to prove that the owner of the
+/// address registers the given voting key in the election of the given manifest digest.
+/// It is the personal_sign hash of the compressed voting key followed by the manifest
+/// digest, so that wallets can produce the signature and contracts can check it with
+/// ecrecover.
+pub fn ownership_message(
+    voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    manifest_digest: &[BaseElement; DIGEST_SIZE],
+) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(PERSONAL_SIGN_PREFIX);
+    hasher.update(compress_point(voting_key));
+    let mut digest_bytes = Vec::with_capacity(BYTES_PER_DIGEST);
+    Serializable::write_batch_into(manifest_digest, &mut digest_bytes);
+    hasher.update(digest_bytes);
+    hasher.finalize().into()
+}
+
+/// Sign the ownership message of a voting key with the secp256k1 key of an Ethereum address
+pub fn sign_ownership(
+    signing_key: &SigningKey,
+    voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    manifest_digest: &[BaseElement; DIGEST_SIZE],
+) -> [u8; BYTES_PER_OWNERSHIP_SIGNATURE] {
+    let (signature, recovery_id) = signing_key
+        .sign_prehash_recoverable(&ownership_message(voting_key, manifest_digest))
+        .expect("signing a 32-byte prehash cannot fail");
+    let mut result = [0u8; BYTES_PER_OWNERSHIP_SIGNATURE];
+    result[..64].copy_from_slice(&signature.to_bytes());
+    result[64] = 27 + recovery_id.to_byte();
+    result
+}
+
+/// Recover the Ethereum address that signed the ownership message of a voting key
+/// in the election of the given manifest digest
+pub fn recover_owner(
+    voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    manifest_digest: &[BaseElement; DIGEST_SIZE],
+    signature: &[u8; BYTES_PER_OWNERSHIP_SIGNATURE],
+) -> Option<Address> {
+    // accept both the raw and the Ethereum encodings of the recovery id
+    let v = match signature[64] {
+        v @ 0..=1 => v,
+        v @ 27..=28 => v - 27,
+        _ => return None,
+    };
+    let recovery_id = RecoveryId::from_byte(v)?;
+    let ecdsa_signature = Signature::from_slice(&signature[..64]).ok()?;
+    let verifying_key = VerifyingKey::recover_from_prehash(
+        &ownership_message(voting_key, manifest_digest),
+        &ecdsa_signature,
+        recovery_id,
+    )
+    .ok()?;
+    Some(address_of(&verifying_key))
+}
+
+/// Check that the ownership message of a voting key in the election of the given
+/// manifest digest was signed by the key of address
+pub fn verify_ownership(
+    voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    manifest_digest: &[BaseElement; DIGEST_SIZE],
+    address: Address,
+    signature: &[u8; BYTES_PER_OWNERSHIP_SIGNATURE],
+) -> bool {
+    recover_owner(voting_key, manifest_digest, signature) == Some(address)
+}
+
+/// Ethereum address of a secp256k1 public key
+pub fn address_of(verifying_key: &VerifyingKey) -> Address {
+    let public_key = verifying_key.to_encoded_point(false);
+    // skip the 0x04 tag of uncompressed points
+    let hash = Keccak256::digest(&public_key.as_bytes()[1..]);
+    Address::from_slice(&hash[12..])
+}
@@ -0,0 +1,161 @@
+use super::cast::EncryptedVote;
+use super::constants::DIGEST_SIZE;
+use crate::cds::verify_cds_proof_projective;
+use crate::compat::curve::ProjectivePoint;
+use core::fmt;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+use winterfell::math::fields::f63::BaseElement;
+
+/// Verification job of the CDS proof of an encrypted vote
+pub(crate) struct Job {
+    pub(crate) encrypted_vote: EncryptedVote,
+    pub(crate) voting_key: ProjectivePoint,
+    pub(crate) blinding_key: ProjectivePoint,
+    pub(crate) context: [BaseElement; DIGEST_SIZE],
+}
+
+/// Bounded pool of worker threads verifying CDS proofs of encrypted votes
+/// off the intake loop of the collector.
+///
+/// At most `capacity` jobs wait in the queue; further submissions are
+/// refused until workers catch up, which gives back-pressure to the intake
+/// loop. Results are collected by polling, in the order they complete.
+/// Clones share the same workers and queues.
+#[derive(Clone)]
+pub struct VerificationPool {
+    inner: Arc<PoolInner>,
+}
+
+struct PoolInner {
+    jobs: Mutex<Option<SyncSender<Job>>>,
+    results: Mutex<Receiver<(EncryptedVote, bool)>>,
+    workers: Vec<JoinHandle<()>>,
+    in_flight: AtomicUsize,
+}
+
+impl VerificationPool {
+    /// Start num_workers threads verifying jobs from a queue of the given capacity
+    pub fn new(num_workers: usize, capacity: usize) -> Self {
+        assert!(num_workers > 0, "Number of workers must be positive.");
+        let (job_sender, job_receiver) = mpsc::sync_channel::<Job>(capacity);
+        let (result_sender, result_receiver) = mpsc::channel();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+
+        let workers = (0..num_workers)
+            .map(|_| {
+                let job_receiver = Arc::clone(&job_receiver);
+                let result_sender = result_sender.clone();
+                thread::spawn(move || loop {
+                    // the lock is released before verifying the job
+                    let job = match job_receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => break,
+                    };
+                    let job = match job {
+                        Ok(job) => job,
+                        // the pool was dropped
+                        Err(_) => break,
+                    };
+                    let encrypted_vote = job.encrypted_vote;
+                    let is_valid = verify_cds_proof_projective(
+                        encrypted_vote.voter_index,
+                        job.voting_key,
+                        job.blinding_key,
+                        encrypted_vote.encrypted_vote,
+                        &encrypted_vote.proof_points,
+                        &encrypted_vote.proof_scalars,
+                        &job.context,
+                    )
+                    .is_ok();
+                    if result_sender.send((encrypted_vote, is_valid)).is_err() {
+                        break;
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            inner: Arc::new(PoolInner {
+                jobs: Mutex::new(Some(job_sender)),
+                results: Mutex::new(result_receiver),
+                workers,
+                in_flight: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Number of worker threads
+    pub fn num_workers(&self) -> usize {
+        self.inner.workers.len()
+    }
+
+    /// Number of jobs submitted whose result has not been collected yet
+    pub fn in_flight(&self) -> usize {
+        self.inner.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Queue a job, returning it back if the queue is full
+    pub(crate) fn try_submit(&self, job: Job) -> Result<(), Job> {
+        let jobs = self.inner.jobs.lock().unwrap();
+        let sender = jobs.as_ref().expect("Verification pool is shut down.");
+        // count the job before a worker can complete it
+        self.inner.in_flight.fetch_add(1, Ordering::SeqCst);
+        match sender.try_send(job) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(job)) | Err(TrySendError::Disconnected(job)) => {
+                self.inner.in_flight.fetch_sub(1, Ordering::SeqCst);
+                Err(job)
+            }
+        }
+    }
+
+    /// Collect the results of completed jobs, blocking until all
+    /// submitted jobs have completed if wait is set
+    pub(crate) fn collect(&self, wait: bool) -> Vec<(EncryptedVote, bool)> {
+        let results = self.inner.results.lock().unwrap();
+        let mut completed = vec![];
+        loop {
+            let result = if wait && self.in_flight() > 0 {
+                results.recv().ok()
+            } else {
+                results.try_recv().ok()
+            };
+            match result {
+                Some(result) => {
+                    self.inner.in_flight.fetch_sub(1, Ordering::SeqCst);
+                    completed.push(result);
+                }
+                None => break,
+            }
+        }
+        completed
+    }
+}
+
+impl fmt::Debug for VerificationPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VerificationPool")
+            .field("num_workers", &self.num_workers())
+            .field("in_flight", &self.in_flight())
+            .finish()
+    }
+}
+
+impl Drop for PoolInner {
+    fn drop(&mut self) {
+        // closing the job queue stops the workers once they are idle
+        if let Ok(mut jobs) = self.jobs.lock() {
+            jobs.take();
+        }
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
@@ -0,0 +1,277 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// BALLOT PUBLICATION
+// ================================================================================================
+// Once all ballots are collected, the aggregator publishes the ballots proven by the cast proof,
+// e.g. on the bulletin board or on IPFS. Ballots are listed in the order of the voting keys, with
+// encrypted votes and CDS proof points in the compressed form the cast proof commits to, so that
+// observers can check each CDS proof and rebuild the public inputs of the cast proof. Ballots
+// flagged as invalid carry the signature of their voter, so that observers can check that the
+// aggregator did not exclude a ballot the voter never submitted.
+
+use super::{
+    cast::{CollectorError, EncryptedVote, VoteCollector},
+    constants::*,
+};
+use crate::{
+    cds::{
+        concat_proof_points, read_ballot_signature, verify_cds_proof_projective,
+        write_ballot_signature, BallotSignature,
+    },
+    compat::curve::{AffinePoint, ProjectivePoint, Scalar},
+    utils::{
+        ecc::{
+            projective_to_elements, read_canonical_scalars, read_compressed_point,
+            read_compressed_points, write_compressed_points,
+        },
+        wire,
+    },
+};
+use winterfell::{
+    math::{fields::f63::BaseElement, FieldElement},
+    ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable, SliceReader,
+};
+
+/// Ballot of a voter as proven by the cast proof
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PublishedBallot {
+    /// Index of the voter
+    pub voter_index: usize,
+    /// Encrypted vote
+    pub encrypted_vote: [BaseElement; AFFINE_POINT_WIDTH],
+    /// Points in CDS proof
+    pub proof_points: [BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH],
+    /// Scalars in CDS proof
+    pub proof_scalars: [Scalar; PROOF_NUM_SCALARS],
+    /// Set to false if the ballot is flagged as invalid in the cast proof
+    pub valid: bool,
+    /// Signature of the voter on a ballot flagged as invalid, None for valid ballots
+    pub signature: Option<BallotSignature>,
+}
+
+impl PublishedBallot {
+    /// Convert the published ballot to an encrypted vote, signed by its voter if flagged as invalid
+    pub fn to_encrypted_vote(&self) -> EncryptedVote {
+        let mut proof_points = [ProjectivePoint::identity(); PROOF_NUM_POINTS];
+        let mut point = [BaseElement::ZERO; AFFINE_POINT_WIDTH];
+        for (i, proof_point) in proof_points.iter_mut().enumerate() {
+            point.copy_from_slice(
+                &self.proof_points[i * AFFINE_POINT_WIDTH..(i + 1) * AFFINE_POINT_WIDTH],
+            );
+            *proof_point = ProjectivePoint::from(AffinePoint::from_raw_coordinates(point));
+        }
+        let encrypted_vote = EncryptedVote::new(
+            self.voter_index,
+            ProjectivePoint::from(AffinePoint::from_raw_coordinates(self.encrypted_vote)),
+            proof_points,
+            self.proof_scalars,
+        );
+        match self.signature {
+            Some(signature) => encrypted_vote.with_signature(signature),
+            None => encrypted_vote,
+        }
+    }
+}
+
+impl Serializable for PublishedBallot {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        wire::write_varint(target, self.voter_index as u64);
+        write_compressed_points(&self.encrypted_vote, target);
+        write_compressed_points(&self.proof_points, target);
+        Serializable::write_batch_into(&self.proof_scalars, target);
+        target.write_u8(self.valid as u8);
+        if !self.valid {
+            let signature = self
+                .signature
+                .expect("Ballots flagged as invalid are signed by their voter.");
+            write_ballot_signature(&signature, target);
+        }
+    }
+}
+
+impl Deserializable for PublishedBallot {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let voter_index = wire::read_varint_usize(source)?;
+        let encrypted_vote = read_compressed_point(source)?;
+        let mut proof_points = [BaseElement::ZERO; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH];
+        proof_points.copy_from_slice(&read_compressed_points(source, PROOF_NUM_POINTS)?);
+        let mut proof_scalars = [Scalar::zero(); PROOF_NUM_SCALARS];
+        proof_scalars.copy_from_slice(&read_canonical_scalars(source, PROOF_NUM_SCALARS)?);
+        let valid = match source.read_u8()? {
+            0 => false,
+            1 => true,
+            _ => {
+                return Err(DeserializationError::InvalidValue(String::from(
+                    "Invalid ballot validity flag.",
+                )))
+            }
+        };
+        let signature = if valid {
+            None
+        } else {
+            Some(read_ballot_signature(source)?)
+        };
+        Ok(Self {
+            voter_index,
+            encrypted_vote,
+            proof_points,
+            proof_scalars,
+            valid,
+            signature,
+        })
+    }
+}
+
+/// Ordered list of the ballots proven by the cast proof
+#[derive(Debug, Clone, PartialEq)]
+pub struct BallotList {
+    /// Ballots, in the order of the voting keys
+    pub ballots: Vec<PublishedBallot>,
+}
+
+impl BallotList {
+    /// Deserialize a ballot list, rejecting any trailing bytes
+    pub fn from_bytes(source: &[u8]) -> Result<Self, DeserializationError> {
+        let mut source = SliceReader::new(source);
+        let ballot_list = Self::read_from(&mut source)?;
+        if source.has_more_bytes() {
+            return Err(DeserializationError::UnconsumedBytes);
+        }
+        Ok(ballot_list)
+    }
+}
+
+impl Serializable for BallotList {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        wire::write_header(target);
+        wire::write_varint(target, self.ballots.len() as u64);
+        for ballot in self.ballots.iter() {
+            ballot.write_into(target);
+        }
+    }
+}
+
+impl Deserializable for BallotList {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        if let wire::WireHeader::Legacy(_) = wire::read_header(source)? {
+            return Err(DeserializationError::InvalidValue(String::from(
+                "Missing wire header.",
+            )));
+        }
+        let num_ballots = wire::read_varint_usize(source)?;
+        // the number of ballots is untrusted, so that ballots are not preallocated
+        let mut ballots = Vec::new();
+        for position in 0..num_ballots {
+            let ballot = PublishedBallot::read_from(source)?;
+            if ballot.voter_index != position {
+                return Err(DeserializationError::InvalidValue(String::from(
+                    "Ballots are not listed in the order of the voting keys.",
+                )));
+            }
+            ballots.push(ballot);
+        }
+        Ok(Self { ballots })
+    }
+}
+
+impl VoteCollector {
+    /// Export the ballots proven by the cast proof, valid or flagged as invalid,
+    /// for publication. All voters must have submitted a ballot.
+    pub fn export_ballot_list(&self) -> Result<BallotList, CollectorError> {
+        let ballots = (0..self.voting_keys.len())
+            .map(|voter_index| {
+                self.published_ballot(voter_index)
+                    .ok_or(CollectorError::NotEnoughEncryptedVotes)
+            })
+            .collect::<Result<Vec<PublishedBallot>, CollectorError>>()?;
+        Ok(BallotList { ballots })
+    }
+
+    /// Import a published ballot list, e.g. by an observer rebuilding the collector from
+    /// the voting keys. CDS proofs are verified again, and ballots flagged as invalid are
+    /// only accepted if their CDS proof is indeed invalid and they are signed by their voter.
+    pub fn import_ballot_list(
+        &mut self,
+        ballot_list: &BallotList,
+    ) -> Vec<Result<(), CollectorError>> {
+        ballot_list
+            .ballots
+            .iter()
+            .map(|ballot| self.import_published_ballot(ballot))
+            .collect()
+    }
+
+    /// Ballot of the voter at voter_index, valid or flagged as invalid, if any
+    pub(crate) fn published_ballot(&self, voter_index: usize) -> Option<PublishedBallot> {
+        match (
+            self.encrypted_votes.get(voter_index)?,
+            self.excluded_ballots[voter_index],
+        ) {
+            (Some(encrypted_vote), _) => Some(PublishedBallot {
+                voter_index,
+                encrypted_vote: *encrypted_vote,
+                proof_points: self.proof_points[voter_index].unwrap(),
+                proof_scalars: self.proof_scalars[voter_index].unwrap(),
+                valid: true,
+                signature: None,
+            }),
+            (None, Some(excluded_ballot)) => Some(PublishedBallot {
+                voter_index,
+                encrypted_vote: projective_to_elements(excluded_ballot.encrypted_vote),
+                proof_points: concat_proof_points(&excluded_ballot.proof_points),
+                proof_scalars: excluded_ballot.proof_scalars,
+                valid: false,
+                signature: excluded_ballot.signature(),
+            }),
+            (None, None) => None,
+        }
+    }
+
+    /// Import a ballot published or transferred by an untrusted party. Its CDS proof
+    /// is verified again, and a ballot flagged as invalid is only accepted if its CDS
+    /// proof is indeed invalid and it is signed by its voter.
+    pub(crate) fn import_published_ballot(
+        &mut self,
+        ballot: &PublishedBallot,
+    ) -> Result<(), CollectorError> {
+        let voter_index = ballot.voter_index;
+        if voter_index >= self.voting_keys.len() {
+            return Err(CollectorError::InvalidEncryptedVote);
+        }
+        let encrypted_vote = ballot.to_encrypted_vote();
+        self.check_reveal(&encrypted_vote)?;
+        let voting_key = ProjectivePoint::from(AffinePoint::from_raw_coordinates(
+            self.voting_keys[voter_index],
+        ));
+        let is_valid = verify_cds_proof_projective(
+            voter_index,
+            voting_key,
+            self.blinding_keys[voter_index],
+            encrypted_vote.encrypted_vote,
+            &encrypted_vote.proof_points,
+            &encrypted_vote.proof_scalars,
+            &self.cds_context(),
+        )
+        .is_ok();
+        match (ballot.valid, is_valid) {
+            (true, true) => self.add_verified_encrypted_vote(encrypted_vote),
+            (false, false)
+                if self.encrypted_votes[voter_index].is_none()
+                    && self.excluded_ballots[voter_index].is_none()
+                    && encrypted_vote
+                        .is_signed_by(self.voting_keys[voter_index], &self.cds_context()) =>
+            {
+                self.excluded_ballots[voter_index] = Some(encrypted_vote);
+                self.serialized_proof.clear();
+                Ok(())
+            }
+            _ => Err(CollectorError::InvalidEncryptedVote),
+        }
+    }
+}
@@ -1,18 +1,46 @@
 use crate::{
-    aggregator::build_options,
-    merkle::{verify_merlke_proof, MerkleProver},
-    schnorr::{verify_signature, SchnorrProver},
+    aggregator::{
+        admission::{AdmissionPolicy, AllowAll, Submission},
+        build_options_with_fri,
+        ownership::BYTES_PER_OWNERSHIP_SIGNATURE,
+        rejection::{RejectionLog, RejectionReason, Submitter},
+        status::{ProofRecord, ProofTimer},
+    },
+    backend::{ElectionProver, RegisterBackend, RegisterTraceInputs},
+    chain::{register_commitment, PhaseCommitment},
+    compat::curve::Scalar,
+    manifest::ElectionManifest,
+    merkle::{prepare_grouped_hash_message, MerkleTree},
+    planner::FriConfig,
+    schnorr::{
+        build_sig_info, sequence_digest, sign_digest, verify_digest_signature,
+        SchnorrSequenceProver, SigInfo, SigningDomain,
+    },
+    utils::{
+        address::Address,
+        ecc::{read_canonical_scalar, read_compressed_point, write_compressed_points},
+        ordering::KeyOrder,
+        rescue::{self, digest_bytes},
+        wire,
+    },
+    voter::{diagnose_registration, RegistrationState},
 };
 use log::debug;
-use web3::types::Address;
+use std::collections::BTreeMap;
 use winterfell::{
-    math::{curves::curve_f63::Scalar, fields::f63::BaseElement, FieldElement},
+    math::{fields::f63::BaseElement, FieldElement},
     ByteReader, ByteWriter, Deserializable, DeserializationError, Prover, ProverError,
     Serializable, SliceReader,
 };
 
 use super::constants::*;
 
+#[cfg(feature = "std")]
+use crate::utils::proof_store::{inputs_key, ProofStore, StoreDigest};
+
+#[cfg(feature = "concurrent")]
+use rayon::prelude::*;
+
 /// registration of a voter
 #[derive(Debug, Clone, Copy)]
 pub struct Registration {
@@ -26,6 +54,76 @@ pub struct Registration {
     pub signature: ([BaseElement; POINT_COORDINATE_WIDTH], Scalar),
     /// Ethereum address
     pub address: Address,
+    /// Signature of the voting key by the key of the Ethereum address,
+    /// required when the registar checks ownership of addresses
+    pub ownership_signature: Option<[u8; BYTES_PER_OWNERSHIP_SIGNATURE]>,
+}
+
+impl Registration {
+    /// Digest signed by a voter to replace their registration with this one, chained to
+    /// the signature of the registration it replaces, so that it cannot be replayed once
+    /// the registration of the voter has been replaced again
+    pub fn replacement_digest(
+        &self,
+        previous_signature: &([BaseElement; POINT_COORDINATE_WIDTH], Scalar),
+    ) -> rescue::Hash {
+        let mut bytes = vec![];
+        Serializable::write_batch_into(&previous_signature.0, &mut bytes);
+        bytes.write(previous_signature.1);
+        self.write_into(&mut bytes);
+        digest_bytes(&bytes)
+    }
+
+    /// Sign the replacement of the registration with the given signature by this one,
+    /// see VoterRegistar::replace_registration
+    pub fn sign_replacement(
+        &self,
+        secret_key: Scalar,
+        previous_signature: &([BaseElement; POINT_COORDINATE_WIDTH], Scalar),
+    ) -> ([BaseElement; POINT_COORDINATE_WIDTH], Scalar) {
+        sign_digest(secret_key, &self.replacement_digest(previous_signature))
+    }
+}
+
+impl Serializable for Registration {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        write_compressed_points(&self.voting_key, target);
+        Serializable::write_batch_into(&self.merkle_branch, target);
+        target.write_u64(self.hash_index as u64);
+        Serializable::write_batch_into(&self.signature.0, target);
+        target.write(self.signature.1);
+        target.write_u8_slice(self.address.as_bytes());
+        write_ownership_signature(&self.ownership_signature, target);
+    }
+}
+
+impl Deserializable for Registration {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let voting_key = read_compressed_point(source)?;
+        let mut merkle_branch = [BaseElement::ZERO; TREE_DEPTH * DIGEST_SIZE];
+        merkle_branch.copy_from_slice(&BaseElement::read_batch_from(
+            source,
+            TREE_DEPTH * DIGEST_SIZE,
+        )?);
+        let hash_index = source.read_u64()? as usize;
+        let mut signature_r = [BaseElement::ZERO; POINT_COORDINATE_WIDTH];
+        signature_r.copy_from_slice(&BaseElement::read_batch_from(
+            source,
+            POINT_COORDINATE_WIDTH,
+        )?);
+        let signature_s = read_canonical_scalar(source)?;
+        let address = Address::from_slice(&source.read_u8_vec(Address::len_bytes())?);
+        let ownership_signature = read_ownership_signature(source)?;
+
+        Ok(Self {
+            voting_key,
+            merkle_branch,
+            hash_index,
+            signature: (signature_r, signature_s),
+            address,
+            ownership_signature,
+        })
+    }
 }
 
 /// Errors raised by VoterRegistar
@@ -38,38 +136,34 @@ pub enum RegistarError {
     InvalidMerkleProof,
     /// This error occurs when Schnorr signature is invalid
     InvalidSchnorrSig,
+    /// This error occurs when the registar checks ownership of addresses and
+    /// the voting key is not signed by the key of the Ethereum address
+    InvalidOwnershipSig,
     /// This error occurs when the number of registrations
     /// exceeds the number eligible voters
     TooManyRegistrations,
+    /// This error occurs when registration is rejected
+    /// by the admission policy
+    Rejected,
+    /// This error occurs when a one-time credential is redeemed again
+    CredentialRedeemed,
+    /// This error occurs when a registration differs from the one already processed
+    /// for the same voting key, and its replacement signature is missing or is not
+    /// chained to the registration it replaces
+    UnchainedReplacement,
+    /// This error occurs when a subset of registrations to prove
+    /// is empty or contains an index out of range, or when all registrations
+    /// are proven and their number is not a power of two greater than 1,
+    /// e.g. in elections with a single registered voter
+    InvalidSubset,
+    /// Wrapper for errors raised by RegisterProver
+    Prover(ProverError),
 }
 
 /// Compact public inputs sent to on-chain verifier
-/// to minimize the cost of calldata
-#[derive(Debug)]
-pub struct CompactPublicInputs {
-    /// voting keys
-    pub voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
-    /// Ethereum addresses
-    pub addresses: Vec<Address>,
-    /// signatures
-    pub signatures: Vec<([BaseElement; POINT_COORDINATE_WIDTH], Scalar)>,
-}
-
-impl Serializable for CompactPublicInputs {
-    fn write_into<W: ByteWriter>(&self, target: &mut W) {
-        target.write_u32(self.voting_keys.len() as u32);
-        for voting_key in self.voting_keys.iter() {
-            Serializable::write_batch_into(voting_key, target);
-        }
-        for address in self.addresses.iter() {
-            target.write_u8_slice(address.as_bytes());
-        }
-        for signature in self.signatures.iter() {
-            Serializable::write_batch_into(&signature.0, target);
-            target.write(signature.1);
-        }
-    }
-}
+/// to minimize the cost of calldata. They share the
+/// byte format of Schnorr public inputs.
+pub use crate::schnorr::PublicInputs as CompactPublicInputs;
 
 /// Type that encapsulates all data and functionalities of
 /// aggregator during voter registration phase
@@ -81,43 +175,123 @@ pub struct VoterRegistar {
     pub num_elg_voters: usize,
     /// voting keys
     pub voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
-    /// sibling nodes on the branch from leaf to root
-    /// in Merkle proof of membership
-    pub merkle_branches: Vec<[BaseElement; TREE_DEPTH * DIGEST_SIZE]>,
+    /// Merkle tree of eligible voters, from which branches
+    /// of Merkle proofs of membership are derived
+    pub tree: MerkleTree,
     /// branch index in Merkle proof of membership
     pub hash_indices: Vec<usize>,
     /// Schnorr signatures
     pub signatures: Vec<([BaseElement; POINT_COORDINATE_WIDTH], Scalar)>,
     /// Ethereum addresses of voters
     pub addresses: Vec<Address>,
+    /// Set to true if voters must prove ownership of their Ethereum address
+    pub require_ownership: bool,
+    /// Signatures of voting keys by the keys of Ethereum addresses
+    pub ownership_signatures: Vec<Option<[u8; BYTES_PER_OWNERSHIP_SIGNATURE]>>,
+    /// Digest of the election manifest bound into the proofs, if any.
+    /// It is not serialized and must be bound again after restoring the registar.
+    pub manifest_digest: Option<[BaseElement; DIGEST_SIZE]>,
+    /// Chain and election to which registration signatures are bound. It is not
+    /// serialized and must be bound again after restoring the registar.
+    pub domain: SigningDomain,
+    /// Order of registered voters in the register proof, see set_key_order. It is
+    /// not serialized and must be set again after restoring the registar.
+    pub key_order: KeyOrder,
+    /// Set to true if the leaf index of each registered voter is bound into the
+    /// register proof, see bind_leaf_indices. It is not serialized.
+    pub leaf_binding: bool,
+    /// Group label committed in each leaf of the tree of eligible voters, empty if
+    /// voters are not grouped, see bind_groups. It is not serialized and must be
+    /// bound again after restoring the registar, see read_from_grouped.
+    pub leaf_groups: Vec<u32>,
+    /// Field extension of the STARK proofs, see build_options. It is not serialized.
+    pub extension: u8,
+    /// FRI parameters of the STARK proofs, see build_options_with_fri. It is not serialized.
+    pub fri: FriConfig,
+    /// Log of rejected registrations. It is not serialized.
+    pub rejections: RejectionLog,
 
     /// Set to true if cached proof is outdated
     pub dirty_flag: bool,
     /// Cached proof
     pub serialized_proof: Vec<u8>,
+    /// Record of the last register proof generated. It is not serialized.
+    pub last_proof: Option<ProofRecord>,
 }
 
 impl VoterRegistar {
-    /// Create an object of type VoterRegistar
+    /// Create an object of type VoterRegistar from the Merkle tree of eligible voters
     /// Initially, dirty_flag is not set
-    pub fn new(elg_root: [BaseElement; DIGEST_SIZE], num_elg_voters: usize) -> Self {
+    ///
+    /// # Panics
+    /// Panics if the depth of the tree is not TREE_DEPTH
+    pub fn new(tree: MerkleTree, num_elg_voters: usize) -> Self {
+        assert_eq!(
+            tree.depth(),
+            TREE_DEPTH,
+            "Depth of the tree of eligible voters must be TREE_DEPTH."
+        );
         Self {
-            elg_root,
+            elg_root: tree.root(),
             num_elg_voters,
             voting_keys: Vec::with_capacity(num_elg_voters),
-            merkle_branches: Vec::with_capacity(num_elg_voters),
+            tree,
             hash_indices: Vec::with_capacity(num_elg_voters),
             signatures: Vec::with_capacity(num_elg_voters),
             addresses: Vec::with_capacity(num_elg_voters),
+            require_ownership: false,
+            ownership_signatures: Vec::with_capacity(num_elg_voters),
+            manifest_digest: None,
+            domain: SigningDomain::default(),
+            key_order: KeyOrder::Submission,
+            leaf_binding: false,
+            leaf_groups: Vec::new(),
+            extension: 1,
+            fri: FriConfig::DEFAULT,
+            rejections: RejectionLog::new(),
             dirty_flag: false,
             serialized_proof: vec![],
+            last_proof: None,
         }
     }
 
+    /// Create an object of type VoterRegistar bound to the given election manifest,
+    /// which only accepts registrations whose voting key is signed for this election
+    /// by the key of the Ethereum address
+    pub fn with_address_ownership(
+        tree: MerkleTree,
+        num_elg_voters: usize,
+        manifest: &ElectionManifest,
+    ) -> Self {
+        let mut registar = Self {
+            require_ownership: true,
+            ..Self::new(tree, num_elg_voters)
+        };
+        registar.bind_manifest(manifest);
+        registar
+    }
+
     /// Reconstruct an object of type Self from a sequence of bytes
-    pub fn from_bytes(source: &[u8]) -> Result<Self, DeserializationError> {
+    /// and the Merkle tree of eligible voters, rejecting trailing bytes
+    pub fn from_bytes(source: &[u8], tree: MerkleTree) -> Result<Self, DeserializationError> {
         let mut source = SliceReader::new(source);
-        Self::read_from(&mut source)
+        let registar = Self::read_from(&mut source, tree)?;
+        if source.has_more_bytes() {
+            return Err(DeserializationError::UnconsumedBytes);
+        }
+        Ok(registar)
+    }
+
+    /// Siblings on the branch from the leaf of the i-th registered voter to the root
+    pub fn merkle_branch(&self, i: usize) -> [BaseElement; TREE_DEPTH * DIGEST_SIZE] {
+        self.tree.branch(self.hash_indices[i])
+    }
+
+    /// Siblings on the branches from the leaves of all registered voters to the root
+    pub fn merkle_branches(&self) -> Vec<[BaseElement; TREE_DEPTH * DIGEST_SIZE]> {
+        (0..self.voting_keys.len())
+            .map(|i| self.merkle_branch(i))
+            .collect()
     }
 
     /// Dump self to an output stream
@@ -127,45 +301,89 @@ impl VoterRegistar {
 
     /// Process new registration submitted by voter
     /// Return Ok if registration is processed successfully.
+    /// A registration of a voter who has already registered is only accepted if it is
+    /// the same as the one already processed, see replace_registration.
     pub fn add_registration(&mut self, registration: Registration) -> Result<(), RegistarError> {
-        // Two voters cannot share one Ethereum address
-        if self.addresses.contains(&registration.address) {
-            let idx = self
-                .addresses
-                .iter()
-                .position(|&a| a == registration.address)
-                .unwrap();
-            if self.voting_keys[idx] != registration.voting_key {
-                return Err(RegistarError::DuplicatedEthAddress);
-            }
+        self.add_registration_with_policy(registration, &mut AllowAll)
+    }
+
+    /// Replace the registration of a voter who has already registered. The replacement
+    /// must be signed by the voting key over the digest chained to the signature of the
+    /// registration it replaces, see Registration::sign_replacement, so that the Merkle
+    /// branch or the address of a voter cannot be overwritten by replaying their
+    /// earlier registrations.
+    pub fn replace_registration(
+        &mut self,
+        registration: Registration,
+        replacement_signature: ([BaseElement; POINT_COORDINATE_WIDTH], Scalar),
+    ) -> Result<(), RegistarError> {
+        let result =
+            self.process_registration(registration, Some(replacement_signature), &mut AllowAll);
+        self.log_rejection(&registration, result)
+    }
+
+    /// Process new registration submitted by voter after consulting
+    /// the admission policy. Rejected registrations are not verified.
+    /// Return Ok if registration is processed successfully, and
+    /// record the rejection in the rejection log otherwise.
+    pub fn add_registration_with_policy<P: AdmissionPolicy>(
+        &mut self,
+        registration: Registration,
+        policy: &mut P,
+    ) -> Result<(), RegistarError> {
+        let result = self.process_registration(registration, None, policy);
+        self.log_rejection(&registration, result)
+    }
+
+    fn process_registration<P: AdmissionPolicy>(
+        &mut self,
+        registration: Registration,
+        replacement_signature: Option<([BaseElement; POINT_COORDINATE_WIDTH], Scalar)>,
+        policy: &mut P,
+    ) -> Result<(), RegistarError> {
+        if !policy.admit(Submission::Registration(&registration)) {
+            return Err(RegistarError::Rejected);
         }
 
-        // Check if Merkle proof of membership is valid
-        if !verify_merlke_proof(
-            &self.elg_root,
-            &registration.voting_key,
-            &registration.merkle_branch,
-            registration.hash_index,
-        ) {
-            return Err(RegistarError::InvalidMerkleProof);
+        // Run the same checks as the pre-flight checks of voters
+        let state = RegistrationState {
+            elg_root: &self.elg_root,
+            num_elg_voters: self.num_elg_voters,
+            require_ownership: self.require_ownership,
+            domain: &self.domain,
+            manifest_digest: self.manifest_digest.as_ref(),
+            voting_keys: &self.voting_keys,
+            addresses: &self.addresses,
+            leaf_groups: &self.leaf_groups,
+        };
+        let diagnostics = diagnose_registration(&registration, &state);
+        if let Some(error) = diagnostics.error() {
+            return Err(error);
         }
+        policy.record(Submission::Registration(&registration));
 
-        // Check if Schnorr signature is valid
-        if !verify_signature(
-            registration.voting_key,
-            registration.address,
-            registration.signature,
-        ) {
-            return Err(RegistarError::InvalidSchnorrSig);
+        // If this voter has already submitted a registration, replace their old
+        // registration with this registration if the replacement is chained to it
+        match (diagnostics.replaced_registration, replacement_signature) {
+            (Some(idx), _) if self.is_processed(idx, &registration) => Ok(()),
+            (Some(idx), Some(replacement_signature)) => {
+                let digest = registration.replacement_digest(&self.signatures[idx]);
+                verify_digest_signature(registration.voting_key, &digest, replacement_signature)
+                    .map_err(|_| RegistarError::UnchainedReplacement)?;
+                self.add_registration_unchecked(registration, Some(idx))
+            }
+            (None, None) => self.add_registration_unchecked(registration, None),
+            _ => Err(RegistarError::UnchainedReplacement),
         }
+    }
 
-        // If this voter has already submitted a registration
-        // replace their old registration with this registration
-        let idx = self
-            .voting_keys
-            .iter()
-            .position(|&vk| vk == registration.voting_key);
-        self.add_registration_unchecked(registration, idx)
+    /// Return true if the registration at the given index is the given registration,
+    /// which is then accepted again without replacing anything
+    fn is_processed(&self, idx: usize, registration: &Registration) -> bool {
+        self.hash_indices[idx] == registration.hash_index
+            && self.signatures[idx] == registration.signature
+            && self.addresses[idx] == registration.address
+            && self.ownership_signatures[idx] == registration.ownership_signature
     }
 
     /// Bulk process new registrations submitted by voters
@@ -190,90 +408,433 @@ impl VoterRegistar {
         if idx.is_some() {
             let idx = idx.unwrap();
             self.voting_keys[idx] = registration.voting_key;
-            self.merkle_branches[idx] = registration.merkle_branch;
             self.hash_indices[idx] = registration.hash_index;
             self.signatures[idx] = registration.signature;
             self.addresses[idx] = registration.address;
+            self.ownership_signatures[idx] = registration.ownership_signature;
         } else {
             if self.voting_keys.len() + 1 > self.num_elg_voters {
                 return Err(RegistarError::TooManyRegistrations);
             }
-            self.voting_keys.push(registration.voting_key);
-            self.merkle_branches.push(registration.merkle_branch);
-            self.hash_indices.push(registration.hash_index);
-            self.signatures.push(registration.signature);
-            self.addresses.push(registration.address);
+            let idx = self
+                .key_order
+                .position(&self.voting_keys, &registration.voting_key);
+            self.voting_keys.insert(idx, registration.voting_key);
+            self.hash_indices.insert(idx, registration.hash_index);
+            self.signatures.insert(idx, registration.signature);
+            self.addresses.insert(idx, registration.address);
+            self.ownership_signatures
+                .insert(idx, registration.ownership_signature);
         }
         self.dirty_flag = true;
         Ok(())
     }
 
+    /// Redeem a one-time credential by processing its registration.
+    /// Unlike add_registration, a registered voting key cannot be registered
+    /// again, so that each credential is only redeemed once.
+    pub fn redeem_credential(&mut self, registration: Registration) -> Result<(), RegistarError> {
+        if self.is_redeemed(&registration.voting_key) {
+            return self.log_rejection(&registration, Err(RegistarError::CredentialRedeemed));
+        }
+        self.add_registration(registration)
+    }
+
+    /// Record the registration in the rejection log if it was rejected
+    fn log_rejection(
+        &mut self,
+        registration: &Registration,
+        result: Result<(), RegistarError>,
+    ) -> Result<(), RegistarError> {
+        if let Some(reason) = result
+            .as_ref()
+            .err()
+            .and_then(RejectionReason::from_registar_error)
+        {
+            self.rejections.record(
+                Submitter::Address(registration.address),
+                reason,
+                &registration.to_bytes(),
+            );
+        }
+        result
+    }
+
+    /// Check whether the credential of the given voting key was redeemed
+    pub fn is_redeemed(&self, voting_key: &[BaseElement; AFFINE_POINT_WIDTH]) -> bool {
+        self.voting_keys.contains(voting_key)
+    }
+
+    /// Bind subsequent register proofs to the given election manifest
+    pub fn bind_manifest(&mut self, manifest: &ElectionManifest) {
+        self.manifest_digest = Some(manifest.digest().to_elements());
+        self.dirty_flag = true;
+    }
+
+    /// Only accept registrations signed in the given signing domain, and bind subsequent
+    /// proofs to it. It must be called before voters register, as signatures of
+    /// registrations already processed are not checked again.
+    pub fn bind_domain(&mut self, domain: SigningDomain) {
+        self.domain = domain;
+        self.dirty_flag = true;
+    }
+
+    /// Bind the leaf index of each registered voter in the tree of eligible voters into
+    /// subsequent register proofs, so that a voting key cannot be proven at another
+    /// position than the one recorded with its registration. Such proofs are verified
+    /// with RegisterVerification::with_leaf_indices.
+    pub fn bind_leaf_indices(&mut self) {
+        self.leaf_binding = true;
+        self.dirty_flag = true;
+    }
+
+    /// Generate subsequent register proofs with the given field extension, see build_options
+    pub fn set_extension(&mut self, extension: u8) {
+        self.extension = extension;
+        self.dirty_flag = true;
+    }
+
+    /// Bind the group label committed in each leaf of the tree of eligible voters, see
+    /// MerkleTree::leaf_of_grouped, into subsequent register proofs. Such proofs are
+    /// verified with RegisterVerification::with_groups. It must be called
+    /// before voters register, as registrations are checked against grouped leaves.
+    ///
+    /// # Panics
+    /// Panics if there is not one group label per leaf of the tree
+    pub fn bind_groups(&mut self, leaf_groups: Vec<u32>) {
+        assert_eq!(
+            leaf_groups.len(),
+            self.tree.num_leaves(),
+            "Number of groups must match the number of leaves."
+        );
+        self.leaf_groups = leaf_groups;
+        self.dirty_flag = true;
+    }
+
+    /// Group label of the i-th registered voter, which is 0 if voters are not grouped
+    pub fn group_of(&self, i: usize) -> u32 {
+        self.leaf_groups
+            .get(self.hash_indices[i])
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Voting keys of registered voters by group label, in the order of registered
+    /// voters. Votes of each group are cast with a VoteCollector of its own voting keys,
+    /// so that each group can be tallied on its own, see breakdown::GroupTallier.
+    pub fn voting_keys_by_group(&self) -> BTreeMap<u32, Vec<[BaseElement; AFFINE_POINT_WIDTH]>> {
+        let mut groups = BTreeMap::new();
+        for (i, voting_key) in self.voting_keys.iter().enumerate() {
+            groups
+                .entry(self.group_of(i))
+                .or_insert_with(Vec::new)
+                .push(*voting_key);
+        }
+        groups
+    }
+
+    /// List registered voters in the given order in subsequent register proofs, and
+    /// in the vote collector created from them. Registrations already processed are
+    /// reordered, so that indices of registered voters may change.
+    pub fn set_key_order(&mut self, key_order: KeyOrder) {
+        let permutation = key_order.permutation(&self.voting_keys);
+        self.voting_keys = permutation.iter().map(|&i| self.voting_keys[i]).collect();
+        self.hash_indices = permutation.iter().map(|&i| self.hash_indices[i]).collect();
+        self.signatures = permutation.iter().map(|&i| self.signatures[i]).collect();
+        self.addresses = permutation.iter().map(|&i| self.addresses[i]).collect();
+        self.ownership_signatures = permutation
+            .iter()
+            .map(|&i| self.ownership_signatures[i])
+            .collect();
+        self.key_order = key_order;
+        self.dirty_flag = true;
+    }
+
+    /// Generate subsequent register proofs with the given FRI parameters,
+    /// see build_options_with_fri
+    pub fn set_fri(&mut self, fri: FriConfig) {
+        self.fri = fri;
+        self.dirty_flag = true;
+    }
+
+    /// Commitment to the registration phase, to which the cast proof is chained
+    /// with VoteCollector::bind_register_commitment
+    pub fn phase_commitment(&self) -> PhaseCommitment {
+        register_commitment(&self.elg_root, &self.voting_keys)
+    }
+
     /// Get compact public inputs to submit to
-    /// on-chain verifier
+    /// on-chain verifier, which stores the signing domain itself
     pub fn get_pub_inputs(&self) -> CompactPublicInputs {
         CompactPublicInputs {
             voting_keys: self.voting_keys.clone(),
             addresses: self.addresses.clone(),
             signatures: self.signatures.clone(),
+            domain: SigningDomain::default(),
         }
     }
 
     /// Generate STARK proofs for verification of registrations
     /// Public inputs and proofs are serialized and returned as
-    /// a single sequenece of bytes
-    pub fn get_register_proof(&mut self) -> Result<Vec<u8>, ProverError> {
+    /// a single sequenece of bytes. The number of registrations
+    /// must be a power of two greater than 1.
+    pub fn get_register_proof(&mut self) -> Result<Vec<u8>, RegistarError> {
+        let num_regs = self.voting_keys.len();
+        if num_regs < 2 || !num_regs.is_power_of_two() {
+            return Err(RegistarError::InvalidSubset);
+        }
         if !self.dirty_flag {
             return Ok(self.serialized_proof.clone());
         }
 
-        // generate proof for verification of Merkle proofs
-        let merkle_prover = MerkleProver::new(
-            build_options(1),
-            self.elg_root.clone(),
-            self.voting_keys.clone(),
-        );
-        let merkle_trace =
-            merkle_prover.build_trace(self.merkle_branches.clone(), self.hash_indices.clone());
-        let merkle_proof = merkle_prover.prove(merkle_trace)?;
-
-        // generate proof for verification of Schnorr signatures
-        let schnorr_prover = SchnorrProver::new(
-            build_options(1),
-            self.voting_keys.clone(),
-            self.addresses.clone(),
-            self.signatures.clone(),
+        let indices = (0..num_regs).collect::<Vec<usize>>();
+        let timer = ProofTimer::start();
+        let serialized_proof = self
+            .prove_registrations(&indices, self.key_order)
+            .map_err(RegistarError::Prover)?;
+        self.last_proof = timer.finish(indices.len());
+
+        // Cache serialized STARK proof
+        self.serialized_proof = serialized_proof.clone();
+        self.dirty_flag = false;
+
+        Ok(serialized_proof)
+    }
+
+    /// Digest of the inputs of the register proof, under which
+    /// the proof is recorded in a ProofStore
+    #[cfg(feature = "std")]
+    pub fn proof_key(&self) -> StoreDigest {
+        let mut inputs = self.to_bytes();
+        match self.manifest_digest {
+            Some(manifest_digest) => {
+                inputs.write_u8(1);
+                Serializable::write_batch_into(&manifest_digest, &mut inputs);
+            }
+            None => inputs.write_u8(0),
+        }
+        self.domain.write_into(&mut inputs);
+        self.key_order.write_into(&mut inputs);
+        if self.leaf_binding {
+            inputs.write_u8_slice(b"leaves");
+        }
+        if !self.leaf_groups.is_empty() {
+            inputs.write_u8_slice(b"groups");
+            for &group in self.leaf_groups.iter() {
+                inputs.write_u32(group);
+            }
+        }
+        inputs.write_u8(self.extension);
+        inputs.write_u32(self.fri.folding_factor() as u32);
+        inputs.write_u32(self.fri.max_remainder_size() as u32);
+        inputs_key(b"register", &inputs)
+    }
+
+    /// Same as get_register_proof, reusing the proof found in the store for the
+    /// same registrations, e.g. one generated before the process was restarted.
+    /// New proofs are recorded in the store. Failing to read or write the store
+    /// is not an error, the proof is then generated again.
+    #[cfg(feature = "std")]
+    pub fn get_register_proof_cached(
+        &mut self,
+        store: &ProofStore,
+    ) -> Result<Vec<u8>, RegistarError> {
+        let num_regs = self.voting_keys.len();
+        if num_regs < 2 || !num_regs.is_power_of_two() {
+            return Err(RegistarError::InvalidSubset);
+        }
+        if !self.dirty_flag {
+            return Ok(self.serialized_proof.clone());
+        }
+
+        let key = self.proof_key();
+        match store.lookup(&key) {
+            Ok(Some(serialized_proof)) => {
+                self.serialized_proof = serialized_proof.clone();
+                self.dirty_flag = false;
+                return Ok(serialized_proof);
+            }
+            Ok(None) => {}
+            Err(error) => debug!("Could not read the proof store: {}", error),
+        }
+
+        let serialized_proof = self.get_register_proof()?;
+        if let Err(error) = store.insert(&key, &serialized_proof) {
+            debug!("Could not write the proof store: {}", error);
+        }
+        Ok(serialized_proof)
+    }
+
+    /// Generate STARK proofs for verification of the registrations at the given
+    /// indices only, so that late registrations can be proven incrementally.
+    /// Registrations are repeated to pad their number to a power of two.
+    /// The proof has the same format as the one of get_register_proof, and lists
+    /// registrations in the given order whatever the key order of the registar.
+    pub fn get_register_proof_for(&self, indices: &[usize]) -> Result<Vec<u8>, RegistarError> {
+        if indices.is_empty() || indices.iter().any(|&i| i >= self.voting_keys.len()) {
+            return Err(RegistarError::InvalidSubset);
+        }
+
+        let num_regs = usize::max(indices.len().next_power_of_two(), 2);
+        let indices = indices
+            .iter()
+            .cycle()
+            .take(num_regs)
+            .copied()
+            .collect::<Vec<usize>>();
+        self.prove_registrations(&indices, KeyOrder::Submission)
+            .map_err(RegistarError::Prover)
+    }
+
+    /// Generate and serialize the proof for the registrations at the given indices,
+    /// bound to the order in which they are listed
+    fn prove_registrations(
+        &self,
+        indices: &[usize],
+        key_order: KeyOrder,
+    ) -> Result<Vec<u8>, ProverError> {
+        let voting_keys = indices
+            .iter()
+            .map(|&i| self.voting_keys[i])
+            .collect::<Vec<_>>();
+        let addresses = indices
+            .iter()
+            .map(|&i| self.addresses[i])
+            .collect::<Vec<_>>();
+        let signatures = indices
+            .iter()
+            .map(|&i| self.signatures[i])
+            .collect::<Vec<_>>();
+        let hash_indices = indices
+            .iter()
+            .map(|&i| self.hash_indices[i])
+            .collect::<Vec<_>>();
+
+        // prepare the inputs of Merkle and Schnorr verifications in a single pass
+        let (merkle_messages, sig_infos) = self.prepare_register_inputs(indices);
+
+        // generate a single proof for verification of Merkle proofs and Schnorr signatures
+        let mut register_prover = RegisterBackend::new(
+            build_options_with_fri(self.extension, self.fri),
+            self.elg_root,
+            &voting_keys,
+            &addresses,
+            &signatures,
         );
-        let schnorr_trace = schnorr_prover.build_trace();
-        let schnorr_proof = schnorr_prover.prove(schnorr_trace)?;
+        if let Some(manifest_digest) = self.manifest_digest {
+            register_prover = register_prover.with_manifest(manifest_digest);
+        }
+        register_prover = register_prover
+            .with_domain(self.domain)
+            .with_key_order(key_order);
+        if self.leaf_binding {
+            register_prover = register_prover.with_leaf_indices(hash_indices.clone());
+        }
+        if !self.leaf_groups.is_empty() {
+            let groups = indices.iter().map(|&i| self.group_of(i)).collect();
+            register_prover = register_prover.with_groups(groups);
+        }
+        let (_, register_proof) = register_prover.prove_phase(RegisterTraceInputs {
+            merkle_messages,
+            hash_indices,
+            sig_infos,
+        })?;
 
-        // serialize public inputs and proofs
-        let compact_pub_inputs = self.get_pub_inputs();
+        // serialize public inputs and proofs, the signing domain
+        // being provided to the verifier separately
+        let compact_pub_inputs = CompactPublicInputs {
+            voting_keys,
+            addresses,
+            signatures,
+            domain: SigningDomain::default(),
+        };
         let mut serialized_proof = vec![];
         compact_pub_inputs.write_into(&mut serialized_proof);
-        // Serialize STARK proof for merkle
-        let merkle_proof_bytes = merkle_proof.to_bytes();
-        serialized_proof.write_u32(merkle_proof_bytes.len() as u32);
-        serialized_proof.write_u8_slice(&merkle_proof_bytes);
-        // Serialize STARK proof for schnorr
-        let schnorr_proof_bytes = &schnorr_proof.to_bytes();
-        serialized_proof.write_u8_slice(&schnorr_proof_bytes);
+        // Serialize STARK proof
+        serialized_proof.write_u8_slice(&register_proof.to_bytes());
 
         debug!("Generated serialized STARK proof of size {} bytes for verification of {} registrations.",
             serialized_proof.len(),
-            self.voting_keys.len()
+            indices.len()
         );
 
-        // Cache serialized STARK proof
-        self.serialized_proof = serialized_proof.clone();
-        self.dirty_flag = false;
+        Ok(serialized_proof)
+    }
 
+    /// Sequence hash of the registrations, as stored by an on-chain verifier which
+    /// absorbs each registration when it is submitted, see verify_signature_sequence_proof
+    pub fn sequence_digest(&self) -> Vec<u8> {
+        let digest = sequence_digest(&self.voting_keys, &self.addresses, &self.signatures);
+        let mut serialized_digest = vec![];
+        Serializable::write_batch_into(&digest, &mut serialized_digest);
+        serialized_digest
+    }
+
+    /// Generate a STARK proof for verification of the Schnorr signatures of all
+    /// registrations, whose only public input is their sequence hash. The number
+    /// of registrations must be a power of two greater than 1.
+    /// The proof is serialized as | number of registrations | STARK proof |
+    pub fn get_signature_sequence_proof(&self) -> Result<Vec<u8>, RegistarError> {
+        let num_regs = self.voting_keys.len();
+        if num_regs < 2 || !num_regs.is_power_of_two() {
+            return Err(RegistarError::InvalidSubset);
+        }
+
+        let prover = SchnorrSequenceProver::new(
+            build_options_with_fri(self.extension, self.fri),
+            &self.voting_keys,
+            &self.addresses,
+            &self.signatures,
+        )
+        .with_domain(self.domain);
+        let trace = prover.build_trace();
+        let proof = prover.prove(trace).map_err(RegistarError::Prover)?;
+
+        let mut serialized_proof = vec![];
+        serialized_proof.write_u32(num_regs as u32);
+        serialized_proof.write_u8_slice(&proof.to_bytes());
         Ok(serialized_proof)
     }
 
+    /// Prepare the Merkle hash messages and the Schnorr signature information
+    /// of the registrations at the given indices in a single pass
+    fn prepare_register_inputs(
+        &self,
+        indices: &[usize],
+    ) -> (
+        Vec<[BaseElement; (TREE_DEPTH + 1) * DIGEST_SIZE]>,
+        Vec<SigInfo>,
+    ) {
+        let prepare = |&i: &usize| {
+            (
+                prepare_grouped_hash_message(
+                    &self.voting_keys[i],
+                    self.group_of(i),
+                    &self.merkle_branch(i),
+                ),
+                build_sig_info(
+                    &self.voting_keys[i],
+                    self.addresses[i],
+                    &self.signatures[i],
+                    &self.domain,
+                ),
+            )
+        };
+
+        #[cfg(not(feature = "concurrent"))]
+        let inputs = indices.iter().map(prepare).collect::<Vec<_>>();
+
+        #[cfg(feature = "concurrent")]
+        let inputs = indices.par_iter().map(prepare).collect::<Vec<_>>();
+
+        inputs.into_iter().unzip()
+    }
+
     /// Randomly generate an object of type Self
     #[cfg(test)]
     pub fn get_example(num_regs: usize) -> Self {
-        use crate::{merkle::build_merkle_tree_from, schnorr::SchnorrExample};
+        use super::build_options;
+        use crate::{merkle::build_merkle_tree_with_keys, schnorr::SchnorrExample};
 
         assert!(
             num_regs > 1,
@@ -285,30 +846,39 @@ impl VoterRegistar {
         );
 
         let schnorr = SchnorrExample::new(build_options(1), num_regs);
-        let (elg_root, merkle_branches, hash_indices) =
-            build_merkle_tree_from(&schnorr.voting_keys);
+        let (tree, hash_indices) = build_merkle_tree_with_keys(&schnorr.voting_keys);
 
         Self {
-            elg_root,
+            elg_root: tree.root(),
             num_elg_voters: num_regs,
             voting_keys: schnorr.voting_keys,
-            merkle_branches,
+            tree,
             hash_indices,
             signatures: schnorr.signatures,
             addresses: schnorr.addresses,
+            require_ownership: false,
+            ownership_signatures: vec![None; num_regs],
+            manifest_digest: None,
+            domain: SigningDomain::default(),
+            key_order: KeyOrder::Submission,
+            leaf_binding: false,
+            leaf_groups: Vec::new(),
+            extension: 1,
+            fri: FriConfig::DEFAULT,
+            rejections: RejectionLog::new(),
             dirty_flag: true,
             serialized_proof: vec![],
+            last_proof: None,
         }
     }
 
     /// Proof generation with fault in public inputs
     #[cfg(test)]
-    pub fn get_register_proof_wrong_pub_inputs(&mut self) -> Result<Vec<u8>, ProverError> {
+    pub fn get_register_proof_wrong_pub_inputs(&mut self) -> Result<Vec<u8>, RegistarError> {
         use rand_core::{OsRng, RngCore};
 
         let mut serialized_proof = self.get_register_proof()?;
-        let pub_inputs_nbytes =
-            self.voting_keys.len() * (BYTES_PER_AFFINE + BYTES_PER_ADDRESS + BYTES_PER_SIGNATURE);
+        let pub_inputs_nbytes = CompactPublicInputs::serialized_len(self.voting_keys.len()) - 4;
         let fault_position = 4 + ((OsRng.next_u32() as usize) % pub_inputs_nbytes);
         serialized_proof[fault_position] ^= 1;
 
@@ -317,12 +887,11 @@ impl VoterRegistar {
 
     /// Proof generation with fault in STARK proofs
     #[cfg(test)]
-    pub fn get_register_proof_wrong_stark_proof(&mut self) -> Result<Vec<u8>, ProverError> {
+    pub fn get_register_proof_wrong_stark_proof(&mut self) -> Result<Vec<u8>, RegistarError> {
         use rand_core::{OsRng, RngCore};
 
         let mut serialized_proof = self.get_register_proof()?;
-        let pub_inputs_nbytes =
-            self.voting_keys.len() * (BYTES_PER_AFFINE + BYTES_PER_ADDRESS + BYTES_PER_SIGNATURE);
+        let pub_inputs_nbytes = CompactPublicInputs::serialized_len(self.voting_keys.len()) - 4;
         let proof_nbytes = serialized_proof.len() - 4 - pub_inputs_nbytes;
         let fault_position = 4 + pub_inputs_nbytes + ((OsRng.next_u32() as usize) % proof_nbytes);
         serialized_proof[fault_position] ^= 1;
@@ -332,73 +901,164 @@ impl VoterRegistar {
 }
 
 impl Serializable for VoterRegistar {
+    /// Merkle branches and indices are not written, as they are derived
+    /// from the Merkle tree of eligible voters on deserialization
     fn write_into<W: ByteWriter>(&self, target: &mut W) {
-        target.write_u32(self.num_elg_voters as u32);
+        wire::write_header(target);
+        wire::write_varint(target, self.num_elg_voters as u64);
         Serializable::write_batch_into(&self.elg_root, target);
-        target.write_u32(self.voting_keys.len() as u32);
+        wire::write_varint(target, self.voting_keys.len() as u64);
         for i in 0..self.voting_keys.len() {
-            Serializable::write_batch_into(&self.voting_keys[i], target);
-            Serializable::write_batch_into(&self.merkle_branches[i], target);
-            target.write_u64(self.hash_indices[i] as u64);
+            write_compressed_points(&self.voting_keys[i], target);
             Serializable::write_batch_into(&self.signatures[i].0, target);
             target.write(self.signatures[i].1);
             target.write_u8_slice(&self.addresses[i].as_bytes());
         }
+        // trailing section, absent from objects serialized before ownership checks
+        target.write_u8(self.require_ownership as u8);
+        for ownership_signature in self.ownership_signatures.iter() {
+            write_ownership_signature(ownership_signature, target);
+        }
     }
 }
 
-impl Deserializable for VoterRegistar {
-    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
-        let num_elg_voters = source.read_u32()? as usize;
+impl VoterRegistar {
+    /// Read an object of type Self from a source, given the Merkle tree of
+    /// eligible voters. Objects serialized with Merkle branches and indices,
+    /// i.e. legacy and version 1 objects, are still accepted.
+    pub fn read_from<R: ByteReader>(
+        source: &mut R,
+        tree: MerkleTree,
+    ) -> Result<Self, DeserializationError> {
+        Self::read_from_grouped(source, tree, Vec::new())
+    }
+
+    /// Read an object of type Self from a source, given the Merkle tree of eligible
+    /// voters and the group label of each of its leaves, see bind_groups
+    pub fn read_from_grouped<R: ByteReader>(
+        source: &mut R,
+        tree: MerkleTree,
+        leaf_groups: Vec<u32>,
+    ) -> Result<Self, DeserializationError> {
+        if !leaf_groups.is_empty() && leaf_groups.len() != tree.num_leaves() {
+            return Err(DeserializationError::InvalidValue(String::from(
+                "Number of groups does not match the number of leaves.",
+            )));
+        }
+        let header = wire::read_header(source)?;
+        let format = header.format();
+        let has_branches = !matches!(header, wire::WireHeader::Compact(v) if v >= 2);
+        let num_elg_voters = format.read_first_count(header, source)?;
         let mut elg_root = [BaseElement::ZERO; DIGEST_SIZE];
         elg_root.copy_from_slice(&BaseElement::read_batch_from(source, DIGEST_SIZE)?);
+        if elg_root != tree.root() {
+            return Err(DeserializationError::InvalidValue(String::from(
+                "Merkle tree does not match the root of eligible voters.",
+            )));
+        }
 
-        let num_regs = source.read_u32()? as usize;
+        let num_regs = format.read_count(source)?;
         let mut voting_keys = Vec::with_capacity(num_regs);
-        let mut merkle_branches = Vec::with_capacity(num_regs);
         let mut hash_indices = Vec::with_capacity(num_regs);
         let mut signatures = Vec::with_capacity(num_regs);
-        let mut messages = Vec::with_capacity(num_regs);
         let mut addresses = Vec::with_capacity(num_regs);
 
-        let mut voting_key = [BaseElement::ZERO; AFFINE_POINT_WIDTH];
-        let mut merkle_branch = [BaseElement::ZERO; TREE_DEPTH * DIGEST_SIZE];
         let mut signature_r = [BaseElement::ZERO; POINT_COORDINATE_WIDTH];
-        let mut message = [BaseElement::ZERO; MSG_LENGTH];
 
         for _ in 0..num_regs {
-            voting_key.copy_from_slice(&BaseElement::read_batch_from(source, AFFINE_POINT_WIDTH)?);
-            merkle_branch.copy_from_slice(&BaseElement::read_batch_from(
-                source,
-                TREE_DEPTH * DIGEST_SIZE,
-            )?);
-            let hash_index = source.read_u64()? as usize;
+            let voting_key = read_compressed_point(source)?;
+            if has_branches {
+                // branches and indices are derived from the tree instead
+                BaseElement::read_batch_from(source, TREE_DEPTH * DIGEST_SIZE)?;
+                format.read_index(source)?;
+            }
+            let hash_index = if leaf_groups.is_empty() {
+                tree.find(&voting_key)
+            } else {
+                tree.find_grouped(&voting_key, &leaf_groups)
+            };
+            let hash_index = hash_index.ok_or_else(|| {
+                DeserializationError::InvalidValue(String::from(
+                    "Voting key is not in the Merkle tree of eligible voters.",
+                ))
+            })?;
             signature_r.copy_from_slice(&BaseElement::read_batch_from(
                 source,
                 POINT_COORDINATE_WIDTH,
             )?);
-            let signature_s = Scalar::read_from(source)?;
-            message.copy_from_slice(&BaseElement::read_batch_from(source, MSG_LENGTH)?);
+            let signature_s = read_canonical_scalar(source)?;
             let address = Address::from_slice(&source.read_u8_vec(Address::len_bytes())?);
 
             voting_keys.push(voting_key);
-            merkle_branches.push(merkle_branch);
             hash_indices.push(hash_index);
             signatures.push((signature_r, signature_s));
-            messages.push(message);
             addresses.push(address);
         }
 
+        let mut require_ownership = false;
+        let mut ownership_signatures = vec![None; num_regs];
+        if source.has_more_bytes() {
+            require_ownership = source.read_u8()? != 0;
+            for ownership_signature in ownership_signatures.iter_mut() {
+                *ownership_signature = read_ownership_signature(source)?;
+            }
+        }
+
         Ok(Self {
             elg_root,
             num_elg_voters,
             voting_keys,
-            merkle_branches,
+            tree,
             hash_indices,
             signatures,
             addresses,
+            require_ownership,
+            ownership_signatures,
+            manifest_digest: None,
+            domain: SigningDomain::default(),
+            key_order: KeyOrder::Submission,
+            leaf_binding: false,
+            leaf_groups,
+            extension: 1,
+            fri: FriConfig::DEFAULT,
+            rejections: RejectionLog::new(),
             dirty_flag: num_regs > 0,
             serialized_proof: vec![],
+            last_proof: None,
         })
     }
 }
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Write an optional ownership signature, preceded by a presence flag
+fn write_ownership_signature<W: ByteWriter>(
+    signature: &Option<[u8; BYTES_PER_OWNERSHIP_SIGNATURE]>,
+    target: &mut W,
+) {
+    match signature {
+        Some(signature) => {
+            target.write_u8(1);
+            target.write_u8_slice(signature);
+        }
+        None => target.write_u8(0),
+    }
+}
+
+/// Read an optional ownership signature, preceded by a presence flag
+fn read_ownership_signature<R: ByteReader>(
+    source: &mut R,
+) -> Result<Option<[u8; BYTES_PER_OWNERSHIP_SIGNATURE]>, DeserializationError> {
+    match source.read_u8()? {
+        0 => Ok(None),
+        1 => {
+            let mut signature = [0u8; BYTES_PER_OWNERSHIP_SIGNATURE];
+            signature.copy_from_slice(&source.read_u8_vec(BYTES_PER_OWNERSHIP_SIGNATURE)?);
+            Ok(Some(signature))
+        }
+        _ => Err(DeserializationError::InvalidValue(String::from(
+            "Invalid ownership signature flag.",
+        ))),
+    }
+}
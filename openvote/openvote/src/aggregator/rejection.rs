@@ -0,0 +1,199 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::{cast::CollectorError, register::RegistarError};
+use crate::utils::address::Address;
+use std::time::{SystemTime, UNIX_EPOCH};
+use winterfell::{
+    crypto::{hashers::Blake3_256, Digest, Hasher},
+    math::fields::f63::BaseElement,
+};
+
+/// Number of bytes of the digest of a rejected payload
+pub const BYTES_PER_PAYLOAD_DIGEST: usize = 32;
+
+// REJECTIONS
+// ================================================================================================
+
+/// Submitter of a rejected submission
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Submitter {
+    /// Ethereum address of a voter submitting a registration
+    Address(Address),
+    /// Index of a registered voter submitting an encrypted vote or a commitment
+    Voter(usize),
+}
+
+/// Reason why a submission was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectionReason {
+    /// The submission was rejected by the admission policy before being verified
+    Admission,
+    /// The Ethereum address is registered under another voting key
+    DuplicatedEthAddress,
+    /// The Merkle proof of membership is invalid
+    InvalidMerkleProof,
+    /// The Schnorr signature is invalid
+    InvalidSchnorrSig,
+    /// The voting key is not signed by the key of the Ethereum address
+    InvalidOwnershipSig,
+    /// All eligible voters have already registered
+    TooManyRegistrations,
+    /// The one-time credential was already redeemed
+    CredentialRedeemed,
+    /// The registration replaces the one of the same voter without being chained to it
+    UnchainedReplacement,
+    /// The encrypted vote is invalid, or the voter has already cast a vote
+    InvalidEncryptedVote,
+    /// The commitment is submitted out of the commit phase, or twice
+    InvalidCommitment,
+    /// The revealed encrypted vote does not match the commitment of the voter
+    InvalidReveal,
+}
+
+impl RejectionReason {
+    /// Reason of a registration rejected with the given error, if the error
+    /// is raised on a submission rather than when proving
+    pub fn from_registar_error(error: &RegistarError) -> Option<Self> {
+        match error {
+            RegistarError::DuplicatedEthAddress => Some(Self::DuplicatedEthAddress),
+            RegistarError::InvalidMerkleProof => Some(Self::InvalidMerkleProof),
+            RegistarError::InvalidSchnorrSig => Some(Self::InvalidSchnorrSig),
+            RegistarError::InvalidOwnershipSig => Some(Self::InvalidOwnershipSig),
+            RegistarError::TooManyRegistrations => Some(Self::TooManyRegistrations),
+            RegistarError::Rejected => Some(Self::Admission),
+            RegistarError::CredentialRedeemed => Some(Self::CredentialRedeemed),
+            RegistarError::UnchainedReplacement => Some(Self::UnchainedReplacement),
+            RegistarError::InvalidSubset | RegistarError::Prover(_) => None,
+        }
+    }
+
+    /// Reason of an encrypted vote or a commitment rejected with the given error,
+    /// if the error is raised on a submission. Busy is not a rejection, as the
+    /// submission should be sent again later.
+    pub fn from_collector_error(error: &CollectorError) -> Option<Self> {
+        match error {
+            CollectorError::InvalidEncryptedVote => Some(Self::InvalidEncryptedVote),
+            CollectorError::Rejected => Some(Self::Admission),
+            CollectorError::InvalidCommitment => Some(Self::InvalidCommitment),
+            CollectorError::InvalidReveal => Some(Self::InvalidReveal),
+            _ => None,
+        }
+    }
+}
+
+/// Record of a rejected submission
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rejection {
+    /// Submitter of the submission
+    pub submitter: Submitter,
+    /// Reason why the submission was rejected
+    pub reason: RejectionReason,
+    /// Time of the rejection, in seconds since the Unix epoch
+    pub timestamp: u64,
+    /// Blake3 digest of the serialized submission, see payload_digest
+    pub payload_digest: [u8; BYTES_PER_PAYLOAD_DIGEST],
+}
+
+// REJECTION LOG
+// ================================================================================================
+
+/// Log of the submissions rejected by an aggregator, in the order they were rejected.
+/// Voters can look up their submission by the digest of its payload to find out why it
+/// was dropped, so that disputes can be adjudicated. The log is not serialized.
+#[derive(Debug, Default, Clone)]
+pub struct RejectionLog {
+    rejections: Vec<Rejection>,
+}
+
+impl RejectionLog {
+    /// Create an empty log
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the rejection of the given serialized submission at the current time
+    pub fn record(&mut self, submitter: Submitter, reason: RejectionReason, payload: &[u8]) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        self.record_at(submitter, reason, payload, timestamp);
+    }
+
+    /// Record the rejection of the given serialized submission at the given time,
+    /// in seconds since the Unix epoch
+    pub fn record_at(
+        &mut self,
+        submitter: Submitter,
+        reason: RejectionReason,
+        payload: &[u8],
+        timestamp: u64,
+    ) {
+        self.rejections.push(Rejection {
+            submitter,
+            reason,
+            timestamp,
+            payload_digest: payload_digest(payload),
+        });
+    }
+
+    /// Number of recorded rejections
+    pub fn len(&self) -> usize {
+        self.rejections.len()
+    }
+
+    /// Return true if no rejection was recorded
+    pub fn is_empty(&self) -> bool {
+        self.rejections.is_empty()
+    }
+
+    /// All recorded rejections, in the order they were recorded
+    pub fn rejections(&self) -> &[Rejection] {
+        &self.rejections
+    }
+
+    /// Rejections of the submissions of the given submitter
+    pub fn by_submitter(&self, submitter: Submitter) -> impl Iterator<Item = &Rejection> + '_ {
+        self.rejections
+            .iter()
+            .filter(move |rejection| rejection.submitter == submitter)
+    }
+
+    /// Rejections of the submission with the given payload digest. The same
+    /// payload may be rejected several times if it is submitted again.
+    pub fn by_payload_digest<'a>(
+        &'a self,
+        payload_digest: &'a [u8; BYTES_PER_PAYLOAD_DIGEST],
+    ) -> impl Iterator<Item = &'a Rejection> + 'a {
+        self.rejections
+            .iter()
+            .filter(move |rejection| &rejection.payload_digest == payload_digest)
+    }
+
+    /// Rejections recorded between start and end included,
+    /// in seconds since the Unix epoch
+    pub fn between(&self, start: u64, end: u64) -> impl Iterator<Item = &Rejection> + '_ {
+        self.rejections
+            .iter()
+            .filter(move |rejection| (start..=end).contains(&rejection.timestamp))
+    }
+
+    /// Remove all recorded rejections
+    pub fn clear(&mut self) {
+        self.rejections.clear();
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Digest under which a serialized submission is recorded, i.e. the Blake3 hash of the
+/// serialized registration or encrypted vote, or of the commitment bytes
+pub fn payload_digest(payload: &[u8]) -> [u8; BYTES_PER_PAYLOAD_DIGEST] {
+    Blake3_256::<BaseElement>::hash(payload).as_bytes()
+}
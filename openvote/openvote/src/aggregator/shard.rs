@@ -0,0 +1,61 @@
+use super::cast::{CollectorError, VoteCollector};
+use crate::{
+    cds::ShardInfo,
+    utils::{
+        ecc::projective_to_elements,
+        wire::{write_header, write_varint},
+    },
+};
+use winterfell::ByteWriter;
+
+impl VoteCollector {
+    /// Generate the cast proof for the shard of num_voters consecutive voters
+    /// starting at first_voter_index. The shard starts from the blinding key
+    /// of its first voter in the global chain, so that shard proofs generated
+    /// by different aggregators can be stitched together.
+    ///
+    /// The shard proof is serialized as | varint first voter index | cast proof |
+    pub fn get_cast_proof_shard(
+        &self,
+        first_voter_index: usize,
+        num_voters: usize,
+    ) -> Result<Vec<u8>, CollectorError> {
+        if num_voters < 2
+            || !num_voters.is_power_of_two()
+            || first_voter_index + num_voters > self.voting_keys.len()
+        {
+            return Err(CollectorError::InvalidShard);
+        }
+        let voters = first_voter_index..first_voter_index + num_voters;
+        if voters.clone().any(|voter_index| {
+            self.encrypted_votes[voter_index].is_none()
+                && self.excluded_ballots[voter_index].is_none()
+        }) {
+            return Err(CollectorError::NotEnoughEncryptedVotes);
+        }
+
+        let shard = ShardInfo {
+            first_voter_index,
+            initial_blinding_key: projective_to_elements(self.blinding_keys[first_voter_index]),
+        };
+        let mut serialized_proof = vec![];
+        write_varint(&mut serialized_proof, first_voter_index as u64);
+        serialized_proof.write_u8_slice(&self.prove_voters(voters, Some(shard))?);
+
+        Ok(serialized_proof)
+    }
+}
+
+/// Stitch shard proofs generated by different aggregators into a single
+/// cast proof, serialized as
+/// | wire header | varint num shards | (varint nbytes | shard proof)* |
+pub fn stitch_cast_proofs(shard_proofs: &[Vec<u8>]) -> Vec<u8> {
+    let mut stitched_proof = vec![];
+    write_header(&mut stitched_proof);
+    write_varint(&mut stitched_proof, shard_proofs.len() as u64);
+    for shard_proof in shard_proofs.iter() {
+        write_varint(&mut stitched_proof, shard_proof.len() as u64);
+        stitched_proof.write_u8_slice(shard_proof);
+    }
+    stitched_proof
+}
@@ -0,0 +1,158 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::{cast::VoteCollector, register::VoterRegistar};
+
+#[cfg(feature = "std")]
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+// PROOF RECORDS
+// ================================================================================================
+
+/// Record of the last STARK proof generated by an aggregator object
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofRecord {
+    /// Time at which proving completed, in seconds since the Unix epoch
+    pub timestamp: u64,
+    /// Time spent proving, in milliseconds
+    pub duration_ms: u64,
+    /// Number of voters covered by the proof
+    pub num_voters: usize,
+}
+
+impl ProofRecord {
+    /// Estimated time, in milliseconds, to prove the given number of voters. Proving time is
+    /// extrapolated linearly in the length of the execution trace, i.e. in the number of voters
+    /// padded to a power of two.
+    pub fn estimate_ms(&self, num_voters: usize) -> u64 {
+        let recorded = self.num_voters.max(1).next_power_of_two() as u64;
+        let requested = num_voters.max(1).next_power_of_two() as u64;
+        self.duration_ms.saturating_mul(requested) / recorded
+    }
+}
+
+/// Timer of a proof generation, recording nothing without the standard library
+#[derive(Debug)]
+pub(crate) struct ProofTimer {
+    #[cfg(feature = "std")]
+    start: Instant,
+}
+
+impl ProofTimer {
+    /// Start timing a proof generation
+    pub(crate) fn start() -> Self {
+        Self {
+            #[cfg(feature = "std")]
+            start: Instant::now(),
+        }
+    }
+
+    /// Record of the proof of the given number of voters, which just completed
+    #[cfg(feature = "std")]
+    pub(crate) fn finish(self, num_voters: usize) -> Option<ProofRecord> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Some(ProofRecord {
+            timestamp,
+            duration_ms: self.start.elapsed().as_millis() as u64,
+            num_voters,
+        })
+    }
+
+    /// Record of the proof of the given number of voters, which just completed
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn finish(self, _num_voters: usize) -> Option<ProofRecord> {
+        None
+    }
+}
+
+// STATUS
+// ================================================================================================
+
+/// Status of the registration phase, e.g. for dashboards and readiness probes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegistarStatus {
+    /// Number of eligible voters
+    pub num_elg_voters: usize,
+    /// Number of registered voters
+    pub num_registered: usize,
+    /// Set to true if the cached register proof covers all registrations
+    pub proof_fresh: bool,
+    /// Last register proof generated, if any
+    pub last_proof: Option<ProofRecord>,
+    /// Estimated time, in milliseconds, to prove the current registrations,
+    /// if a proof was generated before
+    pub estimated_proving_ms: Option<u64>,
+}
+
+/// Status of the vote casting phase, e.g. for dashboards and readiness probes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollectorStatus {
+    /// Number of registered voters
+    pub num_voters: usize,
+    /// Number of voters who submitted a valid encrypted vote
+    pub num_voted: usize,
+    /// Number of voters whose invalid ballot is reported in the cast proof
+    pub num_excluded: usize,
+    /// Number of encrypted votes waiting for verification in the verification pool
+    pub pending_verifications: usize,
+    /// Set to true if a cast proof of all ballots is cached
+    pub proof_fresh: bool,
+    /// Last cast proof generated, if any
+    pub last_proof: Option<ProofRecord>,
+    /// Estimated time, in milliseconds, to prove all ballots,
+    /// if a proof was generated before
+    pub estimated_proving_ms: Option<u64>,
+}
+
+impl CollectorStatus {
+    /// Return true if every voter has submitted a ballot,
+    /// so that the cast proof can be generated
+    pub fn is_complete(&self) -> bool {
+        self.num_voted + self.num_excluded == self.num_voters
+    }
+}
+
+impl VoterRegistar {
+    /// Status of the registration phase
+    pub fn status(&self) -> RegistarStatus {
+        let num_registered = self.voting_keys.len();
+        RegistarStatus {
+            num_elg_voters: self.num_elg_voters,
+            num_registered,
+            proof_fresh: !self.dirty_flag && !self.serialized_proof.is_empty(),
+            last_proof: self.last_proof,
+            estimated_proving_ms: self
+                .last_proof
+                .map(|last_proof| last_proof.estimate_ms(num_registered)),
+        }
+    }
+}
+
+impl VoteCollector {
+    /// Status of the vote casting phase
+    pub fn status(&self) -> CollectorStatus {
+        let num_voters = self.voting_keys.len();
+        #[cfg(feature = "std")]
+        let pending_verifications = self.pending_verifications();
+        #[cfg(not(feature = "std"))]
+        let pending_verifications = 0;
+        CollectorStatus {
+            num_voters,
+            num_voted: self.num_valid_votes,
+            num_excluded: self.num_excluded_ballots(),
+            pending_verifications,
+            proof_fresh: !self.serialized_proof.is_empty(),
+            last_proof: self.last_proof,
+            estimated_proving_ms: self
+                .last_proof
+                .map(|last_proof| last_proof.estimate_ms(num_voters)),
+        }
+    }
+}
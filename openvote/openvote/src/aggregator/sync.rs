@@ -0,0 +1,214 @@
+use super::{
+    cast::{CollectorError, VoteCollector},
+    constants::*,
+    publication::PublishedBallot,
+};
+use winterfell::{
+    crypto::{hashers::Blake3_256, Digest, Hasher},
+    math::fields::f63::BaseElement,
+    ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable,
+};
+
+/// Number of bytes of a node in the state tree
+pub const BYTES_PER_STATE_DIGEST: usize = 32;
+
+/// Digest of a node in the state tree
+pub type StateDigest = [u8; BYTES_PER_STATE_DIGEST];
+
+/// Merkle tree built over the ballot slots of a `VoteCollector`.
+/// Leaf i is the digest of the commitment and ballot of voter i, or zero
+/// if the voter has submitted neither yet.
+///
+/// Two replicas first compare their roots, and only descend into
+/// subtrees whose digests differ to find the ballots to transfer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateTree {
+    /// Nodes of the tree, from the leaves (levels[0]) to the root
+    pub levels: Vec<Vec<StateDigest>>,
+}
+
+impl StateTree {
+    /// Root of the state tree
+    pub fn root(&self) -> StateDigest {
+        self.levels[self.levels.len() - 1][0]
+    }
+
+    /// Number of leaves of the state tree
+    pub fn num_leaves(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Return the indices of the leaves that differ between self and other.
+    /// Only subtrees whose roots differ are visited.
+    pub fn diff(&self, other: &Self) -> Vec<usize> {
+        if self.num_leaves() != other.num_leaves() {
+            return (0..self.num_leaves()).collect();
+        }
+        let mut indices = vec![];
+        self.diff_subtree(other, self.levels.len() - 1, 0, &mut indices);
+        indices
+    }
+
+    fn diff_subtree(&self, other: &Self, level: usize, index: usize, indices: &mut Vec<usize>) {
+        if self.levels[level][index] == other.levels[level][index] {
+            return;
+        }
+        if level == 0 {
+            indices.push(index);
+            return;
+        }
+        self.diff_subtree(other, level - 1, 2 * index, indices);
+        self.diff_subtree(other, level - 1, 2 * index + 1, indices);
+    }
+}
+
+/// Ballot slot transferred between two aggregator replicas: the commitment of the voter
+/// during the commit phase, and their ballot, valid or flagged as invalid, once revealed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BallotTransfer {
+    /// Index of the voter
+    pub voter_index: usize,
+    /// Commitment of the voter to their encrypted vote, if any
+    pub commitment: Option<[u8; BYTES_PER_COMMITMENT]>,
+    /// Ballot of the voter, if any
+    pub ballot: Option<PublishedBallot>,
+}
+
+impl Serializable for BallotTransfer {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u32(self.voter_index as u32);
+        target.write_u8(self.commitment.is_some() as u8);
+        if let Some(commitment) = self.commitment {
+            target.write_u8_slice(&commitment);
+        }
+        target.write_u8(self.ballot.is_some() as u8);
+        if let Some(ballot) = self.ballot {
+            ballot.write_into(target);
+        }
+    }
+}
+
+impl Deserializable for BallotTransfer {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let voter_index = source.read_u32()? as usize;
+        let commitment = match source.read_u8()? {
+            0 => None,
+            1 => {
+                let mut commitment = [0u8; BYTES_PER_COMMITMENT];
+                commitment.copy_from_slice(&source.read_u8_vec(BYTES_PER_COMMITMENT)?);
+                Some(commitment)
+            }
+            _ => {
+                return Err(DeserializationError::InvalidValue(String::from(
+                    "Invalid commitment flag.",
+                )))
+            }
+        };
+        let ballot = match source.read_u8()? {
+            0 => None,
+            1 => Some(PublishedBallot::read_from(source)?),
+            _ => {
+                return Err(DeserializationError::InvalidValue(String::from(
+                    "Invalid ballot flag.",
+                )))
+            }
+        };
+        if ballot.map_or(false, |ballot| ballot.voter_index != voter_index) {
+            return Err(DeserializationError::InvalidValue(String::from(
+                "Transferred ballot does not belong to its slot.",
+            )));
+        }
+        Ok(Self {
+            voter_index,
+            commitment,
+            ballot,
+        })
+    }
+}
+
+impl VoteCollector {
+    /// Build the state tree over the ballot slots of self
+    pub fn state_tree(&self) -> StateTree {
+        let mut level = (0..self.voting_keys.len())
+            .map(|i| self.ballot_digest(i))
+            .collect::<Vec<StateDigest>>();
+        let mut levels = vec![];
+
+        while level.len() > 1 {
+            let next_level = level
+                .chunks(2)
+                .map(|pair| merge_digests(&pair[0], &pair[1]))
+                .collect::<Vec<StateDigest>>();
+            levels.push(level);
+            level = next_level;
+        }
+        levels.push(level);
+
+        StateTree { levels }
+    }
+
+    /// Export the commitments and ballots of the given voters that self has received
+    pub fn export_ballots(&self, voter_indices: &[usize]) -> Vec<BallotTransfer> {
+        voter_indices
+            .iter()
+            .filter(|&&i| i < self.voting_keys.len())
+            .filter_map(|&i| {
+                let transfer = BallotTransfer {
+                    voter_index: i,
+                    commitment: self.commitments[i],
+                    ballot: self.published_ballot(i),
+                };
+                if transfer.commitment.is_none() && transfer.ballot.is_none() {
+                    return None;
+                }
+                Some(transfer)
+            })
+            .collect()
+    }
+
+    /// Import commitments and ballots exported by another replica.
+    /// CDS proofs and signatures of imported ballots are verified
+    /// again, since the other replica is not trusted.
+    pub fn import_ballots(
+        &mut self,
+        ballots: &[BallotTransfer],
+    ) -> Vec<Result<(), CollectorError>> {
+        ballots
+            .iter()
+            .map(|transfer| {
+                let voter_index = transfer.voter_index;
+                if voter_index >= self.voting_keys.len() {
+                    return Err(CollectorError::InvalidEncryptedVote);
+                }
+                if let Some(commitment) = transfer.commitment {
+                    if self.commitments[voter_index] != Some(commitment) {
+                        self.add_commitment(voter_index, commitment)?;
+                    }
+                }
+                match transfer.ballot {
+                    Some(ballot) if ballot.voter_index == voter_index => {
+                        self.import_published_ballot(&ballot)
+                    }
+                    Some(_) => Err(CollectorError::InvalidEncryptedVote),
+                    None => Ok(()),
+                }
+            })
+            .collect()
+    }
+
+    /// Digest of the ballot slot of the voter at voter_index
+    fn ballot_digest(&self, voter_index: usize) -> StateDigest {
+        match self.export_ballots(&[voter_index]).first() {
+            Some(ballot) => Blake3_256::<BaseElement>::hash(&ballot.to_bytes()).as_bytes(),
+            None => [0u8; BYTES_PER_STATE_DIGEST],
+        }
+    }
+}
+
+#[inline]
+fn merge_digests(left: &StateDigest, right: &StateDigest) -> StateDigest {
+    let mut message = [0u8; 2 * BYTES_PER_STATE_DIGEST];
+    message[..BYTES_PER_STATE_DIGEST].copy_from_slice(left);
+    message[BYTES_PER_STATE_DIGEST..].copy_from_slice(right);
+    Blake3_256::<BaseElement>::hash(&message).as_bytes()
+}
@@ -1,13 +1,19 @@
-use winterfell::{
-    math::{
-        curves::curve_f63::{AffinePoint, ProjectivePoint, Scalar},
-        fields::f63::BaseElement,
-        FieldElement,
+use crate::{
+    backend::{ElectionProver, TallyBackend},
+    chain::{tally_commitment, PhaseCommitment},
+    tally::self_tally,
+    utils::{
+        ecc::{read_compressed_point, write_compressed_points},
+        wire,
     },
-    ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable, SliceReader,
+    verifier::encode_encrypted_votes,
+};
+use winterfell::{
+    math::fields::f63::BaseElement, ByteReader, ByteWriter, Deserializable, DeserializationError,
+    ProverError, Serializable, SliceReader,
 };
 
-use super::constants::*;
+use super::{build_options, constants::*};
 
 /// Errors raised by VoteTallier
 #[derive(Debug, PartialEq)]
@@ -15,6 +21,14 @@ pub enum TallierError {
     /// Error occurs when the tally result cannot be found when
     /// looking through all possible solutions
     InvalidTallyResult,
+    /// Error occurs when the number of encrypted votes is not a power
+    /// of two, as required to prove the tally result
+    InvalidNumberOfVotes,
+    /// Error occurs when the STARK prover fails
+    Prover(ProverError),
+    /// Error occurs when the number of registered voters cannot
+    /// be read from the register proof
+    InvalidRegisterProof,
 }
 
 /// Type that encapsulates all data and functionalities of
@@ -25,6 +39,11 @@ pub struct VoteTallier {
     pub encrypted_votes: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
     /// Number of "yes" votes
     pub tally_result: Option<u32>,
+    /// Commitment to the vote casting phase the tally proof is chained to, if any.
+    /// It is not serialized and must be bound again after restoring the tallier.
+    pub ballot_commitment: Option<PhaseCommitment>,
+    /// Field extension of the STARK proofs, see build_options. It is not serialized.
+    pub extension: u8,
 }
 
 impl VoteTallier {
@@ -33,6 +52,8 @@ impl VoteTallier {
         Self {
             encrypted_votes,
             tally_result: None,
+            ballot_commitment: None,
+            extension: 1,
         }
     }
 
@@ -47,43 +68,78 @@ impl VoteTallier {
         target.write(&self.to_bytes())
     }
 
+    /// Serialize the encrypted votes in the format stored on smart contract,
+    /// against which the tally result is verified
+    pub fn encrypted_votes_bytes(&self) -> Vec<u8> {
+        encode_encrypted_votes(&self.encrypted_votes)
+    }
+
     /// Calculate tally result
     pub fn tally_votes(&mut self) -> Result<u32, TallierError> {
         if self.tally_result.is_some() {
             return Ok(self.tally_result.unwrap());
         }
 
-        let num_votes = self.encrypted_votes.len() as u32;
-        let mut yes_sum = ProjectivePoint::generator() * Scalar::from(num_votes);
-        for &encrypted_vote in self.encrypted_votes.iter() {
-            yes_sum += AffinePoint::from_raw_coordinates(encrypted_vote);
-        }
-        yes_sum *= Scalar::from(2u32).invert();
-        let mut tmp = ProjectivePoint::identity();
-        let mut tally_result = 0u32;
+        let tally_result =
+            self_tally(&self.encrypted_votes).ok_or(TallierError::InvalidTallyResult)?;
+        self.tally_result = Some(tally_result);
+        Ok(tally_result)
+    }
 
-        while tmp != yes_sum && tally_result <= num_votes {
-            tmp += AffinePoint::generator();
-            tally_result += 1;
-        }
+    /// Chain subsequent tally proofs to the commitment to the vote
+    /// casting phase, see VoteCollector::phase_commitment
+    pub fn bind_ballot_commitment(&mut self, ballot_commitment: PhaseCommitment) {
+        self.ballot_commitment = Some(ballot_commitment);
+    }
+
+    /// Generate subsequent tally proofs with the given field extension, see build_options
+    pub fn set_extension(&mut self, extension: u8) {
+        self.extension = extension;
+    }
 
-        if tally_result > num_votes {
-            Err(TallierError::InvalidTallyResult)
-        } else {
-            self.tally_result = Some(tally_result);
-            Ok(tally_result)
+    /// Generate a STARK proof that the tally result is the number of "yes" votes of the
+    /// encrypted votes, chained to the ballot commitment if bound. It is verified with
+    /// verify_tally_proof against the encrypted votes stored on smart contract.
+    pub fn get_tally_proof(&mut self) -> Result<Vec<u8>, TallierError> {
+        let num_votes = self.encrypted_votes.len();
+        if num_votes < 2 || !num_votes.is_power_of_two() {
+            return Err(TallierError::InvalidNumberOfVotes);
         }
+        let tally_result = self.tally_votes()?;
+
+        let prover = TallyBackend::new(
+            build_options(self.extension),
+            &self.encrypted_votes,
+            tally_result,
+        );
+        let prover = match self.ballot_commitment {
+            Some(ballot_commitment) => prover.with_ballot_commitment(ballot_commitment),
+            None => prover,
+        };
+        let (_, proof) = prover.prove_phase(()).map_err(TallierError::Prover)?;
+        Ok(proof.to_bytes())
+    }
+
+    /// Commitment to the tally phase, closing the chain of phase commitments.
+    /// Return None if the tally proof is not chained or the votes are not tallied yet.
+    pub fn phase_commitment(&self) -> Option<PhaseCommitment> {
+        Some(tally_commitment(
+            &self.ballot_commitment?,
+            self.tally_result?,
+        ))
     }
 
     #[cfg(test)]
     pub fn get_example(num_votes: usize) -> (Self, u32) {
-        use crate::{aggregator::build_options, tally::TallyExample};
+        use crate::tally::TallyExample;
 
         let example = TallyExample::new(build_options(1), num_votes);
         (
             Self {
                 encrypted_votes: example.encrypted_votes,
                 tally_result: None,
+                ballot_commitment: None,
+                extension: 1,
             },
             example.tally_result,
         )
@@ -108,29 +164,29 @@ impl VoteTallier {
 
 impl Serializable for VoteTallier {
     fn write_into<W: ByteWriter>(&self, target: &mut W) {
-        target.write_u32(self.encrypted_votes.len() as u32);
+        wire::write_header(target);
+        wire::write_varint(target, self.encrypted_votes.len() as u64);
         for encrypted_vote in self.encrypted_votes.iter() {
-            Serializable::write_batch_into(encrypted_vote, target);
+            write_compressed_points(encrypted_vote, target);
         }
     }
 }
 
 impl Deserializable for VoteTallier {
     fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
-        let mut encrypted_vote = [BaseElement::ZERO; AFFINE_POINT_WIDTH];
-
-        let num_votes = source.read_u32()? as usize;
+        let header = wire::read_header(source)?;
+        let num_votes = header.format().read_first_count(header, source)?;
         let mut encrypted_votes = Vec::with_capacity(num_votes);
 
         for _ in 0..num_votes {
-            encrypted_vote
-                .copy_from_slice(&BaseElement::read_batch_from(source, AFFINE_POINT_WIDTH)?);
-            encrypted_votes.push(encrypted_vote);
+            encrypted_votes.push(read_compressed_point(source)?);
         }
 
         Ok(Self {
             encrypted_votes,
             tally_result: None,
+            ballot_commitment: None,
+            extension: 1,
         })
     }
 }
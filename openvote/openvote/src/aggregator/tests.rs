@@ -1,10 +1,64 @@
 use crate::{
-    aggregator::cast::VoteCollector,
-    verifier::{verify_cast_proof, verify_register_proof, verify_tally_result},
+    aggregator::{
+        admission::{Allowlist, ProofOfWork, Submission, SubmissionQuota},
+        batch::CastQueue,
+        build_options,
+        cast::{
+            CdsVerificationError, CollectorError, EncryptedVote, VoteCollector, SEALING_KEY_SIZE,
+        },
+        certificate::{verify_certificate, PolicyEvaluation, ResultCertificate},
+        credential::{Credential, CredentialBatch},
+        ownership::BYTES_PER_OWNERSHIP_SIGNATURE,
+        shard::stitch_cast_proofs,
+    },
+    beacon::{BeaconError, BlockhashBeacon},
+    cds::{
+        compute_vote_commitment, concat_proof_points,
+        constants::{BYTES_PER_COMMITMENT, PROOF_NUM_POINTS, PROOF_NUM_SCALARS, UNBOUND_CONTEXT},
+        format_tracker, signed_ballot_digest, CDSExample,
+    },
+    compat::curve::{ProjectivePoint, Scalar},
+    manifest::{ElectionManifest, PolicyOutcome, ResultPolicy, Schedule, SIMPLE_MAJORITY},
+    merkle::{build_merkle_tree_with_rng, constants::TREE_DEPTH, MerkleTree},
+    planner::FriConfig,
+    schnorr::{random_key_pair_with_rng, sign_digest, PublicInputs as SchnorrPublicInputs},
+    testing::strategies::{address, element, point, scalar, signature},
+    utils::{
+        ecc::{compress_point, decompress_point, projective_to_elements, AFFINE_POINT_WIDTH},
+        ordering::KeyOrder,
+        rescue::{digest_bytes, DIGEST_SIZE},
+        wire::{self, read_varint, varint_len, write_varint},
+    },
+    verifier::{
+        constants::{
+            BYTES_PER_CAST_PUB_INPUTS, BYTES_PER_DIGEST, BYTES_PER_SCALAR, BYTES_PER_SIGNATURE,
+        },
+        estimate,
+        policy::ExtensionPolicy,
+        read_elg_root, read_groups, read_leaf_indices,
+        report::ProofError,
+        verify_cast_proof, verify_cast_proof_with, verify_cast_proofs_sharded, verify_election,
+        verify_election_chained, verify_election_report, verify_register_proof,
+        verify_register_proof_with, verify_signature_sequence_proof,
+        verify_signature_sequence_proof_with_policy, verify_tally_proof,
+        verify_tally_proof_chained, verify_tally_result, CastVerification, ProofKind,
+        RegisterVerification,
+    },
+};
+use proptest::{collection::vec, prelude::*};
+use rand_chacha::ChaCha20Rng;
+use rand_core::{OsRng, SeedableRng};
+use winterfell::{
+    math::{fields::f63::BaseElement, FieldElement},
+    ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable, SliceReader,
 };
-use winterfell::{ByteWriter, Serializable};
 
-use super::{register::*, tally::VoteTallier, AggregatorExample};
+use super::{
+    breakdown::{GroupCount, GroupTallier},
+    register::*,
+    tally::{TallierError, VoteTallier},
+    AggregatorExample,
+};
 
 #[test]
 fn register_test_all_valid() {
@@ -20,6 +74,183 @@ fn register_test_all_valid() {
     assert!(verified.unwrap(), "STARK proofs should be valid.")
 }
 
+#[test]
+fn register_test_extension_policy() {
+    let mut registar = VoterRegistar::get_example(2);
+    let sequence_digest = registar.sequence_digest();
+
+    for extension in 1..=3u8 {
+        registar.set_extension(extension);
+        let register_proof = registar.get_register_proof().unwrap();
+        let sequence_proof = registar.get_signature_sequence_proof().unwrap();
+        for min_degree in 1..=3 {
+            let policy = ExtensionPolicy::at_least(min_degree);
+            let expected = extension as u32 >= min_degree;
+            assert_eq!(
+                verify_register_proof_with(
+                    &registar.elg_root,
+                    &RegisterVerification::new().with_policy(policy),
+                    &register_proof
+                )
+                .into_outcome(),
+                Ok(expected),
+                "Register proof over extension {} with policy {:?}",
+                extension,
+                policy
+            );
+            assert_eq!(
+                verify_signature_sequence_proof_with_policy(
+                    &sequence_digest,
+                    policy,
+                    &sequence_proof
+                ),
+                Ok(expected),
+                "Sequence proof over extension {} with policy {:?}",
+                extension,
+                policy
+            );
+        }
+    }
+}
+
+#[test]
+fn register_test_report() {
+    let mut registar = VoterRegistar::get_example(2);
+    let options = RegisterVerification::new();
+
+    let register_proof = registar.get_register_proof().unwrap();
+    let report = verify_register_proof_with(&registar.elg_root, &options, &register_proof);
+    assert!(report.is_valid());
+
+    let report = verify_register_proof_with(
+        &registar.elg_root,
+        &options.clone().with_policy(ExtensionPolicy::at_least(2)),
+        &register_proof,
+    );
+    assert_eq!(report.public_inputs, Ok(()));
+    assert_eq!(report.stark_proof, Some(Err(ProofError::Extension(1))));
+
+    let register_proof = registar.get_register_proof_wrong_stark_proof().unwrap();
+    let report = verify_register_proof_with(&registar.elg_root, &options, &register_proof);
+    assert_eq!(report.public_inputs, Ok(()));
+    assert!(matches!(report.stark_proof, Some(Err(_))));
+}
+
+#[test]
+fn register_test_fri_policy() {
+    let mut registar = VoterRegistar::get_example(2);
+    let mut elg_root_bytes = vec![];
+    Serializable::write_batch_into(&registar.elg_root, &mut elg_root_bytes);
+
+    let fri = FriConfig::new(16, 32).unwrap();
+    let default_key = registar.proof_key();
+    registar.set_fri(fri);
+    assert!(registar.dirty_flag);
+    assert_ne!(registar.proof_key(), default_key);
+    let register_proof = registar.get_register_proof().unwrap();
+    assert_eq!(
+        verify_register_proof(&elg_root_bytes, &register_proof),
+        Ok(true)
+    );
+
+    let options = RegisterVerification::new().with_policy(ExtensionPolicy::ANY.with_fri(fri));
+    let report = verify_register_proof_with(&registar.elg_root, &options, &register_proof);
+    assert!(report.is_valid());
+    let options = options.with_policy(ExtensionPolicy::ANY.with_fri(FriConfig::DEFAULT));
+    let report = verify_register_proof_with(&registar.elg_root, &options, &register_proof);
+    assert_eq!(report.stark_proof, Some(Err(ProofError::Fri(fri))));
+}
+
+#[test]
+fn register_test_typed_root() {
+    let mut registar = VoterRegistar::get_example(2);
+    let register_proof = registar.get_register_proof().unwrap();
+    let mut elg_root_bytes = vec![];
+    Serializable::write_batch_into(&registar.elg_root, &mut elg_root_bytes);
+    assert_eq!(read_elg_root(&elg_root_bytes), Ok(registar.elg_root));
+    let options = RegisterVerification::new();
+    assert_eq!(
+        verify_register_proof_with(&registar.elg_root, &options, &register_proof).into_outcome(),
+        Ok(true)
+    );
+
+    let mut other_root = registar.elg_root;
+    other_root[0] += BaseElement::ONE;
+    assert_eq!(
+        verify_register_proof_with(&other_root, &options, &register_proof).into_outcome(),
+        Ok(false)
+    );
+}
+
+#[test]
+fn register_test_malformed_root() {
+    let mut registar = VoterRegistar::get_example(2);
+    let register_proof = registar.get_register_proof().unwrap();
+    let mut elg_root_bytes = vec![];
+    Serializable::write_batch_into(&registar.elg_root, &mut elg_root_bytes);
+
+    // a truncated root must not borrow its missing bytes from the proof
+    let truncated = &elg_root_bytes[..BYTES_PER_DIGEST - 1];
+    assert_eq!(
+        verify_register_proof(truncated, &register_proof),
+        Err(DeserializationError::UnexpectedEOF)
+    );
+    let shifted_proof = [&elg_root_bytes[BYTES_PER_DIGEST - 1..], &register_proof[..]].concat();
+    assert_eq!(
+        verify_register_proof(truncated, &shifted_proof),
+        Err(DeserializationError::UnexpectedEOF)
+    );
+    assert!(verify_register_proof(&[], &register_proof).is_err());
+
+    let mut extended = elg_root_bytes.clone();
+    extended.push(0);
+    assert_eq!(
+        verify_register_proof(&extended, &register_proof),
+        Err(DeserializationError::UnconsumedBytes)
+    );
+    let report = verify_election_report(&extended, &register_proof, &[], &[], 0);
+    assert_eq!(report.register.stark_proof, None);
+}
+
+#[test]
+fn register_test_truncated_proof() {
+    let mut registar = VoterRegistar::get_example(2);
+    let register_proof = registar.get_register_proof().unwrap();
+    let bound = SchnorrPublicInputs::serialized_len(2);
+    let options = RegisterVerification::new();
+    for &len in [0, 3, 4, bound - 1].iter() {
+        assert_eq!(
+            verify_register_proof_with(&registar.elg_root, &options, &register_proof[..len])
+                .into_outcome(),
+            Err(DeserializationError::UnexpectedEOF),
+            "Proof truncated to {} bytes was deserialized.",
+            len
+        );
+    }
+    assert!(
+        verify_register_proof_with(&registar.elg_root, &options, &register_proof[..bound])
+            .into_outcome()
+            .is_err()
+    );
+}
+
+#[test]
+fn register_test_estimate() {
+    let mut registar = VoterRegistar::get_example(2);
+    let register_proof = registar.get_register_proof().unwrap();
+    let register_estimate = estimate(ProofKind::Register, &register_proof).unwrap();
+    assert_eq!(register_estimate.num_queries, 42);
+    assert!(register_estimate.num_hashes >= register_estimate.num_queries);
+    assert_eq!(register_estimate.num_point_operations, 2);
+
+    let bound = SchnorrPublicInputs::serialized_len(2);
+    assert!(estimate(ProofKind::Register, &register_proof[..bound]).is_err());
+    assert_eq!(
+        estimate(ProofKind::Register, &register_proof[..bound - 1]),
+        Err(DeserializationError::UnexpectedEOF)
+    );
+}
+
 #[test]
 fn register_test_wrong_pub_inputs() {
     let mut registar = VoterRegistar::get_example(2);
@@ -54,6 +285,89 @@ fn register_test_wrong_stark_proof() {
     )
 }
 
+#[test]
+fn register_test_subset_proof() {
+    let registar = VoterRegistar::get_example(4);
+    let mut elg_root_bytes = vec![];
+    Serializable::write_batch_into(&registar.elg_root, &mut elg_root_bytes);
+
+    // three registrations are padded to four, a single one to two
+    for indices in [vec![0, 2, 3], vec![1]].iter() {
+        let register_proof = registar.get_register_proof_for(indices).unwrap();
+        let verified = verify_register_proof(&elg_root_bytes, &register_proof);
+        assert!(verified.unwrap(), "STARK proofs should be valid.");
+    }
+
+    assert_eq!(
+        registar.get_register_proof_for(&[]),
+        Err(RegistarError::InvalidSubset)
+    );
+    assert_eq!(
+        registar.get_register_proof_for(&[0, 4]),
+        Err(RegistarError::InvalidSubset)
+    );
+}
+
+#[test]
+fn compression_test_proofs() {
+    use crate::utils::compression::{
+        compress_proof, decompress_proof, is_compressed, Compressor, COMPRESSION_HEADER_SIZE,
+    };
+
+    let registar = VoterRegistar::get_example(4);
+    let mut elg_root_bytes = vec![];
+    Serializable::write_batch_into(&registar.elg_root, &mut elg_root_bytes);
+
+    // the registration padding the subset to four is written once
+    let register_proof = registar.get_register_proof_for(&[0, 2, 3]).unwrap();
+    let compressed =
+        compress_proof(ProofKind::Register, &register_proof, Compressor::None).unwrap();
+    assert!(is_compressed(&compressed));
+    assert!(!is_compressed(&register_proof));
+    assert!(compressed.len() < register_proof.len());
+    assert_eq!(
+        decompress_proof(&compressed).unwrap().into_owned(),
+        register_proof
+    );
+    assert_eq!(
+        verify_register_proof(&elg_root_bytes, &compressed),
+        Ok(true)
+    );
+    assert_eq!(
+        estimate(ProofKind::Register, &compressed),
+        estimate(ProofKind::Register, &register_proof)
+    );
+
+    // indices out of the unique registrations are rejected
+    let mut tampered = compressed.clone();
+    tampered[COMPRESSION_HEADER_SIZE + 5] = 3;
+    assert!(verify_register_proof(&elg_root_bytes, &tampered).is_err());
+
+    // cast proofs are decompressed transparently as well
+    let mut collector = VoteCollector::get_example(2);
+    let mut voting_keys = vec![];
+    voting_keys.write_u8_slice(&(collector.voting_keys.len() as u32).to_be_bytes());
+    for voting_key in collector.voting_keys.iter() {
+        voting_keys.write_u8_slice(&compress_point(voting_key));
+    }
+    let cast_proof = collector.get_cast_proof().unwrap();
+    let compressed = compress_proof(ProofKind::Cast, &cast_proof, Compressor::None).unwrap();
+    assert_eq!(verify_cast_proof(&voting_keys, &compressed), Ok(true));
+
+    #[cfg(feature = "zstd")]
+    {
+        let compressed =
+            compress_proof(ProofKind::Register, &register_proof, Compressor::Zstd).unwrap();
+        assert!(compressed.len() < register_proof.len());
+        assert_eq!(
+            verify_register_proof(&elg_root_bytes, &compressed),
+            Ok(true)
+        );
+        let compressed = compress_proof(ProofKind::Cast, &cast_proof, Compressor::Zstd).unwrap();
+        assert_eq!(verify_cast_proof(&voting_keys, &compressed), Ok(true));
+    }
+}
+
 #[test]
 fn cast_test_all_valid() {
     let mut collector = VoteCollector::get_example(2);
@@ -61,7 +375,7 @@ fn cast_test_all_valid() {
     let mut voting_keys = vec![];
     voting_keys.write_u8_slice(&(collector.voting_keys.len() as u32).to_be_bytes());
     for voting_key in collector.voting_keys.iter() {
-        Serializable::write_batch_into(voting_key, &mut voting_keys);
+        voting_keys.write_u8_slice(&compress_point(voting_key));
     }
     let verified = verify_cast_proof(&voting_keys, &cast_proof);
     assert!(
@@ -71,6 +385,109 @@ fn cast_test_all_valid() {
     assert!(verified.unwrap(), "STARK proof should be valid.")
 }
 
+#[test]
+fn cast_test_extension_policy() {
+    let mut collector = VoteCollector::get_example(2);
+    let mut voting_keys = vec![];
+    voting_keys.write_u8_slice(&(collector.voting_keys.len() as u32).to_be_bytes());
+    for voting_key in collector.voting_keys.iter() {
+        voting_keys.write_u8_slice(&compress_point(voting_key));
+    }
+
+    for extension in 1..=3u8 {
+        collector.set_extension(extension);
+        let cast_proof = collector.get_cast_proof().unwrap();
+        for min_degree in 1..=3 {
+            let policy = ExtensionPolicy::at_least(min_degree);
+            let options = CastVerification::new().with_policy(policy);
+            assert_eq!(
+                verify_cast_proof_with(&voting_keys, &options, &cast_proof).into_outcome(),
+                Ok(extension as u32 >= min_degree),
+                "Cast proof over extension {} with policy {:?}",
+                extension,
+                policy
+            );
+        }
+    }
+}
+
+#[test]
+fn cast_test_fri_policy() {
+    let mut collector = VoteCollector::get_example(2);
+    let mut voting_keys = vec![];
+    voting_keys.write_u8_slice(&(collector.voting_keys.len() as u32).to_be_bytes());
+    for voting_key in collector.voting_keys.iter() {
+        voting_keys.write_u8_slice(&compress_point(voting_key));
+    }
+
+    let fri = FriConfig::new(8, 512).unwrap();
+    let default_proof = collector.get_cast_proof().unwrap();
+    collector.set_fri(fri);
+    let cast_proof = collector.get_cast_proof().unwrap();
+    assert!(verify_cast_proof(&voting_keys, &cast_proof).unwrap());
+
+    // proofs generated with other FRI parameters than pinned by the policy are rejected
+    let options = CastVerification::new().with_policy(ExtensionPolicy::ANY.with_fri(fri));
+    assert_eq!(
+        verify_cast_proof_with(&voting_keys, &options, &cast_proof).into_outcome(),
+        Ok(true)
+    );
+    let report = verify_cast_proof_with(&voting_keys, &options, &default_proof);
+    assert!(!report.is_valid());
+    assert_eq!(
+        report.stark_proof,
+        Some(Err(ProofError::Fri(FriConfig::DEFAULT)))
+    );
+}
+
+#[test]
+fn cast_test_report() {
+    let mut collector = VoteCollector::get_example(2);
+    let mut voting_keys = vec![];
+    voting_keys.write_u8_slice(&(collector.voting_keys.len() as u32).to_be_bytes());
+    for voting_key in collector.voting_keys.iter() {
+        voting_keys.write_u8_slice(&compress_point(voting_key));
+    }
+
+    let options = CastVerification::new();
+    let cast_proof = collector.get_cast_proof().unwrap();
+    let report = verify_cast_proof_with(&voting_keys, &options, &cast_proof);
+    assert!(report.is_valid());
+    assert_eq!(report.excluded_ballots, Some(true));
+    assert_eq!(report.commitments, Some(true));
+    assert_eq!(report.trackers.len(), 2);
+
+    let cast_proof = collector.get_cast_proof_wrong_stark_proof().unwrap();
+    let report = verify_cast_proof_with(&voting_keys, &options, &cast_proof);
+    assert_eq!(report.public_inputs, Ok(()));
+    assert!(matches!(report.stark_proof, Some(Err(_))));
+
+    // a proof for another number of voters is rejected before its STARK proof
+    let mut other_keys = voting_keys.clone();
+    other_keys[3] += 1;
+    let report = verify_cast_proof_with(&other_keys, &options, &cast_proof);
+    assert!(report.public_inputs.is_err());
+    assert_eq!(report.stark_proof, None);
+}
+
+#[test]
+fn cast_test_estimate() {
+    let mut collector = VoteCollector::get_example(2);
+    let cast_proof = collector.get_cast_proof().unwrap();
+    let cast_estimate = estimate(ProofKind::Cast, &cast_proof).unwrap();
+    assert_eq!(cast_estimate.num_queries, 42);
+    assert_eq!(cast_estimate.num_scalar_muls, 0);
+    assert!(cast_estimate.gas() > 0);
+
+    assert_eq!(
+        estimate(
+            ProofKind::Cast,
+            &cast_proof[..2 * BYTES_PER_CAST_PUB_INPUTS]
+        ),
+        Err(DeserializationError::UnexpectedEOF)
+    );
+}
+
 #[test]
 fn cast_test_wrong_pub_inputs() {
     let mut collector = VoteCollector::get_example(2);
@@ -78,7 +495,7 @@ fn cast_test_wrong_pub_inputs() {
     let mut voting_keys = vec![];
     voting_keys.write_u8_slice(&(collector.voting_keys.len() as u32).to_be_bytes());
     for voting_key in collector.voting_keys.iter() {
-        Serializable::write_batch_into(voting_key, &mut voting_keys);
+        voting_keys.write_u8_slice(&compress_point(voting_key));
     }
     let verified = verify_cast_proof(&voting_keys, &cast_proof);
     assert!(
@@ -95,7 +512,7 @@ fn cast_test_wrong_stark_proof() {
     let mut voting_keys = vec![];
     voting_keys.write_u8_slice(&(collector.voting_keys.len() as u32).to_be_bytes());
     for voting_key in collector.voting_keys.iter() {
-        Serializable::write_batch_into(voting_key, &mut voting_keys);
+        voting_keys.write_u8_slice(&compress_point(voting_key));
     }
     let verified = verify_cast_proof(&voting_keys, &cast_proof);
     assert!(
@@ -105,15 +522,167 @@ fn cast_test_wrong_stark_proof() {
     assert!(!verified.unwrap(), "STARK proof should be invalid.")
 }
 
+#[test]
+fn cast_test_voter_encryption() {
+    use crate::schnorr::random_key_pairs;
+
+    let (secret_keys, voting_keys) = random_key_pairs(4);
+    let mut collector = VoteCollector::new(voting_keys.clone());
+    for (i, &secret_key) in secret_keys.iter().enumerate() {
+        let encrypted_vote = EncryptedVote::cast(i, secret_key, &voting_keys, i % 2 == 0);
+        assert!(encrypted_vote
+            .verify(&voting_keys, &UNBOUND_CONTEXT)
+            .is_ok());
+        collector.add_encrypted_vote(encrypted_vote).unwrap();
+    }
+    // an out-of-range voter index is reported instead of panicking
+    let encrypted_vote = EncryptedVote::cast(3, secret_keys[3], &voting_keys, true);
+    assert_eq!(
+        encrypted_vote.verify(&voting_keys[..2], &UNBOUND_CONTEXT),
+        Err(CdsVerificationError::InvalidVoterIndex)
+    );
+    let mut small_collector = VoteCollector::new(voting_keys[..2].to_vec());
+    assert_eq!(
+        small_collector.add_encrypted_vote(encrypted_vote),
+        Err(CollectorError::InvalidEncryptedVote)
+    );
+
+    let cast_proof = collector.get_cast_proof().unwrap();
+    let mut voting_keys_bytes = vec![];
+    voting_keys_bytes.write_u8_slice(&(voting_keys.len() as u32).to_be_bytes());
+    for voting_key in voting_keys.iter() {
+        voting_keys_bytes.write_u8_slice(&compress_point(voting_key));
+    }
+    assert!(verify_cast_proof(&voting_keys_bytes, &cast_proof).unwrap());
+}
+
+#[test]
+fn cast_test_duplicate_submission() {
+    use crate::schnorr::random_key_pairs;
+
+    let (secret_keys, voting_keys) = random_key_pairs(4);
+    let mut collector = VoteCollector::new(voting_keys.clone());
+
+    // a second submission from the same voter is rejected
+    let encrypted_vote = EncryptedVote::cast(0, secret_keys[0], &voting_keys, true);
+    collector.add_encrypted_vote(encrypted_vote).unwrap();
+    let encrypted_vote = EncryptedVote::cast(0, secret_keys[0], &voting_keys, false);
+    assert_eq!(
+        collector.add_encrypted_vote(encrypted_vote),
+        Err(CollectorError::InvalidEncryptedVote)
+    );
+
+    // a stored all-zero encrypted vote still counts as submitted
+    collector.encrypted_votes[1] = Some([BaseElement::ZERO; AFFINE_POINT_WIDTH]);
+    let encrypted_vote = EncryptedVote::cast(1, secret_keys[1], &voting_keys, true);
+    assert_eq!(
+        collector.add_encrypted_vote(encrypted_vote),
+        Err(CollectorError::InvalidEncryptedVote)
+    );
+}
+
+#[test]
+fn cast_test_seeded_encryption() {
+    // the same seed yields the same keys and encrypted votes
+    let run = |seed: u64| {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let (secret_keys, voting_keys): (Vec<_>, Vec<_>) =
+            (0..2).map(|_| random_key_pair_with_rng(&mut rng)).unzip();
+        let encrypted_vote =
+            EncryptedVote::cast_with_rng(1, secret_keys[1], &voting_keys, true, &mut rng);
+        assert!(encrypted_vote
+            .verify(&voting_keys, &UNBOUND_CONTEXT)
+            .is_ok());
+        (voting_keys, encrypted_vote.to_bytes())
+    };
+    assert_eq!(run(42), run(42));
+    assert_ne!(run(42), run(43));
+}
+
+#[test]
+fn cast_test_compressed_serialization() {
+    let collector = VoteCollector::get_example(2);
+    for voting_key in collector.voting_keys.iter() {
+        assert_eq!(
+            decompress_point(&compress_point(voting_key)),
+            Some(*voting_key)
+        );
+    }
+
+    let restored = VoteCollector::from_bytes(&collector.to_bytes()).unwrap();
+    assert_eq!(restored.voting_keys, collector.voting_keys);
+    assert_eq!(restored.encrypted_votes, collector.encrypted_votes);
+    assert_eq!(restored.proof_points, collector.proof_points);
+    assert_eq!(restored.num_valid_votes, collector.num_valid_votes);
+}
+
+#[test]
+fn cast_test_sealed_collector() {
+    let collector = VoteCollector::get_example(4);
+    let mut partial = VoteCollector::new(collector.voting_keys.clone());
+    partial.encrypted_votes[1] = collector.encrypted_votes[1];
+    partial.proof_points[1] = collector.proof_points[1];
+    partial.proof_scalars[1] = collector.proof_scalars[1];
+    partial.num_valid_votes = 1;
+    let empty = VoteCollector::new(collector.voting_keys.clone());
+    let key = [7u8; SEALING_KEY_SIZE];
+
+    // the length does not depend on which voters submitted
+    let full_sealed = collector.seal(&key);
+    assert_eq!(partial.seal(&key).len(), full_sealed.len());
+    assert_eq!(empty.seal(&key).len(), full_sealed.len());
+    assert_ne!(empty.seal(&key), empty.seal(&key));
+
+    let restored = VoteCollector::open_sealed(&partial.seal(&key), &key).unwrap();
+    assert_eq!(restored.voting_keys, partial.voting_keys);
+    assert_eq!(restored.encrypted_votes, partial.encrypted_votes);
+    assert_eq!(restored.proof_scalars, partial.proof_scalars);
+    assert_eq!(restored.num_valid_votes, 1);
+
+    assert!(VoteCollector::open_sealed(&partial.seal(&key), &[8u8; SEALING_KEY_SIZE]).is_err());
+    assert!(VoteCollector::open_sealed(&partial.to_bytes(), &key).is_err());
+
+    let restored = VoteCollector::from_bytes(&partial.to_bytes()).unwrap();
+    assert_eq!(restored.encrypted_votes, partial.encrypted_votes);
+    assert_eq!(restored.num_valid_votes, 1);
+}
+
+#[test]
+fn tally_test_legacy_serialization() {
+    let (tallier, _) = VoteTallier::get_example(2);
+    let mut legacy_bytes = vec![];
+    legacy_bytes.write_u32(tallier.encrypted_votes.len() as u32);
+    for encrypted_vote in tallier.encrypted_votes.iter() {
+        legacy_bytes.write_u8_slice(&compress_point(encrypted_vote));
+    }
+
+    let compact_bytes = tallier.to_bytes();
+    let restored = VoteTallier::from_bytes(&compact_bytes).unwrap();
+    assert_eq!(restored.encrypted_votes, tallier.encrypted_votes);
+    let restored = VoteTallier::from_bytes(&legacy_bytes).unwrap();
+    assert_eq!(restored.encrypted_votes, tallier.encrypted_votes);
+}
+
+#[test]
+fn wire_test_varint() {
+    for &value in [0u64, 1, 127, 128, 300, 1 << 32, u64::MAX].iter() {
+        let mut bytes = vec![];
+        write_varint(&mut bytes, value);
+        assert_eq!(bytes.len(), varint_len(value));
+        assert_eq!(read_varint(&mut SliceReader::new(&bytes)).unwrap(), value);
+    }
+
+    // overlong and truncated encodings are rejected
+    assert!(read_varint(&mut SliceReader::new(&[0x80, 0x00])).is_err());
+    assert!(read_varint(&mut SliceReader::new(&[0x80])).is_err());
+    assert!(read_varint(&mut SliceReader::new(&[0xff; 10])).is_err());
+}
+
 #[test]
 fn tally_test_all_valid() {
     let (mut tallier, expected_result) = VoteTallier::get_example(2);
     let tally_result = tallier.tally_votes().unwrap();
-    let mut encrypted_votes = vec![];
-    encrypted_votes.write_u32(tallier.encrypted_votes.len() as u32);
-    for encrypted_vote in tallier.encrypted_votes.iter() {
-        Serializable::write_batch_into(encrypted_vote, &mut encrypted_votes);
-    }
+    let encrypted_votes = tallier.encrypted_votes_bytes();
     assert!(
         tally_result == expected_result,
         "Vote tallying result should be correct."
@@ -130,11 +699,7 @@ fn tally_test_all_valid() {
 fn tally_test_wrong_result() {
     let (mut tallier, expected_result) = VoteTallier::get_example(2);
     let tally_result = tallier.tally_votes_wrong_result().unwrap();
-    let mut encrypted_votes = vec![];
-    encrypted_votes.write_u32(tallier.encrypted_votes.len() as u32);
-    for encrypted_vote in tallier.encrypted_votes.iter() {
-        Serializable::write_batch_into(encrypted_vote, &mut encrypted_votes);
-    }
+    let encrypted_votes = tallier.encrypted_votes_bytes();
     assert!(
         tally_result != expected_result,
         "Vote tallying result should be incorrect."
@@ -147,6 +712,245 @@ fn tally_test_wrong_result() {
     assert!(!verified.unwrap(), "STARK proof should be invalid.")
 }
 
+#[test]
+fn tally_test_result_certificate() {
+    let (mut tallier, tally_result) = VoteTallier::get_example(8);
+    let secret_key = Scalar::random(OsRng);
+    let aggregator_key = projective_to_elements(ProjectivePoint::generator() * secret_key);
+    let manifest = ElectionManifest::new("Adopt the proposal?", Schedule::default());
+    let manifest_digest = manifest.digest();
+    let proof = vec![7u8; 100];
+
+    let certificate = tallier.certify(&[&proof], &manifest, secret_key).unwrap();
+    assert_eq!(certificate.yes_votes, tally_result);
+    assert_eq!(certificate.num_votes(), 8);
+    assert!(certificate.commits_to(&tallier.encrypted_votes));
+    assert!(certificate.contains_proof(&proof));
+    assert!(!certificate.contains_proof(&proof[1..]));
+    assert!(verify_certificate(
+        &certificate,
+        &manifest_digest,
+        &aggregator_key
+    ));
+
+    // the certificate survives serialization
+    let certificate = ResultCertificate::from_bytes(&certificate.to_bytes()).unwrap();
+    assert!(verify_certificate(
+        &certificate,
+        &manifest_digest,
+        &aggregator_key
+    ));
+
+    // a non-canonical encoding of the signature scalar is rejected
+    let mut bytes = certificate.to_bytes();
+    let scalar_start = bytes.len() - BYTES_PER_SCALAR;
+    bytes[scalar_start..].copy_from_slice(&[0xff; BYTES_PER_SCALAR]);
+    assert!(ResultCertificate::from_bytes(&bytes).is_err());
+
+    // tampered counts invalidate the signature
+    let mut wrong_certificate = certificate.clone();
+    wrong_certificate.yes_votes ^= 1;
+    wrong_certificate.no_votes ^= 1;
+    assert_ne!(wrong_certificate.yes_votes, certificate.yes_votes);
+    assert!(!verify_certificate(
+        &wrong_certificate,
+        &manifest_digest,
+        &aggregator_key
+    ));
+    wrong_certificate = certificate.clone();
+    wrong_certificate.proof_digests.clear();
+    assert!(!verify_certificate(
+        &wrong_certificate,
+        &manifest_digest,
+        &aggregator_key
+    ));
+
+    // the manifest digest is signed, and must be the one of the election
+    let other_manifest = ElectionManifest::new("Reject the proposal?", Schedule::default());
+    assert!(!verify_certificate(
+        &certificate,
+        &other_manifest.digest(),
+        &aggregator_key
+    ));
+    wrong_certificate = certificate.clone();
+    wrong_certificate.manifest_digest = other_manifest.digest();
+    assert!(!verify_certificate(
+        &wrong_certificate,
+        &other_manifest.digest(),
+        &aggregator_key
+    ));
+
+    // a certificate signed by another aggregator is rejected
+    let other_key = projective_to_elements(ProjectivePoint::generator() * Scalar::random(OsRng));
+    assert!(!verify_certificate(
+        &certificate,
+        &manifest_digest,
+        &other_key
+    ));
+}
+
+#[test]
+fn tally_test_certificate_publication_digest() {
+    let (mut tallier, _) = VoteTallier::get_example(2);
+    let secret_key = Scalar::random(OsRng);
+    let aggregator_key = projective_to_elements(ProjectivePoint::generator() * secret_key);
+    let manifest = ElectionManifest::new("Adopt the proposal?", Schedule::default());
+    let publication_digest =
+        digest_bytes(b"BallotList:QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG\n");
+
+    let certificate = tallier
+        .certify_with_publication(&[], &manifest, Some(publication_digest), secret_key)
+        .unwrap();
+    assert!(verify_certificate(
+        &certificate,
+        &manifest.digest(),
+        &aggregator_key
+    ));
+    let decoded = ResultCertificate::from_bytes(&certificate.to_bytes()).unwrap();
+    assert_eq!(decoded, certificate);
+
+    // the publication digest is signed
+    let mut wrong_certificate = certificate.clone();
+    wrong_certificate.publication_digest = None;
+    assert!(!verify_certificate(
+        &wrong_certificate,
+        &manifest.digest(),
+        &aggregator_key
+    ));
+
+    // certificates without publication digest keep their encoding
+    let plain = tallier.certify(&[], &manifest, secret_key).unwrap();
+    assert_eq!(
+        plain.to_bytes().len() + BYTES_PER_DIGEST,
+        certificate.to_bytes().len()
+    );
+    let decoded = ResultCertificate::from_bytes(&plain.to_bytes()).unwrap();
+    assert_eq!(decoded.publication_digest, None);
+}
+
+#[test]
+fn tally_test_certificate_policy() {
+    let (mut tallier, tally_result) = VoteTallier::get_example(8);
+    let secret_key = Scalar::random(OsRng);
+    let aggregator_key = projective_to_elements(ProjectivePoint::generator() * secret_key);
+    let mut manifest = ElectionManifest::new("Adopt the proposal?", Schedule::default());
+    let policy = ResultPolicy {
+        min_turnout: 5_000,
+        threshold: SIMPLE_MAJORITY,
+    };
+    manifest.policy = Some(policy);
+    // only the number of registrations heading a register proof is read
+    let register_proof = |num_regs: u32| {
+        let mut register_proof = num_regs.to_le_bytes().to_vec();
+        register_proof.extend_from_slice(&[0u8; 32]);
+        register_proof
+    };
+    let (register_proof_10, register_proof_20) = (register_proof(10), register_proof(20));
+
+    let certificate = tallier
+        .certify_with_policy(&[], &manifest, &register_proof_10, secret_key)
+        .unwrap();
+    let evaluation = certificate.policy_evaluation.unwrap();
+    assert_eq!(evaluation.outcome, policy.evaluate(10, tally_result, 8));
+    assert!(verify_certificate(
+        &certificate,
+        &manifest.digest(),
+        &aggregator_key
+    ));
+    assert!(certificate.contains_proof(&register_proof_10));
+    assert!(certificate.follows_policy(&manifest, &register_proof_10));
+    let decoded = ResultCertificate::from_bytes(&certificate.to_bytes()).unwrap();
+    assert_eq!(decoded, certificate);
+
+    // too few votes for the number of registered voters
+    let certificate = tallier
+        .certify_with_policy(&[], &manifest, &register_proof_20, secret_key)
+        .unwrap();
+    assert_eq!(
+        certificate.policy_evaluation.unwrap().outcome,
+        PolicyOutcome::QuorumNotReached
+    );
+    assert!(certificate.follows_policy(&manifest, &register_proof_20));
+
+    // the number of voters must be the one of the certified register proof
+    assert!(!certificate.follows_policy(&manifest, &register_proof_10));
+    let mut wrong_certificate = tallier
+        .certify_with_policy(&[], &manifest, &register_proof_10, secret_key)
+        .unwrap();
+    wrong_certificate.proof_digests = certificate.proof_digests.clone();
+    assert!(!wrong_certificate.follows_policy(&manifest, &register_proof_20));
+    assert_eq!(
+        tallier.certify_with_policy(&[], &manifest, &[1, 0], secret_key),
+        Err(TallierError::InvalidRegisterProof)
+    );
+
+    // the outcome is signed and must follow from the tally and the policy of the manifest
+    let mut wrong_certificate = certificate.clone();
+    wrong_certificate.policy_evaluation = Some(PolicyEvaluation {
+        outcome: PolicyOutcome::Adopted,
+        ..certificate.policy_evaluation.unwrap()
+    });
+    assert!(!verify_certificate(
+        &wrong_certificate,
+        &manifest.digest(),
+        &aggregator_key
+    ));
+    assert!(!wrong_certificate.follows_policy(&manifest, &register_proof_20));
+    manifest.policy = None;
+    assert!(!certificate.follows_policy(&manifest, &register_proof_20));
+    assert!(tallier
+        .certify(&[], &manifest, secret_key)
+        .unwrap()
+        .follows_policy(&manifest, &register_proof_20));
+
+    // the policy evaluation follows the publication digest
+    let mut certificate = tallier
+        .certify_with_publication(&[], &manifest, Some(digest_bytes(b"artifacts")), secret_key)
+        .unwrap();
+    certificate.policy_evaluation = Some(evaluation);
+    let decoded = ResultCertificate::from_bytes(&certificate.to_bytes()).unwrap();
+    assert_eq!(decoded, certificate);
+}
+
+#[test]
+fn tally_test_group_breakdown() {
+    let mut tallier = GroupTallier::new(2);
+    let mut expected = vec![];
+    for (group, num_votes) in [(1u32, 4usize), (2, 8), (3, 2)] {
+        let (group_tallier, tally_result) = VoteTallier::get_example(num_votes);
+        assert!(tallier.insert(group, group_tallier).is_none());
+        expected.push(GroupCount {
+            group,
+            num_votes: num_votes as u32,
+            tally_result,
+        });
+    }
+
+    // group 3 does not exceed the threshold, and group 1 is suppressed along with it
+    let breakdown = tallier.tally_groups().unwrap();
+    assert_eq!(breakdown.groups, vec![expected[1]]);
+    assert_eq!(breakdown.num_suppressed, 2);
+    assert_eq!(breakdown.num_votes, 14);
+    assert_eq!(
+        breakdown.tally_result,
+        expected.iter().map(|count| count.tally_result as u64).sum()
+    );
+
+    tallier.threshold = 1;
+    let breakdown = tallier.tally_groups().unwrap();
+    assert_eq!(breakdown.groups, expected);
+    assert_eq!(breakdown.num_suppressed, 0);
+
+    // votes blinded across groups do not cancel their blinding keys within a group
+    let (other_tallier, _) = VoteTallier::get_example(4);
+    let split = VoteTallier::new(other_tallier.encrypted_votes[..2].to_vec());
+    tallier.insert(4, split);
+    assert_eq!(
+        tallier.tally_groups(),
+        Err(TallierError::InvalidTallyResult)
+    );
+}
+
 #[test]
 fn aggregator_test_all_valid() {
     let mut aggregator = AggregatorExample::new(2);
@@ -165,7 +969,7 @@ fn aggregator_test_all_valid() {
     let mut voting_keys = vec![];
     voting_keys.write_u8_slice(&(aggregator.vote_collector.voting_keys.len() as u32).to_be_bytes());
     for voting_key in aggregator.vote_collector.voting_keys.iter() {
-        Serializable::write_batch_into(voting_key, &mut voting_keys);
+        voting_keys.write_u8_slice(&compress_point(voting_key));
     }
     let verified = verify_cast_proof(&voting_keys, &cast_proof);
     assert!(
@@ -175,11 +979,7 @@ fn aggregator_test_all_valid() {
     assert!(verified.unwrap(), "Cast proof should be valid.");
     // Vote tallying
     let tally_result = aggregator.vote_tallier.tally_votes().unwrap();
-    let mut encrypted_votes = vec![];
-    encrypted_votes.write_u32(aggregator.vote_tallier.encrypted_votes.len() as u32);
-    for encrypted_vote in aggregator.vote_tallier.encrypted_votes.iter() {
-        Serializable::write_batch_into(encrypted_vote, &mut encrypted_votes);
-    }
+    let encrypted_votes = aggregator.vote_tallier.encrypted_votes_bytes();
     let verified = verify_tally_result(&encrypted_votes, tally_result);
     assert!(
         verified.is_ok(),
@@ -187,3 +987,2273 @@ fn aggregator_test_all_valid() {
     );
     assert!(verified.unwrap(), "Tally result should be valid.")
 }
+
+#[test]
+fn aggregator_test_verify_election() {
+    let mut aggregator = AggregatorExample::new(2);
+    let register_proof = aggregator.voter_registar.get_register_proof().unwrap();
+    let mut elg_root_bytes = vec![];
+    Serializable::write_batch_into(&aggregator.voter_registar.elg_root, &mut elg_root_bytes);
+    let cast_proof = aggregator.vote_collector.get_cast_proof().unwrap();
+    let tally_result = aggregator.vote_tallier.tally_votes().unwrap();
+    let encrypted_votes = aggregator.vote_tallier.encrypted_votes_bytes();
+
+    assert_eq!(
+        verify_election(
+            &elg_root_bytes,
+            &register_proof,
+            &cast_proof,
+            &encrypted_votes,
+            tally_result
+        ),
+        Ok(true)
+    );
+
+    // a cast proof of other voters is rejected, although it is valid on its own
+    let mut other_collector = VoteCollector::get_example(2);
+    let other_cast_proof = other_collector.get_cast_proof().unwrap();
+    let report = verify_election_report(
+        &elg_root_bytes,
+        &register_proof,
+        &other_cast_proof,
+        &encrypted_votes,
+        tally_result,
+    );
+    assert!(report.register.is_valid());
+    assert!(!report.cast.unwrap().is_valid());
+
+    // encrypted votes tallied must be those of the cast proof
+    let other_votes = other_collector
+        .encrypted_votes
+        .iter()
+        .map(|encrypted_vote| encrypted_vote.unwrap())
+        .collect::<Vec<_>>();
+    let other_result = crate::tally::self_tally(&other_votes).unwrap();
+    let other_votes = VoteTallier::new(other_votes).encrypted_votes_bytes();
+    let report = verify_election_report(
+        &elg_root_bytes,
+        &register_proof,
+        &cast_proof,
+        &other_votes,
+        other_result,
+    );
+    assert!(report.tally.is_valid());
+    assert!(report.cast.unwrap().is_valid());
+    assert_eq!(report.encrypted_votes, Some(false));
+}
+
+#[test]
+fn aggregator_test_phase_commitment_chain() {
+    use crate::chain::{phase_commitment_to_bytes, tally_commitment};
+
+    let mut aggregator = AggregatorExample::new(2);
+    let register_proof = aggregator.voter_registar.get_register_proof().unwrap();
+    let mut elg_root_bytes = vec![];
+    Serializable::write_batch_into(&aggregator.voter_registar.elg_root, &mut elg_root_bytes);
+    let mut voting_keys = vec![];
+    voting_keys.write_u8_slice(&(2u32).to_be_bytes());
+    for voting_key in aggregator.vote_collector.voting_keys.iter() {
+        voting_keys.write_u8_slice(&compress_point(voting_key));
+    }
+
+    // the cast proof is chained to the registration set
+    let register_commitment = aggregator.voter_registar.phase_commitment();
+    assert_eq!(aggregator.vote_collector.phase_commitment(), None);
+    aggregator
+        .vote_collector
+        .bind_register_commitment(register_commitment);
+    let cast_proof = aggregator.vote_collector.get_cast_proof().unwrap();
+    let options = CastVerification::new().with_register_commitment(register_commitment);
+    assert_eq!(
+        verify_cast_proof_with(&voting_keys, &options, &cast_proof).into_outcome(),
+        Ok(true)
+    );
+    assert_eq!(verify_cast_proof(&voting_keys, &cast_proof), Ok(false));
+    let other_commitment = VoterRegistar::get_example(2).phase_commitment();
+    let options = CastVerification::new().with_register_commitment(other_commitment);
+    assert_eq!(
+        verify_cast_proof_with(&voting_keys, &options, &cast_proof).into_outcome(),
+        Ok(false)
+    );
+
+    // the tally proof is chained to the valid encrypted votes of the cast proof
+    let ballot_commitment = aggregator.vote_collector.phase_commitment().unwrap();
+    aggregator
+        .vote_tallier
+        .bind_ballot_commitment(ballot_commitment);
+    let tally_result = aggregator.vote_tallier.tally_votes().unwrap();
+    let tally_proof = aggregator.vote_tallier.get_tally_proof().unwrap();
+    let encrypted_votes = aggregator.vote_tallier.encrypted_votes_bytes();
+    assert_eq!(
+        verify_tally_proof_chained(
+            &encrypted_votes,
+            tally_result,
+            &phase_commitment_to_bytes(&ballot_commitment),
+            &tally_proof
+        ),
+        Ok(true)
+    );
+    assert_eq!(
+        verify_tally_proof_chained(
+            &encrypted_votes,
+            tally_result,
+            &register_bytes,
+            &tally_proof
+        ),
+        Ok(false)
+    );
+    assert_eq!(
+        verify_tally_proof(&encrypted_votes, tally_result, &tally_proof),
+        Ok(false)
+    );
+    assert_eq!(
+        aggregator.vote_tallier.phase_commitment(),
+        Some(tally_commitment(&ballot_commitment, tally_result))
+    );
+
+    assert_eq!(
+        verify_election_chained(
+            &elg_root_bytes,
+            &register_proof,
+            &cast_proof,
+            &encrypted_votes,
+            tally_result,
+            &tally_proof
+        ),
+        Ok(true)
+    );
+    assert_eq!(
+        verify_election_chained(
+            &elg_root_bytes,
+            &register_proof,
+            &cast_proof,
+            &encrypted_votes,
+            aggregator.vote_tallier.encrypted_votes.len() as u32 - tally_result,
+            &tally_proof
+        ),
+        Ok(false)
+    );
+
+    // tally proofs need a power of two encrypted votes
+    let mut tallier = VoteTallier::new(aggregator.vote_tallier.encrypted_votes[..1].to_vec());
+    assert_eq!(
+        tallier.get_tally_proof(),
+        Err(TallierError::InvalidNumberOfVotes)
+    );
+}
+
+#[test]
+fn register_test_submission_quota() {
+    let example = VoterRegistar::get_example(2);
+    let mut registar = VoterRegistar::new(example.tree.clone(), 2);
+    let mut policy = SubmissionQuota::new(1);
+    let registration = Registration {
+        voting_key: example.voting_keys[0],
+        merkle_branch: example.merkle_branch(0),
+        hash_index: example.hash_indices[0],
+        signature: example.signatures[0],
+        address: example.addresses[0],
+        ownership_signature: None,
+    };
+    assert_eq!(
+        registar.add_registration_with_policy(registration, &mut policy),
+        Ok(())
+    );
+    assert_eq!(
+        registar.add_registration_with_policy(registration, &mut policy),
+        Err(RegistarError::Rejected),
+        "Second registration from the same address should be rejected."
+    );
+}
+
+#[test]
+fn register_test_rejection_log() {
+    use super::rejection::{payload_digest, RejectionLog, RejectionReason, Submitter};
+
+    let example = VoterRegistar::get_example(2);
+    let mut registar = VoterRegistar::new(example.tree.clone(), 2);
+    // forged registrations are not charged to the quota of the voter
+    let mut policy = SubmissionQuota::new(1);
+    let mut registration = Registration {
+        voting_key: example.voting_keys[0],
+        merkle_branch: example.merkle_branch(0),
+        hash_index: example.hash_indices[0],
+        signature: example.signatures[1],
+        address: example.addresses[0],
+        ownership_signature: None,
+    };
+    let forged_digest = payload_digest(&registration.to_bytes());
+    assert_eq!(
+        registar.add_registration_with_policy(registration, &mut policy),
+        Err(RegistarError::InvalidSchnorrSig)
+    );
+    registration.signature = example.signatures[0];
+    assert_eq!(
+        registar.add_registration_with_policy(registration, &mut policy),
+        Ok(())
+    );
+    assert_eq!(
+        registar.add_registration_with_policy(registration, &mut policy),
+        Err(RegistarError::Rejected)
+    );
+
+    // accepted registrations are not logged
+    let submitter = Submitter::Address(example.addresses[0]);
+    let reasons = registar
+        .rejections
+        .by_submitter(submitter)
+        .map(|rejection| rejection.reason)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        reasons,
+        vec![
+            RejectionReason::InvalidSchnorrSig,
+            RejectionReason::Admission
+        ]
+    );
+
+    // the voter finds out why their registration was dropped from its payload
+    let rejection = registar
+        .rejections
+        .by_payload_digest(&forged_digest)
+        .next()
+        .unwrap();
+    assert_eq!(rejection.reason, RejectionReason::InvalidSchnorrSig);
+    assert_eq!(rejection.submitter, submitter);
+    assert!(registar.rejections.between(0, rejection.timestamp).count() >= 1);
+    assert_eq!(
+        registar
+            .rejections
+            .by_submitter(Submitter::Address(example.addresses[1]))
+            .count(),
+        0
+    );
+
+    // rejections are queried by time range, in seconds since the Unix epoch
+    let mut log = RejectionLog::new();
+    log.record_at(submitter, RejectionReason::InvalidMerkleProof, &[0u8], 10);
+    log.record_at(submitter, RejectionReason::InvalidMerkleProof, &[1u8], 20);
+    assert_eq!(log.between(0, 15).count(), 1);
+    assert_eq!(log.between(10, 20).count(), 2);
+    assert_eq!(log.between(21, 30).count(), 0);
+}
+
+#[test]
+fn register_test_address_ownership() {
+    use crate::{
+        aggregator::ownership::{address_of, recover_owner, sign_ownership},
+        merkle::build_merkle_tree_with_keys,
+        schnorr::{random_key_pairs, sign_messages},
+    };
+    use k256::ecdsa::SigningKey;
+
+    let (secret_keys, voting_keys) = random_key_pairs(2);
+    let signing_keys = (0..2)
+        .map(|_| SigningKey::random(&mut OsRng))
+        .collect::<Vec<SigningKey>>();
+    let addresses = signing_keys
+        .iter()
+        .map(|signing_key| address_of(signing_key.verifying_key()))
+        .collect::<Vec<_>>();
+    let signatures = sign_messages(&voting_keys, &addresses, &secret_keys);
+    let (tree, hash_indices) = build_merkle_tree_with_keys(&voting_keys);
+    let mut registration = Registration {
+        voting_key: voting_keys[0],
+        merkle_branch: tree.branch(hash_indices[0]),
+        hash_index: hash_indices[0],
+        signature: signatures[0],
+        address: addresses[0],
+        ownership_signature: None,
+    };
+
+    // ownership is not checked by default
+    let mut registar = VoterRegistar::new(tree.clone(), 2);
+    assert_eq!(registar.add_registration(registration), Ok(()));
+
+    let manifest = ElectionManifest::new("Adopt the proposal?", Schedule::default());
+    let manifest_digest = manifest.digest().to_elements();
+    let mut registar = VoterRegistar::with_address_ownership(tree.clone(), 2, &manifest);
+    assert_eq!(
+        registar.add_registration(registration),
+        Err(RegistarError::InvalidOwnershipSig),
+        "Registration without ownership signature should be rejected."
+    );
+    registration.ownership_signature = Some(sign_ownership(
+        &signing_keys[1],
+        &voting_keys[0],
+        &manifest_digest,
+    ));
+    assert_eq!(
+        registar.add_registration(registration),
+        Err(RegistarError::InvalidOwnershipSig),
+        "Registration signed by another address should be rejected."
+    );
+
+    // signatures for another election cannot be replayed
+    let other_manifest = ElectionManifest::new("Reject the proposal?", Schedule::default());
+    registration.ownership_signature = Some(sign_ownership(
+        &signing_keys[0],
+        &voting_keys[0],
+        &other_manifest.digest().to_elements(),
+    ));
+    assert_eq!(
+        registar.add_registration(registration),
+        Err(RegistarError::InvalidOwnershipSig),
+        "Registration signed for another election should be rejected."
+    );
+
+    let ownership_signature = sign_ownership(&signing_keys[0], &voting_keys[0], &manifest_digest);
+    assert_eq!(
+        recover_owner(&voting_keys[0], &manifest_digest, &ownership_signature),
+        Some(addresses[0])
+    );
+    registration.ownership_signature = Some(ownership_signature);
+    assert_eq!(registar.add_registration(registration), Ok(()));
+
+    // the ownership signatures survive serialization
+    let restored = VoterRegistar::from_bytes(&registar.to_bytes(), tree).unwrap();
+    assert!(restored.require_ownership);
+    assert_eq!(restored.ownership_signatures, registar.ownership_signatures);
+    assert_eq!(restored.voting_keys, registar.voting_keys);
+}
+
+#[test]
+fn register_test_signing_domain() {
+    use crate::{
+        merkle::build_merkle_tree_with_keys,
+        schnorr::{random_key_pairs, sign_message_in_domain, SigningDomain},
+        utils::address::Address,
+        verifier::verify_signature_sequence_proof_in_domain,
+    };
+
+    let domain = SigningDomain::new(1, [7u8; 16]);
+    let (secret_keys, voting_keys) = random_key_pairs(2);
+    let addresses = vec![Address::random(), Address::random()];
+    let (tree, hash_indices) = build_merkle_tree_with_keys(&voting_keys);
+    let registration = |i: usize, domain: &SigningDomain| Registration {
+        voting_key: voting_keys[i],
+        merkle_branch: tree.branch(hash_indices[i]),
+        hash_index: hash_indices[i],
+        signature: sign_message_in_domain(
+            secret_keys[i],
+            &voting_keys[i],
+            addresses[i],
+            domain,
+            &mut OsRng,
+        ),
+        address: addresses[i],
+        ownership_signature: None,
+    };
+
+    // registrations signed for another chain or election are rejected
+    let mut registar = VoterRegistar::new(tree.clone(), 2);
+    registar.bind_domain(domain);
+    assert_eq!(
+        registar.add_registration(registration(0, &SigningDomain::default())),
+        Err(RegistarError::InvalidSchnorrSig)
+    );
+    assert_eq!(
+        registar.add_registration(registration(0, &SigningDomain::new(2, domain.election_id))),
+        Err(RegistarError::InvalidSchnorrSig)
+    );
+    assert_eq!(registar.add_registration(registration(0, &domain)), Ok(()));
+    assert_eq!(registar.add_registration(registration(1, &domain)), Ok(()));
+
+    // proofs only verify against the domain of the registar
+    let mut elg_root_bytes = vec![];
+    Serializable::write_batch_into(&registar.elg_root, &mut elg_root_bytes);
+    let register_proof = registar.get_register_proof().unwrap();
+    let options = RegisterVerification::new().with_domain(domain);
+    assert!(
+        verify_register_proof_with(&registar.elg_root, &options, &register_proof)
+            .into_outcome()
+            .unwrap()
+    );
+    assert!(!verify_register_proof(&elg_root_bytes, &register_proof).unwrap());
+
+    let sequence_digest = registar.sequence_digest();
+    let sequence_proof = registar.get_signature_sequence_proof().unwrap();
+    assert!(verify_signature_sequence_proof_in_domain(
+        &sequence_digest,
+        &domain.to_bytes(),
+        &sequence_proof
+    )
+    .unwrap());
+    assert!(!verify_signature_sequence_proof(&sequence_digest, &sequence_proof).unwrap());
+}
+
+#[test]
+fn register_test_replacement_chaining() {
+    use super::rejection::{RejectionReason, Submitter};
+    use crate::{
+        merkle::build_merkle_tree_with_keys,
+        schnorr::{random_key_pairs, sign_message_in_domain, SigningDomain},
+        utils::address::Address,
+    };
+
+    let (secret_keys, voting_keys) = random_key_pairs(2);
+    let (tree, hash_indices) = build_merkle_tree_with_keys(&voting_keys);
+    let registration = |address: Address| Registration {
+        voting_key: voting_keys[0],
+        merkle_branch: tree.branch(hash_indices[0]),
+        hash_index: hash_indices[0],
+        signature: sign_message_in_domain(
+            secret_keys[0],
+            &voting_keys[0],
+            address,
+            &SigningDomain::default(),
+            &mut OsRng,
+        ),
+        address,
+        ownership_signature: None,
+    };
+    let first = registration(Address::random());
+    let second = registration(Address::random());
+    let third = registration(Address::random());
+
+    let mut registar = VoterRegistar::new(tree.clone(), 2);
+    assert_eq!(registar.add_registration(first), Ok(()));
+    // submitting the same registration again does not replace anything
+    assert_eq!(registar.add_registration(first), Ok(()));
+
+    // the voter replaces their registration with one chained to it
+    assert_eq!(
+        registar.add_registration(second),
+        Err(RegistarError::UnchainedReplacement)
+    );
+    let second_chain = second.sign_replacement(secret_keys[0], &first.signature);
+    assert_eq!(registar.replace_registration(second, second_chain), Ok(()));
+    assert_eq!(registar.addresses, vec![second.address]);
+
+    // an attacker cannot restore the earlier registration of the voter, even with
+    // the replacement signature published alongside the current one
+    assert_eq!(
+        registar.add_registration(first),
+        Err(RegistarError::UnchainedReplacement)
+    );
+    assert_eq!(
+        registar.replace_registration(first, second_chain),
+        Err(RegistarError::UnchainedReplacement)
+    );
+
+    // replacements cannot be replayed once the registration is replaced again
+    let third_chain = third.sign_replacement(secret_keys[0], &second.signature);
+    assert_eq!(registar.replace_registration(third, third_chain), Ok(()));
+    assert_eq!(
+        registar.replace_registration(second, second_chain),
+        Err(RegistarError::UnchainedReplacement)
+    );
+    assert_eq!(registar.addresses, vec![third.address]);
+    assert_eq!(registar.signatures, vec![third.signature]);
+    assert_eq!(
+        registar
+            .rejections
+            .by_submitter(Submitter::Address(first.address))
+            .map(|rejection| rejection.reason)
+            .collect::<Vec<_>>(),
+        vec![
+            RejectionReason::UnchainedReplacement,
+            RejectionReason::UnchainedReplacement
+        ]
+    );
+}
+
+#[test]
+fn register_test_allowlist() {
+    let example = VoterRegistar::get_example(2);
+    let mut registar = VoterRegistar::new(example.tree.clone(), 2);
+    let mut policy = Allowlist::new(&example.addresses[..1]);
+    let registrations = (0..2)
+        .map(|i| Registration {
+            voting_key: example.voting_keys[i],
+            merkle_branch: example.merkle_branch(i),
+            hash_index: example.hash_indices[i],
+            signature: example.signatures[i],
+            address: example.addresses[i],
+            ownership_signature: None,
+        })
+        .collect::<Vec<Registration>>();
+    assert_eq!(
+        registar.add_registration_with_policy(registrations[0], &mut policy),
+        Ok(())
+    );
+    assert_eq!(
+        registar.add_registration_with_policy(registrations[1], &mut policy),
+        Err(RegistarError::Rejected),
+        "Registration from an address outside the allowlist should be rejected."
+    );
+}
+
+#[test]
+fn register_test_proof_of_work() {
+    let example = VoterRegistar::get_example(2);
+    let mut registar = VoterRegistar::new(example.tree.clone(), 2);
+    let registration = Registration {
+        voting_key: example.voting_keys[0],
+        merkle_branch: example.merkle_branch(0),
+        hash_index: example.hash_indices[0],
+        signature: example.signatures[0],
+        address: example.addresses[0],
+        ownership_signature: None,
+    };
+    let leading_zeros = ProofOfWork::leading_zeros(Submission::Registration(&registration));
+
+    let mut policy = ProofOfWork::new(leading_zeros + 1);
+    assert_eq!(
+        registar.add_registration_with_policy(registration, &mut policy),
+        Err(RegistarError::Rejected),
+        "Registration below the difficulty should be rejected."
+    );
+    let mut policy = ProofOfWork::new(leading_zeros);
+    assert_eq!(
+        registar.add_registration_with_policy(registration, &mut policy),
+        Ok(())
+    );
+}
+
+#[test]
+fn cast_test_admission_policies() {
+    use crate::schnorr::random_key_pairs;
+
+    let (secret_keys, voting_keys) = random_key_pairs(4);
+    let mut collector = VoteCollector::new(voting_keys.clone());
+
+    // the quota is consulted before the duplicate check
+    let mut policy = SubmissionQuota::new(1);
+    let encrypted_vote = EncryptedVote::cast(0, secret_keys[0], &voting_keys, true);
+    assert_eq!(
+        collector.add_encrypted_vote_with_policy(encrypted_vote, &mut policy),
+        Ok(())
+    );
+    assert_eq!(
+        collector.add_encrypted_vote_with_policy(encrypted_vote, &mut policy),
+        Err(CollectorError::Rejected)
+    );
+
+    // only verified encrypted votes are charged, so a forged encrypted vote
+    // does not exhaust the quota of the voter it claims to come from
+    let forged_vote = EncryptedVote::cast(3, secret_keys[2], &voting_keys, true);
+    assert_eq!(
+        collector.add_encrypted_vote_with_policy(forged_vote, &mut policy),
+        Err(CollectorError::InvalidEncryptedVote)
+    );
+    let encrypted_vote = EncryptedVote::cast(3, secret_keys[3], &voting_keys, true);
+    assert_eq!(
+        collector.add_encrypted_vote_with_policy(encrypted_vote, &mut policy),
+        Ok(())
+    );
+
+    // encrypted votes are always admitted by an allowlist
+    let mut policy = Allowlist::new(&[]);
+    let encrypted_vote = EncryptedVote::cast(1, secret_keys[1], &voting_keys, false);
+    assert_eq!(
+        collector.add_encrypted_vote_with_policy(encrypted_vote, &mut policy),
+        Ok(())
+    );
+
+    let encrypted_vote = EncryptedVote::cast(2, secret_keys[2], &voting_keys, true);
+    let leading_zeros = ProofOfWork::leading_zeros(Submission::EncryptedVote(&encrypted_vote));
+    let mut policy = ProofOfWork::new(leading_zeros + 1);
+    assert_eq!(
+        collector.add_encrypted_vote_with_policy(encrypted_vote, &mut policy),
+        Err(CollectorError::Rejected)
+    );
+    let mut policy = ProofOfWork::new(leading_zeros);
+    assert_eq!(
+        collector.add_encrypted_vote_with_policy(encrypted_vote, &mut policy),
+        Ok(())
+    );
+}
+
+#[test]
+fn register_test_snapshot_derives_branches() {
+    let example = VoterRegistar::get_example(2);
+    let mut registar = VoterRegistar::new(example.tree.clone(), 2);
+    for i in 0..2 {
+        let registration = Registration {
+            voting_key: example.voting_keys[i],
+            merkle_branch: example.merkle_branch(i),
+            hash_index: example.hash_indices[i],
+            signature: example.signatures[i],
+            address: example.addresses[i],
+            ownership_signature: None,
+        };
+        assert_eq!(registar.add_registration(registration), Ok(()));
+    }
+
+    // snapshots do not carry Merkle branches
+    let snapshot = registar.to_bytes();
+    assert!(snapshot.len() < 2 * TREE_DEPTH * DIGEST_SIZE * 8);
+
+    let restored = VoterRegistar::from_bytes(&snapshot, example.tree.clone()).unwrap();
+    assert_eq!(restored.hash_indices, registar.hash_indices);
+    assert_eq!(restored.merkle_branches(), registar.merkle_branches());
+
+    // the snapshot cannot be restored with another tree
+    let other_tree = VoterRegistar::get_example(2).tree;
+    assert!(VoterRegistar::from_bytes(&snapshot, other_tree).is_err());
+}
+
+#[test]
+fn register_test_snapshot_migration() {
+    let registar = VoterRegistar::get_example(2);
+    let write_registrations = |bytes: &mut Vec<u8>, format: wire::WireFormat| {
+        for i in 0..2 {
+            bytes.write_u8_slice(&compress_point(&registar.voting_keys[i]));
+            Serializable::write_batch_into(&registar.merkle_branch(i), bytes);
+            match format {
+                wire::WireFormat::Legacy => bytes.write_u64(registar.hash_indices[i] as u64),
+                wire::WireFormat::Compact => write_varint(bytes, registar.hash_indices[i] as u64),
+            }
+            Serializable::write_batch_into(&registar.signatures[i].0, bytes);
+            bytes.write(registar.signatures[i].1);
+            bytes.write_u8_slice(registar.addresses[i].as_bytes());
+        }
+    };
+
+    // legacy dumps have u32 counts and Merkle branches, and no ownership section
+    let mut legacy = vec![];
+    legacy.write_u32(2);
+    Serializable::write_batch_into(&registar.elg_root, &mut legacy);
+    legacy.write_u32(2);
+    write_registrations(&mut legacy, wire::WireFormat::Legacy);
+
+    // version 1 dumps have varint counts and Merkle branches
+    let mut version_1 = vec![];
+    version_1.write_u32(wire::WIRE_TAG);
+    version_1.write_u8(1);
+    write_varint(&mut version_1, 2);
+    Serializable::write_batch_into(&registar.elg_root, &mut version_1);
+    write_varint(&mut version_1, 2);
+    write_registrations(&mut version_1, wire::WireFormat::Compact);
+    version_1.write_u8(0);
+    version_1.write_u8_slice(&[0, 0]);
+
+    // both are migrated to the current format
+    for dump in [legacy, version_1].iter() {
+        let restored = VoterRegistar::from_bytes(dump, registar.tree.clone()).unwrap();
+        assert_eq!(restored.voting_keys, registar.voting_keys);
+        assert_eq!(restored.hash_indices, registar.hash_indices);
+        assert_eq!(restored.signatures, registar.signatures);
+        assert_eq!(restored.addresses, registar.addresses);
+        assert_eq!(restored.to_bytes(), registar.to_bytes());
+    }
+
+    // snapshots are canonical, so trailing bytes are rejected
+    let mut snapshot = registar.to_bytes();
+    snapshot.push(0);
+    assert_eq!(
+        VoterRegistar::from_bytes(&snapshot, registar.tree.clone()).unwrap_err(),
+        DeserializationError::UnconsumedBytes
+    );
+}
+
+#[test]
+fn cast_test_batch_verification() {
+    let (example, (_, encrypted_votes, proof_scalars, proof_points)) =
+        CDSExample::new(build_options(1), 4);
+    let mut collector = VoteCollector::new(example.voting_keys);
+    let mut queue = CastQueue::new();
+    for i in 0..4 {
+        queue.push(EncryptedVote::new(
+            i,
+            encrypted_votes[i],
+            proof_points[i],
+            proof_scalars[i],
+        ));
+    }
+    let results = queue.flush(&mut collector);
+    assert!(queue.is_empty());
+    assert!(
+        results.iter().all(|result| result.is_ok()),
+        "All encrypted votes should be valid."
+    );
+    assert_eq!(collector.num_valid_votes, 4);
+}
+
+#[test]
+fn cast_test_batch_verification_with_beacon() {
+    let (example, (_, encrypted_votes, proof_scalars, proof_points)) =
+        CDSExample::new(build_options(1), 4);
+    let mut collector = VoteCollector::new(example.voting_keys);
+    let mut beacon = BlockhashBeacon::new();
+    beacon.insert(100, [9u8; 32]);
+    let mut queue = CastQueue::new();
+    for i in 0..4 {
+        queue.push(EncryptedVote::new(
+            i,
+            encrypted_votes[i],
+            proof_points[i],
+            proof_scalars[i],
+        ));
+    }
+
+    // the weights must be drawn from a round published after the queue was closed
+    assert_eq!(
+        queue.flush_with_beacon(&mut collector, &beacon, 100),
+        Err(BeaconError::PrematureRound(100))
+    );
+    queue.close(100);
+    assert_eq!(
+        queue.flush_with_beacon(&mut collector, &beacon, 100),
+        Err(BeaconError::PrematureRound(100))
+    );
+    queue.close(99);
+    queue.push(EncryptedVote::new(
+        3,
+        encrypted_votes[3],
+        proof_points[3],
+        proof_scalars[3],
+    ));
+    assert_eq!(
+        queue.flush_with_beacon(&mut collector, &beacon, 100),
+        Err(BeaconError::PrematureRound(100))
+    );
+    queue.close(99);
+
+    // the weights cannot be drawn before the round is known
+    assert_eq!(
+        queue.flush_with_beacon(&mut collector, &beacon, 101),
+        Err(BeaconError::UnknownRound(101))
+    );
+    let results = queue
+        .flush_with_beacon(&mut collector, &beacon, 100)
+        .unwrap();
+    assert_eq!(results.len(), 5);
+    assert!(results[..4].iter().all(|result| result.is_ok()));
+    assert_eq!(results[4], Err(CollectorError::InvalidEncryptedVote));
+    assert_eq!(collector.num_valid_votes, 4);
+}
+
+#[test]
+fn cast_test_batch_verification_wrong_proof() {
+    let (example, (_, encrypted_votes, mut proof_scalars, proof_points)) =
+        CDSExample::new(build_options(1), 4);
+    proof_scalars[2][2] += proof_scalars[2][3];
+    let mut collector = VoteCollector::new(example.voting_keys);
+    let mut queue = CastQueue::new();
+    for i in 0..4 {
+        queue.push(EncryptedVote::new(
+            i,
+            encrypted_votes[i],
+            proof_points[i],
+            proof_scalars[i],
+        ));
+    }
+    let results = queue.flush(&mut collector);
+    for (i, result) in results.into_iter().enumerate() {
+        if i == 2 {
+            assert_eq!(result, Err(CollectorError::InvalidEncryptedVote));
+        } else {
+            assert!(result.is_ok(), "Other encrypted votes should be valid.");
+        }
+    }
+    assert_eq!(collector.num_valid_votes, 3);
+}
+
+#[test]
+fn cast_test_rejection_log() {
+    use super::rejection::{payload_digest, RejectionReason, Submitter};
+
+    let (example, (_, encrypted_votes, mut proof_scalars, proof_points)) =
+        CDSExample::new(build_options(1), 4);
+    let valid_ballot = EncryptedVote::new(1, encrypted_votes[1], proof_points[1], proof_scalars[1]);
+    proof_scalars[2][2] += proof_scalars[2][3];
+    let invalid_ballot =
+        EncryptedVote::new(2, encrypted_votes[2], proof_points[2], proof_scalars[2]);
+    let mut collector = VoteCollector::new(example.voting_keys);
+
+    assert_eq!(
+        collector.add_encrypted_vote(invalid_ballot),
+        Err(CollectorError::InvalidEncryptedVote)
+    );
+    assert_eq!(collector.add_encrypted_vote(valid_ballot), Ok(()));
+    assert_eq!(collector.rejections.len(), 1);
+
+    // a second ballot of the same voter is logged, including when verified in batch
+    let mut queue = CastQueue::new();
+    queue.push(valid_ballot);
+    assert_eq!(
+        queue.flush(&mut collector),
+        vec![Err(CollectorError::InvalidEncryptedVote)]
+    );
+    assert_eq!(
+        collector.add_commitment(0, [1u8; 32]),
+        Err(CollectorError::InvalidCommitment)
+    );
+
+    let rejections = collector.rejections.rejections();
+    assert_eq!(rejections.len(), 3);
+    assert_eq!(rejections[0].submitter, Submitter::Voter(2));
+    assert_eq!(
+        rejections[0].payload_digest,
+        payload_digest(&invalid_ballot.to_bytes())
+    );
+    assert_eq!(rejections[1].submitter, Submitter::Voter(1));
+    assert_eq!(rejections[1].reason, RejectionReason::InvalidEncryptedVote);
+    assert_eq!(rejections[2].reason, RejectionReason::InvalidCommitment);
+    assert_eq!(rejections[2].payload_digest, payload_digest(&[1u8; 32]));
+}
+
+#[test]
+fn cast_test_invalid_ballot_reporting() {
+    use super::publication::BallotList;
+
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let (secret_keys, voting_keys): (Vec<_>, Vec<_>) =
+        (0..4).map(|_| random_key_pair_with_rng(&mut rng)).unzip();
+    let mut collector = VoteCollector::new(voting_keys.clone());
+    collector.report_invalid_ballots = true;
+
+    // An invalid ballot is not reported unless signed by its voter
+    let mut invalid_ballot =
+        EncryptedVote::cast_with_rng(2, secret_keys[2], &voting_keys, true, &mut rng);
+    invalid_ballot.proof_scalars[2] += invalid_ballot.proof_scalars[3];
+    assert!(collector.add_encrypted_vote(invalid_ballot).is_err());
+    assert_eq!(collector.num_excluded_ballots(), 0);
+    let forged_ballot = sign_ballot(invalid_ballot, secret_keys[3]);
+    assert!(collector.add_encrypted_vote(forged_ballot).is_err());
+    assert_eq!(collector.num_excluded_ballots(), 0);
+
+    for i in 0..4 {
+        let encrypted_vote = if i == 2 {
+            sign_ballot(invalid_ballot, secret_keys[2])
+        } else {
+            EncryptedVote::cast_with_rng(i, secret_keys[i], &voting_keys, i % 2 == 0, &mut rng)
+        };
+        let result = collector.add_encrypted_vote(encrypted_vote);
+        assert_eq!(result.is_ok(), i != 2);
+    }
+    assert_eq!(collector.num_valid_votes, 3);
+    assert_eq!(collector.num_excluded_ballots(), 1);
+    assert_eq!(
+        collector.exclusion_reason(2),
+        Some(CdsVerificationError::InvalidA1)
+    );
+    assert_eq!(collector.exclusion_reason(1), None);
+
+    // The excluded ballot survives serialization
+    let restored = VoteCollector::from_bytes(&collector.to_bytes()).unwrap();
+    assert_eq!(restored.num_valid_votes, 3);
+    assert_eq!(restored.num_excluded_ballots(), 1);
+
+    // and publication with the signature of its voter, without which
+    // observers do not accept the exclusion
+    let ballot_list =
+        BallotList::from_bytes(&collector.export_ballot_list().unwrap().to_bytes()).unwrap();
+    let mut observer = VoteCollector::new(voting_keys.clone());
+    assert!(observer
+        .import_ballot_list(&ballot_list)
+        .iter()
+        .all(|result| result.is_ok()));
+    assert_eq!(observer.num_excluded_ballots(), 1);
+    for signature in [None, forged_ballot.signature()].iter() {
+        let mut unsigned_list = ballot_list.clone();
+        unsigned_list.ballots[2].signature = *signature;
+        let mut observer = VoteCollector::new(voting_keys.clone());
+        assert_eq!(
+            observer.import_ballot_list(&unsigned_list)[2],
+            Err(CollectorError::InvalidEncryptedVote)
+        );
+        assert_eq!(observer.num_excluded_ballots(), 0);
+    }
+
+    let cast_proof = collector.get_cast_proof().unwrap();
+    let mut voting_keys_bytes = vec![];
+    voting_keys_bytes.write_u8_slice(&(voting_keys.len() as u32).to_be_bytes());
+    for voting_key in voting_keys.iter() {
+        voting_keys_bytes.write_u8_slice(&compress_point(voting_key));
+    }
+    assert!(verify_cast_proof(&voting_keys_bytes, &cast_proof).unwrap());
+
+    // The bitmap follows the compact public inputs
+    let bitmap_start = 4 + 4 * BYTES_PER_CAST_PUB_INPUTS;
+    assert_eq!(cast_proof[bitmap_start], 0b1011);
+
+    // A valid ballot cannot be flagged as invalid
+    let mut forged_proof = cast_proof.clone();
+    forged_proof[bitmap_start] = 0b1010;
+    assert!(!verify_cast_proof(&voting_keys_bytes, &forged_proof).unwrap_or(false));
+
+    // The signature of the excluded ballot follows its scalars
+    let signature_start = bitmap_start + 1 + PROOF_NUM_SCALARS * BYTES_PER_SCALAR;
+    let mut forged_proof = cast_proof.clone();
+    forged_proof[signature_start + BYTES_PER_SIGNATURE - 1] ^= 1;
+    assert!(!verify_cast_proof(&voting_keys_bytes, &forged_proof).unwrap_or(false));
+}
+
+/// Sign the ballot with the given secret key in the unbound election context
+fn sign_ballot(encrypted_vote: EncryptedVote, secret_key: Scalar) -> EncryptedVote {
+    let digest = signed_ballot_digest(
+        encrypted_vote.voter_index(),
+        &projective_to_elements(encrypted_vote.encrypted_vote),
+        &concat_proof_points(&encrypted_vote.proof_points),
+        &encrypted_vote.proof_scalars,
+        &UNBOUND_CONTEXT,
+    );
+    encrypted_vote.with_signature(sign_digest(secret_key, &digest))
+}
+
+#[test]
+fn cast_test_commit_reveal() {
+    let (example, (_, encrypted_votes, proof_scalars, proof_points)) =
+        CDSExample::new(build_options(1), 2);
+    let mut collector = VoteCollector::with_commit_phase(example.voting_keys.clone());
+    let ballots = (0..2)
+        .map(|i| EncryptedVote::new(i, encrypted_votes[i], proof_points[i], proof_scalars[i]))
+        .collect::<Vec<EncryptedVote>>();
+
+    // Reveal without commitment is rejected
+    assert_eq!(
+        collector.add_encrypted_vote(ballots[0]),
+        Err(CollectorError::InvalidReveal)
+    );
+
+    for i in 0..2 {
+        let commitment =
+            compute_vote_commitment(i, &example.encrypted_votes[i], &example.proof_points[i]);
+        assert_eq!(collector.add_commitment(i, commitment), Ok(()));
+    }
+    assert_eq!(
+        collector.add_commitment(0, [1u8; 32]),
+        Err(CollectorError::InvalidCommitment),
+        "Commitments cannot be replaced."
+    );
+
+    for ballot in ballots {
+        assert_eq!(collector.add_encrypted_vote(ballot), Ok(()));
+    }
+
+    let cast_proof = collector.get_cast_proof().unwrap();
+    let mut voting_keys = vec![];
+    voting_keys.write_u8_slice(&(collector.voting_keys.len() as u32).to_be_bytes());
+    for voting_key in collector.voting_keys.iter() {
+        voting_keys.write_u8_slice(&compress_point(voting_key));
+    }
+    let verified = verify_cast_proof(&voting_keys, &cast_proof);
+    assert!(
+        verified.is_ok(),
+        "Serialized proof should be deserialized with no error."
+    );
+    assert!(
+        verified.unwrap(),
+        "Cast proof with commitments should be valid."
+    );
+
+    // encrypted votes are checked against the commitments submitted on smart contract
+    let commitments = collector
+        .commitments
+        .iter()
+        .map(|commitment| commitment.unwrap())
+        .collect::<Vec<_>>();
+    let options = CastVerification::new().with_commitments(commitments.clone());
+    let report = verify_cast_proof_with(&voting_keys, &options, &cast_proof);
+    assert!(report.is_valid());
+    let mut other_commitments = commitments.clone();
+    other_commitments[1][0] ^= 1;
+    let other_options = CastVerification::new().with_commitments(other_commitments);
+    let report = verify_cast_proof_with(&voting_keys, &other_options, &cast_proof);
+    assert_eq!(report.commitments, Some(false));
+
+    // a proof leaving the commitments out is rejected
+    let mut unbound_collector = VoteCollector::new(example.voting_keys.clone());
+    for i in 0..2 {
+        let ballot = EncryptedVote::new(i, encrypted_votes[i], proof_points[i], proof_scalars[i]);
+        unbound_collector.add_encrypted_vote(ballot).unwrap();
+    }
+    let unbound_proof = unbound_collector.get_cast_proof().unwrap();
+    assert!(verify_cast_proof(&voting_keys, &unbound_proof).unwrap());
+    let report = verify_cast_proof_with(&voting_keys, &options, &unbound_proof);
+    assert_eq!(report.commitments, Some(false));
+}
+
+#[test]
+fn cast_test_commit_reveal_all_paths() {
+    let (example, (_, encrypted_votes, proof_scalars, proof_points)) =
+        CDSExample::new(build_options(1), 2);
+    let mut source = VoteCollector::new(example.voting_keys.clone());
+    for i in 0..2 {
+        let ballot = EncryptedVote::new(i, encrypted_votes[i], proof_points[i], proof_scalars[i]);
+        source.add_encrypted_vote(ballot).unwrap();
+    }
+    let committed_collector = || {
+        let mut collector = VoteCollector::with_commit_phase(example.voting_keys.clone());
+        let commitment =
+            compute_vote_commitment(0, &example.encrypted_votes[0], &example.proof_points[0]);
+        collector.add_commitment(0, commitment).unwrap();
+        collector
+    };
+    let expected = vec![Ok(()), Err(CollectorError::InvalidReveal)];
+
+    // queued ballots
+    let mut collector = committed_collector();
+    let mut queue = CastQueue::new();
+    for i in 0..2 {
+        queue.push(EncryptedVote::new(
+            i,
+            encrypted_votes[i],
+            proof_points[i],
+            proof_scalars[i],
+        ));
+    }
+    assert_eq!(queue.flush(&mut collector), expected);
+    assert_eq!(collector.num_valid_votes, 1);
+
+    // ballots synced from another replica
+    let mut collector = committed_collector();
+    let ballots = source.export_ballots(&[0, 1]);
+    assert_eq!(collector.import_ballots(&ballots), expected);
+    assert_eq!(collector.num_valid_votes, 1);
+
+    // ballots imported from a published list
+    let mut collector = committed_collector();
+    let ballot_list = source.export_ballot_list().unwrap();
+    assert_eq!(collector.import_ballot_list(&ballot_list), expected);
+    assert_eq!(collector.num_valid_votes, 1);
+}
+
+#[test]
+fn sync_test_transfer_missing_ballots() {
+    let primary = VoteCollector::get_example(4);
+    let mut standby = VoteCollector::new(primary.voting_keys.clone());
+    assert_ne!(primary.state_tree().root(), standby.state_tree().root());
+
+    // Standby already received the ballot of voter 1
+    let results = standby.import_ballots(&primary.export_ballots(&[1]));
+    assert!(results.iter().all(|result| result.is_ok()));
+
+    let missing = primary.state_tree().diff(&standby.state_tree());
+    assert_eq!(missing, vec![0, 2, 3]);
+    let results = standby.import_ballots(&primary.export_ballots(&missing));
+    assert!(
+        results.iter().all(|result| result.is_ok()),
+        "Transferred ballots should be valid."
+    );
+    assert_eq!(primary.state_tree().root(), standby.state_tree().root());
+    assert_eq!(standby.num_valid_votes, 4);
+}
+
+#[test]
+fn sync_test_transfer_commitments_and_exclusions() {
+    use super::sync::BallotTransfer;
+
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let (secret_keys, voting_keys): (Vec<_>, Vec<_>) =
+        (0..3).map(|_| random_key_pair_with_rng(&mut rng)).unzip();
+    let mut primary = VoteCollector::with_commit_phase(voting_keys.clone());
+    primary.report_invalid_ballots = true;
+    let mut ballots = (0..3)
+        .map(|i| EncryptedVote::cast_with_rng(i, secret_keys[i], &voting_keys, true, &mut rng))
+        .collect::<Vec<_>>();
+    ballots[1].proof_scalars[2] += ballots[1].proof_scalars[3];
+    ballots[1] = sign_ballot(ballots[1], secret_keys[1]);
+    for ballot in ballots.iter() {
+        let commitment = compute_vote_commitment(
+            ballot.voter_index(),
+            &projective_to_elements(ballot.encrypted_vote),
+            &concat_proof_points(&ballot.proof_points),
+        );
+        primary
+            .add_commitment(ballot.voter_index(), commitment)
+            .unwrap();
+    }
+    // voter 2 has committed but not revealed yet
+    assert!(primary.add_encrypted_vote(ballots[0]).is_ok());
+    assert!(primary.add_encrypted_vote(ballots[1]).is_err());
+    assert_eq!(primary.num_excluded_ballots(), 1);
+
+    // commitments and the signed exclusion are transferred in compressed form
+    let mut standby = VoteCollector::with_commit_phase(voting_keys.clone());
+    let missing = primary.state_tree().diff(&standby.state_tree());
+    assert_eq!(missing, vec![0, 1, 2]);
+    let transfers = primary
+        .export_ballots(&missing)
+        .iter()
+        .map(|transfer| {
+            let bytes = transfer.to_bytes();
+            BallotTransfer::read_from(&mut SliceReader::new(&bytes)).unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(transfers, primary.export_ballots(&missing));
+    assert!(standby
+        .import_ballots(&transfers)
+        .iter()
+        .all(|result| result.is_ok()));
+    assert_eq!(primary.state_tree().root(), standby.state_tree().root());
+    assert_eq!(standby.commitments, primary.commitments);
+    assert_eq!(standby.num_excluded_ballots(), 1);
+
+    // voter 2 then reveals against the transferred commitment
+    assert!(standby.add_encrypted_vote(ballots[2]).is_ok());
+
+    // an exclusion without the signature of its voter is not transferred
+    let mut unsigned = transfers[1];
+    unsigned.ballot.as_mut().unwrap().signature = None;
+    let mut standby = VoteCollector::with_commit_phase(voting_keys);
+    assert_eq!(
+        standby.import_ballots(&[unsigned]),
+        vec![Err(CollectorError::InvalidEncryptedVote)]
+    );
+    assert_eq!(standby.num_excluded_ballots(), 0);
+}
+
+#[test]
+fn publication_test_ballot_list() {
+    use super::publication::BallotList;
+
+    let mut collector = VoteCollector::get_example(4);
+    let ballot_list = collector.export_ballot_list().unwrap();
+    let bytes = ballot_list.to_bytes();
+    let restored = BallotList::from_bytes(&bytes).unwrap();
+    assert_eq!(restored, ballot_list);
+    assert!(BallotList::from_bytes(&[bytes.as_slice(), &[0u8]].concat()).is_err());
+
+    // encrypted votes are published in the compressed form the cast proof commits to
+    let cast_proof = collector.get_cast_proof().unwrap();
+    for (i, ballot) in restored.ballots.iter().enumerate() {
+        let compressed_vote = compress_point(&ballot.encrypted_vote);
+        let offset = 4 + i * compressed_vote.len();
+        assert_eq!(
+            &cast_proof[offset..offset + compressed_vote.len()],
+            &compressed_vote[..]
+        );
+    }
+
+    // an observer rebuilds the ballots of the collector from the voting keys
+    let mut observer = VoteCollector::new(collector.voting_keys.clone());
+    let results = observer.import_ballot_list(&restored);
+    assert!(results.iter().all(|result| result.is_ok()));
+    assert_eq!(observer.encrypted_votes, collector.encrypted_votes);
+    assert_eq!(observer.num_valid_votes, 4);
+
+    // a valid ballot cannot be flagged as invalid
+    let mut flagged = restored.clone();
+    flagged.ballots[0].valid = false;
+    let mut observer = VoteCollector::new(collector.voting_keys.clone());
+    let results = observer.import_ballot_list(&flagged);
+    assert_eq!(results[0], Err(CollectorError::InvalidEncryptedVote));
+    assert_eq!(observer.num_excluded_ballots(), 0);
+}
+
+#[test]
+fn shard_test_stitched_proofs() {
+    let collector = VoteCollector::get_example(4);
+    let mut voting_keys = vec![];
+    voting_keys.write_u8_slice(&(collector.voting_keys.len() as u32).to_be_bytes());
+    for voting_key in collector.voting_keys.iter() {
+        voting_keys.write_u8_slice(&compress_point(voting_key));
+    }
+    let shard_proofs = vec![
+        collector.get_cast_proof_shard(0, 2).unwrap(),
+        collector.get_cast_proof_shard(2, 2).unwrap(),
+    ];
+
+    let stitched_proof = stitch_cast_proofs(&shard_proofs);
+    let verified = verify_cast_proofs_sharded(&voting_keys, &stitched_proof);
+    assert!(
+        verified.is_ok(),
+        "Serialized proof should be deserialized with no error."
+    );
+    assert!(verified.unwrap(), "Stitched STARK proofs should be valid.");
+
+    // shards must cover all voters
+    let stitched_proof = stitch_cast_proofs(&shard_proofs[1..]);
+    let verified = verify_cast_proofs_sharded(&voting_keys, &stitched_proof);
+    assert!(
+        !verified.unwrap(),
+        "Stitched STARK proofs should be invalid."
+    );
+
+    assert_eq!(
+        collector.get_cast_proof_shard(3, 2),
+        Err(CollectorError::InvalidShard)
+    );
+}
+
+#[test]
+fn cast_test_resumable_proof() {
+    use crate::utils::checkpoint::PROOF_FILE;
+    use rand_core::RngCore;
+
+    let dir = std::env::temp_dir().join(format!("openvote-cast-{}", OsRng.next_u32()));
+    let collector = VoteCollector::get_example(2);
+    let mut voting_keys = vec![];
+    voting_keys.write_u8_slice(&(collector.voting_keys.len() as u32).to_be_bytes());
+    for voting_key in collector.voting_keys.iter() {
+        voting_keys.write_u8_slice(&compress_point(voting_key));
+    }
+
+    let cast_proof = collector.clone().get_cast_proof_resumable(&dir).unwrap();
+    assert!(verify_cast_proof(&voting_keys, &cast_proof).unwrap());
+
+    // a run interrupted after the trace was saved resumes from the trace
+    std::fs::remove_file(dir.join(PROOF_FILE)).unwrap();
+    let cast_proof = collector.clone().get_cast_proof_resumable(&dir).unwrap();
+    assert!(verify_cast_proof(&voting_keys, &cast_proof).unwrap());
+
+    // a completed run returns the saved proof
+    assert_eq!(
+        collector.clone().get_cast_proof_resumable(&dir).unwrap(),
+        cast_proof
+    );
+
+    // checkpoints of other elections are ignored
+    let mut other = VoteCollector::get_example(2);
+    let mut other_voting_keys = vec![];
+    other_voting_keys.write_u8_slice(&(other.voting_keys.len() as u32).to_be_bytes());
+    for voting_key in other.voting_keys.iter() {
+        other_voting_keys.write_u8_slice(&compress_point(voting_key));
+    }
+    let other_proof = other.get_cast_proof_resumable(&dir).unwrap();
+    assert!(verify_cast_proof(&other_voting_keys, &other_proof).unwrap());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn proof_store_test_cached_proofs() {
+    use crate::utils::proof_store::{store_digest, ProofStore, BLOBS_DIR};
+    use rand_core::RngCore;
+
+    let dir = std::env::temp_dir().join(format!("openvote-proofs-{}", OsRng.next_u32()));
+    let store = ProofStore::open(&dir).unwrap();
+
+    // identical proofs are stored once, and corrupted blobs are ignored
+    let digest = store.put(b"proof").unwrap();
+    assert_eq!(store.put(b"proof").unwrap(), digest);
+    assert_eq!(store.get(&digest).unwrap(), Some(b"proof".to_vec()));
+    assert_eq!(store.get(&store_digest(b"other")).unwrap(), None);
+    std::fs::write(dir.join(BLOBS_DIR).join(hex::encode(digest)), b"tampered").unwrap();
+    assert_eq!(store.get(&digest).unwrap(), None);
+
+    // a registar restored after a restart reuses the stored register proof
+    let mut registar = VoterRegistar::get_example(2);
+    let register_proof = registar.get_register_proof_cached(&store).unwrap();
+    let mut restored =
+        VoterRegistar::from_bytes(&registar.to_bytes(), registar.tree.clone()).unwrap();
+    assert_eq!(restored.proof_key(), registar.proof_key());
+    assert_eq!(
+        store.lookup(&registar.proof_key()).unwrap(),
+        Some(register_proof.clone())
+    );
+    assert_eq!(
+        restored.get_register_proof_cached(&store).unwrap(),
+        register_proof
+    );
+    assert!(!restored.dirty_flag);
+
+    // proofs of other field extensions are not reused
+    restored.dirty_flag = true;
+    restored.extension = 2;
+    assert_ne!(restored.proof_key(), registar.proof_key());
+    assert_eq!(store.lookup(&restored.proof_key()).unwrap(), None);
+
+    // same for the cast proof
+    let mut collector = VoteCollector::get_example(2);
+    let cast_proof = collector.get_cast_proof_cached(&store).unwrap();
+    let mut restored = VoteCollector::from_bytes(&collector.to_bytes()).unwrap();
+    assert_eq!(restored.proof_key(), collector.proof_key());
+    assert_eq!(restored.get_cast_proof_cached(&store).unwrap(), cast_proof);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cast_test_verification_pool() {
+    use crate::schnorr::random_key_pairs;
+
+    let (secret_keys, voting_keys) = random_key_pairs(4);
+    let mut collector = VoteCollector::new(voting_keys.clone());
+    let encrypted_vote = EncryptedVote::cast(0, secret_keys[0], &voting_keys, true);
+    assert_eq!(
+        collector.submit_encrypted_vote(encrypted_vote),
+        Err(CollectorError::Busy)
+    );
+
+    collector.start_verification_pool(2, 4);
+    for (i, &secret_key) in secret_keys.iter().enumerate() {
+        // the last voter encrypts their vote with the key of another voter
+        let encrypted_vote = if i == 3 {
+            EncryptedVote::cast(i, secret_keys[0], &voting_keys, true)
+        } else {
+            EncryptedVote::cast(i, secret_key, &voting_keys, i % 2 == 0)
+        };
+        collector.submit_encrypted_vote(encrypted_vote).unwrap();
+    }
+
+    let mut results = collector.wait_verified_votes();
+    results.sort_by_key(|&(voter_index, _)| voter_index);
+    assert_eq!(collector.pending_verifications(), 0);
+    assert_eq!(
+        results,
+        vec![
+            (0, Ok(())),
+            (1, Ok(())),
+            (2, Ok(())),
+            (3, Err(CollectorError::InvalidEncryptedVote)),
+        ]
+    );
+    assert_eq!(collector.num_valid_votes, 3);
+    assert!(collector.poll_verified_votes().is_empty());
+}
+
+#[test]
+fn register_test_manifest_binding() {
+    let manifest = ElectionManifest::new("Adopt the proposal?", Schedule::default());
+    let other_manifest = ElectionManifest::new("Reject the proposal?", Schedule::default());
+    let mut registar = VoterRegistar::get_example(2);
+    registar.bind_manifest(&manifest);
+    let register_proof = registar.get_register_proof().unwrap();
+    let mut elg_root_bytes = vec![];
+    Serializable::write_batch_into(&registar.elg_root, &mut elg_root_bytes);
+
+    let digest = manifest.digest().to_elements();
+    let other_digest = other_manifest.digest().to_elements();
+    let options = RegisterVerification::new().with_manifest(digest);
+    assert!(verify_register_proof_with(&registar.elg_root, &options, &register_proof).is_valid());
+    let options = RegisterVerification::new().with_manifest(other_digest);
+    assert!(!verify_register_proof_with(&registar.elg_root, &options, &register_proof).is_valid());
+    assert!(!verify_register_proof(&elg_root_bytes, &register_proof).unwrap());
+}
+
+#[test]
+fn cast_test_manifest_binding() {
+    use crate::schnorr::random_key_pairs;
+
+    let manifest = ElectionManifest::new("Adopt the proposal?", Schedule::default());
+    let other_manifest = ElectionManifest::new("Reject the proposal?", Schedule::default());
+    let manifest_digest = manifest.digest().to_elements();
+    let (secret_keys, voting_keys) = random_key_pairs(2);
+    let mut unbound_collector = VoteCollector::new(voting_keys.clone());
+    let mut collector = VoteCollector::new(voting_keys.clone());
+    collector.bind_manifest(&manifest);
+
+    // CDS proofs of encrypted votes are bound to the manifest as well
+    let unbound_vote = EncryptedVote::cast(0, secret_keys[0], &voting_keys, true);
+    assert_eq!(
+        collector.add_encrypted_vote(unbound_vote),
+        Err(CollectorError::InvalidEncryptedVote)
+    );
+    for (i, &secret_key) in secret_keys.iter().enumerate() {
+        let vote = i % 2 == 0;
+        unbound_collector
+            .add_encrypted_vote(EncryptedVote::cast(i, secret_key, &voting_keys, vote))
+            .unwrap();
+        collector
+            .add_encrypted_vote(EncryptedVote::cast_with_manifest(
+                i,
+                secret_key,
+                &voting_keys,
+                vote,
+                &manifest_digest,
+            ))
+            .unwrap();
+    }
+    // voters check their encrypted vote against the context of the collector
+    let encrypted_vote =
+        EncryptedVote::cast_with_manifest(0, secret_keys[0], &voting_keys, true, &manifest_digest);
+    assert!(encrypted_vote
+        .verify(&voting_keys, &collector.cds_context())
+        .is_ok());
+    assert!(encrypted_vote
+        .verify(&voting_keys, &unbound_collector.cds_context())
+        .is_err());
+    let unbound_proof = unbound_collector.get_cast_proof().unwrap();
+    let cast_proof = collector.get_cast_proof().unwrap();
+    assert_ne!(cast_proof, unbound_proof);
+    let mut voting_keys = vec![];
+    voting_keys.write_u8_slice(&(collector.voting_keys.len() as u32).to_be_bytes());
+    for voting_key in collector.voting_keys.iter() {
+        voting_keys.write_u8_slice(&compress_point(voting_key));
+    }
+
+    let digest = manifest.digest().to_elements();
+    let other_digest = other_manifest.digest().to_elements();
+    let options = CastVerification::new().with_manifest(digest);
+    let other_options = CastVerification::new().with_manifest(other_digest);
+    assert!(verify_cast_proof_with(&voting_keys, &options, &cast_proof).is_valid());
+    assert!(!verify_cast_proof_with(&voting_keys, &other_options, &cast_proof).is_valid());
+    assert!(!verify_cast_proof(&voting_keys, &cast_proof).unwrap());
+    assert!(!verify_cast_proof_with(&voting_keys, &options, &unbound_proof).is_valid());
+}
+
+#[test]
+fn register_test_credentials() {
+    let batch = CredentialBatch::generate(4);
+    let mut registar = batch.registar();
+    assert_eq!(registar.num_elg_voters, 4);
+
+    // credentials survive printing and typing back in
+    let printed = batch.credentials[0].to_printable();
+    let credential = Credential::from_printable(&printed.replace('-', " ")).unwrap();
+    assert_eq!(credential.secret_key, batch.credentials[0].secret_key);
+    assert_eq!(credential.voting_key(), batch.credentials[0].voting_key());
+    let mut typo = printed.into_bytes();
+    let last = typo.len() - 1;
+    typo[last] = if typo[last] == b'0' { b'1' } else { b'0' };
+    assert!(Credential::from_printable(&String::from_utf8(typo).unwrap()).is_err());
+
+    // each credential is redeemed once
+    for credential in batch.credentials.iter() {
+        assert!(!registar.is_redeemed(&credential.voting_key()));
+        assert_eq!(registar.redeem_credential(credential.registration), Ok(()));
+        assert!(registar.is_redeemed(&credential.voting_key()));
+    }
+    assert_eq!(
+        registar.redeem_credential(batch.credentials[0].registration),
+        Err(RegistarError::CredentialRedeemed)
+    );
+
+    let register_proof = registar.get_register_proof().unwrap();
+    let mut elg_root_bytes = vec![];
+    Serializable::write_batch_into(&registar.elg_root, &mut elg_root_bytes);
+    assert!(verify_register_proof(&elg_root_bytes, &register_proof).unwrap());
+}
+
+#[test]
+fn register_test_canonical_order() {
+    let batch = CredentialBatch::generate(4);
+    let mut registar = batch.registar();
+    registar.set_key_order(KeyOrder::Canonical);
+    let mut reversed_registar = batch.registar();
+    for (credential, reversed) in batch.credentials.iter().zip(batch.credentials.iter().rev()) {
+        registar.redeem_credential(credential.registration).unwrap();
+        reversed_registar
+            .redeem_credential(reversed.registration)
+            .unwrap();
+    }
+    assert!(KeyOrder::Canonical.is_ordered(&registar.voting_keys));
+
+    // the canonical order does not depend on the order of submission
+    reversed_registar.set_key_order(KeyOrder::Canonical);
+    assert_eq!(reversed_registar.voting_keys, registar.voting_keys);
+    assert_eq!(reversed_registar.addresses, registar.addresses);
+    assert_eq!(reversed_registar.hash_indices, registar.hash_indices);
+
+    // proofs are bound to the order of the voting keys
+    let register_proof = registar.get_register_proof().unwrap();
+    let mut elg_root_bytes = vec![];
+    Serializable::write_batch_into(&registar.elg_root, &mut elg_root_bytes);
+    let options = RegisterVerification::new().with_key_order(KeyOrder::Canonical);
+    assert!(verify_register_proof_with(&registar.elg_root, &options, &register_proof).is_valid());
+    assert!(!verify_register_proof(&elg_root_bytes, &register_proof).unwrap());
+}
+
+#[test]
+fn register_test_leaf_index_binding() {
+    let mut registar = VoterRegistar::get_example(4);
+    registar.bind_leaf_indices();
+    let register_proof = registar.get_register_proof().unwrap();
+    let mut elg_root_bytes = vec![];
+    Serializable::write_batch_into(&registar.elg_root, &mut elg_root_bytes);
+    let mut leaf_indices = vec![];
+    for &hash_index in registar.hash_indices.iter() {
+        leaf_indices.write_u32(hash_index as u32);
+    }
+    let verify_with_leaf_indices = |leaf_indices: &[u8]| {
+        let options =
+            RegisterVerification::new().with_leaf_indices(read_leaf_indices(leaf_indices)?);
+        verify_register_proof_with(&registar.elg_root, &options, &register_proof).into_outcome()
+    };
+    assert!(verify_with_leaf_indices(&leaf_indices).unwrap());
+    assert!(!verify_register_proof(&elg_root_bytes, &register_proof).unwrap());
+
+    // a voting key proven at another position than the recorded one is rejected
+    let mut moved_indices = leaf_indices.clone();
+    moved_indices[..4].copy_from_slice(&((registar.hash_indices[0] ^ 1) as u32).to_le_bytes());
+    assert!(!verify_with_leaf_indices(&moved_indices).unwrap());
+    assert!(verify_with_leaf_indices(&leaf_indices[4..]).is_err());
+}
+
+#[test]
+fn register_test_group_binding() {
+    let mut registar = VoterRegistar::get_example(4);
+
+    // commit the first two voters to group 1 and the others to group 2
+    let mut leaves = registar.tree.leaves().to_vec();
+    let mut leaf_groups = vec![0; leaves.len()];
+    for (i, &hash_index) in registar.hash_indices.iter().enumerate() {
+        leaf_groups[hash_index] = 1 + (i as u32) / 2;
+        leaves[hash_index] =
+            MerkleTree::leaf_of_grouped(&registar.voting_keys[i], leaf_groups[hash_index]);
+    }
+    registar.tree = MerkleTree::new(&leaves);
+    registar.elg_root = registar.tree.root();
+    registar.bind_groups(leaf_groups.clone());
+    assert_eq!(registar.group_of(0), 1);
+    assert_eq!(registar.group_of(3), 2);
+    let voting_keys_by_group = registar.voting_keys_by_group();
+    assert_eq!(voting_keys_by_group[&1], registar.voting_keys[..2].to_vec());
+    assert_eq!(voting_keys_by_group[&2], registar.voting_keys[2..].to_vec());
+
+    // registrations are checked against the leaves of their group
+    let registration = Registration {
+        voting_key: registar.voting_keys[0],
+        merkle_branch: registar.merkle_branch(0),
+        hash_index: registar.hash_indices[0],
+        signature: registar.signatures[0],
+        address: registar.addresses[0],
+        ownership_signature: None,
+    };
+    assert!(registar.add_registration(registration).is_ok());
+    registar.leaf_groups[registar.hash_indices[0]] = 2;
+    assert_eq!(
+        registar.add_registration(registration),
+        Err(RegistarError::InvalidMerkleProof)
+    );
+    registar.bind_groups(leaf_groups.clone());
+
+    let register_proof = registar.get_register_proof().unwrap();
+    let mut elg_root_bytes = vec![];
+    Serializable::write_batch_into(&registar.elg_root, &mut elg_root_bytes);
+    let mut groups = vec![];
+    for i in 0..registar.voting_keys.len() {
+        groups.write_u32(registar.group_of(i));
+    }
+    let verify_with_groups = |groups: &[u8]| {
+        let options = RegisterVerification::new().with_groups(read_groups(groups)?);
+        verify_register_proof_with(&registar.elg_root, &options, &register_proof).into_outcome()
+    };
+    assert!(verify_with_groups(&groups).unwrap());
+    assert!(!verify_register_proof(&elg_root_bytes, &register_proof).unwrap());
+
+    // a voter proven in another group than the recorded one is rejected
+    let mut moved_groups = groups.clone();
+    moved_groups[..4].copy_from_slice(&2u32.to_le_bytes());
+    assert!(!verify_with_groups(&moved_groups).unwrap());
+    assert!(verify_with_groups(&groups[4..]).is_err());
+
+    // grouped registars are restored with the groups of the leaves
+    let restored = VoterRegistar::read_from_grouped(
+        &mut SliceReader::new(&registar.to_bytes()),
+        registar.tree.clone(),
+        leaf_groups,
+    )
+    .unwrap();
+    assert_eq!(restored.hash_indices, registar.hash_indices);
+    assert!(VoterRegistar::from_bytes(&registar.to_bytes(), registar.tree.clone()).is_err());
+}
+
+#[test]
+fn cast_test_canonical_order() {
+    let batch = CredentialBatch::generate(4);
+    let registered_keys = batch
+        .credentials
+        .iter()
+        .map(Credential::voting_key)
+        .collect::<Vec<_>>();
+    let mut collector =
+        VoteCollector::try_new_with_order(registered_keys, KeyOrder::Canonical).unwrap();
+    assert!(KeyOrder::Canonical.is_ordered(&collector.voting_keys));
+    for credential in batch.credentials.iter() {
+        let voter_index = collector
+            .voting_keys
+            .iter()
+            .position(|voting_key| *voting_key == credential.voting_key())
+            .unwrap();
+        let encrypted_vote = EncryptedVote::cast(
+            voter_index,
+            credential.secret_key,
+            &collector.voting_keys,
+            voter_index % 2 == 0,
+        );
+        collector.add_encrypted_vote(encrypted_vote).unwrap();
+    }
+    let cast_proof = collector.get_cast_proof().unwrap();
+    let encode_keys = |keys: &[[BaseElement; AFFINE_POINT_WIDTH]]| {
+        let mut bytes = vec![];
+        bytes.write_u8_slice(&(keys.len() as u32).to_be_bytes());
+        for voting_key in keys.iter() {
+            bytes.write_u8_slice(&compress_point(voting_key));
+        }
+        bytes
+    };
+
+    let voting_keys = encode_keys(&collector.voting_keys);
+    let options = CastVerification::new().with_key_order(KeyOrder::Canonical);
+    assert!(verify_cast_proof_with(&voting_keys, &options, &cast_proof).is_valid());
+    assert!(!verify_cast_proof(&voting_keys, &cast_proof).unwrap());
+
+    // voting keys which are not sorted are rejected
+    let mut unordered_keys = collector.voting_keys.clone();
+    unordered_keys.swap(0, 1);
+    let report = verify_cast_proof_with(&encode_keys(&unordered_keys), &options, &cast_proof);
+    assert!(report.public_inputs.is_err());
+}
+
+#[test]
+fn cast_test_trackers() {
+    let mut collector = VoteCollector::get_example(4);
+    let trackers = (0..4)
+        .map(|i| collector.tracker_for(i).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(collector.tracker_for(4), None);
+    assert_eq!(format_tracker(&trackers[0]).len(), 19);
+
+    let cast_proof = collector.get_cast_proof().unwrap();
+    let mut voting_keys = vec![];
+    voting_keys.write_u8_slice(&(collector.voting_keys.len() as u32).to_be_bytes());
+    for voting_key in collector.voting_keys.iter() {
+        voting_keys.write_u8_slice(&compress_point(voting_key));
+    }
+    let options = CastVerification::new();
+    let report = verify_cast_proof_with(&voting_keys, &options, &cast_proof);
+    assert!(report.is_valid());
+    assert_eq!(report.trackers, trackers);
+
+    // trackers of invalid proofs cannot be relied upon
+    let cast_proof = collector.get_cast_proof_wrong_pub_inputs().unwrap();
+    assert!(!verify_cast_proof_with(&voting_keys, &options, &cast_proof).is_valid());
+}
+
+#[test]
+fn election_test_registry() {
+    use super::election::{ElectionError, ElectionId, ElectionRegistry, REGISTER_PROOF_FILE};
+    use rand_core::RngCore;
+
+    let dir = std::env::temp_dir().join(format!("openvote-elections-{}", OsRng.next_u32()));
+    let mut registry = ElectionRegistry::new(&dir);
+    let manifest = ElectionManifest::new("Adopt the proposal?", Schedule::default());
+    let other_manifest = ElectionManifest::new("Reject the proposal?", Schedule::default());
+    let batch = CredentialBatch::generate(2);
+    let other_batch = CredentialBatch::generate(2);
+
+    let id = registry
+        .create(manifest.clone(), batch.tree.clone(), 2)
+        .unwrap()
+        .id();
+    let other_id = registry
+        .create(other_manifest, other_batch.tree.clone(), 2)
+        .unwrap()
+        .id();
+    assert_ne!(id, other_id);
+    assert_eq!(id, ElectionId::from_manifest(&manifest));
+    assert_eq!(registry.len(), 2);
+    assert!(matches!(
+        registry.create(manifest.clone(), batch.tree.clone(), 2),
+        Err(ElectionError::DuplicateElection(duplicate)) if duplicate == id
+    ));
+
+    // registrations of one election are not visible to the other
+    let election = registry.get_mut(&id).unwrap();
+    for credential in batch.credentials.iter() {
+        election
+            .registar
+            .redeem_credential(credential.registration)
+            .unwrap();
+    }
+    assert_eq!(
+        registry.get(&other_id).unwrap().registar.voting_keys.len(),
+        0
+    );
+
+    // proofs are bound to the manifest and cached in the namespace of the election
+    let election = registry.get_mut(&id).unwrap();
+    let register_proof = election.register_proof().unwrap();
+    assert_eq!(
+        std::fs::read(election.storage().join(REGISTER_PROOF_FILE)).unwrap(),
+        register_proof
+    );
+    assert!(election.storage().starts_with(&dir));
+    assert_ne!(
+        election.storage(),
+        registry.get(&other_id).unwrap().storage()
+    );
+    let options = RegisterVerification::new().with_manifest(manifest.digest().to_elements());
+    assert!(verify_register_proof_with(&batch.tree.root(), &options, &register_proof).is_valid());
+
+    let election = registry.get_mut(&id).unwrap();
+    assert!(matches!(
+        election.start_tallying(),
+        Err(ElectionError::InvalidPhase)
+    ));
+    assert_eq!(election.status().collector, None);
+    election.start_casting().unwrap();
+    let status = election.status();
+    assert_eq!(status.id, id);
+    assert_eq!(status.registar.num_registered, 2);
+    assert!(status.registar.proof_fresh);
+    assert_eq!(status.collector.unwrap().num_voted, 0);
+    assert_eq!(status.tally_result, None);
+    assert!(matches!(
+        election.start_casting(),
+        Err(ElectionError::InvalidPhase)
+    ));
+    assert!(matches!(
+        election.start_tallying(),
+        Err(ElectionError::Collector(
+            CollectorError::NotEnoughEncryptedVotes
+        ))
+    ));
+    election.save().unwrap();
+
+    registry.remove(&id).unwrap();
+    assert!(matches!(
+        registry.get(&id),
+        Err(ElectionError::UnknownElection(_))
+    ));
+    assert_eq!(registry.ids().collect::<Vec<_>>(), vec![&other_id]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn election_test_manifest_amendment() {
+    use super::election::{ElectionError, ElectionRegistry};
+    use crate::manifest::{ManifestError, ManifestUpdate};
+    use rand_core::RngCore;
+
+    let dir = std::env::temp_dir().join(format!("openvote-elections-{}", OsRng.next_u32()));
+    let mut registry = ElectionRegistry::new(&dir);
+    let trustee_secret = Scalar::random(&mut OsRng);
+    let mut manifest = ElectionManifest::new(
+        "Adopt the proposal?",
+        Schedule {
+            registration_start: 1_000,
+            registration_end: 2_000,
+            casting_start: 2_000,
+            casting_end: 3_000,
+        },
+    );
+    manifest.trustee_keys.push(projective_to_elements(
+        ProjectivePoint::generator() * trustee_secret,
+    ));
+    let batch = CredentialBatch::generate(2);
+    let election = registry
+        .create(manifest.clone(), batch.tree.clone(), 2)
+        .unwrap();
+    let id = election.id();
+    for credential in batch.credentials.iter() {
+        election
+            .registar
+            .redeem_credential(credential.registration)
+            .unwrap();
+    }
+    let collector = election.start_casting().unwrap();
+    let cast = |collector: &VoteCollector, credential: &Credential| {
+        let voter_index = collector
+            .voting_keys
+            .iter()
+            .position(|voting_key| *voting_key == credential.voting_key())
+            .unwrap();
+        EncryptedVote::cast_with_manifest(
+            voter_index,
+            credential.secret_key,
+            &collector.voting_keys,
+            true,
+            &manifest.digest().to_elements(),
+        )
+    };
+    let encrypted_vote = cast(collector, &batch.credentials[0]);
+    collector.add_encrypted_vote(encrypted_vote).unwrap();
+
+    // casting is extended by the trustee, and the identifier of the election is unchanged
+    let mut schedule = manifest.schedule;
+    schedule.casting_end += 1_000;
+    let update = ManifestUpdate::sign(&manifest, schedule, trustee_secret);
+    let election = registry.get_mut(&id).unwrap();
+    assert!(matches!(
+        election.amend_manifest(&ManifestUpdate::sign(
+            &manifest,
+            schedule,
+            trustee_secret + Scalar::one()
+        )),
+        Err(ElectionError::InvalidManifest(
+            ManifestError::UnknownAuthority
+        ))
+    ));
+    election.amend_manifest(&update).unwrap();
+    let amended = election.manifest().clone();
+    assert_eq!(amended.previous_digest, Some(manifest.digest()));
+    assert_eq!(election.id(), id);
+
+    // ballots are still cast against the manifest in effect when casting started
+    let collector = election.collector.as_mut().unwrap();
+    let encrypted_vote = cast(collector, &batch.credentials[1]);
+    collector.add_encrypted_vote(encrypted_vote).unwrap();
+
+    // subsequent proofs are bound to the digest of the amended manifest
+    let register_proof = election.register_proof().unwrap();
+    let options = RegisterVerification::new().with_manifest(amended.digest().to_elements());
+    assert!(verify_register_proof_with(&batch.tree.root(), &options, &register_proof).is_valid());
+    let options = RegisterVerification::new().with_manifest(manifest.digest().to_elements());
+    assert!(!verify_register_proof_with(&batch.tree.root(), &options, &register_proof).is_valid());
+
+    let cast_proof = election.cast_proof().unwrap();
+    let collector = election.collector.as_ref().unwrap();
+    let mut voting_keys = vec![];
+    voting_keys.write_u8_slice(&(collector.voting_keys.len() as u32).to_be_bytes());
+    for voting_key in collector.voting_keys.iter() {
+        voting_keys.write_u8_slice(&compress_point(voting_key));
+    }
+    let options = CastVerification::new().with_amended_manifest(
+        manifest.digest().to_elements(),
+        amended.digest().to_elements(),
+    );
+    assert!(verify_cast_proof_with(&voting_keys, &options, &cast_proof).is_valid());
+    let options = CastVerification::new().with_manifest(manifest.digest().to_elements());
+    assert!(!verify_cast_proof_with(&voting_keys, &options, &cast_proof).is_valid());
+    let options = CastVerification::new().with_manifest(amended.digest().to_elements());
+    assert!(!verify_cast_proof_with(&voting_keys, &options, &cast_proof).is_valid());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn election_test_key_reuse() {
+    use super::election::{ElectionError, ElectionRegistry};
+    use rand_core::RngCore;
+
+    let dir = std::env::temp_dir().join(format!("openvote-elections-{}", OsRng.next_u32()));
+    let manifest = ElectionManifest::new("Adopt the proposal?", Schedule::default());
+    let other_manifest = ElectionManifest::new("Reject the proposal?", Schedule::default());
+    // both elections share the tree, so that the same registrations are eligible
+    let batch = CredentialBatch::generate(2);
+    let registration = batch.credentials[0].registration;
+
+    let mut registry = ElectionRegistry::new(&dir);
+    let id = registry
+        .create(manifest.clone(), batch.tree.clone(), 2)
+        .unwrap()
+        .id();
+    let other_id = registry
+        .create(other_manifest.clone(), batch.tree.clone(), 2)
+        .unwrap()
+        .id();
+    registry.register(&id, registration).unwrap();
+    assert!(registry
+        .key_reused_in(&other_id, &registration.voting_key)
+        .contains(&id));
+    assert!(registry
+        .key_reused_in(&id, &registration.voting_key)
+        .is_empty());
+
+    // reuse is only detected by default
+    registry.register(&other_id, registration).unwrap();
+    assert_eq!(
+        registry.shared_voting_keys(&id, &other_id).unwrap(),
+        vec![registration.voting_key]
+    );
+
+    let mut registry = ElectionRegistry::without_key_reuse(&dir);
+    registry.create(manifest, batch.tree.clone(), 2).unwrap();
+    registry
+        .create(other_manifest, batch.tree.clone(), 2)
+        .unwrap();
+    registry.register(&id, registration).unwrap();
+    assert!(matches!(
+        registry.register(&other_id, registration),
+        Err(ElectionError::KeyReused(reused_in)) if reused_in == id
+    ));
+    // registering again in the same election is not a reuse
+    registry.register(&id, registration).unwrap();
+    registry
+        .register(&other_id, batch.credentials[1].registration)
+        .unwrap();
+    assert!(registry
+        .shared_voting_keys(&id, &other_id)
+        .unwrap()
+        .is_empty());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn register_test_signature_sequence_proof() {
+    let mut registar = VoterRegistar::get_example(2);
+    let sequence_digest = registar.sequence_digest();
+    let sequence_proof = registar.get_signature_sequence_proof().unwrap();
+    assert!(verify_signature_sequence_proof(&sequence_digest, &sequence_proof).unwrap());
+
+    // the proof is only valid for the digest of the registrations it was generated for
+    let other_digest = VoterRegistar::get_example(2).sequence_digest();
+    assert!(!verify_signature_sequence_proof(&other_digest, &sequence_proof).unwrap());
+
+    registar.voting_keys.pop();
+    assert_eq!(
+        registar.get_signature_sequence_proof(),
+        Err(RegistarError::InvalidSubset)
+    );
+}
+
+#[test]
+fn aggregator_test_small_order_points() {
+    use crate::{
+        compat::curve::AffinePoint,
+        schnorr::SchnorrVerificationError,
+        testing::small_order_point,
+        voter::{preflight, preflight_vote, CastParams, ElectionParams},
+    };
+
+    // a voting key with a component of small order is rejected at registration
+    let example = VoterRegistar::get_example(2);
+    let mut registar = VoterRegistar::new(example.tree.clone(), 2);
+    let voting_key =
+        ProjectivePoint::from(AffinePoint::from_raw_coordinates(example.voting_keys[0]));
+    let registration = Registration {
+        voting_key: projective_to_elements(voting_key + small_order_point()),
+        merkle_branch: example.merkle_branch(0),
+        hash_index: example.hash_indices[0],
+        signature: example.signatures[0],
+        address: example.addresses[0],
+        ownership_signature: None,
+    };
+    let diagnostics = preflight(&registration, &ElectionParams::from(&registar));
+    assert_eq!(
+        diagnostics.schnorr_signature_error,
+        Some(SchnorrVerificationError::SmallOrderKey)
+    );
+    assert!(registar.add_registration(registration).is_err());
+    assert!(registar.voting_keys.is_empty());
+
+    // so is an encrypted vote with a component of small order, whether
+    // it is verified on its own or in batch
+    let (example, (_, encrypted_votes, proof_scalars, proof_points)) =
+        CDSExample::new(build_options(1), 4);
+    let crafted_ballot = EncryptedVote::new(
+        1,
+        encrypted_votes[1] + small_order_point(),
+        proof_points[1],
+        proof_scalars[1],
+    );
+    let mut collector = VoteCollector::new(example.voting_keys.clone());
+    collector.report_invalid_ballots = true;
+    assert_eq!(
+        preflight_vote(&crafted_ballot, &CastParams::from(&collector)).cds_proof,
+        Err(CdsVerificationError::InvalidPoint)
+    );
+    assert_eq!(
+        collector.add_encrypted_vote(crafted_ballot),
+        Err(CollectorError::InvalidEncryptedVote)
+    );
+    // The crafted ballot is not signed by its voter, hence not reported
+    assert_eq!(collector.exclusion_reason(1), None);
+
+    let mut collector = VoteCollector::new(example.voting_keys);
+    let mut queue = CastQueue::new();
+    queue.push(EncryptedVote::new(
+        0,
+        encrypted_votes[0],
+        proof_points[0],
+        proof_scalars[0],
+    ));
+    queue.push(crafted_ballot);
+    assert_eq!(
+        queue.flush(&mut collector),
+        vec![Ok(()), Err(CollectorError::InvalidEncryptedVote)]
+    );
+}
+
+#[test]
+fn aggregator_test_status() {
+    use super::status::ProofRecord;
+
+    let mut registar = VoterRegistar::get_example(2);
+    let status = registar.status();
+    assert_eq!(status.num_elg_voters, 2);
+    assert_eq!(status.num_registered, 2);
+    assert!(!status.proof_fresh);
+    assert_eq!(status.last_proof, None);
+    assert_eq!(status.estimated_proving_ms, None);
+
+    registar.get_register_proof().unwrap();
+    let status = registar.status();
+    assert!(status.proof_fresh);
+    let last_proof = status.last_proof.unwrap();
+    assert_eq!(last_proof.num_voters, 2);
+    assert!(last_proof.timestamp > 0);
+    assert_eq!(status.estimated_proving_ms, Some(last_proof.duration_ms));
+
+    let mut collector = VoteCollector::get_example(2);
+    let status = collector.status();
+    assert_eq!(status.num_voters, 2);
+    assert_eq!(status.num_voted, 2);
+    assert_eq!(status.num_excluded, 0);
+    assert_eq!(status.pending_verifications, 0);
+    assert!(status.is_complete());
+    assert_eq!(status.last_proof, None);
+    collector.get_cast_proof().unwrap();
+    assert_eq!(collector.status().last_proof.unwrap().num_voters, 2);
+
+    // proving time is extrapolated from the padded number of voters
+    let record = ProofRecord {
+        timestamp: 0,
+        duration_ms: 100,
+        num_voters: 3,
+    };
+    assert_eq!(record.estimate_ms(4), 100);
+    assert_eq!(record.estimate_ms(5), 200);
+    assert_eq!(record.estimate_ms(16), 400);
+}
+
+#[test]
+fn aggregator_test_tiny_elections() {
+    use super::election::{ElectionError, ElectionRegistry};
+    use rand_core::RngCore;
+
+    let mut example = VoterRegistar::get_example(2);
+    let mut elg_root_bytes = vec![];
+    Serializable::write_batch_into(&example.elg_root, &mut elg_root_bytes);
+
+    // registrations of fewer than two voters cannot be proven
+    let mut registar = VoterRegistar::new(example.tree.clone(), 2);
+    assert_eq!(
+        registar.get_register_proof(),
+        Err(RegistarError::InvalidSubset)
+    );
+    let mut single = VoterRegistar::get_example(2);
+    single.voting_keys.truncate(1);
+    single.hash_indices.truncate(1);
+    single.signatures.truncate(1);
+    single.addresses.truncate(1);
+    single.ownership_signatures.truncate(1);
+    assert_eq!(
+        single.get_register_proof(),
+        Err(RegistarError::InvalidSubset)
+    );
+    assert_eq!(
+        single.get_signature_sequence_proof(),
+        Err(RegistarError::InvalidSubset)
+    );
+
+    // proofs claiming fewer than two voters are rejected without being deserialized
+    let mut register_proof = example.get_register_proof().unwrap();
+    assert!(verify_register_proof(&elg_root_bytes, &register_proof).unwrap());
+    register_proof[..4].copy_from_slice(&1u32.to_le_bytes());
+    assert!(!verify_register_proof(&elg_root_bytes, &register_proof).unwrap());
+
+    // votes of fewer than two voters cannot be collected, proven or tallied
+    assert_eq!(
+        VoteCollector::try_new(vec![]).unwrap_err(),
+        CollectorError::InvalidNumberOfVoters
+    );
+    assert_eq!(
+        VoteCollector::try_new(example.voting_keys[..1].to_vec()).unwrap_err(),
+        CollectorError::InvalidNumberOfVoters
+    );
+    assert!(VoteCollector::try_new(example.voting_keys.clone()).is_ok());
+    let (mut tallier, _) = VoteTallier::get_example(2);
+    tallier.encrypted_votes.truncate(1);
+    let encrypted_votes = tallier.encrypted_votes_bytes();
+    assert!(!verify_tally_proof(&encrypted_votes, 0, &[]).unwrap());
+
+    // an election with a single registered voter cannot enter the casting phase
+    let dir = std::env::temp_dir().join(format!("openvote-tiny-{}", OsRng.next_u32()));
+    let mut registry = ElectionRegistry::new(&dir);
+    let manifest = ElectionManifest::new("Adopt the proposal?", Schedule::default());
+    let batch = CredentialBatch::generate(2);
+    let id = registry
+        .create(manifest, batch.tree.clone(), 2)
+        .unwrap()
+        .id();
+    registry
+        .register(&id, batch.credentials[0].registration)
+        .unwrap();
+    let election = registry.get_mut(&id).unwrap();
+    assert!(matches!(
+        election.start_casting(),
+        Err(ElectionError::Collector(
+            CollectorError::InvalidNumberOfVoters
+        ))
+    ));
+    assert!(matches!(
+        election.register_proof(),
+        Err(ElectionError::Registar(RegistarError::InvalidSubset))
+    ));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(8))]
+
+    #[test]
+    fn register_test_registration_roundtrip(
+        voting_key in point(),
+        merkle_branch in vec(element(), TREE_DEPTH * DIGEST_SIZE),
+        hash_index in any::<u32>(),
+        signature in signature(),
+        address in address(),
+        ownership_signature in any::<Option<u8>>(),
+    ) {
+        let mut registration = Registration {
+            voting_key,
+            merkle_branch: [BaseElement::ZERO; TREE_DEPTH * DIGEST_SIZE],
+            hash_index: hash_index as usize,
+            signature,
+            address,
+            ownership_signature: ownership_signature
+                .map(|byte| [byte; BYTES_PER_OWNERSHIP_SIGNATURE]),
+        };
+        registration.merkle_branch.copy_from_slice(&merkle_branch);
+
+        let bytes = registration.to_bytes();
+        let restored = Registration::read_from(&mut SliceReader::new(&bytes)).unwrap();
+        prop_assert_eq!(restored.voting_key, registration.voting_key);
+        prop_assert_eq!(restored.hash_index, registration.hash_index);
+        prop_assert_eq!(restored.signature, registration.signature);
+        prop_assert_eq!(restored.address, registration.address);
+        prop_assert_eq!(restored.ownership_signature, registration.ownership_signature);
+        prop_assert_eq!(restored.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn register_test_serialization_roundtrip(
+        seed: u64,
+        num_regs in 0usize..5,
+        num_absent in 0usize..3,
+        signatures in vec(signature(), 4),
+        addresses in vec(address(), 4),
+        ownership_signatures in vec(any::<Option<u8>>(), 4),
+        require_ownership: bool,
+    ) {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let voting_keys = (0..num_regs)
+            .map(|_| random_key_pair_with_rng(&mut rng).1)
+            .collect::<Vec<_>>();
+        let (tree, hash_indices) = build_merkle_tree_with_rng(&voting_keys, &mut rng);
+
+        // some eligible voters may not have registered yet
+        let mut registar = VoterRegistar::new(tree.clone(), num_regs + num_absent);
+        registar.voting_keys = voting_keys;
+        registar.hash_indices = hash_indices;
+        registar.signatures = signatures[..num_regs].to_vec();
+        registar.addresses = addresses[..num_regs].to_vec();
+        registar.require_ownership = require_ownership;
+        registar.ownership_signatures = ownership_signatures[..num_regs]
+            .iter()
+            .map(|byte| byte.map(|byte| [byte; BYTES_PER_OWNERSHIP_SIGNATURE]))
+            .collect();
+
+        let bytes = registar.to_bytes();
+        let restored = VoterRegistar::from_bytes(&bytes, tree).unwrap();
+        prop_assert_eq!(restored.elg_root, registar.elg_root);
+        prop_assert_eq!(restored.num_elg_voters, registar.num_elg_voters);
+        prop_assert_eq!(&restored.voting_keys, &registar.voting_keys);
+        prop_assert_eq!(&restored.hash_indices, &registar.hash_indices);
+        prop_assert_eq!(&restored.signatures, &registar.signatures);
+        prop_assert_eq!(&restored.addresses, &registar.addresses);
+        prop_assert_eq!(restored.require_ownership, registar.require_ownership);
+        prop_assert_eq!(&restored.ownership_signatures, &registar.ownership_signatures);
+        prop_assert_eq!(restored.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn cast_test_serialization_roundtrip(
+        seed: u64,
+        log_num_voters in 1usize..4,
+        slots in vec(0u8..4, 8),
+        commit_phase: bool,
+    ) {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let num_voters = 1 << log_num_voters;
+        let (secret_keys, voting_keys): (Vec<_>, Vec<_>) = (0..num_voters)
+            .map(|_| random_key_pair_with_rng(&mut rng))
+            .unzip();
+
+        // each slot holds no ballot (0), a "yes" vote (1), a "no" vote (2)
+        // or an invalid ballot reported in the cast proof (3)
+        let mut collector = VoteCollector::new(voting_keys.clone());
+        collector.report_invalid_ballots = true;
+        for (i, &slot) in slots[..num_voters].iter().enumerate() {
+            if slot == 0 {
+                continue;
+            }
+            let mut encrypted_vote =
+                EncryptedVote::cast_with_rng(i, secret_keys[i], &voting_keys, slot == 1, &mut rng);
+            if slot == 3 {
+                encrypted_vote.proof_scalars[2] += Scalar::one();
+                encrypted_vote = sign_ballot(encrypted_vote, secret_keys[i]);
+            }
+            prop_assert_eq!(collector.add_encrypted_vote(encrypted_vote).is_ok(), slot != 3);
+        }
+        if commit_phase {
+            collector.commit_phase = true;
+            for (i, commitment) in collector.commitments.iter_mut().enumerate() {
+                if slots[i] != 0 {
+                    *commitment = Some([i as u8 + 1; BYTES_PER_COMMITMENT]);
+                }
+            }
+        }
+
+        let bytes = collector.to_bytes();
+        let restored = VoteCollector::from_bytes(&bytes).unwrap();
+        prop_assert_eq!(&restored.voting_keys, &collector.voting_keys);
+        prop_assert_eq!(&restored.blinding_keys, &collector.blinding_keys);
+        prop_assert_eq!(&restored.encrypted_votes, &collector.encrypted_votes);
+        prop_assert_eq!(&restored.proof_points, &collector.proof_points);
+        prop_assert_eq!(&restored.proof_scalars, &collector.proof_scalars);
+        prop_assert_eq!(restored.num_valid_votes, collector.num_valid_votes);
+        prop_assert_eq!(restored.commit_phase, collector.commit_phase);
+        prop_assert_eq!(&restored.commitments, &collector.commitments);
+        prop_assert_eq!(restored.num_excluded_ballots(), collector.num_excluded_ballots());
+        for i in 0..num_voters {
+            prop_assert_eq!(
+                restored.excluded_ballots[i].map(|ballot| ballot.to_bytes()),
+                collector.excluded_ballots[i].map(|ballot| ballot.to_bytes())
+            );
+        }
+        // unlike sealed slots, plain slots are written deterministically
+        prop_assert_eq!(restored.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn cast_test_compact_pub_inputs_roundtrip(
+        ballots in vec(
+            (
+                point(),
+                vec(point(), PROOF_NUM_POINTS),
+                any::<bool>(),
+                vec(scalar(), PROOF_NUM_SCALARS),
+                signature(),
+            ),
+            0..6,
+        ),
+    ) {
+        use super::cast::CompactPublicInputs;
+
+        let mut pub_inputs = CompactPublicInputs {
+            encrypted_votes: vec![],
+            cds_proofs: vec![],
+            validity: vec![],
+            excluded_scalars: vec![],
+            excluded_signatures: vec![],
+        };
+        for (encrypted_vote, proof_points, is_valid, proof_scalars, signature) in ballots.iter() {
+            let mut cds_proof = [BaseElement::ZERO; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH];
+            for (chunk, proof_point) in cds_proof
+                .chunks_mut(AFFINE_POINT_WIDTH)
+                .zip(proof_points.iter())
+            {
+                chunk.copy_from_slice(proof_point);
+            }
+            pub_inputs.encrypted_votes.push(*encrypted_vote);
+            pub_inputs.cds_proofs.push(cds_proof);
+            pub_inputs.validity.push(*is_valid);
+            if !is_valid {
+                let mut scalars = [Scalar::zero(); PROOF_NUM_SCALARS];
+                scalars.copy_from_slice(proof_scalars);
+                pub_inputs.excluded_scalars.push(scalars);
+                pub_inputs.excluded_signatures.push(*signature);
+            }
+        }
+
+        let bytes = pub_inputs.to_bytes();
+        let mut source = SliceReader::new(&bytes);
+        let restored = CompactPublicInputs::read_from(&mut source).unwrap();
+        prop_assert!(!source.has_more_bytes());
+        prop_assert_eq!(&restored.encrypted_votes, &pub_inputs.encrypted_votes);
+        prop_assert_eq!(&restored.cds_proofs, &pub_inputs.cds_proofs);
+        prop_assert_eq!(&restored.validity, &pub_inputs.validity);
+        prop_assert_eq!(&restored.excluded_scalars, &pub_inputs.excluded_scalars);
+        prop_assert_eq!(&restored.excluded_signatures, &pub_inputs.excluded_signatures);
+    }
+
+    #[test]
+    fn tally_test_serialization_roundtrip(encrypted_votes in vec(point(), 0..8)) {
+        let tallier = VoteTallier::new(encrypted_votes);
+        let bytes = tallier.to_bytes();
+        let restored = VoteTallier::from_bytes(&bytes).unwrap();
+        prop_assert_eq!(&restored.encrypted_votes, &tallier.encrypted_votes);
+        prop_assert_eq!(restored.to_bytes(), bytes);
+    }
+}
@@ -0,0 +1,403 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// ELECTION ARCHIVE
+// ================================================================================================
+// Bundles the artifacts of all phases of an election into a single gzip-compressed tar file,
+// along with a manifest of their Blake3 digests, so that the election can be re-verified later
+// without access to the aggregator.
+
+use crate::{
+    aggregator::{
+        cast::{CollectorError, VoteCollector},
+        certificate::{verify_certificate, ResultCertificate},
+        register::{RegistarError, VoterRegistar},
+        tally::{TallierError, VoteTallier},
+    },
+    utils::{
+        compression::decompress_proof,
+        ecc::{compress_point, AFFINE_POINT_WIDTH},
+        rescue,
+    },
+    verifier::{
+        decode_encrypted_votes, read_registered_voting_keys, read_valid_encrypted_votes,
+        verify_cast_proof, verify_register_proof, verify_tally_result,
+    },
+};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    io::{Read, Write},
+    path::Path,
+};
+use winterfell::{
+    crypto::{hashers::Blake3_256, Digest, Hasher},
+    math::fields::f63::BaseElement,
+    DeserializationError, Serializable,
+};
+
+#[cfg(test)]
+mod tests;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Name of the manifest entry
+pub const MANIFEST: &str = "MANIFEST";
+/// First line of the manifest
+pub const MANIFEST_HEADER: &str = "openvote-archive 1";
+/// Serialized root of the Merkle tree of eligible voters
+pub const ELG_ROOT: &str = "elg_root.dat";
+/// Serialized state of the voter registar
+pub const REGISTRATIONS: &str = "registrations.dat";
+/// Register proof, as accepted by verify_register_proof
+pub const REGISTER_PROOF: &str = "truncated_register_proof.dat";
+/// Voting keys, as accepted by verify_cast_proof
+pub const VOTING_KEYS: &str = "voting_keys.dat";
+/// Serialized state of the vote collector
+pub const BALLOTS: &str = "ballots.dat";
+/// Cast proof, as accepted by verify_cast_proof
+pub const CAST_PROOF: &str = "truncated_cast_proof.dat";
+/// Encrypted votes, as accepted by verify_tally_result
+pub const ENCRYPTED_VOTES: &str = "encrypted_votes.dat";
+/// Big-endian tally result
+pub const TALLY_RESULT: &str = "tally_result.dat";
+/// Serialized result certificate, if any
+pub const CERTIFICATE: &str = "certificate.dat";
+
+// ERRORS
+// ================================================================================================
+
+/// Errors raised when building, reading or writing an election archive
+#[derive(Debug)]
+pub enum ArchiveError {
+    /// This error occurs when the archive file cannot be read or written
+    Io(std::io::Error),
+    /// This error occurs when a required entry or the manifest is missing
+    MissingEntry(String),
+    /// This error occurs when an entry is not listed in the manifest
+    UnlistedEntry(String),
+    /// This error occurs when the digest of an entry does not match the manifest
+    DigestMismatch(String),
+    /// This error occurs when the manifest or an entry is malformed
+    Malformed(String),
+    /// Wrapper for errors raised by VoterRegistar when proving
+    RegisterProof(RegistarError),
+    /// Wrapper for errors raised by VoteCollector
+    CastProof(CollectorError),
+    /// Wrapper for errors raised by VoteTallier
+    Tally(TallierError),
+}
+
+impl From<std::io::Error> for ArchiveError {
+    fn from(error: std::io::Error) -> Self {
+        ArchiveError::Io(error)
+    }
+}
+
+// ELECTION ARCHIVE
+// ================================================================================================
+
+/// Public values of an election against which the certificate of an archive is checked.
+/// They must come from a source trusted by the auditor, e.g. the smart contract of the
+/// election, rather than from the archive itself.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CertificateAnchors {
+    /// Rescue digest of the manifest of the election
+    pub manifest_digest: rescue::Hash,
+    /// Public key of the aggregator
+    pub aggregator_key: [BaseElement; AFFINE_POINT_WIDTH],
+}
+
+/// Artifacts of all phases of an election
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElectionArchive {
+    /// Serialized root of the Merkle tree of eligible voters
+    pub elg_root: Vec<u8>,
+    /// Serialized state of the voter registar
+    pub registrations: Vec<u8>,
+    /// Register proof
+    pub register_proof: Vec<u8>,
+    /// Voting keys of registered voters, prefixed with their big-endian count
+    pub voting_keys: Vec<u8>,
+    /// Serialized state of the vote collector
+    pub ballots: Vec<u8>,
+    /// Cast proof
+    pub cast_proof: Vec<u8>,
+    /// Encrypted votes, prefixed with their little-endian count
+    pub encrypted_votes: Vec<u8>,
+    /// Number of "yes" votes
+    pub tally_result: u32,
+    /// Serialized result certificate
+    pub certificate: Option<Vec<u8>>,
+}
+
+impl ElectionArchive {
+    /// Collect the artifacts of an election from the aggregator objects of each phase,
+    /// generating the register and cast proofs and the tally result if needed
+    pub fn collect(
+        registar: &mut VoterRegistar,
+        collector: &mut VoteCollector,
+        tallier: &mut VoteTallier,
+        certificate: Option<&ResultCertificate>,
+    ) -> Result<Self, ArchiveError> {
+        let mut elg_root = vec![];
+        Serializable::write_batch_into(&registar.elg_root, &mut elg_root);
+        let register_proof = registar
+            .get_register_proof()
+            .map_err(ArchiveError::RegisterProof)?;
+
+        let mut voting_keys = vec![];
+        voting_keys.extend_from_slice(&(collector.voting_keys.len() as u32).to_be_bytes());
+        for voting_key in collector.voting_keys.iter() {
+            voting_keys.extend_from_slice(&compress_point(voting_key));
+        }
+        let cast_proof = collector
+            .get_cast_proof()
+            .map_err(ArchiveError::CastProof)?;
+
+        let tally_result = tallier.tally_votes().map_err(ArchiveError::Tally)?;
+        let encrypted_votes = tallier.encrypted_votes_bytes();
+
+        Ok(Self {
+            elg_root,
+            registrations: registar.to_bytes(),
+            register_proof,
+            voting_keys,
+            ballots: collector.to_bytes(),
+            cast_proof,
+            encrypted_votes,
+            tally_result,
+            certificate: certificate.map(|certificate| certificate.to_bytes()),
+        })
+    }
+
+    /// Entries of the archive, in the order they are written
+    pub fn entries(&self) -> Vec<(&'static str, Vec<u8>)> {
+        let mut entries = vec![
+            (ELG_ROOT, self.elg_root.clone()),
+            (REGISTRATIONS, self.registrations.clone()),
+            (REGISTER_PROOF, self.register_proof.clone()),
+            (VOTING_KEYS, self.voting_keys.clone()),
+            (BALLOTS, self.ballots.clone()),
+            (CAST_PROOF, self.cast_proof.clone()),
+            (ENCRYPTED_VOTES, self.encrypted_votes.clone()),
+            (TALLY_RESULT, self.tally_result.to_be_bytes().to_vec()),
+        ];
+        if let Some(certificate) = &self.certificate {
+            entries.push((CERTIFICATE, certificate.clone()));
+        }
+        entries
+    }
+
+    /// Manifest listing the Blake3 digest of each entry, one `<digest>  <name>` line per entry
+    pub fn manifest(&self) -> String {
+        let mut manifest = format!("{}\n", MANIFEST_HEADER);
+        for (name, data) in self.entries() {
+            manifest.push_str(&format!("{}  {}\n", hex::encode(digest(&data)), name));
+        }
+        manifest
+    }
+
+    /// Write the archive as a gzip-compressed tar stream
+    pub fn write_to<W: Write>(&self, target: W) -> Result<(), ArchiveError> {
+        let mut builder = tar::Builder::new(GzEncoder::new(target, Compression::default()));
+        append_entry(&mut builder, MANIFEST, self.manifest().as_bytes())?;
+        for (name, data) in self.entries() {
+            append_entry(&mut builder, name, &data)?;
+        }
+        builder.into_inner()?.finish()?;
+        Ok(())
+    }
+
+    /// Read an archive from a gzip-compressed tar stream, checking every entry
+    /// against the digests of the manifest
+    pub fn read_from<R: Read>(source: R) -> Result<Self, ArchiveError> {
+        let mut archive = tar::Archive::new(GzDecoder::new(source));
+        let mut entries = BTreeMap::new();
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let name = entry.path()?.to_string_lossy().into_owned();
+            let mut data = vec![];
+            entry.read_to_end(&mut data)?;
+            entries.insert(name, data);
+        }
+
+        let manifest = entries
+            .remove(MANIFEST)
+            .ok_or_else(|| ArchiveError::MissingEntry(String::from(MANIFEST)))?;
+        let manifest = String::from_utf8(manifest)
+            .map_err(|_| ArchiveError::Malformed(String::from(MANIFEST)))?;
+        let mut lines = manifest.lines();
+        if lines.next() != Some(MANIFEST_HEADER) {
+            return Err(ArchiveError::Malformed(String::from(MANIFEST)));
+        }
+        let mut listed = BTreeSet::new();
+        for line in lines {
+            let (expected, name) = line
+                .split_once("  ")
+                .ok_or_else(|| ArchiveError::Malformed(String::from(MANIFEST)))?;
+            let data = entries
+                .get(name)
+                .ok_or_else(|| ArchiveError::MissingEntry(String::from(name)))?;
+            if hex::encode(digest(data)) != expected {
+                return Err(ArchiveError::DigestMismatch(String::from(name)));
+            }
+            listed.insert(name);
+        }
+        if let Some(name) = entries.keys().find(|name| !listed.contains(name.as_str())) {
+            return Err(ArchiveError::UnlistedEntry(name.clone()));
+        }
+
+        let mut take = |name: &str| {
+            entries
+                .remove(name)
+                .ok_or_else(|| ArchiveError::MissingEntry(String::from(name)))
+        };
+        let tally_result = take(TALLY_RESULT)?;
+        if tally_result.len() != 4 {
+            return Err(ArchiveError::Malformed(String::from(TALLY_RESULT)));
+        }
+        let mut tmp = [0u8; 4];
+        tmp.copy_from_slice(&tally_result);
+
+        Ok(Self {
+            elg_root: take(ELG_ROOT)?,
+            registrations: take(REGISTRATIONS)?,
+            register_proof: take(REGISTER_PROOF)?,
+            voting_keys: take(VOTING_KEYS)?,
+            ballots: take(BALLOTS)?,
+            cast_proof: take(CAST_PROOF)?,
+            encrypted_votes: take(ENCRYPTED_VOTES)?,
+            tally_result: u32::from_be_bytes(tmp),
+            certificate: take(CERTIFICATE).ok(),
+        })
+    }
+
+    /// Write the archive to a file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ArchiveError> {
+        self.write_to(File::create(path)?)
+    }
+
+    /// Read the archive from a file
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ArchiveError> {
+        Self::read_from(File::open(path)?)
+    }
+
+    /// Re-verify all phases of the election and their consistency. The certificate, if any,
+    /// is checked against the given anchors, and rejected if there are none.
+    pub fn verify(&self, anchors: Option<&CertificateAnchors>) -> VerificationReport {
+        let certificate = self.certificate.as_ref().map(|certificate| {
+            let certificate = ResultCertificate::from_bytes(certificate)?;
+            match anchors {
+                Some(anchors) => self.check_certificate(&certificate, anchors),
+                None => Ok(false),
+            }
+        });
+
+        VerificationReport {
+            register: verify_register_proof(&self.elg_root, &self.register_proof),
+            cast: verify_cast_proof(&self.voting_keys, &self.cast_proof),
+            tally: verify_tally_result(&self.encrypted_votes, self.tally_result),
+            consistency: self.check_consistency(),
+            certificate,
+        }
+    }
+
+    /// Check that the cast proof is verified against the voting keys listed by the register
+    /// proof, and that the tallied encrypted votes are those of the valid ballots of the
+    /// cast proof
+    fn check_consistency(&self) -> Result<bool, DeserializationError> {
+        let registered_keys =
+            read_registered_voting_keys(&decompress_proof(&self.register_proof)?)?;
+        let cast_votes = read_valid_encrypted_votes(&decompress_proof(&self.cast_proof)?)?;
+        let tallied_votes = decode_encrypted_votes(&self.encrypted_votes)?;
+        Ok(registered_keys == self.voting_keys && cast_votes == tallied_votes)
+    }
+
+    /// Check that a certificate is signed by the aggregator of the anchors for the election
+    /// of their manifest, and that it matches the artifacts of the archive
+    fn check_certificate(
+        &self,
+        certificate: &ResultCertificate,
+        anchors: &CertificateAnchors,
+    ) -> Result<bool, DeserializationError> {
+        let tallied_votes = decode_encrypted_votes(&self.encrypted_votes)?;
+        let num_no_votes = (tallied_votes.len() as u64).checked_sub(self.tally_result as u64);
+        Ok(verify_certificate(
+            certificate,
+            &anchors.manifest_digest,
+            &anchors.aggregator_key,
+        ) && certificate.yes_votes == self.tally_result
+            && num_no_votes == Some(certificate.no_votes as u64)
+            && certificate.commits_to(&tallied_votes)
+            && certificate.contains_proof(&self.register_proof)
+            && certificate.contains_proof(&self.cast_proof))
+    }
+}
+
+/// Read the election archive at path and re-verify all of its phases,
+/// checking its certificate, if any, against the given anchors
+pub fn replay_and_verify<P: AsRef<Path>>(
+    path: P,
+    anchors: Option<&CertificateAnchors>,
+) -> Result<VerificationReport, ArchiveError> {
+    Ok(ElectionArchive::open(path)?.verify(anchors))
+}
+
+// VERIFICATION REPORT
+// ================================================================================================
+
+/// Outcome of the verification of each phase of an election
+#[derive(Debug)]
+pub struct VerificationReport {
+    /// Outcome of the register proof verification
+    pub register: Result<bool, DeserializationError>,
+    /// Outcome of the cast proof verification
+    pub cast: Result<bool, DeserializationError>,
+    /// Outcome of the tally result verification
+    pub tally: Result<bool, DeserializationError>,
+    /// Outcome of the checks that the registered voting keys are those of the cast proof,
+    /// and that the tallied encrypted votes are those of its valid ballots
+    pub consistency: Result<bool, DeserializationError>,
+    /// Outcome of the certificate verification, if the archive contains one
+    pub certificate: Option<Result<bool, DeserializationError>>,
+}
+
+impl VerificationReport {
+    /// Return true if every phase, their consistency and the certificate if any, was verified
+    pub fn is_valid(&self) -> bool {
+        let passed = |outcome: &Result<bool, DeserializationError>| matches!(outcome, Ok(true));
+        passed(&self.register)
+            && passed(&self.cast)
+            && passed(&self.tally)
+            && passed(&self.consistency)
+            && self.certificate.as_ref().map_or(true, passed)
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn digest(data: &[u8]) -> [u8; 32] {
+    Blake3_256::<BaseElement>::hash(data).as_bytes()
+}
+
+fn append_entry<W: Write>(
+    builder: &mut tar::Builder<W>,
+    name: &str,
+    data: &[u8],
+) -> Result<(), ArchiveError> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, name, data)?;
+    Ok(())
+}
@@ -0,0 +1,160 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+use crate::aggregator::AggregatorExample;
+use crate::compat::curve::Scalar;
+use crate::manifest::{ElectionManifest, Schedule};
+use crate::utils::ecc::{mul_generator_constant_time, projective_to_elements};
+use rand_core::{OsRng, RngCore};
+
+#[test]
+fn archive_test_round_trip() {
+    let (archive, anchors) = build_archive();
+    let mut bytes = vec![];
+    archive.write_to(&mut bytes).unwrap();
+
+    let restored = ElectionArchive::read_from(bytes.as_slice()).unwrap();
+    assert_eq!(restored, archive);
+    let report = restored.verify(Some(&anchors));
+    assert!(report.is_valid(), "{:?}", report);
+    assert!(matches!(report.consistency, Ok(true)));
+    assert!(matches!(report.certificate, Some(Ok(true))));
+
+    // without anchors, the certificate cannot be trusted
+    let report = restored.verify(None);
+    assert!(!report.is_valid());
+    assert!(matches!(report.certificate, Some(Ok(false))));
+}
+
+#[test]
+fn archive_test_replay_and_verify() {
+    let (archive, anchors) = build_archive();
+    let path = std::env::temp_dir().join(format!("openvote-archive-{}.tar.gz", OsRng.next_u64()));
+    archive.save(&path).unwrap();
+    let report = replay_and_verify(&path, Some(&anchors));
+    std::fs::remove_file(&path).unwrap();
+    assert!(report.unwrap().is_valid());
+}
+
+#[test]
+fn archive_test_wrong_tally_result() {
+    let (mut archive, anchors) = build_archive();
+    archive.tally_result ^= 1;
+    let mut bytes = vec![];
+    archive.write_to(&mut bytes).unwrap();
+
+    // the manifest is consistent, but the election is not
+    let report = ElectionArchive::read_from(bytes.as_slice())
+        .unwrap()
+        .verify(Some(&anchors));
+    assert!(!report.is_valid());
+    assert!(matches!(report.register, Ok(true)));
+    assert!(matches!(report.cast, Ok(true)));
+    assert!(matches!(report.tally, Ok(false)));
+    assert!(matches!(report.consistency, Ok(true)));
+    assert!(matches!(report.certificate, Some(Ok(false))));
+}
+
+#[test]
+fn archive_test_wrong_aggregator_key() {
+    let (archive, mut anchors) = build_archive();
+    anchors.aggregator_key =
+        projective_to_elements(mul_generator_constant_time(Scalar::random(OsRng)));
+
+    let report = archive.verify(Some(&anchors));
+    assert!(!report.is_valid());
+    assert!(matches!(report.tally, Ok(true)));
+    assert!(matches!(report.certificate, Some(Ok(false))));
+}
+
+#[test]
+fn archive_test_inconsistent_phases() {
+    // a self-consistent tally of encrypted votes which were not cast
+    let (mut archive, anchors) = build_archive();
+    let (other, _) = build_archive();
+    archive.encrypted_votes = other.encrypted_votes;
+    archive.tally_result = other.tally_result;
+
+    let report = archive.verify(Some(&anchors));
+    assert!(!report.is_valid());
+    assert!(matches!(report.register, Ok(true)));
+    assert!(matches!(report.cast, Ok(true)));
+    assert!(matches!(report.tally, Ok(true)));
+    assert!(matches!(report.consistency, Ok(false)));
+    assert!(matches!(report.certificate, Some(Ok(false))));
+}
+
+#[test]
+fn archive_test_digest_mismatch() {
+    let (archive, _) = build_archive();
+    let mut tampered = archive.clone();
+    tampered.cast_proof[0] ^= 1;
+
+    // write the entries of the tampered archive along with the original manifest
+    let mut bytes = vec![];
+    let mut builder = tar::Builder::new(GzEncoder::new(&mut bytes, Compression::default()));
+    append_entry(&mut builder, MANIFEST, archive.manifest().as_bytes()).unwrap();
+    for (name, data) in tampered.entries() {
+        append_entry(&mut builder, name, &data).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap();
+
+    assert!(matches!(
+        ElectionArchive::read_from(bytes.as_slice()),
+        Err(ArchiveError::DigestMismatch(name)) if name == CAST_PROOF
+    ));
+}
+
+#[test]
+fn archive_test_missing_entry() {
+    let (archive, _) = build_archive();
+    let mut bytes = vec![];
+    let mut builder = tar::Builder::new(GzEncoder::new(&mut bytes, Compression::default()));
+    for (name, data) in archive.entries() {
+        append_entry(&mut builder, name, &data).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap();
+
+    assert!(matches!(
+        ElectionArchive::read_from(bytes.as_slice()),
+        Err(ArchiveError::MissingEntry(name)) if name == MANIFEST
+    ));
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn build_archive() -> (ElectionArchive, CertificateAnchors) {
+    let mut example = AggregatorExample::new(4);
+    let mut archive = ElectionArchive::collect(
+        &mut example.voter_registar,
+        &mut example.vote_collector,
+        &mut example.vote_tallier,
+        None,
+    )
+    .unwrap();
+
+    let manifest = ElectionManifest::new("Adopt the proposal?", Schedule::default());
+    let secret_key = Scalar::random(OsRng);
+    let certificate = example
+        .vote_tallier
+        .certify(
+            &[&archive.register_proof, &archive.cast_proof],
+            &manifest,
+            secret_key,
+        )
+        .unwrap();
+    archive.certificate = Some(certificate.to_bytes());
+
+    let anchors = CertificateAnchors {
+        manifest_digest: manifest.digest(),
+        aggregator_key: projective_to_elements(mul_generator_constant_time(secret_key)),
+    };
+    (archive, anchors)
+}
@@ -0,0 +1,161 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// PROVING BACKENDS
+// ================================================================================================
+// The aggregator proves each phase of an election through the ElectionProver trait, and creates
+// the provers of the selected backend through the RegisterBackend, CastBackend and TallyBackend
+// aliases. An alternative backend, e.g. a GPU-accelerated fork of winterfell, provides provers
+// with the same constructors and implements ElectionProver for them behind a feature, leaving
+// the aggregator unchanged. Proofs remain winterfell STARK proofs, as checked by the verifier.
+
+use crate::{
+    cds::{CDSProver, PublicInputs as CdsPublicInputs},
+    planner::Phase,
+    register::{
+        constants::{DIGEST_SIZE, TREE_DEPTH},
+        PublicInputs as RegisterPublicInputs, RegisterProver,
+    },
+    schnorr::SigInfo,
+    tally::{PublicInputs as TallyPublicInputs, TallyProver},
+};
+use winterfell::{
+    math::fields::f63::BaseElement, Prover, ProverError, Serializable, StarkProof, TraceTable,
+};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+use log::debug;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+#[cfg(test)]
+mod tests;
+
+/// Prover of a phase of an election
+pub trait ElectionProver {
+    /// Phase of the election proven
+    const PHASE: Phase;
+    /// Inputs of the execution trace, besides those the prover was created with
+    type TraceInputs;
+    /// Execution trace of the phase
+    type Trace;
+    /// Public inputs of the proof
+    type PublicInputs: Serializable;
+
+    /// Build the execution trace of the phase
+    fn trace(&self, inputs: Self::TraceInputs) -> Self::Trace;
+
+    /// Public inputs of the proof of the execution trace
+    fn public_inputs(&self, trace: &Self::Trace) -> Self::PublicInputs;
+
+    /// Generate a STARK proof of the execution trace
+    fn generate_proof(&self, trace: Self::Trace) -> Result<StarkProof, ProverError>;
+
+    /// Build the execution trace and prove it, returning the public inputs and the proof
+    fn prove_phase(
+        &self,
+        inputs: Self::TraceInputs,
+    ) -> Result<(Self::PublicInputs, StarkProof), ProverError> {
+        #[cfg(feature = "std")]
+        let now = Instant::now();
+        let trace = self.trace(inputs);
+        let pub_inputs = self.public_inputs(&trace);
+        let proof = self.generate_proof(trace)?;
+        #[cfg(feature = "std")]
+        debug!(
+            "Proved {:?} phase in {} ms",
+            Self::PHASE,
+            now.elapsed().as_millis()
+        );
+        Ok((pub_inputs, proof))
+    }
+}
+
+/// Inputs of the execution trace of the registration phase, prepared by the aggregator
+pub(crate) struct RegisterTraceInputs {
+    pub merkle_messages: Vec<[BaseElement; (TREE_DEPTH + 1) * DIGEST_SIZE]>,
+    pub hash_indices: Vec<usize>,
+    pub sig_infos: Vec<SigInfo>,
+}
+
+// SELECTED BACKEND
+// ================================================================================================
+
+/// Prover of the registration phase of the selected backend
+pub(crate) type RegisterBackend<'a> = RegisterProver<'a>;
+/// Prover of the vote casting phase of the selected backend
+pub(crate) type CastBackend<'a> = CDSProver<'a>;
+/// Prover of the tally phase of the selected backend
+pub(crate) type TallyBackend<'a> = TallyProver<'a>;
+
+// WINTERFELL BACKEND
+// ================================================================================================
+
+impl<'a> ElectionProver for RegisterProver<'a> {
+    const PHASE: Phase = Phase::Register;
+    type TraceInputs = RegisterTraceInputs;
+    type Trace = TraceTable<BaseElement>;
+    type PublicInputs = RegisterPublicInputs;
+
+    fn trace(&self, inputs: RegisterTraceInputs) -> Self::Trace {
+        self.build_trace_from_inputs(
+            &inputs.merkle_messages,
+            &inputs.hash_indices,
+            &inputs.sig_infos,
+        )
+    }
+
+    fn public_inputs(&self, trace: &Self::Trace) -> RegisterPublicInputs {
+        self.get_pub_inputs(trace)
+    }
+
+    fn generate_proof(&self, trace: Self::Trace) -> Result<StarkProof, ProverError> {
+        self.prove(trace)
+    }
+}
+
+impl<'a> ElectionProver for CDSProver<'a> {
+    const PHASE: Phase = Phase::Cast;
+    type TraceInputs = ();
+    type Trace = TraceTable<BaseElement>;
+    type PublicInputs = CdsPublicInputs;
+
+    fn trace(&self, _inputs: ()) -> Self::Trace {
+        self.build_trace()
+    }
+
+    fn public_inputs(&self, trace: &Self::Trace) -> CdsPublicInputs {
+        self.get_pub_inputs(trace)
+    }
+
+    fn generate_proof(&self, trace: Self::Trace) -> Result<StarkProof, ProverError> {
+        self.prove(trace)
+    }
+}
+
+impl<'a> ElectionProver for TallyProver<'a> {
+    const PHASE: Phase = Phase::Tally;
+    type TraceInputs = ();
+    type Trace = TraceTable<BaseElement>;
+    type PublicInputs = TallyPublicInputs;
+
+    fn trace(&self, _inputs: ()) -> Self::Trace {
+        self.build_trace()
+    }
+
+    fn public_inputs(&self, trace: &Self::Trace) -> TallyPublicInputs {
+        self.get_pub_inputs(trace)
+    }
+
+    fn generate_proof(&self, trace: Self::Trace) -> Result<StarkProof, ProverError> {
+        self.prove(trace)
+    }
+}
@@ -0,0 +1,43 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+use crate::{
+    aggregator::build_options,
+    cds::{CDSAir, CDSExample},
+    compat::verify,
+    tally::{TallyAir, TallyExample},
+};
+
+#[test]
+fn backend_test_prove_phases() {
+    assert_eq!(RegisterBackend::PHASE, Phase::Register);
+    assert_eq!(CastBackend::PHASE, Phase::Cast);
+    assert_eq!(TallyBackend::PHASE, Phase::Tally);
+
+    let (example, _) = CDSExample::new(build_options(1), 4);
+    let prover = CastBackend::new(
+        build_options(1),
+        &example.voting_keys,
+        &example.encrypted_votes,
+        &example.proof_points,
+        &example.proof_scalars,
+    );
+    let (pub_inputs, proof) = prover.prove_phase(()).unwrap();
+    assert!(verify::<CDSAir>(proof, pub_inputs).is_ok());
+
+    let example = TallyExample::new(build_options(1), 4);
+    let prover = TallyBackend::new(
+        build_options(1),
+        &example.encrypted_votes,
+        example.tally_result,
+    );
+    let (pub_inputs, proof) = prover.prove_phase(()).unwrap();
+    assert_eq!(pub_inputs.tally_result, example.tally_result);
+    assert!(verify::<TallyAir>(proof, pub_inputs).is_ok());
+}
@@ -0,0 +1,393 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// WRITE-IN CANDIDATES
+// ================================================================================================
+// A ballot may carry a write-in alongside its encrypted vote: a salted Rescue commitment to a
+// free-text candidate. The voter signs the whole ballot with their voting key, bound to the
+// election context, so that the aggregator can neither swap the write-in, move it to another
+// ballot, nor strip it. Write-ins stay hidden while the structured options are tallied. Openings
+// are then submitted without voter index, and the revealed write-ins are counted. Write-ins
+// which are never opened are only counted as unrevealed.
+
+use crate::{
+    aggregator::{
+        cast::{CollectorError, EncryptedVote, VoteCollector},
+        tally::{TallierError, VoteTallier},
+    },
+    cds::{concat_proof_points, constants::UNBOUND_CONTEXT},
+    compat::curve::Scalar,
+    manifest::{encode_candidate, CandidateError},
+    schnorr::{sign_digest, verify_digest_signature},
+    utils::{
+        ecc::{projective_to_elements, AFFINE_POINT_WIDTH, POINT_COORDINATE_WIDTH},
+        rescue::{self, Rescue63, DIGEST_SIZE},
+        wire,
+    },
+};
+use rand_core::{OsRng, RngCore};
+use winterfell::{
+    math::{fields::f63::BaseElement, FieldElement, StarkField},
+    ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable, SliceReader,
+};
+
+#[cfg(not(feature = "std"))]
+use alloc::{collections::BTreeMap, string::String};
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+#[cfg(test)]
+mod tests;
+
+/// Number of field elements of the salt of a write-in commitment
+pub const SALT_SIZE: usize = DIGEST_SIZE;
+
+// ERRORS
+// ================================================================================================
+
+/// Errors raised when collecting and revealing write-ins
+#[derive(Debug, PartialEq)]
+pub enum BallotError {
+    /// This error occurs when the voter index of a ballot is out of range
+    UnknownVoter,
+    /// This error occurs when a ballot is not signed by the voting key of its voter
+    /// in the election context of the vote collector
+    InvalidSignature,
+    /// This error occurs when the write-in of a ballot has the same commitment
+    /// as the write-in of an accepted ballot
+    DuplicateWriteIn,
+    /// This error occurs when the encrypted vote of a ballot is rejected by the vote collector
+    Collector(CollectorError),
+    /// This error occurs when an opening matches the write-in of no accepted ballot
+    NoWriteIn,
+    /// This error occurs when the write-in of a voter was already revealed
+    AlreadyRevealed,
+    /// This error occurs when the candidate of an opening cannot be encoded
+    InvalidCandidate(CandidateError),
+    /// This error occurs when an opening does not match the commitment of the write-in
+    InvalidOpening,
+}
+
+// WRITE-INS
+// ================================================================================================
+
+/// Opening of a write-in commitment, kept by the voter until the structured options are tallied
+#[derive(Debug, Clone, PartialEq)]
+pub struct WriteInOpening {
+    /// Free-text candidate, subject to the rules of manifest::validate_candidate
+    pub candidate: String,
+    /// Random salt hiding the candidate
+    pub salt: [BaseElement; SALT_SIZE],
+}
+
+impl WriteInOpening {
+    /// Create an opening of the given candidate with a random salt
+    pub fn new(candidate: &str) -> Result<Self, CandidateError> {
+        Self::new_with_rng(candidate, &mut OsRng)
+    }
+
+    /// Same as `new`, drawing the salt from the given random number generator
+    pub fn new_with_rng<R: RngCore>(candidate: &str, rng: &mut R) -> Result<Self, CandidateError> {
+        encode_candidate(candidate)?;
+        let mut salt = [BaseElement::ZERO; SALT_SIZE];
+        for element in salt.iter_mut() {
+            *element = BaseElement::from(rng.next_u64());
+        }
+        Ok(Self {
+            candidate: String::from(candidate),
+            salt,
+        })
+    }
+
+    /// Reconstruct an object of type Self from a sequence of bytes
+    pub fn from_bytes(source: &[u8]) -> Result<Self, DeserializationError> {
+        let mut source = SliceReader::new(source);
+        Self::read_from(&mut source)
+    }
+
+    /// Commitment to the candidate of the opening
+    pub fn commit(&self) -> Result<rescue::Hash, CandidateError> {
+        commit_write_in(&self.candidate, &self.salt)
+    }
+}
+
+/// Ballot of a voter: an encrypted vote on the structured options, and possibly the commitment
+/// to a write-in, signed together by the voter
+#[derive(Debug, Clone, Copy)]
+pub struct Ballot {
+    /// Encrypted vote on the structured options
+    pub encrypted_vote: EncryptedVote,
+    /// Salted commitment to the write-in of the voter, if any
+    pub write_in: Option<rescue::Hash>,
+    /// Schnorr signature of the ballot with the voting key of the voter, see ballot_digest
+    pub signature: ([BaseElement; POINT_COORDINATE_WIDTH], Scalar),
+}
+
+impl Ballot {
+    /// Encrypt the vote of the voter at voter_index, commit to the given write-in,
+    /// if any, and sign the ballot
+    pub fn cast(
+        voter_index: usize,
+        secret_key: Scalar,
+        voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+        vote: bool,
+        write_in: Option<&WriteInOpening>,
+    ) -> Result<Self, CandidateError> {
+        let encrypted_vote = EncryptedVote::cast(voter_index, secret_key, voting_keys, vote);
+        Self::sign(encrypted_vote, secret_key, &UNBOUND_CONTEXT, write_in)
+    }
+
+    /// Same as `cast`, for an election whose collector is bound to
+    /// the manifest with the given digest
+    pub fn cast_with_manifest(
+        voter_index: usize,
+        secret_key: Scalar,
+        voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+        vote: bool,
+        manifest_digest: &[BaseElement; DIGEST_SIZE],
+        write_in: Option<&WriteInOpening>,
+    ) -> Result<Self, CandidateError> {
+        let encrypted_vote = EncryptedVote::cast_with_manifest(
+            voter_index,
+            secret_key,
+            voting_keys,
+            vote,
+            manifest_digest,
+        );
+        Self::sign(encrypted_vote, secret_key, manifest_digest, write_in)
+    }
+
+    /// Sign an encrypted vote along with the commitment of the given write-in, if any,
+    /// in the given election context
+    pub fn sign(
+        encrypted_vote: EncryptedVote,
+        secret_key: Scalar,
+        context: &[BaseElement; DIGEST_SIZE],
+        write_in: Option<&WriteInOpening>,
+    ) -> Result<Self, CandidateError> {
+        let write_in = match write_in {
+            Some(opening) => Some(opening.commit()?),
+            None => None,
+        };
+        let digest = ballot_digest(context, &encrypted_vote, write_in.as_ref());
+        Ok(Self {
+            encrypted_vote,
+            write_in,
+            signature: sign_digest(secret_key, &digest),
+        })
+    }
+
+    /// Check that the ballot is signed with the given voting key in the given election
+    /// context, i.e. the one bound into the CDS proofs of the vote collector
+    pub fn verify(
+        &self,
+        voting_key: [BaseElement; AFFINE_POINT_WIDTH],
+        context: &[BaseElement; DIGEST_SIZE],
+    ) -> bool {
+        let digest = ballot_digest(context, &self.encrypted_vote, self.write_in.as_ref());
+        verify_digest_signature(voting_key, &digest, self.signature).is_ok()
+    }
+}
+
+// WRITE-IN TALLY
+// ================================================================================================
+
+/// Result of an election with write-ins
+#[derive(Debug, Clone, PartialEq)]
+pub struct WriteInResult {
+    /// Number of "yes" votes on the structured options
+    pub tally_result: u32,
+    /// Number of tallied votes
+    pub num_votes: u32,
+    /// Number of revealed write-ins of each candidate
+    pub write_ins: BTreeMap<String, u32>,
+    /// Number of write-ins which were not revealed
+    pub num_unrevealed: usize,
+}
+
+/// Type that collects the write-ins of accepted ballots and counts them once revealed.
+/// Write-ins are recorded by commitment only, so that their openings are not linked to
+/// voter indices.
+#[derive(Debug, Clone, Default)]
+pub struct WriteInTally {
+    /// Write-ins of accepted ballots, by commitment, with their candidate once revealed
+    write_ins: BTreeMap<CommitmentKey, Option<String>>,
+}
+
+impl WriteInTally {
+    /// Create an object of type WriteInTally without write-ins
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check the signature of a ballot, add its encrypted vote to the collector, and
+    /// record its write-in, if any, once the encrypted vote is accepted
+    pub fn add_ballot(
+        &mut self,
+        collector: &mut VoteCollector,
+        ballot: Ballot,
+    ) -> Result<(), BallotError> {
+        let voter_index = ballot.encrypted_vote.voter_index();
+        let voting_key = *collector
+            .voting_keys
+            .get(voter_index)
+            .ok_or(BallotError::UnknownVoter)?;
+        if !ballot.verify(voting_key, &collector.cds_context()) {
+            return Err(BallotError::InvalidSignature);
+        }
+        let key = ballot.write_in.as_ref().map(commitment_key);
+        if let Some(key) = key {
+            if self.write_ins.contains_key(&key) {
+                return Err(BallotError::DuplicateWriteIn);
+            }
+        }
+        collector
+            .add_encrypted_vote(ballot.encrypted_vote)
+            .map_err(BallotError::Collector)?;
+        if let Some(key) = key {
+            self.write_ins.insert(key, None);
+        }
+        Ok(())
+    }
+
+    /// Reveal the write-in committed to by the given opening
+    pub fn reveal(&mut self, opening: &WriteInOpening) -> Result<(), BallotError> {
+        let commitment = opening.commit().map_err(BallotError::InvalidCandidate)?;
+        match self.write_ins.get_mut(&commitment_key(&commitment)) {
+            None => Err(BallotError::NoWriteIn),
+            Some(Some(_)) => Err(BallotError::AlreadyRevealed),
+            Some(candidate) => {
+                *candidate = Some(opening.candidate.clone());
+                Ok(())
+            }
+        }
+    }
+
+    /// Return true if the write-in with the given commitment was recorded, so that a voter
+    /// can check that their write-in was not stripped from their ballot
+    pub fn contains(&self, commitment: &rescue::Hash) -> bool {
+        self.write_ins.contains_key(&commitment_key(commitment))
+    }
+
+    /// Number of write-ins of accepted ballots
+    pub fn num_write_ins(&self) -> usize {
+        self.write_ins.len()
+    }
+
+    /// Number of write-ins which were not revealed
+    pub fn num_unrevealed(&self) -> usize {
+        self.write_ins
+            .values()
+            .filter(|candidate| candidate.is_none())
+            .count()
+    }
+
+    /// Tally the structured options, then count the revealed write-ins
+    pub fn tally(&self, tallier: &mut VoteTallier) -> Result<WriteInResult, TallierError> {
+        let tally_result = tallier.tally_votes()?;
+        let mut write_ins = BTreeMap::new();
+        for candidate in self.write_ins.values().flatten() {
+            *write_ins.entry(candidate.clone()).or_insert(0) += 1;
+        }
+        Ok(WriteInResult {
+            tally_result,
+            num_votes: tallier.encrypted_votes.len() as u32,
+            write_ins,
+            num_unrevealed: self.num_unrevealed(),
+        })
+    }
+}
+
+// OPENINGS
+// ================================================================================================
+
+/// Salted commitment to a free-text candidate. Candidates are encoded with
+/// manifest::encode_candidate, which is injective on valid identifiers.
+pub fn commit_write_in(
+    candidate: &str,
+    salt: &[BaseElement; SALT_SIZE],
+) -> Result<rescue::Hash, CandidateError> {
+    let encoded = encode_candidate(candidate)?;
+    let mut hasher = Rescue63::new();
+    hasher.update(salt);
+    hasher.update(&encoded);
+    Ok(hasher.finalize())
+}
+
+/// Check that an opening matches a write-in commitment
+pub fn verify_opening(
+    commitment: &rescue::Hash,
+    opening: &WriteInOpening,
+) -> Result<(), BallotError> {
+    let expected = opening.commit().map_err(BallotError::InvalidCandidate)?;
+    if expected != *commitment {
+        return Err(BallotError::InvalidOpening);
+    }
+    Ok(())
+}
+
+// SERIALIZATION
+// ================================================================================================
+
+impl Serializable for WriteInOpening {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        wire::write_varint(target, self.candidate.len() as u64);
+        target.write_u8_slice(self.candidate.as_bytes());
+        Serializable::write_batch_into(&self.salt, target);
+    }
+}
+
+impl Deserializable for WriteInOpening {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let len = wire::read_varint_usize(source)?;
+        let candidate = String::from_utf8(source.read_u8_vec(len)?).map_err(|_| {
+            DeserializationError::InvalidValue(String::from("Invalid UTF-8 string."))
+        })?;
+        let mut salt = [BaseElement::ZERO; SALT_SIZE];
+        salt.copy_from_slice(&BaseElement::read_batch_from(source, SALT_SIZE)?);
+        Ok(Self { candidate, salt })
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Ordering key of a write-in commitment
+type CommitmentKey = [u64; DIGEST_SIZE];
+
+/// Key of a write-in commitment in a WriteInTally
+fn commitment_key(commitment: &rescue::Hash) -> CommitmentKey {
+    let mut key = [0u64; DIGEST_SIZE];
+    for (k, element) in key.iter_mut().zip(commitment.to_elements().iter()) {
+        *k = element.as_int();
+    }
+    key
+}
+
+/// Digest of a ballot, which is what the voter signs: the election context, the voter index,
+/// the encrypted vote and its CDS proof points, followed by the write-in commitment, if any.
+/// A flag tells whether the ballot has a write-in, so that it cannot be stripped.
+fn ballot_digest(
+    context: &[BaseElement; DIGEST_SIZE],
+    encrypted_vote: &EncryptedVote,
+    write_in: Option<&rescue::Hash>,
+) -> rescue::Hash {
+    let mut hasher = Rescue63::new();
+    hasher.update(context);
+    hasher.update(&[BaseElement::from(encrypted_vote.voter_index as u64)]);
+    hasher.update(&projective_to_elements(encrypted_vote.encrypted_vote));
+    hasher.update(&concat_proof_points(&encrypted_vote.proof_points));
+    match write_in {
+        Some(commitment) => {
+            hasher.update(&[BaseElement::ONE]);
+            hasher.update(&commitment.to_elements());
+        }
+        None => hasher.update(&[BaseElement::ZERO]),
+    }
+    hasher.finalize()
+}
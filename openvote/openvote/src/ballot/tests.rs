@@ -0,0 +1,122 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+use crate::schnorr::random_key_pairs;
+
+#[test]
+fn ballot_test_write_in_tally() {
+    let (secret_keys, voting_keys) = random_key_pairs(4);
+    let mut collector = VoteCollector::new(voting_keys.clone());
+    let mut write_ins = WriteInTally::new();
+    let candidates = ["Jane Roe", "John Doe", "Jane Roe"];
+    let openings = candidates
+        .iter()
+        .map(|candidate| WriteInOpening::new(candidate).unwrap())
+        .collect::<Vec<_>>();
+
+    for (i, &secret_key) in secret_keys.iter().enumerate() {
+        let ballot = Ballot::cast(i, secret_key, &voting_keys, i == 3, openings.get(i)).unwrap();
+        assert!(write_ins.add_ballot(&mut collector, ballot).is_ok());
+    }
+    assert_eq!(write_ins.num_write_ins(), 3);
+    assert!(write_ins.contains(&openings[1].commit().unwrap()));
+
+    // write-ins are revealed with their openings only, without voter index
+    let unknown = WriteInOpening::new("Jane Roe").unwrap();
+    assert_eq!(write_ins.reveal(&unknown), Err(BallotError::NoWriteIn));
+    assert!(write_ins.reveal(&openings[0]).is_ok());
+    assert_eq!(
+        write_ins.reveal(&openings[0]),
+        Err(BallotError::AlreadyRevealed)
+    );
+    assert!(write_ins.reveal(&openings[2]).is_ok());
+
+    let mut tallier = VoteTallier::new(
+        collector
+            .encrypted_votes
+            .iter()
+            .map(|encrypted_vote| encrypted_vote.unwrap())
+            .collect(),
+    );
+    let result = write_ins.tally(&mut tallier).unwrap();
+    assert_eq!(result.tally_result, 1);
+    assert_eq!(result.num_votes, 4);
+    assert_eq!(result.write_ins.len(), 1);
+    assert_eq!(result.write_ins["Jane Roe"], 2);
+    assert_eq!(result.num_unrevealed, 1);
+}
+
+#[test]
+fn ballot_test_write_in_binding() {
+    let (secret_keys, voting_keys) = random_key_pairs(2);
+    let mut collector = VoteCollector::new(voting_keys.clone());
+    let mut write_ins = WriteInTally::new();
+    let opening = WriteInOpening::new("Jane Roe").unwrap();
+    assert_eq!(
+        WriteInOpening::from_bytes(&opening.to_bytes()).unwrap(),
+        opening
+    );
+
+    // the write-in is signed along with the encrypted vote, in the election context
+    let ballot = Ballot::cast(0, secret_keys[0], &voting_keys, true, Some(&opening)).unwrap();
+    assert!(ballot.verify(voting_keys[0], &UNBOUND_CONTEXT));
+    assert!(!ballot.verify(voting_keys[1], &UNBOUND_CONTEXT));
+    assert!(!ballot.verify(voting_keys[0], &[BaseElement::ONE; DIGEST_SIZE]));
+
+    // the write-in can neither be stripped nor moved to another ballot
+    let mut stripped = ballot;
+    stripped.write_in = None;
+    assert!(!stripped.verify(voting_keys[0], &UNBOUND_CONTEXT));
+    let mut moved = Ballot::cast(1, secret_keys[1], &voting_keys, true, None).unwrap();
+    moved.write_in = ballot.write_in;
+    moved.signature = ballot.signature;
+    assert_eq!(
+        write_ins.add_ballot(&mut collector, moved),
+        Err(BallotError::InvalidSignature)
+    );
+    assert_eq!(collector.num_valid_votes, 0);
+
+    // a ballot signed for another manifest is rejected
+    let manifest_digest = [BaseElement::ONE; DIGEST_SIZE];
+    let bound = Ballot::cast_with_manifest(
+        1,
+        secret_keys[1],
+        &voting_keys,
+        true,
+        &manifest_digest,
+        None,
+    )
+    .unwrap();
+    assert!(bound.verify(voting_keys[1], &manifest_digest));
+    assert_eq!(
+        write_ins.add_ballot(&mut collector, bound),
+        Err(BallotError::InvalidSignature)
+    );
+
+    // write-ins are unique
+    assert!(write_ins.add_ballot(&mut collector, ballot).is_ok());
+    let copied = Ballot::cast(1, secret_keys[1], &voting_keys, true, Some(&opening)).unwrap();
+    assert_eq!(
+        write_ins.add_ballot(&mut collector, copied),
+        Err(BallotError::DuplicateWriteIn)
+    );
+
+    // candidates follow the rules of the manifest identifiers
+    assert!(WriteInOpening::new(" Jane Roe").is_err());
+    let mut invalid = opening.clone();
+    invalid.candidate = String::from("Jane\nRoe");
+    assert!(matches!(
+        verify_opening(&opening.commit().unwrap(), &invalid),
+        Err(BallotError::InvalidCandidate(_))
+    ));
+    assert!(matches!(
+        write_ins.reveal(&invalid),
+        Err(BallotError::InvalidCandidate(_))
+    ));
+}
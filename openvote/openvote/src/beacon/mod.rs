@@ -0,0 +1,331 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// RANDOMNESS BEACONS
+// ================================================================================================
+// Public randomness used by the protocol where nothing needs to stay secret: the placement of
+// voting keys among the leaves of the eligibility tree, the weights of batch verification and
+// the sampling of audits. Drawing it from a public beacon round lets anyone re-derive it, so an
+// aggregator cannot pick favorable values. The randomness of a round is expanded into a stream
+// by BeaconRng, separated by purpose with a domain label. Secrets such as voting keys, blinding
+// factors and signature nonces are still drawn from the operating system.
+
+use rand_core::{impls, OsRng, RngCore};
+use winterfell::{
+    crypto::{hashers::Blake3_256, Digest, Hasher},
+    math::fields::f63::BaseElement,
+};
+
+#[cfg(not(feature = "std"))]
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+#[cfg(test)]
+mod tests;
+
+/// Randomness of a beacon round
+pub type BeaconOutput = [u8; 32];
+
+/// Domain of the placement of voting keys among the leaves of the eligibility tree
+pub const LEAF_SHUFFLE_DOMAIN: &[u8] = b"openvote/leaf-shuffle";
+/// Domain of the weights of batch verification of CDS proofs
+pub const BATCH_WEIGHTS_DOMAIN: &[u8] = b"openvote/batch-weights";
+/// Domain of the sampling of audits
+pub const AUDIT_SAMPLE_DOMAIN: &[u8] = b"openvote/audit-sample";
+
+/// Chain hash of the drand mainnet chain
+pub const DRAND_MAINNET_CHAIN_HASH: &str =
+    "8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce";
+
+// ERRORS
+// ================================================================================================
+
+/// Errors raised when reading the randomness of a beacon
+#[derive(Debug, Clone, PartialEq)]
+pub enum BeaconError {
+    /// This error occurs when the randomness of the requested round is not known to the beacon
+    UnknownRound(u64),
+    /// This error occurs when the beacon cannot be reached
+    Request(String),
+    /// This error occurs when the response of the beacon has no valid randomness
+    InvalidResponse(String),
+    /// This error occurs when the requested round is not later than the round at which
+    /// the randomness was committed to, so that it may have been known in advance
+    PrematureRound(u64),
+}
+
+// BEACON
+// ================================================================================================
+
+/// Source of public randomness, indexed by round
+pub trait Beacon {
+    /// Randomness of the given round
+    fn randomness(&self, round: u64) -> Result<BeaconOutput, BeaconError>;
+
+    /// Random number generator expanding the randomness of the given round for the given purpose
+    fn rng(&self, round: u64, domain: &[u8]) -> Result<BeaconRng, BeaconError> {
+        Ok(BeaconRng::new(&self.randomness(round)?, domain, &[]))
+    }
+}
+
+/// Deterministic random number generator expanding the randomness of a beacon round with
+/// Blake3 in counter mode. Its output is public, so it must not be used to draw secrets.
+#[derive(Debug, Clone)]
+pub struct BeaconRng {
+    seed: BeaconOutput,
+    counter: u64,
+    block: BeaconOutput,
+    position: usize,
+}
+
+impl BeaconRng {
+    /// Create a generator from the randomness of a round, separated by a domain label
+    /// and bound to a context, e.g. the data the randomness is applied to
+    pub fn new(randomness: &BeaconOutput, domain: &[u8], context: &[u8]) -> Self {
+        let mut bytes = Vec::with_capacity(randomness.len() + 8 + domain.len() + context.len());
+        bytes.extend_from_slice(randomness);
+        bytes.extend_from_slice(&(domain.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(domain);
+        bytes.extend_from_slice(context);
+        Self {
+            seed: blake3(&bytes),
+            counter: 0,
+            block: [0u8; 32],
+            position: 32,
+        }
+    }
+
+    /// Uniformly random integer below bound, without modulo bias
+    pub fn below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "Bound must be positive.");
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+impl RngCore for BeaconRng {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest.iter_mut() {
+            if self.position == self.block.len() {
+                let mut bytes = [0u8; 40];
+                bytes[..32].copy_from_slice(&self.seed);
+                bytes[32..].copy_from_slice(&self.counter.to_le_bytes());
+                self.block = blake3(&bytes);
+                self.counter += 1;
+                self.position = 0;
+            }
+            *byte = self.block[self.position];
+            self.position += 1;
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+// BEACON IMPLEMENTATIONS
+// ================================================================================================
+
+/// Rounds of a drand chain. The BLS signatures of the rounds are not checked,
+/// so rounds must be fetched from a trusted relay or checked out of band.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrandBeacon {
+    /// Hex-encoded hash of the drand chain
+    pub chain_hash: String,
+    rounds: BTreeMap<u64, BeaconOutput>,
+}
+
+impl DrandBeacon {
+    /// Create a beacon of the given drand chain without known rounds
+    pub fn new(chain_hash: &str) -> Self {
+        Self {
+            chain_hash: String::from(chain_hash),
+            rounds: BTreeMap::new(),
+        }
+    }
+
+    /// Record the randomness of a round, without checking its signature
+    pub fn insert(&mut self, round: u64, randomness: BeaconOutput) {
+        self.rounds.insert(round, randomness);
+    }
+
+    /// Record the round of a JSON response of the public endpoint of a drand relay,
+    /// and return its number. The signature of the round is not checked, so the
+    /// response must come from a trusted relay.
+    pub fn insert_response(&mut self, response: &str) -> Result<u64, BeaconError> {
+        let invalid = || BeaconError::InvalidResponse(String::from(response));
+        let round = json_field(response, "round")
+            .and_then(|round| round.parse::<u64>().ok())
+            .ok_or_else(invalid)?;
+        let randomness = json_field(response, "randomness")
+            .and_then(|randomness| hex::decode(randomness.trim_matches('"')).ok())
+            .filter(|randomness| randomness.len() == 32)
+            .ok_or_else(invalid)?;
+        let mut output = [0u8; 32];
+        output.copy_from_slice(&randomness);
+        self.insert(round, output);
+        Ok(round)
+    }
+
+    /// Fetch a round from the public endpoint of a drand relay, e.g. `https://api.drand.sh`
+    #[cfg(feature = "drand")]
+    pub fn fetch(&mut self, relay_url: &str, round: u64) -> Result<BeaconOutput, BeaconError> {
+        let url = format!(
+            "{}/{}/public/{}",
+            relay_url.trim_end_matches('/'),
+            self.chain_hash,
+            round
+        );
+        let response = ureq::get(&url)
+            .call()
+            .map_err(|e| BeaconError::Request(e.to_string()))?
+            .into_string()
+            .map_err(|e| BeaconError::Request(e.to_string()))?;
+        if self.insert_response(&response)? != round {
+            return Err(BeaconError::InvalidResponse(response));
+        }
+        self.randomness(round)
+    }
+}
+
+impl Beacon for DrandBeacon {
+    fn randomness(&self, round: u64) -> Result<BeaconOutput, BeaconError> {
+        self.rounds
+            .get(&round)
+            .copied()
+            .ok_or(BeaconError::UnknownRound(round))
+    }
+}
+
+/// Block hashes of the chain the election contract runs on, the round being the block number.
+/// Block proposers can bias a block hash by withholding their block, so this beacon is only
+/// suitable where such a bias is tolerable.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockhashBeacon {
+    block_hashes: BTreeMap<u64, [u8; 32]>,
+}
+
+impl BlockhashBeacon {
+    /// Create a beacon without known blocks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the hash of a block
+    pub fn insert(&mut self, block_number: u64, block_hash: [u8; 32]) {
+        self.block_hashes.insert(block_number, block_hash);
+    }
+}
+
+impl Beacon for BlockhashBeacon {
+    fn randomness(&self, round: u64) -> Result<BeaconOutput, BeaconError> {
+        self.block_hashes
+            .get(&round)
+            .copied()
+            .ok_or(BeaconError::UnknownRound(round))
+    }
+}
+
+/// Beacon drawing a seed from the operating system, used when no public beacon is
+/// configured. Its randomness is unpredictable but cannot be re-derived by others.
+#[derive(Debug, Clone)]
+pub struct LocalBeacon {
+    seed: BeaconOutput,
+}
+
+impl LocalBeacon {
+    /// Create a beacon with a fresh seed
+    pub fn new() -> Self {
+        Self {
+            seed: local_randomness(),
+        }
+    }
+}
+
+impl Default for LocalBeacon {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Beacon for LocalBeacon {
+    fn randomness(&self, round: u64) -> Result<BeaconOutput, BeaconError> {
+        let mut bytes = [0u8; 40];
+        bytes[..32].copy_from_slice(&self.seed);
+        bytes[32..].copy_from_slice(&round.to_le_bytes());
+        Ok(blake3(&bytes))
+    }
+}
+
+// AUDIT SAMPLING
+// ================================================================================================
+
+/// Sample count distinct indices below population from a beacon round, e.g. the ballots
+/// or registrations to spot-check. Anyone can re-derive the sample from the round.
+pub fn sample_indices<B: Beacon>(
+    beacon: &B,
+    round: u64,
+    population: usize,
+    count: usize,
+) -> Result<Vec<usize>, BeaconError> {
+    assert!(count <= population, "Sample exceeds the population.");
+    let mut rng = beacon.rng(round, AUDIT_SAMPLE_DOMAIN)?;
+    // partial Fisher-Yates shuffle, remembering only the swapped positions
+    let mut swapped = BTreeMap::new();
+    let mut sample = Vec::with_capacity(count);
+    for i in 0..count {
+        let j = i + rng.below((population - i) as u64) as usize;
+        let picked = *swapped.get(&j).unwrap_or(&j);
+        swapped.insert(j, *swapped.get(&i).unwrap_or(&i));
+        sample.push(picked);
+    }
+    Ok(sample)
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Fresh randomness drawn from the operating system
+pub fn local_randomness() -> BeaconOutput {
+    let mut randomness = [0u8; 32];
+    OsRng.fill_bytes(&mut randomness);
+    randomness
+}
+
+fn blake3(bytes: &[u8]) -> BeaconOutput {
+    Blake3_256::<BaseElement>::hash(bytes).as_bytes()
+}
+
+/// Raw value of a field of a flat JSON object
+fn json_field<'a>(json: &'a str, name: &str) -> Option<&'a str> {
+    let key = format!("\"{}\":", name);
+    let start = json.find(&key)? + key.len();
+    let value = json[start..].trim_start();
+    let end = if value.starts_with('"') {
+        value[1..].find('"')? + 2
+    } else {
+        value.find(|c: char| c == ',' || c == '}')?
+    };
+    Some(value[..end].trim_end())
+}
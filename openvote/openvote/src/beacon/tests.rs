@@ -0,0 +1,109 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+use crate::{
+    merkle::{build_merkle_tree_with_beacon, constants::TREE_DEPTH},
+    utils::{ecc::AFFINE_POINT_WIDTH, rescue::DIGEST_SIZE},
+};
+use winterfell::math::FieldElement;
+
+const DRAND_RESPONSE: &str = r#"{"round":1000,"randomness":"fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd","signature":"b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39","previous_signature":"a5d2f8ca2a69dce5e4e9d3f2b09a2e76cb8e39d4c4b02e5c5bd85bc24e6e05f0c2e9c63a4a0a9f9a0c45f34cbb7c52ff"}"#;
+
+#[test]
+fn beacon_test_drand_rounds() {
+    let mut beacon = DrandBeacon::new(DRAND_MAINNET_CHAIN_HASH);
+    assert_eq!(
+        beacon.randomness(1000),
+        Err(BeaconError::UnknownRound(1000))
+    );
+    assert_eq!(beacon.insert_response(DRAND_RESPONSE), Ok(1000));
+    let randomness = beacon.randomness(1000).unwrap();
+    assert_eq!(
+        hex::encode(randomness),
+        "fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd"
+    );
+
+    assert!(beacon.insert_response(r#"{"round":1001}"#).is_err());
+    assert!(beacon
+        .insert_response(r#"{"round":1001,"randomness":"fe29"}"#)
+        .is_err());
+    assert_eq!(
+        beacon.randomness(1001),
+        Err(BeaconError::UnknownRound(1001))
+    );
+}
+
+#[test]
+fn beacon_test_rng_derivation() {
+    let mut beacon = BlockhashBeacon::new();
+    beacon.insert(17, [7u8; 32]);
+    assert_eq!(beacon.randomness(17), Ok([7u8; 32]));
+
+    // the stream is determined by the round and the domain
+    let mut rng = beacon.rng(17, LEAF_SHUFFLE_DOMAIN).unwrap();
+    let mut same_rng = beacon.rng(17, LEAF_SHUFFLE_DOMAIN).unwrap();
+    let mut other_rng = beacon.rng(17, AUDIT_SAMPLE_DOMAIN).unwrap();
+    let mut bytes = [0u8; 100];
+    let mut same_bytes = [0u8; 100];
+    let mut other_bytes = [0u8; 100];
+    rng.fill_bytes(&mut bytes);
+    same_rng.fill_bytes(&mut same_bytes[..33]);
+    same_rng.fill_bytes(&mut same_bytes[33..]);
+    other_rng.fill_bytes(&mut other_bytes);
+    assert_eq!(bytes, same_bytes);
+    assert_ne!(bytes, other_bytes);
+    assert!(rng.below(10) < 10);
+
+    // the local beacon is consistent within an instance only
+    let local = LocalBeacon::new();
+    assert_eq!(local.randomness(3), local.randomness(3));
+    assert_ne!(local.randomness(3), local.randomness(4));
+    assert_ne!(local.randomness(3), LocalBeacon::new().randomness(3));
+}
+
+#[test]
+fn beacon_test_audit_sample() {
+    let mut beacon = BlockhashBeacon::new();
+    beacon.insert(1, [1u8; 32]);
+    let sample = sample_indices(&beacon, 1, 50, 10).unwrap();
+    assert_eq!(sample.len(), 10);
+    assert!(sample.iter().all(|&index| index < 50));
+    let mut distinct = sample.clone();
+    distinct.sort_unstable();
+    distinct.dedup();
+    assert_eq!(distinct.len(), 10);
+    assert_eq!(sample_indices(&beacon, 1, 50, 10).unwrap(), sample);
+
+    let mut full = sample_indices(&beacon, 1, 8, 8).unwrap();
+    full.sort_unstable();
+    assert_eq!(full, (0..8).collect::<Vec<usize>>());
+    assert_eq!(
+        sample_indices(&beacon, 2, 8, 1),
+        Err(BeaconError::UnknownRound(2))
+    );
+}
+
+#[test]
+fn beacon_test_leaf_shuffle() {
+    let mut beacon = BlockhashBeacon::new();
+    beacon.insert(5, [5u8; 32]);
+    let voting_keys = (1..=4u64)
+        .map(|i| [BaseElement::from(i); AFFINE_POINT_WIDTH])
+        .collect::<Vec<_>>();
+
+    // anyone can re-derive the tree from the voting keys and the round
+    let (tree, hash_indices) =
+        build_merkle_tree_with_beacon(&voting_keys, TREE_DEPTH, &beacon, 5).unwrap();
+    let (same_tree, same_indices) =
+        build_merkle_tree_with_beacon(&voting_keys, TREE_DEPTH, &beacon, 5).unwrap();
+    assert_eq!(tree.root(), same_tree.root());
+    assert_eq!(hash_indices, same_indices);
+    assert_ne!(tree.root(), [BaseElement::ZERO; DIGEST_SIZE]);
+    assert!(build_merkle_tree_with_beacon(&voting_keys, TREE_DEPTH, &beacon, 6).is_err());
+}
@@ -0,0 +1,147 @@
+use openvote::archive::{
+    CertificateAnchors, ElectionArchive, VerificationReport, CAST_PROOF, CERTIFICATE, ELG_ROOT,
+    ENCRYPTED_VOTES, REGISTER_PROOF, TALLY_RESULT, VOTING_KEYS,
+};
+use openvote::utils::{
+    ecc::{decompress_point, BYTES_PER_COMPRESSED_POINT},
+    rescue,
+};
+use std::{env, fs, path::Path, process};
+use winterfell::{Deserializable, DeserializationError, SliceReader};
+
+const USAGE: &str = "Usage: openvote-audit <archive file | example directory> \
+[<manifest digest> <aggregator key>]
+
+Re-verifies the register proof, cast proof and tally result of an election, either
+from an archive or from the .dat files written by generate-example, and checks that
+they are consistent with each other.
+
+The result certificate, if any, is only accepted when the hex-encoded manifest digest
+and compressed aggregator key are given, and should be taken from a trusted source
+such as the smart contract of the election.";
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if (args.len() != 2 && args.len() != 4) || args[1] == "-h" || args[1] == "--help" {
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    }
+
+    let anchors = if args.len() == 4 {
+        match parse_anchors(&args[2], &args[3]) {
+            Ok(anchors) => Some(anchors),
+            Err(e) => {
+                eprintln!("Could not read certificate anchors: {}", e);
+                process::exit(2);
+            }
+        }
+    } else {
+        None
+    };
+
+    let path = Path::new(&args[1]);
+    let archive = if path.is_dir() {
+        println!("Auditing election files in directory {}", path.display());
+        read_example_dir(path)
+    } else {
+        println!("Auditing election archive {}", path.display());
+        ElectionArchive::open(path).map_err(|e| format!("{:?}", e))
+    };
+    let archive = match archive {
+        Ok(archive) => archive,
+        Err(e) => {
+            eprintln!("Could not read election artifacts: {}", e);
+            process::exit(2);
+        }
+    };
+
+    let report = archive.verify(anchors.as_ref());
+    print_report(&archive, &report);
+    if !report.is_valid() {
+        process::exit(1);
+    }
+}
+
+/// Collect the artifacts written by generate-example into an archive
+fn read_example_dir(dir: &Path) -> Result<ElectionArchive, String> {
+    let read =
+        |name: &str| fs::read(dir.join(name)).map_err(|e| format!("cannot read {}: {}", name, e));
+
+    let tally_result = read(TALLY_RESULT)?;
+    if tally_result.len() != 4 {
+        return Err(format!("{} must contain exactly 4 bytes", TALLY_RESULT));
+    }
+    let mut tmp = [0u8; 4];
+    tmp.copy_from_slice(&tally_result);
+
+    Ok(ElectionArchive {
+        elg_root: read(ELG_ROOT)?,
+        registrations: vec![],
+        register_proof: read(REGISTER_PROOF)?,
+        voting_keys: read(VOTING_KEYS)?,
+        ballots: vec![],
+        cast_proof: read(CAST_PROOF)?,
+        encrypted_votes: read(ENCRYPTED_VOTES)?,
+        tally_result: u32::from_be_bytes(tmp),
+        certificate: read(CERTIFICATE).ok(),
+    })
+}
+
+/// Parse the hex-encoded manifest digest and compressed aggregator key
+fn parse_anchors(
+    manifest_digest: &str,
+    aggregator_key: &str,
+) -> Result<CertificateAnchors, String> {
+    let manifest_digest =
+        hex::decode(manifest_digest).map_err(|e| format!("invalid manifest digest: {}", e))?;
+    let manifest_digest = rescue::Hash::read_from(&mut SliceReader::new(&manifest_digest))
+        .map_err(|e| format!("invalid manifest digest: {}", e))?;
+
+    let aggregator_key =
+        hex::decode(aggregator_key).map_err(|e| format!("invalid aggregator key: {}", e))?;
+    if aggregator_key.len() != BYTES_PER_COMPRESSED_POINT {
+        return Err(format!(
+            "aggregator key must contain exactly {} bytes",
+            BYTES_PER_COMPRESSED_POINT
+        ));
+    }
+    let mut tmp = [0u8; BYTES_PER_COMPRESSED_POINT];
+    tmp.copy_from_slice(&aggregator_key);
+    let aggregator_key =
+        decompress_point(&tmp).ok_or_else(|| "aggregator key is not a curve point".to_string())?;
+
+    Ok(CertificateAnchors {
+        manifest_digest,
+        aggregator_key,
+    })
+}
+
+fn print_report(archive: &ElectionArchive, report: &VerificationReport) {
+    println!();
+    print_outcome("Voter registration (register proof)", &report.register);
+    print_outcome("Vote casting (CDS proofs)", &report.cast);
+    print_outcome(
+        &format!("Vote tallying ({} \"yes\" votes)", archive.tally_result),
+        &report.tally,
+    );
+    print_outcome("Consistency of the phases", &report.consistency);
+    match &report.certificate {
+        Some(outcome) => print_outcome("Result certificate", outcome),
+        None => println!("  {:<45} NOT PROVIDED", "Result certificate"),
+    }
+    println!();
+
+    if report.is_valid() {
+        println!("RESULT: the election was verified successfully.");
+    } else {
+        println!("RESULT: the election could NOT be verified.");
+    }
+}
+
+fn print_outcome(step: &str, outcome: &Result<bool, DeserializationError>) {
+    match outcome {
+        Ok(true) => println!("  {:<45} PASSED", step),
+        Ok(false) => println!("  {:<45} FAILED", step),
+        Err(e) => println!("  {:<45} FAILED (malformed data: {})", step, e),
+    }
+}
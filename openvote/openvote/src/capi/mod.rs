@@ -0,0 +1,125 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// C API
+// ================================================================================================
+// `extern "C"` wrappers of the verifier entry points, so that the verifier can be embedded in
+// other runtimes. Inputs are passed as (pointer, length) byte slices, and panics are caught so
+// that they never unwind across the FFI boundary. The header include/openvote.h is generated
+// with cbindgen, see cbindgen.toml.
+
+use crate::verifier;
+use core::slice;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use winterfell::DeserializationError;
+
+#[cfg(test)]
+mod tests;
+
+/// Status codes returned by the C API
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenvoteStatus {
+    /// The proof or result is valid
+    Valid = 0,
+    /// The proof or result is invalid
+    Invalid = 1,
+    /// The input bytes could not be deserialized
+    Malformed = 2,
+    /// A required pointer is null
+    NullPointer = 3,
+    /// The verifier panicked, which no input is expected to cause
+    Panic = 4,
+}
+
+/// Verify a register proof against the serialized root of the Merkle tree of eligible voters
+///
+/// # Safety
+/// `elg_root` and `register_proof` must point to `elg_root_len` and `register_proof_len`
+/// readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn openvote_verify_register_proof(
+    elg_root: *const u8,
+    elg_root_len: usize,
+    register_proof: *const u8,
+    register_proof_len: usize,
+) -> OpenvoteStatus {
+    let elg_root = match as_slice(elg_root, elg_root_len) {
+        Some(elg_root) => elg_root,
+        None => return OpenvoteStatus::NullPointer,
+    };
+    let register_proof = match as_slice(register_proof, register_proof_len) {
+        Some(register_proof) => register_proof,
+        None => return OpenvoteStatus::NullPointer,
+    };
+    guard(|| verifier::verify_register_proof(elg_root, register_proof))
+}
+
+/// Verify a cast proof against the voting keys of registered voters
+///
+/// # Safety
+/// `voting_keys` and `cast_proof` must point to `voting_keys_len` and `cast_proof_len`
+/// readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn openvote_verify_cast_proof(
+    voting_keys: *const u8,
+    voting_keys_len: usize,
+    cast_proof: *const u8,
+    cast_proof_len: usize,
+) -> OpenvoteStatus {
+    let voting_keys = match as_slice(voting_keys, voting_keys_len) {
+        Some(voting_keys) => voting_keys,
+        None => return OpenvoteStatus::NullPointer,
+    };
+    let cast_proof = match as_slice(cast_proof, cast_proof_len) {
+        Some(cast_proof) => cast_proof,
+        None => return OpenvoteStatus::NullPointer,
+    };
+    guard(|| verifier::verify_cast_proof(voting_keys, cast_proof))
+}
+
+/// Verify a tally result against the encrypted votes
+///
+/// # Safety
+/// `encrypted_votes` must point to `encrypted_votes_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn openvote_verify_tally_result(
+    encrypted_votes: *const u8,
+    encrypted_votes_len: usize,
+    tally_result: u32,
+) -> OpenvoteStatus {
+    let encrypted_votes = match as_slice(encrypted_votes, encrypted_votes_len) {
+        Some(encrypted_votes) => encrypted_votes,
+        None => return OpenvoteStatus::NullPointer,
+    };
+    guard(|| verifier::verify_tally_result(encrypted_votes, tally_result))
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Build a byte slice from a C pointer and length, accepting a null pointer for empty slices
+unsafe fn as_slice<'a>(data: *const u8, len: usize) -> Option<&'a [u8]> {
+    if data.is_null() {
+        return if len == 0 { Some(&[]) } else { None };
+    }
+    Some(slice::from_raw_parts(data, len))
+}
+
+/// Run a verification, converting its outcome and any panic into a status code
+fn guard<F>(verification: F) -> OpenvoteStatus
+where
+    F: FnOnce() -> Result<bool, DeserializationError>,
+{
+    match catch_unwind(AssertUnwindSafe(verification)) {
+        Ok(Ok(true)) => OpenvoteStatus::Valid,
+        Ok(Ok(false)) => OpenvoteStatus::Invalid,
+        Ok(Err(_)) => OpenvoteStatus::Malformed,
+        Err(_) => OpenvoteStatus::Panic,
+    }
+}
@@ -0,0 +1,74 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+use crate::aggregator::{register::VoterRegistar, tally::VoteTallier};
+use core::ptr;
+use winterfell::Serializable;
+
+#[test]
+fn capi_test_register_proof() {
+    let mut registar = VoterRegistar::get_example(2);
+    let register_proof = registar.get_register_proof().unwrap();
+    let mut elg_root = vec![];
+    Serializable::write_batch_into(&registar.elg_root, &mut elg_root);
+
+    let status = unsafe {
+        openvote_verify_register_proof(
+            elg_root.as_ptr(),
+            elg_root.len(),
+            register_proof.as_ptr(),
+            register_proof.len(),
+        )
+    };
+    assert_eq!(status, OpenvoteStatus::Valid);
+
+    // truncated input is rejected without panicking
+    for &len in [2, 4, register_proof.len() / 2].iter() {
+        let status = unsafe {
+            openvote_verify_register_proof(
+                elg_root.as_ptr(),
+                elg_root.len(),
+                register_proof.as_ptr(),
+                len,
+            )
+        };
+        assert_eq!(status, OpenvoteStatus::Malformed);
+    }
+}
+
+#[test]
+fn capi_test_tally_result() {
+    let (mut tallier, _) = VoteTallier::get_example(4);
+    let tally_result = tallier.tally_votes().unwrap();
+    let encrypted_votes = tallier.encrypted_votes_bytes();
+
+    let verify = |bytes: &[u8], tally_result| unsafe {
+        openvote_verify_tally_result(bytes.as_ptr(), bytes.len(), tally_result)
+    };
+    assert_eq!(
+        verify(&encrypted_votes, tally_result),
+        OpenvoteStatus::Valid
+    );
+    assert_eq!(
+        verify(&encrypted_votes, tally_result + 1),
+        OpenvoteStatus::Invalid
+    );
+    assert_eq!(
+        verify(&encrypted_votes[..encrypted_votes.len() - 1], tally_result),
+        OpenvoteStatus::Malformed
+    );
+}
+
+#[test]
+fn capi_test_null_pointer() {
+    let status = unsafe { openvote_verify_tally_result(ptr::null(), 4, 0) };
+    assert_eq!(status, OpenvoteStatus::NullPointer);
+    let status = unsafe { openvote_verify_cast_proof(ptr::null(), 0, ptr::null(), 8) };
+    assert_eq!(status, OpenvoteStatus::NullPointer);
+}
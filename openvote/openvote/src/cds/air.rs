@@ -9,8 +9,14 @@
 use super::super::utils::periodic_columns::stitch;
 use super::constants::*;
 use super::trace::prepare_encrypted_votes;
-use super::{ecc, field, rescue};
-use crate::utils::{are_equal, not, EvaluationResult};
+use super::{
+    build_transcript, compute_tracker, ecc, election_context, field, read_validity_bitmap, rescue,
+    write_validity_bitmap,
+};
+use crate::{
+    chain::{write_phase_commitment, PhaseCommitment},
+    utils::{are_equal, not, ordering::KeyOrder, EvaluationResult},
+};
 use winterfell::{
     math::{fields::f63::BaseElement, FieldElement},
     Air, AirContext, Assertion, ByteReader, ByteWriter, Deserializable, DeserializationError,
@@ -24,78 +30,107 @@ use alloc::vec::Vec;
 // CDS AIR
 // ================================================================================================
 
+/// Position of a shard of voters in the global list of voters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShardInfo {
+    /// Global index of the first voter of the shard
+    pub first_voter_index: usize,
+    /// Blinding key of the first voter of the shard, derived
+    /// from the global list of voting keys
+    pub initial_blinding_key: [BaseElement; AFFINE_POINT_WIDTH],
+}
+
 #[derive(Debug, Clone)]
 pub struct PublicInputs {
     pub voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
     pub encrypted_votes: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
     // [a1, b1, a2, b2]
     pub cds_proofs: Vec<[BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH]>,
-    pub outputs: Vec<[BaseElement; AFFINE_POINT_WIDTH * 5]>,
+    // validity flags of ballots, the CDS proofs of invalid ballots are not asserted
+    pub validity: Vec<bool>,
+    // None if the proof covers all voters
+    pub shard: Option<ShardInfo>,
+    // digest of the election manifest (not read back), set by the verifier
+    pub manifest_digest: Option<[BaseElement; DIGEST_SIZE]>,
+    // digest of the manifest ballots were cast against, if it was amended since
+    // (not read back), set by the verifier
+    pub ballot_context: Option<[BaseElement; DIGEST_SIZE]>,
+    // commitment to the registration phase (not read back), set by the verifier
+    pub register_commitment: Option<PhaseCommitment>,
+    // order of the voting keys (not read back), set by the verifier
+    pub key_order: KeyOrder,
+    // affine blinding keys derived from the voting keys (not serialized),
+    // computed when the AIR is instantiated if left empty
+    pub blinding_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
 }
 
 impl Serializable for PublicInputs {
     fn write_into<W: ByteWriter>(&self, target: &mut W) {
         target.write_u32(self.voting_keys.len() as u32);
         for voting_key in self.voting_keys.iter() {
-            Serializable::write_batch_into(voting_key, target);
+            ecc::write_compressed_points(voting_key, target);
         }
         for encrypted_vote in self.encrypted_votes.iter() {
-            Serializable::write_batch_into(encrypted_vote, target);
+            ecc::write_compressed_points(encrypted_vote, target);
         }
         for cds_proof in self.cds_proofs.iter() {
-            Serializable::write_batch_into(cds_proof, target);
+            ecc::write_compressed_points(cds_proof, target);
+        }
+        write_validity_bitmap(&self.validity, target);
+        // shard information is only written for sharded proofs, so that
+        // the encoding of unsharded public inputs stays unchanged
+        if let Some(shard) = self.shard {
+            target.write_u32(shard.first_voter_index as u32);
+            Serializable::write_batch_into(&shard.initial_blinding_key, target);
         }
-        for output in self.outputs.iter() {
-            Serializable::write_batch_into(output, target);
+        if let Some(manifest_digest) = self.manifest_digest {
+            Serializable::write_batch_into(&manifest_digest, target);
         }
+        // the ballot context is only written for amended manifests,
+        // so that the encoding of other public inputs stays unchanged
+        if let Some(ballot_context) = self.ballot_context {
+            Serializable::write_batch_into(&ballot_context, target);
+        }
+        write_phase_commitment(self.register_commitment, target);
+        self.key_order.write_into(target);
     }
 }
 
 impl Deserializable for PublicInputs {
     fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
-        let mut voting_key = [BaseElement::ZERO; AFFINE_POINT_WIDTH];
-        let mut encrypted_vote = [BaseElement::ZERO; AFFINE_POINT_WIDTH];
         let mut cds_proof = [BaseElement::ZERO; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH];
-        let mut output = [BaseElement::ZERO; AFFINE_POINT_WIDTH * 5];
 
         let num_proofs = source.read_u32()? as usize;
         let mut voting_keys = Vec::with_capacity(num_proofs);
         let mut encrypted_votes = Vec::with_capacity(num_proofs);
         let mut cds_proofs = Vec::with_capacity(num_proofs);
-        let mut outputs = Vec::with_capacity(num_proofs);
 
         for _ in 0..num_proofs {
-            voting_key.copy_from_slice(&BaseElement::read_batch_from(source, AFFINE_POINT_WIDTH)?);
-            voting_keys.push(voting_key);
+            voting_keys.push(ecc::read_compressed_point(source)?);
         }
 
         for _ in 0..num_proofs {
-            encrypted_vote
-                .copy_from_slice(&BaseElement::read_batch_from(source, AFFINE_POINT_WIDTH)?);
-            encrypted_votes.push(encrypted_vote);
+            encrypted_votes.push(ecc::read_compressed_point(source)?);
         }
 
         for _ in 0..num_proofs {
-            cds_proof.copy_from_slice(&BaseElement::read_batch_from(
-                source,
-                PROOF_NUM_POINTS * AFFINE_POINT_WIDTH,
-            )?);
+            cds_proof.copy_from_slice(&ecc::read_compressed_points(source, PROOF_NUM_POINTS)?);
             cds_proofs.push(cds_proof);
         }
 
-        for _ in 0..num_proofs {
-            output.copy_from_slice(&BaseElement::read_batch_from(
-                source,
-                AFFINE_POINT_WIDTH * 5,
-            )?);
-            outputs.push(output);
-        }
+        let validity = read_validity_bitmap(source, num_proofs)?;
 
         Ok(Self {
             voting_keys,
             encrypted_votes,
             cds_proofs,
-            outputs,
+            validity,
+            shard: None,
+            manifest_digest: None,
+            ballot_context: None,
+            register_commitment: None,
+            key_order: KeyOrder::Submission,
+            blinding_keys: Vec::new(),
         })
     }
 }
@@ -105,6 +140,20 @@ impl PublicInputs {
         let mut source = SliceReader::new(source);
         Self::read_from(&mut source)
     }
+
+    /// Trackers of the encrypted votes, in the order of voters. They are derived
+    /// from the public inputs, so that the proof binds the ordered list of trackers.
+    pub fn trackers(&self) -> Vec<[u8; BYTES_PER_TRACKER]> {
+        let first_voter_index = self.shard.map_or(0, |shard| shard.first_voter_index);
+        self.encrypted_votes
+            .iter()
+            .zip(self.cds_proofs.iter())
+            .enumerate()
+            .map(|(i, (encrypted_vote, cds_proof))| {
+                compute_tracker(first_voter_index + i, encrypted_vote, cds_proof)
+            })
+            .collect()
+    }
 }
 
 pub struct CDSAir {
@@ -112,7 +161,10 @@ pub struct CDSAir {
     voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
     encrypted_votes: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
     cds_proofs: Vec<[BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH]>,
-    outputs: Vec<[BaseElement; AFFINE_POINT_WIDTH * 5]>,
+    validity: Vec<bool>,
+    shard: Option<ShardInfo>,
+    ballot_context: [BaseElement; DIGEST_SIZE],
+    blinding_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
 }
 
 impl Air for CDSAir {
@@ -124,12 +176,22 @@ impl Air for CDSAir {
     fn new(trace_info: TraceInfo, pub_inputs: PublicInputs, options: ProofOptions) -> Self {
         let degrees = transition_constraint_degrees();
         assert_eq!(TRACE_WIDTH, trace_info.width());
+        let blinding_keys = if pub_inputs.blinding_keys.len() == pub_inputs.voting_keys.len() {
+            pub_inputs.blinding_keys
+        } else {
+            compute_blinding_keys(&pub_inputs.voting_keys, pub_inputs.shard)
+        };
         CDSAir {
             context: AirContext::new(trace_info, degrees, options),
             voting_keys: pub_inputs.voting_keys,
             encrypted_votes: pub_inputs.encrypted_votes,
             cds_proofs: pub_inputs.cds_proofs,
-            outputs: pub_inputs.outputs,
+            validity: pub_inputs.validity,
+            shard: pub_inputs.shard,
+            ballot_context: election_context(
+                pub_inputs.ballot_context.or(pub_inputs.manifest_digest),
+            ),
+            blinding_keys,
         }
     }
 
@@ -205,9 +267,6 @@ impl Air for CDSAir {
     }
 
     fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
-        let (proof_points_a, proof_points_b, c_diff_value) =
-            transpose_proof_points(&self.cds_proofs, &self.outputs);
-
         // Assert starting and ending values
         let mut assertions = vec![];
 
@@ -273,14 +332,21 @@ impl Air for CDSAir {
             ));
         }
         // Rescue registers
+        let first_voter_index = self.shard.map_or(0, |shard| shard.first_voter_index);
         for i in 0..self.voting_keys.len() {
             assertions.push(Assertion::single(
                 5 * PROJECTIVE_POINT_WIDTH + 7,
                 i * CDS_CYCLE_LENGTH,
-                BaseElement::from(i as u8),
+                BaseElement::from((first_voter_index + i) as u64),
             ));
         }
-        for i in 1..HASH_STATE_WIDTH {
+        assertions.push(Assertion::periodic(
+            5 * PROJECTIVE_POINT_WIDTH + 8,
+            0,
+            CDS_CYCLE_LENGTH,
+            BaseElement::from(CDS_TRANSCRIPT_VERSION),
+        ));
+        for i in 2..HASH_STATE_WIDTH {
             assertions.push(Assertion::periodic(
                 i + 5 * PROJECTIVE_POINT_WIDTH + 7,
                 0,
@@ -289,39 +355,11 @@ impl Air for CDSAir {
             ));
         }
 
-        // END OF PHASE
-        for i in 0..AFFINE_POINT_WIDTH {
-            // a1, a2
-            assertions.push(Assertion::sequence(
-                i + PROJECTIVE_POINT_WIDTH + 1,
-                SCALAR_MUL_LENGTH + 1,
-                NROWS_PER_PHASE,
-                proof_points_a[i].to_owned(),
-            ));
-            // b1, b2
-            assertions.push(Assertion::sequence(
-                i + 2 * PROJECTIVE_POINT_WIDTH + 1,
-                SCALAR_MUL_LENGTH + 1,
-                NROWS_PER_PHASE,
-                proof_points_b[i].to_owned(),
-            ));
-        }
-
-        // END OF CYCLE
-        // (c - d1 - d2) * vk
-        for i in 0..POINT_COORDINATE_WIDTH {
-            assertions.push(Assertion::sequence(
-                i,
-                CDS_CYCLE_LENGTH - 1,
-                CDS_CYCLE_LENGTH,
-                c_diff_value[i].to_owned(),
-            ));
-            assertions.push(Assertion::sequence(
-                i + AFFINE_POINT_WIDTH,
-                CDS_CYCLE_LENGTH - 1,
-                CDS_CYCLE_LENGTH,
-                c_diff_value[i + POINT_COORDINATE_WIDTH].to_owned(),
-            ));
+        // Results of CDS proof verifications, for valid ballots only
+        if self.validity.iter().all(|&is_valid| is_valid) {
+            append_result_assertions(&mut assertions, &self.cds_proofs);
+        } else {
+            append_valid_result_assertions(&mut assertions, &self.cds_proofs, &self.validity);
         }
 
         assertions
@@ -357,26 +395,24 @@ impl Air for CDSAir {
         let mut voting_keys = vec![vec![BaseElement::ZERO; trace_width]; AFFINE_POINT_WIDTH];
         let mut encrypted_votes = vec![vec![BaseElement::ZERO; trace_width]; AFFINE_POINT_WIDTH];
 
-        let mut blinding_key = ecc::IDENTITY;
-        for voting_key in self.voting_keys.iter().skip(1) {
-            ecc::compute_add_mixed(&mut blinding_key, &ecc::compute_negation_affine(voting_key));
-        }
-        // we don't need to set hash_message[0] = BaseElement::from(voter_index)
-        // because we only take hash_message[HASH_RATE_WIDTH..]
-        let mut hash_message = [BaseElement::ZERO; HASH_MSG_LENGTH];
-
+        // the first block of the hash message, holding the voter index
+        // and the version of the message, is set by assertions
+        let first_voter_index = self.shard.map_or(0, |shard| shard.first_voter_index);
+        let context = self.ballot_context;
         for voter_index in 0..self.voting_keys.len() {
             let voting_key = self.voting_keys[voter_index];
             let encrypted_vote = self.encrypted_votes[voter_index];
-            let cds_proof = self.cds_proofs[voter_index];
-
-            hash_message[AFFINE_POINT_WIDTH..AFFINE_POINT_WIDTH * 2].copy_from_slice(&voting_key);
-            hash_message[AFFINE_POINT_WIDTH * 2..AFFINE_POINT_WIDTH * 3]
-                .copy_from_slice(&encrypted_vote);
-            hash_message[AFFINE_POINT_WIDTH * 3..AFFINE_POINT_WIDTH * 7]
-                .copy_from_slice(&cds_proof);
+            let affine_blinding_key = self.blinding_keys[voter_index];
+            let transcript = build_transcript(
+                first_voter_index + voter_index,
+                &voting_key,
+                &affine_blinding_key,
+                &encrypted_vote,
+                &self.cds_proofs[voter_index],
+                &context,
+            );
+            let hash_message = transcript.message();
 
-            let affine_blinding_key = ecc::reduce_to_affine(&blinding_key);
             let (encrypted_vote_1, encrypted_vote_2) = prepare_encrypted_votes(&encrypted_vote);
 
             for i in 0..NUM_HASH_ITER - 1 {
@@ -402,12 +438,6 @@ impl Air for CDSAir {
                     ..(voter_index + 1) * CDS_CYCLE_LENGTH]
                     .fill(encrypted_vote_2[i]);
             }
-
-            // get the blinding key of the next voter
-            if voter_index + 1 < self.voting_keys.len() {
-                ecc::compute_add_mixed(&mut blinding_key, &voting_key);
-                ecc::compute_add_mixed(&mut blinding_key, &self.voting_keys[voter_index + 1])
-            }
         }
 
         // Stitch in the above columns in the appropriate places
@@ -478,6 +508,45 @@ fn enforce_hash_copy<E: FieldElement>(
 // HELPER FUNCTIONS
 // ------------------------------------------------------------------------------------------------
 
+/// Compute the affine blinding keys of the given voters.
+/// For a shard, the chain starts from the affine key in the shard information,
+/// otherwise from the negated sum of all the voting keys but the first one.
+/// All keys are reduced to affine coordinates with a single field inversion.
+pub(crate) fn compute_blinding_keys(
+    voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+    shard: Option<ShardInfo>,
+) -> Vec<[BaseElement; AFFINE_POINT_WIDTH]> {
+    let mut blinding_key = match shard {
+        Some(shard) => {
+            let mut blinding_key = [BaseElement::ZERO; PROJECTIVE_POINT_WIDTH];
+            blinding_key[..AFFINE_POINT_WIDTH].copy_from_slice(&shard.initial_blinding_key);
+            blinding_key[AFFINE_POINT_WIDTH] = BaseElement::ONE;
+            blinding_key
+        }
+        None => {
+            let mut blinding_key = ecc::IDENTITY;
+            for voting_key in voting_keys.iter().skip(1) {
+                ecc::compute_add_mixed(
+                    &mut blinding_key,
+                    &ecc::compute_negation_affine(voting_key),
+                );
+            }
+            blinding_key
+        }
+    };
+
+    let mut blinding_keys = Vec::with_capacity(voting_keys.len());
+    for i in 0..voting_keys.len() {
+        blinding_keys.push(blinding_key);
+        // get the blinding key of the next voter
+        if i + 1 < voting_keys.len() {
+            ecc::compute_add_mixed(&mut blinding_key, &voting_keys[i]);
+            ecc::compute_add_mixed(&mut blinding_key, &voting_keys[i + 1]);
+        }
+    }
+    ecc::batch_reduce_to_affine(&blinding_keys)
+}
+
 pub(crate) fn periodic_columns() -> Vec<Vec<BaseElement>> {
     // We are computing the values for one whole CDS trace, i.e.
     // having only 1 global period of length CDS_CYCLE_LENGTH.
@@ -850,39 +919,118 @@ pub(crate) fn transition_constraint_degrees() -> Vec<TransitionConstraintDegree>
     degrees
 }
 
-#[allow(clippy::type_complexity)]
-fn transpose_proof_points(
-    cds_proofs: &Vec<[BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH]>,
-    outputs: &Vec<[BaseElement; AFFINE_POINT_WIDTH * 5]>,
-) -> (
-    Vec<Vec<BaseElement>>,
-    Vec<Vec<BaseElement>>,
-    Vec<Vec<BaseElement>>,
+/// Append the assertions on the results of CDS proof verifications
+/// when all ballots are valid, i.e. the computed points must be equal
+/// to the proof points and (c - d1 - d2) * vk must be the point at infinity
+fn append_result_assertions(
+    assertions: &mut Vec<Assertion<BaseElement>>,
+    cds_proofs: &[[BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH]],
+) {
+    let (proof_points_a, proof_points_b) = transpose_proof_points(cds_proofs);
+
+    // END OF PHASE
+    for i in 0..AFFINE_POINT_WIDTH {
+        // a1, a2
+        assertions.push(Assertion::sequence(
+            i + PROJECTIVE_POINT_WIDTH + 1,
+            SCALAR_MUL_LENGTH + 1,
+            NROWS_PER_PHASE,
+            proof_points_a[i].to_owned(),
+        ));
+        // b1, b2
+        assertions.push(Assertion::sequence(
+            i + 2 * PROJECTIVE_POINT_WIDTH + 1,
+            SCALAR_MUL_LENGTH + 1,
+            NROWS_PER_PHASE,
+            proof_points_b[i].to_owned(),
+        ));
+    }
+
+    // END OF CYCLE
+    // x and z coordinates of (c - d1 - d2) * vk
+    for i in 0..POINT_COORDINATE_WIDTH {
+        assertions.push(Assertion::periodic(
+            i,
+            CDS_CYCLE_LENGTH - 1,
+            CDS_CYCLE_LENGTH,
+            BaseElement::ZERO,
+        ));
+        assertions.push(Assertion::periodic(
+            i + AFFINE_POINT_WIDTH,
+            CDS_CYCLE_LENGTH - 1,
+            CDS_CYCLE_LENGTH,
+            BaseElement::ZERO,
+        ));
+    }
+}
+
+/// Append the same assertions as append_result_assertions for the
+/// valid ballots only. Nothing is asserted on invalid ballots, which
+/// are excluded from the tally.
+fn append_valid_result_assertions(
+    assertions: &mut Vec<Assertion<BaseElement>>,
+    cds_proofs: &[[BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH]],
+    validity: &[bool],
 ) {
+    for (voter_index, proof_points) in cds_proofs.iter().enumerate() {
+        if !validity[voter_index] {
+            continue;
+        }
+        let cycle_start = voter_index * CDS_CYCLE_LENGTH;
+
+        // END OF PHASE
+        for phase in 0..2 {
+            let step = cycle_start + phase * NROWS_PER_PHASE + SCALAR_MUL_LENGTH + 1;
+            let offset = 2 * phase * AFFINE_POINT_WIDTH;
+            for i in 0..AFFINE_POINT_WIDTH {
+                // a1 / a2
+                assertions.push(Assertion::single(
+                    i + PROJECTIVE_POINT_WIDTH + 1,
+                    step,
+                    proof_points[offset + i],
+                ));
+                // b1 / b2
+                assertions.push(Assertion::single(
+                    i + 2 * PROJECTIVE_POINT_WIDTH + 1,
+                    step,
+                    proof_points[offset + AFFINE_POINT_WIDTH + i],
+                ));
+            }
+        }
+
+        // END OF CYCLE
+        // x and z coordinates of (c - d1 - d2) * vk
+        let step = cycle_start + CDS_CYCLE_LENGTH - 1;
+        for i in 0..POINT_COORDINATE_WIDTH {
+            assertions.push(Assertion::single(i, step, BaseElement::ZERO));
+            assertions.push(Assertion::single(
+                i + AFFINE_POINT_WIDTH,
+                step,
+                BaseElement::ZERO,
+            ));
+        }
+    }
+}
+
+fn transpose_proof_points(
+    cds_proofs: &[[BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH]],
+) -> (Vec<Vec<BaseElement>>, Vec<Vec<BaseElement>>) {
     let n = cds_proofs.len() * 2;
     let mut result1 = vec![Vec::with_capacity(n); AFFINE_POINT_WIDTH];
     let mut result2 = vec![Vec::with_capacity(n); AFFINE_POINT_WIDTH];
-    let mut result3 = vec![Vec::with_capacity(n / 2); AFFINE_POINT_WIDTH];
 
-    for (proof_points, output) in cds_proofs.iter().zip(outputs.iter()) {
+    for proof_points in cds_proofs.iter() {
         // a1, a2
         for i in 0..AFFINE_POINT_WIDTH {
-            result1[i].push(proof_points[i] + output[i]);
-            result1[i]
-                .push(proof_points[i + 2 * AFFINE_POINT_WIDTH] + output[i + 2 * AFFINE_POINT_WIDTH])
+            result1[i].push(proof_points[i]);
+            result1[i].push(proof_points[i + 2 * AFFINE_POINT_WIDTH]);
         }
         // b1, b2
         for i in 0..AFFINE_POINT_WIDTH {
-            result2[i].push(proof_points[i + AFFINE_POINT_WIDTH] + output[i + AFFINE_POINT_WIDTH]);
-            result2[i].push(
-                proof_points[i + 3 * AFFINE_POINT_WIDTH] + output[i + 3 * AFFINE_POINT_WIDTH],
-            );
-        }
-        // x and z coordinates of (c - d1 - d2) * vk
-        for i in 0..AFFINE_POINT_WIDTH {
-            result3[i].push(output[i + 4 * AFFINE_POINT_WIDTH]);
+            result2[i].push(proof_points[i + AFFINE_POINT_WIDTH]);
+            result2[i].push(proof_points[i + 3 * AFFINE_POINT_WIDTH]);
         }
     }
 
-    (result1, result2, result3)
+    (result1, result2)
 }
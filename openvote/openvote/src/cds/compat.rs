@@ -0,0 +1,272 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// CDS COMPATIBILITY VECTORS
+// ================================================================================================
+// Encrypted votes and CDS proofs computed from fixed keys and nonces are checked against the
+// equations of the Hao-Ryan-Zielinski paper, against a transcript rebuilt field by field, and
+// against the vectors recorded in test_vectors.txt, so that any change to the encryption or to
+// the transcript encoding is caught. The vectors are recorded on the first run if the file does
+// not exist, or when OPENVOTE_BLESS_VECTORS is set after an intended change.
+
+use super::*;
+use crate::utils::ecc::compress_point;
+use std::{env, fmt::Write, fs, string::String};
+
+const VECTORS_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/cds/test_vectors.txt");
+
+const NUM_VOTERS: usize = 4;
+
+#[test]
+fn cds_compat_test_paper_equations() {
+    for vector in vectors() {
+        let generator = ProjectivePoint::generator();
+        let [a1, b1, a2, b2] = vector.proof_points;
+        let [d1, d2, r1, r2] = vector.proof_scalars;
+
+        // the vote is encrypted as x * bk + G for "yes" and x * bk - G for "no"
+        let vote_point = if vector.vote {
+            generator
+        } else {
+            ProjectivePoint::identity() - generator
+        };
+        assert_eq!(
+            vector.encrypted_vote,
+            vector.blinding_key * vector.secret_key + vote_point
+        );
+
+        // a1 = r1 * G + d1 * vk, b1 = r1 * bk + d1 * (ev + G)
+        // a2 = r2 * G + d2 * vk, b2 = r2 * bk + d2 * (ev - G)
+        assert_eq!(a1, generator * r1 + vector.voting_key * d1);
+        assert_eq!(
+            b1,
+            vector.blinding_key * r1 + (vector.encrypted_vote + generator) * d1
+        );
+        assert_eq!(a2, generator * r2 + vector.voting_key * d2);
+        assert_eq!(
+            b2,
+            vector.blinding_key * r2 + (vector.encrypted_vote - generator) * d2
+        );
+        assert_eq!(d1 + d2, vector.challenge);
+    }
+}
+
+#[test]
+fn cds_compat_test_transcript_layout() {
+    for vector in vectors() {
+        // the hash message is (i, version, 0, .., 0, vk, ev, a1, b1, a2, b2, bk, context, 0, .., 0)
+        let mut message = vec![BaseElement::ZERO; HASH_MSG_LENGTH];
+        message[0] = BaseElement::from(vector.voter_index as u64);
+        message[1] = BaseElement::from(CDS_TRANSCRIPT_VERSION);
+        let points = [
+            vector.voting_key,
+            vector.encrypted_vote,
+            vector.proof_points[0],
+            vector.proof_points[1],
+            vector.proof_points[2],
+            vector.proof_points[3],
+            vector.blinding_key,
+        ];
+        for (i, &point) in points.iter().enumerate() {
+            let offset = (i + 1) * AFFINE_POINT_WIDTH;
+            message[offset..offset + AFFINE_POINT_WIDTH]
+                .copy_from_slice(&projective_to_elements(point));
+        }
+        let offset = (points.len() + 1) * AFFINE_POINT_WIDTH;
+        message[offset..offset + DIGEST_SIZE].copy_from_slice(&vector.context);
+
+        let mut transcript = Transcript::new();
+        transcript.absorb_elements(&message);
+        assert_eq!(transcript.challenge_scalar(), vector.challenge);
+    }
+}
+
+#[test]
+fn cds_compat_test_legacy_proofs_rejected() {
+    for vector in vectors() {
+        // proofs computed with the unversioned message (i, 0, .., 0, vk, ev, a1, b1, a2, b2)
+        // satisfy the point equations, but not the challenge equation
+        let mut message = vec![BaseElement::ZERO; AFFINE_POINT_WIDTH];
+        message[0] = BaseElement::from(vector.voter_index as u64);
+        message.extend_from_slice(&projective_to_elements(vector.voting_key));
+        message.extend_from_slice(&projective_to_elements(vector.encrypted_vote));
+        message.extend_from_slice(&concat_proof_points(&vector.proof_points));
+        let mut transcript = Transcript::new();
+        transcript.absorb_elements(&message);
+        let legacy_challenge = transcript.challenge_scalar();
+
+        // the simulated branch is kept, and the real branch is answered with the nonce w
+        let w = vector.nonces[0];
+        let mut proof_scalars = vector.proof_scalars;
+        if vector.vote {
+            proof_scalars[1] = legacy_challenge - proof_scalars[0];
+            proof_scalars[3] = w - vector.secret_key * proof_scalars[1];
+        } else {
+            proof_scalars[0] = legacy_challenge - proof_scalars[1];
+            proof_scalars[2] = w - vector.secret_key * proof_scalars[0];
+        }
+
+        let verify = |proof_scalars: &[Scalar; PROOF_NUM_SCALARS],
+                      context: &[BaseElement; DIGEST_SIZE]| {
+            verify_cds_proof_projective(
+                vector.voter_index,
+                vector.voting_key,
+                vector.blinding_key,
+                vector.encrypted_vote,
+                &vector.proof_points,
+                proof_scalars,
+                context,
+            )
+        };
+        assert_eq!(verify(&vector.proof_scalars, &vector.context), Ok(()));
+        assert_eq!(
+            verify(&proof_scalars, &vector.context),
+            Err(CdsVerificationError::InvalidChallenge)
+        );
+
+        // the proof is bound to its election context
+        assert_eq!(
+            verify(&vector.proof_scalars, &UNBOUND_CONTEXT),
+            Err(CdsVerificationError::InvalidChallenge)
+        );
+    }
+}
+
+#[test]
+fn cds_compat_test_recorded_vectors() {
+    let mut actual = String::new();
+    for vector in vectors() {
+        vector.write_to(&mut actual);
+    }
+
+    match fs::read_to_string(VECTORS_PATH) {
+        Ok(expected) if env::var_os("OPENVOTE_BLESS_VECTORS").is_none() => {
+            assert_eq!(
+                actual, expected,
+                "CDS vectors differ from {}, set OPENVOTE_BLESS_VECTORS \
+                 to record them if the change is intended",
+                VECTORS_PATH
+            );
+        }
+        _ => fs::write(VECTORS_PATH, actual).unwrap(),
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Encrypted vote and CDS proof of a voter, with the inputs they are computed from
+struct Vector {
+    voter_index: usize,
+    vote: bool,
+    secret_key: Scalar,
+    voting_key: ProjectivePoint,
+    blinding_key: ProjectivePoint,
+    encrypted_vote: ProjectivePoint,
+    proof_points: [ProjectivePoint; PROOF_NUM_POINTS],
+    proof_scalars: [Scalar; PROOF_NUM_SCALARS],
+    nonces: [Scalar; 3],
+    context: [BaseElement; DIGEST_SIZE],
+    challenge: Scalar,
+}
+
+impl Vector {
+    /// Append the vector to target, one hex-encoded value per line
+    fn write_to(&self, target: &mut String) {
+        writeln!(target, "voter {} vote {}", self.voter_index, self.vote).unwrap();
+        writeln!(target, "vk {}", hex_point(self.voting_key)).unwrap();
+        writeln!(target, "bk {}", hex_point(self.blinding_key)).unwrap();
+        writeln!(target, "ev {}", hex_point(self.encrypted_vote)).unwrap();
+        for (name, &point) in ["a1", "b1", "a2", "b2"]
+            .iter()
+            .zip(self.proof_points.iter())
+        {
+            writeln!(target, "{} {}", name, hex_point(point)).unwrap();
+        }
+        for (name, scalar) in ["d1", "d2", "r1", "r2"]
+            .iter()
+            .zip(self.proof_scalars.iter())
+        {
+            writeln!(target, "{} {}", name, hex::encode(scalar.to_bytes())).unwrap();
+        }
+        let context = self
+            .context
+            .iter()
+            .map(|element| hex::encode(element.to_bytes()))
+            .collect::<Vec<String>>();
+        writeln!(target, "context {}", context.join("")).unwrap();
+        writeln!(target, "c {}", hex::encode(self.challenge.to_bytes())).unwrap();
+    }
+}
+
+/// Vectors of NUM_VOTERS voters with fixed secret keys and nonces, voting alternately
+/// "yes" and "no"
+fn vectors() -> Vec<Vector> {
+    let secret_keys = (0..NUM_VOTERS)
+        .map(|i| Scalar::from(2 * i as u64 + 3))
+        .collect::<Vec<Scalar>>();
+    let voting_keys = secret_keys
+        .iter()
+        .map(|&secret_key| projective_to_elements(ProjectivePoint::generator() * secret_key))
+        .collect::<Vec<[BaseElement; AFFINE_POINT_WIDTH]>>();
+    let blinding_keys = compute_all_blinding_keys(&voting_keys);
+    let mut context = [BaseElement::ZERO; DIGEST_SIZE];
+    for (i, element) in context.iter_mut().enumerate() {
+        *element = BaseElement::from(i as u64 + 1);
+    }
+
+    (0..NUM_VOTERS)
+        .map(|voter_index| {
+            let vote = voter_index % 2 == 0;
+            let voting_key =
+                ProjectivePoint::from(AffinePoint::from_raw_coordinates(voting_keys[voter_index]));
+            let blinding_key = ProjectivePoint::from(AffinePoint::from_raw_coordinates(
+                blinding_keys[voter_index],
+            ));
+            let nonces = [
+                Scalar::from(1000 + voter_index as u64),
+                Scalar::from(2000 + voter_index as u64),
+                Scalar::from(3000 + voter_index as u64),
+            ];
+            let (encrypted_vote, proof_scalars, proof_points) =
+                encrypt_vote_and_compute_proof_with_nonces(
+                    voter_index,
+                    secret_keys[voter_index],
+                    voting_key,
+                    blinding_key,
+                    vote,
+                    nonces,
+                    &context,
+                );
+            Vector {
+                voter_index,
+                vote,
+                secret_key: secret_keys[voter_index],
+                voting_key,
+                blinding_key,
+                encrypted_vote,
+                proof_points,
+                proof_scalars,
+                nonces,
+                context,
+                challenge: compute_challenge(
+                    voter_index,
+                    voting_key,
+                    blinding_key,
+                    encrypted_vote,
+                    &proof_points,
+                    &context,
+                ),
+            }
+        })
+        .collect()
+}
+
+fn hex_point(point: ProjectivePoint) -> String {
+    hex::encode(compress_point(&projective_to_elements(point)))
+}
@@ -10,9 +10,10 @@ pub(crate) use super::ecc::{
     AFFINE_POINT_WIDTH, GENERATOR, POINT_COORDINATE_WIDTH, PROJECTIVE_POINT_WIDTH,
 };
 pub(crate) use super::rescue::{
-    HASH_CYCLE_LENGTH, HASH_CYCLE_MASK, NUM_HASH_ROUNDS, RATE_WIDTH as HASH_RATE_WIDTH,
-    STATE_WIDTH as HASH_STATE_WIDTH,
+    DIGEST_SIZE, HASH_CYCLE_LENGTH, HASH_CYCLE_MASK, NUM_HASH_ROUNDS,
+    RATE_WIDTH as HASH_RATE_WIDTH, STATE_WIDTH as HASH_STATE_WIDTH,
 };
+use winterfell::math::{fields::f63::BaseElement, FieldElement};
 
 // CONSTANTS
 // ================================================================================================
@@ -20,7 +21,7 @@ pub(crate) use super::rescue::{
 // Rescue constants
 
 /// Number of hash iterations for hashing the public parameters and transcript
-pub const NUM_HASH_ITER: usize = 12;
+pub const NUM_HASH_ITER: usize = 15;
 
 /// Total number of steps for the iterated hash of the message to be signed
 pub const TOTAL_HASH_LENGTH: usize = HASH_CYCLE_LENGTH * NUM_HASH_ITER;
@@ -55,5 +56,27 @@ pub const PROOF_NUM_POINTS: usize = 4;
 pub const PROOF_NUM_SCALARS: usize = 4;
 
 /// Length of hash message to calculate challenge
-/// [i, vk, ev, a1, b1, a2, b2] (i is voter index)
+/// [i, version, vk, ev, a1, b1, a2, b2, bk, context] (i is voter index)
 pub const HASH_MSG_LENGTH: usize = NUM_HASH_ITER * HASH_RATE_WIDTH;
+
+/// Version of the hash message, absorbed after the voter index so that
+/// proofs computed with another layout of the message are rejected
+pub const CDS_TRANSCRIPT_VERSION: u64 = 2;
+
+/// Election context of proofs not bound to an election manifest
+pub const UNBOUND_CONTEXT: [BaseElement; DIGEST_SIZE] = [BaseElement::ZERO; DIGEST_SIZE];
+
+/// Number of bytes of a commitment to an encrypted vote
+pub const BYTES_PER_COMMITMENT: usize = 32;
+
+/// Number of bytes of the tracker of an encrypted vote
+pub const BYTES_PER_TRACKER: usize = 8;
+
+/// Domain separator of trackers, so that they differ from commitments
+pub const TRACKER_DOMAIN: &[u8] = b"openvote-tracker";
+
+/// Domain separator of the digests of ballots signed by voters
+pub const BALLOT_DOMAIN: &[u8] = b"openvote-ballot";
+
+/// Domain separator of the proofs opening audited ballots
+pub const AUDIT_DOMAIN: &[u8] = b"openvote-audit";
@@ -7,25 +7,20 @@
 // except according to those terms.
 
 use self::constants::*;
-use super::utils::{
-    ecc, field,
-    rescue::{self, Rescue63},
-};
-use crate::schnorr::projective_to_elements;
-use bitvec::{order::Lsb0, view::AsBits};
+use super::utils::{ecc, field, rescue, transcript::Transcript};
+use crate::compat::curve::{AffinePoint, ProjectivePoint, Scalar};
+use crate::compat::proof_options;
+use crate::utils::ecc::{projective_points_to_elements, projective_to_elements};
 use rand_core::{OsRng, RngCore};
 use winterfell::{
-    crypto::Hasher,
-    math::{
-        curves::curve_f63::{ProjectivePoint, Scalar},
-        fields::f63::BaseElement,
-        FieldElement,
-    },
-    FieldExtension, HashFunction, ProofOptions, Prover, StarkProof, VerifierError,
+    crypto::{hashers::Blake3_256, Digest, Hasher},
+    math::{fields::f63::BaseElement, FieldElement},
+    ByteReader, ByteWriter, Deserializable, DeserializationError, FieldExtension, HashFunction,
+    ProofOptions, Prover, Serializable, StarkProof, VerifierError,
 };
 
 #[cfg(not(feature = "std"))]
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
 
 #[cfg(feature = "std")]
 use log::debug;
@@ -38,11 +33,14 @@ pub(crate) mod constants;
 mod trace;
 
 mod air;
-pub(crate) use air::{CDSAir, PublicInputs};
+pub(crate) use air::{compute_blinding_keys, CDSAir, PublicInputs, ShardInfo};
 
 mod prover;
 pub(crate) use prover::CDSProver;
 
+#[cfg(all(test, feature = "std"))]
+mod compat;
+
 #[cfg(test)]
 mod tests;
 
@@ -62,7 +60,7 @@ pub fn get_example(
     ),
 ) {
     CDSExample::new(
-        ProofOptions::new(
+        proof_options(
             42,
             8,
             0,
@@ -113,7 +111,7 @@ impl CDSExample {
         // prepare secret keys and public keys
         for _ in 0..num_proofs {
             let secret_key = Scalar::random(&mut rng);
-            let voting_key = ProjectivePoint::generator() * secret_key;
+            let voting_key = ecc::mul_generator_constant_time(secret_key);
             secret_keys.push(secret_key);
             voting_keys.push(voting_key);
         }
@@ -215,10 +213,10 @@ impl CDSExample {
 
         let prover = CDSProver::new(
             self.options.clone(),
-            self.voting_keys.clone(),
-            self.encrypted_votes.clone(),
-            self.proof_points.clone(),
-            self.proof_scalars.clone(),
+            &self.voting_keys,
+            &self.encrypted_votes,
+            &self.proof_points,
+            &self.proof_scalars,
         );
 
         // generate the execution trace
@@ -239,7 +237,7 @@ impl CDSExample {
 
     /// Verifies the validity of a proof of correct Schnorr signature verification
     pub fn verify(&self, proof: StarkProof, pub_inputs: PublicInputs) -> Result<(), VerifierError> {
-        winterfell::verify::<CDSAir>(proof, pub_inputs)
+        crate::compat::verify::<CDSAir>(proof, pub_inputs)
     }
 
     #[cfg(test)]
@@ -253,7 +251,7 @@ impl CDSExample {
         let fault_index = (rng.next_u32() as usize) % (pub_inputs.voting_keys.len());
         let fault_position = (rng.next_u32() as usize) % (pub_inputs.voting_keys[0].len());
         pub_inputs.voting_keys[fault_index][fault_position] += BaseElement::ONE;
-        winterfell::verify::<CDSAir>(proof, pub_inputs)
+        crate::compat::verify::<CDSAir>(proof, pub_inputs)
     }
 
     #[cfg(test)]
@@ -267,7 +265,7 @@ impl CDSExample {
         let fault_index = (rng.next_u32() as usize) % (pub_inputs.encrypted_votes.len());
         let fault_position = (rng.next_u32() as usize) % (pub_inputs.encrypted_votes[0].len());
         pub_inputs.encrypted_votes[fault_index][fault_position] += BaseElement::ONE;
-        winterfell::verify::<CDSAir>(proof, pub_inputs)
+        crate::compat::verify::<CDSAir>(proof, pub_inputs)
     }
 
     #[cfg(test)]
@@ -281,22 +279,32 @@ impl CDSExample {
         let fault_index = (rng.next_u32() as usize) % (pub_inputs.cds_proofs.len());
         let fault_position = (rng.next_u32() as usize) % (pub_inputs.cds_proofs[0].len());
         pub_inputs.cds_proofs[fault_index][fault_position] += BaseElement::ONE;
-        winterfell::verify::<CDSAir>(proof, pub_inputs)
+        crate::compat::verify::<CDSAir>(proof, pub_inputs)
     }
+}
 
-    #[cfg(test)]
-    fn verify_with_wrong_output(
-        &self,
-        proof: StarkProof,
-        pub_inputs: PublicInputs,
-    ) -> Result<(), VerifierError> {
-        let mut pub_inputs = pub_inputs;
-        let mut rng = OsRng;
-        let fault_index = (rng.next_u32() as usize) % (pub_inputs.outputs.len());
-        let fault_position = (rng.next_u32() as usize) % (pub_inputs.outputs[0].len());
-        pub_inputs.outputs[fault_index][fault_position] += BaseElement::ONE;
-        winterfell::verify::<CDSAir>(proof, pub_inputs)
-    }
+// ERRORS
+// ================================================================================================
+
+/// Errors raised when a CDS proof is invalid, indicating which
+/// of the five verification equations does not hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CdsVerificationError {
+    /// The challenge c is not equal to d1 + d2
+    InvalidChallenge,
+    /// a1 is not equal to r1 * G + d1 * vk
+    InvalidA1,
+    /// b1 is not equal to r1 * bk + d1 * (ev + G)
+    InvalidB1,
+    /// a2 is not equal to r2 * G + d2 * vk
+    InvalidA2,
+    /// b2 is not equal to r2 * bk + d2 * (ev - G)
+    InvalidB2,
+    /// One of the supplied points is not on the curve,
+    /// or has a component of small order
+    InvalidPoint,
+    /// The voter index is out of range of the voting keys
+    InvalidVoterIndex,
 }
 
 // HELPER FUNCTIONS
@@ -321,79 +329,164 @@ pub(crate) fn encrypt_votes_and_compute_proofs(
             && votes.len() == num_proofs,
         "Inconsistent length."
     );
-    let rng = OsRng;
-    let mut ws = Vec::with_capacity(num_proofs);
     let mut encrypted_votes = Vec::with_capacity(num_proofs);
     let mut proof_scalars = Vec::with_capacity(num_proofs);
     let mut proof_points = Vec::with_capacity(num_proofs);
 
-    // compute the encrypted votes
     for i in 0..num_proofs {
-        let encrypted_vote = if votes[i] {
-            blinding_keys[i] * secret_keys[i] + ProjectivePoint::generator()
-        } else {
-            blinding_keys[i] * secret_keys[i] - ProjectivePoint::generator()
-        };
+        let (encrypted_vote, scalars, points) = encrypt_vote_and_compute_proof(
+            i,
+            secret_keys[i],
+            voting_keys[i],
+            blinding_keys[i],
+            votes[i],
+        );
         encrypted_votes.push(encrypted_vote);
+        proof_scalars.push(scalars);
+        proof_points.push(points);
     }
 
-    //compute the proof points (a1, b1, a2, b2)
-    for i in 0..num_proofs {
-        let w = Scalar::random(rng);
-        ws.push(w);
-
-        if votes[i] {
-            let r1 = Scalar::random(rng);
-            let d1 = Scalar::random(rng);
-            let a1 = ProjectivePoint::generator() * r1 + voting_keys[i] * d1;
-            let b1 =
-                blinding_keys[i] * r1 + (encrypted_votes[i] + ProjectivePoint::generator()) * d1;
-            let a2 = ProjectivePoint::generator() * w;
-            let b2 = blinding_keys[i] * w;
-            proof_points.push([a1, b1, a2, b2]);
-            proof_scalars.push([d1, Scalar::zero(), r1, Scalar::zero()])
-        } else {
-            let r2 = Scalar::random(rng);
-            let d2 = Scalar::random(rng);
-            let a2 = ProjectivePoint::generator() * r2 + voting_keys[i] * d2;
-            let b2 =
-                blinding_keys[i] * r2 + (encrypted_votes[i] - ProjectivePoint::generator()) * d2;
-            let a1 = ProjectivePoint::generator() * w;
-            let b1 = blinding_keys[i] * w;
-            proof_points.push([a1, b1, a2, b2]);
-            proof_scalars.push([Scalar::zero(), d2, Scalar::zero(), r2])
-        }
-    }
+    (encrypted_votes, proof_scalars, proof_points)
+}
 
-    // compute the challenge and proof scalars
-    for i in 0..num_proofs {
-        let hash_message =
-            points_to_hash_message(i, voting_keys[i], encrypted_votes[i], &proof_points[i]);
-        let c_bytes = hash_message_bytes(&hash_message);
-        let c_bits = c_bytes.as_bits::<Lsb0>();
-        let c_scalar = Scalar::from_bits(c_bits);
-
-        if votes[i] {
-            let d2 = c_scalar - proof_scalars[i][0];
-            proof_scalars[i][1] = d2;
-            let r2 = ws[i] - secret_keys[i] * d2;
-            proof_scalars[i][3] = r2;
-        } else {
-            let d1 = c_scalar - proof_scalars[i][1];
-            proof_scalars[i][0] = d1;
-            let r1 = ws[i] - secret_keys[i] * d1;
-            proof_scalars[i][2] = r1;
-        }
-    }
+/// Encrypt the vote of the voter at voter_index and compute the CDS proof
+/// that the encrypted vote is either "yes" or "no"
+pub fn encrypt_vote_and_compute_proof(
+    voter_index: usize,
+    secret_key: Scalar,
+    voting_key: ProjectivePoint,
+    blinding_key: ProjectivePoint,
+    vote: bool,
+) -> (
+    ProjectivePoint,
+    [Scalar; PROOF_NUM_SCALARS],
+    [ProjectivePoint; PROOF_NUM_POINTS],
+) {
+    encrypt_vote_and_compute_proof_with_rng(
+        voter_index,
+        secret_key,
+        voting_key,
+        blinding_key,
+        vote,
+        &mut OsRng,
+    )
+}
 
-    assert!(
-        encrypted_votes.len() == num_proofs
-            && proof_scalars.len() == num_proofs
-            && proof_points.len() == num_proofs,
-        "Inconsistent length."
+/// Encrypt the vote of the voter at voter_index and compute its CDS proof,
+/// drawing the randomness of the proof from the given random number generator
+pub fn encrypt_vote_and_compute_proof_with_rng<R: RngCore>(
+    voter_index: usize,
+    secret_key: Scalar,
+    voting_key: ProjectivePoint,
+    blinding_key: ProjectivePoint,
+    vote: bool,
+    rng: &mut R,
+) -> (
+    ProjectivePoint,
+    [Scalar; PROOF_NUM_SCALARS],
+    [ProjectivePoint; PROOF_NUM_POINTS],
+) {
+    encrypt_vote_and_compute_proof_in_context(
+        voter_index,
+        secret_key,
+        voting_key,
+        blinding_key,
+        vote,
+        &UNBOUND_CONTEXT,
+        rng,
+    )
+}
+
+/// Same as `encrypt_vote_and_compute_proof_with_rng`, binding the challenge
+/// of the proof to the given election context
+pub(crate) fn encrypt_vote_and_compute_proof_in_context<R: RngCore>(
+    voter_index: usize,
+    secret_key: Scalar,
+    voting_key: ProjectivePoint,
+    blinding_key: ProjectivePoint,
+    vote: bool,
+    context: &[BaseElement; DIGEST_SIZE],
+    rng: &mut R,
+) -> (
+    ProjectivePoint,
+    [Scalar; PROOF_NUM_SCALARS],
+    [ProjectivePoint; PROOF_NUM_POINTS],
+) {
+    let w = Scalar::random(&mut *rng);
+    let r = Scalar::random(&mut *rng);
+    let d = Scalar::random(&mut *rng);
+    encrypt_vote_and_compute_proof_with_nonces(
+        voter_index,
+        secret_key,
+        voting_key,
+        blinding_key,
+        vote,
+        [w, r, d],
+        context,
+    )
+}
+
+/// Encrypt the vote of the voter at voter_index and compute its CDS proof with the given
+/// nonces (w, r, d), where r and d are those of the simulated proof of the other vote
+pub(crate) fn encrypt_vote_and_compute_proof_with_nonces(
+    voter_index: usize,
+    secret_key: Scalar,
+    voting_key: ProjectivePoint,
+    blinding_key: ProjectivePoint,
+    vote: bool,
+    nonces: [Scalar; 3],
+    context: &[BaseElement; DIGEST_SIZE],
+) -> (
+    ProjectivePoint,
+    [Scalar; PROOF_NUM_SCALARS],
+    [ProjectivePoint; PROOF_NUM_POINTS],
+) {
+    let [w, r, d] = nonces;
+
+    // compute the encrypted vote
+    let encrypted_vote = if vote {
+        ecc::mul_constant_time(blinding_key, secret_key) + ProjectivePoint::generator()
+    } else {
+        ecc::mul_constant_time(blinding_key, secret_key) - ProjectivePoint::generator()
+    };
+
+    // compute the proof points (a1, b1, a2, b2)
+    let (proof_points, mut proof_scalars) = if vote {
+        let (r1, d1) = (r, d);
+        let a1 = ProjectivePoint::generator() * r1 + voting_key * d1;
+        let b1 = blinding_key * r1 + (encrypted_vote + ProjectivePoint::generator()) * d1;
+        let a2 = ecc::mul_generator_constant_time(w);
+        let b2 = ecc::mul_constant_time(blinding_key, w);
+        ([a1, b1, a2, b2], [d1, Scalar::zero(), r1, Scalar::zero()])
+    } else {
+        let (r2, d2) = (r, d);
+        let a2 = ProjectivePoint::generator() * r2 + voting_key * d2;
+        let b2 = blinding_key * r2 + (encrypted_vote - ProjectivePoint::generator()) * d2;
+        let a1 = ecc::mul_generator_constant_time(w);
+        let b1 = ecc::mul_constant_time(blinding_key, w);
+        ([a1, b1, a2, b2], [Scalar::zero(), d2, Scalar::zero(), r2])
+    };
+
+    // compute the challenge and proof scalars
+    let c_scalar = compute_challenge(
+        voter_index,
+        voting_key,
+        blinding_key,
+        encrypted_vote,
+        &proof_points,
+        context,
     );
+    if vote {
+        let d2 = c_scalar - proof_scalars[0];
+        proof_scalars[1] = d2;
+        proof_scalars[3] = w - secret_key * d2;
+    } else {
+        let d1 = c_scalar - proof_scalars[1];
+        proof_scalars[0] = d1;
+        proof_scalars[2] = w - secret_key * d1;
+    }
 
-    (encrypted_votes, proof_scalars, proof_points)
+    (encrypted_vote, proof_scalars, proof_points)
 }
 
 /// Naively varify CDS proofs
@@ -412,14 +505,19 @@ pub fn naive_verify_cds_proofs(
     }
 
     for i in 0..num_proofs {
-        verify_cds_proof(
+        if verify_cds_proof_projective(
             i,
             voting_keys[i],
             blinding_key,
             encrypted_votes[i],
             &proof_points[i],
             &proof_scalars[i],
-        );
+            &UNBOUND_CONTEXT,
+        )
+        .is_err()
+        {
+            return false;
+        }
 
         if i + 1 < num_proofs {
             blinding_key += voting_keys[i];
@@ -430,15 +528,109 @@ pub fn naive_verify_cds_proofs(
     true
 }
 
+/// Verify a CDS proof that an encrypted vote is the encryption of
+/// either a "yes" or a "no" vote, without the need for a `VoteCollector`.
+///
+/// All points are expected in affine coordinates, encoded as
+/// `[x_0, ..., x_5, y_0, ..., y_5]`, and `proof_points` is the
+/// concatenation of the points (a1, b1, a2, b2). The proof scalars are
+/// ordered as (d1, d2, r1, r2). The blinding key of a voter can be
+/// obtained from the list of voting keys with [compute_blinding_key].
+///
+/// Return `Ok(())` if the proof is valid, or which of the five
+/// verification equations does not hold otherwise.
+pub fn verify_cds_proof(
+    voter_index: usize,
+    voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    blinding_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    encrypted_vote: &[BaseElement; AFFINE_POINT_WIDTH],
+    proof_points: &[BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH],
+    proof_scalars: &[Scalar; PROOF_NUM_SCALARS],
+) -> Result<(), CdsVerificationError> {
+    verify_cds_proof_bound(
+        voter_index,
+        voting_key,
+        blinding_key,
+        encrypted_vote,
+        proof_points,
+        proof_scalars,
+        &UNBOUND_CONTEXT,
+    )
+}
+
+/// Verify a CDS proof as verify_cds_proof does, for a proof whose challenge
+/// is bound to the digest of an election manifest
+pub fn verify_cds_proof_bound(
+    voter_index: usize,
+    voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    blinding_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    encrypted_vote: &[BaseElement; AFFINE_POINT_WIDTH],
+    proof_points: &[BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH],
+    proof_scalars: &[Scalar; PROOF_NUM_SCALARS],
+    manifest_digest: &[BaseElement; DIGEST_SIZE],
+) -> Result<(), CdsVerificationError> {
+    let voting_key = affine_to_projective(voting_key)?;
+    let blinding_key = affine_to_projective(blinding_key)?;
+    let encrypted_vote = affine_to_projective(encrypted_vote)?;
+    let mut points = [ProjectivePoint::identity(); PROOF_NUM_POINTS];
+    let mut point = [BaseElement::ZERO; AFFINE_POINT_WIDTH];
+    for (i, p) in points.iter_mut().enumerate() {
+        point.copy_from_slice(&proof_points[i * AFFINE_POINT_WIDTH..(i + 1) * AFFINE_POINT_WIDTH]);
+        *p = affine_to_projective(&point)?;
+    }
+
+    verify_cds_proof_projective(
+        voter_index,
+        voting_key,
+        blinding_key,
+        encrypted_vote,
+        &points,
+        proof_scalars,
+        manifest_digest,
+    )
+}
+
+/// Compute the blinding key of the voter at `voter_index` from the
+/// list of voting keys of all registered voters, in affine coordinates.
+/// The blinding key is the sum of the voting keys of previous voters
+/// minus the sum of the voting keys of following voters.
+pub fn compute_blinding_key(
+    voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+    voter_index: usize,
+) -> [BaseElement; AFFINE_POINT_WIDTH] {
+    let mut blinding_key = ProjectivePoint::identity();
+    for (i, &voting_key) in voting_keys.iter().enumerate() {
+        let voting_key = ProjectivePoint::from(AffinePoint::from_raw_coordinates(voting_key));
+        if i < voter_index {
+            blinding_key += voting_key;
+        } else if i > voter_index {
+            blinding_key -= voting_key;
+        }
+    }
+    projective_to_elements(blinding_key)
+}
+
+/// Compute the blinding keys of all voters from the list of voting keys,
+/// in affine coordinates. The chain is computed once and reduced to affine
+/// coordinates with a single field inversion.
+pub fn compute_all_blinding_keys(
+    voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+) -> Vec<[BaseElement; AFFINE_POINT_WIDTH]> {
+    air::compute_blinding_keys(voting_keys, None)
+}
+
+/// Verify a CDS proof of the validity of an encrypted vote.
+/// Return which of the five equations does not hold if the proof is invalid.
 #[inline]
-pub(crate) fn verify_cds_proof(
+pub(crate) fn verify_cds_proof_projective(
     voter_index: usize,
     voting_key: ProjectivePoint,
     blinding_key: ProjectivePoint,
     encrypted_vote: ProjectivePoint,
     proof_points: &[ProjectivePoint; PROOF_NUM_POINTS],
     proof_scalars: &[Scalar; PROOF_NUM_SCALARS],
-) -> bool {
+    context: &[BaseElement; DIGEST_SIZE],
+) -> Result<(), CdsVerificationError> {
     let d1 = proof_scalars[0];
     let d2 = proof_scalars[1];
     let r1 = proof_scalars[2];
@@ -449,84 +641,224 @@ pub(crate) fn verify_cds_proof(
     let a2 = proof_points[2];
     let b2 = proof_points[3];
 
-    let hash_message =
-        points_to_hash_message(voter_index, voting_key, encrypted_vote, proof_points);
-    let c_bytes = hash_message_bytes(&hash_message);
-    let c_bits = c_bytes.as_bits::<Lsb0>();
-    let c_scalar = Scalar::from_bits(c_bits);
-
-    (c_scalar == d1 + d2)
-        && (a1 == ProjectivePoint::generator() * r1 + voting_key * d1)
-        && (b1 == blinding_key * r1 + (encrypted_vote + ProjectivePoint::generator()) * d1)
-        && (a2 == ProjectivePoint::generator() * r2 + voting_key * d2)
-        && (b2 == blinding_key * r2 + (encrypted_vote - ProjectivePoint::generator()) * d2)
+    // voting and blinding keys are derived from registered voting keys, which are checked
+    // at registration, while the encrypted vote is supplied with the ballot. The proof
+    // points are then in the prime-order subgroup whenever the equations hold.
+    if !ecc::is_in_prime_subgroup(encrypted_vote) {
+        return Err(CdsVerificationError::InvalidPoint);
+    }
+
+    let c_scalar = compute_challenge(
+        voter_index,
+        voting_key,
+        blinding_key,
+        encrypted_vote,
+        proof_points,
+        context,
+    );
+
+    if c_scalar != d1 + d2 {
+        return Err(CdsVerificationError::InvalidChallenge);
+    }
+    if a1 != ProjectivePoint::generator() * r1 + voting_key * d1 {
+        return Err(CdsVerificationError::InvalidA1);
+    }
+    if b1 != blinding_key * r1 + (encrypted_vote + ProjectivePoint::generator()) * d1 {
+        return Err(CdsVerificationError::InvalidB1);
+    }
+    if a2 != ProjectivePoint::generator() * r2 + voting_key * d2 {
+        return Err(CdsVerificationError::InvalidA2);
+    }
+    if b2 != blinding_key * r2 + (encrypted_vote - ProjectivePoint::generator()) * d2 {
+        return Err(CdsVerificationError::InvalidB2);
+    }
+
+    Ok(())
 }
 
-#[inline]
-pub(crate) fn concat_proof_points(
-    proof_points: &[ProjectivePoint; PROOF_NUM_POINTS],
-) -> [BaseElement; AFFINE_POINT_WIDTH * PROOF_NUM_POINTS] {
-    let mut result = [BaseElement::ZERO; AFFINE_POINT_WIDTH * PROOF_NUM_POINTS];
-    let proof_points = proof_points.map(|p| projective_to_elements(p)).concat();
-    result.copy_from_slice(&proof_points);
-    result
+/// Compute the commitment to an encrypted vote submitted during the
+/// commit phase of vote casting. The commitment binds the voter index,
+/// the encrypted vote and the points of its CDS proof.
+pub fn compute_vote_commitment(
+    voter_index: usize,
+    encrypted_vote: &[BaseElement; AFFINE_POINT_WIDTH],
+    proof_points: &[BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH],
+) -> [u8; BYTES_PER_COMMITMENT] {
+    let mut message = vec![];
+    message.write_u32(voter_index as u32);
+    Serializable::write_batch_into(encrypted_vote, &mut message);
+    Serializable::write_batch_into(proof_points, &mut message);
+    Blake3_256::<BaseElement>::hash(&message).as_bytes()
+}
+
+/// Compute the tracker of an encrypted vote, a short hash which the voter can
+/// locate in the published list of ballots. It binds the same data as the
+/// commitment to the encrypted vote, under a separate domain.
+pub fn compute_tracker(
+    voter_index: usize,
+    encrypted_vote: &[BaseElement; AFFINE_POINT_WIDTH],
+    proof_points: &[BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH],
+) -> [u8; BYTES_PER_TRACKER] {
+    let mut message = TRACKER_DOMAIN.to_vec();
+    message.write_u32(voter_index as u32);
+    Serializable::write_batch_into(encrypted_vote, &mut message);
+    Serializable::write_batch_into(proof_points, &mut message);
+    let mut tracker = [0u8; BYTES_PER_TRACKER];
+    tracker.copy_from_slice(
+        &Blake3_256::<BaseElement>::hash(&message).as_bytes()[..BYTES_PER_TRACKER],
+    );
+    tracker
+}
+
+/// Schnorr signature of a ballot with the voting key of its voter, see signed_ballot_digest
+pub type BallotSignature = ([BaseElement; ecc::POINT_COORDINATE_WIDTH], Scalar);
+
+/// Compute the digest of a ballot which the voter signs with their voting key. It binds
+/// the voter index, the encrypted vote and its full CDS proof to the election context,
+/// so that the proof scalars of a ballot excluded from a cast proof are those submitted
+/// by the voter rather than scalars chosen by the aggregator.
+pub fn signed_ballot_digest(
+    voter_index: usize,
+    encrypted_vote: &[BaseElement; AFFINE_POINT_WIDTH],
+    proof_points: &[BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH],
+    proof_scalars: &[Scalar; PROOF_NUM_SCALARS],
+    context: &[BaseElement; DIGEST_SIZE],
+) -> rescue::Hash {
+    let mut message = BALLOT_DOMAIN.to_vec();
+    message.write_u32(voter_index as u32);
+    Serializable::write_batch_into(context, &mut message);
+    Serializable::write_batch_into(encrypted_vote, &mut message);
+    Serializable::write_batch_into(proof_points, &mut message);
+    Serializable::write_batch_into(proof_scalars, &mut message);
+    rescue::digest_bytes(&message)
+}
+
+/// Serialize the signature of a ballot
+pub(crate) fn write_ballot_signature<W: ByteWriter>(signature: &BallotSignature, target: &mut W) {
+    Serializable::write_batch_into(&signature.0, target);
+    target.write(signature.1);
+}
+
+/// Deserialize the signature of a ballot, rejecting non-canonical scalars
+pub(crate) fn read_ballot_signature<R: ByteReader>(
+    source: &mut R,
+) -> Result<BallotSignature, DeserializationError> {
+    let mut signature_r = [BaseElement::ZERO; ecc::POINT_COORDINATE_WIDTH];
+    signature_r.copy_from_slice(&BaseElement::read_batch_from(
+        source,
+        ecc::POINT_COORDINATE_WIDTH,
+    )?);
+    Ok((signature_r, ecc::read_canonical_scalar(source)?))
+}
+
+/// Format a tracker as dash-separated groups of hex digits, for display to voters
+pub fn format_tracker(tracker: &[u8; BYTES_PER_TRACKER]) -> String {
+    tracker
+        .chunks(2)
+        .map(hex::encode)
+        .collect::<Vec<String>>()
+        .join("-")
+}
+
+/// Number of bytes of the validity bitmap of num_voters ballots
+pub fn validity_bitmap_size(num_voters: usize) -> usize {
+    (num_voters + 7) / 8
+}
+
+/// Write the validity flags of ballots as a bitmap, the flag of
+/// the ballot at index i being the bit i % 8 of the byte i / 8
+pub(crate) fn write_validity_bitmap<W: ByteWriter>(validity: &[bool], target: &mut W) {
+    let mut bitmap = vec![0u8; validity_bitmap_size(validity.len())];
+    for (i, &is_valid) in validity.iter().enumerate() {
+        bitmap[i / 8] |= (is_valid as u8) << (i % 8);
+    }
+    target.write_u8_slice(&bitmap);
+}
+
+/// Read the validity flags of num_voters ballots from a bitmap.
+/// Padding bits of the last byte must be zero.
+pub(crate) fn read_validity_bitmap<R: ByteReader>(
+    source: &mut R,
+    num_voters: usize,
+) -> Result<Vec<bool>, DeserializationError> {
+    let bitmap = source.read_u8_vec(validity_bitmap_size(num_voters))?;
+    if num_voters % 8 != 0 && bitmap[num_voters / 8] >> (num_voters % 8) != 0 {
+        return Err(DeserializationError::InvalidValue(String::from(
+            "Padding bits of the validity bitmap are not zero.",
+        )));
+    }
+    Ok((0..num_voters)
+        .map(|i| (bitmap[i / 8] >> (i % 8)) & 1 == 1)
+        .collect())
 }
 
+/// Compute the challenge of a CDS proof
 #[inline]
-fn points_to_hash_message(
+pub(crate) fn compute_challenge(
     voter_index: usize,
     voting_key: ProjectivePoint,
+    blinding_key: ProjectivePoint,
     encrypted_vote: ProjectivePoint,
     proof_points: &[ProjectivePoint; PROOF_NUM_POINTS],
-) -> [BaseElement; HASH_MSG_LENGTH] {
-    let mut hash_message = [BaseElement::ZERO; HASH_MSG_LENGTH];
-    let proof_points = concat_proof_points(proof_points);
-    hash_message[0] = BaseElement::from(voter_index as u8);
-    hash_message[AFFINE_POINT_WIDTH..AFFINE_POINT_WIDTH * 2]
-        .copy_from_slice(&projective_to_elements(voting_key));
-    hash_message[AFFINE_POINT_WIDTH * 2..AFFINE_POINT_WIDTH * 3]
-        .copy_from_slice(&projective_to_elements(encrypted_vote));
-    hash_message[AFFINE_POINT_WIDTH * 3..AFFINE_POINT_WIDTH * (PROOF_NUM_POINTS + 3)]
-        .copy_from_slice(&proof_points);
-    hash_message
+    context: &[BaseElement; DIGEST_SIZE],
+) -> Scalar {
+    build_transcript(
+        voter_index,
+        &projective_to_elements(voting_key),
+        &projective_to_elements(blinding_key),
+        &projective_to_elements(encrypted_vote),
+        &concat_proof_points(proof_points),
+        context,
+    )
+    .challenge_scalar()
 }
 
+/// Election context bound into the challenges of CDS proofs,
+/// i.e. the digest of the election manifest if any
 #[inline]
-fn hash_message_bytes(message: &[BaseElement; HASH_MSG_LENGTH]) -> [u8; 32] {
-    debug_assert!(
-        HASH_MSG_LENGTH % HASH_RATE_WIDTH == 0,
-        "Length of hash message must be divisible by rate width."
-    );
-    let mut h = Rescue63::digest(&message[..HASH_RATE_WIDTH]);
-    let mut message_chunk;
-    for i in (HASH_RATE_WIDTH..HASH_MSG_LENGTH).step_by(HASH_RATE_WIDTH) {
-        message_chunk = rescue::Hash::new(
-            message[i],
-            message[i + 1],
-            message[i + 2],
-            message[i + 3],
-            message[i + 4],
-            message[i + 5],
-            message[i + 6],
-        );
-        h = Rescue63::merge(&[h, message_chunk]);
-    }
-    let h = h.to_elements();
-    let mut h_bytes = [0u8; 32];
-    for (i, h_word) in h.iter().enumerate().take(4) {
-        h_bytes[8 * i..8 * i + 8].copy_from_slice(&h_word.to_bytes());
-    }
-    h_bytes
+pub(crate) fn election_context(
+    manifest_digest: Option<[BaseElement; DIGEST_SIZE]>,
+) -> [BaseElement; DIGEST_SIZE] {
+    manifest_digest.unwrap_or(UNBOUND_CONTEXT)
 }
 
 #[inline]
-fn diff_registers<const NREGS: usize>(
-    a: &[BaseElement],
-    b: &[BaseElement],
-) -> [BaseElement; NREGS] {
-    let mut result = [BaseElement::ZERO; NREGS];
-    for i in 0..NREGS {
-        result[i] = a[i] - b[i];
-    }
+pub(crate) fn concat_proof_points(
+    proof_points: &[ProjectivePoint; PROOF_NUM_POINTS],
+) -> [BaseElement; AFFINE_POINT_WIDTH * PROOF_NUM_POINTS] {
+    let mut result = [BaseElement::ZERO; AFFINE_POINT_WIDTH * PROOF_NUM_POINTS];
+    result.copy_from_slice(&projective_points_to_elements(proof_points));
     result
 }
+
+#[inline]
+fn affine_to_projective(
+    point: &[BaseElement; AFFINE_POINT_WIDTH],
+) -> Result<ProjectivePoint, CdsVerificationError> {
+    let point = ecc::validate_point(point).map_err(|_| CdsVerificationError::InvalidPoint)?;
+    Ok(ProjectivePoint::from(point))
+}
+
+/// Build the transcript of a CDS proof, i.e. the message
+/// (i, version, vk, ev, a1, b1, a2, b2, bk, context) hashed to compute its challenge.
+/// The voter index and the version are padded to the width of a point.
+pub(crate) fn build_transcript(
+    voter_index: usize,
+    voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    blinding_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    encrypted_vote: &[BaseElement; AFFINE_POINT_WIDTH],
+    proof_points: &[BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH],
+    context: &[BaseElement; DIGEST_SIZE],
+) -> Transcript {
+    let mut transcript = Transcript::new();
+    transcript.absorb_element(BaseElement::from(voter_index as u64));
+    transcript.absorb_element(BaseElement::from(CDS_TRANSCRIPT_VERSION));
+    transcript.pad_to_length(AFFINE_POINT_WIDTH);
+    transcript.absorb_point(voting_key);
+    transcript.absorb_point(encrypted_vote);
+    transcript.absorb_elements(proof_points);
+    transcript.absorb_point(blinding_key);
+    transcript.absorb_elements(context);
+    transcript.pad_to_length(HASH_MSG_LENGTH);
+    transcript
+}
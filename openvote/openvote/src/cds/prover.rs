@@ -1,11 +1,19 @@
 use super::trace::*;
 use super::PublicInputs;
-use super::{air::CDSAir, constants::*, diff_registers};
-use crate::{cds::hash_message_bytes, utils::ecc};
+use super::{
+    air::{compute_blinding_keys, CDSAir, ShardInfo},
+    constants::*,
+};
+use crate::{
+    cds::{build_transcript, election_context},
+    chain::PhaseCommitment,
+    compat::curve::Scalar,
+    utils::ordering::KeyOrder,
+};
 use bitvec::{order::Lsb0, view::AsBits};
 use winterfell::{
-    math::{curves::curve_f63::Scalar, fields::f63::BaseElement, FieldElement},
-    ProofOptions, Prover, Trace, TraceTable,
+    math::{fields::f63::BaseElement, FieldElement},
+    ProofOptions, Prover, TraceTable,
 };
 
 #[cfg(feature = "concurrent")]
@@ -14,40 +22,135 @@ use winterfell::iterators::*;
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 
+#[cfg(feature = "std")]
+use super::write_validity_bitmap;
+#[cfg(feature = "std")]
+use crate::chain::write_phase_commitment;
+#[cfg(feature = "std")]
+use crate::utils::checkpoint::{self, Checkpoint, CheckpointError};
+#[cfg(feature = "std")]
+use std::path::Path;
+#[cfg(feature = "std")]
+use winterfell::{ByteWriter, Serializable, StarkProof};
+
 // CDS PROVER
 // ================================================================================================
 
-// the voters' public keys are public (known by prover and verifier)
-pub struct CDSProver {
+// the voters' public keys are public (known by prover and verifier).
+// Inputs are borrowed from the caller to avoid cloning them for large elections.
+pub struct CDSProver<'a> {
     options: ProofOptions,
     // x = g^{x_i}
-    voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+    voting_keys: &'a [[BaseElement; AFFINE_POINT_WIDTH]],
     // y = h^{x_i} * G^{v_i}, v_i \in {-1, 1}
-    encrypted_votes: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+    encrypted_votes: &'a [[BaseElement; AFFINE_POINT_WIDTH]],
     // a1, b1, a2, b2
-    proof_points: Vec<[BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH]>,
+    proof_points: &'a [[BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH]],
     // d1, d2, r1, r2
-    proof_scalars: Vec<[Scalar; PROOF_NUM_SCALARS]>,
+    proof_scalars: &'a [[Scalar; PROOF_NUM_SCALARS]],
+    // position of the voters in the global list, if proving a shard
+    shard: Option<ShardInfo>,
+    // validity flags of ballots, the CDS proofs of invalid ballots are not asserted
+    validity: Vec<bool>,
+    // digest of the election manifest the proof is bound to, if any
+    manifest_digest: Option<[BaseElement; DIGEST_SIZE]>,
+    // digest of the manifest ballots were cast against, if it was amended since
+    ballot_context: Option<[BaseElement; DIGEST_SIZE]>,
+    // commitment to the registration phase the proof is chained to, if any
+    register_commitment: Option<PhaseCommitment>,
+    // order in which voting keys are listed
+    key_order: KeyOrder,
+    // affine blinding keys, shared between the trace and the public inputs
+    blinding_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
 }
 
 // assume that
-impl CDSProver {
+impl<'a> CDSProver<'a> {
     pub fn new(
         options: ProofOptions,
-        voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
-        encrypted_votes: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
-        proof_points: Vec<[BaseElement; AFFINE_POINT_WIDTH * PROOF_NUM_POINTS]>,
-        proof_scalars: Vec<[Scalar; PROOF_NUM_SCALARS]>,
+        voting_keys: &'a [[BaseElement; AFFINE_POINT_WIDTH]],
+        encrypted_votes: &'a [[BaseElement; AFFINE_POINT_WIDTH]],
+        proof_points: &'a [[BaseElement; AFFINE_POINT_WIDTH * PROOF_NUM_POINTS]],
+        proof_scalars: &'a [[Scalar; PROOF_NUM_SCALARS]],
     ) -> Self {
+        let blinding_keys = compute_blinding_keys(voting_keys, None);
         Self {
             options,
             voting_keys,
             encrypted_votes,
             proof_points,
             proof_scalars,
+            shard: None,
+            validity: vec![true; voting_keys.len()],
+            manifest_digest: None,
+            ballot_context: None,
+            register_commitment: None,
+            key_order: KeyOrder::Submission,
+            blinding_keys,
         }
     }
 
+    /// Create a prover for a shard of consecutive voters, starting at the
+    /// global index and blinding key given in the shard information
+    pub fn new_shard(
+        options: ProofOptions,
+        shard: ShardInfo,
+        voting_keys: &'a [[BaseElement; AFFINE_POINT_WIDTH]],
+        encrypted_votes: &'a [[BaseElement; AFFINE_POINT_WIDTH]],
+        proof_points: &'a [[BaseElement; AFFINE_POINT_WIDTH * PROOF_NUM_POINTS]],
+        proof_scalars: &'a [[Scalar; PROOF_NUM_SCALARS]],
+    ) -> Self {
+        let blinding_keys = compute_blinding_keys(voting_keys, Some(shard));
+        Self {
+            options,
+            voting_keys,
+            encrypted_votes,
+            proof_points,
+            proof_scalars,
+            shard: Some(shard),
+            validity: vec![true; voting_keys.len()],
+            manifest_digest: None,
+            ballot_context: None,
+            register_commitment: None,
+            key_order: KeyOrder::Submission,
+            blinding_keys,
+        }
+    }
+
+    /// Flag the ballots whose CDS proof is invalid, so that the proof only
+    /// asserts the validity of the other ballots. All ballots are valid by default.
+    pub fn with_validity(mut self, validity: Vec<bool>) -> Self {
+        debug_assert_eq!(validity.len(), self.voting_keys.len());
+        self.validity = validity;
+        self
+    }
+
+    /// Bind the proof to the digest of an election manifest
+    pub fn with_manifest(mut self, manifest_digest: [BaseElement; DIGEST_SIZE]) -> Self {
+        self.manifest_digest = Some(manifest_digest);
+        self
+    }
+
+    /// Verify the CDS proofs of ballots cast against the digest of a manifest which
+    /// was amended since, see ElectionManifest::amend. The proof is still bound to
+    /// the digest given to with_manifest.
+    pub fn with_ballot_context(mut self, ballot_context: [BaseElement; DIGEST_SIZE]) -> Self {
+        self.ballot_context = Some(ballot_context);
+        self
+    }
+
+    /// Chain the proof to the commitment to the registration phase
+    pub fn with_register_commitment(mut self, register_commitment: PhaseCommitment) -> Self {
+        self.register_commitment = Some(register_commitment);
+        self
+    }
+
+    /// Bind the proof to the order in which voting keys are listed
+    pub fn with_key_order(mut self, key_order: KeyOrder) -> Self {
+        self.key_order = key_order;
+        self
+    }
+
     pub fn build_trace(&self) -> TraceTable<BaseElement> {
         let num_proofs = self.voting_keys.len();
         debug_assert!(num_proofs >= 2, "Number of proofs cannot be less than 2.");
@@ -58,20 +161,8 @@ impl CDSProver {
         // allocate memory to hold the trace table
         let trace_length: usize = CDS_CYCLE_LENGTH * num_proofs;
         let mut trace = TraceTable::new(TRACE_WIDTH, trace_length);
-        // compute the initial blinding key
-        let mut blinding_keys = Vec::with_capacity(num_proofs);
-        let mut blinding_key = ecc::IDENTITY;
-
-        for voting_key in self.voting_keys[1..].iter() {
-            ecc::compute_add_mixed(&mut blinding_key, &ecc::compute_negation_affine(voting_key));
-        }
-
-        for i in 0..num_proofs - 1 {
-            blinding_keys.push(ecc::reduce_to_affine(&blinding_key));
-            ecc::compute_add_mixed(&mut blinding_key, &self.voting_keys[i]);
-            ecc::compute_add_mixed(&mut blinding_key, &self.voting_keys[i + 1]);
-        }
-        blinding_keys.push(ecc::reduce_to_affine(&blinding_key));
+        let first_voter_index = self.shard.map_or(0, |shard| shard.first_voter_index);
+        let context = election_context(self.ballot_context.or(self.manifest_digest));
 
         trace.fragments(CDS_CYCLE_LENGTH).for_each(|mut cds_trace| {
             // voter index
@@ -87,27 +178,31 @@ impl CDSProver {
             let r1_bits = r1_bytes.as_bits::<Lsb0>();
             let r2_bits = r2_bytes.as_bits::<Lsb0>();
 
-            // hash_msg = [i, vk, ev, a1, b1, a2, b2]
-            let hash_msg = prepare_hash_message(
-                i,
+            // hash_msg = [i, version, vk, ev, a1, b1, a2, b2, bk, context]
+            let transcript = build_transcript(
+                first_voter_index + i,
                 &self.voting_keys[i],
+                &self.blinding_keys[i],
                 &self.encrypted_votes[i],
                 &self.proof_points[i],
+                &context,
             );
+            let mut hash_msg = [BaseElement::ZERO; HASH_MSG_LENGTH];
+            hash_msg.copy_from_slice(transcript.message());
 
-            let c_bytes = hash_message_bytes(&hash_msg);
+            let c_bytes = transcript.challenge_bytes();
             let c_bits = c_bytes.as_bits::<Lsb0>();
 
             cds_trace.fill(
                 |state| {
-                    init_cds_verification_state(i, state);
+                    init_cds_verification_state(first_voter_index + i, state);
                 },
                 |step, state| {
                     update_cds_verification_state(
                         step,
                         &hash_msg,
                         &self.voting_keys[i],
-                        &blinding_keys[i],
+                        &self.blinding_keys[i],
                         &encrypted_vote_1,
                         &encrypted_vote_2,
                         d1_bits,
@@ -122,78 +217,65 @@ impl CDSProver {
         });
         trace
     }
+
+    /// Prove from the checkpoint saved in the directory at path by a previous
+    /// interrupted run on the same inputs, if any, and checkpoint each stage
+    #[cfg(feature = "std")]
+    pub fn prove_resumable(
+        &self,
+        path: &Path,
+    ) -> Result<(PublicInputs, StarkProof), CheckpointError> {
+        let checkpoint = Checkpoint::open(path, self.checkpoint_binding())?;
+        checkpoint::prove_resumable(self, &checkpoint, || self.build_trace())
+    }
+
+    /// Digest of the proof options and of all inputs of the prover
+    #[cfg(feature = "std")]
+    fn checkpoint_binding(&self) -> [u8; checkpoint::BINDING_SIZE] {
+        let mut inputs = self.options.to_bytes();
+        let shard_index = self.shard.map_or(0, |shard| shard.first_voter_index);
+        inputs.write_u64(shard_index as u64);
+        if let Some(manifest_digest) = self.manifest_digest {
+            Serializable::write_batch_into(&manifest_digest, &mut inputs);
+        }
+        if let Some(ballot_context) = self.ballot_context {
+            Serializable::write_batch_into(&ballot_context, &mut inputs);
+        }
+        write_phase_commitment(self.register_commitment, &mut inputs);
+        self.key_order.write_into(&mut inputs);
+        write_validity_bitmap(&self.validity, &mut inputs);
+        for i in 0..self.voting_keys.len() {
+            Serializable::write_batch_into(&self.voting_keys[i], &mut inputs);
+            Serializable::write_batch_into(&self.blinding_keys[i], &mut inputs);
+            Serializable::write_batch_into(&self.encrypted_votes[i], &mut inputs);
+            Serializable::write_batch_into(&self.proof_points[i], &mut inputs);
+            for proof_scalar in self.proof_scalars[i].iter() {
+                inputs.write_u8_slice(&proof_scalar.to_bytes());
+            }
+        }
+        checkpoint::compute_binding(&inputs)
+    }
 }
 
-impl Prover for CDSProver {
+impl<'a> Prover for CDSProver<'a> {
     type BaseField = BaseElement;
     type Air = CDSAir;
     type Trace = TraceTable<BaseElement>;
 
-    // This method should use the existing trace to extract the public inputs to be given
-    // to the verifier.
-    fn get_pub_inputs(&self, trace: &Self::Trace) -> PublicInputs {
-        let num_proofs = self.voting_keys.len();
-        let mut outputs = Vec::with_capacity(num_proofs);
-
-        for i in 0..num_proofs {
-            // get the output of CDS proof verifications from execution trace
-            let mut output = [BaseElement::ZERO; AFFINE_POINT_WIDTH * 5];
-            let mut row = [BaseElement::ZERO; TRACE_WIDTH];
-
-            trace.read_row_into(SCALAR_MUL_LENGTH + CDS_CYCLE_LENGTH * i + 1, &mut row);
-
-            // validate a1
-            output[..AFFINE_POINT_WIDTH].copy_from_slice(&diff_registers::<AFFINE_POINT_WIDTH>(
-                &row[PROJECTIVE_POINT_WIDTH + 1..PROJECTIVE_POINT_WIDTH + AFFINE_POINT_WIDTH + 1],
-                &self.proof_points[i][..AFFINE_POINT_WIDTH],
-            ));
-
-            // validate b1
-            output[AFFINE_POINT_WIDTH..AFFINE_POINT_WIDTH * 2].copy_from_slice(&diff_registers::<
-                AFFINE_POINT_WIDTH,
-            >(
-                &row[2 * PROJECTIVE_POINT_WIDTH + 1
-                    ..2 * PROJECTIVE_POINT_WIDTH + AFFINE_POINT_WIDTH + 1],
-                &self.proof_points[i][AFFINE_POINT_WIDTH..AFFINE_POINT_WIDTH * 2],
-            ));
-
-            trace.read_row_into(
-                SCALAR_MUL_LENGTH + NROWS_PER_PHASE + CDS_CYCLE_LENGTH * i + 1,
-                &mut row,
-            );
-
-            // validate a2
-            output[AFFINE_POINT_WIDTH * 2..AFFINE_POINT_WIDTH * 3].copy_from_slice(
-                &diff_registers::<AFFINE_POINT_WIDTH>(
-                    &row[PROJECTIVE_POINT_WIDTH + 1
-                        ..PROJECTIVE_POINT_WIDTH + AFFINE_POINT_WIDTH + 1],
-                    &self.proof_points[i][AFFINE_POINT_WIDTH * 2..AFFINE_POINT_WIDTH * 3],
-                ),
-            );
-
-            // validate b2
-            output[AFFINE_POINT_WIDTH * 3..AFFINE_POINT_WIDTH * 4].copy_from_slice(
-                &diff_registers::<AFFINE_POINT_WIDTH>(
-                    &row[2 * PROJECTIVE_POINT_WIDTH + 1
-                        ..2 * PROJECTIVE_POINT_WIDTH + AFFINE_POINT_WIDTH + 1],
-                    &self.proof_points[i][AFFINE_POINT_WIDTH * 3..AFFINE_POINT_WIDTH * 4],
-                ),
-            );
-
-            // validate x and z coordinates of (c - d1 - d2) * vk
-            output[AFFINE_POINT_WIDTH * 4..AFFINE_POINT_WIDTH * 4 + POINT_COORDINATE_WIDTH]
-                .copy_from_slice(&row[..POINT_COORDINATE_WIDTH]);
-            output[AFFINE_POINT_WIDTH * 4 + POINT_COORDINATE_WIDTH..AFFINE_POINT_WIDTH * 5]
-                .copy_from_slice(&row[AFFINE_POINT_WIDTH..PROJECTIVE_POINT_WIDTH]);
-
-            outputs.push(output);
-        }
-
+    // The results of CDS proof verifications are asserted by the AIR, so that
+    // public inputs only consist of the inputs of the prover.
+    fn get_pub_inputs(&self, _trace: &Self::Trace) -> PublicInputs {
         PublicInputs {
-            voting_keys: self.voting_keys.clone(),
-            encrypted_votes: self.encrypted_votes.clone(),
-            cds_proofs: self.proof_points.clone(),
-            outputs,
+            voting_keys: self.voting_keys.to_vec(),
+            encrypted_votes: self.encrypted_votes.to_vec(),
+            cds_proofs: self.proof_points.to_vec(),
+            validity: self.validity.clone(),
+            shard: self.shard,
+            manifest_digest: self.manifest_digest,
+            ballot_context: self.ballot_context,
+            register_commitment: self.register_commitment,
+            key_order: self.key_order,
+            blinding_keys: self.blinding_keys.clone(),
         }
     }
 
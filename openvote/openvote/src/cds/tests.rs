@@ -6,7 +6,22 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use winterfell::{FieldExtension, HashFunction, ProofOptions};
+use super::constants::{PROOF_NUM_POINTS, UNBOUND_CONTEXT};
+use super::PublicInputs;
+use super::{
+    compute_all_blinding_keys, compute_blinding_key, verify_cds_proof, verify_cds_proof_projective,
+    CdsVerificationError,
+};
+use crate::compat::curve::{ProjectivePoint, Scalar};
+use crate::compat::proof_options;
+use crate::testing::strategies::point;
+use crate::utils::ecc::{AFFINE_POINT_WIDTH, BYTES_PER_COMPRESSED_POINT};
+use crate::utils::ordering::KeyOrder;
+use proptest::{collection::vec, prelude::*};
+use winterfell::{
+    math::{fields::f63::BaseElement, FieldElement},
+    FieldExtension, HashFunction, ProofOptions, Serializable,
+};
 
 #[test]
 fn cds_test_proof_verification() {
@@ -54,15 +69,137 @@ fn cds_test_proof_verification_wrong_proof() {
 }
 
 #[test]
-fn cds_test_proof_verification_wrong_output() {
+fn cds_test_public_inputs_without_outputs() {
     let cds = super::CDSExample::new(build_options(1), 2).0;
     let (pub_inputs, proof) = cds.prove();
-    let verified = cds.verify_with_wrong_output(proof, pub_inputs);
-    assert!(verified.is_err());
+    // Only the count and the compressed voting keys, encrypted votes
+    // and proof points are serialized, CDS outputs are asserted by the AIR
+    let pub_inputs_bytes = pub_inputs.to_bytes();
+    assert_eq!(
+        pub_inputs_bytes.len(),
+        4 + 2 * (2 + PROOF_NUM_POINTS) * BYTES_PER_COMPRESSED_POINT
+    );
+    let pub_inputs = super::PublicInputs::from_bytes(&pub_inputs_bytes).unwrap();
+    assert!(cds.verify(proof, pub_inputs).is_ok());
+}
+
+#[test]
+fn cds_test_naive_verification_reports_failed_equation() {
+    let (_, (voting_keys, encrypted_votes, proof_scalars, proof_points)) =
+        super::CDSExample::new(build_options(1), 2);
+    // With two voters, the blinding key of the first voter is -vk_1
+    let blinding_key = ProjectivePoint::identity() - voting_keys[1];
+    let verify = |scalars| {
+        verify_cds_proof_projective(
+            0,
+            voting_keys[0],
+            blinding_key,
+            encrypted_votes[0],
+            &proof_points[0],
+            &scalars,
+            &UNBOUND_CONTEXT,
+        )
+    };
+    assert_eq!(verify(proof_scalars[0]), Ok(()));
+
+    let mut wrong_scalars = proof_scalars[0];
+    wrong_scalars[0] += Scalar::from(1u32);
+    assert_eq!(
+        verify(wrong_scalars),
+        Err(CdsVerificationError::InvalidChallenge)
+    );
+
+    let mut wrong_scalars = proof_scalars[0];
+    wrong_scalars[2] += Scalar::from(1u32);
+    assert_eq!(verify(wrong_scalars), Err(CdsVerificationError::InvalidA1));
+}
+
+#[test]
+fn cds_test_standalone_verification() {
+    let (cds, _) = super::CDSExample::new(build_options(1), 4);
+    for i in 0..4 {
+        let blinding_key = compute_blinding_key(&cds.voting_keys, i);
+        assert_eq!(
+            verify_cds_proof(
+                i,
+                &cds.voting_keys[i],
+                &blinding_key,
+                &cds.encrypted_votes[i],
+                &cds.proof_points[i],
+                &cds.proof_scalars[i],
+            ),
+            Ok(())
+        );
+    }
+    let blinding_key = compute_blinding_key(&cds.voting_keys, 0);
+    assert_eq!(
+        verify_cds_proof(
+            1,
+            &cds.voting_keys[0],
+            &blinding_key,
+            &cds.encrypted_votes[0],
+            &cds.proof_points[0],
+            &cds.proof_scalars[0],
+        ),
+        Err(CdsVerificationError::InvalidChallenge),
+        "Proof should be bound to the voter index."
+    );
+}
+
+#[test]
+fn cds_test_cached_blinding_keys() {
+    let (cds, _) = super::CDSExample::new(build_options(1), 8);
+    let blinding_keys = compute_all_blinding_keys(&cds.voting_keys);
+    for (i, blinding_key) in blinding_keys.iter().enumerate() {
+        assert_eq!(*blinding_key, compute_blinding_key(&cds.voting_keys, i));
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn cds_test_pub_inputs_roundtrip(
+        ballots in vec((point(), point(), vec(point(), PROOF_NUM_POINTS), any::<bool>()), 0..6),
+    ) {
+        let mut pub_inputs = PublicInputs {
+            voting_keys: vec![],
+            encrypted_votes: vec![],
+            cds_proofs: vec![],
+            validity: vec![],
+            shard: None,
+            manifest_digest: None,
+            ballot_context: None,
+            register_commitment: None,
+            key_order: KeyOrder::Submission,
+            blinding_keys: vec![],
+        };
+        for (voting_key, encrypted_vote, proof_points, is_valid) in ballots.iter() {
+            let mut cds_proof = [BaseElement::ZERO; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH];
+            for (chunk, proof_point) in cds_proof
+                .chunks_mut(AFFINE_POINT_WIDTH)
+                .zip(proof_points.iter())
+            {
+                chunk.copy_from_slice(proof_point);
+            }
+            pub_inputs.voting_keys.push(*voting_key);
+            pub_inputs.encrypted_votes.push(*encrypted_vote);
+            pub_inputs.cds_proofs.push(cds_proof);
+            pub_inputs.validity.push(*is_valid);
+        }
+
+        let bytes = pub_inputs.to_bytes();
+        let restored = PublicInputs::from_bytes(&bytes).unwrap();
+        prop_assert_eq!(&restored.voting_keys, &pub_inputs.voting_keys);
+        prop_assert_eq!(&restored.encrypted_votes, &pub_inputs.encrypted_votes);
+        prop_assert_eq!(&restored.cds_proofs, &pub_inputs.cds_proofs);
+        prop_assert_eq!(&restored.validity, &pub_inputs.validity);
+        prop_assert_eq!(restored.to_bytes(), bytes);
+    }
 }
 
 fn build_options(extension: u8) -> ProofOptions {
-    ProofOptions::new(
+    proof_options(
         42,
         8,
         0,
@@ -8,9 +8,10 @@
 
 use super::constants::*;
 use super::{ecc, field, rescue};
+use crate::compat::curve::Scalar;
 use bitvec::{order::Lsb0, slice::BitSlice};
 use core::cmp::Ordering;
-use winterfell::math::{curves::curve_f63::Scalar, fields::f63::BaseElement, FieldElement};
+use winterfell::math::{fields::f63::BaseElement, FieldElement};
 
 // TRACE INITIALIZATION
 // ================================================================================================
@@ -31,7 +32,8 @@ pub(crate) fn init_cds_verification_state(voter_index: usize, state: &mut [BaseE
     state[PROJECTIVE_POINT_WIDTH * 4 + POINT_COORDINATE_WIDTH + 2] = BaseElement::ONE;
 
     // copy the first RATE_WIDTH bytes of hash_msg into the registers for hashing
-    state[PROJECTIVE_POINT_WIDTH * 5 + 7] = BaseElement::from(voter_index as u8);
+    state[PROJECTIVE_POINT_WIDTH * 5 + 7] = BaseElement::from(voter_index as u64);
+    state[PROJECTIVE_POINT_WIDTH * 5 + 8] = BaseElement::from(CDS_TRANSCRIPT_VERSION);
 }
 
 // TRANSITION FUNCTION
@@ -238,26 +240,6 @@ pub(crate) fn decompose_scalars(
     )
 }
 
-/// Prepare the hash message to compute the challenge
-/// The hash message consists of 9 public parameters
-#[inline]
-pub(crate) fn prepare_hash_message(
-    voter_index: usize,
-    voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
-    encrypted_vote: &[BaseElement; AFFINE_POINT_WIDTH],
-    proof_points: &[BaseElement; PROOF_NUM_POINTS * AFFINE_POINT_WIDTH],
-) -> [BaseElement; HASH_MSG_LENGTH] {
-    // Message contains (i, vk, ev, a1, b1, a2, b2)
-    // 4 last null bytes are for padding
-    let mut hash_msg = [BaseElement::ZERO; HASH_MSG_LENGTH];
-    hash_msg[0] = BaseElement::from(voter_index as u8);
-    hash_msg[AFFINE_POINT_WIDTH..AFFINE_POINT_WIDTH * 2].copy_from_slice(voting_key); // x
-    hash_msg[AFFINE_POINT_WIDTH * 2..AFFINE_POINT_WIDTH * 3].copy_from_slice(encrypted_vote); // y
-    hash_msg[AFFINE_POINT_WIDTH * 3..AFFINE_POINT_WIDTH * (PROOF_NUM_POINTS + 3)]
-        .copy_from_slice(proof_points); // a1, b1, a2, b2
-    hash_msg
-}
-
 /// Calculate two points (ev + G, ev - G)
 #[inline]
 pub(crate) fn prepare_encrypted_votes(
@@ -0,0 +1,682 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// TRUSTEE KEY CEREMONY
+// ================================================================================================
+// Distributed generation of a threshold key among the trustees of an election, following
+// Feldman's verifiable secret sharing. Each trustee runs a Trustee state machine locally:
+// 1. Deal: it shares a random polynomial of degree threshold - 1, broadcasting commitments to
+//    its coefficients and sending each other trustee its share over a private channel.
+// 2. Complain: it checks the shares it received against the commitments of their dealers, and
+//    broadcasts a complaint against each dealer whose share is missing or invalid.
+// 3. Respond: each accused dealer reveals the disputed shares.
+// 4. Finish: dealers whose commitments are malformed or whose complaints are not answered by a
+//    valid share are disqualified, and the shares of qualified dealers sum to a key share.
+// Broadcast messages are collected in a CeremonyTranscript, from which auditors recompute the
+// qualified dealers, the group key and the verification key of each trustee. Authentication
+// of broadcast messages and encryption of shares are left to the transport.
+
+use crate::compat::curve::{AffinePoint, ProjectivePoint, Scalar};
+use crate::utils::{
+    ecc::{
+        mul_generator_constant_time, projective_to_elements, read_canonical_scalar,
+        read_compressed_point, write_compressed_points, AFFINE_POINT_WIDTH,
+    },
+    wire,
+};
+use core::fmt;
+use rand_core::{OsRng, RngCore};
+use winterfell::{
+    math::fields::f63::BaseElement, ByteReader, ByteWriter, Deserializable, DeserializationError,
+    Serializable, SliceReader,
+};
+
+#[cfg(test)]
+mod tests;
+
+// ERRORS
+// ================================================================================================
+
+/// Errors raised by the trustee key ceremony
+#[derive(Debug, Clone, PartialEq)]
+pub enum CeremonyError {
+    /// This error occurs when the threshold is zero or larger than the number of trustees
+    InvalidThreshold,
+    /// This error occurs when a message refers to a trustee index out of range
+    InvalidTrustee(usize),
+    /// This error occurs when a message is received or sent in a round where it is not expected
+    UnexpectedRound(Round),
+    /// This error occurs when a message of the given trustee was already received
+    DuplicateMessage(usize),
+    /// This error occurs when a share is addressed to another trustee
+    WrongRecipient(usize),
+    /// This error occurs when the transcript was produced for other ceremony parameters
+    ParametersMismatch,
+    /// This error occurs when no valid share of a qualified dealer is known to the trustee
+    MissingShare(usize),
+    /// This error occurs when fewer dealers than the threshold are qualified
+    NotEnoughQualified(usize),
+}
+
+// MESSAGES
+// ================================================================================================
+
+/// Number of trustees and number of trustees required to use the group key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CeremonyParams {
+    num_trustees: usize,
+    threshold: usize,
+}
+
+impl CeremonyParams {
+    /// Parameters of a ceremony among num_trustees trustees, threshold of which
+    /// are required to use the group key
+    pub fn new(num_trustees: usize, threshold: usize) -> Result<Self, CeremonyError> {
+        if threshold == 0 || threshold > num_trustees {
+            return Err(CeremonyError::InvalidThreshold);
+        }
+        Ok(Self {
+            num_trustees,
+            threshold,
+        })
+    }
+
+    /// Number of trustees
+    pub fn num_trustees(&self) -> usize {
+        self.num_trustees
+    }
+
+    /// Number of trustees required to use the group key
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    fn check_trustee(&self, trustee: usize) -> Result<(), CeremonyError> {
+        if trustee < self.num_trustees {
+            Ok(())
+        } else {
+            Err(CeremonyError::InvalidTrustee(trustee))
+        }
+    }
+}
+
+/// Commitments of a dealer to the coefficients of its polynomial, broadcast in the deal round
+#[derive(Debug, Clone, PartialEq)]
+pub struct Commitments {
+    /// Index of the dealer
+    pub dealer: usize,
+    /// Coefficients of the polynomial times the generator, constant term first
+    pub coefficients: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+}
+
+impl Commitments {
+    /// Returns true if share was dealt by this dealer and is the evaluation of the
+    /// committed polynomial at the point of its recipient
+    pub fn verify_share(&self, share: &Share) -> bool {
+        share.dealer == self.dealer
+            && self.evaluate(share.recipient) == mul_generator_constant_time(share.value)
+    }
+
+    /// Commitment to the evaluation of the polynomial at the point of the given trustee
+    fn evaluate(&self, trustee: usize) -> ProjectivePoint {
+        let x = evaluation_point(trustee);
+        let mut result = ProjectivePoint::identity();
+        for coefficient in self.coefficients.iter().rev() {
+            result = result * x;
+            result += AffinePoint::from_raw_coordinates(*coefficient);
+        }
+        result
+    }
+}
+
+/// Evaluation of the polynomial of a dealer at the point of a trustee, sent privately
+/// in the deal round or revealed in the respond round
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Share {
+    /// Index of the dealer
+    pub dealer: usize,
+    /// Index of the trustee the share is addressed to
+    pub recipient: usize,
+    /// Value of the share
+    pub value: Scalar,
+}
+
+/// Complaint of a trustee against a dealer whose share is missing or invalid,
+/// broadcast in the complain round
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Complaint {
+    /// Index of the complaining trustee
+    pub complainer: usize,
+    /// Index of the accused dealer
+    pub dealer: usize,
+}
+
+// TRUSTEE
+// ================================================================================================
+
+/// Rounds of the ceremony, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Round {
+    /// Commitments and shares are dealt
+    Deal,
+    /// Received shares are checked and complaints are broadcast
+    Complain,
+    /// Accused dealers reveal the disputed shares
+    Respond,
+    /// Key shares are computed from the transcript
+    Finish,
+    /// The ceremony is over
+    Done,
+}
+
+/// Secret key share of a trustee at the end of the ceremony
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyShare {
+    /// Index of the trustee
+    pub index: usize,
+    /// Secret share of the group key
+    pub secret: Scalar,
+    /// Group public key
+    pub group_key: [BaseElement; AFFINE_POINT_WIDTH],
+    /// Public key of the secret share
+    pub verification_key: [BaseElement; AFFINE_POINT_WIDTH],
+}
+
+/// State of the ceremony as run locally by a trustee
+pub struct Trustee {
+    params: CeremonyParams,
+    index: usize,
+    round: Round,
+    polynomial: Vec<Scalar>,
+    commitments: Vec<Option<Commitments>>,
+    shares: Vec<Option<Scalar>>,
+}
+
+impl Trustee {
+    /// Start the ceremony as the trustee at the given index
+    pub fn new(params: CeremonyParams, index: usize) -> Result<Self, CeremonyError> {
+        Self::new_with_rng(params, index, &mut OsRng)
+    }
+
+    /// Start the ceremony as Trustee::new does, drawing the polynomial from the given RNG
+    pub fn new_with_rng<R: RngCore>(
+        params: CeremonyParams,
+        index: usize,
+        rng: &mut R,
+    ) -> Result<Self, CeremonyError> {
+        params.check_trustee(index)?;
+        let polynomial = (0..params.threshold)
+            .map(|_| Scalar::random(&mut *rng))
+            .collect::<Vec<Scalar>>();
+        let mut shares = vec![None; params.num_trustees];
+        shares[index] = Some(evaluate(&polynomial, index));
+
+        Ok(Self {
+            params,
+            index,
+            round: Round::Deal,
+            polynomial,
+            commitments: vec![None; params.num_trustees],
+            shares,
+        })
+    }
+
+    /// Index of the trustee
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Current round of the ceremony
+    pub fn round(&self) -> Round {
+        self.round
+    }
+
+    /// Deal round: return the commitments to broadcast and the shares to send
+    /// to each other trustee
+    pub fn deal(&mut self) -> Result<(Commitments, Vec<Share>), CeremonyError> {
+        self.expect_round(Round::Deal)?;
+        let commitments = Commitments {
+            dealer: self.index,
+            coefficients: self
+                .polynomial
+                .iter()
+                .map(|&coefficient| {
+                    projective_to_elements(mul_generator_constant_time(coefficient))
+                })
+                .collect(),
+        };
+        let shares = (0..self.params.num_trustees)
+            .filter(|&recipient| recipient != self.index)
+            .map(|recipient| Share {
+                dealer: self.index,
+                recipient,
+                value: evaluate(&self.polynomial, recipient),
+            })
+            .collect();
+        self.commitments[self.index] = Some(commitments.clone());
+        self.round = Round::Complain;
+        Ok((commitments, shares))
+    }
+
+    /// Record the commitments broadcast by another trustee
+    pub fn receive_commitments(&mut self, commitments: Commitments) -> Result<(), CeremonyError> {
+        self.expect_dealing()?;
+        self.params.check_trustee(commitments.dealer)?;
+        let slot = &mut self.commitments[commitments.dealer];
+        if slot.is_some() {
+            return Err(CeremonyError::DuplicateMessage(commitments.dealer));
+        }
+        *slot = Some(commitments);
+        Ok(())
+    }
+
+    /// Record the share sent privately by another trustee
+    pub fn receive_share(&mut self, share: Share) -> Result<(), CeremonyError> {
+        self.expect_dealing()?;
+        self.params.check_trustee(share.dealer)?;
+        if share.recipient != self.index {
+            return Err(CeremonyError::WrongRecipient(share.recipient));
+        }
+        let slot = &mut self.shares[share.dealer];
+        if slot.is_some() {
+            return Err(CeremonyError::DuplicateMessage(share.dealer));
+        }
+        *slot = Some(share.value);
+        Ok(())
+    }
+
+    /// Complain round: check the received shares against the commitments of their dealers,
+    /// and return the complaints to broadcast. Dealers who did not broadcast commitments are
+    /// disqualified without complaint.
+    pub fn complain(&mut self) -> Result<Vec<Complaint>, CeremonyError> {
+        self.expect_round(Round::Complain)?;
+        let complaints = (0..self.params.num_trustees)
+            .filter(|&dealer| match &self.commitments[dealer] {
+                Some(commitments) => !self.has_valid_share(commitments),
+                None => false,
+            })
+            .map(|dealer| Complaint {
+                complainer: self.index,
+                dealer,
+            })
+            .collect();
+        self.round = Round::Respond;
+        Ok(complaints)
+    }
+
+    /// Respond round: return the shares to reveal in response to the complaints
+    /// broadcast against this trustee
+    pub fn respond(&mut self, complaints: &[Complaint]) -> Result<Vec<Share>, CeremonyError> {
+        self.expect_round(Round::Respond)?;
+        let mut responses = vec![];
+        for complaint in complaints.iter().filter(|c| c.dealer == self.index) {
+            self.params.check_trustee(complaint.complainer)?;
+            responses.push(Share {
+                dealer: self.index,
+                recipient: complaint.complainer,
+                value: evaluate(&self.polynomial, complaint.complainer),
+            });
+        }
+        self.round = Round::Finish;
+        Ok(responses)
+    }
+
+    /// Finish round: sum the shares of the dealers qualified by the transcript
+    /// into the key share of this trustee
+    pub fn finish(&mut self, transcript: &CeremonyTranscript) -> Result<KeyShare, CeremonyError> {
+        self.expect_round(Round::Finish)?;
+        if transcript.params != self.params {
+            return Err(CeremonyError::ParametersMismatch);
+        }
+        let outcome = transcript.verify()?;
+
+        let mut secret = Scalar::zero();
+        for &dealer in outcome.qualified.iter() {
+            let commitments = transcript.commitments_of(dealer);
+            let share = match self.shares[dealer] {
+                Some(value) if self.has_valid_share(commitments) => value,
+                _ => transcript
+                    .response(dealer, self.index)
+                    .ok_or(CeremonyError::MissingShare(dealer))?,
+            };
+            secret += share;
+        }
+
+        self.polynomial.clear();
+        self.round = Round::Done;
+        Ok(KeyShare {
+            index: self.index,
+            secret,
+            group_key: outcome.group_key,
+            verification_key: outcome.verification_keys[self.index],
+        })
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    fn expect_round(&self, round: Round) -> Result<(), CeremonyError> {
+        if self.round == round {
+            Ok(())
+        } else {
+            Err(CeremonyError::UnexpectedRound(self.round))
+        }
+    }
+
+    /// Messages of the deal round can be received until complaints are sent
+    fn expect_dealing(&self) -> Result<(), CeremonyError> {
+        match self.round {
+            Round::Deal | Round::Complain => Ok(()),
+            round => Err(CeremonyError::UnexpectedRound(round)),
+        }
+    }
+
+    fn has_valid_share(&self, commitments: &Commitments) -> bool {
+        let dealer = commitments.dealer;
+        commitments.coefficients.len() == self.params.threshold
+            && self.shares[dealer].map_or(false, |value| {
+                commitments.verify_share(&Share {
+                    dealer,
+                    recipient: self.index,
+                    value,
+                })
+            })
+    }
+}
+
+impl fmt::Debug for Trustee {
+    // the polynomial and the received shares are secret
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Trustee")
+            .field("params", &self.params)
+            .field("index", &self.index)
+            .field("round", &self.round)
+            .finish()
+    }
+}
+
+// TRANSCRIPT
+// ================================================================================================
+
+/// Broadcast messages of a ceremony
+#[derive(Debug, Clone, PartialEq)]
+pub struct CeremonyTranscript {
+    /// Parameters of the ceremony
+    pub params: CeremonyParams,
+    /// Commitments broadcast in the deal round
+    pub commitments: Vec<Commitments>,
+    /// Complaints broadcast in the complain round
+    pub complaints: Vec<Complaint>,
+    /// Shares revealed in the respond round
+    pub responses: Vec<Share>,
+}
+
+/// Result of a ceremony, as recomputed from its transcript
+#[derive(Debug, Clone, PartialEq)]
+pub struct CeremonyOutcome {
+    /// Indices of the qualified dealers, in increasing order
+    pub qualified: Vec<usize>,
+    /// Group public key
+    pub group_key: [BaseElement; AFFINE_POINT_WIDTH],
+    /// Public key of the secret share of each trustee
+    pub verification_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+}
+
+impl CeremonyTranscript {
+    /// Create an empty transcript
+    pub fn new(params: CeremonyParams) -> Self {
+        Self {
+            params,
+            commitments: vec![],
+            complaints: vec![],
+            responses: vec![],
+        }
+    }
+
+    /// Reconstruct a transcript from a sequence of bytes
+    pub fn from_bytes(source: &[u8]) -> Result<Self, DeserializationError> {
+        let mut source = SliceReader::new(source);
+        Self::read_from(&mut source)
+    }
+
+    /// Check the transcript and recompute the qualified dealers, the group key
+    /// and the verification key of each trustee
+    pub fn verify(&self) -> Result<CeremonyOutcome, CeremonyError> {
+        self.check_messages()?;
+        let qualified = (0..self.params.num_trustees)
+            .filter(|&dealer| self.is_qualified(dealer))
+            .collect::<Vec<usize>>();
+        if qualified.len() < self.params.threshold {
+            return Err(CeremonyError::NotEnoughQualified(qualified.len()));
+        }
+
+        let mut group_key = ProjectivePoint::identity();
+        let mut verification_keys = vec![ProjectivePoint::identity(); self.params.num_trustees];
+        for &dealer in qualified.iter() {
+            let commitments = self.commitments_of(dealer);
+            group_key += AffinePoint::from_raw_coordinates(commitments.coefficients[0]);
+            for (trustee, verification_key) in verification_keys.iter_mut().enumerate() {
+                *verification_key += commitments.evaluate(trustee);
+            }
+        }
+
+        Ok(CeremonyOutcome {
+            qualified,
+            group_key: projective_to_elements(group_key),
+            verification_keys: verification_keys
+                .into_iter()
+                .map(projective_to_elements)
+                .collect(),
+        })
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Check that all messages refer to trustees in range and are not duplicated
+    fn check_messages(&self) -> Result<(), CeremonyError> {
+        let mut dealers = vec![false; self.params.num_trustees];
+        for commitments in self.commitments.iter() {
+            self.params.check_trustee(commitments.dealer)?;
+            if dealers[commitments.dealer] {
+                return Err(CeremonyError::DuplicateMessage(commitments.dealer));
+            }
+            dealers[commitments.dealer] = true;
+        }
+        for (i, complaint) in self.complaints.iter().enumerate() {
+            self.params.check_trustee(complaint.complainer)?;
+            self.params.check_trustee(complaint.dealer)?;
+            if self.complaints[..i].contains(complaint) {
+                return Err(CeremonyError::DuplicateMessage(complaint.complainer));
+            }
+        }
+        for response in self.responses.iter() {
+            self.params.check_trustee(response.dealer)?;
+            self.params.check_trustee(response.recipient)?;
+        }
+        Ok(())
+    }
+
+    /// A dealer is qualified if its commitments have one coefficient per degree of
+    /// freedom and every complaint against it is answered with a valid share
+    fn is_qualified(&self, dealer: usize) -> bool {
+        let commitments = match self.commitments.iter().find(|c| c.dealer == dealer) {
+            Some(commitments) => commitments,
+            None => return false,
+        };
+        commitments.coefficients.len() == self.params.threshold
+            && self
+                .complaints
+                .iter()
+                .filter(|complaint| complaint.dealer == dealer)
+                .all(|complaint| self.response(dealer, complaint.complainer).is_some())
+    }
+
+    /// Commitments of a dealer, which must be in the transcript
+    fn commitments_of(&self, dealer: usize) -> &Commitments {
+        self.commitments
+            .iter()
+            .find(|c| c.dealer == dealer)
+            .expect("Qualified dealers have commitments.")
+    }
+
+    /// Valid share revealed by a dealer in response to the complaint of a trustee
+    fn response(&self, dealer: usize, recipient: usize) -> Option<Scalar> {
+        let commitments = self.commitments.iter().find(|c| c.dealer == dealer)?;
+        self.responses
+            .iter()
+            .find(|share| {
+                share.dealer == dealer
+                    && share.recipient == recipient
+                    && commitments.verify_share(share)
+            })
+            .map(|share| share.value)
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Point at which polynomials are evaluated for the trustee at the given index,
+/// as the constant term at 0 is the secret of the dealer
+fn evaluation_point(trustee: usize) -> Scalar {
+    Scalar::from(trustee as u64 + 1)
+}
+
+fn evaluate(polynomial: &[Scalar], trustee: usize) -> Scalar {
+    let x = evaluation_point(trustee);
+    polynomial
+        .iter()
+        .rev()
+        .fold(Scalar::zero(), |result, &coefficient| {
+            result * x + coefficient
+        })
+}
+
+// SERIALIZATION
+// ================================================================================================
+
+impl Serializable for CeremonyParams {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        wire::write_varint(target, self.num_trustees as u64);
+        wire::write_varint(target, self.threshold as u64);
+    }
+}
+
+impl Deserializable for CeremonyParams {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let num_trustees = wire::read_varint_usize(source)?;
+        let threshold = wire::read_varint_usize(source)?;
+        Self::new(num_trustees, threshold).map_err(|_| {
+            DeserializationError::InvalidValue(String::from("Invalid ceremony threshold."))
+        })
+    }
+}
+
+impl Serializable for Commitments {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        wire::write_varint(target, self.dealer as u64);
+        wire::write_varint(target, self.coefficients.len() as u64);
+        for coefficient in self.coefficients.iter() {
+            write_compressed_points(coefficient, target);
+        }
+    }
+}
+
+impl Deserializable for Commitments {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let dealer = wire::read_varint_usize(source)?;
+        let num_coefficients = wire::read_varint_usize(source)?;
+        let mut coefficients = Vec::with_capacity(num_coefficients.min(u8::MAX as usize));
+        for _ in 0..num_coefficients {
+            coefficients.push(read_compressed_point(source)?);
+        }
+        Ok(Self {
+            dealer,
+            coefficients,
+        })
+    }
+}
+
+impl Serializable for Share {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        wire::write_varint(target, self.dealer as u64);
+        wire::write_varint(target, self.recipient as u64);
+        target.write(self.value);
+    }
+}
+
+impl Deserializable for Share {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        Ok(Self {
+            dealer: wire::read_varint_usize(source)?,
+            recipient: wire::read_varint_usize(source)?,
+            value: read_canonical_scalar(source)?,
+        })
+    }
+}
+
+impl Serializable for Complaint {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        wire::write_varint(target, self.complainer as u64);
+        wire::write_varint(target, self.dealer as u64);
+    }
+}
+
+impl Deserializable for Complaint {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        Ok(Self {
+            complainer: wire::read_varint_usize(source)?,
+            dealer: wire::read_varint_usize(source)?,
+        })
+    }
+}
+
+impl Serializable for CeremonyTranscript {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        wire::write_header(target);
+        self.params.write_into(target);
+        write_messages(&self.commitments, target);
+        write_messages(&self.complaints, target);
+        write_messages(&self.responses, target);
+    }
+}
+
+impl Deserializable for CeremonyTranscript {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        if let wire::WireHeader::Legacy(_) = wire::read_header(source)? {
+            return Err(DeserializationError::InvalidValue(String::from(
+                "Missing wire header.",
+            )));
+        }
+        Ok(Self {
+            params: CeremonyParams::read_from(source)?,
+            commitments: read_messages(source)?,
+            complaints: read_messages(source)?,
+            responses: read_messages(source)?,
+        })
+    }
+}
+
+fn write_messages<M: Serializable, W: ByteWriter>(messages: &[M], target: &mut W) {
+    wire::write_varint(target, messages.len() as u64);
+    for message in messages.iter() {
+        message.write_into(target);
+    }
+}
+
+fn read_messages<M: Deserializable, R: ByteReader>(
+    source: &mut R,
+) -> Result<Vec<M>, DeserializationError> {
+    let num_messages = wire::read_varint_usize(source)?;
+    let mut messages = Vec::with_capacity(num_messages.min(u16::MAX as usize));
+    for _ in 0..num_messages {
+        messages.push(M::read_from(source)?);
+    }
+    Ok(messages)
+}
@@ -0,0 +1,223 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+
+#[test]
+fn ceremony_test_honest_trustees() {
+    let params = CeremonyParams::new(4, 3).unwrap();
+    let (key_shares, transcript) = run_ceremony(params, None, true);
+    assert!(transcript.complaints.is_empty());
+
+    let outcome = transcript.verify().unwrap();
+    assert_eq!(outcome.qualified, vec![0, 1, 2, 3]);
+    check_key_shares(&key_shares, &outcome, params.threshold());
+
+    // auditors recompute the same outcome from the serialized transcript
+    let restored = CeremonyTranscript::from_bytes(&transcript.to_bytes()).unwrap();
+    assert_eq!(restored, transcript);
+    assert_eq!(restored.verify().unwrap(), outcome);
+}
+
+#[test]
+fn ceremony_test_answered_complaint() {
+    let params = CeremonyParams::new(4, 2).unwrap();
+    let (key_shares, transcript) = run_ceremony(params, Some((1, 2)), true);
+    assert_eq!(
+        transcript.complaints,
+        vec![Complaint {
+            complainer: 2,
+            dealer: 1
+        }]
+    );
+
+    // the revealed share is valid, so the dealer stays qualified
+    let outcome = transcript.verify().unwrap();
+    assert_eq!(outcome.qualified, vec![0, 1, 2, 3]);
+    check_key_shares(&key_shares, &outcome, params.threshold());
+}
+
+#[test]
+fn ceremony_test_unanswered_complaint() {
+    let params = CeremonyParams::new(4, 2).unwrap();
+    let (key_shares, transcript) = run_ceremony(params, Some((1, 2)), false);
+
+    let outcome = transcript.verify().unwrap();
+    assert_eq!(outcome.qualified, vec![0, 2, 3]);
+    check_key_shares(&key_shares, &outcome, params.threshold());
+
+    // a revealed share which does not match the commitments does not answer the complaint
+    let mut forged = transcript.clone();
+    forged.responses.push(Share {
+        dealer: 1,
+        recipient: 2,
+        value: Scalar::random(OsRng),
+    });
+    assert_eq!(forged.verify().unwrap().qualified, vec![0, 2, 3]);
+}
+
+#[test]
+fn ceremony_test_not_enough_qualified() {
+    let params = CeremonyParams::new(3, 3).unwrap();
+    let (_, mut transcript) = run_ceremony(params, None, true);
+    transcript
+        .commitments
+        .retain(|commitments| commitments.dealer != 0);
+    assert_eq!(
+        transcript.verify(),
+        Err(CeremonyError::NotEnoughQualified(2))
+    );
+
+    let (_, mut transcript) = run_ceremony(params, None, true);
+    transcript.commitments[1].coefficients.pop();
+    assert_eq!(
+        transcript.verify(),
+        Err(CeremonyError::NotEnoughQualified(2))
+    );
+}
+
+#[test]
+fn ceremony_test_invalid_messages() {
+    assert_eq!(
+        CeremonyParams::new(3, 0),
+        Err(CeremonyError::InvalidThreshold)
+    );
+    assert_eq!(
+        CeremonyParams::new(3, 4),
+        Err(CeremonyError::InvalidThreshold)
+    );
+    let params = CeremonyParams::new(3, 2).unwrap();
+    assert!(Trustee::new(params, 3).is_err());
+
+    let mut trustees = (0..3)
+        .map(|index| Trustee::new(params, index).unwrap())
+        .collect::<Vec<Trustee>>();
+    assert_eq!(
+        trustees[0].complain(),
+        Err(CeremonyError::UnexpectedRound(Round::Deal))
+    );
+    let (commitments, shares) = trustees[1].deal().unwrap();
+    assert_eq!(shares.len(), 2);
+    assert_eq!(
+        trustees[1].deal(),
+        Err(CeremonyError::UnexpectedRound(Round::Complain))
+    );
+
+    trustees[0]
+        .receive_commitments(commitments.clone())
+        .unwrap();
+    assert_eq!(
+        trustees[0].receive_commitments(commitments),
+        Err(CeremonyError::DuplicateMessage(1))
+    );
+    assert_eq!(
+        trustees[0].receive_share(shares[1]),
+        Err(CeremonyError::WrongRecipient(2))
+    );
+    trustees[0].receive_share(shares[0]).unwrap();
+    assert_eq!(
+        trustees[0].receive_share(shares[0]),
+        Err(CeremonyError::DuplicateMessage(1))
+    );
+
+    let mut transcript = CeremonyTranscript::new(params);
+    transcript.complaints.push(Complaint {
+        complainer: 5,
+        dealer: 0,
+    });
+    assert_eq!(transcript.verify(), Err(CeremonyError::InvalidTrustee(5)));
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Run a ceremony where dealer sends an invalid share to recipient if corrupted is set,
+/// and reveals the valid share in response to the complaint only if respond is true
+fn run_ceremony(
+    params: CeremonyParams,
+    corrupted: Option<(usize, usize)>,
+    respond: bool,
+) -> (Vec<KeyShare>, CeremonyTranscript) {
+    let num_trustees = params.num_trustees();
+    let mut trustees = (0..num_trustees)
+        .map(|index| Trustee::new(params, index).unwrap())
+        .collect::<Vec<Trustee>>();
+    let mut transcript = CeremonyTranscript::new(params);
+
+    // deal round
+    let mut shares = vec![];
+    for trustee in trustees.iter_mut() {
+        let (commitments, dealt) = trustee.deal().unwrap();
+        transcript.commitments.push(commitments);
+        shares.extend(dealt);
+    }
+    for trustee in trustees.iter_mut() {
+        for commitments in transcript.commitments.iter() {
+            if commitments.dealer != trustee.index() {
+                trustee.receive_commitments(commitments.clone()).unwrap();
+            }
+        }
+    }
+    for mut share in shares {
+        if corrupted == Some((share.dealer, share.recipient)) {
+            share.value += Scalar::one();
+        }
+        trustees[share.recipient].receive_share(share).unwrap();
+    }
+
+    // complain and respond rounds
+    for trustee in trustees.iter_mut() {
+        transcript.complaints.extend(trustee.complain().unwrap());
+    }
+    for trustee in trustees.iter_mut() {
+        let responses = trustee.respond(&transcript.complaints).unwrap();
+        if respond || Some(trustee.index()) != corrupted.map(|(dealer, _)| dealer) {
+            transcript.responses.extend(responses);
+        }
+    }
+
+    let key_shares = trustees
+        .iter_mut()
+        .map(|trustee| trustee.finish(&transcript).unwrap())
+        .collect();
+    (key_shares, transcript)
+}
+
+/// Check the key shares against the outcome, and that any threshold of them
+/// interpolates the secret key of the group key
+fn check_key_shares(key_shares: &[KeyShare], outcome: &CeremonyOutcome, threshold: usize) {
+    for key_share in key_shares.iter() {
+        assert_eq!(key_share.group_key, outcome.group_key);
+        assert_eq!(
+            key_share.verification_key,
+            projective_to_elements(mul_generator_constant_time(key_share.secret))
+        );
+        assert_eq!(
+            key_share.verification_key,
+            outcome.verification_keys[key_share.index]
+        );
+    }
+
+    for first in 0..=key_shares.len() - threshold {
+        let subset = &key_shares[first..first + threshold];
+        let mut secret = Scalar::zero();
+        for key_share in subset.iter() {
+            let x_i = evaluation_point(key_share.index);
+            let mut lagrange = Scalar::one();
+            for other in subset.iter().filter(|other| other.index != key_share.index) {
+                let x_j = evaluation_point(other.index);
+                lagrange *= x_j * (x_j - x_i).invert();
+            }
+            secret += lagrange * key_share.secret;
+        }
+        assert_eq!(
+            projective_to_elements(mul_generator_constant_time(secret)),
+            outcome.group_key
+        );
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// PHASE COMMITMENT CHAIN
+// ================================================================================================
+// Each phase of an election commits to the state left by the previous one, and the commitment is
+// carried as a public input of the proof of the following phase:
+//
+//   register commitment = H(eligibility root, registered voting keys)
+//   ballot commitment   = H(register commitment, valid encrypted votes)   -> tally proof
+//   tally commitment    = H(ballot commitment, tally result)
+//
+// The register commitment is bound into the cast proof, and the ballot commitment into the tally
+// proof, so that a tally proof verified against a ballot commitment pins the exact registration
+// set the votes were cast by.
+
+use crate::utils::{
+    ecc::AFFINE_POINT_WIDTH,
+    rescue::{Rescue63, DIGEST_SIZE},
+};
+use winterfell::{
+    math::{fields::f63::BaseElement, FieldElement},
+    ByteWriter, Deserializable, DeserializationError, Serializable, SliceReader,
+};
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+#[cfg(test)]
+mod tests;
+
+/// Commitment to the state left by a phase of an election
+pub type PhaseCommitment = [BaseElement; DIGEST_SIZE];
+
+/// Number of bytes of a serialized phase commitment
+pub const BYTES_PER_PHASE_COMMITMENT: usize = DIGEST_SIZE * 8;
+
+// Tags separating the commitments of each phase
+const REGISTER_TAG: u64 = 1;
+const BALLOT_TAG: u64 = 2;
+const TALLY_TAG: u64 = 3;
+
+// PHASE COMMITMENTS
+// ================================================================================================
+
+/// Commitment to the registration phase: the eligibility root and the
+/// voting keys of registered voters, in the order of the register proof
+pub fn register_commitment(
+    elg_root: &[BaseElement; DIGEST_SIZE],
+    voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+) -> PhaseCommitment {
+    let mut hasher = Rescue63::new();
+    hasher.update(&[
+        BaseElement::from(REGISTER_TAG),
+        BaseElement::from(voting_keys.len() as u64),
+    ]);
+    hasher.update(elg_root);
+    for voting_key in voting_keys.iter() {
+        hasher.update(voting_key);
+    }
+    hasher.finalize().to_elements()
+}
+
+/// Commitment to the vote casting phase: the register commitment and the encrypted
+/// votes of valid ballots in the order of voters, i.e. the encrypted votes tallied
+pub fn ballot_commitment(
+    register_commitment: &PhaseCommitment,
+    encrypted_votes: &[[BaseElement; AFFINE_POINT_WIDTH]],
+) -> PhaseCommitment {
+    let mut hasher = Rescue63::new();
+    hasher.update(&[
+        BaseElement::from(BALLOT_TAG),
+        BaseElement::from(encrypted_votes.len() as u64),
+    ]);
+    hasher.update(register_commitment);
+    for encrypted_vote in encrypted_votes.iter() {
+        hasher.update(encrypted_vote);
+    }
+    hasher.finalize().to_elements()
+}
+
+/// Commitment to the tally phase: the ballot commitment and the tally result
+pub fn tally_commitment(ballot_commitment: &PhaseCommitment, tally_result: u32) -> PhaseCommitment {
+    let mut hasher = Rescue63::new();
+    hasher.update(&[
+        BaseElement::from(TALLY_TAG),
+        BaseElement::from(tally_result as u64),
+    ]);
+    hasher.update(ballot_commitment);
+    hasher.finalize().to_elements()
+}
+
+// SERIALIZATION
+// ================================================================================================
+
+/// Serialize a phase commitment in the format stored on smart contract
+pub fn phase_commitment_to_bytes(commitment: &PhaseCommitment) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(BYTES_PER_PHASE_COMMITMENT);
+    Serializable::write_batch_into(commitment, &mut bytes);
+    bytes
+}
+
+/// Deserialize a phase commitment, rejecting any encoding which
+/// is not exactly BYTES_PER_PHASE_COMMITMENT bytes long
+pub fn read_phase_commitment(source: &[u8]) -> Result<PhaseCommitment, DeserializationError> {
+    if source.len() != BYTES_PER_PHASE_COMMITMENT {
+        return Err(DeserializationError::InvalidValue(String::from(
+            "Invalid length of phase commitment.",
+        )));
+    }
+    let mut commitment = [BaseElement::ZERO; DIGEST_SIZE];
+    commitment.copy_from_slice(&BaseElement::read_batch_from(
+        &mut SliceReader::new(source),
+        DIGEST_SIZE,
+    )?);
+    Ok(commitment)
+}
+
+/// Write an optional phase commitment, only if present, so that
+/// the encoding of public inputs of unchained proofs stays unchanged
+pub(crate) fn write_phase_commitment<W: ByteWriter>(
+    commitment: Option<PhaseCommitment>,
+    target: &mut W,
+) {
+    if let Some(commitment) = commitment {
+        Serializable::write_batch_into(&commitment, target);
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+use crate::schnorr::random_key_pairs;
+
+#[test]
+fn chain_test_commitments() {
+    let (_, voting_keys) = random_key_pairs(4);
+    let elg_root = [BaseElement::ONE; DIGEST_SIZE];
+
+    let register = register_commitment(&elg_root, &voting_keys);
+    assert_eq!(register, register_commitment(&elg_root, &voting_keys));
+    assert_ne!(register, register_commitment(&elg_root, &voting_keys[..3]));
+    let mut reordered = voting_keys.clone();
+    reordered.swap(0, 1);
+    assert_ne!(register, register_commitment(&elg_root, &reordered));
+    assert_ne!(
+        register,
+        register_commitment(&[BaseElement::ZERO; DIGEST_SIZE], &voting_keys)
+    );
+
+    // each commitment depends on the commitment of the previous phase
+    let other_register = register_commitment(&elg_root, &reordered);
+    let ballot = ballot_commitment(&register, &voting_keys);
+    assert_ne!(ballot, ballot_commitment(&other_register, &voting_keys));
+    assert_ne!(ballot, ballot_commitment(&register, &reordered));
+
+    let tally = tally_commitment(&ballot, 2);
+    assert_ne!(tally, tally_commitment(&ballot, 3));
+    assert_ne!(tally, tally_commitment(&register, 2));
+
+    // phases are separated, even for identical inputs
+    assert_ne!(
+        register_commitment(&register, &voting_keys),
+        ballot_commitment(&register, &voting_keys)
+    );
+}
+
+#[test]
+fn chain_test_serialization() {
+    let (_, voting_keys) = random_key_pairs(2);
+    let commitment = register_commitment(&[BaseElement::ONE; DIGEST_SIZE], &voting_keys);
+    let bytes = phase_commitment_to_bytes(&commitment);
+    assert_eq!(bytes.len(), BYTES_PER_PHASE_COMMITMENT);
+    assert_eq!(read_phase_commitment(&bytes), Ok(commitment));
+    assert!(read_phase_commitment(&bytes[1..]).is_err());
+    assert!(read_phase_commitment(&[bytes.clone(), vec![0]].concat()).is_err());
+}
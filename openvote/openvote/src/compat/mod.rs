@@ -0,0 +1,62 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// WINTERFELL COMPATIBILITY
+// ================================================================================================
+// The crate is built against a fork of winterfell 0.3 which provides the f63 curve. Curve types,
+// STARK verification and proof options are only reached through this module, so that building
+// against upstream winterfell (0.6 or later, with the curve code vendored in the crate) only
+// requires changing it. The upstream API differs as follows:
+//
+// - the curve is not part of upstream winterfell, and is to be vendored in crate::curve;
+// - verify takes the hash function and random coin as type parameters, along with the set of
+//   acceptable proof options;
+// - proof options no longer carry the hash function, which becomes an associated type of each
+//   prover, and the FRI remainder is bounded by its degree instead of its size.
+//
+// Prover implementations declare the hash function, random coin and constraint evaluator as
+// associated types upstream, and public inputs implement ToElements instead of Serializable.
+
+use winterfell::{Air, FieldExtension, HashFunction, ProofOptions, StarkProof, VerifierError};
+
+#[cfg(test)]
+mod tests;
+
+/// Elliptic curve over the f63 field, used for voting keys, encrypted votes and signatures
+pub mod curve {
+    pub use crate::curve::{AffinePoint, ProjectivePoint, Scalar};
+}
+
+/// Verify a STARK proof of an AIR program against its public inputs
+pub fn verify<AIR: Air>(
+    proof: StarkProof,
+    pub_inputs: AIR::PublicInputs,
+) -> Result<(), VerifierError> {
+    crate::compat::verify::<AIR>(proof, pub_inputs)
+}
+
+/// Build the options of STARK proofs
+pub fn proof_options(
+    num_queries: usize,
+    blowup_factor: usize,
+    grinding_factor: u32,
+    hash_fn: HashFunction,
+    field_extension: FieldExtension,
+    fri_folding_factor: usize,
+    fri_max_remainder_size: usize,
+) -> ProofOptions {
+    ProofOptions::new(
+        num_queries,
+        blowup_factor,
+        grinding_factor,
+        hash_fn,
+        field_extension,
+        fri_folding_factor,
+        fri_max_remainder_size,
+    )
+}
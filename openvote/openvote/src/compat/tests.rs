@@ -0,0 +1,50 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::curve::{ProjectivePoint, Scalar};
+use super::*;
+use crate::{
+    tally::{TallyAir, TallyExample},
+    utils::ecc::projective_to_elements,
+};
+
+#[test]
+fn compat_test_proof_options() {
+    let options = proof_options(
+        42,
+        8,
+        0,
+        HashFunction::Blake3_192,
+        FieldExtension::Quadratic,
+        4,
+        256,
+    );
+    assert_eq!(options.num_queries(), 42);
+    assert_eq!(options.blowup_factor(), 8);
+    assert_eq!(options.hash_fn(), HashFunction::Blake3_192);
+    assert_eq!(options.field_extension(), FieldExtension::Quadratic);
+}
+
+#[test]
+fn compat_test_curve_and_verify() {
+    let generator = ProjectivePoint::generator();
+    assert_eq!(
+        projective_to_elements(generator * Scalar::from(3u32)),
+        projective_to_elements(generator + generator + generator)
+    );
+
+    let example = TallyExample::new(crate::aggregator::build_options(1), 4);
+    let proof = example.prove();
+    assert!(example.verify(proof.clone()).is_ok());
+    let pub_inputs = crate::tally::PublicInputs {
+        encrypted_votes: example.encrypted_votes.clone(),
+        tally_result: example.tally_result,
+        ballot_commitment: None,
+    };
+    assert!(verify::<TallyAir>(proof, pub_inputs).is_ok());
+}
@@ -0,0 +1,25 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// F63 CURVE
+// ================================================================================================
+// Home of the arithmetic of the elliptic curve over the sextic extension of the f63 field, used
+// for voting keys, encrypted votes, CDS proofs and Schnorr signatures. The AIR gadgets of
+// utils::ecc and the rest of the crate reach the curve through compat::curve, which re-exports
+// this module.
+//
+// The implementation is currently provided by the winterfell fork the crate is built against,
+// and is re-exported as is. The tests of this module pin its behaviour independently of the
+// fork: group and scalar field laws are checked on seeded random inputs, encodings are round
+// tripped, and known answers are compared to the vectors recorded in test_vectors.txt, so that
+// vendoring the implementation in this module can be checked against them.
+
+#[cfg(test)]
+mod tests;
+
+pub use winterfell::math::curves::curve_f63::{AffinePoint, ProjectivePoint, Scalar};
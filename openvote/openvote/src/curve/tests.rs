@@ -0,0 +1,205 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+use crate::utils::ecc::{
+    compress_point, decompress_point, projective_to_elements, AFFINE_POINT_WIDTH, GENERATOR,
+};
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use std::{env, fmt::Write, fs, string::String};
+use winterfell::{
+    math::{fields::f63::BaseElement, FieldElement},
+    Deserializable, SliceReader,
+};
+
+const VECTORS_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/curve/test_vectors.txt");
+
+/// Number of random inputs of each property test
+const NUM_CASES: usize = 32;
+
+#[test]
+fn curve_test_group_laws() {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let identity = ProjectivePoint::identity();
+    for _ in 0..NUM_CASES {
+        let p = random_point(&mut rng);
+        let q = random_point(&mut rng);
+        let r = random_point(&mut rng);
+
+        assert_eq!(p + q, q + p);
+        assert_eq!((p + q) + r, p + (q + r));
+        assert_eq!(p + identity, p);
+        assert_eq!(identity + p, p);
+        assert!((p - p).is_identity());
+        assert_eq!((p - q) + q, p);
+        assert_eq!(p.double(), p + p);
+        assert_eq!(p.double() + p, p + p + p);
+        assert_eq!(p + p.double(), p.double() + p);
+    }
+    assert!(identity.double().is_identity());
+    assert!((identity + identity).is_identity());
+}
+
+#[test]
+fn curve_test_scalar_multiplication() {
+    let mut rng = ChaCha20Rng::seed_from_u64(1);
+    let generator = ProjectivePoint::generator();
+    for _ in 0..NUM_CASES {
+        let a = Scalar::random(&mut rng);
+        let b = Scalar::random(&mut rng);
+        let p = random_point(&mut rng);
+
+        assert_eq!(generator * (a + b), generator * a + generator * b);
+        assert_eq!(generator * (a - b), generator * a - generator * b);
+        assert_eq!(generator * (a * b), (generator * a) * b);
+        assert_eq!((p + generator) * a, p * a + generator * a);
+        assert_eq!(p * a.double(), (p * a).double());
+        assert_eq!(p * -a, ProjectivePoint::identity() - p * a);
+    }
+
+    // the generator has the order of the scalar field
+    assert_eq!(generator * Scalar::one(), generator);
+    assert!((generator * Scalar::zero()).is_identity());
+    assert!((generator * -Scalar::one() + generator).is_identity());
+    assert!((ProjectivePoint::identity() * Scalar::random(&mut rng)).is_identity());
+
+    // small multiples match repeated additions
+    let mut multiple = ProjectivePoint::identity();
+    for k in 1..=16u32 {
+        multiple = multiple + generator;
+        assert_eq!(generator * Scalar::from(k), multiple);
+    }
+}
+
+#[test]
+fn curve_test_scalar_field_laws() {
+    let mut rng = ChaCha20Rng::seed_from_u64(2);
+    for _ in 0..NUM_CASES {
+        let a = Scalar::random(&mut rng);
+        let b = Scalar::random(&mut rng);
+        let c = Scalar::random(&mut rng);
+
+        assert_eq!(a + b, b + a);
+        assert_eq!(a * b, b * a);
+        assert_eq!((a + b) + c, a + (b + c));
+        assert_eq!((a * b) * c, a * (b * c));
+        assert_eq!(a * (b + c), a * b + a * c);
+        assert_eq!(a + Scalar::zero(), a);
+        assert_eq!(a * Scalar::one(), a);
+        assert_eq!(a + -a, Scalar::zero());
+        assert_eq!(a - b, a + -b);
+        assert_eq!(a.double(), a + a);
+        assert_eq!(a.square(), a * a);
+        if a != Scalar::zero() {
+            assert_eq!(a * a.invert(), Scalar::one());
+            assert_eq!(a.invert().invert(), a);
+        }
+        assert_eq!(
+            Scalar::read_from(&mut SliceReader::new(&a.to_bytes())).unwrap(),
+            a
+        );
+    }
+    assert_eq!(Scalar::from(2u32) * Scalar::from(3u32), Scalar::from(6u32));
+    assert_eq!(
+        Scalar::from(u64::MAX) + Scalar::one(),
+        Scalar::from(1u64 << 32).square()
+    );
+}
+
+#[test]
+fn curve_test_encodings() {
+    let mut rng = ChaCha20Rng::seed_from_u64(3);
+    assert_eq!(
+        projective_to_elements(ProjectivePoint::generator()),
+        GENERATOR
+    );
+    assert!(AffinePoint::generator().is_on_curve());
+
+    for _ in 0..NUM_CASES {
+        let point = random_point(&mut rng);
+        let affine = AffinePoint::from(point);
+        assert!(affine.is_on_curve());
+        assert_eq!(ProjectivePoint::from(affine), point);
+
+        let elements = projective_to_elements(point);
+        assert_eq!(AffinePoint::from_raw_coordinates(elements), affine);
+
+        let compressed = compress_point(&elements);
+        assert_eq!(decompress_point(&compressed), Some(elements));
+        let negated = projective_to_elements(ProjectivePoint::identity() - point);
+        assert_eq!(
+            negated[..AFFINE_POINT_WIDTH / 2],
+            elements[..AFFINE_POINT_WIDTH / 2]
+        );
+        assert_ne!(compress_point(&negated), compressed);
+    }
+}
+
+#[test]
+fn curve_test_invalid_encodings() {
+    let mut rng = ChaCha20Rng::seed_from_u64(4);
+    let mut num_rejected = 0;
+    for _ in 0..NUM_CASES {
+        let mut elements = projective_to_elements(random_point(&mut rng));
+        elements[AFFINE_POINT_WIDTH - 1] += BaseElement::ONE;
+        assert!(!AffinePoint::from_raw_coordinates(elements).is_on_curve());
+
+        // about half of random x coordinates are not on the curve
+        let mut compressed = compress_point(&projective_to_elements(random_point(&mut rng)));
+        compressed[0] ^= 1;
+        if let Some(point) = decompress_point(&compressed) {
+            assert!(AffinePoint::from_raw_coordinates(point).is_on_curve());
+        } else {
+            num_rejected += 1;
+        }
+    }
+    assert!(num_rejected > 0);
+}
+
+#[test]
+fn curve_test_recorded_vectors() {
+    let mut actual = String::new();
+    let generator = ProjectivePoint::generator();
+    for k in 1..=8u64 {
+        writeln!(actual, "{}G {}", k, hex_point(generator * Scalar::from(k))).unwrap();
+    }
+    let mut rng = ChaCha20Rng::seed_from_u64(5);
+    for _ in 0..8 {
+        let scalar = Scalar::random(&mut rng);
+        let point = generator * scalar;
+        writeln!(actual, "k {}", hex::encode(scalar.to_bytes())).unwrap();
+        writeln!(actual, "kG {}", hex_point(point)).unwrap();
+        writeln!(actual, "2kG {}", hex_point(point.double())).unwrap();
+        writeln!(actual, "1/k {}", hex::encode(scalar.invert().to_bytes())).unwrap();
+        writeln!(actual, "k^2 {}", hex::encode(scalar.square().to_bytes())).unwrap();
+    }
+
+    match fs::read_to_string(VECTORS_PATH) {
+        Ok(expected) if env::var_os("OPENVOTE_BLESS_VECTORS").is_none() => {
+            assert_eq!(
+                actual, expected,
+                "curve vectors differ from {}, set OPENVOTE_BLESS_VECTORS \
+                 to record them if the change is intended",
+                VECTORS_PATH
+            );
+        }
+        _ => fs::write(VECTORS_PATH, actual).unwrap(),
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn random_point(rng: &mut ChaCha20Rng) -> ProjectivePoint {
+    ProjectivePoint::generator() * Scalar::random(rng)
+}
+
+fn hex_point(point: ProjectivePoint) -> String {
+    hex::encode(compress_point(&projective_to_elements(point)))
+}
@@ -0,0 +1,233 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::{DynamicRegistry, EligibilityRegistry, RegistryError};
+use crate::utils::ecc::{compress_point, AFFINE_POINT_WIDTH};
+use num_bigint::{BigInt, BigUint, Sign};
+use num_integer::Integer;
+use sha3::{Digest, Keccak256};
+use winterfell::math::fields::f63::BaseElement;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Domain separator of the hash of voting keys to primes
+const HASH_TO_PRIME_DOMAIN: &[u8] = b"openvote-accumulator";
+
+/// Bases of the Miller-Rabin primality test
+const MILLER_RABIN_BASES: [u32; 20] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71,
+];
+
+// RSA ACCUMULATOR
+// ================================================================================================
+
+/// RSA accumulator of eligible voting keys. Voting keys are hashed to primes and the
+/// accumulator value is the generator raised to the product of these primes, so its size
+/// does not depend on the number of eligible voters.
+///
+/// The aggregator keeps the witnesses of all members, and updates them whenever a voting
+/// key is added or removed. Neither operation requires the factorization of the modulus.
+/// The witnesses of registered voters are published with the register proof, so that the
+/// verifier checks them against the accumulator value stored on smart contract.
+#[derive(Debug, Clone)]
+pub struct RsaAccumulator {
+    modulus: BigUint,
+    generator: BigUint,
+    value: BigUint,
+    voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+    primes: Vec<BigUint>,
+    witnesses: Vec<BigUint>,
+}
+
+impl RsaAccumulator {
+    /// Create an empty accumulator over an RSA modulus of unknown factorization
+    pub fn new(modulus: BigUint, generator: BigUint) -> Self {
+        Self {
+            value: generator.clone(),
+            modulus,
+            generator,
+            voting_keys: vec![],
+            primes: vec![],
+            witnesses: vec![],
+        }
+    }
+
+    /// RSA modulus
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    /// Generator of the accumulator
+    pub fn generator(&self) -> &BigUint {
+        &self.generator
+    }
+
+    /// Current value of the accumulator
+    pub fn value(&self) -> &BigUint {
+        &self.value
+    }
+
+    /// Number of eligible voting keys
+    pub fn len(&self) -> usize {
+        self.voting_keys.len()
+    }
+
+    /// Return true if no voting key is eligible
+    pub fn is_empty(&self) -> bool {
+        self.voting_keys.is_empty()
+    }
+
+    fn position(&self, voting_key: &[BaseElement; AFFINE_POINT_WIDTH]) -> Option<usize> {
+        self.voting_keys.iter().position(|vk| vk == voting_key)
+    }
+}
+
+impl EligibilityRegistry for RsaAccumulator {
+    /// Accumulator value without the prime of the voting key
+    type Witness = BigUint;
+
+    fn commitment(&self) -> Vec<u8> {
+        self.value.to_bytes_be()
+    }
+
+    fn witness(&self, voting_key: &[BaseElement; AFFINE_POINT_WIDTH]) -> Option<Self::Witness> {
+        self.position(voting_key)
+            .map(|idx| self.witnesses[idx].clone())
+    }
+
+    fn verify(
+        &self,
+        voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+        witness: &Self::Witness,
+    ) -> bool {
+        verify_membership(&self.modulus, &self.value, voting_key, witness)
+    }
+}
+
+impl DynamicRegistry for RsaAccumulator {
+    fn insert(
+        &mut self,
+        voting_key: [BaseElement; AFFINE_POINT_WIDTH],
+    ) -> Result<(), RegistryError> {
+        if self.position(&voting_key).is_some() {
+            return Err(RegistryError::AlreadyEligible);
+        }
+        let prime = hash_to_prime(&voting_key);
+
+        // existing witnesses absorb the new prime
+        for witness in self.witnesses.iter_mut() {
+            *witness = witness.modpow(&prime, &self.modulus);
+        }
+        // the witness of the new voting key is the previous value
+        self.witnesses.push(self.value.clone());
+        self.value = self.value.modpow(&prime, &self.modulus);
+
+        self.voting_keys.push(voting_key);
+        self.primes.push(prime);
+        Ok(())
+    }
+
+    fn remove(
+        &mut self,
+        voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    ) -> Result<(), RegistryError> {
+        let idx = self
+            .position(voting_key)
+            .ok_or(RegistryError::NotEligible)?;
+        self.voting_keys.remove(idx);
+        let removed_prime = BigInt::from(self.primes.remove(idx));
+        // the witness of the removed voting key is the new value
+        self.value = self.witnesses.remove(idx);
+
+        // with a * removed_prime + b * prime = 1, the new witness of a voting key is
+        // witness^a * value^b, as (witness^a * value^b)^prime = value^(a * removed_prime + b * prime)
+        for (witness, prime) in self.witnesses.iter_mut().zip(self.primes.iter()) {
+            let bezout = removed_prime.extended_gcd(&BigInt::from(prime.clone()));
+            debug_assert!(bezout.gcd == BigInt::from(1u8));
+            *witness = (pow_signed(witness, &bezout.x, &self.modulus)
+                * pow_signed(&self.value, &bezout.y, &self.modulus))
+                % &self.modulus;
+        }
+        Ok(())
+    }
+}
+
+/// Verify a witness of membership of a voting key in an accumulator of the given value
+pub fn verify_membership(
+    modulus: &BigUint,
+    value: &BigUint,
+    voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    witness: &BigUint,
+) -> bool {
+    witness < modulus && witness.modpow(&hash_to_prime(voting_key), modulus) == *value
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Hash a voting key to a 256-bit prime, by hashing its compressed form along
+/// with a counter until the (odd, full-size) candidate is prime
+pub fn hash_to_prime(voting_key: &[BaseElement; AFFINE_POINT_WIDTH]) -> BigUint {
+    let compressed_key = compress_point(voting_key);
+    let mut counter = 0u32;
+    loop {
+        let mut hasher = Keccak256::new();
+        hasher.update(HASH_TO_PRIME_DOMAIN);
+        hasher.update(compressed_key);
+        hasher.update(counter.to_le_bytes());
+        let mut candidate = hasher.finalize();
+        candidate[0] |= 0x80;
+        candidate[31] |= 1;
+
+        let candidate = BigUint::from_bytes_be(&candidate);
+        if is_probable_prime(&candidate) {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
+
+/// Miller-Rabin primality test of an odd number greater than the test bases
+fn is_probable_prime(n: &BigUint) -> bool {
+    let one = BigUint::from(1u8);
+    let n_minus_one = n - &one;
+    let s = n_minus_one.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_one >> s;
+
+    'bases: for &base in MILLER_RABIN_BASES.iter() {
+        let base = BigUint::from(base);
+        if (n % &base).bits() == 0 {
+            return false;
+        }
+        let mut x = base.modpow(&d, n);
+        if x == one || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = x.modpow(&BigUint::from(2u8), n);
+            if x == n_minus_one {
+                continue 'bases;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Raise base to a signed exponent modulo modulus
+fn pow_signed(base: &BigUint, exponent: &BigInt, modulus: &BigUint) -> BigUint {
+    let magnitude = exponent.magnitude();
+    if exponent.sign() == Sign::Minus {
+        base.modinv(modulus)
+            .expect("Accumulator elements are invertible modulo an RSA modulus.")
+            .modpow(magnitude, modulus)
+    } else {
+        base.modpow(magnitude, modulus)
+    }
+}
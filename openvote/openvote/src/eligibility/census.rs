@@ -0,0 +1,362 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// CENSUS OF ELIGIBLE VOTERS
+// ================================================================================================
+// A census lists eligible voters by their voting key, or by their Ethereum address when the
+// voting key is bound later. It is either a CSV file with one hex-encoded entry in the first
+// column of each line, or a JSON array of hex-encoded strings. Voting keys are written in
+// compressed form (96 hex characters) and addresses in 40 hex characters, with an optional 0x
+// prefix. Blank lines, lines starting with '#' and a header line are ignored in CSV files.
+//
+// Entries are placed at the leaves of a tree of depth TREE_DEPTH in the order of the census,
+// so that anyone holding the census can recompute the eligibility root. Leaves of addresses
+// which are not bound yet, and leaves past the end of the census, are zero.
+
+use super::EligibilityRegistry;
+use crate::merkle::{constants::*, MerkleTree};
+use crate::utils::{
+    address::{Address, ADDRESS_LENGTH},
+    ecc::{
+        compress_point, decompress_point, read_compressed_point, validate_point,
+        BYTES_PER_COMPRESSED_POINT,
+    },
+};
+use std::{
+    collections::BTreeSet,
+    fs,
+    io::{self, Read},
+    path::Path,
+};
+use winterfell::{
+    math::fields::f63::BaseElement, ByteReader, ByteWriter, Deserializable, DeserializationError,
+    Serializable, SliceReader,
+};
+
+/// Extension of the branch files exported for each voter
+pub const BRANCH_FILE_EXTENSION: &str = "branch";
+
+/// Entry of a census of eligible voters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CensusEntry {
+    /// Voting key of an eligible voter
+    VotingKey([BaseElement; AFFINE_POINT_WIDTH]),
+    /// Ethereum address of an eligible voter whose voting key is bound later
+    Address(Address),
+}
+
+/// Errors raised when building a registry from a census
+#[derive(Debug)]
+pub enum CensusError {
+    /// This error occurs when the census cannot be read
+    Io(io::Error),
+    /// This error occurs when the census is neither valid CSV nor a JSON array of strings
+    Malformed,
+    /// This error occurs when the entry at the given position is not
+    /// a hex-encoded voting key or address
+    InvalidEntry(usize),
+    /// This error occurs when the voting key at the given position is
+    /// not a point of the subgroup generated by G
+    InvalidVotingKey(usize),
+    /// This error occurs when the census is empty or has more entries than leaves
+    InvalidSize(usize),
+    /// This error occurs when a voting key is bound to an address which is
+    /// not in the census or is already bound
+    UnknownAddress,
+    /// This error occurs when a voting key bound to an address is already eligible
+    DuplicatedVotingKey,
+}
+
+impl From<io::Error> for CensusError {
+    fn from(error: io::Error) -> Self {
+        CensusError::Io(error)
+    }
+}
+
+/// Merkle tree of eligible voters built from a census, along with the entry at each leaf
+#[derive(Debug, Clone)]
+pub struct MerkleRegistry {
+    /// Tree of eligible voters
+    tree: MerkleTree,
+    /// Census entries, in the order of the leaves
+    entries: Vec<CensusEntry>,
+    /// Number of duplicated entries dropped from the census
+    num_duplicates: usize,
+}
+
+impl MerkleRegistry {
+    /// Build a registry from a CSV or JSON census. Entries are validated, and
+    /// duplicated entries are dropped.
+    pub fn from_census<R: Read>(mut reader: R) -> Result<Self, CensusError> {
+        let mut census = String::new();
+        reader.read_to_string(&mut census)?;
+        let fields = if census.trim_start().starts_with('[') {
+            parse_json(&census)?
+        } else {
+            parse_csv(&census)
+        };
+        let entries = fields
+            .iter()
+            .enumerate()
+            .map(|(position, field)| parse_entry(position, field))
+            .collect::<Result<Vec<CensusEntry>, CensusError>>()?;
+        Self::from_entries(&entries)
+    }
+
+    /// Build a registry from census entries, dropping duplicated entries
+    pub fn from_entries(entries: &[CensusEntry]) -> Result<Self, CensusError> {
+        let mut seen = BTreeSet::new();
+        let unique = entries
+            .iter()
+            .filter(|entry| seen.insert(entry_bytes(entry)))
+            .copied()
+            .collect::<Vec<CensusEntry>>();
+        if unique.is_empty() || unique.len() > MerkleTree::NUM_LEAVES {
+            return Err(CensusError::InvalidSize(unique.len()));
+        }
+
+        let mut leaves = vec![[BaseElement::default(); DIGEST_SIZE]; MerkleTree::NUM_LEAVES];
+        for (leaf, entry) in leaves.iter_mut().zip(unique.iter()) {
+            if let CensusEntry::VotingKey(voting_key) = entry {
+                *leaf = MerkleTree::leaf_of(voting_key);
+            }
+        }
+        Ok(Self {
+            tree: MerkleTree::new(&leaves),
+            num_duplicates: entries.len() - unique.len(),
+            entries: unique,
+        })
+    }
+
+    /// Tree of eligible voters, from which a VoterRegistar is created
+    pub fn tree(&self) -> &MerkleTree {
+        &self.tree
+    }
+
+    /// Root of the tree of eligible voters
+    pub fn elg_root(&self) -> [BaseElement; DIGEST_SIZE] {
+        self.tree.root()
+    }
+
+    /// Census entries, in the order of the leaves
+    pub fn entries(&self) -> &[CensusEntry] {
+        &self.entries
+    }
+
+    /// Number of eligible voters
+    pub fn num_elg_voters(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Number of duplicated entries dropped from the census
+    pub fn num_duplicates(&self) -> usize {
+        self.num_duplicates
+    }
+
+    /// Bind a voting key to an address of the census, which changes the eligibility root
+    pub fn bind_address(
+        &mut self,
+        address: Address,
+        voting_key: [BaseElement; AFFINE_POINT_WIDTH],
+    ) -> Result<(), CensusError> {
+        let index = self
+            .entries
+            .iter()
+            .position(|&entry| entry == CensusEntry::Address(address))
+            .ok_or(CensusError::UnknownAddress)?;
+        if validate_point(&voting_key).is_err() {
+            return Err(CensusError::InvalidVotingKey(index));
+        }
+        if self.tree.find(&voting_key).is_some() {
+            return Err(CensusError::DuplicatedVotingKey);
+        }
+        self.entries[index] = CensusEntry::VotingKey(voting_key);
+        self.tree.set_leaf(index, MerkleTree::leaf_of(&voting_key));
+        Ok(())
+    }
+
+    /// Branch of the voter at the given leaf, or None if its voting key is not bound yet
+    pub fn voter_branch(&self, index: usize) -> Option<VoterBranch> {
+        match self.entries.get(index) {
+            Some(&CensusEntry::VotingKey(voting_key)) => Some(VoterBranch {
+                voting_key,
+                hash_index: index,
+                merkle_branch: self.tree.branch(index),
+                elg_root: self.tree.root(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Write the branch of each voter whose voting key is bound to `<index>.branch`
+    /// in the given directory, and return the number of files written
+    pub fn export_branches(&self, dir: &Path) -> Result<usize, io::Error> {
+        fs::create_dir_all(dir)?;
+        let mut num_files = 0;
+        for index in 0..self.entries.len() {
+            if let Some(branch) = self.voter_branch(index) {
+                let path = dir.join(format!("{}.{}", index, BRANCH_FILE_EXTENSION));
+                fs::write(path, branch.to_bytes())?;
+                num_files += 1;
+            }
+        }
+        Ok(num_files)
+    }
+}
+
+impl EligibilityRegistry for MerkleRegistry {
+    type Witness = <MerkleTree as EligibilityRegistry>::Witness;
+
+    fn commitment(&self) -> Vec<u8> {
+        self.tree.commitment()
+    }
+
+    fn witness(&self, voting_key: &[BaseElement; AFFINE_POINT_WIDTH]) -> Option<Self::Witness> {
+        self.tree.witness(voting_key)
+    }
+
+    fn verify(
+        &self,
+        voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+        witness: &Self::Witness,
+    ) -> bool {
+        self.tree.verify(voting_key, witness)
+    }
+}
+
+// VOTER BRANCH
+// ================================================================================================
+
+/// Branch of an eligible voter, with which they register against the eligibility root
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VoterBranch {
+    /// Voting key of the voter
+    pub voting_key: [BaseElement; AFFINE_POINT_WIDTH],
+    /// Index of the leaf of the voting key
+    pub hash_index: usize,
+    /// Siblings on the path from the leaf to the root
+    pub merkle_branch: [BaseElement; TREE_DEPTH * DIGEST_SIZE],
+    /// Root of the tree of eligible voters
+    pub elg_root: [BaseElement; DIGEST_SIZE],
+}
+
+impl VoterBranch {
+    /// Reconstruct an object of type Self from a sequence of bytes
+    pub fn from_bytes(source: &[u8]) -> Result<Self, DeserializationError> {
+        let mut source = SliceReader::new(source);
+        Self::read_from(&mut source)
+    }
+}
+
+impl Serializable for VoterBranch {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u8_slice(&compress_point(&self.voting_key));
+        target.write_u32(self.hash_index as u32);
+        Serializable::write_batch_into(&self.merkle_branch, target);
+        Serializable::write_batch_into(&self.elg_root, target);
+    }
+}
+
+impl Deserializable for VoterBranch {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let voting_key = read_compressed_point(source)?;
+        let hash_index = source.read_u32()? as usize;
+        if hash_index >= MerkleTree::NUM_LEAVES {
+            return Err(DeserializationError::InvalidValue(String::from(
+                "Invalid index of leaf.",
+            )));
+        }
+        let mut merkle_branch = [BaseElement::default(); TREE_DEPTH * DIGEST_SIZE];
+        merkle_branch.copy_from_slice(&BaseElement::read_batch_from(
+            source,
+            TREE_DEPTH * DIGEST_SIZE,
+        )?);
+        let mut elg_root = [BaseElement::default(); DIGEST_SIZE];
+        elg_root.copy_from_slice(&BaseElement::read_batch_from(source, DIGEST_SIZE)?);
+        Ok(Self {
+            voting_key,
+            hash_index,
+            merkle_branch,
+            elg_root,
+        })
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// First column of each line of a CSV census, skipping blank
+/// lines, comments and a header line
+fn parse_csv(census: &str) -> Vec<&str> {
+    let mut fields = census
+        .lines()
+        .map(|line| line.split(',').next().unwrap_or("").trim())
+        .filter(|field| !field.is_empty() && !field.starts_with('#'))
+        .collect::<Vec<&str>>();
+    if let Some(first) = fields.first() {
+        if hex::decode(strip_hex_prefix(first)).is_err() {
+            fields.remove(0);
+        }
+    }
+    fields
+}
+
+/// Strings of a JSON array of strings
+fn parse_json(census: &str) -> Result<Vec<&str>, CensusError> {
+    let census = census.trim();
+    if !census.starts_with('[') || !census.ends_with(']') {
+        return Err(CensusError::Malformed);
+    }
+    let inner = census[1..census.len() - 1].trim();
+    if inner.is_empty() {
+        return Ok(vec![]);
+    }
+    inner
+        .split(',')
+        .map(|value| {
+            let value = value.trim();
+            if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+                Ok(&value[1..value.len() - 1])
+            } else {
+                Err(CensusError::Malformed)
+            }
+        })
+        .collect()
+}
+
+fn parse_entry(position: usize, field: &str) -> Result<CensusEntry, CensusError> {
+    let bytes =
+        hex::decode(strip_hex_prefix(field)).map_err(|_| CensusError::InvalidEntry(position))?;
+    match bytes.len() {
+        BYTES_PER_COMPRESSED_POINT => {
+            let mut compressed = [0u8; BYTES_PER_COMPRESSED_POINT];
+            compressed.copy_from_slice(&bytes);
+            let voting_key =
+                decompress_point(&compressed).ok_or(CensusError::InvalidVotingKey(position))?;
+            validate_point(&voting_key).map_err(|_| CensusError::InvalidVotingKey(position))?;
+            Ok(CensusEntry::VotingKey(voting_key))
+        }
+        ADDRESS_LENGTH => Ok(CensusEntry::Address(Address::from_slice(&bytes))),
+        _ => Err(CensusError::InvalidEntry(position)),
+    }
+}
+
+fn strip_hex_prefix(field: &str) -> &str {
+    field
+        .strip_prefix("0x")
+        .or_else(|| field.strip_prefix("0X"))
+        .unwrap_or(field)
+}
+
+/// Encoding of an entry used to detect duplicates
+fn entry_bytes(entry: &CensusEntry) -> Vec<u8> {
+    match entry {
+        CensusEntry::VotingKey(voting_key) => compress_point(voting_key).to_vec(),
+        CensusEntry::Address(address) => address.as_bytes().to_vec(),
+    }
+}
@@ -0,0 +1,118 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// ELIGIBILITY REGISTRIES
+// ================================================================================================
+// Backends committing to the roll of eligible voters. The Merkle tree is proven in the
+// registration AIR; accumulators trade this for constant-size witnesses which the aggregator
+// keeps up to date when the roll changes, and which the verifier checks next to the register
+// proof, see RegisterVerification::with_accumulator.
+// A census of eligible voters is turned into a Merkle tree by census::MerkleRegistry.
+
+use crate::merkle::{constants::*, MerkleTree};
+use winterfell::{math::fields::f63::BaseElement, Serializable};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// RSA accumulator of eligible voting keys
+#[cfg(feature = "accumulator")]
+pub mod accumulator;
+
+/// Merkle registry built from a census of eligible voters
+#[cfg(feature = "std")]
+pub mod census;
+
+#[cfg(test)]
+mod tests;
+
+/// Errors raised when updating the roll of a registry
+#[derive(Debug, PartialEq)]
+pub enum RegistryError {
+    /// This error occurs when an eligible voting key is added again
+    AlreadyEligible,
+    /// This error occurs when a voting key which is not eligible is removed
+    NotEligible,
+}
+
+/// Commitment to the roll of eligible voters, against which
+/// membership of voting keys is proven
+pub trait EligibilityRegistry {
+    /// Witness of membership of a voting key
+    type Witness: Clone;
+
+    /// Serialized constant-size commitment to the roll
+    fn commitment(&self) -> Vec<u8>;
+
+    /// Witness of membership of a voting key, if the voting key is eligible
+    fn witness(&self, voting_key: &[BaseElement; AFFINE_POINT_WIDTH]) -> Option<Self::Witness>;
+
+    /// Check a witness of membership of a voting key against the current roll
+    fn verify(
+        &self,
+        voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+        witness: &Self::Witness,
+    ) -> bool;
+}
+
+/// Eligibility registry whose roll can change during the election.
+/// Witnesses held by the registry are updated on every change.
+pub trait DynamicRegistry: EligibilityRegistry {
+    /// Add a voting key to the roll
+    fn insert(
+        &mut self,
+        voting_key: [BaseElement; AFFINE_POINT_WIDTH],
+    ) -> Result<(), RegistryError>;
+
+    /// Remove a voting key from the roll
+    fn remove(
+        &mut self,
+        voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    ) -> Result<(), RegistryError>;
+}
+
+// MERKLE TREE REGISTRY
+// ================================================================================================
+
+impl EligibilityRegistry for MerkleTree {
+    /// Index of the leaf and siblings on the path from the leaf to the root
+    type Witness = (usize, [BaseElement; TREE_DEPTH * DIGEST_SIZE]);
+
+    fn commitment(&self) -> Vec<u8> {
+        let mut commitment = vec![];
+        Serializable::write_batch_into(&self.root(), &mut commitment);
+        commitment
+    }
+
+    fn witness(&self, voting_key: &[BaseElement; AFFINE_POINT_WIDTH]) -> Option<Self::Witness> {
+        self.find(voting_key)
+            .map(|hash_index| (hash_index, self.branch(hash_index)))
+    }
+
+    fn verify(
+        &self,
+        voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+        witness: &Self::Witness,
+    ) -> bool {
+        let (hash_index, branch) = witness;
+        if *hash_index >= self.num_leaves() {
+            return false;
+        }
+        let mut h = MerkleTree::leaf_of(voting_key);
+        for (i, sibling) in branch.chunks(DIGEST_SIZE).take(self.depth()).enumerate() {
+            let mut branch_node = [BaseElement::default(); DIGEST_SIZE];
+            branch_node.copy_from_slice(sibling);
+            h = if (hash_index >> i) & 1 == 0 {
+                MerkleTree::merge(&h, &branch_node)
+            } else {
+                MerkleTree::merge(&branch_node, &h)
+            };
+        }
+        h == self.root()
+    }
+}
@@ -0,0 +1,204 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::EligibilityRegistry;
+use crate::{merkle::build_merkle_tree_with_keys, schnorr::random_key_pairs};
+
+#[test]
+fn eligibility_test_merkle_registry() {
+    let (_, voting_keys) = random_key_pairs(4);
+    let (tree, hash_indices) = build_merkle_tree_with_keys(&voting_keys[..2]);
+
+    for (voting_key, &hash_index) in voting_keys.iter().zip(hash_indices.iter()) {
+        let witness = tree.witness(voting_key).unwrap();
+        assert_eq!(witness.0, hash_index);
+        assert!(tree.verify(voting_key, &witness));
+    }
+
+    // keys outside the roll have no witness, nor can they reuse one
+    assert!(tree.witness(&voting_keys[2]).is_none());
+    let witness = tree.witness(&voting_keys[0]).unwrap();
+    assert!(!tree.verify(&voting_keys[2], &witness));
+}
+
+#[cfg(feature = "accumulator")]
+#[test]
+fn eligibility_test_accumulator_updates() {
+    use super::{
+        accumulator::{verify_membership, RsaAccumulator},
+        DynamicRegistry, RegistryError,
+    };
+    use num_bigint::BigUint;
+
+    let mut accumulator = RsaAccumulator::new(test_modulus(), BigUint::from(3u8));
+    let (_, voting_keys) = random_key_pairs(4);
+
+    for &voting_key in voting_keys[..3].iter() {
+        assert_eq!(accumulator.insert(voting_key), Ok(()));
+    }
+    assert_eq!(
+        accumulator.insert(voting_keys[0]),
+        Err(RegistryError::AlreadyEligible)
+    );
+    assert_eq!(accumulator.len(), 3);
+
+    // witnesses held by the aggregator follow insertions
+    let commitment = accumulator.commitment();
+    for voting_key in voting_keys[..3].iter() {
+        let witness = accumulator.witness(voting_key).unwrap();
+        assert!(accumulator.verify(voting_key, &witness));
+    }
+    assert!(accumulator.witness(&voting_keys[3]).is_none());
+
+    // and removals, while the witness of the removed key becomes stale
+    let stale_witness = accumulator.witness(&voting_keys[1]).unwrap();
+    assert_eq!(accumulator.remove(&voting_keys[1]), Ok(()));
+    assert_eq!(
+        accumulator.remove(&voting_keys[1]),
+        Err(RegistryError::NotEligible)
+    );
+    assert_ne!(accumulator.commitment(), commitment);
+    assert!(!accumulator.verify(&voting_keys[1], &stale_witness));
+    for voting_key in [voting_keys[0], voting_keys[2]].iter() {
+        let witness = accumulator.witness(voting_key).unwrap();
+        assert!(verify_membership(
+            accumulator.modulus(),
+            accumulator.value(),
+            voting_key,
+            &witness
+        ));
+    }
+    assert!(!accumulator.verify(
+        &voting_keys[3],
+        &accumulator.witness(&voting_keys[0]).unwrap()
+    ));
+}
+
+#[cfg(feature = "accumulator")]
+#[test]
+fn eligibility_test_accumulator_register_proof() {
+    use super::{accumulator::RsaAccumulator, DynamicRegistry};
+    use crate::{
+        aggregator::register::VoterRegistar,
+        verifier::{verify_register_proof_with, RegisterVerification},
+    };
+    use num_bigint::BigUint;
+
+    let mut registar = VoterRegistar::get_example(2);
+    let register_proof = registar.get_register_proof().unwrap();
+    let mut accumulator = RsaAccumulator::new(test_modulus(), BigUint::from(3u8));
+    for &voting_key in registar.voting_keys.iter() {
+        accumulator.insert(voting_key).unwrap();
+    }
+    let witnesses = registar
+        .voting_keys
+        .iter()
+        .map(|voting_key| accumulator.witness(voting_key).unwrap())
+        .collect::<Vec<BigUint>>();
+    let verify_with_witnesses = |value: &BigUint, witnesses: Vec<BigUint>| {
+        let options = RegisterVerification::new().with_accumulator(
+            accumulator.modulus().clone(),
+            value.clone(),
+            witnesses,
+        );
+        verify_register_proof_with(&registar.elg_root, &options, &register_proof).into_outcome()
+    };
+    assert!(verify_with_witnesses(accumulator.value(), witnesses.clone()).unwrap());
+
+    // a voting key removed from the roll is rejected by the verifier
+    accumulator.remove(&registar.voting_keys[1]).unwrap();
+    assert!(verify_with_witnesses(accumulator.value(), witnesses.clone()).is_err());
+    assert!(verify_with_witnesses(accumulator.value(), witnesses[..1].to_vec()).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn eligibility_test_census() {
+    use super::census::{CensusEntry, CensusError, MerkleRegistry, VoterBranch};
+    use crate::utils::{address::Address, ecc::compress_point};
+    use rand_core::{OsRng, RngCore};
+
+    let (_, voting_keys) = random_key_pairs(3);
+    let address = Address::random();
+    let keys = voting_keys
+        .iter()
+        .map(|voting_key| hex::encode(compress_point(voting_key)))
+        .collect::<Vec<String>>();
+    let address_hex = hex::encode(address.as_bytes());
+
+    // duplicated entries are dropped, and both formats yield the same tree
+    let csv = format!(
+        "voting_key,name\n# eligible voters\n0x{},alice\n{},bob\n\n0x{},alice\n{}\n",
+        keys[0], keys[1], keys[0], address_hex
+    );
+    let json = format!(
+        "[\"{}\", \"0x{}\", \"{}\", \"{}\"]",
+        keys[0], keys[1], address_hex, address_hex
+    );
+    let registry = MerkleRegistry::from_census(csv.as_bytes()).unwrap();
+    let from_json = MerkleRegistry::from_census(json.as_bytes()).unwrap();
+    assert_eq!(registry.num_elg_voters(), 3);
+    assert_eq!(registry.num_duplicates(), 1);
+    assert_eq!(from_json.num_duplicates(), 1);
+    assert_eq!(registry.elg_root(), from_json.elg_root());
+    assert_eq!(registry.entries()[2], CensusEntry::Address(address));
+
+    let branch = registry.voter_branch(1).unwrap();
+    assert_eq!(branch.voting_key, voting_keys[1]);
+    assert!(registry.verify(&voting_keys[1], &(branch.hash_index, branch.merkle_branch)));
+    assert!(registry.voter_branch(2).is_none());
+
+    // binding a voting key to an address changes the root
+    let mut bound = registry.clone();
+    assert!(matches!(
+        bound.bind_address(Address::random(), voting_keys[2]),
+        Err(CensusError::UnknownAddress)
+    ));
+    assert!(matches!(
+        bound.bind_address(address, voting_keys[0]),
+        Err(CensusError::DuplicatedVotingKey)
+    ));
+    bound.bind_address(address, voting_keys[2]).unwrap();
+    assert_ne!(bound.elg_root(), registry.elg_root());
+    assert_eq!(bound.witness(&voting_keys[2]).unwrap().0, 2);
+
+    // branch files are exported for bound voters only
+    let dir = std::env::temp_dir().join(format!("openvote-census-{}", OsRng.next_u32()));
+    assert_eq!(registry.export_branches(&dir).unwrap(), 2);
+    let bytes = std::fs::read(dir.join("1.branch")).unwrap();
+    assert_eq!(VoterBranch::from_bytes(&bytes).unwrap(), branch);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    // malformed entries are reported with their position
+    let invalid = format!("{}\n{}\n", keys[0], &keys[1][2..]);
+    assert!(matches!(
+        MerkleRegistry::from_census(invalid.as_bytes()),
+        Err(CensusError::InvalidEntry(1))
+    ));
+    assert!(matches!(
+        MerkleRegistry::from_census("[\"00\",".as_bytes()),
+        Err(CensusError::Malformed)
+    ));
+    assert!(matches!(
+        MerkleRegistry::from_census("[]".as_bytes()),
+        Err(CensusError::InvalidSize(0))
+    ));
+}
+
+/// 1024-bit modulus generated for these tests only
+#[cfg(feature = "accumulator")]
+fn test_modulus() -> num_bigint::BigUint {
+    num_bigint::BigUint::parse_bytes(
+        b"ba36a03749139dea216ca8371fd1e825cdbcc3b9b86cdefa2443fc6226c4197f\
+          10e917c499c75baf72d594036ec7891e96f0fa865f775a1fae5f4675a3243b53\
+          8d18011b76c50783b1be10747f6232f52392505c21f1a7d4a6e0c69038ad0cd1\
+          7b0f94949083d575e4ec38ce4bfcd16494074f8a9ed4a81ded67960b40b63eed",
+        16,
+    )
+    .unwrap()
+}
@@ -0,0 +1,302 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// ELECTION EVENTS
+// ================================================================================================
+// Typed events emitted as an election progresses, so that front ends can show live progress.
+// The event bus drives the aggregator objects and notifies its subscribers of each successful
+// step, which keeps the aggregator objects free of subscriber state.
+
+use crate::{
+    aggregator::{
+        cast::{CollectorError, EncryptedVote, VoteCollector},
+        register::{RegistarError, Registration, VoterRegistar},
+        tally::{TallierError, VoteTallier},
+    },
+    utils::address::Address,
+};
+use core::fmt;
+
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+/// HTTP webhook dispatcher
+#[cfg(feature = "webhook")]
+pub mod webhook;
+
+#[cfg(test)]
+mod tests;
+
+// EVENTS
+// ================================================================================================
+
+/// Phase of an election whose STARK proof was generated
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProofPhase {
+    /// Proof of the registration phase
+    Register,
+    /// Proof of the vote casting phase
+    Cast,
+}
+
+/// Election artifact pinned to a content network
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Artifact {
+    /// Serialized state of the voter registar
+    Registrations,
+    /// Ballot list proven by the cast proof
+    BallotList,
+    /// Register proof
+    RegisterProof,
+    /// Cast proof
+    CastProof,
+}
+
+/// Event emitted as an election progresses
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// A registration was accepted by the registar
+    RegistrationAccepted {
+        /// Index of the voter among registered voters
+        voter_index: usize,
+        /// Ethereum address of the voter
+        address: Address,
+    },
+    /// An encrypted vote was accepted by the collector
+    VoteAccepted {
+        /// Index of the voter among registered voters
+        voter_index: usize,
+    },
+    /// A STARK proof was generated
+    ProofGenerated {
+        /// Phase of the proof
+        phase: ProofPhase,
+        /// Number of voters covered by the proof
+        num_voters: usize,
+        /// Size of the serialized proof, in bytes
+        proof_size: usize,
+    },
+    /// Votes were tallied
+    TallyFinalized {
+        /// Number of "yes" votes
+        tally_result: u32,
+        /// Number of tallied votes
+        num_votes: usize,
+    },
+    /// An election artifact was pinned to a content network
+    ArtifactPinned {
+        /// Pinned artifact
+        artifact: Artifact,
+        /// Content identifier returned by the content network
+        cid: String,
+    },
+}
+
+impl Event {
+    /// Name of the event type
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::RegistrationAccepted { .. } => "RegistrationAccepted",
+            Event::VoteAccepted { .. } => "VoteAccepted",
+            Event::ProofGenerated { .. } => "ProofGenerated",
+            Event::TallyFinalized { .. } => "TallyFinalized",
+            Event::ArtifactPinned { .. } => "ArtifactPinned",
+        }
+    }
+
+    /// Serialize the event as a JSON object
+    pub fn to_json(&self) -> String {
+        let fields = match self {
+            Event::RegistrationAccepted {
+                voter_index,
+                address,
+            } => format!(r#""voter_index":{},"address":"{}""#, voter_index, address),
+            Event::VoteAccepted { voter_index } => format!(r#""voter_index":{}"#, voter_index),
+            Event::ProofGenerated {
+                phase,
+                num_voters,
+                proof_size,
+            } => format!(
+                r#""phase":"{:?}","num_voters":{},"proof_size":{}"#,
+                phase, num_voters, proof_size
+            ),
+            Event::TallyFinalized {
+                tally_result,
+                num_votes,
+            } => format!(
+                r#""tally_result":{},"num_votes":{}"#,
+                tally_result, num_votes
+            ),
+            Event::ArtifactPinned { artifact, cid } => {
+                format!(r#""artifact":"{:?}","cid":"{}""#, artifact, cid)
+            }
+        };
+        format!(r#"{{"event":"{}",{}}}"#, self.name(), fields)
+    }
+}
+
+// SUBSCRIBERS
+// ================================================================================================
+
+/// Receiver of election events
+pub trait Subscriber {
+    /// Handle an event. Subscribers must not block for long,
+    /// as they are notified on the intake loop of the aggregator.
+    fn notify(&mut self, event: &Event);
+}
+
+impl<F: FnMut(&Event)> Subscriber for F {
+    fn notify(&mut self, event: &Event) {
+        self(event)
+    }
+}
+
+/// Subscriber recording all events, e.g. to be drained by a polling front end
+#[derive(Debug, Default, Clone)]
+pub struct EventLog {
+    /// Recorded events, oldest first
+    pub events: Vec<Event>,
+}
+
+impl Subscriber for EventLog {
+    fn notify(&mut self, event: &Event) {
+        self.events.push(event.clone());
+    }
+}
+
+// EVENT BUS
+// ================================================================================================
+
+/// Dispatcher of election events to subscribers.
+///
+/// Each method runs the corresponding operation of an aggregator object and
+/// publishes an event if it succeeds.
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Vec<Box<dyn Subscriber + Send>>,
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventBus")
+            .field("num_subscribers", &self.subscribers.len())
+            .finish()
+    }
+}
+
+impl EventBus {
+    /// Create an event bus without subscribers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a subscriber, notified of all subsequent events
+    pub fn subscribe<S: Subscriber + Send + 'static>(&mut self, subscriber: S) {
+        self.subscribers.push(Box::new(subscriber));
+    }
+
+    /// Number of subscribers
+    pub fn num_subscribers(&self) -> usize {
+        self.subscribers.len()
+    }
+
+    /// Notify all subscribers of an event, in the order they subscribed
+    pub fn publish(&mut self, event: Event) {
+        for subscriber in self.subscribers.iter_mut() {
+            subscriber.notify(&event);
+        }
+    }
+
+    /// Process a registration and publish RegistrationAccepted if it is accepted
+    pub fn add_registration(
+        &mut self,
+        registar: &mut VoterRegistar,
+        registration: Registration,
+    ) -> Result<(), RegistarError> {
+        registar.add_registration(registration)?;
+        // a registration may replace an earlier one of the same voter
+        let voter_index = registar
+            .voting_keys
+            .iter()
+            .position(|voting_key| *voting_key == registration.voting_key)
+            .expect("Accepted voting key must be registered.");
+        self.publish(Event::RegistrationAccepted {
+            voter_index,
+            address: registration.address,
+        });
+        Ok(())
+    }
+
+    /// Process an encrypted vote and publish VoteAccepted if it is accepted
+    pub fn add_encrypted_vote(
+        &mut self,
+        collector: &mut VoteCollector,
+        encrypted_vote: EncryptedVote,
+    ) -> Result<(), CollectorError> {
+        let voter_index = encrypted_vote.voter_index();
+        collector.add_encrypted_vote(encrypted_vote)?;
+        self.publish(Event::VoteAccepted { voter_index });
+        Ok(())
+    }
+
+    /// Collect encrypted votes verified by the verification pool of the collector
+    /// and publish VoteAccepted for each accepted one
+    #[cfg(feature = "std")]
+    pub fn poll_verified_votes(
+        &mut self,
+        collector: &mut VoteCollector,
+    ) -> Vec<(usize, Result<(), CollectorError>)> {
+        let results = collector.poll_verified_votes();
+        for (voter_index, result) in results.iter() {
+            if result.is_ok() {
+                self.publish(Event::VoteAccepted {
+                    voter_index: *voter_index,
+                });
+            }
+        }
+        results
+    }
+
+    /// Generate the register proof and publish ProofGenerated
+    pub fn get_register_proof(
+        &mut self,
+        registar: &mut VoterRegistar,
+    ) -> Result<Vec<u8>, RegistarError> {
+        let proof = registar.get_register_proof()?;
+        self.publish(Event::ProofGenerated {
+            phase: ProofPhase::Register,
+            num_voters: registar.voting_keys.len(),
+            proof_size: proof.len(),
+        });
+        Ok(proof)
+    }
+
+    /// Generate the cast proof and publish ProofGenerated
+    pub fn get_cast_proof(
+        &mut self,
+        collector: &mut VoteCollector,
+    ) -> Result<Vec<u8>, CollectorError> {
+        let proof = collector.get_cast_proof()?;
+        self.publish(Event::ProofGenerated {
+            phase: ProofPhase::Cast,
+            num_voters: collector.voting_keys.len(),
+            proof_size: proof.len(),
+        });
+        Ok(proof)
+    }
+
+    /// Tally votes and publish TallyFinalized
+    pub fn tally_votes(&mut self, tallier: &mut VoteTallier) -> Result<u32, TallierError> {
+        let tally_result = tallier.tally_votes()?;
+        self.publish(Event::TallyFinalized {
+            tally_result,
+            num_votes: tallier.encrypted_votes.len(),
+        });
+        Ok(tally_result)
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+use crate::{
+    aggregator::{cast::EncryptedVote, register::Registration},
+    schnorr::random_key_pairs,
+};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn events_test_registration_and_votes() {
+    let example = VoterRegistar::get_example(2);
+    let mut registar = VoterRegistar::new(example.tree.clone(), 2);
+    let mut bus = EventBus::new();
+    let log = Arc::new(Mutex::new(EventLog::default()));
+    let subscriber = Arc::clone(&log);
+    bus.subscribe(move |event: &Event| subscriber.lock().unwrap().notify(event));
+
+    let registration = Registration {
+        voting_key: example.voting_keys[1],
+        merkle_branch: example.merkle_branch(1),
+        hash_index: example.hash_indices[1],
+        signature: example.signatures[1],
+        address: example.addresses[1],
+        ownership_signature: None,
+    };
+    assert!(bus.add_registration(&mut registar, registration).is_ok());
+
+    // rejected submissions publish nothing
+    let mut invalid = registration;
+    invalid.hash_index ^= 1;
+    assert!(bus.add_registration(&mut registar, invalid).is_err());
+
+    let (secret_keys, voting_keys) = random_key_pairs(2);
+    let mut collector = VoteCollector::new(voting_keys.clone());
+    let encrypted_vote = EncryptedVote::cast(1, secret_keys[1], &voting_keys, true);
+    assert!(bus
+        .add_encrypted_vote(&mut collector, encrypted_vote)
+        .is_ok());
+    assert!(bus
+        .add_encrypted_vote(&mut collector, encrypted_vote)
+        .is_err());
+
+    assert_eq!(
+        log.lock().unwrap().events,
+        vec![
+            Event::RegistrationAccepted {
+                voter_index: 0,
+                address: example.addresses[1],
+            },
+            Event::VoteAccepted { voter_index: 1 },
+        ]
+    );
+}
+
+#[test]
+fn events_test_tally_and_json() {
+    let (mut tallier, tally_result) = VoteTallier::get_example(4);
+    let mut bus = EventBus::new();
+    let log = Arc::new(Mutex::new(EventLog::default()));
+    let subscriber = Arc::clone(&log);
+    bus.subscribe(move |event: &Event| subscriber.lock().unwrap().notify(event));
+
+    assert_eq!(bus.tally_votes(&mut tallier), Ok(tally_result));
+    let event = Event::TallyFinalized {
+        tally_result,
+        num_votes: 4,
+    };
+    assert_eq!(log.lock().unwrap().events, vec![event.clone()]);
+    assert_eq!(
+        event.to_json(),
+        format!(
+            r#"{{"event":"TallyFinalized","tally_result":{},"num_votes":4}}"#,
+            tally_result
+        )
+    );
+}
@@ -0,0 +1,96 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::{Event, Subscriber};
+use log::warn;
+use std::{
+    sync::mpsc::{self, SyncSender, TrySendError},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// Maximum number of events waiting for delivery
+pub const DEFAULT_QUEUE_CAPACITY: usize = 1024;
+
+/// Timeout of each delivery request
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Subscriber posting each event as a JSON object to an HTTP endpoint.
+///
+/// Deliveries run on a background thread so that the aggregator is never
+/// blocked by a slow endpoint. Failed deliveries are logged and not retried,
+/// and events are dropped if the delivery queue is full.
+#[derive(Debug)]
+pub struct WebhookDispatcher {
+    sender: Option<SyncSender<String>>,
+    worker: Option<JoinHandle<()>>,
+    num_dropped: usize,
+}
+
+impl WebhookDispatcher {
+    /// Create a dispatcher posting events to url with default settings
+    pub fn new(url: &str) -> Self {
+        Self::with_settings(url, DEFAULT_QUEUE_CAPACITY, DEFAULT_TIMEOUT)
+    }
+
+    /// Create a dispatcher posting events to url, with at most queue_capacity
+    /// events waiting for delivery and the given timeout per request
+    pub fn with_settings(url: &str, queue_capacity: usize, timeout: Duration) -> Self {
+        let (sender, receiver) = mpsc::sync_channel::<String>(queue_capacity);
+        let url = url.to_string();
+        let worker = thread::spawn(move || {
+            let agent = ureq::AgentBuilder::new().timeout(timeout).build();
+            for body in receiver.iter() {
+                if let Err(error) = agent
+                    .post(&url)
+                    .set("Content-Type", "application/json")
+                    .send_string(&body)
+                {
+                    warn!("Failed to deliver event to {}: {}", url, error);
+                }
+            }
+        });
+        Self {
+            sender: Some(sender),
+            worker: Some(worker),
+            num_dropped: 0,
+        }
+    }
+
+    /// Number of events dropped because the delivery queue was full
+    pub fn num_dropped(&self) -> usize {
+        self.num_dropped
+    }
+}
+
+impl Subscriber for WebhookDispatcher {
+    fn notify(&mut self, event: &Event) {
+        let sender = match self.sender.as_ref() {
+            Some(sender) => sender,
+            None => return,
+        };
+        match sender.try_send(event.to_json()) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                self.num_dropped += 1;
+                warn!("Webhook delivery queue is full, dropped {}", event.name());
+            }
+            Err(TrySendError::Disconnected(_)) => self.sender = None,
+        }
+    }
+}
+
+impl Drop for WebhookDispatcher {
+    /// Deliver the queued events before returning
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
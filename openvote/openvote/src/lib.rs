@@ -20,17 +20,71 @@
 #[macro_use]
 extern crate alloc;
 
+/// Combination of precinct tallies into region-level results
+pub mod aggregate;
 /// Module for off-chain aggregator
 pub mod aggregator;
+/// Bundling and re-verification of election transcripts
+#[cfg(feature = "archive")]
+pub mod archive;
+/// Proving backends of the phases of an election
+pub mod backend;
+/// Ballots carrying write-in candidates, and verification of their openings
+pub mod ballot;
+/// Public randomness beacons for the non-secret randomness of the protocol
+pub mod beacon;
+/// C API of the verifier entry points
+#[cfg(feature = "capi")]
+pub mod capi;
 /// The CDS sub-AIR program
 pub mod cds;
+/// Distributed key generation ceremony of trustees
+#[cfg(feature = "std")]
+pub mod ceremony;
+/// Commitments chaining the phases of an election
+pub mod chain;
+/// Compatibility layer over the winterfell version the crate is built against
+pub mod compat;
+/// Arithmetic of the f63 elliptic curve
+pub mod curve;
+/// Backends committing to the roll of eligible voters
+pub mod eligibility;
+/// Typed election events and their subscribers
+pub mod events;
+/// Election manifests bound into the proofs
+pub mod manifest;
 /// The Merkle proof of membership sub-AIR program
 pub mod merkle;
+/// Read-only verification node mirroring the bulletin board of an election
+pub mod observer;
+/// Proof parameters tuned to the size of an election
+pub mod planner;
+/// Protobuf messages exchanged between voter clients and the aggregator
+#[cfg(feature = "proto")]
+pub mod proto;
+/// Pinning of election artifacts to IPFS or S3-compatible buckets
+#[cfg(feature = "publisher")]
+pub mod publisher;
+/// Python bindings of the verifier and of voter primitives
+#[cfg(feature = "python")]
+pub mod python;
+/// The combined Merkle and Schnorr registration AIR program
+pub mod register;
 /// The Schnorr signature sub-AIR program
 pub mod schnorr;
+/// gRPC service for remote proving workers
+#[cfg(feature = "prover-service")]
+pub mod service;
+/// Simulated elections for load testing the aggregator
+#[cfg(feature = "std")]
+pub mod simulation;
 /// The vote tallying sub-AIR program
 pub mod tally;
+/// Small-depth presets to speed up tests
+pub mod testing;
 /// Utility module
 pub mod utils;
 /// Module for on-chain verifier
 pub mod verifier;
+/// Pre-flight checks of submissions for voters
+pub mod voter;
@@ -0,0 +1,689 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// ELECTION MANIFEST
+// ================================================================================================
+// Configuration of an election as presented to voters. The Rescue digest of the manifest is
+// appended to the public inputs of the register and cast proofs, which seed the proof transcript,
+// so that a proof only verifies against the manifest the aggregator committed to.
+//
+// The schedule of a running election can be amended by a trustee, e.g. to extend casting. The
+// amended manifest records the digest of the manifest it replaces, so that manifest digests form
+// a chain, and subsequent proofs are bound to the digest of the amended manifest.
+//
+// The manifest may also set a result policy, i.e. a minimum turnout and the share of votes the
+// first candidate needs to be adopted. The policy is part of the manifest digest stored by the
+// contract, so that the contract and the result certificate agree on the validity conditions.
+
+use crate::{
+    compat::curve::Scalar,
+    merkle::constants::{is_supported_depth, TREE_DEPTH},
+    schnorr::{sign_digest, verify_digest_signature},
+    utils::{
+        ecc::{
+            mul_generator_constant_time, projective_to_elements, read_canonical_scalar,
+            read_compressed_point, write_compressed_points, AFFINE_POINT_WIDTH,
+            POINT_COORDINATE_WIDTH,
+        },
+        rescue::{self, digest_bytes, Rescue63, BYTES_PER_DIGEST_ELEMENT, DIGEST_SIZE, RATE_WIDTH},
+        wire,
+    },
+};
+use winterfell::{
+    math::{fields::f63::BaseElement, FieldElement},
+    ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable, SliceReader,
+};
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+#[cfg(test)]
+mod tests;
+
+/// Name of the curve over which voting keys and votes are defined
+pub const CURVE_NAME: &str = "curve_f63";
+/// Name of the hash function used in the proofs
+pub const HASH_NAME: &str = "rescue63";
+
+/// Number of candidates, as votes are encrypted yes/no choices
+pub const NUM_CANDIDATES: usize = 2;
+
+/// Number of bytes of a serialized manifest digest
+pub const BYTES_PER_MANIFEST_DIGEST: usize = DIGEST_SIZE * 8;
+
+/// Number of field elements encoding a candidate identifier,
+/// so that it is absorbed in a single rate of the Rescue hash
+pub const CANDIDATE_NUM_ELEMENTS: usize = RATE_WIDTH;
+/// Maximum length of a candidate identifier, in UTF-8 bytes
+pub const MAX_CANDIDATE_LEN: usize = CANDIDATE_NUM_ELEMENTS * BYTES_PER_DIGEST_ELEMENT;
+
+/// Number of basis points in a whole, in which the thresholds of a result policy are expressed
+pub const BASIS_POINTS: u32 = 10_000;
+/// Threshold of a result policy requiring a simple majority of the votes
+pub const SIMPLE_MAJORITY: u32 = BASIS_POINTS / 2;
+
+// ERRORS
+// ================================================================================================
+
+/// Errors raised when encoding a candidate identifier into field elements
+#[derive(Debug, Clone, PartialEq)]
+pub enum CandidateError {
+    /// This error occurs when the identifier is empty
+    Empty,
+    /// This error occurs when the identifier is longer than MAX_CANDIDATE_LEN bytes
+    TooLong {
+        /// Length of the identifier, in UTF-8 bytes
+        len: usize,
+    },
+    /// This error occurs when the identifier contains a control character
+    ControlCharacter {
+        /// Byte offset of the character in the identifier
+        position: usize,
+    },
+    /// This error occurs when the identifier starts or ends with whitespace,
+    /// which would make identifiers that look the same encode differently
+    SurroundingWhitespace,
+    /// This error occurs when the identifier is listed twice in the manifest
+    Duplicate {
+        /// Index of the first occurrence of the identifier
+        first_index: usize,
+    },
+}
+
+/// Errors raised when validating an election manifest
+#[derive(Debug, PartialEq)]
+pub enum ManifestError {
+    /// This error occurs when the question is empty
+    EmptyQuestion,
+    /// This error occurs when the manifest does not list exactly NUM_CANDIDATES candidates
+    UnsupportedCandidates,
+    /// This error occurs when a candidate identifier cannot be encoded
+    InvalidCandidate {
+        /// Index of the candidate in the manifest
+        index: usize,
+        /// Reason why the identifier cannot be encoded
+        error: CandidateError,
+    },
+    /// This error occurs when a ballot selects a candidate which is not in the manifest
+    UnknownCandidate,
+    /// This error occurs when the phases of the schedule are out of order
+    InvalidSchedule,
+    /// This error occurs when the curve, hash or tree depth is not supported by the prover
+    UnsupportedParameters,
+    /// This error occurs when a manifest update does not amend the digest of the manifest
+    UnchainedUpdate,
+    /// This error occurs when a manifest update is not signed by a trustee of the manifest
+    UnknownAuthority,
+    /// This error occurs when the signature of a manifest update is invalid
+    InvalidUpdateSignature,
+    /// This error occurs when a manifest update moves a phase of the schedule earlier
+    ScheduleRewound,
+    /// This error occurs when the minimum turnout of the result policy exceeds BASIS_POINTS,
+    /// or its threshold is not between SIMPLE_MAJORITY and BASIS_POINTS
+    InvalidPolicy,
+}
+
+// MANIFEST
+// ================================================================================================
+
+/// Timestamps of the election phases, in seconds since the Unix epoch
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Schedule {
+    /// Opening of the registration phase
+    pub registration_start: u64,
+    /// Closing of the registration phase
+    pub registration_end: u64,
+    /// Opening of the casting phase
+    pub casting_start: u64,
+    /// Closing of the casting phase
+    pub casting_end: u64,
+}
+
+impl Schedule {
+    /// Check that each phase opens before it closes and that casting
+    /// opens after registration is closed
+    pub fn is_ordered(&self) -> bool {
+        self.registration_start <= self.registration_end
+            && self.registration_end <= self.casting_start
+            && self.casting_start <= self.casting_end
+    }
+
+    /// Check that no timestamp of the schedule is earlier than in the given schedule
+    pub fn postpones(&self, other: &Schedule) -> bool {
+        self.registration_start >= other.registration_start
+            && self.registration_end >= other.registration_end
+            && self.casting_start >= other.casting_start
+            && self.casting_end >= other.casting_end
+    }
+
+    /// Timestamps of the schedule split into 32-bit limbs, so that
+    /// no timestamp is reduced modulo the field order
+    fn to_elements(self) -> [BaseElement; 8] {
+        let mut elements = [BaseElement::ZERO; 8];
+        let timestamps = [
+            self.registration_start,
+            self.registration_end,
+            self.casting_start,
+            self.casting_end,
+        ];
+        for (limbs, timestamp) in elements.chunks_mut(2).zip(timestamps.iter()) {
+            limbs[0] = BaseElement::from(*timestamp as u32);
+            limbs[1] = BaseElement::from((*timestamp >> 32) as u32);
+        }
+        elements
+    }
+}
+
+/// Cryptographic parameters of the election
+#[derive(Debug, Clone, PartialEq)]
+pub struct CryptoParameters {
+    /// Name of the curve
+    pub curve: String,
+    /// Name of the hash function
+    pub hash: String,
+    /// Depth of the Merkle tree of eligible voters
+    pub tree_depth: usize,
+}
+
+impl Default for CryptoParameters {
+    /// Parameters of the deployed AIR programs
+    fn default() -> Self {
+        Self {
+            curve: String::from(CURVE_NAME),
+            hash: String::from(HASH_NAME),
+            tree_depth: TREE_DEPTH,
+        }
+    }
+}
+
+/// Outcome of an election under a result policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyOutcome {
+    /// The first candidate is adopted
+    Adopted,
+    /// The first candidate is rejected
+    Rejected,
+    /// Too few votes were cast for the result to be valid
+    QuorumNotReached,
+}
+
+impl PolicyOutcome {
+    /// Code of the outcome, as serialized and signed in result certificates
+    pub fn code(self) -> u8 {
+        match self {
+            PolicyOutcome::Adopted => 0,
+            PolicyOutcome::Rejected => 1,
+            PolicyOutcome::QuorumNotReached => 2,
+        }
+    }
+
+    /// Outcome with the given code, if any
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(PolicyOutcome::Adopted),
+            1 => Some(PolicyOutcome::Rejected),
+            2 => Some(PolicyOutcome::QuorumNotReached),
+            _ => None,
+        }
+    }
+}
+
+/// Validity conditions of the result of an election, in basis points
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResultPolicy {
+    /// Minimum number of votes, in basis points of the number of eligible voters
+    pub min_turnout: u32,
+    /// Share of the votes the first candidate must exceed to be adopted,
+    /// e.g. SIMPLE_MAJORITY or 6_666 for a two-thirds supermajority
+    pub threshold: u32,
+}
+
+impl Default for ResultPolicy {
+    /// Simple majority without minimum turnout
+    fn default() -> Self {
+        Self {
+            min_turnout: 0,
+            threshold: SIMPLE_MAJORITY,
+        }
+    }
+}
+
+impl ResultPolicy {
+    /// Check that the thresholds are expressed in basis points, and that a minority
+    /// of the votes cannot adopt the first candidate
+    pub fn is_valid(&self) -> bool {
+        self.min_turnout <= BASIS_POINTS
+            && self.threshold >= SIMPLE_MAJORITY
+            && self.threshold < BASIS_POINTS
+    }
+
+    /// Outcome of an election with the given number of eligible voters and tally
+    pub fn evaluate(&self, num_elg_voters: u32, yes_votes: u32, num_votes: u32) -> PolicyOutcome {
+        let basis_points = BASIS_POINTS as u64;
+        if (num_votes as u64) * basis_points < (self.min_turnout as u64) * (num_elg_voters as u64) {
+            PolicyOutcome::QuorumNotReached
+        } else if (yes_votes as u64) * basis_points > (self.threshold as u64) * (num_votes as u64) {
+            PolicyOutcome::Adopted
+        } else {
+            PolicyOutcome::Rejected
+        }
+    }
+}
+
+/// Configuration of an election, as shown to voters
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElectionManifest {
+    /// Question submitted to voters
+    pub question: String,
+    /// Names of the candidates, the first one being the "yes" choice
+    pub candidates: Vec<String>,
+    /// Timestamps of the election phases
+    pub schedule: Schedule,
+    /// Cryptographic parameters
+    pub parameters: CryptoParameters,
+    /// Public keys of the trustees overseeing the election
+    pub trustee_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+    /// Digest of the manifest this one amends, if any
+    pub previous_digest: Option<rescue::Hash>,
+    /// Validity conditions of the result, if any
+    pub policy: Option<ResultPolicy>,
+}
+
+impl ElectionManifest {
+    /// Create a yes/no manifest with the default cryptographic parameters
+    pub fn new(question: &str, schedule: Schedule) -> Self {
+        Self {
+            question: String::from(question),
+            candidates: vec![String::from("yes"), String::from("no")],
+            schedule,
+            parameters: CryptoParameters::default(),
+            trustee_keys: vec![],
+            previous_digest: None,
+            policy: None,
+        }
+    }
+
+    /// Reconstruct an object of type Self from a sequence of bytes
+    pub fn from_bytes(source: &[u8]) -> Result<Self, DeserializationError> {
+        let mut source = SliceReader::new(source);
+        Self::read_from(&mut source)
+    }
+
+    /// Check that the manifest can be used with the deployed AIR programs
+    pub fn validate(&self) -> Result<(), ManifestError> {
+        if self.question.is_empty() {
+            return Err(ManifestError::EmptyQuestion);
+        }
+        if self.candidates.len() != NUM_CANDIDATES {
+            return Err(ManifestError::UnsupportedCandidates);
+        }
+        self.encode_candidates()?;
+        if !self.schedule.is_ordered() {
+            return Err(ManifestError::InvalidSchedule);
+        }
+        if self.parameters.curve != CURVE_NAME
+            || self.parameters.hash != HASH_NAME
+            || !is_supported_depth(self.parameters.tree_depth)
+        {
+            return Err(ManifestError::UnsupportedParameters);
+        }
+        if !self.policy.map_or(true, |policy| policy.is_valid()) {
+            return Err(ManifestError::InvalidPolicy);
+        }
+        Ok(())
+    }
+
+    /// Encode the candidate identifiers into field elements, in the order of the manifest
+    pub fn encode_candidates(
+        &self,
+    ) -> Result<Vec<[BaseElement; CANDIDATE_NUM_ELEMENTS]>, ManifestError> {
+        let mut encoded = Vec::with_capacity(self.candidates.len());
+        for (index, candidate) in self.candidates.iter().enumerate() {
+            let invalid_candidate = |error| ManifestError::InvalidCandidate { index, error };
+            if let Some(first_index) = self.candidates[..index].iter().position(|c| c == candidate)
+            {
+                return Err(invalid_candidate(CandidateError::Duplicate { first_index }));
+            }
+            encoded.push(encode_candidate(candidate).map_err(invalid_candidate)?);
+        }
+        Ok(encoded)
+    }
+
+    /// Vote of a ballot selecting the given candidate, true for the first
+    /// candidate of the manifest and false for the second one
+    pub fn choice(&self, candidate: &str) -> Result<bool, ManifestError> {
+        match self.candidates.iter().position(|c| c == candidate) {
+            Some(0) => Ok(true),
+            Some(1) => Ok(false),
+            _ => Err(ManifestError::UnknownCandidate),
+        }
+    }
+
+    /// Rescue digest of the serialized manifest, bound into the proofs
+    pub fn digest(&self) -> rescue::Hash {
+        digest_bytes(&self.to_bytes())
+    }
+
+    /// Serialized digest of the manifest, as stored alongside the election
+    /// on the verifier side
+    pub fn digest_to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(BYTES_PER_MANIFEST_DIGEST);
+        Serializable::write_batch_into(&self.digest().to_elements(), &mut bytes);
+        bytes
+    }
+
+    /// Manifest amended by the given update, which must be signed by one of the trustees
+    /// and chained to the digest of this manifest. The amended manifest records this
+    /// digest, so that its own digest is chained to it.
+    pub fn amend(&self, update: &ManifestUpdate) -> Result<Self, ManifestError> {
+        if update.previous_digest != self.digest() {
+            return Err(ManifestError::UnchainedUpdate);
+        }
+        if !self.trustee_keys.contains(&update.authority_key) {
+            return Err(ManifestError::UnknownAuthority);
+        }
+        if !update.verify() {
+            return Err(ManifestError::InvalidUpdateSignature);
+        }
+        if !update.schedule.is_ordered() {
+            return Err(ManifestError::InvalidSchedule);
+        }
+        if !update.schedule.postpones(&self.schedule) {
+            return Err(ManifestError::ScheduleRewound);
+        }
+        Ok(Self {
+            schedule: update.schedule,
+            previous_digest: Some(update.previous_digest),
+            ..self.clone()
+        })
+    }
+}
+
+// MANIFEST UPDATES
+// ================================================================================================
+
+/// Amendment of the schedule of an election, signed by one of its trustees
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestUpdate {
+    /// Digest of the manifest being amended
+    pub previous_digest: rescue::Hash,
+    /// Amended schedule
+    pub schedule: Schedule,
+    /// Public key of the trustee authorizing the update
+    pub authority_key: [BaseElement; AFFINE_POINT_WIDTH],
+    /// Schnorr signature of the update by the trustee
+    pub signature: ([BaseElement; POINT_COORDINATE_WIDTH], Scalar),
+}
+
+impl ManifestUpdate {
+    /// Create an update of the schedule of the given manifest, signed
+    /// with the secret key of a trustee
+    pub fn sign(manifest: &ElectionManifest, schedule: Schedule, secret_key: Scalar) -> Self {
+        let mut update = Self {
+            previous_digest: manifest.digest(),
+            schedule,
+            authority_key: projective_to_elements(mul_generator_constant_time(secret_key)),
+            signature: ([BaseElement::ZERO; POINT_COORDINATE_WIDTH], Scalar::zero()),
+        };
+        update.signature = sign_digest(secret_key, &update.digest());
+        update
+    }
+
+    /// Reconstruct an object of type Self from a sequence of bytes
+    pub fn from_bytes(source: &[u8]) -> Result<Self, DeserializationError> {
+        let mut source = SliceReader::new(source);
+        Self::read_from(&mut source)
+    }
+
+    /// Digest of the update, which is what the trustee signs
+    pub fn digest(&self) -> rescue::Hash {
+        let mut hasher = Rescue63::new();
+        hasher.update(&self.previous_digest.to_elements());
+        hasher.update(&self.schedule.to_elements());
+        hasher.update(&self.authority_key);
+        hasher.finalize()
+    }
+
+    /// Check that the update is signed by its authority key
+    pub fn verify(&self) -> bool {
+        verify_digest_signature(self.authority_key, &self.digest(), self.signature).is_ok()
+    }
+}
+
+// SERIALIZATION
+// ================================================================================================
+
+impl Serializable for ElectionManifest {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        wire::write_header(target);
+        write_string(&self.question, target);
+        wire::write_varint(target, self.candidates.len() as u64);
+        for candidate in self.candidates.iter() {
+            write_string(candidate, target);
+        }
+        target.write_u64(self.schedule.registration_start);
+        target.write_u64(self.schedule.registration_end);
+        target.write_u64(self.schedule.casting_start);
+        target.write_u64(self.schedule.casting_end);
+        write_string(&self.parameters.curve, target);
+        write_string(&self.parameters.hash, target);
+        target.write_u8(self.parameters.tree_depth as u8);
+        wire::write_varint(target, self.trustee_keys.len() as u64);
+        for trustee_key in self.trustee_keys.iter() {
+            write_compressed_points(trustee_key, target);
+        }
+        // the previous digest is only written for amended manifests, and the policy
+        // in a marked section, so that the encoding of other manifests stays unchanged
+        if let Some(previous_digest) = self.previous_digest {
+            previous_digest.write_into(target);
+        }
+        if let Some(policy) = self.policy {
+            wire::write_section_marker(target);
+            policy.write_into(target);
+        }
+    }
+}
+
+impl Deserializable for ElectionManifest {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        if let wire::WireHeader::Legacy(_) = wire::read_header(source)? {
+            return Err(DeserializationError::InvalidValue(String::from(
+                "Missing wire header.",
+            )));
+        }
+        let question = read_string(source)?;
+        let num_candidates = wire::read_varint_usize(source)?;
+        let mut candidates = Vec::with_capacity(num_candidates);
+        for _ in 0..num_candidates {
+            candidates.push(read_string(source)?);
+        }
+        let schedule = Schedule {
+            registration_start: source.read_u64()?,
+            registration_end: source.read_u64()?,
+            casting_start: source.read_u64()?,
+            casting_end: source.read_u64()?,
+        };
+        let parameters = CryptoParameters {
+            curve: read_string(source)?,
+            hash: read_string(source)?,
+            tree_depth: source.read_u8()? as usize,
+        };
+        let num_trustees = wire::read_varint_usize(source)?;
+        let mut trustee_keys = Vec::with_capacity(num_trustees);
+        for _ in 0..num_trustees {
+            trustee_keys.push(read_compressed_point(source)?);
+        }
+        let mut previous_digest = None;
+        let mut policy = None;
+        while source.has_more_bytes() {
+            match wire::read_digest_or_marker(source)? {
+                Some(digest) if previous_digest.is_none() && policy.is_none() => {
+                    previous_digest = Some(digest)
+                }
+                None if policy.is_none() => policy = Some(ResultPolicy::read_from(source)?),
+                _ => {
+                    return Err(DeserializationError::InvalidValue(String::from(
+                        "Unexpected trailing section.",
+                    )))
+                }
+            }
+        }
+
+        Ok(Self {
+            question,
+            candidates,
+            schedule,
+            parameters,
+            trustee_keys,
+            previous_digest,
+            policy,
+        })
+    }
+}
+
+impl Serializable for ResultPolicy {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        wire::write_varint(target, self.min_turnout as u64);
+        wire::write_varint(target, self.threshold as u64);
+    }
+}
+
+impl Deserializable for ResultPolicy {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let min_turnout = wire::read_varint(source)?;
+        let threshold = wire::read_varint(source)?;
+        if min_turnout > BASIS_POINTS as u64 || threshold > BASIS_POINTS as u64 {
+            return Err(DeserializationError::InvalidValue(String::from(
+                "Policy threshold exceeds basis points.",
+            )));
+        }
+        Ok(Self {
+            min_turnout: min_turnout as u32,
+            threshold: threshold as u32,
+        })
+    }
+}
+
+impl Serializable for ManifestUpdate {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        wire::write_header(target);
+        self.previous_digest.write_into(target);
+        target.write_u64(self.schedule.registration_start);
+        target.write_u64(self.schedule.registration_end);
+        target.write_u64(self.schedule.casting_start);
+        target.write_u64(self.schedule.casting_end);
+        write_compressed_points(&self.authority_key, target);
+        Serializable::write_batch_into(&self.signature.0, target);
+        target.write(self.signature.1);
+    }
+}
+
+impl Deserializable for ManifestUpdate {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        if let wire::WireHeader::Legacy(_) = wire::read_header(source)? {
+            return Err(DeserializationError::InvalidValue(String::from(
+                "Missing wire header.",
+            )));
+        }
+        let previous_digest = rescue::Hash::read_from(source)?;
+        let schedule = Schedule {
+            registration_start: source.read_u64()?,
+            registration_end: source.read_u64()?,
+            casting_start: source.read_u64()?,
+            casting_end: source.read_u64()?,
+        };
+        let authority_key = read_compressed_point(source)?;
+        let mut signature_r = [BaseElement::ZERO; POINT_COORDINATE_WIDTH];
+        signature_r.copy_from_slice(&BaseElement::read_batch_from(
+            source,
+            POINT_COORDINATE_WIDTH,
+        )?);
+        let signature_s = read_canonical_scalar(source)?;
+
+        Ok(Self {
+            previous_digest,
+            schedule,
+            authority_key,
+            signature: (signature_r, signature_s),
+        })
+    }
+}
+
+/// Read a manifest digest serialized by ElectionManifest::digest_to_bytes
+pub fn read_manifest_digest(
+    source: &[u8],
+) -> Result<[BaseElement; DIGEST_SIZE], DeserializationError> {
+    if source.len() != BYTES_PER_MANIFEST_DIGEST {
+        return Err(DeserializationError::InvalidValue(String::from(
+            "Invalid length of manifest digest.",
+        )));
+    }
+    let mut digest = [BaseElement::ZERO; DIGEST_SIZE];
+    digest.copy_from_slice(&BaseElement::read_batch_from(
+        &mut SliceReader::new(source),
+        DIGEST_SIZE,
+    )?);
+    Ok(digest)
+}
+
+// CANDIDATE ENCODING
+// ================================================================================================
+
+/// Check that a candidate identifier can be encoded into field elements.
+/// Identifiers are UTF-8 strings of any script, without control characters
+/// or surrounding whitespace.
+pub fn validate_candidate(candidate: &str) -> Result<(), CandidateError> {
+    if candidate.is_empty() {
+        return Err(CandidateError::Empty);
+    }
+    if candidate.len() > MAX_CANDIDATE_LEN {
+        return Err(CandidateError::TooLong {
+            len: candidate.len(),
+        });
+    }
+    if let Some((position, _)) = candidate.char_indices().find(|(_, c)| c.is_control()) {
+        return Err(CandidateError::ControlCharacter { position });
+    }
+    if candidate.trim() != candidate {
+        return Err(CandidateError::SurroundingWhitespace);
+    }
+    Ok(())
+}
+
+/// Encode a candidate identifier into field elements, packing BYTES_PER_DIGEST_ELEMENT
+/// bytes per element so that no element is reduced modulo the field order.
+/// Identifiers which cannot be encoded without truncation are rejected.
+pub fn encode_candidate(
+    candidate: &str,
+) -> Result<[BaseElement; CANDIDATE_NUM_ELEMENTS], CandidateError> {
+    validate_candidate(candidate)?;
+    let mut encoded = [BaseElement::ZERO; CANDIDATE_NUM_ELEMENTS];
+    for (element, chunk) in encoded
+        .iter_mut()
+        .zip(candidate.as_bytes().chunks(BYTES_PER_DIGEST_ELEMENT))
+    {
+        let mut element_bytes = [0u8; 8];
+        element_bytes[..chunk.len()].copy_from_slice(chunk);
+        *element = BaseElement::from(u64::from_le_bytes(element_bytes));
+    }
+    Ok(encoded)
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn write_string<W: ByteWriter>(value: &str, target: &mut W) {
+    wire::write_varint(target, value.len() as u64);
+    target.write_u8_slice(value.as_bytes());
+}
+
+fn read_string<R: ByteReader>(source: &mut R) -> Result<String, DeserializationError> {
+    let len = wire::read_varint_usize(source)?;
+    String::from_utf8(source.read_u8_vec(len)?)
+        .map_err(|_| DeserializationError::InvalidValue(String::from("Invalid UTF-8 string.")))
+}
@@ -0,0 +1,270 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+use crate::compat::curve::{ProjectivePoint, Scalar};
+use crate::utils::ecc::projective_to_elements;
+
+fn build_manifest() -> ElectionManifest {
+    let mut manifest = ElectionManifest::new(
+        "Adopt the proposal?",
+        Schedule {
+            registration_start: 1_000,
+            registration_end: 2_000,
+            casting_start: 2_000,
+            casting_end: 3_000,
+        },
+    );
+    manifest
+        .trustee_keys
+        .push(projective_to_elements(ProjectivePoint::generator()));
+    manifest
+}
+
+#[test]
+fn manifest_test_serialization() {
+    let manifest = build_manifest();
+    let bytes = manifest.to_bytes();
+    assert_eq!(ElectionManifest::from_bytes(&bytes).unwrap(), manifest);
+    assert!(ElectionManifest::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+    let digest = manifest.digest_to_bytes();
+    assert_eq!(digest.len(), BYTES_PER_MANIFEST_DIGEST);
+    assert_eq!(
+        read_manifest_digest(&digest).unwrap(),
+        manifest.digest().to_elements()
+    );
+    assert!(read_manifest_digest(&digest[1..]).is_err());
+}
+
+#[test]
+fn manifest_test_digest() {
+    let manifest = build_manifest();
+    assert_eq!(manifest.digest(), build_manifest().digest());
+
+    let mut other = build_manifest();
+    other.candidates.swap(0, 1);
+    assert_ne!(other.digest(), manifest.digest());
+
+    let mut other = build_manifest();
+    other.schedule.casting_end += 1;
+    assert_ne!(other.digest(), manifest.digest());
+
+    let mut other = build_manifest();
+    other.trustee_keys.clear();
+    assert_ne!(other.digest(), manifest.digest());
+}
+
+#[test]
+fn manifest_test_validation() {
+    assert_eq!(build_manifest().validate(), Ok(()));
+
+    let mut manifest = build_manifest();
+    manifest.question.clear();
+    assert_eq!(manifest.validate(), Err(ManifestError::EmptyQuestion));
+
+    let mut manifest = build_manifest();
+    manifest.candidates.push(String::from("abstain"));
+    assert_eq!(
+        manifest.validate(),
+        Err(ManifestError::UnsupportedCandidates)
+    );
+
+    let mut manifest = build_manifest();
+    manifest.schedule.casting_start = manifest.schedule.registration_start;
+    assert_eq!(manifest.validate(), Err(ManifestError::InvalidSchedule));
+
+    let mut manifest = build_manifest();
+    manifest.parameters.tree_depth = 5;
+    assert_eq!(
+        manifest.validate(),
+        Err(ManifestError::UnsupportedParameters)
+    );
+}
+
+#[test]
+fn manifest_test_amendment() {
+    // the trustee key of the manifest is the generator
+    let trustee_secret = Scalar::one();
+    let manifest = build_manifest();
+    let mut schedule = manifest.schedule;
+    schedule.casting_end += 1_000;
+
+    let update = ManifestUpdate::sign(&manifest, schedule, trustee_secret);
+    assert!(update.verify());
+    assert_eq!(
+        ManifestUpdate::from_bytes(&update.to_bytes()).unwrap(),
+        update
+    );
+    let amended = manifest.amend(&update).unwrap();
+    assert_eq!(amended.schedule, schedule);
+    assert_eq!(amended.previous_digest, Some(manifest.digest()));
+    assert_ne!(amended.digest(), manifest.digest());
+    assert_eq!(
+        ElectionManifest::from_bytes(&amended.to_bytes()).unwrap(),
+        amended
+    );
+
+    // amendments are chained to the digest of the manifest they amend
+    assert_eq!(amended.amend(&update), Err(ManifestError::UnchainedUpdate));
+    schedule.casting_end += 1_000;
+    let chained = amended
+        .amend(&ManifestUpdate::sign(&amended, schedule, trustee_secret))
+        .unwrap();
+    assert_eq!(chained.previous_digest, Some(amended.digest()));
+
+    // updates must be signed by a trustee
+    let other_update = ManifestUpdate::sign(&manifest, schedule, trustee_secret + Scalar::one());
+    assert_eq!(
+        manifest.amend(&other_update),
+        Err(ManifestError::UnknownAuthority)
+    );
+    let mut forged = update.clone();
+    forged.schedule.casting_end += 1;
+    assert_eq!(
+        manifest.amend(&forged),
+        Err(ManifestError::InvalidUpdateSignature)
+    );
+
+    // phases cannot be moved earlier
+    let mut rewound = manifest.schedule;
+    rewound.casting_end -= 1;
+    assert_eq!(
+        manifest.amend(&ManifestUpdate::sign(&manifest, rewound, trustee_secret)),
+        Err(ManifestError::ScheduleRewound)
+    );
+    let mut unordered = manifest.schedule;
+    unordered.registration_end = unordered.casting_start + 1;
+    assert_eq!(
+        manifest.amend(&ManifestUpdate::sign(&manifest, unordered, trustee_secret)),
+        Err(ManifestError::InvalidSchedule)
+    );
+}
+
+#[test]
+fn manifest_test_candidate_encoding() {
+    // identifiers of any script are accepted
+    let encoded = encode_candidate("Oui, je l'approuve").unwrap();
+    assert_ne!(encoded, encode_candidate("Oui, je l’approuve").unwrap());
+    assert!(encode_candidate("賛成").is_ok());
+    assert!(encode_candidate(&"é".repeat(MAX_CANDIDATE_LEN / 2)).is_ok());
+
+    // identifiers are rejected rather than truncated
+    assert_eq!(
+        encode_candidate(&"é".repeat(MAX_CANDIDATE_LEN / 2 + 1)),
+        Err(CandidateError::TooLong {
+            len: 2 * (MAX_CANDIDATE_LEN / 2 + 1)
+        })
+    );
+    assert_eq!(encode_candidate(""), Err(CandidateError::Empty));
+    assert_eq!(
+        encode_candidate("yes\u{0}"),
+        Err(CandidateError::ControlCharacter { position: 3 })
+    );
+    assert_eq!(
+        encode_candidate(" yes"),
+        Err(CandidateError::SurroundingWhitespace)
+    );
+
+    // identifiers sharing a prefix encode differently
+    assert_ne!(
+        encode_candidate(&"a".repeat(BYTES_PER_DIGEST_ELEMENT)).unwrap(),
+        encode_candidate(&"a".repeat(BYTES_PER_DIGEST_ELEMENT + 1)).unwrap()
+    );
+}
+
+#[test]
+fn manifest_test_candidate_validation() {
+    let mut manifest = build_manifest();
+    manifest.candidates[1] = String::from("yes");
+    assert_eq!(
+        manifest.validate(),
+        Err(ManifestError::InvalidCandidate {
+            index: 1,
+            error: CandidateError::Duplicate { first_index: 0 }
+        })
+    );
+
+    let mut manifest = build_manifest();
+    manifest.candidates[0] = "x".repeat(MAX_CANDIDATE_LEN + 1);
+    assert_eq!(
+        manifest.validate(),
+        Err(ManifestError::InvalidCandidate {
+            index: 0,
+            error: CandidateError::TooLong {
+                len: MAX_CANDIDATE_LEN + 1
+            }
+        })
+    );
+
+    let manifest = build_manifest();
+    assert_eq!(manifest.encode_candidates().unwrap().len(), NUM_CANDIDATES);
+    assert_eq!(manifest.choice("yes"), Ok(true));
+    assert_eq!(manifest.choice("no"), Ok(false));
+    assert_eq!(
+        manifest.choice("abstain"),
+        Err(ManifestError::UnknownCandidate)
+    );
+}
+
+#[test]
+fn manifest_test_result_policy() {
+    let policy = ResultPolicy {
+        min_turnout: 4_000,
+        threshold: 6_666,
+    };
+    assert!(policy.is_valid());
+    // turnout below 40% of 10 eligible voters
+    assert_eq!(policy.evaluate(10, 3, 3), PolicyOutcome::QuorumNotReached);
+    // strictly more than two thirds of the votes
+    assert_eq!(policy.evaluate(5, 2, 3), PolicyOutcome::Adopted);
+    assert_eq!(policy.evaluate(10, 4, 6), PolicyOutcome::Adopted);
+    assert_eq!(policy.evaluate(10, 3, 6), PolicyOutcome::Rejected);
+    assert_eq!(
+        ResultPolicy::default().evaluate(10, 2, 4),
+        PolicyOutcome::Rejected
+    );
+
+    // the policy is bound into the digest and survives serialization
+    let mut manifest = build_manifest();
+    let digest = manifest.digest();
+    let plain_len = manifest.to_bytes().len();
+    manifest.policy = Some(policy);
+    assert_eq!(manifest.validate(), Ok(()));
+    assert_ne!(manifest.digest(), digest);
+    let bytes = manifest.to_bytes();
+    assert_eq!(ElectionManifest::from_bytes(&bytes).unwrap(), manifest);
+    assert!(ElectionManifest::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    manifest.policy = None;
+    assert_eq!(manifest.to_bytes().len(), plain_len);
+
+    // amended manifests keep their policy after the previous digest
+    manifest.policy = Some(policy);
+    let mut schedule = manifest.schedule;
+    schedule.casting_end += 1_000;
+    let update = ManifestUpdate::sign(&manifest, schedule, Scalar::one());
+    let amended = manifest.amend(&update).unwrap();
+    assert_eq!(amended.policy, Some(policy));
+    assert_eq!(
+        ElectionManifest::from_bytes(&amended.to_bytes()).unwrap(),
+        amended
+    );
+
+    // a minority threshold or a turnout above 100% is rejected
+    manifest.policy = Some(ResultPolicy {
+        min_turnout: 0,
+        threshold: SIMPLE_MAJORITY - 1,
+    });
+    assert_eq!(manifest.validate(), Err(ManifestError::InvalidPolicy));
+    manifest.policy = Some(ResultPolicy {
+        min_turnout: BASIS_POINTS + 1,
+        threshold: SIMPLE_MAJORITY,
+    });
+    assert_eq!(manifest.validate(), Err(ManifestError::InvalidPolicy));
+    assert!(ElectionManifest::from_bytes(&manifest.to_bytes()).is_err());
+}
@@ -19,8 +19,19 @@ use winterfell::{
 pub struct PublicInputs {
     pub tree_root: [BaseElement; DIGEST_SIZE],
     pub voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+    // depth of the Merkle proofs verified in each cycle, TREE_DEPTH in production
+    pub depth: usize,
+    // root and depth of the tree of each voting key, e.g. a main roll and a
+    // supplemental roll; empty if all keys belong to tree_root, of depth `depth`
+    pub trees: Vec<([BaseElement; DIGEST_SIZE], usize)>,
+    // leaf index of each voting key, asserted on the index bits of its Merkle path;
+    // empty if the leaf indices are not bound
+    pub leaf_indices: Vec<usize>,
 }
 
+// flag set on the depth byte when the leaf indices are bound
+const LEAF_INDICES_FLAG: u8 = 0x80;
+
 impl Serializable for PublicInputs {
     fn write_into<W: ByteWriter>(&self, target: &mut W) {
         Serializable::write_batch_into(&self.tree_root, target);
@@ -28,6 +39,22 @@ impl Serializable for PublicInputs {
         for voting_key in self.voting_keys.iter() {
             Serializable::write_batch_into(voting_key, target);
         }
+        // trailing section, omitted when all keys belong to tree_root of depth TREE_DEPTH
+        if self.depth == TREE_DEPTH && self.trees.is_empty() && self.leaf_indices.is_empty() {
+            return;
+        }
+        if self.leaf_indices.is_empty() {
+            target.write_u8(self.depth as u8);
+        } else {
+            target.write_u8(self.depth as u8 | LEAF_INDICES_FLAG);
+            for &leaf_index in self.leaf_indices.iter() {
+                target.write_u32(leaf_index as u32);
+            }
+        }
+        for (root, depth) in self.trees.iter() {
+            Serializable::write_batch_into(root, target);
+            target.write_u8(*depth as u8);
+        }
     }
 }
 
@@ -42,9 +69,48 @@ impl Deserializable for PublicInputs {
             voting_key.copy_from_slice(&BaseElement::read_batch_from(source, AFFINE_POINT_WIDTH)?);
             voting_keys.push(voting_key);
         }
+        let mut depth = TREE_DEPTH;
+        let mut trees = Vec::new();
+        let mut leaf_indices = Vec::new();
+        if source.has_more_bytes() {
+            let depth_byte = source.read_u8()?;
+            depth = (depth_byte & !LEAF_INDICES_FLAG) as usize;
+            if !is_supported_depth(depth) {
+                return Err(DeserializationError::InvalidValue(String::from(
+                    "Unsupported Merkle proof depth.",
+                )));
+            }
+            if depth_byte & LEAF_INDICES_FLAG != 0 {
+                for _ in 0..num_voters {
+                    let leaf_index = source.read_u32()? as usize;
+                    if leaf_index >> depth != 0 {
+                        return Err(DeserializationError::InvalidValue(String::from(
+                            "Invalid Merkle leaf index.",
+                        )));
+                    }
+                    leaf_indices.push(leaf_index);
+                }
+            }
+        }
+        if source.has_more_bytes() {
+            let mut root = [BaseElement::ZERO; DIGEST_SIZE];
+            for _ in 0..num_voters {
+                root.copy_from_slice(&BaseElement::read_batch_from(source, DIGEST_SIZE)?);
+                let tree_depth = source.read_u8()? as usize;
+                if tree_depth == 0 || tree_depth > depth {
+                    return Err(DeserializationError::InvalidValue(String::from(
+                        "Invalid Merkle tree depth.",
+                    )));
+                }
+                trees.push((root, tree_depth));
+            }
+        }
         Ok(Self {
             tree_root,
             voting_keys,
+            depth,
+            trees,
+            leaf_indices,
         })
     }
 }
@@ -60,6 +126,9 @@ pub struct MerkleAir {
     context: AirContext<BaseElement>,
     tree_root: [BaseElement; DIGEST_SIZE],
     voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+    depth: usize,
+    trees: Vec<([BaseElement; DIGEST_SIZE], usize)>,
+    leaf_indices: Vec<usize>,
 }
 
 impl Air for MerkleAir {
@@ -69,12 +138,34 @@ impl Air for MerkleAir {
     // CONSTRUCTOR
     // --------------------------------------------------------------------------------------------
     fn new(trace_info: TraceInfo, pub_inputs: PublicInputs, options: ProofOptions) -> Self {
-        let degrees = transition_constraint_degrees();
+        assert!(
+            pub_inputs.trees.is_empty() || pub_inputs.trees.len() == pub_inputs.voting_keys.len(),
+            "Number of trees must match the number of voting keys."
+        );
+        assert!(
+            is_supported_depth(pub_inputs.depth),
+            "Unsupported Merkle proof depth."
+        );
+        assert!(
+            pub_inputs.leaf_indices.is_empty()
+                || pub_inputs.leaf_indices.len() == pub_inputs.voting_keys.len(),
+            "Number of leaf indices must match the number of voting keys."
+        );
+        // the depth mask repeats every cycle when all keys share the same depth
+        let depth_mask_period = if pub_inputs.trees.is_empty() {
+            merkle_cycle_length(pub_inputs.depth)
+        } else {
+            trace_info.length()
+        };
+        let degrees = transition_constraint_degrees_with_depth(depth_mask_period);
         assert_eq!(TRACE_WIDTH, trace_info.width());
         MerkleAir {
             context: AirContext::new(trace_info, degrees, options),
             tree_root: pub_inputs.tree_root,
             voting_keys: pub_inputs.voting_keys,
+            depth: pub_inputs.depth,
+            trees: pub_inputs.trees,
+            leaf_indices: pub_inputs.leaf_indices,
         }
     }
 
@@ -95,111 +186,303 @@ impl Air for MerkleAir {
         debug_assert_eq!(TRACE_WIDTH, next.len());
 
         // split periodic values into masks and Rescue round constants
-        let hash_flag = periodic_values[0];
-        let cycle_mask = periodic_values[1];
-        let ark = &periodic_values[2..];
-
-        // when hash_flag = 1, constraints for Rescue round are enforced
-        rescue::enforce_round(
-            &mut result[1..HASH_STATE_WIDTH + 1],
-            &current[1..HASH_STATE_WIDTH + 1],
-            &next[1..HASH_STATE_WIDTH + 1],
-            ark,
-            cycle_mask * hash_flag,
+        evaluate_transition_constraints_with_depth(
+            result,
+            current,
+            next,
+            periodic_values[0],
+            periodic_values[1],
+            periodic_values[2],
+            &periodic_values[3..],
         );
+    }
 
-        // when hash_flag = 0, make sure accumulated hash is placed in the right place in the hash
-        // state for the next round of hashing.
-        let hash_init_flag = cycle_mask * not(hash_flag);
-        let hash_index_bit = next[0];
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        let mut assertions = if self.trees.is_empty() {
+            build_assertions_with_depth(&self.tree_root, &self.voting_keys, self.depth)
+        } else {
+            build_assertions_with_trees(&self.voting_keys, &self.trees, self.depth)
+        };
+        assertions.append(&mut build_leaf_index_assertions(
+            &self.leaf_indices,
+            &self.trees,
+            self.depth,
+        ));
+        assertions
+    }
 
-        // ensure that index bit is binary
-        result.agg_constraint(0, hash_init_flag, is_binary(hash_index_bit));
+    fn get_periodic_column_values(&self) -> Vec<Vec<Self::BaseField>> {
+        let mut columns = build_periodic_columns_with_depth(self.depth);
+        let depth_mask = if self.trees.is_empty() {
+            columns[1].clone()
+        } else {
+            self.trees
+                .iter()
+                .flat_map(|&(_, depth)| build_depth_mask(depth, self.depth))
+                .collect()
+        };
+        columns.insert(2, depth_mask);
+        columns
+    }
+}
 
-        // if index bit = 0, accumulated hash remains unchanged.
-        field::enforce_copy::<HASH_RATE_WIDTH, E>(
-            &mut result[1..HASH_RATE_WIDTH + 1],
-            &current[1..HASH_RATE_WIDTH + 1],
-            &next[1..HASH_RATE_WIDTH + 1],
-            hash_init_flag * not(hash_index_bit),
-        );
+// HELPER FUNCTIONS
+// ------------------------------------------------------------------------------------------------
 
-        // if index bit = 1, accumulated hash is stored in capacity registers.
-        field::enforce_copy::<HASH_RATE_WIDTH, E>(
-            &mut result[HASH_RATE_WIDTH + 1..HASH_STATE_WIDTH + 1],
-            &current[1..HASH_RATE_WIDTH + 1],
-            &next[HASH_RATE_WIDTH + 1..HASH_STATE_WIDTH + 1],
-            hash_init_flag * hash_index_bit,
-        );
-    }
+/// Evaluate the transition constraints of Merkle path verification,
+/// shared with the combined registration AIR
+pub(crate) fn evaluate_transition_constraints<E: FieldElement + From<BaseElement>>(
+    result: &mut [E],
+    current: &[E],
+    next: &[E],
+    periodic_values: &[E],
+) {
+    // split periodic values into masks and Rescue round constants
+    // all paths have depth TREE_DEPTH, so the depth mask is the cycle mask
+    evaluate_transition_constraints_with_depth(
+        result,
+        current,
+        next,
+        periodic_values[0],
+        periodic_values[1],
+        periodic_values[1],
+        &periodic_values[2..],
+    );
+}
 
-    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
-        let mut assertions = vec![];
-
-        // START OF TRACE
-        // ensure that the hash pf public key is initiated correctly
-        for (key_index, voting_key) in self.voting_keys.iter().enumerate() {
-            for i in 0..POINT_COORDINATE_WIDTH {
-                assertions.push(Assertion::single(
-                    i + 1,
-                    key_index * MERKLE_CYCLE_LENGTH,
-                    voting_key[i],
-                ));
-                assertions.push(Assertion::single(
-                    i + HASH_RATE_WIDTH + 1,
-                    key_index * MERKLE_CYCLE_LENGTH + HASH_CYCLE_LENGTH,
-                    voting_key[i + POINT_COORDINATE_WIDTH],
-                ));
-            }
-            for i in POINT_COORDINATE_WIDTH + 1..HASH_STATE_WIDTH + 1 {
-                assertions.push(Assertion::single(
-                    i,
-                    key_index * MERKLE_CYCLE_LENGTH,
-                    BaseElement::ZERO,
-                ));
-            }
+/// Evaluate the transition constraints of Merkle path verification against
+/// trees of any depth. The depth mask is set on the steps of a cycle before the
+/// root of the tree of the voter is reached, after which the root is carried
+/// over to the end of the cycle.
+pub(crate) fn evaluate_transition_constraints_with_depth<E: FieldElement + From<BaseElement>>(
+    result: &mut [E],
+    current: &[E],
+    next: &[E],
+    hash_flag: E,
+    cycle_mask: E,
+    depth_mask: E,
+    ark: &[E],
+) {
+    // when hash_flag = 1, constraints for Rescue round are enforced
+    rescue::enforce_round(
+        &mut result[1..HASH_STATE_WIDTH + 1],
+        &current[1..HASH_STATE_WIDTH + 1],
+        &next[1..HASH_STATE_WIDTH + 1],
+        ark,
+        depth_mask * hash_flag,
+    );
+
+    // once the root is reached, it remains unchanged until the end of the cycle
+    field::enforce_copy::<HASH_RATE_WIDTH, E>(
+        &mut result[1..HASH_RATE_WIDTH + 1],
+        &current[1..HASH_RATE_WIDTH + 1],
+        &next[1..HASH_RATE_WIDTH + 1],
+        cycle_mask - depth_mask,
+    );
+
+    // when hash_flag = 0, make sure accumulated hash is placed in the right place in the hash
+    // state for the next round of hashing.
+    let hash_init_flag = depth_mask * not(hash_flag);
+    let hash_index_bit = next[0];
+
+    // ensure that index bit is binary
+    result.agg_constraint(0, hash_init_flag, is_binary(hash_index_bit));
+
+    // if index bit = 0, accumulated hash remains unchanged.
+    field::enforce_copy::<HASH_RATE_WIDTH, E>(
+        &mut result[1..HASH_RATE_WIDTH + 1],
+        &current[1..HASH_RATE_WIDTH + 1],
+        &next[1..HASH_RATE_WIDTH + 1],
+        hash_init_flag * not(hash_index_bit),
+    );
+
+    // if index bit = 1, accumulated hash is stored in capacity registers.
+    field::enforce_copy::<HASH_RATE_WIDTH, E>(
+        &mut result[HASH_RATE_WIDTH + 1..HASH_STATE_WIDTH + 1],
+        &current[1..HASH_RATE_WIDTH + 1],
+        &next[HASH_RATE_WIDTH + 1..HASH_STATE_WIDTH + 1],
+        hash_init_flag * hash_index_bit,
+    );
+}
+
+/// Build the assertions of Merkle path verification for the given voting keys
+pub(crate) fn build_assertions(
+    tree_root: &[BaseElement; DIGEST_SIZE],
+    voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+) -> Vec<Assertion<BaseElement>> {
+    build_assertions_with_depth(tree_root, voting_keys, TREE_DEPTH)
+}
+
+/// Build the assertions of Merkle path verification for the given voting keys
+/// in a tree of the given depth
+pub(crate) fn build_assertions_with_depth(
+    tree_root: &[BaseElement; DIGEST_SIZE],
+    voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+    depth: usize,
+) -> Vec<Assertion<BaseElement>> {
+    let cycle_length = merkle_cycle_length(depth);
+    let mut assertions = vec![];
+
+    // START OF TRACE
+    // ensure that the hash pf public key is initiated correctly
+    for (key_index, voting_key) in voting_keys.iter().enumerate() {
+        for i in 0..POINT_COORDINATE_WIDTH {
             assertions.push(Assertion::single(
-                0,
-                key_index * MERKLE_CYCLE_LENGTH + HASH_CYCLE_LENGTH,
+                i + 1,
+                key_index * cycle_length,
+                voting_key[i],
+            ));
+            assertions.push(Assertion::single(
+                i + HASH_RATE_WIDTH + 1,
+                key_index * cycle_length + HASH_CYCLE_LENGTH,
+                voting_key[i + POINT_COORDINATE_WIDTH],
+            ));
+        }
+        for i in POINT_COORDINATE_WIDTH + 1..HASH_STATE_WIDTH + 1 {
+            assertions.push(Assertion::single(
+                i,
+                key_index * cycle_length,
                 BaseElement::ZERO,
             ));
         }
+        assertions.push(Assertion::single(
+            0,
+            key_index * cycle_length + HASH_CYCLE_LENGTH,
+            BaseElement::ZERO,
+        ));
+    }
 
-        // END OF TRACE
-        let last_cycle_step = MERKLE_CYCLE_LENGTH - 1;
+    // END OF TRACE
+    let last_cycle_step = cycle_length - 1;
+
+    for i in 0..HASH_RATE_WIDTH {
+        assertions.push(Assertion::periodic(
+            i + 1,
+            last_cycle_step,
+            cycle_length,
+            tree_root[i],
+        ));
+    }
 
+    assertions
+}
+
+/// Build the assertions of Merkle path verification of each voting key
+/// against the root of its own tree
+pub(crate) fn build_assertions_with_trees(
+    voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+    trees: &[([BaseElement; DIGEST_SIZE], usize)],
+    depth: usize,
+) -> Vec<Assertion<BaseElement>> {
+    // the root assertions for the first key are replaced below
+    let mut assertions = build_assertions_with_depth(&trees[0].0, voting_keys, depth);
+    assertions.truncate(assertions.len() - HASH_RATE_WIDTH);
+
+    // END OF CYCLES
+    let cycle_length = merkle_cycle_length(depth);
+    let last_cycle_step = cycle_length - 1;
+
+    for (key_index, (tree_root, _)) in trees.iter().enumerate() {
         for i in 0..HASH_RATE_WIDTH {
-            assertions.push(Assertion::periodic(
+            assertions.push(Assertion::single(
                 i + 1,
-                last_cycle_step,
-                MERKLE_CYCLE_LENGTH,
-                self.tree_root[i],
+                key_index * cycle_length + last_cycle_step,
+                tree_root[i],
             ));
         }
-
-        assertions
     }
 
-    fn get_periodic_column_values(&self) -> Vec<Vec<Self::BaseField>> {
-        let mut result = vec![rescue::HASH_CYCLE_MASK.to_vec()];
-        let mut cycle_mask = vec![BaseElement::ONE; MERKLE_CYCLE_LENGTH];
-        cycle_mask[MERKLE_CYCLE_LENGTH - 1] = BaseElement::ZERO;
-        result.push(cycle_mask);
-        result.append(&mut rescue::get_round_constants());
-        result
+    assertions
+}
+
+/// Build the assertions binding the Merkle path of each voting key to its leaf index.
+/// The index bit of each level of the tree is set in the first register at the start
+/// of the hash cycle of that level, after the two hash cycles of the voting key.
+pub(crate) fn build_leaf_index_assertions(
+    leaf_indices: &[usize],
+    trees: &[([BaseElement; DIGEST_SIZE], usize)],
+    depth: usize,
+) -> Vec<Assertion<BaseElement>> {
+    let cycle_length = merkle_cycle_length(depth);
+    let mut assertions = vec![];
+
+    for (key_index, &leaf_index) in leaf_indices.iter().enumerate() {
+        let key_depth = trees.get(key_index).map_or(depth, |&(_, depth)| depth);
+        for level in 0..key_depth {
+            assertions.push(Assertion::single(
+                0,
+                key_index * cycle_length + (level + 2) * HASH_CYCLE_LENGTH,
+                BaseElement::from(((leaf_index >> level) & 1) as u8),
+            ));
+        }
     }
+
+    assertions
+}
+
+/// Build the assertions binding the leaf of each voting key to its group label, which
+/// is inserted in the last capacity register with the y coordinate of the voting key
+pub(crate) fn build_group_assertions(groups: &[u32], depth: usize) -> Vec<Assertion<BaseElement>> {
+    let cycle_length = merkle_cycle_length(depth);
+    groups
+        .iter()
+        .enumerate()
+        .map(|(key_index, &group)| {
+            Assertion::single(
+                HASH_STATE_WIDTH,
+                key_index * cycle_length + HASH_CYCLE_LENGTH,
+                BaseElement::from(group),
+            )
+        })
+        .collect()
+}
+
+/// Build the depth mask of a cycle verifying Merkle paths of depth cycle_depth
+/// for a path of the given depth. The root is obtained after hashing the voting
+/// key (two hash cycles) and one hash cycle per level of the tree.
+pub(crate) fn build_depth_mask(depth: usize, cycle_depth: usize) -> Vec<BaseElement> {
+    let root_step = merkle_cycle_length(depth) - 1;
+    let mut depth_mask = vec![BaseElement::ZERO; merkle_cycle_length(cycle_depth)];
+    depth_mask[..root_step].fill(BaseElement::ONE);
+    depth_mask
+}
+
+/// Build the periodic columns of Merkle path verification
+pub(crate) fn build_periodic_columns() -> Vec<Vec<BaseElement>> {
+    build_periodic_columns_with_depth(TREE_DEPTH)
+}
+
+/// Build the periodic columns of Merkle path verification in cycles
+/// verifying Merkle paths of the given depth
+pub(crate) fn build_periodic_columns_with_depth(depth: usize) -> Vec<Vec<BaseElement>> {
+    let cycle_length = merkle_cycle_length(depth);
+    let mut result = vec![rescue::HASH_CYCLE_MASK.to_vec()];
+    let mut cycle_mask = vec![BaseElement::ONE; cycle_length];
+    cycle_mask[cycle_length - 1] = BaseElement::ZERO;
+    result.push(cycle_mask);
+    result.append(&mut rescue::get_round_constants());
+    result
 }
 
 pub(crate) fn transition_constraint_degrees() -> Vec<TransitionConstraintDegree> {
+    // all paths have depth TREE_DEPTH, so the depth mask is the cycle mask
+    transition_constraint_degrees_with_depth(MERKLE_CYCLE_LENGTH)
+}
+
+/// Transition constraint degrees of Merkle path verification against trees of any
+/// depth, where the depth mask repeats every depth_mask_period steps
+pub(crate) fn transition_constraint_degrees_with_depth(
+    depth_mask_period: usize,
+) -> Vec<TransitionConstraintDegree> {
     // First scalar multiplication
     let mut degrees = vec![TransitionConstraintDegree::with_cycles(
         2,
-        vec![HASH_CYCLE_LENGTH, MERKLE_CYCLE_LENGTH],
+        vec![HASH_CYCLE_LENGTH, depth_mask_period],
     )];
     degrees.append(&mut vec![
         TransitionConstraintDegree::with_cycles(
             3,
-            vec![HASH_CYCLE_LENGTH, MERKLE_CYCLE_LENGTH]
+            vec![HASH_CYCLE_LENGTH, depth_mask_period]
         );
         TRACE_WIDTH - 1
     ]);
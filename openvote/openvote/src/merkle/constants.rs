@@ -19,17 +19,23 @@ pub(crate) use crate::utils::rescue::{
 /// Layout: | position bit | hash state |
 pub const TRACE_WIDTH: usize = HASH_STATE_WIDTH + 1;
 
-///
-// pub const
-
 /// Depth of Merkle tree (root is excluded)
 /// depth = log_2(no. leaves)
-#[cfg(not(test))]
-pub const TREE_DEPTH: usize = 14;
-
-#[cfg(test)]
+/// Tests use the smaller depths of crate::testing instead
 pub const TREE_DEPTH: usize = 14;
 
 /// Total number of steps in a verification of Merkle proof of membership
 /// Two hash iterations to calculate the hash of public key
-pub const MERKLE_CYCLE_LENGTH: usize = (TREE_DEPTH + 2) * HASH_CYCLE_LENGTH;
+pub const MERKLE_CYCLE_LENGTH: usize = merkle_cycle_length(TREE_DEPTH);
+
+/// Total number of steps in a verification of Merkle proof of membership
+/// in a tree of the given depth
+pub const fn merkle_cycle_length(depth: usize) -> usize {
+    (depth + 2) * HASH_CYCLE_LENGTH
+}
+
+/// Return true if Merkle proofs of the given depth can be verified in cycles
+/// of their own, i.e. if the cycle length is a power of two (depths 2, 6 and 14)
+pub const fn is_supported_depth(depth: usize) -> bool {
+    depth > 0 && depth <= TREE_DEPTH && (depth + 2).is_power_of_two()
+}
@@ -5,6 +5,8 @@
 // LICENSE file in the root directory of this source tree.
 
 use self::constants::*;
+use crate::beacon::{local_randomness, Beacon, BeaconError, BeaconRng, LEAF_SHUFFLE_DOMAIN};
+use crate::compat::proof_options;
 use crate::utils::rescue::{self, Hash, Rescue63};
 use core::usize;
 use log::debug;
@@ -19,20 +21,24 @@ use winterfell::{
 
 pub(crate) mod constants;
 mod trace;
+pub(crate) use trace::{prepare_grouped_hash_message, prepare_hash_message};
 
-mod air;
+pub(crate) mod air;
 pub(crate) use air::{MerkleAir, PublicInputs};
 
 mod prover;
 pub(crate) use prover::MerkleProver;
 
+mod tree;
+pub use tree::MerkleTree;
+
 #[cfg(test)]
 mod tests;
 
 /// Outputs a new `MerkleExample` with `num_keys` Merkle proofs of membership on random public keys.
 pub fn get_example(num_keys: usize) -> MerkleExample {
     MerkleExample::new(
-        ProofOptions::new(
+        proof_options(
             42,
             8,
             0,
@@ -57,24 +63,35 @@ pub struct MerkleExample {
     pub branches: Vec<[BaseElement; TREE_DEPTH * DIGEST_SIZE]>,
     /// Hash index to determine the path
     pub hash_indices: Vec<usize>,
+    /// Depth of Merkle tree
+    pub depth: usize,
 }
 
 impl MerkleExample {
     /// create random public keys and a Merkle tree that contains
     /// these keys
     pub fn new(options: ProofOptions, num_keys: usize) -> MerkleExample {
-        let (tree_root, voting_keys, branches, hash_indices) = build_merkle_tree(num_keys);
+        Self::with_depth(options, num_keys, TREE_DEPTH)
+    }
+
+    /// create random public keys and a Merkle tree of the given depth
+    /// that contains these keys
+    pub fn with_depth(options: ProofOptions, num_keys: usize, depth: usize) -> MerkleExample {
+        let (tree_root, voting_keys, branches, hash_indices) = build_merkle_tree(num_keys, depth);
 
         // verify the Merkle proofs
         #[cfg(feature = "std")]
         let now = Instant::now();
 
-        assert!(naive_verify_merkle_proofs(
-            &tree_root,
-            &voting_keys,
-            &branches,
-            &hash_indices,
-        ));
+        for i in 0..voting_keys.len() {
+            assert!(verify_merkle_proof_with_depth(
+                &tree_root,
+                &voting_keys[i],
+                &branches[i],
+                hash_indices[i],
+                depth,
+            ));
+        }
 
         #[cfg(feature = "std")]
         debug!(
@@ -89,6 +106,7 @@ impl MerkleExample {
             voting_keys,
             branches,
             hash_indices,
+            depth,
         }
     }
 
@@ -98,18 +116,19 @@ impl MerkleExample {
         debug!(
             "Generating proof for proving membership in a Merkle tree of depth {}\n\
             ---------------------",
-            TREE_DEPTH
+            self.depth
         );
         // create the prover
-        let prover = MerkleProver::new(
+        let prover = MerkleProver::with_depth(
             self.options.clone(),
             self.tree_root,
-            self.voting_keys.clone(),
+            &self.voting_keys,
+            self.depth,
         );
 
         // generate the execution trace
         let now = Instant::now();
-        let trace = prover.build_trace(self.branches.clone(), self.hash_indices.clone());
+        let trace = prover.build_trace(&self.branches, &self.hash_indices);
 
         let trace_length = trace.length();
         debug!(
@@ -128,8 +147,11 @@ impl MerkleExample {
         let pub_inputs = PublicInputs {
             tree_root: self.tree_root.clone(),
             voting_keys: self.voting_keys.clone(),
+            depth: self.depth,
+            trees: vec![],
+            leaf_indices: vec![],
         };
-        winterfell::verify::<MerkleAir>(proof, pub_inputs)
+        crate::compat::verify::<MerkleAir>(proof, pub_inputs)
     }
 
     #[cfg(test)]
@@ -140,9 +162,12 @@ impl MerkleExample {
         let mut pub_inputs = PublicInputs {
             tree_root: self.tree_root.clone(),
             voting_keys: self.voting_keys.clone(),
+            depth: self.depth,
+            trees: vec![],
+            leaf_indices: vec![],
         };
         pub_inputs.voting_keys[fault_index][fault_position] += BaseElement::ONE;
-        winterfell::verify::<MerkleAir>(proof, pub_inputs)
+        crate::compat::verify::<MerkleAir>(proof, pub_inputs)
     }
 
     #[cfg(test)]
@@ -154,17 +179,21 @@ impl MerkleExample {
         let pub_inputs = PublicInputs {
             tree_root: wrong_tree_root,
             voting_keys: self.voting_keys.clone(),
+            depth: self.depth,
+            trees: vec![],
+            leaf_indices: vec![],
         };
-        winterfell::verify::<MerkleAir>(proof, pub_inputs)
+        crate::compat::verify::<MerkleAir>(proof, pub_inputs)
     }
 }
 
 // HELPER FUNCTIONS
 // ================================================================================================
-/// Create a random Merkle tree of public keys
+/// Create a random Merkle tree of public keys of the given depth
 /// and return (tree_root, voting_keys, branches, hash_indices)
 fn build_merkle_tree(
     num_keys: usize,
+    depth: usize,
 ) -> (
     [BaseElement; DIGEST_SIZE],
     Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
@@ -173,28 +202,68 @@ fn build_merkle_tree(
 ) {
     let voting_keys = (0..num_keys)
         .into_iter()
-        .map(|_| random_array::<AFFINE_POINT_WIDTH>())
+        .map(|_| random_array::<AFFINE_POINT_WIDTH, _>(&mut OsRng))
         .collect::<Vec<[BaseElement; AFFINE_POINT_WIDTH]>>();
-    let (tree_root, branches, hash_indices) = build_merkle_tree_from(&voting_keys);
-    (tree_root, voting_keys, branches, hash_indices)
+    let (tree, hash_indices) = build_merkle_tree_with_depth(&voting_keys, depth);
+    let branches = hash_indices
+        .iter()
+        .map(|&hash_index| tree.branch(hash_index))
+        .collect();
+    (tree.root(), voting_keys, branches, hash_indices)
 }
 
-pub(crate) fn build_merkle_tree_from(
-    voting_keys: &Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
-) -> (
-    [BaseElement; DIGEST_SIZE],
-    Vec<[BaseElement; TREE_DEPTH * DIGEST_SIZE]>,
-    Vec<usize>,
-) {
-    let num_keys = voting_keys.len();
-    let mut rng = OsRng;
-    let num_leaves = usize::pow(2, TREE_DEPTH as u32);
-    let mut leaves = vec![[BaseElement::ZERO; DIGEST_SIZE]; num_leaves];
+/// Create a Merkle tree with the hashes of voting keys at random leaves
+/// and random values at the other leaves, and return (tree, hash_indices)
+pub(crate) fn build_merkle_tree_with_keys(
+    voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+) -> (MerkleTree, Vec<usize>) {
+    build_merkle_tree_with_depth(voting_keys, TREE_DEPTH)
+}
 
-    let key_hashes = voting_keys
-        .iter()
-        .map(|voting_key| hash_voting_key(voting_key))
-        .collect::<Vec<[BaseElement; DIGEST_SIZE]>>();
+/// Create a Merkle tree of the given depth with the hashes of voting keys at
+/// random leaves and random values at the other leaves, and return (tree, hash_indices)
+pub(crate) fn build_merkle_tree_with_depth(
+    voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+    depth: usize,
+) -> (MerkleTree, Vec<usize>) {
+    let mut rng = BeaconRng::new(&local_randomness(), LEAF_SHUFFLE_DOMAIN, &[]);
+    fill_merkle_tree(voting_keys, depth, &mut rng)
+}
+
+/// Create a Merkle tree of the given depth as build_merkle_tree_with_depth does, placing
+/// the voting keys with the randomness of a beacon round, so that anyone can re-derive
+/// the tree from the voting keys and the round, and return (tree, hash_indices)
+pub fn build_merkle_tree_with_beacon<B: Beacon>(
+    voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+    depth: usize,
+    beacon: &B,
+    round: u64,
+) -> Result<(MerkleTree, Vec<usize>), BeaconError> {
+    let mut rng = beacon.rng(round, LEAF_SHUFFLE_DOMAIN)?;
+    Ok(fill_merkle_tree(voting_keys, depth, &mut rng))
+}
+
+/// Create a Merkle tree as build_merkle_tree_with_keys does, drawing the leaves
+/// of voting keys and the values at the other leaves from the given random number
+/// generator, and return (tree, hash_indices)
+pub fn build_merkle_tree_with_rng<R: RngCore>(
+    voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+    rng: &mut R,
+) -> (MerkleTree, Vec<usize>) {
+    fill_merkle_tree(voting_keys, TREE_DEPTH, rng)
+}
+
+fn fill_merkle_tree<R: RngCore>(
+    voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+    depth: usize,
+    rng: &mut R,
+) -> (MerkleTree, Vec<usize>) {
+    let num_keys = voting_keys.len();
+    let num_leaves = 1 << depth;
+    assert!(
+        num_keys <= num_leaves,
+        "Number of voting keys exceeds the number of leaves."
+    );
 
     let mut hash_indices = Vec::with_capacity(num_keys);
     while hash_indices.len() < num_keys {
@@ -205,21 +274,14 @@ pub(crate) fn build_merkle_tree_from(
         }
     }
 
-    for index in 0..num_leaves {
-        if !hash_indices.contains(&index) {
-            leaves[index] = random_array::<DIGEST_SIZE>();
-        }
-    }
-
-    let mut branches = vec![[BaseElement::ZERO; TREE_DEPTH * DIGEST_SIZE]; num_keys];
-
-    for (&hash_index, key_hash) in hash_indices.iter().zip(key_hashes.into_iter()) {
-        leaves[hash_index] = key_hash;
+    let mut leaves = (0..num_leaves)
+        .map(|_| random_array::<DIGEST_SIZE, _>(rng))
+        .collect::<Vec<[BaseElement; DIGEST_SIZE]>>();
+    for (&hash_index, voting_key) in hash_indices.iter().zip(voting_keys.iter()) {
+        leaves[hash_index] = hash_voting_key(voting_key);
     }
 
-    let tree_root = calculate_merkle_proof(&leaves, &mut branches, &hash_indices, 0);
-
-    (tree_root, branches, hash_indices)
+    (MerkleTree::new(&leaves), hash_indices)
 }
 
 /// Naively verify Merkle proofs of membership
@@ -245,9 +307,54 @@ pub(crate) fn verify_merlke_proof(
     branch: &[BaseElement; TREE_DEPTH * DIGEST_SIZE],
     hash_index: usize,
 ) -> bool {
-    let mut h = hash_voting_key(&voting_key);
+    verify_merkle_proof_with_depth(tree_root, voting_key, branch, hash_index, TREE_DEPTH)
+}
 
-    for i in 0..TREE_DEPTH {
+/// Verify a Merkle proof of a voting key whose leaf commits to the given group label
+pub(crate) fn verify_grouped_merkle_proof(
+    tree_root: &[BaseElement; DIGEST_SIZE],
+    voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    group: u32,
+    branch: &[BaseElement; TREE_DEPTH * DIGEST_SIZE],
+    hash_index: usize,
+) -> bool {
+    verify_merkle_path(
+        tree_root,
+        hash_grouped_voting_key(voting_key, group),
+        branch,
+        hash_index,
+        TREE_DEPTH,
+    )
+}
+
+/// Verify a Merkle proof in a tree of the given depth
+pub(crate) fn verify_merkle_proof_with_depth(
+    tree_root: &[BaseElement; DIGEST_SIZE],
+    voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    branch: &[BaseElement; TREE_DEPTH * DIGEST_SIZE],
+    hash_index: usize,
+    depth: usize,
+) -> bool {
+    verify_merkle_path(
+        tree_root,
+        hash_voting_key(voting_key),
+        branch,
+        hash_index,
+        depth,
+    )
+}
+
+/// Verify the path from a leaf to the root in a tree of the given depth
+fn verify_merkle_path(
+    tree_root: &[BaseElement; DIGEST_SIZE],
+    leaf: [BaseElement; DIGEST_SIZE],
+    branch: &[BaseElement; TREE_DEPTH * DIGEST_SIZE],
+    hash_index: usize,
+    depth: usize,
+) -> bool {
+    let mut h = leaf;
+
+    for i in 0..depth {
         let hash_bit_index = (hash_index >> i) & 1;
         let mut branch_node = [BaseElement::ZERO; DIGEST_SIZE];
         branch_node.copy_from_slice(&branch[i * DIGEST_SIZE..(i + 1) * DIGEST_SIZE]);
@@ -261,47 +368,9 @@ pub(crate) fn verify_merlke_proof(
     h == *tree_root
 }
 
-fn calculate_merkle_proof(
-    tree: &[[BaseElement; DIGEST_SIZE]],
-    branches: &mut Vec<[BaseElement; TREE_DEPTH * DIGEST_SIZE]>,
-    hash_indices: &Vec<usize>,
-    branch_index: usize,
-) -> [BaseElement; DIGEST_SIZE] {
-    if tree.len() == 1 {
-        return tree[0];
-    }
-
-    let half_length = tree.len() / 2;
-    let branch_node_index = log2(half_length) as usize;
-    let left = calculate_merkle_proof(
-        &tree[..half_length],
-        branches,
-        hash_indices,
-        branch_index << 1,
-    );
-    let right = calculate_merkle_proof(
-        &tree[half_length..],
-        branches,
-        hash_indices,
-        (branch_index << 1) + 1,
-    );
-
-    for (branch, &hash_index) in branches.iter_mut().zip(hash_indices.iter()) {
-        let hash_index = hash_index >> branch_node_index;
-        if hash_index >> 1 == branch_index {
-            let bit_index = hash_index & 1;
-            branch[branch_node_index * DIGEST_SIZE..(branch_node_index + 1) * DIGEST_SIZE]
-                .copy_from_slice(if bit_index == 0 { &right } else { &left });
-        }
-    }
-
-    merge_hash(&left, &right)
-}
-
 /// Generate a random array of length NREGS
-fn random_array<const NREGS: usize>() -> [BaseElement; NREGS] {
+fn random_array<const NREGS: usize, R: RngCore>(rng: &mut R) -> [BaseElement; NREGS] {
     let mut point = [BaseElement::ZERO; NREGS];
-    let mut rng = OsRng;
     for i in 0..NREGS {
         point[i] = BaseElement::from(rng.next_u64());
     }
@@ -310,6 +379,15 @@ fn random_array<const NREGS: usize>() -> [BaseElement; NREGS] {
 }
 
 fn hash_voting_key(voting_key: &[BaseElement; AFFINE_POINT_WIDTH]) -> [BaseElement; DIGEST_SIZE] {
+    hash_grouped_voting_key(voting_key, 0)
+}
+
+/// Leaf of a voting key committing to a group label, which takes the last element of
+/// the second message chunk. Leaves of group 0 are the leaves of ungrouped voting keys.
+fn hash_grouped_voting_key(
+    voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    group: u32,
+) -> [BaseElement; DIGEST_SIZE] {
     let mut hash_message = [BaseElement::ZERO; DIGEST_SIZE];
     hash_message[..POINT_COORDINATE_WIDTH].copy_from_slice(&voting_key[..POINT_COORDINATE_WIDTH]);
     let mut h = Rescue63::digest(&hash_message);
@@ -320,7 +398,7 @@ fn hash_voting_key(voting_key: &[BaseElement; AFFINE_POINT_WIDTH]) -> [BaseEleme
         voting_key[POINT_COORDINATE_WIDTH + 3],
         voting_key[POINT_COORDINATE_WIDTH + 4],
         voting_key[POINT_COORDINATE_WIDTH + 5],
-        BaseElement::ZERO,
+        BaseElement::from(group),
     );
     h = Rescue63::merge(&[h, message_chunk]);
 
@@ -5,7 +5,9 @@
 
 use super::{
     constants::*,
-    trace::{init_merkle_verification_state, update_merkle_verification_state},
+    trace::{
+        init_merkle_verification_state, prepare_hash_message, update_merkle_verification_state,
+    },
     BaseElement, FieldElement, MerkleAir, ProofOptions, Prover, PublicInputs, TraceTable,
 };
 
@@ -18,67 +20,132 @@ use alloc::vec::Vec;
 // MERKLE PROVER
 // ================================================================================================
 
-pub struct MerkleProver {
+// inputs are borrowed from the caller to avoid cloning them for large elections
+pub struct MerkleProver<'a> {
     options: ProofOptions,
     tree_root: [BaseElement; DIGEST_SIZE],
-    voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+    voting_keys: &'a [[BaseElement; AFFINE_POINT_WIDTH]],
+    depth: usize,
+    trees: Vec<([BaseElement; DIGEST_SIZE], usize)>,
+    leaf_indices: Vec<usize>,
 }
 
-impl MerkleProver {
+impl<'a> MerkleProver<'a> {
     pub fn new(
         options: ProofOptions,
         tree_root: [BaseElement; DIGEST_SIZE],
-        voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+        voting_keys: &'a [[BaseElement; AFFINE_POINT_WIDTH]],
     ) -> Self {
+        Self::with_depth(options, tree_root, voting_keys, TREE_DEPTH)
+    }
+
+    /// Create a prover of membership in a tree of the given depth, which must
+    /// be supported, e.g. a small depth to speed up tests
+    pub fn with_depth(
+        options: ProofOptions,
+        tree_root: [BaseElement; DIGEST_SIZE],
+        voting_keys: &'a [[BaseElement; AFFINE_POINT_WIDTH]],
+        depth: usize,
+    ) -> Self {
+        assert!(is_supported_depth(depth), "Unsupported Merkle proof depth.");
         Self {
             options,
             tree_root,
             voting_keys,
+            depth,
+            trees: Vec::new(),
+            leaf_indices: Vec::new(),
+        }
+    }
+
+    /// Create a prover of membership of each voting key in its own tree,
+    /// given as (root, depth) with depth at most TREE_DEPTH
+    pub fn with_trees(
+        options: ProofOptions,
+        voting_keys: &'a [[BaseElement; AFFINE_POINT_WIDTH]],
+        trees: Vec<([BaseElement; DIGEST_SIZE], usize)>,
+    ) -> Self {
+        assert_eq!(
+            voting_keys.len(),
+            trees.len(),
+            "Number of trees must match the number of voting keys."
+        );
+        Self {
+            options,
+            tree_root: trees[0].0,
+            voting_keys,
+            depth: TREE_DEPTH,
+            trees,
+            leaf_indices: Vec::new(),
         }
     }
 
+    /// Bind the proof to the leaf index of each voting key, which must be
+    /// the hash indices the trace is built from
+    pub fn with_leaf_indices(mut self, leaf_indices: Vec<usize>) -> Self {
+        assert_eq!(
+            self.voting_keys.len(),
+            leaf_indices.len(),
+            "Number of leaf indices must match the number of voting keys."
+        );
+        self.leaf_indices = leaf_indices;
+        self
+    }
+
     pub fn build_trace(
         &self,
         // contains the siblings of the nodes on the path
         // from root to corresponding public key
-        branches: Vec<[BaseElement; TREE_DEPTH * DIGEST_SIZE]>,
-        hash_indices: Vec<usize>,
+        branches: &[[BaseElement; TREE_DEPTH * DIGEST_SIZE]],
+        hash_indices: &[usize],
+    ) -> TraceTable<BaseElement> {
+        let hash_messages = branches
+            .iter()
+            .zip(self.voting_keys.iter())
+            .map(|(branch, voting_key)| prepare_hash_message(voting_key, branch))
+            .collect::<Vec<_>>();
+        self.build_trace_from_messages(&hash_messages, hash_indices)
+    }
+
+    /// Build the trace from hash messages prepared beforehand,
+    /// e.g. in a precomputation stage shared with other sub-proofs
+    pub fn build_trace_from_messages(
+        &self,
+        hash_messages: &[[BaseElement; (TREE_DEPTH + 1) * DIGEST_SIZE]],
+        hash_indices: &[usize],
     ) -> TraceTable<BaseElement> {
         debug_assert!(
-            branches.len().is_power_of_two(),
+            hash_messages.len().is_power_of_two(),
             "Number of Merkle proofs to verify must be a power of two."
         );
         // allocate memory to hold the trace table
-        let trace_length = branches.len() * MERKLE_CYCLE_LENGTH;
+        let cycle_length = merkle_cycle_length(self.depth);
+        let trace_length = hash_messages.len() * cycle_length;
         let mut trace = TraceTable::new(TRACE_WIDTH, trace_length);
 
-        trace
-            .fragments(MERKLE_CYCLE_LENGTH)
-            .for_each(|mut merkle_trace| {
-                let i = merkle_trace.index();
-
-                let hash_index = hash_indices[i] << 1;
-                let voting_key = self.voting_keys[i];
-                let mut hash_message = [BaseElement::ZERO; (TREE_DEPTH + 1) * DIGEST_SIZE];
-                hash_message[..POINT_COORDINATE_WIDTH]
-                    .copy_from_slice(&voting_key[POINT_COORDINATE_WIDTH..AFFINE_POINT_WIDTH]);
-                hash_message[DIGEST_SIZE..].copy_from_slice(&branches[i]);
-
-                merkle_trace.fill(
-                    |state| {
-                        init_merkle_verification_state(&voting_key, state);
-                    },
-                    |step, state| {
-                        update_merkle_verification_state(step, &hash_message, hash_index, state);
-                    },
-                );
-            });
+        trace.fragments(cycle_length).for_each(|mut merkle_trace| {
+            let i = merkle_trace.index();
+
+            let hash_index = hash_indices[i] << 1;
+            let voting_key = self.voting_keys[i];
+            let hash_message = &hash_messages[i];
+            let depth = self.trees.get(i).map_or(self.depth, |&(_, depth)| depth);
+
+            merkle_trace.fill(
+                |state| {
+                    init_merkle_verification_state(&voting_key, state);
+                },
+                |step, state| {
+                    update_merkle_verification_state(step, hash_message, hash_index, depth, state);
+                },
+            );
+        });
 
         trace
     }
 }
 
-impl Prover for MerkleProver {
+impl<'a> Prover for MerkleProver<'a> {
     type BaseField = BaseElement;
     type Air = MerkleAir;
     type Trace = TraceTable<BaseElement>;
@@ -86,7 +153,10 @@ impl Prover for MerkleProver {
     fn get_pub_inputs(&self, _trace: &Self::Trace) -> PublicInputs {
         PublicInputs {
             tree_root: self.tree_root,
-            voting_keys: self.voting_keys.clone(),
+            voting_keys: self.voting_keys.to_vec(),
+            depth: self.depth,
+            trees: self.trees.clone(),
+            leaf_indices: self.leaf_indices.clone(),
         }
     }
 
@@ -4,7 +4,12 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use winterfell::{FieldExtension, HashFunction, ProofOptions};
+use crate::testing::{
+    strategies::{digest, point},
+    test_options as build_options, MEDIUM_TREE_DEPTH, SMALL_TREE_DEPTH,
+};
+use proptest::{collection::vec, prelude::*, sample::select};
+use winterfell::{Deserializable, Serializable, SliceReader};
 
 #[test]
 fn merkle_test_proof_verification() {
@@ -15,21 +20,29 @@ fn merkle_test_proof_verification() {
 
 #[test]
 fn merkle_test_proof_verification_quadratic_extension() {
-    let merkle = Box::new(super::MerkleExample::new(build_options(2), 8));
+    let merkle = Box::new(super::MerkleExample::with_depth(
+        build_options(2),
+        8,
+        MEDIUM_TREE_DEPTH,
+    ));
     let proof = merkle.prove();
     assert!(merkle.verify(proof).is_ok());
 }
 
 #[test]
 fn merkle_test_proof_verification_cubic_extension() {
-    let merkle = Box::new(super::MerkleExample::new(build_options(3), 8));
+    let merkle = Box::new(super::MerkleExample::with_depth(
+        build_options(3),
+        8,
+        MEDIUM_TREE_DEPTH,
+    ));
     let proof = merkle.prove();
     assert!(merkle.verify(proof).is_ok());
 }
 
 #[test]
 fn merkle_test_proof_verification_wrong_voting_key() {
-    let merkle = super::MerkleExample::new(build_options(1), 8);
+    let merkle = crate::testing::small_merkle_example(4);
     let proof = merkle.prove();
     let verified = merkle.verify_with_wrong_voting_key(proof);
     assert!(verified.is_err());
@@ -37,24 +50,238 @@ fn merkle_test_proof_verification_wrong_voting_key() {
 
 #[test]
 fn merkle_test_proof_verification_wrong_root() {
-    let merkle = super::MerkleExample::new(build_options(1), 8);
+    let merkle = crate::testing::small_merkle_example(4);
     let proof = merkle.prove();
     let verified = merkle.verify_with_wrong_root(proof);
     assert!(verified.is_err());
 }
 
-fn build_options(extension: u8) -> ProofOptions {
-    ProofOptions::new(
-        42,
-        8,
-        0,
-        HashFunction::Blake3_192,
-        match extension {
-            2 => FieldExtension::Quadratic,
-            3 => FieldExtension::Cubic,
-            _ => FieldExtension::None,
-        },
-        4,
-        256,
+#[test]
+fn merkle_test_tree_branches() {
+    let merkle = crate::testing::small_merkle_example(8);
+    let (tree, hash_indices) = super::build_merkle_tree_with_keys(&merkle.voting_keys);
+    for (voting_key, &hash_index) in merkle.voting_keys.iter().zip(hash_indices.iter()) {
+        assert_eq!(tree.find(voting_key), Some(hash_index));
+        assert!(super::verify_merlke_proof(
+            &tree.root(),
+            voting_key,
+            &tree.branch(hash_index),
+            hash_index
+        ));
+    }
+
+    let restored = super::MerkleTree::read_from(&mut SliceReader::new(&tree.to_bytes())).unwrap();
+    assert_eq!(restored.root(), tree.root());
+}
+
+#[test]
+fn merkle_test_grouped_leaves() {
+    use super::MerkleTree;
+
+    let merkle = crate::testing::small_merkle_example(2);
+    let voting_key = merkle.voting_keys[0];
+    assert_eq!(
+        MerkleTree::leaf_of_grouped(&voting_key, 0),
+        MerkleTree::leaf_of(&voting_key)
+    );
+    assert_ne!(
+        MerkleTree::leaf_of_grouped(&voting_key, 1),
+        MerkleTree::leaf_of(&voting_key)
+    );
+
+    // leaves of grouped voters commit to their group label
+    let mut leaf_groups = vec![0; MerkleTree::NUM_LEAVES];
+    leaf_groups[2] = 7;
+    let mut leaves = vec![[Default::default(); super::constants::DIGEST_SIZE]; leaf_groups.len()];
+    leaves[2] = MerkleTree::leaf_of_grouped(&voting_key, 7);
+    let tree = MerkleTree::new(&leaves);
+    assert_eq!(tree.find(&voting_key), None);
+    assert_eq!(tree.find_grouped(&voting_key, &leaf_groups), Some(2));
+    assert!(super::verify_grouped_merkle_proof(
+        &tree.root(),
+        &voting_key,
+        7,
+        &tree.branch(2),
+        2
+    ));
+    assert!(!super::verify_grouped_merkle_proof(
+        &tree.root(),
+        &voting_key,
+        6,
+        &tree.branch(2),
+        2
+    ));
+    assert!(!super::verify_merlke_proof(
+        &tree.root(),
+        &voting_key,
+        &tree.branch(2),
+        2
+    ));
+}
+
+#[test]
+fn merkle_test_proof_verification_mixed_depths() {
+    use super::{
+        build_merkle_tree_with_keys, constants::*, random_array, MerkleAir, MerkleProver,
+        MerkleTree, PublicInputs,
+    };
+    use winterfell::Prover;
+
+    // two keys in the main roll and two keys in a supplemental roll of depth 3
+    let voting_keys = (0..4)
+        .map(|_| random_array::<AFFINE_POINT_WIDTH>())
+        .collect::<Vec<_>>();
+    let (main_roll, main_indices) = build_merkle_tree_with_keys(&voting_keys[..2]);
+    let mut leaves = (0..8)
+        .map(|_| random_array::<DIGEST_SIZE>())
+        .collect::<Vec<_>>();
+    leaves[1] = MerkleTree::leaf_of(&voting_keys[2]);
+    leaves[6] = MerkleTree::leaf_of(&voting_keys[3]);
+    let supplemental_roll = MerkleTree::new(&leaves);
+    assert_eq!(supplemental_roll.depth(), 3);
+
+    let hash_indices = vec![main_indices[0], main_indices[1], 1, 6];
+    let branches = vec![
+        main_roll.branch(hash_indices[0]),
+        main_roll.branch(hash_indices[1]),
+        supplemental_roll.branch(hash_indices[2]),
+        supplemental_roll.branch(hash_indices[3]),
+    ];
+    let mut trees = vec![
+        (main_roll.root(), TREE_DEPTH),
+        (main_roll.root(), TREE_DEPTH),
+        (supplemental_roll.root(), 3),
+        (supplemental_roll.root(), 3),
+    ];
+
+    let prover = MerkleProver::with_trees(build_options(1), &voting_keys, trees.clone());
+    let trace = prover.build_trace(&branches, &hash_indices);
+    let proof = prover.prove(trace).unwrap();
+
+    let pub_inputs = PublicInputs {
+        tree_root: main_roll.root(),
+        voting_keys: voting_keys.clone(),
+        depth: TREE_DEPTH,
+        trees: trees.clone(),
+        leaf_indices: vec![],
+    };
+    assert!(crate::compat::verify::<MerkleAir>(proof.clone(), pub_inputs).is_ok());
+
+    // claiming the wrong depth for a supplemental key fails
+    trees[2].1 = 4;
+    let pub_inputs = PublicInputs {
+        tree_root: main_roll.root(),
+        voting_keys,
+        depth: TREE_DEPTH,
+        trees,
+        leaf_indices: vec![],
+    };
+    assert!(crate::compat::verify::<MerkleAir>(proof, pub_inputs).is_err());
+}
+
+#[test]
+fn merkle_test_proof_verification_small_depth() {
+    use super::{constants::*, MerkleAir, PublicInputs};
+
+    let merkle = crate::testing::small_merkle_example(4);
+    assert_eq!(merkle.depth, SMALL_TREE_DEPTH);
+    let proof = merkle.prove();
+    assert!(merkle.verify(proof.clone()).is_ok());
+
+    // the depth is bound to the proof through the public inputs
+    let pub_inputs = PublicInputs {
+        tree_root: merkle.tree_root,
+        voting_keys: merkle.voting_keys.clone(),
+        depth: TREE_DEPTH,
+        trees: vec![],
+        leaf_indices: vec![],
+    };
+    assert!(crate::compat::verify::<MerkleAir>(proof, pub_inputs).is_err());
+
+    let pub_inputs = PublicInputs {
+        tree_root: merkle.tree_root,
+        voting_keys: merkle.voting_keys.clone(),
+        depth: SMALL_TREE_DEPTH,
+        trees: vec![],
+        leaf_indices: vec![],
+    };
+    let restored = PublicInputs::from_bytes(&pub_inputs.to_bytes()).unwrap();
+    assert_eq!(restored.depth, SMALL_TREE_DEPTH);
+    assert!(!is_supported_depth(3));
+}
+
+#[test]
+fn merkle_test_leaf_index_binding() {
+    use super::{MerkleAir, MerkleProver, PublicInputs};
+    use winterfell::Prover;
+
+    let merkle = crate::testing::small_merkle_example(4);
+    let prover = MerkleProver::with_depth(
+        build_options(1),
+        merkle.tree_root,
+        &merkle.voting_keys,
+        merkle.depth,
     )
+    .with_leaf_indices(merkle.hash_indices.clone());
+    let trace = prover.build_trace(&merkle.branches, &merkle.hash_indices);
+    let proof = prover.prove(trace).unwrap();
+
+    let pub_inputs = PublicInputs {
+        tree_root: merkle.tree_root,
+        voting_keys: merkle.voting_keys.clone(),
+        depth: merkle.depth,
+        trees: vec![],
+        leaf_indices: merkle.hash_indices.clone(),
+    };
+    let restored = PublicInputs::from_bytes(&pub_inputs.to_bytes()).unwrap();
+    assert_eq!(restored.leaf_indices, merkle.hash_indices);
+    assert!(crate::compat::verify::<MerkleAir>(proof.clone(), pub_inputs).is_ok());
+
+    // claiming another position for a voting key fails
+    let mut leaf_indices = merkle.hash_indices.clone();
+    leaf_indices[1] ^= 1;
+    let pub_inputs = PublicInputs {
+        tree_root: merkle.tree_root,
+        voting_keys: merkle.voting_keys.clone(),
+        depth: merkle.depth,
+        trees: vec![],
+        leaf_indices,
+    };
+    assert!(crate::compat::verify::<MerkleAir>(proof, pub_inputs).is_err());
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn merkle_test_pub_inputs_roundtrip(
+        tree_root in digest(),
+        voters in vec((point(), digest(), 1..=super::constants::TREE_DEPTH), 0..6),
+        depth in select(vec![SMALL_TREE_DEPTH, MEDIUM_TREE_DEPTH, super::constants::TREE_DEPTH]),
+        mixed_depths: bool,
+    ) {
+        use super::PublicInputs;
+
+        // keys either all belong to tree_root, or each to its own tree
+        let pub_inputs = PublicInputs {
+            tree_root,
+            voting_keys: voters.iter().map(|voter| voter.0).collect(),
+            depth,
+            trees: match mixed_depths {
+                true => voters
+                    .iter()
+                    .map(|voter| (voter.1, voter.2.min(depth)))
+                    .collect(),
+                false => vec![],
+            },
+            leaf_indices: vec![],
+        };
+        let bytes = pub_inputs.to_bytes();
+        let restored = PublicInputs::from_bytes(&bytes).unwrap();
+        prop_assert_eq!(restored.tree_root, pub_inputs.tree_root);
+        prop_assert_eq!(&restored.voting_keys, &pub_inputs.voting_keys);
+        prop_assert_eq!(restored.depth, pub_inputs.depth);
+        prop_assert_eq!(&restored.trees, &pub_inputs.trees);
+        prop_assert_eq!(restored.to_bytes(), bytes);
+    }
 }
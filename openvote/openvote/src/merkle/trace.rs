@@ -30,8 +30,14 @@ pub(crate) fn update_merkle_verification_state(
     step: usize,
     hash_message: &[BaseElement; (TREE_DEPTH + 1) * DIGEST_SIZE],
     hash_index: usize,
+    depth: usize,
     state: &mut [BaseElement],
 ) {
+    // once the root of a tree of smaller depth is reached, the state is left unchanged
+    if step >= (depth + 2) * HASH_CYCLE_LENGTH - 1 {
+        return;
+    }
+
     // calculate Rescue hash of public params
     let rescue_step = step % HASH_CYCLE_LENGTH;
 
@@ -61,3 +67,30 @@ pub(crate) fn update_merkle_verification_state(
         state[0] = hash_index_bit;
     }
 }
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Prepare the message hashed along the Merkle path of a voting key,
+/// i.e. the y coordinate of the key followed by the siblings on the path
+pub(crate) fn prepare_hash_message(
+    voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    branch: &[BaseElement; TREE_DEPTH * DIGEST_SIZE],
+) -> [BaseElement; (TREE_DEPTH + 1) * DIGEST_SIZE] {
+    prepare_grouped_hash_message(voting_key, 0, branch)
+}
+
+/// Prepare the message hashed along the Merkle path of a voting key
+/// whose leaf commits to the given group label, see MerkleTree::leaf_of_grouped
+pub(crate) fn prepare_grouped_hash_message(
+    voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    group: u32,
+    branch: &[BaseElement; TREE_DEPTH * DIGEST_SIZE],
+) -> [BaseElement; (TREE_DEPTH + 1) * DIGEST_SIZE] {
+    let mut hash_message = [BaseElement::ZERO; (TREE_DEPTH + 1) * DIGEST_SIZE];
+    hash_message[..POINT_COORDINATE_WIDTH]
+        .copy_from_slice(&voting_key[POINT_COORDINATE_WIDTH..AFFINE_POINT_WIDTH]);
+    hash_message[POINT_COORDINATE_WIDTH] = BaseElement::from(group);
+    hash_message[DIGEST_SIZE..].copy_from_slice(branch);
+    hash_message
+}
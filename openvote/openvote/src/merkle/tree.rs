@@ -0,0 +1,163 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::{constants::*, hash_grouped_voting_key, hash_voting_key, merge_hash};
+use winterfell::{
+    math::fields::f63::BaseElement, ByteReader, ByteWriter, Deserializable, DeserializationError,
+    Serializable,
+};
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+/// Merkle tree of eligible voters of depth at most TREE_DEPTH, storing all of
+/// its nodes so that branches can be derived on demand
+#[derive(Debug, Clone, PartialEq)]
+pub struct MerkleTree {
+    /// Nodes in breadth-first order: nodes[1] is the root and the
+    /// children of nodes[i] are nodes[2 * i] and nodes[2 * i + 1]
+    nodes: Vec<[BaseElement; DIGEST_SIZE]>,
+}
+
+impl MerkleTree {
+    /// Number of leaves of a tree of depth TREE_DEPTH
+    pub const NUM_LEAVES: usize = 1 << TREE_DEPTH;
+
+    /// Build a tree from its leaves
+    ///
+    /// # Panics
+    /// Panics if the number of leaves is not a power of two between 2 and NUM_LEAVES
+    pub fn new(leaves: &[[BaseElement; DIGEST_SIZE]]) -> Self {
+        let num_leaves = leaves.len();
+        assert!(
+            num_leaves > 1 && num_leaves <= Self::NUM_LEAVES && num_leaves.is_power_of_two(),
+            "Number of leaves must be a power of two between 2 and 2^TREE_DEPTH."
+        );
+        let mut nodes = vec![[BaseElement::default(); DIGEST_SIZE]; 2 * num_leaves];
+        nodes[num_leaves..].copy_from_slice(leaves);
+        for i in (1..num_leaves).rev() {
+            nodes[i] = merge_hash(&nodes[2 * i], &nodes[2 * i + 1]);
+        }
+        Self { nodes }
+    }
+
+    /// Depth of the tree (root is excluded)
+    pub fn depth(&self) -> usize {
+        self.num_leaves().trailing_zeros() as usize
+    }
+
+    /// Number of leaves of the tree
+    pub fn num_leaves(&self) -> usize {
+        self.nodes.len() / 2
+    }
+
+    /// Leaf of a voting key
+    pub fn leaf_of(voting_key: &[BaseElement; AFFINE_POINT_WIDTH]) -> [BaseElement; DIGEST_SIZE] {
+        hash_voting_key(voting_key)
+    }
+
+    /// Leaf of a voting key committing to the group label of the voter,
+    /// which is the leaf of the voting key for group 0
+    pub fn leaf_of_grouped(
+        voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+        group: u32,
+    ) -> [BaseElement; DIGEST_SIZE] {
+        hash_grouped_voting_key(voting_key, group)
+    }
+
+    /// Parent of two sibling nodes
+    pub fn merge(
+        left: &[BaseElement; DIGEST_SIZE],
+        right: &[BaseElement; DIGEST_SIZE],
+    ) -> [BaseElement; DIGEST_SIZE] {
+        merge_hash(left, right)
+    }
+
+    /// Root of the tree
+    pub fn root(&self) -> [BaseElement; DIGEST_SIZE] {
+        self.nodes[1]
+    }
+
+    /// Leaves of the tree
+    pub fn leaves(&self) -> &[[BaseElement; DIGEST_SIZE]] {
+        &self.nodes[self.num_leaves()..]
+    }
+
+    /// Siblings on the path from the leaf at index to the root, padded
+    /// with zeros to TREE_DEPTH siblings for trees of smaller depth
+    pub fn branch(&self, index: usize) -> [BaseElement; TREE_DEPTH * DIGEST_SIZE] {
+        let mut branch = [BaseElement::default(); TREE_DEPTH * DIGEST_SIZE];
+        let mut node = self.num_leaves() + index;
+        for sibling in branch.chunks_mut(DIGEST_SIZE).take(self.depth()) {
+            sibling.copy_from_slice(&self.nodes[node ^ 1]);
+            node >>= 1;
+        }
+        branch
+    }
+
+    /// Replace the leaf at index and recompute the nodes on its path to the root
+    pub(crate) fn set_leaf(&mut self, index: usize, leaf: [BaseElement; DIGEST_SIZE]) {
+        let mut node = self.num_leaves() + index;
+        self.nodes[node] = leaf;
+        while node > 1 {
+            node >>= 1;
+            self.nodes[node] = merge_hash(&self.nodes[2 * node], &self.nodes[2 * node + 1]);
+        }
+    }
+
+    /// Index of the leaf of a voting key, if the voting key is eligible
+    pub fn find(&self, voting_key: &[BaseElement; AFFINE_POINT_WIDTH]) -> Option<usize> {
+        let leaf = Self::leaf_of(voting_key);
+        self.leaves().iter().position(|&l| l == leaf)
+    }
+
+    /// Index of the leaf of a voting key in a tree whose leaves commit to the
+    /// given group labels, one per leaf, if the voting key is eligible
+    pub fn find_grouped(
+        &self,
+        voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+        leaf_groups: &[u32],
+    ) -> Option<usize> {
+        self.leaves()
+            .iter()
+            .zip(leaf_groups.iter())
+            .position(|(&leaf, &group)| leaf == Self::leaf_of_grouped(voting_key, group))
+    }
+}
+
+impl Serializable for MerkleTree {
+    /// Only the depth and the leaves are written, inner nodes are
+    /// recomputed on deserialization
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u8(self.depth() as u8);
+        for leaf in self.leaves().iter() {
+            Serializable::write_batch_into(leaf, target);
+        }
+    }
+}
+
+impl Deserializable for MerkleTree {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let depth = source.read_u8()? as usize;
+        if depth == 0 || depth > TREE_DEPTH {
+            return Err(DeserializationError::InvalidValue(String::from(
+                "Invalid Merkle tree depth.",
+            )));
+        }
+        let elements = BaseElement::read_batch_from(source, (1 << depth) * DIGEST_SIZE)?;
+        let leaves = elements
+            .chunks(DIGEST_SIZE)
+            .map(|chunk| {
+                let mut leaf = [BaseElement::default(); DIGEST_SIZE];
+                leaf.copy_from_slice(chunk);
+                leaf
+            })
+            .collect::<Vec<_>>();
+        Ok(Self::new(&leaves))
+    }
+}
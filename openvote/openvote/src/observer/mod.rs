@@ -0,0 +1,261 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// OBSERVER
+// ================================================================================================
+// A read-only node replaying the public bulletin board of an election. The observer keeps its own
+// mirror of registrations and ballots, verifies each of them naively as it is published, and checks
+// the proofs published by the aggregator against the mirror. Any divergence raises an alert.
+//
+// The observer follows elections which are not bound to a manifest, a signing domain or a phase
+// commitment chain, and in which voters do not commit to their encrypted votes.
+
+use crate::{
+    aggregator::{
+        cast::{CollectorError, EncryptedVote, VoteCollector},
+        register::{RegistarError, Registration, VoterRegistar},
+    },
+    cds::compute_blinding_key,
+    merkle::MerkleTree,
+    utils::{
+        address::Address,
+        ecc::{
+            compress_point, projective_to_elements, AFFINE_POINT_WIDTH, BYTES_PER_COMPRESSED_POINT,
+        },
+    },
+    verifier::{
+        encode_encrypted_votes, read_valid_encrypted_votes, verify_cast_proof,
+        verify_register_proof_with, verify_tally_proof, verify_tally_result, RegisterVerification,
+    },
+};
+use winterfell::{math::fields::f63::BaseElement, ByteWriter};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+#[cfg(test)]
+mod tests;
+
+// BULLETIN BOARD
+// ================================================================================================
+
+/// Entry published on the bulletin board of an election
+#[derive(Debug, Clone)]
+pub enum BulletinEntry {
+    /// Registration accepted by the aggregator
+    Registration(Registration),
+    /// Encrypted vote accepted by the aggregator
+    Ballot(EncryptedVote),
+    /// Proof of the registration phase
+    RegisterProof(Vec<u8>),
+    /// Proof of the vote casting phase
+    CastProof(Vec<u8>),
+    /// Result of the election, with its STARK proof if one was published
+    Tally {
+        /// Number of "yes" votes
+        tally_result: u32,
+        /// Proof of the tally result, if any
+        tally_proof: Option<Vec<u8>>,
+    },
+}
+
+/// Divergence between the bulletin board and the mirror of the observer
+#[derive(Debug, PartialEq)]
+pub enum Alert {
+    /// A published registration does not pass naive verification
+    InvalidRegistration {
+        /// Ethereum address of the registration
+        address: Address,
+        /// Error raised by the mirror
+        error: RegistarError,
+    },
+    /// A published encrypted vote does not pass naive verification
+    InvalidBallot {
+        /// Index of the voter among registered voters
+        voter_index: usize,
+        /// Error raised by the mirror
+        error: CollectorError,
+    },
+    /// An encrypted vote was published before the register proof
+    UnexpectedBallot {
+        /// Index of the voter among registered voters
+        voter_index: usize,
+    },
+    /// The published register proof does not verify
+    InvalidRegisterProof,
+    /// The published cast proof does not verify
+    InvalidCastProof,
+    /// The published tally proof does not verify
+    InvalidTallyProof,
+    /// The voting keys listed by the register proof differ from the mirrored registrations
+    RegistrationsDiverge,
+    /// The number of registered voters is not a power of two greater than 1
+    InvalidNumVoters(usize),
+    /// The blinding key of a voter differs from the one recomputed naively
+    BlindingKeyDiverges(usize),
+    /// The encrypted votes listed by the cast proof differ from the mirrored ballots
+    BallotsDiverge,
+    /// The published tally result does not match the mirrored encrypted votes
+    TallyDiverges {
+        /// Published number of "yes" votes
+        tally_result: u32,
+    },
+}
+
+// OBSERVER
+// ================================================================================================
+
+/// Read-only verification node mirroring the bulletin board of an election
+#[derive(Debug)]
+pub struct Observer {
+    /// Mirror of the registration phase
+    pub registar: VoterRegistar,
+    /// Mirror of the vote casting phase, created once the register proof is published
+    pub collector: Option<VoteCollector>,
+    /// Alerts raised so far, oldest first
+    pub alerts: Vec<Alert>,
+}
+
+impl Observer {
+    /// Create an observer given the Merkle tree of eligible voters
+    pub fn new(tree: MerkleTree, num_elg_voters: usize) -> Self {
+        Self {
+            registar: VoterRegistar::new(tree, num_elg_voters),
+            collector: None,
+            alerts: vec![],
+        }
+    }
+
+    /// Process the next entry of the bulletin board and return the alerts it raised
+    pub fn observe(&mut self, entry: BulletinEntry) -> &[Alert] {
+        let num_alerts = self.alerts.len();
+        match entry {
+            BulletinEntry::Registration(registration) => {
+                if let Err(error) = self.registar.add_registration(registration) {
+                    self.alerts.push(Alert::InvalidRegistration {
+                        address: registration.address,
+                        error,
+                    });
+                }
+            }
+            BulletinEntry::Ballot(encrypted_vote) => self.observe_ballot(encrypted_vote),
+            BulletinEntry::RegisterProof(register_proof) => {
+                self.observe_register_proof(&register_proof)
+            }
+            BulletinEntry::CastProof(cast_proof) => self.observe_cast_proof(&cast_proof),
+            BulletinEntry::Tally {
+                tally_result,
+                tally_proof,
+            } => self.observe_tally(tally_result, tally_proof.as_deref()),
+        }
+        &self.alerts[num_alerts..]
+    }
+
+    /// Return true if no alert was raised so far
+    pub fn is_consistent(&self) -> bool {
+        self.alerts.is_empty()
+    }
+
+    /// Encrypted votes of the mirrored ballots, in the order of voters
+    pub fn encrypted_votes(&self) -> Vec<[BaseElement; AFFINE_POINT_WIDTH]> {
+        match &self.collector {
+            Some(collector) => collector
+                .encrypted_votes
+                .iter()
+                .flatten()
+                .copied()
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    fn observe_ballot(&mut self, encrypted_vote: EncryptedVote) {
+        let voter_index = encrypted_vote.voter_index;
+        match self.collector.as_mut() {
+            Some(collector) => {
+                if let Err(error) = collector.add_encrypted_vote(encrypted_vote) {
+                    self.alerts
+                        .push(Alert::InvalidBallot { voter_index, error });
+                }
+            }
+            None => self.alerts.push(Alert::UnexpectedBallot { voter_index }),
+        }
+    }
+
+    fn observe_register_proof(&mut self, register_proof: &[u8]) {
+        let options = RegisterVerification::new();
+        let report = verify_register_proof_with(&self.registar.elg_root, &options, register_proof);
+        if report.into_outcome() != Ok(true) {
+            self.alerts.push(Alert::InvalidRegisterProof);
+        }
+        // register proofs start with the number of voters and their compressed voting keys
+        let num_voters = self.registar.voting_keys.len();
+        let voting_keys = compress_voting_keys(&self.registar.voting_keys);
+        if register_proof.get(..4) != Some(&(num_voters as u32).to_le_bytes()[..])
+            || register_proof.get(4..4 + voting_keys.len()) != Some(&voting_keys[..])
+        {
+            self.alerts.push(Alert::RegistrationsDiverge);
+        }
+
+        let collector = match VoteCollector::try_new(self.registar.voting_keys.clone()) {
+            Ok(collector) => collector,
+            Err(_) => {
+                self.alerts.push(Alert::InvalidNumVoters(num_voters));
+                return;
+            }
+        };
+        for (i, blinding_key) in collector.blinding_keys.iter().enumerate() {
+            if projective_to_elements(*blinding_key)
+                != compute_blinding_key(&collector.voting_keys, i)
+            {
+                self.alerts.push(Alert::BlindingKeyDiverges(i));
+            }
+        }
+        self.collector = Some(collector);
+    }
+
+    fn observe_cast_proof(&mut self, cast_proof: &[u8]) {
+        let num_voters = self.registar.voting_keys.len() as u32;
+        let voting_keys = [
+            &num_voters.to_be_bytes()[..],
+            &compress_voting_keys(&self.registar.voting_keys),
+        ]
+        .concat();
+        if verify_cast_proof(&voting_keys, cast_proof) != Ok(true) {
+            self.alerts.push(Alert::InvalidCastProof);
+        }
+        match read_valid_encrypted_votes(cast_proof) {
+            Ok(encrypted_votes) if encrypted_votes == self.encrypted_votes() => {}
+            _ => self.alerts.push(Alert::BallotsDiverge),
+        }
+    }
+
+    fn observe_tally(&mut self, tally_result: u32, tally_proof: Option<&[u8]>) {
+        let encrypted_votes = encode_encrypted_votes(&self.encrypted_votes());
+        if verify_tally_result(&encrypted_votes, tally_result) != Ok(true) {
+            self.alerts.push(Alert::TallyDiverges { tally_result });
+        }
+        if let Some(tally_proof) = tally_proof {
+            if verify_tally_proof(&encrypted_votes, tally_result, tally_proof) != Ok(true) {
+                self.alerts.push(Alert::InvalidTallyProof);
+            }
+        }
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Concatenation of compressed voting keys
+fn compress_voting_keys(voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(voting_keys.len() * BYTES_PER_COMPRESSED_POINT);
+    for voting_key in voting_keys.iter() {
+        bytes.write_u8_slice(&compress_point(voting_key));
+    }
+    bytes
+}
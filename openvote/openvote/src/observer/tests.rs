@@ -0,0 +1,109 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+use crate::{
+    aggregator::tally::VoteTallier, merkle::build_merkle_tree_with_keys, schnorr::random_key_pairs,
+    voter::registration_from_kit,
+};
+
+#[test]
+fn observer_test_mirror() {
+    let (secret_keys, voting_keys) = random_key_pairs(2);
+    let (tree, _) = build_merkle_tree_with_keys(&voting_keys);
+    let mut registar = VoterRegistar::new(tree.clone(), 2);
+    let mut observer = Observer::new(tree, 2);
+
+    // registrations are mirrored, and tampered ones raise an alert
+    let mut registrations = vec![];
+    for (voting_key, &secret_key) in voting_keys.iter().zip(secret_keys.iter()) {
+        let kit = registar.registration_kit(voting_key).unwrap();
+        registrations.push(registration_from_kit(&kit, secret_key, Address::random()));
+    }
+    let mut tampered = registrations[1];
+    tampered.signature.1 = tampered.signature.1 + crate::compat::curve::Scalar::one();
+    assert_eq!(
+        observer.observe(BulletinEntry::Registration(tampered)),
+        &[Alert::InvalidRegistration {
+            address: tampered.address,
+            error: RegistarError::InvalidSchnorrSig,
+        }]
+    );
+    observer.alerts.clear();
+
+    // ballots published before the register proof cannot be verified
+    let encrypted_vote = EncryptedVote::cast(0, secret_keys[0], &voting_keys, true);
+    assert_eq!(
+        observer.observe(BulletinEntry::Ballot(encrypted_vote)),
+        &[Alert::UnexpectedBallot { voter_index: 0 }]
+    );
+    observer.alerts.clear();
+
+    for &registration in registrations.iter() {
+        registar.add_registration(registration).unwrap();
+        assert!(observer
+            .observe(BulletinEntry::Registration(registration))
+            .is_empty());
+    }
+    let register_proof = registar.get_register_proof().unwrap();
+    assert!(observer
+        .observe(BulletinEntry::RegisterProof(register_proof.clone()))
+        .is_empty());
+
+    // a register proof listing other voting keys diverges from the mirror
+    let mut other_proof = register_proof;
+    other_proof[4] ^= 1;
+    let alerts = observer.observe(BulletinEntry::RegisterProof(other_proof));
+    assert!(alerts.contains(&Alert::RegistrationsDiverge));
+    observer.alerts.clear();
+
+    // ballots are mirrored, and invalid ones raise an alert
+    let mut collector = VoteCollector::new(registar.voting_keys.clone());
+    let invalid_vote = EncryptedVote::cast(1, secret_keys[0], &registar.voting_keys, true);
+    assert_eq!(
+        observer.observe(BulletinEntry::Ballot(invalid_vote)),
+        &[Alert::InvalidBallot {
+            voter_index: 1,
+            error: CollectorError::InvalidEncryptedVote,
+        }]
+    );
+    observer.alerts.clear();
+    for (i, &secret_key) in secret_keys.iter().enumerate() {
+        let encrypted_vote = EncryptedVote::cast(i, secret_key, &registar.voting_keys, i == 0);
+        collector.add_encrypted_vote(encrypted_vote).unwrap();
+        assert!(observer
+            .observe(BulletinEntry::Ballot(encrypted_vote))
+            .is_empty());
+    }
+    let cast_proof = collector.get_cast_proof().unwrap();
+    assert!(observer
+        .observe(BulletinEntry::CastProof(cast_proof))
+        .is_empty());
+
+    // the tally is checked against the mirrored encrypted votes
+    let mut tallier = VoteTallier::new(observer.encrypted_votes());
+    let tally_result = tallier.tally_votes().unwrap();
+    assert_eq!(tally_result, 1);
+    let tally_proof = tallier.get_tally_proof().unwrap();
+    assert!(observer
+        .observe(BulletinEntry::Tally {
+            tally_result,
+            tally_proof: Some(tally_proof),
+        })
+        .is_empty());
+    assert!(observer.is_consistent());
+
+    assert_eq!(
+        observer.observe(BulletinEntry::Tally {
+            tally_result: 2,
+            tally_proof: None,
+        }),
+        &[Alert::TallyDiverges { tally_result: 2 }]
+    );
+    assert!(!observer.is_consistent());
+}
@@ -0,0 +1,376 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// PROOF PLANNING
+// ================================================================================================
+// Predicts the trace length of the STARK proof of each phase from the number of voters, and picks
+// the blowup factor, number of queries and field extension reaching a target conjectured security
+// level with the smallest expected proof size. Sizes are upper bounds, as the prover batches the
+// Merkle authentication paths of queries.
+
+use crate::{
+    aggregator::field_extension,
+    cds::constants::{CDS_CYCLE_LENGTH, TRACE_WIDTH as CDS_TRACE_WIDTH},
+    compat::proof_options,
+    register::constants::{REGISTER_CYCLE_LENGTH, TRACE_WIDTH as REGISTER_TRACE_WIDTH},
+    tally::constants::TRACE_WIDTH as TALLY_TRACE_WIDTH,
+};
+use core::cmp;
+use winterfell::{HashFunction, ProofOptions};
+
+#[cfg(test)]
+mod tests;
+
+/// Number of bits of the modulus of the base field
+pub const FIELD_BITS: u32 = 63;
+
+/// Minimum length of an execution trace
+pub const MIN_TRACE_LENGTH: usize = 8;
+
+/// Maximum number of queries of a STARK proof
+pub const MAX_NUM_QUERIES: usize = 128;
+
+/// Maximum blowup factor of a STARK proof
+pub const MAX_BLOWUP_FACTOR: usize = 128;
+
+/// Blowup factor of the constraint evaluations of all phases, i.e. the smallest
+/// blowup factor of their proofs and the number of composition columns
+pub const CONSTRAINT_BLOWUP_FACTOR: usize = 8;
+
+/// Default folding factor of the FRI protocol, as in build_options
+pub const FRI_FOLDING_FACTOR: usize = 4;
+
+/// Default maximum size of the FRI remainder, as in build_options
+pub const FRI_MAX_REMAINDER_SIZE: usize = 256;
+
+/// Folding factors of the FRI protocol supported by the prover
+pub const FRI_FOLDING_FACTORS: [usize; 3] = [4, 8, 16];
+
+/// Smallest maximum size of the FRI remainder supported by the prover
+pub const FRI_MIN_MAX_REMAINDER_SIZE: usize = 32;
+
+/// Largest maximum size of the FRI remainder supported by the prover
+pub const FRI_MAX_MAX_REMAINDER_SIZE: usize = 1024;
+
+/// Number of bytes of a serialized BaseElement
+const BYTES_PER_ELEMENT: usize = 8;
+
+/// Errors raised by the planner
+#[derive(Debug, PartialEq)]
+pub enum PlannerError {
+    /// This error occurs when a proof is planned for no voter
+    NoVoters,
+    /// This error occurs when the given conjectured security level, in bits,
+    /// cannot be reached with the supported hash functions and field extensions
+    UnreachableSecurity(u32),
+    /// This error occurs when the FRI folding factor is not supported, or the maximum
+    /// size of the FRI remainder is not a supported power of two
+    InvalidFri,
+}
+
+// FRI CONFIGURATION
+// ================================================================================================
+
+/// Parameters of the FRI protocol of a STARK proof. Larger folding factors and
+/// remainders speed up proving and verification, at the cost of larger proofs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FriConfig {
+    folding_factor: usize,
+    max_remainder_size: usize,
+}
+
+impl FriConfig {
+    /// Parameters of build_options
+    pub const DEFAULT: Self = Self {
+        folding_factor: FRI_FOLDING_FACTOR,
+        max_remainder_size: FRI_MAX_REMAINDER_SIZE,
+    };
+
+    /// Create FRI parameters, checking that the prover supports them
+    pub fn new(folding_factor: usize, max_remainder_size: usize) -> Result<Self, PlannerError> {
+        if !FRI_FOLDING_FACTORS.contains(&folding_factor)
+            || !max_remainder_size.is_power_of_two()
+            || !(FRI_MIN_MAX_REMAINDER_SIZE..=FRI_MAX_MAX_REMAINDER_SIZE)
+                .contains(&max_remainder_size)
+        {
+            return Err(PlannerError::InvalidFri);
+        }
+        Ok(Self {
+            folding_factor,
+            max_remainder_size,
+        })
+    }
+
+    /// FRI parameters of the given proof options
+    pub fn of(options: &ProofOptions) -> Self {
+        let fri_options = options.to_fri_options();
+        Self {
+            folding_factor: fri_options.folding_factor(),
+            max_remainder_size: fri_options.max_remainder_size(),
+        }
+    }
+
+    /// Folding factor of the FRI protocol
+    pub fn folding_factor(&self) -> usize {
+        self.folding_factor
+    }
+
+    /// Maximum size of the FRI remainder
+    pub fn max_remainder_size(&self) -> usize {
+        self.max_remainder_size
+    }
+}
+
+impl Default for FriConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// FRI parameters of the STARK proof of each phase
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ProofConfig {
+    register: FriConfig,
+    cast: FriConfig,
+    tally: FriConfig,
+}
+
+impl ProofConfig {
+    /// Configuration with the given FRI parameters for the given phase
+    pub fn with_fri(mut self, phase: Phase, fri: FriConfig) -> Self {
+        match phase {
+            Phase::Register => self.register = fri,
+            Phase::Cast => self.cast = fri,
+            Phase::Tally => self.tally = fri,
+        }
+        self
+    }
+
+    /// FRI parameters of the given phase
+    pub fn fri(&self, phase: Phase) -> FriConfig {
+        match phase {
+            Phase::Register => self.register,
+            Phase::Cast => self.cast,
+            Phase::Tally => self.tally,
+        }
+    }
+
+    /// Plan the STARK proof of the given phase with its FRI parameters, see plan
+    pub fn plan(
+        &self,
+        phase: Phase,
+        num_voters: usize,
+        target_security: u32,
+    ) -> Result<ProofPlan, PlannerError> {
+        plan_with_fri(phase, num_voters, target_security, self.fri(phase))
+    }
+}
+
+// PROOF PLAN
+// ================================================================================================
+
+/// Phase of an election proven with a STARK proof
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Verification of Merkle proofs and Schnorr signatures of registrations
+    Register,
+    /// Verification of CDS proofs of encrypted votes
+    Cast,
+    /// Verification of the tally result
+    Tally,
+}
+
+impl Phase {
+    /// Length of the execution trace proving the given number of voters,
+    /// padded to a power of two as the provers do
+    pub fn trace_length(&self, num_voters: usize) -> usize {
+        let num_proofs = cmp::max(num_voters.next_power_of_two(), 2);
+        cmp::max(num_proofs * self.cycle_length(), MIN_TRACE_LENGTH)
+    }
+
+    /// Number of registers of the execution trace
+    pub fn trace_width(&self) -> usize {
+        match self {
+            Phase::Register => REGISTER_TRACE_WIDTH,
+            Phase::Cast => CDS_TRACE_WIDTH,
+            Phase::Tally => TALLY_TRACE_WIDTH,
+        }
+    }
+
+    fn cycle_length(&self) -> usize {
+        match self {
+            Phase::Register => REGISTER_CYCLE_LENGTH,
+            Phase::Cast => CDS_CYCLE_LENGTH,
+            Phase::Tally => 1,
+        }
+    }
+}
+
+/// Parameters of the STARK proof of a phase
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProofPlan {
+    /// Proven phase
+    pub phase: Phase,
+    /// Length of the execution trace
+    pub trace_length: usize,
+    /// Blowup factor of the low-degree extension
+    pub blowup_factor: usize,
+    /// Number of queries
+    pub num_queries: usize,
+    /// Degree of the field extension, see build_options
+    pub extension: u8,
+    /// Conjectured security level of the proof, in bits
+    pub security_level: u32,
+    /// Expected size of the serialized STARK proof, in bytes
+    pub proof_size: usize,
+    /// Options to generate the proof with
+    pub options: ProofOptions,
+}
+
+/// Plan the STARK proof of the given phase for the given number of voters, picking
+/// the parameters with the smallest expected proof size among those reaching the
+/// target conjectured security level, in bits
+pub fn plan(
+    phase: Phase,
+    num_voters: usize,
+    target_security: u32,
+) -> Result<ProofPlan, PlannerError> {
+    plan_with_fri(phase, num_voters, target_security, FriConfig::DEFAULT)
+}
+
+/// Same as plan, with the given FRI parameters
+pub fn plan_with_fri(
+    phase: Phase,
+    num_voters: usize,
+    target_security: u32,
+    fri: FriConfig,
+) -> Result<ProofPlan, PlannerError> {
+    if num_voters == 0 {
+        return Err(PlannerError::NoVoters);
+    }
+    let hash_fn = match target_security {
+        0..=96 => HashFunction::Blake3_192,
+        97..=128 => HashFunction::Blake3_256,
+        _ => return Err(PlannerError::UnreachableSecurity(target_security)),
+    };
+
+    let trace_length = phase.trace_length(num_voters);
+    let mut best_plan: Option<ProofPlan> = None;
+    let mut blowup_factor = CONSTRAINT_BLOWUP_FACTOR;
+    while blowup_factor <= MAX_BLOWUP_FACTOR {
+        let lde_domain_size = trace_length * blowup_factor;
+        let num_queries = ceil_div(target_security as usize + 1, log2(blowup_factor) as usize);
+        let extension =
+            (1..=3).find(|&extension| field_security(extension, lde_domain_size) > target_security);
+
+        if let Some(extension) = extension.filter(|_| num_queries <= MAX_NUM_QUERIES) {
+            let options = proof_options(
+                num_queries,
+                blowup_factor,
+                0,
+                hash_fn,
+                field_extension(extension),
+                fri.folding_factor(),
+                fri.max_remainder_size(),
+            );
+            let proof_size = expected_proof_size(phase, trace_length, &options);
+            if best_plan
+                .as_ref()
+                .map_or(true, |best_plan| proof_size < best_plan.proof_size)
+            {
+                best_plan = Some(ProofPlan {
+                    phase,
+                    trace_length,
+                    blowup_factor,
+                    num_queries,
+                    extension,
+                    security_level: conjectured_security(&options, trace_length),
+                    proof_size,
+                    options,
+                });
+            }
+        }
+        blowup_factor *= 2;
+    }
+
+    best_plan.ok_or(PlannerError::UnreachableSecurity(target_security))
+}
+
+/// Conjectured security level, in bits, of a STARK proof generated
+/// with the given options for an execution trace of the given length
+pub fn conjectured_security(options: &ProofOptions, trace_length: usize) -> u32 {
+    let lde_domain_size = trace_length * options.blowup_factor();
+    let extension = options.field_extension().degree() as u8;
+    let query_security =
+        options.num_queries() as u32 * log2(options.blowup_factor()) + options.grinding_factor();
+    cmp::min(
+        cmp::min(field_security(extension, lde_domain_size), query_security).saturating_sub(1),
+        collision_resistance(options.hash_fn()),
+    )
+}
+
+/// Expected size, in bytes, of the STARK proof of the given phase
+/// generated with the given options for an execution trace of the given length
+pub fn expected_proof_size(phase: Phase, trace_length: usize, options: &ProofOptions) -> usize {
+    let lde_domain_size = trace_length * options.blowup_factor();
+    let num_queries = options.num_queries();
+    let bytes_per_element = BYTES_PER_ELEMENT * options.field_extension().degree() as usize;
+    let bytes_per_digest = digest_size(options.hash_fn());
+    let trace_width = phase.trace_width();
+    let fri_options = options.to_fri_options();
+    let num_fri_layers = fri_options.num_fri_layers(lde_domain_size);
+
+    // commitments to the trace, the constraint evaluations and the FRI layers
+    let mut proof_size = (2 + num_fri_layers) * bytes_per_digest;
+    // out-of-domain frame, over two consecutive rows
+    proof_size += (2 * trace_width + CONSTRAINT_BLOWUP_FACTOR) * bytes_per_element;
+    // trace and constraint evaluations at each query, with their authentication paths
+    proof_size += num_queries
+        * (trace_width * BYTES_PER_ELEMENT
+            + CONSTRAINT_BLOWUP_FACTOR * bytes_per_element
+            + 2 * log2(lde_domain_size) as usize * bytes_per_digest);
+    // folded evaluations at each query and FRI layer, with their authentication paths
+    let mut domain_size = lde_domain_size;
+    for _ in 0..num_fri_layers {
+        domain_size /= fri_options.folding_factor();
+        proof_size += num_queries
+            * (fri_options.folding_factor() * bytes_per_element
+                + log2(domain_size) as usize * bytes_per_digest);
+    }
+    // evaluations of the remainder
+    proof_size + domain_size * bytes_per_element
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn field_security(extension: u8, lde_domain_size: usize) -> u32 {
+    (FIELD_BITS * extension as u32).saturating_sub(log2(lde_domain_size))
+}
+
+fn collision_resistance(hash_fn: HashFunction) -> u32 {
+    match hash_fn {
+        HashFunction::Blake3_192 => 96,
+        _ => 128,
+    }
+}
+
+fn digest_size(hash_fn: HashFunction) -> usize {
+    match hash_fn {
+        HashFunction::Blake3_192 => 24,
+        _ => 32,
+    }
+}
+
+fn ceil_div(a: usize, b: usize) -> usize {
+    (a + b - 1) / b
+}
+
+fn log2(n: usize) -> u32 {
+    n.trailing_zeros()
+}
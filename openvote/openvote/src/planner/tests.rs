@@ -0,0 +1,89 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+use crate::aggregator::build_options;
+
+#[test]
+fn planner_test_trace_length() {
+    assert_eq!(Phase::Register.trace_length(1), 2 * REGISTER_CYCLE_LENGTH);
+    assert_eq!(Phase::Register.trace_length(5), 8 * REGISTER_CYCLE_LENGTH);
+    assert_eq!(Phase::Cast.trace_length(8), 8 * CDS_CYCLE_LENGTH);
+    assert_eq!(Phase::Tally.trace_length(3), MIN_TRACE_LENGTH);
+    assert_eq!(Phase::Tally.trace_length(100), 128);
+}
+
+#[test]
+fn planner_test_plan_reaches_target_security() {
+    for &phase in [Phase::Register, Phase::Cast, Phase::Tally].iter() {
+        for &target_security in [80, 96, 100, 128].iter() {
+            let plan = plan(phase, 16, target_security).unwrap();
+            assert_eq!(plan.trace_length, phase.trace_length(16));
+            assert_eq!(plan.options.num_queries(), plan.num_queries);
+            assert_eq!(plan.options.blowup_factor(), plan.blowup_factor);
+            assert!(plan.security_level >= target_security);
+            assert_eq!(
+                conjectured_security(&plan.options, plan.trace_length),
+                plan.security_level
+            );
+            assert_eq!(
+                expected_proof_size(phase, plan.trace_length, &plan.options),
+                plan.proof_size
+            );
+        }
+    }
+
+    // the plan is no larger than the default options at the same security level
+    let plan = plan(Phase::Register, 16, 96).unwrap();
+    let default_options = build_options(3);
+    assert!(conjectured_security(&default_options, plan.trace_length) >= 96);
+    assert!(
+        plan.proof_size
+            <= expected_proof_size(Phase::Register, plan.trace_length, &default_options)
+    );
+}
+
+#[test]
+fn planner_test_plan_errors() {
+    assert_eq!(plan(Phase::Cast, 0, 96), Err(PlannerError::NoVoters));
+    assert_eq!(
+        plan(Phase::Cast, 8, 200),
+        Err(PlannerError::UnreachableSecurity(200))
+    );
+}
+
+#[test]
+fn planner_test_planned_proof_verification() {
+    let plan = plan(Phase::Tally, 8, 96).unwrap();
+    let tally = crate::tally::TallyExample::new(plan.options.clone(), 8);
+    let proof = tally.prove();
+    assert_eq!(
+        proof.lde_domain_size(),
+        plan.trace_length * plan.blowup_factor
+    );
+    assert!(tally.verify(proof).is_ok());
+}
+
+#[test]
+fn planner_test_fri_config() {
+    assert_eq!(FriConfig::default(), FriConfig::of(&build_options(1)));
+    assert_eq!(FriConfig::new(2, 256), Err(PlannerError::InvalidFri));
+    assert_eq!(FriConfig::new(8, 300), Err(PlannerError::InvalidFri));
+    assert_eq!(FriConfig::new(8, 2048), Err(PlannerError::InvalidFri));
+
+    let fri = FriConfig::new(16, 64).unwrap();
+    let config = ProofConfig::default().with_fri(Phase::Cast, fri);
+    assert_eq!(config.fri(Phase::Cast), fri);
+    assert_eq!(config.fri(Phase::Register), FriConfig::DEFAULT);
+
+    let cast_plan = config.plan(Phase::Cast, 16, 96).unwrap();
+    assert_eq!(FriConfig::of(&cast_plan.options), fri);
+    assert!(cast_plan.security_level >= 96);
+    let register_plan = config.plan(Phase::Register, 16, 96).unwrap();
+    assert_eq!(register_plan, plan(Phase::Register, 16, 96).unwrap());
+}
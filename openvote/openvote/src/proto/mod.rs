@@ -0,0 +1,265 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// PROTOBUF MESSAGES
+// ================================================================================================
+// Bindings generated from proto/openvote.proto, along with conversions between the messages
+// and the aggregator types, so that voter clients only need the schema to interoperate.
+
+use crate::{
+    aggregator::{
+        cast::{EncryptedVote, VoteCollector},
+        ownership::BYTES_PER_OWNERSHIP_SIGNATURE,
+        register::{Registration, VoterRegistar},
+        tally::VoteTallier,
+    },
+    cds::constants::{PROOF_NUM_POINTS, PROOF_NUM_SCALARS},
+    compat::curve::{AffinePoint, ProjectivePoint, Scalar},
+    merkle::constants::TREE_DEPTH,
+    utils::{
+        address::Address,
+        ecc::{
+            compress_point, decompress_point, projective_to_elements, read_canonical_scalar,
+            AFFINE_POINT_WIDTH, BYTES_PER_COMPRESSED_POINT, POINT_COORDINATE_WIDTH,
+        },
+        rescue::DIGEST_SIZE,
+    },
+};
+use core::convert::TryFrom;
+use winterfell::{
+    math::{fields::f63::BaseElement, FieldElement},
+    Deserializable, DeserializationError, Serializable, SliceReader,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Messages generated from the protobuf schema
+#[allow(missing_docs)]
+pub mod messages {
+    include!(concat!(env!("OUT_DIR"), "/openvote.rs"));
+}
+
+pub use messages::Phase;
+
+// REGISTRATION
+// ================================================================================================
+
+impl From<&Registration> for messages::Registration {
+    fn from(registration: &Registration) -> Self {
+        Self {
+            voting_key: compress_point(&registration.voting_key).to_vec(),
+            merkle_branch: elements_to_bytes(&registration.merkle_branch),
+            hash_index: registration.hash_index as u64,
+            signature: Some(messages::Signature {
+                r: elements_to_bytes(&registration.signature.0),
+                s: Serializable::to_bytes(&registration.signature.1),
+            }),
+            address: registration.address.as_bytes().to_vec(),
+            ownership_signature: registration
+                .ownership_signature
+                .map(|signature| signature.to_vec())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl TryFrom<messages::Registration> for Registration {
+    type Error = DeserializationError;
+
+    fn try_from(message: messages::Registration) -> Result<Self, Self::Error> {
+        let signature = message
+            .signature
+            .ok_or_else(|| invalid_value("Missing Schnorr signature."))?;
+        if message.address.len() != Address::len_bytes() {
+            return Err(invalid_value("Invalid Ethereum address."));
+        }
+
+        let ownership_signature = match message.ownership_signature.len() {
+            0 => None,
+            BYTES_PER_OWNERSHIP_SIGNATURE => {
+                let mut signature = [0u8; BYTES_PER_OWNERSHIP_SIGNATURE];
+                signature.copy_from_slice(&message.ownership_signature);
+                Some(signature)
+            }
+            _ => return Err(invalid_value("Invalid ownership signature length.")),
+        };
+
+        let mut merkle_branch = [BaseElement::ZERO; TREE_DEPTH * DIGEST_SIZE];
+        merkle_branch.copy_from_slice(&read_elements(
+            &message.merkle_branch,
+            TREE_DEPTH * DIGEST_SIZE,
+        )?);
+        let mut signature_r = [BaseElement::ZERO; POINT_COORDINATE_WIDTH];
+        signature_r.copy_from_slice(&read_elements(&signature.r, POINT_COORDINATE_WIDTH)?);
+
+        Ok(Self {
+            voting_key: read_point(&message.voting_key)?,
+            merkle_branch,
+            hash_index: message.hash_index as usize,
+            signature: (signature_r, read_scalar(&signature.s)?),
+            address: Address::from_slice(&message.address),
+            ownership_signature,
+        })
+    }
+}
+
+// ENCRYPTED VOTE
+// ================================================================================================
+
+impl From<&EncryptedVote> for messages::EncryptedVote {
+    fn from(encrypted_vote: &EncryptedVote) -> Self {
+        Self {
+            voter_index: encrypted_vote.voter_index as u32,
+            encrypted_vote: compress_point(&projective_to_elements(encrypted_vote.encrypted_vote))
+                .to_vec(),
+            proof_points: encrypted_vote
+                .proof_points
+                .iter()
+                .map(|&point| compress_point(&projective_to_elements(point)).to_vec())
+                .collect(),
+            proof_scalars: encrypted_vote
+                .proof_scalars
+                .iter()
+                .map(Serializable::to_bytes)
+                .collect(),
+            signature: encrypted_vote
+                .signature
+                .map(|signature| messages::Signature {
+                    r: elements_to_bytes(&signature.0),
+                    s: Serializable::to_bytes(&signature.1),
+                }),
+        }
+    }
+}
+
+impl TryFrom<messages::EncryptedVote> for EncryptedVote {
+    type Error = DeserializationError;
+
+    fn try_from(message: messages::EncryptedVote) -> Result<Self, Self::Error> {
+        if message.proof_points.len() != PROOF_NUM_POINTS
+            || message.proof_scalars.len() != PROOF_NUM_SCALARS
+        {
+            return Err(invalid_value("Invalid number of CDS proof elements."));
+        }
+
+        let mut proof_points = [ProjectivePoint::identity(); PROOF_NUM_POINTS];
+        for (proof_point, bytes) in proof_points.iter_mut().zip(message.proof_points.iter()) {
+            *proof_point = read_projective_point(bytes)?;
+        }
+        let mut proof_scalars = [Scalar::zero(); PROOF_NUM_SCALARS];
+        for (proof_scalar, bytes) in proof_scalars.iter_mut().zip(message.proof_scalars.iter()) {
+            *proof_scalar = read_scalar(bytes)?;
+        }
+
+        let encrypted_vote = EncryptedVote::new(
+            message.voter_index as usize,
+            read_projective_point(&message.encrypted_vote)?,
+            proof_points,
+            proof_scalars,
+        );
+        match message.signature {
+            Some(signature) => {
+                let mut signature_r = [BaseElement::ZERO; POINT_COORDINATE_WIDTH];
+                signature_r.copy_from_slice(&read_elements(&signature.r, POINT_COORDINATE_WIDTH)?);
+                Ok(encrypted_vote.with_signature((signature_r, read_scalar(&signature.s)?)))
+            }
+            None => Ok(encrypted_vote),
+        }
+    }
+}
+
+// PHASE STATUS
+// ================================================================================================
+
+impl From<&VoterRegistar> for messages::PhaseStatus {
+    fn from(registar: &VoterRegistar) -> Self {
+        Self {
+            phase: Phase::Registration as i32,
+            num_voters: registar.num_elg_voters as u32,
+            num_submissions: registar.voting_keys.len() as u32,
+            tally_result: None,
+        }
+    }
+}
+
+impl From<&VoteCollector> for messages::PhaseStatus {
+    fn from(collector: &VoteCollector) -> Self {
+        Self {
+            phase: Phase::Casting as i32,
+            num_voters: collector.voting_keys.len() as u32,
+            num_submissions: collector.num_valid_votes as u32,
+            tally_result: None,
+        }
+    }
+}
+
+impl From<&VoteTallier> for messages::PhaseStatus {
+    fn from(tallier: &VoteTallier) -> Self {
+        Self {
+            phase: Phase::Tallying as i32,
+            num_voters: tallier.encrypted_votes.len() as u32,
+            num_submissions: tallier.encrypted_votes.len() as u32,
+            tally_result: tallier.tally_result,
+        }
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+pub(crate) fn invalid_value(message: &str) -> DeserializationError {
+    DeserializationError::InvalidValue(String::from(message))
+}
+
+/// Serialize field elements as 8-byte little-endian integers
+pub(crate) fn elements_to_bytes(elements: &[BaseElement]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(elements.len() * 8);
+    Serializable::write_batch_into(elements, &mut bytes);
+    bytes
+}
+
+/// Read exactly num_elements field elements from bytes
+pub(crate) fn read_elements(
+    bytes: &[u8],
+    num_elements: usize,
+) -> Result<Vec<BaseElement>, DeserializationError> {
+    let mut reader = SliceReader::new(bytes);
+    let elements = BaseElement::read_batch_from(&mut reader, num_elements)?;
+    if bytes.len() != elements.len() * 8 {
+        return Err(invalid_value("Unexpected trailing bytes."));
+    }
+    Ok(elements)
+}
+
+/// Read a Scalar from exactly 32 bytes
+pub(crate) fn read_scalar(bytes: &[u8]) -> Result<Scalar, DeserializationError> {
+    if bytes.len() != 32 {
+        return Err(invalid_value("Invalid scalar length."));
+    }
+    read_canonical_scalar(&mut SliceReader::new(bytes))
+}
+
+/// Read a point in affine coordinates from its compressed form
+pub(crate) fn read_point(
+    bytes: &[u8],
+) -> Result<[BaseElement; AFFINE_POINT_WIDTH], DeserializationError> {
+    if bytes.len() != BYTES_PER_COMPRESSED_POINT {
+        return Err(invalid_value("Invalid compressed point length."));
+    }
+    let mut compressed = [0u8; BYTES_PER_COMPRESSED_POINT];
+    compressed.copy_from_slice(bytes);
+    decompress_point(&compressed).ok_or_else(|| invalid_value("Invalid compressed curve point."))
+}
+
+/// Read a point in projective coordinates from its compressed form
+fn read_projective_point(bytes: &[u8]) -> Result<ProjectivePoint, DeserializationError> {
+    Ok(ProjectivePoint::from(AffinePoint::from_raw_coordinates(
+        read_point(bytes)?,
+    )))
+}
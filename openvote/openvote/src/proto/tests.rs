@@ -0,0 +1,97 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::{messages, Phase};
+use crate::{
+    aggregator::{
+        cast::{EncryptedVote, VoteCollector},
+        register::{Registration, VoterRegistar},
+    },
+    cds::{constants::UNBOUND_CONTEXT, get_example},
+    schnorr::random_key_pair_with_rng,
+};
+use core::convert::TryFrom;
+use prost::Message;
+use rand_core::OsRng;
+
+#[test]
+fn proto_test_registration_round_trip() {
+    let registar = VoterRegistar::get_example(2);
+    let registration = Registration {
+        voting_key: registar.voting_keys[0],
+        merkle_branch: registar.merkle_branch(0),
+        hash_index: registar.hash_indices[0],
+        signature: registar.signatures[0],
+        address: registar.addresses[0],
+        ownership_signature: None,
+    };
+
+    let bytes = messages::Registration::from(&registration).encode_to_vec();
+    let decoded = messages::Registration::decode(bytes.as_slice()).unwrap();
+    let restored = Registration::try_from(decoded).unwrap();
+    assert_eq!(restored.voting_key, registration.voting_key);
+    assert_eq!(restored.merkle_branch, registration.merkle_branch);
+    assert_eq!(restored.hash_index, registration.hash_index);
+    assert_eq!(restored.signature, registration.signature);
+    assert_eq!(restored.address, registration.address);
+}
+
+#[test]
+fn proto_test_encrypted_vote_round_trip() {
+    let (example, (_, encrypted_votes, proof_scalars, proof_points)) = get_example(2);
+    let encrypted_vote =
+        EncryptedVote::new(0, encrypted_votes[0], proof_points[0], proof_scalars[0]);
+
+    let bytes = messages::EncryptedVote::from(&encrypted_vote).encode_to_vec();
+    let decoded = messages::EncryptedVote::decode(bytes.as_slice()).unwrap();
+    let restored = EncryptedVote::try_from(decoded).unwrap();
+    assert_eq!(restored.voter_index(), 0);
+    assert!(restored
+        .verify(&example.voting_keys, &UNBOUND_CONTEXT)
+        .is_ok());
+    assert_eq!(restored.signature(), None);
+
+    let (secret_key, _) = random_key_pair_with_rng(&mut OsRng);
+    let voting_keys = example.voting_keys;
+    let signed_vote = EncryptedVote::cast(0, secret_key, &voting_keys, true);
+    let bytes = messages::EncryptedVote::from(&signed_vote).encode_to_vec();
+    let decoded = messages::EncryptedVote::decode(bytes.as_slice()).unwrap();
+    let restored = EncryptedVote::try_from(decoded).unwrap();
+    assert_eq!(restored.signature(), signed_vote.signature());
+}
+
+#[test]
+fn proto_test_invalid_messages() {
+    let mut message = messages::EncryptedVote {
+        voter_index: 0,
+        encrypted_vote: vec![0u8; 3],
+        proof_points: vec![],
+        proof_scalars: vec![],
+        signature: None,
+    };
+    assert!(EncryptedVote::try_from(message.clone()).is_err());
+    message.proof_points = vec![vec![0u8; 3]; 4];
+    message.proof_scalars = vec![vec![0u8; 32]; 4];
+    assert!(EncryptedVote::try_from(message).is_err());
+
+    let message = messages::Registration {
+        signature: None,
+        ..Default::default()
+    };
+    assert!(Registration::try_from(message).is_err());
+}
+
+#[test]
+fn proto_test_phase_status() {
+    let collector = VoteCollector::get_example(2);
+    let status = messages::PhaseStatus::from(&collector);
+    assert_eq!(status.phase(), Phase::Casting);
+    assert_eq!(status.num_voters, 2);
+    assert_eq!(status.num_submissions, 2);
+    assert_eq!(status.tally_result, None);
+}
@@ -0,0 +1,203 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// ARTIFACT PUBLISHER
+// ================================================================================================
+// Pins the artifacts of an election (registrations, ballot list and proofs) to IPFS or to an
+// S3-compatible bucket, so that observers can fetch them without access to the aggregator. The
+// content identifiers are recorded as events, and their digest can be embedded into the result
+// certificate, so that the certificate also commits to where the artifacts were published.
+
+use crate::{
+    aggregator::{publication::BallotList, register::VoterRegistar},
+    events::{Artifact, Event, EventBus},
+    utils::rescue::{self, digest_bytes},
+};
+use std::time::Duration;
+use winterfell::{
+    crypto::{hashers::Blake3_256, Digest, Hasher},
+    math::fields::f63::BaseElement,
+    Serializable,
+};
+
+#[cfg(test)]
+mod tests;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Timeout of each pinning request
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Boundary of the multipart body of IPFS add requests
+const MULTIPART_BOUNDARY: &str = "openvote-artifact-boundary";
+
+// ERRORS
+// ================================================================================================
+
+/// Errors raised when pinning election artifacts
+#[derive(Debug)]
+pub enum PublisherError {
+    /// This error occurs when the content network cannot be reached or rejects the request
+    Request(String),
+    /// This error occurs when the response of the content network has no valid content identifier
+    InvalidResponse(String),
+}
+
+// PUBLISHER
+// ================================================================================================
+
+/// Content network to which artifacts are pinned
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Backend {
+    /// IPFS node, addressed by the URL of its HTTP API, e.g. `http://127.0.0.1:5001`
+    Ipfs(String),
+    /// S3-compatible bucket accepting unsigned PUT requests, addressed by its URL.
+    /// Objects are named after the hex-encoded Blake3 digest of their content.
+    S3(String),
+}
+
+/// Election artifact pinned to a content network
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinnedArtifact {
+    /// Pinned artifact
+    pub artifact: Artifact,
+    /// Content identifier: IPFS CID, or hex-encoded Blake3 digest for S3 buckets
+    pub cid: String,
+}
+
+/// Client pinning election artifacts to a content network
+#[derive(Debug)]
+pub struct Publisher {
+    backend: Backend,
+    agent: ureq::Agent,
+}
+
+impl Publisher {
+    /// Create a publisher pinning artifacts to the given backend with default settings
+    pub fn new(backend: Backend) -> Self {
+        Self::with_timeout(backend, DEFAULT_TIMEOUT)
+    }
+
+    /// Create a publisher pinning artifacts to the given backend, with the given timeout per request
+    pub fn with_timeout(backend: Backend, timeout: Duration) -> Self {
+        Self {
+            backend,
+            agent: ureq::AgentBuilder::new().timeout(timeout).build(),
+        }
+    }
+
+    /// Pin an artifact and return its content identifier
+    pub fn pin(&self, artifact: Artifact, bytes: &[u8]) -> Result<PinnedArtifact, PublisherError> {
+        let cid = match &self.backend {
+            Backend::Ipfs(api_url) => {
+                let url = format!("{}/api/v0/add?pin=true", api_url.trim_end_matches('/'));
+                let response = self
+                    .agent
+                    .post(&url)
+                    .set(
+                        "Content-Type",
+                        &format!("multipart/form-data; boundary={}", MULTIPART_BOUNDARY),
+                    )
+                    .send_bytes(&multipart_body(artifact, bytes))
+                    .map_err(|e| PublisherError::Request(e.to_string()))?
+                    .into_string()
+                    .map_err(|e| PublisherError::Request(e.to_string()))?;
+                parse_ipfs_response(&response)?
+            }
+            Backend::S3(bucket_url) => {
+                let cid = content_digest(bytes);
+                let url = format!("{}/{}", bucket_url.trim_end_matches('/'), cid);
+                self.agent
+                    .put(&url)
+                    .set("Content-Type", "application/octet-stream")
+                    .send_bytes(bytes)
+                    .map_err(|e| PublisherError::Request(e.to_string()))?;
+                cid
+            }
+        };
+        Ok(PinnedArtifact { artifact, cid })
+    }
+
+    /// Pin an artifact and publish ArtifactPinned, so that its content
+    /// identifier is recorded by the subscribers of the event bus
+    pub fn pin_and_record(
+        &self,
+        bus: &mut EventBus,
+        artifact: Artifact,
+        bytes: &[u8],
+    ) -> Result<PinnedArtifact, PublisherError> {
+        let pinned = self.pin(artifact, bytes)?;
+        bus.publish(Event::ArtifactPinned {
+            artifact,
+            cid: pinned.cid.clone(),
+        });
+        Ok(pinned)
+    }
+
+    /// Pin the registrations, ballot list and proofs of an election and record
+    /// their content identifiers, in this order
+    pub fn publish_election(
+        &self,
+        bus: &mut EventBus,
+        registar: &VoterRegistar,
+        ballot_list: &BallotList,
+        register_proof: &[u8],
+        cast_proof: &[u8],
+    ) -> Result<Vec<PinnedArtifact>, PublisherError> {
+        Ok(vec![
+            self.pin_and_record(bus, Artifact::Registrations, &registar.to_bytes())?,
+            self.pin_and_record(bus, Artifact::BallotList, &ballot_list.to_bytes())?,
+            self.pin_and_record(bus, Artifact::RegisterProof, register_proof)?,
+            self.pin_and_record(bus, Artifact::CastProof, cast_proof)?,
+        ])
+    }
+}
+
+/// Rescue digest of the content identifiers of pinned artifacts, to be embedded into
+/// the result certificate. It hashes one `<artifact>:<cid>` line per artifact, in order.
+pub fn publication_digest(pinned: &[PinnedArtifact]) -> rescue::Hash {
+    let lines = pinned
+        .iter()
+        .map(|p| format!("{:?}:{}\n", p.artifact, p.cid))
+        .collect::<String>();
+    digest_bytes(lines.as_bytes())
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Hex-encoded Blake3 digest of an artifact
+fn content_digest(bytes: &[u8]) -> String {
+    hex::encode(Blake3_256::<BaseElement>::hash(bytes).as_bytes())
+}
+
+/// Multipart body holding a single file, as expected by the IPFS add endpoint
+fn multipart_body(artifact: Artifact, bytes: &[u8]) -> Vec<u8> {
+    let mut body = format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{:?}\"\r\n\
+         Content-Type: application/octet-stream\r\n\r\n",
+        MULTIPART_BOUNDARY, artifact
+    )
+    .into_bytes();
+    body.extend_from_slice(bytes);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", MULTIPART_BOUNDARY).as_bytes());
+    body
+}
+
+/// Extract the CID from the JSON response of the IPFS add endpoint
+fn parse_ipfs_response(response: &str) -> Result<String, PublisherError> {
+    let invalid = || PublisherError::InvalidResponse(response.to_string());
+    let start = response.find(r#""Hash":""#).ok_or_else(invalid)? + r#""Hash":""#.len();
+    let length = response[start..].find('"').ok_or_else(invalid)?;
+    let cid = &response[start..start + length];
+    if cid.is_empty() || !cid.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(invalid());
+    }
+    Ok(cid.to_string())
+}
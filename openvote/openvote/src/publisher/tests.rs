@@ -0,0 +1,60 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+
+#[test]
+fn publisher_test_ipfs_response() {
+    let response = r#"{"Name":"BallotList","Hash":"QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG","Size":"12"}"#;
+    assert_eq!(
+        parse_ipfs_response(response).unwrap(),
+        "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG"
+    );
+
+    assert!(parse_ipfs_response(r#"{"Name":"BallotList"}"#).is_err());
+    assert!(parse_ipfs_response(r#"{"Hash":""}"#).is_err());
+    assert!(parse_ipfs_response(r#"{"Hash":"Qm\nInjected"}"#).is_err());
+}
+
+#[test]
+fn publisher_test_publication_digest() {
+    let registrations = PinnedArtifact {
+        artifact: Artifact::Registrations,
+        cid: content_digest(b"registrations"),
+    };
+    let ballot_list = PinnedArtifact {
+        artifact: Artifact::BallotList,
+        cid: content_digest(b"ballots"),
+    };
+    assert_eq!(registrations.cid.len(), 64);
+    assert_ne!(registrations.cid, ballot_list.cid);
+
+    let digest = publication_digest(&[registrations.clone(), ballot_list.clone()]);
+    assert_eq!(
+        digest,
+        publication_digest(&[registrations.clone(), ballot_list.clone()])
+    );
+    // the digest depends on the order and kind of the artifacts
+    assert_ne!(
+        digest,
+        publication_digest(&[ballot_list.clone(), registrations.clone()])
+    );
+    let mut relabeled = ballot_list.clone();
+    relabeled.artifact = Artifact::CastProof;
+    assert_ne!(digest, publication_digest(&[registrations, relabeled]));
+}
+
+#[test]
+fn publisher_test_multipart_body() {
+    let body = multipart_body(Artifact::CastProof, &[0, 1, 2]);
+    let text = String::from_utf8_lossy(&body);
+    assert!(text.starts_with(&format!("--{}\r\n", MULTIPART_BOUNDARY)));
+    assert!(text.contains(r#"filename="CastProof""#));
+    assert!(body.windows(3).any(|w| w == [0, 1, 2]));
+    assert!(text.ends_with(&format!("--{}--\r\n", MULTIPART_BOUNDARY)));
+}
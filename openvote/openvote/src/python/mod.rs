@@ -0,0 +1,155 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// PYTHON BINDINGS
+// ================================================================================================
+// Exposes the verifier entry points and the voter primitives as the `openvote` Python module.
+// Points are exchanged in compressed form and scalars as 32 bytes, as in the wire formats.
+
+use crate::{
+    aggregator::cast::EncryptedVote,
+    cds::compute_blinding_key,
+    compat::curve::{AffinePoint, ProjectivePoint, Scalar},
+    utils::ecc::{
+        compress_point, decompress_point, mul_constant_time, mul_generator_constant_time,
+        projective_to_elements, read_canonical_scalar, AFFINE_POINT_WIDTH,
+        BYTES_PER_COMPRESSED_POINT,
+    },
+    verifier,
+};
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyBytes, wrap_pyfunction};
+use rand_core::OsRng;
+use winterfell::{math::fields::f63::BaseElement, DeserializationError, Serializable, SliceReader};
+
+// VERIFIER
+// ================================================================================================
+
+/// Verify the register proof against the serialized root of the Merkle tree of eligible voters
+#[pyfunction]
+fn verify_register_proof(elg_root: &[u8], register_proof: &[u8]) -> PyResult<bool> {
+    verifier::verify_register_proof(elg_root, register_proof).map_err(value_error)
+}
+
+/// Verify the cast proof against the voting keys of registered voters
+#[pyfunction]
+fn verify_cast_proof(voting_keys: &[u8], cast_proof: &[u8]) -> PyResult<bool> {
+    verifier::verify_cast_proof(voting_keys, cast_proof).map_err(value_error)
+}
+
+/// Verify the tally result against the encrypted votes
+#[pyfunction]
+fn verify_tally_result(encrypted_votes: &[u8], tally_result: u32) -> PyResult<bool> {
+    verifier::verify_tally_result(encrypted_votes, tally_result).map_err(value_error)
+}
+
+// VOTER PRIMITIVES
+// ================================================================================================
+
+/// Generate a random secret key and the corresponding compressed voting key
+#[pyfunction]
+fn generate_key_pair(py: Python) -> (PyObject, PyObject) {
+    let secret_key = Scalar::random(OsRng);
+    let voting_key = projective_to_elements(mul_generator_constant_time(secret_key));
+    (
+        PyBytes::new(py, &secret_key.to_bytes()).into(),
+        PyBytes::new(py, &compress_point(&voting_key)).into(),
+    )
+}
+
+/// Encrypt the vote of the voter at voter_index, returning the compressed encrypted vote
+#[pyfunction]
+fn encrypt_vote(
+    py: Python,
+    voter_index: usize,
+    secret_key: &[u8],
+    voting_keys: Vec<Vec<u8>>,
+    vote: bool,
+) -> PyResult<PyObject> {
+    let (secret_key, voting_keys) = read_voter_inputs(voter_index, secret_key, &voting_keys)?;
+    let blinding_key = ProjectivePoint::from(AffinePoint::from_raw_coordinates(
+        compute_blinding_key(&voting_keys, voter_index),
+    ));
+    let encrypted_vote = if vote {
+        mul_constant_time(blinding_key, secret_key) + ProjectivePoint::generator()
+    } else {
+        mul_constant_time(blinding_key, secret_key) - ProjectivePoint::generator()
+    };
+    let encrypted_vote = compress_point(&projective_to_elements(encrypted_vote));
+    Ok(PyBytes::new(py, &encrypted_vote).into())
+}
+
+/// Encrypt the vote of the voter at voter_index and compute its CDS proof, returning
+/// the serialized encrypted vote to submit to the aggregator
+#[pyfunction]
+fn prove_vote(
+    py: Python,
+    voter_index: usize,
+    secret_key: &[u8],
+    voting_keys: Vec<Vec<u8>>,
+    vote: bool,
+) -> PyResult<PyObject> {
+    let (secret_key, voting_keys) = read_voter_inputs(voter_index, secret_key, &voting_keys)?;
+    let encrypted_vote = EncryptedVote::cast(voter_index, secret_key, &voting_keys, vote);
+    Ok(PyBytes::new(py, &encrypted_vote.to_bytes()).into())
+}
+
+/// The openvote Python module
+#[pymodule]
+fn openvote(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(verify_register_proof, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_cast_proof, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_tally_result, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_key_pair, m)?)?;
+    m.add_function(wrap_pyfunction!(encrypt_vote, m)?)?;
+    m.add_function(wrap_pyfunction!(prove_vote, m)?)?;
+    Ok(())
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn value_error(error: DeserializationError) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+/// Read the secret key and voting keys of a voter, checking that the secret key
+/// matches the voting key registered at voter_index
+fn read_voter_inputs(
+    voter_index: usize,
+    secret_key: &[u8],
+    voting_keys: &[Vec<u8>],
+) -> PyResult<(Scalar, Vec<[BaseElement; AFFINE_POINT_WIDTH]>)> {
+    if secret_key.len() != 32 {
+        return Err(PyValueError::new_err("Secret key must be 32 bytes long."));
+    }
+    let secret_key =
+        read_canonical_scalar(&mut SliceReader::new(secret_key)).map_err(value_error)?;
+
+    let mut points = Vec::with_capacity(voting_keys.len());
+    for voting_key in voting_keys.iter() {
+        if voting_key.len() != BYTES_PER_COMPRESSED_POINT {
+            return Err(PyValueError::new_err("Invalid compressed point length."));
+        }
+        let mut compressed = [0u8; BYTES_PER_COMPRESSED_POINT];
+        compressed.copy_from_slice(voting_key);
+        points.push(
+            decompress_point(&compressed)
+                .ok_or_else(|| PyValueError::new_err("Invalid compressed curve point."))?,
+        );
+    }
+
+    if voter_index >= points.len() {
+        return Err(PyValueError::new_err("Voter index out of range."));
+    }
+    if projective_to_elements(mul_generator_constant_time(secret_key)) != points[voter_index] {
+        return Err(PyValueError::new_err(
+            "Secret key does not match the voting key at voter index.",
+        ));
+    }
+    Ok((secret_key, points))
+}
@@ -0,0 +1,276 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::constants::*;
+use crate::utils::address::Address;
+use crate::{
+    compat::curve::Scalar,
+    merkle::air as merkle_air,
+    schnorr::{air as schnorr_air, SigningDomain},
+    utils::{ecc::write_compressed_points, ordering::KeyOrder},
+};
+use winterfell::{
+    math::{fields::f63::BaseElement, FieldElement},
+    Air, AirContext, Assertion, ByteReader, ByteWriter, Deserializable, DeserializationError,
+    EvaluationFrame, ProofOptions, Serializable, SliceReader, TraceInfo,
+};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+// COMBINED REGISTRATION AIR
+// ================================================================================================
+
+pub struct PublicInputs {
+    pub tree_root: [BaseElement; DIGEST_SIZE],
+    pub voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+    pub addresses: Vec<Address>,
+    pub signatures: Vec<([BaseElement; POINT_COORDINATE_WIDTH], Scalar)>,
+    // digest of the election manifest (not read back), set by the verifier
+    pub manifest_digest: Option<[BaseElement; DIGEST_SIZE]>,
+    // signing domain of the signatures (not read back), set by the verifier
+    pub domain: SigningDomain,
+    // order of the voting keys (not read back), set by the verifier
+    pub key_order: KeyOrder,
+    // leaf index of each voting key in the tree of eligible voters (not read back),
+    // set by the verifier; empty if the leaf indices are not bound
+    pub leaf_indices: Vec<usize>,
+    // group label committed in the leaf of each voting key (not read back),
+    // set by the verifier; empty if the groups are not bound
+    pub groups: Vec<u32>,
+}
+
+impl Serializable for PublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        Serializable::write_batch_into(&self.tree_root, target);
+        target.write_u32(self.voting_keys.len() as u32);
+        for voting_key in self.voting_keys.iter() {
+            write_compressed_points(voting_key, target);
+        }
+        for address in self.addresses.iter() {
+            target.write_u8_slice(address.as_bytes());
+        }
+        for signature in self.signatures.iter() {
+            Serializable::write_batch_into(&signature.0, target);
+            target.write(signature.1);
+        }
+        // the manifest digest is only written when bound, so that
+        // the encoding of unbound public inputs stays unchanged
+        if let Some(manifest_digest) = self.manifest_digest {
+            Serializable::write_batch_into(&manifest_digest, target);
+        }
+        if self.domain.is_bound() {
+            self.domain.write_into(target);
+        }
+        self.key_order.write_into(target);
+        for &leaf_index in self.leaf_indices.iter() {
+            target.write_u32(leaf_index as u32);
+        }
+        for &group in self.groups.iter() {
+            target.write_u32(group);
+        }
+    }
+}
+
+impl Deserializable for PublicInputs {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let mut tree_root = [BaseElement::ZERO; DIGEST_SIZE];
+        tree_root.copy_from_slice(&BaseElement::read_batch_from(source, DIGEST_SIZE)?);
+        let schnorr_pub_inputs = schnorr_air::PublicInputs::read_from(source)?;
+
+        Ok(Self {
+            tree_root,
+            voting_keys: schnorr_pub_inputs.voting_keys,
+            addresses: schnorr_pub_inputs.addresses,
+            signatures: schnorr_pub_inputs.signatures,
+            manifest_digest: None,
+            domain: SigningDomain::default(),
+            key_order: KeyOrder::Submission,
+            leaf_indices: Vec::new(),
+            groups: Vec::new(),
+        })
+    }
+}
+
+impl PublicInputs {
+    pub fn from_bytes(source: &[u8]) -> Result<Self, DeserializationError> {
+        let mut source = SliceReader::new(source);
+        Self::read_from(&mut source)
+    }
+
+    /// Deserialize the registrations from bytes in the format of Schnorr public inputs,
+    /// and bind them to the given tree root
+    pub fn from_tree_root_and_bytes(
+        tree_root: [BaseElement; DIGEST_SIZE],
+        source: &[u8],
+    ) -> Result<Self, DeserializationError> {
+        let schnorr_pub_inputs = schnorr_air::PublicInputs::from_bytes(source)?;
+
+        Ok(Self {
+            tree_root,
+            voting_keys: schnorr_pub_inputs.voting_keys,
+            addresses: schnorr_pub_inputs.addresses,
+            signatures: schnorr_pub_inputs.signatures,
+            manifest_digest: None,
+            domain: SigningDomain::default(),
+            key_order: KeyOrder::Submission,
+            leaf_indices: Vec::new(),
+            groups: Vec::new(),
+        })
+    }
+}
+
+pub struct RegisterAir {
+    context: AirContext<BaseElement>,
+    tree_root: [BaseElement; DIGEST_SIZE],
+    voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+    addresses: Vec<Address>,
+    signatures: Vec<([BaseElement; POINT_COORDINATE_WIDTH], Scalar)>,
+    domain: SigningDomain,
+    leaf_indices: Vec<usize>,
+    groups: Vec<u32>,
+}
+
+impl Air for RegisterAir {
+    type BaseField = BaseElement;
+    type PublicInputs = PublicInputs;
+
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    fn new(trace_info: TraceInfo, pub_inputs: PublicInputs, options: ProofOptions) -> Self {
+        let mut degrees = merkle_air::transition_constraint_degrees();
+        degrees.append(&mut schnorr_air::transition_constraint_degrees(
+            pub_inputs.signatures.len(),
+            SIG_CYCLE_LENGTH,
+        ));
+        assert!(
+            pub_inputs.leaf_indices.is_empty()
+                || pub_inputs.leaf_indices.len() == pub_inputs.voting_keys.len(),
+            "Number of leaf indices must match the number of voting keys."
+        );
+        assert!(
+            pub_inputs.groups.is_empty() || pub_inputs.groups.len() == pub_inputs.voting_keys.len(),
+            "Number of groups must match the number of voting keys."
+        );
+        assert_eq!(TRACE_WIDTH, trace_info.width());
+        RegisterAir {
+            context: AirContext::new(trace_info, degrees, options),
+            tree_root: pub_inputs.tree_root,
+            voting_keys: pub_inputs.voting_keys,
+            addresses: pub_inputs.addresses,
+            signatures: pub_inputs.signatures,
+            domain: pub_inputs.domain,
+            leaf_indices: pub_inputs.leaf_indices,
+            groups: pub_inputs.groups,
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+
+        // Expected state width is TRACE_WIDTH field elements
+        debug_assert_eq!(TRACE_WIDTH, current.len());
+        debug_assert_eq!(TRACE_WIDTH, next.len());
+
+        // Merkle path verification in the first registers
+        let (merkle_result, schnorr_result) = result.split_at_mut(MERKLE_TRACE_WIDTH);
+        merkle_air::evaluate_transition_constraints(
+            merkle_result,
+            &current[..MERKLE_TRACE_WIDTH],
+            &next[..MERKLE_TRACE_WIDTH],
+            &periodic_values[..MERKLE_NUM_PERIODIC_COLUMNS],
+        );
+
+        // Schnorr signature verification in the remaining registers
+        schnorr_air::evaluate_transition_constraints(
+            schnorr_result,
+            &current[MERKLE_TRACE_WIDTH..],
+            &next[MERKLE_TRACE_WIDTH..],
+            &periodic_values[MERKLE_NUM_PERIODIC_COLUMNS..],
+        );
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        // The Merkle path of each voter is verified MERKLE_CYCLES_PER_VOTER times
+        let merkle_voting_keys = repeat_per_voter(&self.voting_keys);
+        let mut assertions = merkle_air::build_assertions(&self.tree_root, &merkle_voting_keys);
+        assertions.append(&mut merkle_air::build_leaf_index_assertions(
+            &repeat_per_voter(&self.leaf_indices),
+            &[],
+            TREE_DEPTH,
+        ));
+        assertions.append(&mut merkle_air::build_group_assertions(
+            &repeat_per_voter(&self.groups),
+            TREE_DEPTH,
+        ));
+
+        assertions.extend(
+            schnorr_air::build_assertions(&self.signatures)
+                .into_iter()
+                .map(|assertion| shift_assertion(assertion, MERKLE_TRACE_WIDTH)),
+        );
+
+        assertions
+    }
+
+    fn get_periodic_column_values(&self) -> Vec<Vec<Self::BaseField>> {
+        let mut columns = merkle_air::build_periodic_columns();
+        debug_assert_eq!(MERKLE_NUM_PERIODIC_COLUMNS, columns.len());
+        columns.append(&mut schnorr_air::build_periodic_columns(
+            &self.voting_keys,
+            &self.addresses,
+            self.signatures.len(),
+            &self.domain,
+        ));
+        columns
+    }
+}
+
+// HELPER FUNCTIONS
+// ------------------------------------------------------------------------------------------------
+
+/// Repeat each per-voter value MERKLE_CYCLES_PER_VOTER times, to match
+/// the Merkle path verifications in the registration cycle of each voter
+pub(crate) fn repeat_per_voter<T: Copy>(values: &[T]) -> Vec<T> {
+    values
+        .iter()
+        .flat_map(|&value| core::iter::repeat(value).take(MERKLE_CYCLES_PER_VOTER))
+        .collect()
+}
+
+/// Move an assertion to the register at the given offset
+fn shift_assertion(assertion: Assertion<BaseElement>, offset: usize) -> Assertion<BaseElement> {
+    let column = assertion.column() + offset;
+    if assertion.values().len() > 1 {
+        Assertion::sequence(
+            column,
+            assertion.first_step(),
+            assertion.stride(),
+            assertion.values().to_vec(),
+        )
+    } else if assertion.stride() == 0 {
+        Assertion::single(column, assertion.first_step(), assertion.values()[0])
+    } else {
+        Assertion::periodic(
+            column,
+            assertion.first_step(),
+            assertion.stride(),
+            assertion.values()[0],
+        )
+    }
+}
@@ -0,0 +1,35 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// CONSTANTS USED IN COMBINED REGISTRATION VERIFICATION
+// ================================================================================================
+
+pub(crate) use crate::merkle::constants::{
+    DIGEST_SIZE, MERKLE_CYCLE_LENGTH, TRACE_WIDTH as MERKLE_TRACE_WIDTH, TREE_DEPTH,
+};
+pub(crate) use crate::schnorr::constants::{
+    AFFINE_POINT_WIDTH, POINT_COORDINATE_WIDTH, SIG_CYCLE_LENGTH,
+    TRACE_WIDTH as SCHNORR_TRACE_WIDTH,
+};
+use crate::utils::rescue::STATE_WIDTH as HASH_STATE_WIDTH;
+
+/// Total number of registers in the trace
+/// Layout: | Merkle registers | Schnorr registers |
+pub const TRACE_WIDTH: usize = MERKLE_TRACE_WIDTH + SCHNORR_TRACE_WIDTH;
+
+/// Number of steps to verify the registration of one voter
+pub const REGISTER_CYCLE_LENGTH: usize = SIG_CYCLE_LENGTH;
+
+/// Number of Merkle path verifications fitting in one registration cycle.
+/// The Merkle path of a voter is verified that many times, so that
+/// both sub-computations share the same cycle.
+pub const MERKLE_CYCLES_PER_VOTER: usize = REGISTER_CYCLE_LENGTH / MERKLE_CYCLE_LENGTH;
+
+/// Number of periodic columns used by Merkle path verification:
+/// hash flag, cycle mask and Rescue round constants
+pub const MERKLE_NUM_PERIODIC_COLUMNS: usize = 2 + 2 * HASH_STATE_WIDTH;
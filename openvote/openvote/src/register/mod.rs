@@ -0,0 +1,25 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// COMBINED REGISTRATION AIR
+// ================================================================================================
+// Verifies, in each cycle of REGISTER_CYCLE_LENGTH steps, both the Merkle proof of membership
+// and the Schnorr signature of a voter. Registers of Merkle path verification are placed next
+// to those of Schnorr signature verification, so that registrations are proven with a single
+// STARK proof instead of two.
+
+pub(crate) mod constants;
+
+mod air;
+pub(crate) use air::{PublicInputs, RegisterAir};
+
+mod prover;
+pub(crate) use prover::RegisterProver;
+
+#[cfg(test)]
+mod tests;
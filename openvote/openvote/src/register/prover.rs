@@ -0,0 +1,198 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::{
+    air::{repeat_per_voter, RegisterAir},
+    constants::*,
+    PublicInputs,
+};
+use crate::utils::{address::Address, ordering::KeyOrder};
+use crate::{
+    compat::curve::Scalar,
+    merkle::{prepare_hash_message, MerkleProver},
+    schnorr::{build_sig_info, SchnorrProver, SigInfo, SigningDomain},
+};
+use winterfell::{math::fields::f63::BaseElement, ProofOptions, Prover, Trace, TraceTable};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+// COMBINED REGISTRATION PROVER
+// ================================================================================================
+
+// inputs are borrowed from the caller to avoid cloning them for large elections
+pub struct RegisterProver<'a> {
+    options: ProofOptions,
+    tree_root: [BaseElement; DIGEST_SIZE],
+    voting_keys: &'a [[BaseElement; AFFINE_POINT_WIDTH]],
+    addresses: &'a [Address],
+    signatures: &'a [([BaseElement; POINT_COORDINATE_WIDTH], Scalar)],
+    manifest_digest: Option<[BaseElement; DIGEST_SIZE]>,
+    domain: SigningDomain,
+    key_order: KeyOrder,
+    leaf_indices: Vec<usize>,
+    groups: Vec<u32>,
+}
+
+impl<'a> RegisterProver<'a> {
+    pub fn new(
+        options: ProofOptions,
+        tree_root: [BaseElement; DIGEST_SIZE],
+        voting_keys: &'a [[BaseElement; AFFINE_POINT_WIDTH]],
+        addresses: &'a [Address],
+        signatures: &'a [([BaseElement; POINT_COORDINATE_WIDTH], Scalar)],
+    ) -> Self {
+        Self {
+            options,
+            tree_root,
+            voting_keys,
+            addresses,
+            signatures,
+            manifest_digest: None,
+            domain: SigningDomain::default(),
+            key_order: KeyOrder::Submission,
+            leaf_indices: Vec::new(),
+            groups: Vec::new(),
+        }
+    }
+
+    /// Bind the proof to the digest of an election manifest
+    pub fn with_manifest(mut self, manifest_digest: [BaseElement; DIGEST_SIZE]) -> Self {
+        self.manifest_digest = Some(manifest_digest);
+        self
+    }
+
+    /// Verify signatures bound to the given signing domain
+    pub fn with_domain(mut self, domain: SigningDomain) -> Self {
+        self.domain = domain;
+        self
+    }
+
+    /// Bind the proof to the order in which voting keys are listed
+    pub fn with_key_order(mut self, key_order: KeyOrder) -> Self {
+        self.key_order = key_order;
+        self
+    }
+
+    /// Bind the proof to the leaf index of each voting key in the tree of
+    /// eligible voters, which must be the hash indices the trace is built from
+    pub fn with_leaf_indices(mut self, leaf_indices: Vec<usize>) -> Self {
+        assert_eq!(
+            self.voting_keys.len(),
+            leaf_indices.len(),
+            "Number of leaf indices must match the number of voting keys."
+        );
+        self.leaf_indices = leaf_indices;
+        self
+    }
+
+    /// Bind the proof to the group label committed in the leaf of each voting key,
+    /// which must be the groups the Merkle messages of the trace are built from
+    pub fn with_groups(mut self, groups: Vec<u32>) -> Self {
+        assert_eq!(
+            self.voting_keys.len(),
+            groups.len(),
+            "Number of groups must match the number of voting keys."
+        );
+        self.groups = groups;
+        self
+    }
+
+    pub fn build_trace(
+        &self,
+        branches: &[[BaseElement; TREE_DEPTH * DIGEST_SIZE]],
+        hash_indices: &[usize],
+    ) -> TraceTable<BaseElement> {
+        let merkle_messages = branches
+            .iter()
+            .zip(self.voting_keys.iter())
+            .map(|(branch, voting_key)| prepare_hash_message(voting_key, branch))
+            .collect::<Vec<_>>();
+        let sig_infos = (0..self.voting_keys.len())
+            .map(|i| {
+                build_sig_info(
+                    &self.voting_keys[i],
+                    self.addresses[i],
+                    &self.signatures[i],
+                    &self.domain,
+                )
+            })
+            .collect::<Vec<SigInfo>>();
+        self.build_trace_from_inputs(&merkle_messages, hash_indices, &sig_infos)
+    }
+
+    /// Build the trace from Merkle hash messages and signature information
+    /// prepared beforehand. The Merkle registers and the Schnorr registers
+    /// are filled side by side, one registration cycle per voter.
+    pub fn build_trace_from_inputs(
+        &self,
+        merkle_messages: &[[BaseElement; (TREE_DEPTH + 1) * DIGEST_SIZE]],
+        hash_indices: &[usize],
+        sig_infos: &[SigInfo],
+    ) -> TraceTable<BaseElement> {
+        let build_merkle_trace = || {
+            // The Merkle path of each voter is verified MERKLE_CYCLES_PER_VOTER times
+            let merkle_voting_keys = repeat_per_voter(self.voting_keys);
+            let merkle_prover =
+                MerkleProver::new(self.options.clone(), self.tree_root, &merkle_voting_keys);
+            merkle_prover.build_trace_from_messages(
+                &repeat_per_voter(merkle_messages),
+                &repeat_per_voter(hash_indices),
+            )
+        };
+        let build_schnorr_trace = || {
+            let schnorr_prover = SchnorrProver::new(
+                self.options.clone(),
+                self.voting_keys,
+                self.addresses,
+                self.signatures,
+            );
+            schnorr_prover.build_trace_from_sig_infos(sig_infos)
+        };
+
+        // both sub-traces are built concurrently on the thread pool if available
+        #[cfg(feature = "concurrent")]
+        let (merkle_trace, schnorr_trace) = rayon::join(build_merkle_trace, build_schnorr_trace);
+        #[cfg(not(feature = "concurrent"))]
+        let (merkle_trace, schnorr_trace) = (build_merkle_trace(), build_schnorr_trace());
+        debug_assert_eq!(merkle_trace.length(), schnorr_trace.length());
+
+        let mut columns = Vec::with_capacity(TRACE_WIDTH);
+        for i in 0..MERKLE_TRACE_WIDTH {
+            columns.push(merkle_trace.get_column(i).to_vec());
+        }
+        for i in 0..SCHNORR_TRACE_WIDTH {
+            columns.push(schnorr_trace.get_column(i).to_vec());
+        }
+        TraceTable::init(columns)
+    }
+}
+
+impl<'a> Prover for RegisterProver<'a> {
+    type BaseField = BaseElement;
+    type Air = RegisterAir;
+    type Trace = TraceTable<BaseElement>;
+
+    fn get_pub_inputs(&self, _trace: &Self::Trace) -> PublicInputs {
+        PublicInputs {
+            tree_root: self.tree_root,
+            voting_keys: self.voting_keys.to_vec(),
+            addresses: self.addresses.to_vec(),
+            signatures: self.signatures.to_vec(),
+            manifest_digest: self.manifest_digest,
+            domain: self.domain,
+            key_order: self.key_order,
+            leaf_indices: self.leaf_indices.clone(),
+            groups: self.groups.clone(),
+        }
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
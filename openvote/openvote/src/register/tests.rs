@@ -0,0 +1,148 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::{PublicInputs, RegisterAir, RegisterProver};
+use crate::{
+    aggregator::{build_options, register::VoterRegistar},
+    compat::verify,
+    schnorr::SigningDomain,
+    testing::strategies::{address, digest, point, signature},
+    utils::ordering::KeyOrder,
+};
+use proptest::{collection::vec, prelude::*};
+use winterfell::{Prover, Serializable};
+
+#[test]
+fn register_test_combined_proof_verification() {
+    let registar = VoterRegistar::get_example(2);
+    let prover = RegisterProver::new(
+        build_options(1),
+        registar.elg_root,
+        &registar.voting_keys,
+        &registar.addresses,
+        &registar.signatures,
+    );
+    let trace = prover.build_trace(&registar.merkle_branches(), &registar.hash_indices);
+    let pub_inputs = prover.get_pub_inputs(&trace);
+    let proof = prover.prove(trace).unwrap();
+    assert!(verify::<RegisterAir>(proof, pub_inputs).is_ok());
+}
+
+#[test]
+fn register_test_combined_proof_verification_wrong_root() {
+    let registar = VoterRegistar::get_example(2);
+    let prover = RegisterProver::new(
+        build_options(1),
+        registar.elg_root,
+        &registar.voting_keys,
+        &registar.addresses,
+        &registar.signatures,
+    );
+    let trace = prover.build_trace(&registar.merkle_branches(), &registar.hash_indices);
+    let proof = prover.prove(trace).unwrap();
+
+    let mut wrong_tree_root = registar.elg_root;
+    wrong_tree_root[0] += winterfell::math::fields::f63::BaseElement::from(1u8);
+    let pub_inputs = PublicInputs {
+        tree_root: wrong_tree_root,
+        voting_keys: registar.voting_keys.clone(),
+        addresses: registar.addresses.clone(),
+        signatures: registar.signatures.clone(),
+        manifest_digest: None,
+        domain: SigningDomain::default(),
+        key_order: KeyOrder::Submission,
+        leaf_indices: vec![],
+        groups: vec![],
+    };
+    assert!(verify::<RegisterAir>(proof, pub_inputs).is_err());
+}
+
+#[test]
+fn register_test_leaf_index_binding() {
+    let registar = VoterRegistar::get_example(2);
+    let prover = RegisterProver::new(
+        build_options(1),
+        registar.elg_root,
+        &registar.voting_keys,
+        &registar.addresses,
+        &registar.signatures,
+    )
+    .with_leaf_indices(registar.hash_indices.clone());
+    let trace = prover.build_trace(&registar.merkle_branches(), &registar.hash_indices);
+    let mut pub_inputs = prover.get_pub_inputs(&trace);
+    let proof = prover.prove(trace).unwrap();
+
+    // the leaf indices are bound to the proof through the public inputs
+    pub_inputs.leaf_indices.swap(0, 1);
+    assert!(verify::<RegisterAir>(proof.clone(), pub_inputs).is_err());
+
+    let pub_inputs = PublicInputs {
+        tree_root: registar.elg_root,
+        voting_keys: registar.voting_keys.clone(),
+        addresses: registar.addresses.clone(),
+        signatures: registar.signatures.clone(),
+        manifest_digest: None,
+        domain: SigningDomain::default(),
+        key_order: KeyOrder::Submission,
+        leaf_indices: registar.hash_indices.clone(),
+        groups: vec![],
+    };
+    assert!(verify::<RegisterAir>(proof, pub_inputs).is_ok());
+}
+
+#[test]
+fn register_test_group_binding() {
+    // leaves of the example tree are the leaves of voters of group 0
+    let registar = VoterRegistar::get_example(2);
+    let prover = RegisterProver::new(
+        build_options(1),
+        registar.elg_root,
+        &registar.voting_keys,
+        &registar.addresses,
+        &registar.signatures,
+    )
+    .with_groups(vec![0, 0]);
+    let trace = prover.build_trace(&registar.merkle_branches(), &registar.hash_indices);
+    let pub_inputs = prover.get_pub_inputs(&trace);
+    let mut wrong_pub_inputs = prover.get_pub_inputs(&trace);
+    let proof = prover.prove(trace).unwrap();
+    assert!(verify::<RegisterAir>(proof.clone(), pub_inputs).is_ok());
+
+    // the groups are bound to the proof through the public inputs
+    wrong_pub_inputs.groups[1] = 1;
+    assert!(verify::<RegisterAir>(proof, wrong_pub_inputs).is_err());
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn register_test_pub_inputs_roundtrip(
+        tree_root in digest(),
+        signers in vec((point(), address(), signature()), 0..6),
+    ) {
+        let pub_inputs = PublicInputs {
+            tree_root,
+            voting_keys: signers.iter().map(|signer| signer.0).collect(),
+            addresses: signers.iter().map(|signer| signer.1).collect(),
+            signatures: signers.iter().map(|signer| signer.2).collect(),
+            manifest_digest: None,
+            domain: SigningDomain::default(),
+            key_order: KeyOrder::Submission,
+            leaf_indices: vec![],
+            groups: vec![],
+        };
+        let bytes = pub_inputs.to_bytes();
+        let restored = PublicInputs::from_bytes(&bytes).unwrap();
+        prop_assert_eq!(restored.tree_root, pub_inputs.tree_root);
+        prop_assert_eq!(&restored.voting_keys, &pub_inputs.voting_keys);
+        prop_assert_eq!(&restored.addresses, &pub_inputs.addresses);
+        prop_assert_eq!(&restored.signatures, &pub_inputs.signatures);
+        prop_assert_eq!(restored.to_bytes(), bytes);
+    }
+}
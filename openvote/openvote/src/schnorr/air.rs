@@ -8,12 +8,21 @@
 
 use super::super::utils::periodic_columns::stitch;
 use super::rescue::{RATE_WIDTH as HASH_RATE_WIDTH, STATE_WIDTH as HASH_STATE_WIDTH};
-use super::{constants::*, prepare_messages};
+use super::{constants::*, prepare_messages, SigningDomain};
 use super::{ecc, field, rescue};
-use crate::utils::{are_equal, is_zero, not, EvaluationResult};
-use web3::ethabi::Address;
+use crate::compat::curve::Scalar;
+use crate::utils::address::Address;
+use crate::utils::{
+    are_equal,
+    ecc::{
+        read_canonical_scalar, read_compressed_point, write_compressed_points,
+        BYTES_PER_COMPRESSED_POINT,
+    },
+    is_zero, not, EvaluationResult,
+};
+use crate::verifier::constants::{BYTES_PER_ADDRESS, BYTES_PER_SIGNATURE};
 use winterfell::{
-    math::{curves::curve_f63::Scalar, fields::f63::BaseElement, FieldElement},
+    math::{fields::f63::BaseElement, FieldElement},
     Air, AirContext, Assertion, ByteReader, ByteWriter, Deserializable, DeserializationError,
     EvaluationFrame, ProofOptions, Serializable, SliceReader, TraceInfo,
     TransitionConstraintDegree,
@@ -25,17 +34,32 @@ use alloc::vec::Vec;
 // SCHNORR AIR
 // ================================================================================================
 
+/// Public inputs of Schnorr signature verification. They are also the compact
+/// public inputs of registrations sent by the aggregator to the on-chain verifier.
+///
+/// Fields are serialized in the following order, with n the number of signatures:
+/// | n (u32, little-endian) | n voting keys | n Ethereum addresses | n signatures |
+/// where a voting key is a compressed point, an address is 20 bytes,
+/// and a signature is POINT_COORDINATE_WIDTH field elements (x coordinate of R)
+/// followed by a 32-byte scalar (s). A bound signing domain is appended
+/// to the encoding, but it is not read back and must be set by the verifier.
+#[derive(Debug, Clone, PartialEq)]
 pub struct PublicInputs {
+    /// Voting keys of the signers
     pub voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+    /// Ethereum addresses of the signers
     pub addresses: Vec<Address>,
+    /// Signatures of (voting key, address)
     pub signatures: Vec<([BaseElement; POINT_COORDINATE_WIDTH], Scalar)>,
+    /// Signing domain of the signatures (not read back), set by the verifier
+    pub domain: SigningDomain,
 }
 
 impl Serializable for PublicInputs {
     fn write_into<W: ByteWriter>(&self, target: &mut W) {
         target.write_u32(self.voting_keys.len() as u32);
         for voting_key in self.voting_keys.iter() {
-            Serializable::write_batch_into(voting_key, target);
+            write_compressed_points(voting_key, target);
         }
         for address in self.addresses.iter() {
             target.write_u8_slice(address.as_bytes());
@@ -44,12 +68,16 @@ impl Serializable for PublicInputs {
             Serializable::write_batch_into(&signature.0, target);
             target.write(signature.1);
         }
+        // the domain is only written when bound, so that
+        // the encoding of unbound public inputs stays unchanged
+        if self.domain.is_bound() {
+            self.domain.write_into(target);
+        }
     }
 }
 
 impl Deserializable for PublicInputs {
     fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
-        let mut voting_key = [BaseElement::ZERO; AFFINE_POINT_WIDTH];
         let mut signature_r = [BaseElement::ZERO; POINT_COORDINATE_WIDTH];
 
         let num_sigs = source.read_u32()? as usize;
@@ -58,8 +86,7 @@ impl Deserializable for PublicInputs {
         let mut signatures = Vec::with_capacity(num_sigs);
 
         for _ in 0..num_sigs {
-            voting_key.copy_from_slice(&BaseElement::read_batch_from(source, AFFINE_POINT_WIDTH)?);
-            voting_keys.push(voting_key);
+            voting_keys.push(read_compressed_point(source)?);
         }
 
         for _ in 0..num_sigs {
@@ -72,7 +99,7 @@ impl Deserializable for PublicInputs {
                 source,
                 POINT_COORDINATE_WIDTH,
             )?);
-            let signature_s = Scalar::read_from(source)?;
+            let signature_s = read_canonical_scalar(source)?;
             signatures.push((signature_r, signature_s));
         }
 
@@ -80,11 +107,18 @@ impl Deserializable for PublicInputs {
             voting_keys,
             addresses,
             signatures,
+            domain: SigningDomain::default(),
         })
     }
 }
 
 impl PublicInputs {
+    /// Number of bytes of serialized public inputs with num_signatures signatures
+    pub fn serialized_len(num_signatures: usize) -> usize {
+        4 + num_signatures * (BYTES_PER_COMPRESSED_POINT + BYTES_PER_ADDRESS + BYTES_PER_SIGNATURE)
+    }
+
+    /// Deserialize public inputs from bytes in the format described above
     pub fn from_bytes(source: &[u8]) -> Result<Self, DeserializationError> {
         let mut source = SliceReader::new(source);
         Self::read_from(&mut source)
@@ -96,6 +130,7 @@ pub struct SchnorrAir {
     voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
     addresses: Vec<Address>,
     signatures: Vec<([BaseElement; POINT_COORDINATE_WIDTH], Scalar)>,
+    domain: SigningDomain,
 }
 
 impl Air for SchnorrAir {
@@ -112,6 +147,7 @@ impl Air for SchnorrAir {
             voting_keys: pub_inputs.voting_keys,
             addresses: pub_inputs.addresses,
             signatures: pub_inputs.signatures,
+            domain: pub_inputs.domain,
         }
     }
 
@@ -132,222 +168,20 @@ impl Air for SchnorrAir {
         debug_assert_eq!(TRACE_WIDTH, current.len());
         debug_assert_eq!(TRACE_WIDTH, next.len());
 
-        // Split periodic values
-        let global_mask = periodic_values[0];
-        let scalar_mult_flag = periodic_values[1];
-        let doubling_flag = periodic_values[2];
-        let hash_digest_register_flag = &periodic_values[3..7];
-        let vkey_point = &periodic_values[7..AFFINE_POINT_WIDTH + 7];
-        let hash_flag = periodic_values[AFFINE_POINT_WIDTH + 7];
-        let hash_internal_inputs =
-            &periodic_values[AFFINE_POINT_WIDTH + 8..AFFINE_POINT_WIDTH + 15];
-        // Rescue round constants
-        let ark = &periodic_values[AFFINE_POINT_WIDTH + 15..];
-
-        let copy_hash_flag = not(hash_flag) * global_mask;
-        let final_point_addition_flag = not(scalar_mult_flag) * global_mask;
-        let addition_flag = not(doubling_flag) * scalar_mult_flag;
-
-        evaluate_constraints(
-            result,
-            current,
-            next,
-            ark,
-            doubling_flag,
-            addition_flag,
-            hash_digest_register_flag,
-            vkey_point,
-            final_point_addition_flag,
-            hash_flag,
-            copy_hash_flag,
-            hash_internal_inputs,
-        );
+        evaluate_transition_constraints(result, current, next, periodic_values);
     }
 
     fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
-        let signatures = transpose_signatures(&self.signatures);
-        // Assert starting and ending values
-        let mut assertions = vec![];
-        // First projective points
-        for i in 0..PROJECTIVE_POINT_WIDTH {
-            if i == POINT_COORDINATE_WIDTH {
-                assertions.push(Assertion::periodic(
-                    i,
-                    0,
-                    SIG_CYCLE_LENGTH,
-                    BaseElement::ONE,
-                ));
-            } else {
-                assertions.push(Assertion::periodic(
-                    i,
-                    0,
-                    SIG_CYCLE_LENGTH,
-                    BaseElement::ZERO,
-                ));
-            }
-        }
-        assertions.push(Assertion::periodic(
-            PROJECTIVE_POINT_WIDTH,
-            0,
-            SIG_CYCLE_LENGTH,
-            BaseElement::ZERO,
-        ));
-        // Second projective points
-        for i in 0..PROJECTIVE_POINT_WIDTH {
-            if i == POINT_COORDINATE_WIDTH {
-                assertions.push(Assertion::periodic(
-                    i + PROJECTIVE_POINT_WIDTH + 1,
-                    0,
-                    SIG_CYCLE_LENGTH,
-                    BaseElement::ONE,
-                ));
-            } else {
-                assertions.push(Assertion::periodic(
-                    i + PROJECTIVE_POINT_WIDTH + 1,
-                    0,
-                    SIG_CYCLE_LENGTH,
-                    BaseElement::ZERO,
-                ));
-            }
-        }
-        for i in 0..5 {
-            assertions.push(Assertion::periodic(
-                i + 2 * PROJECTIVE_POINT_WIDTH + 1,
-                0,
-                SIG_CYCLE_LENGTH,
-                BaseElement::ZERO,
-            ));
-        }
-
-        // TODO: find a way to do this better with indexing
-        assertions.append(&mut vec![
-            Assertion::sequence(
-                2 * PROJECTIVE_POINT_WIDTH + 6,
-                0,
-                SIG_CYCLE_LENGTH,
-                signatures.0.clone(),
-            ),
-            Assertion::sequence(
-                2 * PROJECTIVE_POINT_WIDTH + 7,
-                0,
-                SIG_CYCLE_LENGTH,
-                signatures.1.clone(),
-            ),
-            Assertion::sequence(
-                2 * PROJECTIVE_POINT_WIDTH + 8,
-                0,
-                SIG_CYCLE_LENGTH,
-                signatures.2.clone(),
-            ),
-            Assertion::sequence(
-                2 * PROJECTIVE_POINT_WIDTH + 9,
-                0,
-                SIG_CYCLE_LENGTH,
-                signatures.3.clone(),
-            ),
-            Assertion::sequence(
-                2 * PROJECTIVE_POINT_WIDTH + 10,
-                0,
-                SIG_CYCLE_LENGTH,
-                signatures.4.clone(),
-            ),
-            Assertion::sequence(
-                2 * PROJECTIVE_POINT_WIDTH + 11,
-                0,
-                SIG_CYCLE_LENGTH,
-                signatures.5.clone(),
-            ),
-        ]);
-        for i in 0..HASH_RATE_WIDTH {
-            assertions.push(Assertion::periodic(
-                i + 2 * PROJECTIVE_POINT_WIDTH + POINT_COORDINATE_WIDTH + 6,
-                0,
-                SIG_CYCLE_LENGTH,
-                BaseElement::ZERO,
-            ));
-        }
-        // Ending values
-        // We can compute R = S + h.P in the registers of S directly,
-        // hence checking the x_coord of R in the first registers (i.e. x(S))
-        // TODO: find a way to do this better with indexing
-        assertions.append(&mut vec![
-            Assertion::sequence(0, SCALAR_MUL_LENGTH + 1, SIG_CYCLE_LENGTH, signatures.0),
-            Assertion::sequence(1, SCALAR_MUL_LENGTH + 1, SIG_CYCLE_LENGTH, signatures.1),
-            Assertion::sequence(2, SCALAR_MUL_LENGTH + 1, SIG_CYCLE_LENGTH, signatures.2),
-            Assertion::sequence(3, SCALAR_MUL_LENGTH + 1, SIG_CYCLE_LENGTH, signatures.3),
-            Assertion::sequence(4, SCALAR_MUL_LENGTH + 1, SIG_CYCLE_LENGTH, signatures.4),
-            Assertion::sequence(5, SCALAR_MUL_LENGTH + 1, SIG_CYCLE_LENGTH, signatures.5),
-        ]);
-
-        assertions
+        build_assertions(&self.signatures)
     }
 
     fn get_periodic_column_values(&self) -> Vec<Vec<Self::BaseField>> {
-        // Start with empty periodic columns
-        let mut columns = vec![Vec::new(); POINT_COORDINATE_WIDTH + PROJECTIVE_POINT_WIDTH + 3];
-        // Stitch in the periodic columns applicable to all uses of Schnorr
-        stitch(
-            &mut columns,
-            periodic_columns(),
-            vec![
-                (0, 0),
-                (1, 1),
-                (2, 2),
-                (3, 3),
-                (4, 4),
-                (5, 5),
-                (6, 6),
-                (7, 7 + AFFINE_POINT_WIDTH),
-            ],
-        );
-        // Values to feed to the last registers of the hash state at the end of a cycle.
-        // Always zero (i.e. resetting the rate) or equal to the chunks of the message.
-        let mut hash_intermediate_inputs =
-            vec![
-                vec![BaseElement::ZERO; SIG_CYCLE_LENGTH * self.signatures.len()];
-                HASH_RATE_WIDTH
-            ];
-
-        // Public key coordinates extracted from the signed messages and to be used during scalar multiplications
-        let mut pub_keys = vec![
-            vec![BaseElement::ZERO; SIG_CYCLE_LENGTH * self.signatures.len()];
-            AFFINE_POINT_WIDTH
-        ];
-
-        let messages = prepare_messages(&self.voting_keys, &self.addresses);
-        for message_index in 0..self.signatures.len() {
-            for i in 0..NUM_HASH_ITER - 1 {
-                for (j, input) in hash_intermediate_inputs.iter_mut().enumerate() {
-                    input[i * HASH_CYCLE_LENGTH
-                        + NUM_HASH_ROUNDS
-                        + message_index * SIG_CYCLE_LENGTH] =
-                        messages[message_index][j + i * HASH_RATE_WIDTH];
-                }
-            }
-            for (i, key) in pub_keys.iter_mut().enumerate() {
-                key[message_index * SIG_CYCLE_LENGTH..(message_index + 1) * SIG_CYCLE_LENGTH]
-                    .fill(messages[message_index][i]);
-            }
-        }
-
-        // Stitch in the above columns in the appropriate places
-        stitch(
-            &mut columns,
-            pub_keys,
-            (7..7 + AFFINE_POINT_WIDTH).enumerate().collect(),
-        );
-        stitch(
-            &mut columns,
-            hash_intermediate_inputs,
-            (8 + AFFINE_POINT_WIDTH..8 + AFFINE_POINT_WIDTH + HASH_RATE_WIDTH)
-                .enumerate()
-                .collect(),
-        );
-
-        // Append the rescue round constants
-        columns.append(&mut rescue::get_round_constants());
-
-        columns
+        build_periodic_columns(
+            &self.voting_keys,
+            &self.addresses,
+            self.signatures.len(),
+            &self.domain,
+        )
     }
 }
 
@@ -578,6 +412,250 @@ pub(crate) fn evaluate_constraints<E: FieldElement + From<BaseElement>>(
     }
 }
 
+/// Evaluate the transition constraints of Schnorr signature verification,
+/// shared with the combined registration AIR
+pub(crate) fn evaluate_transition_constraints<E: FieldElement + From<BaseElement>>(
+    result: &mut [E],
+    current: &[E],
+    next: &[E],
+    periodic_values: &[E],
+) {
+    // Split periodic values
+    let global_mask = periodic_values[0];
+    let scalar_mult_flag = periodic_values[1];
+    let doubling_flag = periodic_values[2];
+    let hash_digest_register_flag = &periodic_values[3..7];
+    let vkey_point = &periodic_values[7..AFFINE_POINT_WIDTH + 7];
+    let hash_flag = periodic_values[AFFINE_POINT_WIDTH + 7];
+    let hash_internal_inputs = &periodic_values[AFFINE_POINT_WIDTH + 8..AFFINE_POINT_WIDTH + 15];
+    // Rescue round constants
+    let ark = &periodic_values[AFFINE_POINT_WIDTH + 15..];
+
+    let copy_hash_flag = not(hash_flag) * global_mask;
+    let final_point_addition_flag = not(scalar_mult_flag) * global_mask;
+    let addition_flag = not(doubling_flag) * scalar_mult_flag;
+
+    evaluate_constraints(
+        result,
+        current,
+        next,
+        ark,
+        doubling_flag,
+        addition_flag,
+        hash_digest_register_flag,
+        vkey_point,
+        final_point_addition_flag,
+        hash_flag,
+        copy_hash_flag,
+        hash_internal_inputs,
+    );
+}
+
+/// Build the assertions of Schnorr signature verification for the given signatures
+pub(crate) fn build_assertions(
+    signatures: &[([BaseElement; POINT_COORDINATE_WIDTH], Scalar)],
+) -> Vec<Assertion<BaseElement>> {
+    let signatures = transpose_signatures(signatures);
+    // Assert starting and ending values
+    let mut assertions = build_initial_point_assertions();
+
+    // TODO: find a way to do this better with indexing
+    assertions.append(&mut vec![
+        Assertion::sequence(
+            2 * PROJECTIVE_POINT_WIDTH + 6,
+            0,
+            SIG_CYCLE_LENGTH,
+            signatures.0.clone(),
+        ),
+        Assertion::sequence(
+            2 * PROJECTIVE_POINT_WIDTH + 7,
+            0,
+            SIG_CYCLE_LENGTH,
+            signatures.1.clone(),
+        ),
+        Assertion::sequence(
+            2 * PROJECTIVE_POINT_WIDTH + 8,
+            0,
+            SIG_CYCLE_LENGTH,
+            signatures.2.clone(),
+        ),
+        Assertion::sequence(
+            2 * PROJECTIVE_POINT_WIDTH + 9,
+            0,
+            SIG_CYCLE_LENGTH,
+            signatures.3.clone(),
+        ),
+        Assertion::sequence(
+            2 * PROJECTIVE_POINT_WIDTH + 10,
+            0,
+            SIG_CYCLE_LENGTH,
+            signatures.4.clone(),
+        ),
+        Assertion::sequence(
+            2 * PROJECTIVE_POINT_WIDTH + 11,
+            0,
+            SIG_CYCLE_LENGTH,
+            signatures.5.clone(),
+        ),
+    ]);
+    assertions.append(&mut build_initial_rate_assertions());
+    // Ending values
+    // We can compute R = S + h.P in the registers of S directly,
+    // hence checking the x_coord of R in the first registers (i.e. x(S))
+    // TODO: find a way to do this better with indexing
+    assertions.append(&mut vec![
+        Assertion::sequence(0, SCALAR_MUL_LENGTH + 1, SIG_CYCLE_LENGTH, signatures.0),
+        Assertion::sequence(1, SCALAR_MUL_LENGTH + 1, SIG_CYCLE_LENGTH, signatures.1),
+        Assertion::sequence(2, SCALAR_MUL_LENGTH + 1, SIG_CYCLE_LENGTH, signatures.2),
+        Assertion::sequence(3, SCALAR_MUL_LENGTH + 1, SIG_CYCLE_LENGTH, signatures.3),
+        Assertion::sequence(4, SCALAR_MUL_LENGTH + 1, SIG_CYCLE_LENGTH, signatures.4),
+        Assertion::sequence(5, SCALAR_MUL_LENGTH + 1, SIG_CYCLE_LENGTH, signatures.5),
+    ]);
+
+    assertions
+}
+
+/// Build the assertions on the initial values of the registers of the scalar
+/// multiplications and of the hash digest limbs, which do not depend on the signatures
+pub(crate) fn build_initial_point_assertions() -> Vec<Assertion<BaseElement>> {
+    let mut assertions = vec![];
+    // First projective points
+    for i in 0..PROJECTIVE_POINT_WIDTH {
+        if i == POINT_COORDINATE_WIDTH {
+            assertions.push(Assertion::periodic(
+                i,
+                0,
+                SIG_CYCLE_LENGTH,
+                BaseElement::ONE,
+            ));
+        } else {
+            assertions.push(Assertion::periodic(
+                i,
+                0,
+                SIG_CYCLE_LENGTH,
+                BaseElement::ZERO,
+            ));
+        }
+    }
+    assertions.push(Assertion::periodic(
+        PROJECTIVE_POINT_WIDTH,
+        0,
+        SIG_CYCLE_LENGTH,
+        BaseElement::ZERO,
+    ));
+    // Second projective points
+    for i in 0..PROJECTIVE_POINT_WIDTH {
+        if i == POINT_COORDINATE_WIDTH {
+            assertions.push(Assertion::periodic(
+                i + PROJECTIVE_POINT_WIDTH + 1,
+                0,
+                SIG_CYCLE_LENGTH,
+                BaseElement::ONE,
+            ));
+        } else {
+            assertions.push(Assertion::periodic(
+                i + PROJECTIVE_POINT_WIDTH + 1,
+                0,
+                SIG_CYCLE_LENGTH,
+                BaseElement::ZERO,
+            ));
+        }
+    }
+    for i in 0..5 {
+        assertions.push(Assertion::periodic(
+            i + 2 * PROJECTIVE_POINT_WIDTH + 1,
+            0,
+            SIG_CYCLE_LENGTH,
+            BaseElement::ZERO,
+        ));
+    }
+
+    assertions
+}
+
+/// Build the assertions on the initial values of the hash registers
+/// following the x coordinate of R, which do not depend on the signatures
+pub(crate) fn build_initial_rate_assertions() -> Vec<Assertion<BaseElement>> {
+    (0..HASH_RATE_WIDTH)
+        .map(|i| {
+            Assertion::periodic(
+                i + 2 * PROJECTIVE_POINT_WIDTH + POINT_COORDINATE_WIDTH + 6,
+                0,
+                SIG_CYCLE_LENGTH,
+                BaseElement::ZERO,
+            )
+        })
+        .collect()
+}
+
+/// Build the periodic columns of Schnorr signature verification, feeding
+/// the messages signed in the given domain to the hash
+pub(crate) fn build_periodic_columns(
+    voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+    addresses: &[Address],
+    num_signatures: usize,
+    domain: &SigningDomain,
+) -> Vec<Vec<BaseElement>> {
+    // Start with empty periodic columns
+    let mut columns = vec![Vec::new(); POINT_COORDINATE_WIDTH + PROJECTIVE_POINT_WIDTH + 3];
+    // Stitch in the periodic columns applicable to all uses of Schnorr
+    stitch(
+        &mut columns,
+        periodic_columns(),
+        vec![
+            (0, 0),
+            (1, 1),
+            (2, 2),
+            (3, 3),
+            (4, 4),
+            (5, 5),
+            (6, 6),
+            (7, 7 + AFFINE_POINT_WIDTH),
+        ],
+    );
+    // Values to feed to the last registers of the hash state at the end of a cycle.
+    // Always zero (i.e. resetting the rate) or equal to the chunks of the message.
+    let mut hash_intermediate_inputs =
+        vec![vec![BaseElement::ZERO; SIG_CYCLE_LENGTH * num_signatures]; HASH_RATE_WIDTH];
+
+    // Public key coordinates extracted from the signed messages and to be used during scalar multiplications
+    let mut pub_keys =
+        vec![vec![BaseElement::ZERO; SIG_CYCLE_LENGTH * num_signatures]; AFFINE_POINT_WIDTH];
+
+    let messages = prepare_messages(voting_keys, addresses, domain);
+    for message_index in 0..num_signatures {
+        for i in 0..NUM_HASH_ITER - 1 {
+            for (j, input) in hash_intermediate_inputs.iter_mut().enumerate() {
+                input[i * HASH_CYCLE_LENGTH + NUM_HASH_ROUNDS + message_index * SIG_CYCLE_LENGTH] =
+                    messages[message_index][j + i * HASH_RATE_WIDTH];
+            }
+        }
+        for (i, key) in pub_keys.iter_mut().enumerate() {
+            key[message_index * SIG_CYCLE_LENGTH..(message_index + 1) * SIG_CYCLE_LENGTH]
+                .fill(messages[message_index][i]);
+        }
+    }
+
+    // Stitch in the above columns in the appropriate places
+    stitch(
+        &mut columns,
+        pub_keys,
+        (7..7 + AFFINE_POINT_WIDTH).enumerate().collect(),
+    );
+    stitch(
+        &mut columns,
+        hash_intermediate_inputs,
+        (8 + AFFINE_POINT_WIDTH..8 + AFFINE_POINT_WIDTH + HASH_RATE_WIDTH)
+            .enumerate()
+            .collect(),
+    );
+
+    // Append the rescue round constants
+    columns.append(&mut rescue::get_round_constants());
+
+    columns
+}
+
 pub(crate) fn transition_constraint_degrees(
     num_tx: usize,
     cycle_length: usize,
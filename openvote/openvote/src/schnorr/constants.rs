@@ -10,8 +10,8 @@ pub(crate) use super::ecc::{
     AFFINE_POINT_WIDTH, GENERATOR, POINT_COORDINATE_WIDTH, PROJECTIVE_POINT_WIDTH,
 };
 pub(crate) use super::rescue::{
-    HASH_CYCLE_LENGTH, HASH_CYCLE_MASK, NUM_HASH_ROUNDS, RATE_WIDTH as HASH_RATE_WIDTH,
-    STATE_WIDTH as HASH_STATE_WIDTH,
+    DIGEST_SIZE, HASH_CYCLE_LENGTH, HASH_CYCLE_MASK, NUM_HASH_ROUNDS,
+    RATE_WIDTH as HASH_RATE_WIDTH, STATE_WIDTH as HASH_STATE_WIDTH,
 };
 
 // CONSTANTS
@@ -20,6 +20,14 @@ pub(crate) use super::rescue::{
 /// Max message length (in BaseElement) of a Schnorr signature
 pub const MSG_LENGTH: usize = AFFINE_POINT_WIDTH * 2 + 4;
 
+/// Number of bytes of the election identifier of a signing domain
+pub const DOMAIN_ELECTION_ID_SIZE: usize = 16;
+/// Number of elements of the signing domain absorbed after the address, i.e. the
+/// chain id packed in 2 elements followed by the election id packed in 4 elements
+pub const MSG_DOMAIN_WIDTH: usize = 6;
+/// Number of bytes of a serialized signing domain
+pub const BYTES_PER_SIGNING_DOMAIN: usize = 8 + DOMAIN_ELECTION_ID_SIZE;
+
 // Rescue constants
 
 /// Number of hash iterations for hashing the message
@@ -43,3 +51,20 @@ pub const SCALAR_MUL_LENGTH: usize = 510;
 pub const TRACE_WIDTH: usize = 2 * PROJECTIVE_POINT_WIDTH + 2 + 4 + HASH_STATE_WIDTH;
 /// Total number of steps in the trace for a single signature
 pub const SIG_CYCLE_LENGTH: usize = 512;
+
+// Sequence hash constants
+
+/// Number of elements of the message of a signature which are not padding,
+/// i.e. the voting key followed by the Ethereum address packed in 4 elements
+pub const MSG_INPUT_WIDTH: usize = AFFINE_POINT_WIDTH + 4;
+/// Number of elements of a (voting key, address, signature) tuple absorbed into
+/// the sequence hash, the signature being represented by the x coordinate of R
+pub const SEQUENCE_TUPLE_WIDTH: usize = MSG_INPUT_WIDTH + POINT_COORDINATE_WIDTH;
+/// Number of Rescue permutations absorbing a tuple
+pub const SEQUENCE_NUM_BLOCKS: usize =
+    (SEQUENCE_TUPLE_WIDTH + HASH_RATE_WIDTH - 1) / HASH_RATE_WIDTH;
+/// Number of elements of a tuple padded with zeros to a multiple of the rate
+pub const SEQUENCE_TUPLE_LENGTH: usize = SEQUENCE_NUM_BLOCKS * HASH_RATE_WIDTH;
+/// Total number of registers in the trace of the sequence hash variant
+// Schnorr registers, tuple of the current signature, sequence hash state
+pub const SEQUENCE_TRACE_WIDTH: usize = TRACE_WIDTH + SEQUENCE_TUPLE_WIDTH + HASH_STATE_WIDTH;
@@ -8,20 +8,19 @@
 
 use self::constants::*;
 use super::utils::{
-    ecc, field,
+    ecc::{self, projective_to_elements, PointValidationError},
+    field,
     rescue::{self, Rescue63},
+    transcript::Transcript,
 };
-use bitvec::{order::Lsb0, view::AsBits};
-use rand_core::OsRng;
-use web3::types::Address;
+use crate::compat::curve::{AffinePoint, Scalar};
+use crate::compat::proof_options;
+use crate::utils::address::Address;
+use rand_core::{OsRng, RngCore};
 use winterfell::{
-    crypto::Hasher,
-    math::{
-        curves::curve_f63::{AffinePoint, ProjectivePoint, Scalar},
-        fields::f63::BaseElement,
-        FieldElement,
-    },
-    FieldExtension, HashFunction, ProofOptions, Prover, StarkProof, VerifierError,
+    math::{fields::f63::BaseElement, FieldElement, StarkField},
+    ByteReader, ByteWriter, Deserializable, DeserializationError, FieldExtension, HashFunction,
+    ProofOptions, Prover, Serializable, SliceReader, StarkProof, VerifierError,
 };
 
 #[cfg(not(feature = "std"))]
@@ -36,13 +35,19 @@ use winterfell::{math::log2, Trace};
 
 pub(crate) mod constants;
 mod trace;
+pub(crate) use trace::{build_sig_info, SigInfo};
 
-mod air;
-pub(crate) use air::{PublicInputs, SchnorrAir};
+pub(crate) mod air;
+pub use air::PublicInputs;
+pub(crate) use air::SchnorrAir;
 
 mod prover;
 pub(crate) use prover::SchnorrProver;
 
+mod sequence;
+pub use sequence::{sequence_digest, SequencePublicInputs};
+pub(crate) use sequence::{SchnorrSequenceAir, SchnorrSequenceProver};
+
 #[cfg(test)]
 mod tests;
 
@@ -53,7 +58,7 @@ mod tests;
 pub fn get_example(num_signatures: usize) -> SchnorrExample {
     SchnorrExample::new(
         // TODO: make it customizable
-        ProofOptions::new(
+        proof_options(
             42,
             8,
             0,
@@ -137,9 +142,9 @@ impl SchnorrExample {
 
         let prover = SchnorrProver::new(
             self.options.clone(),
-            self.voting_keys.clone(),
-            self.addresses.clone(),
-            self.signatures.clone(),
+            &self.voting_keys,
+            &self.addresses,
+            &self.signatures,
         );
 
         // generate the execution trace
@@ -164,8 +169,33 @@ impl SchnorrExample {
             voting_keys: self.voting_keys.clone(),
             addresses: self.addresses.clone(),
             signatures: self.signatures.clone(),
+            domain: SigningDomain::default(),
+        };
+        crate::compat::verify::<SchnorrAir>(proof, pub_inputs)
+    }
+
+    /// Proves the validity of a sequence of Schnorr signatures, with the sequence
+    /// hash of the signed tuples as only public input
+    pub fn prove_sequence(&self) -> StarkProof {
+        let prover = SchnorrSequenceProver::new(
+            self.options.clone(),
+            &self.voting_keys,
+            &self.addresses,
+            &self.signatures,
+        );
+        let trace = prover.build_trace();
+        prover.prove(trace).unwrap()
+    }
+
+    /// Verifies the validity of a proof generated by prove_sequence
+    /// against the sequence hash of the signed tuples
+    pub fn verify_sequence(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        let pub_inputs = SequencePublicInputs {
+            num_signatures: self.voting_keys.len(),
+            digest: sequence_digest(&self.voting_keys, &self.addresses, &self.signatures),
+            domain: SigningDomain::default(),
         };
-        winterfell::verify::<SchnorrAir>(proof, pub_inputs)
+        crate::compat::verify::<SchnorrSequenceAir>(proof, pub_inputs)
     }
 
     #[cfg(test)]
@@ -176,6 +206,7 @@ impl SchnorrExample {
             voting_keys: self.voting_keys.clone(),
             addresses: self.addresses.clone(),
             signatures: self.signatures.clone(),
+            domain: SigningDomain::default(),
         };
         let mut rng = OsRng;
 
@@ -192,7 +223,7 @@ impl SchnorrExample {
             wrong_address[fault_position] ^= 1;
             pub_inputs.addresses[fault_index] = Address::from_slice(&wrong_address);
         }
-        winterfell::verify::<SchnorrAir>(proof, pub_inputs)
+        crate::compat::verify::<SchnorrAir>(proof, pub_inputs)
     }
 
     #[cfg(test)]
@@ -208,8 +239,100 @@ impl SchnorrExample {
             voting_keys: self.voting_keys.clone(),
             addresses: self.addresses.clone(),
             signatures: wrong_signatures,
+            domain: SigningDomain::default(),
         };
-        winterfell::verify::<SchnorrAir>(proof, pub_inputs)
+        crate::compat::verify::<SchnorrAir>(proof, pub_inputs)
+    }
+}
+
+// ERRORS
+// ================================================================================================
+
+/// Errors raised when a Schnorr signature cannot be verified
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchnorrVerificationError {
+    /// A coordinate of the public key or of the signature is not a canonical field element
+    NonCanonicalElement,
+    /// The public key is not on the curve
+    OffCurveKey,
+    /// The public key has a component of small order, which would let a voter
+    /// skew the blinding keys of other voters
+    SmallOrderKey,
+    /// The public key is the point at infinity, for which any signature can be forged
+    IdentityKey,
+    /// The signature does not match the public key and the message
+    InvalidSignature,
+}
+
+// SIGNING DOMAIN
+// ================================================================================================
+
+/// Deployment and election to which registration signatures are bound, so that
+/// a signature cannot be replayed on another chain or in another election.
+/// The default domain is unbound and leaves the signed message unchanged.
+///
+/// It is serialized as | chain id (u64, little-endian) | election id (16 bytes) |
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SigningDomain {
+    /// Identifier of the chain on which the verifier is deployed
+    pub chain_id: u64,
+    /// Identifier of the election
+    pub election_id: [u8; DOMAIN_ELECTION_ID_SIZE],
+}
+
+impl SigningDomain {
+    /// Create the signing domain of an election on the given chain
+    pub fn new(chain_id: u64, election_id: [u8; DOMAIN_ELECTION_ID_SIZE]) -> Self {
+        Self {
+            chain_id,
+            election_id,
+        }
+    }
+
+    /// Return true if signatures are bound to a chain or an election
+    pub fn is_bound(&self) -> bool {
+        *self != Self::default()
+    }
+
+    /// Elements of the domain absorbed into the signed message, all zero when unbound
+    pub fn to_elements(&self) -> [BaseElement; MSG_DOMAIN_WIDTH] {
+        let mut transcript = Transcript::new();
+        self.absorb_into(&mut transcript);
+        let mut elements = [BaseElement::ZERO; MSG_DOMAIN_WIDTH];
+        elements.copy_from_slice(transcript.message());
+        elements
+    }
+
+    /// Deserialize a signing domain, rejecting any encoding
+    /// which is not exactly BYTES_PER_SIGNING_DOMAIN bytes long
+    pub fn from_bytes(source: &[u8]) -> Result<Self, DeserializationError> {
+        let mut source = SliceReader::new(source);
+        let domain = Self::read_from(&mut source)?;
+        if source.has_more_bytes() {
+            return Err(DeserializationError::UnconsumedBytes);
+        }
+        Ok(domain)
+    }
+
+    fn absorb_into(&self, transcript: &mut Transcript) {
+        transcript.absorb_bytes(&self.chain_id.to_be_bytes());
+        transcript.absorb_bytes(&self.election_id);
+    }
+}
+
+impl Serializable for SigningDomain {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u64(self.chain_id);
+        target.write_u8_slice(&self.election_id);
+    }
+}
+
+impl Deserializable for SigningDomain {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let chain_id = source.read_u64()?;
+        let mut election_id = [0u8; DOMAIN_ELECTION_ID_SIZE];
+        election_id.copy_from_slice(&source.read_u8_vec(DOMAIN_ELECTION_ID_SIZE)?);
+        Ok(Self::new(chain_id, election_id))
     }
 }
 
@@ -226,27 +349,51 @@ pub(crate) fn sign_messages(
     let mut signatures = Vec::with_capacity(voting_keys.len());
 
     for i in 0..voting_keys.len() {
-        let r = Scalar::random(&mut rng);
-        let r_point = AffinePoint::from(AffinePoint::generator() * r);
-        let message = prepare_message(&voting_keys[i], addresses[i]);
-        let h = hash_message(&r_point.get_x(), &message);
-        let mut h_bytes = [0u8; 32];
-        // take the first 4 elements of the hash
-        for (i, h_word) in h.iter().enumerate().take(4) {
-            h_bytes[8 * i..8 * i + 8].copy_from_slice(&h_word.to_bytes());
-        }
-        let h_bits = h_bytes.as_bits::<Lsb0>();
-
-        // Reconstruct a scalar from the binary sequence of h
-        let h_scalar = Scalar::from_bits(h_bits);
-
-        let s = r - secret_keys[i] * h_scalar;
-        signatures.push((r_point.get_x(), s))
+        signatures.push(sign_message_with_rng(
+            secret_keys[i],
+            &voting_keys[i],
+            addresses[i],
+            &mut rng,
+        ));
     }
 
     signatures
 }
 
+/// Computes the Schnorr signature of (voting_key, address), drawing
+/// the nonce from the given random number generator
+pub fn sign_message_with_rng<R: RngCore>(
+    secret_key: Scalar,
+    voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    address: Address,
+    rng: &mut R,
+) -> ([BaseElement; POINT_COORDINATE_WIDTH], Scalar) {
+    sign_message_in_domain(
+        secret_key,
+        voting_key,
+        address,
+        &SigningDomain::default(),
+        rng,
+    )
+}
+
+/// Computes the Schnorr signature of (voting_key, address) bound to the given
+/// signing domain, drawing the nonce from the given random number generator
+pub fn sign_message_in_domain<R: RngCore>(
+    secret_key: Scalar,
+    voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    address: Address,
+    domain: &SigningDomain,
+    rng: &mut R,
+) -> ([BaseElement; POINT_COORDINATE_WIDTH], Scalar) {
+    let r = Scalar::random(rng);
+    let r_point = AffinePoint::from(ecc::mul_generator_constant_time(r));
+    let message = prepare_message(voting_key, address, domain);
+    let h_scalar = build_transcript(&r_point.get_x(), &message).challenge_scalar();
+
+    (r_point.get_x(), r - secret_key * h_scalar)
+}
+
 /// Naively verify Schnorr signatures
 pub fn naive_verify_signatures(
     voting_keys: &Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
@@ -254,140 +401,159 @@ pub fn naive_verify_signatures(
     signatures: &Vec<([BaseElement; POINT_COORDINATE_WIDTH], Scalar)>,
 ) -> bool {
     for i in 0..voting_keys.len() {
-        if !verify_signature(voting_keys[i], addresses[i], signatures[i]) {
+        if verify_signature(
+            voting_keys[i],
+            addresses[i],
+            signatures[i],
+            &SigningDomain::default(),
+        )
+        .is_err()
+        {
             return false;
         }
     }
     true
 }
 
-/// Verify a Schnorr signature
+/// Verify a Schnorr signature bound to the given signing domain. Off-curve voting
+/// keys and non-canonical coordinates are reported as errors, so that a crafted
+/// registration cannot make the verifier panic.
 #[inline]
 pub(crate) fn verify_signature(
     voting_key: [BaseElement; AFFINE_POINT_WIDTH],
     address: Address,
     signature: ([BaseElement; POINT_COORDINATE_WIDTH], Scalar),
-) -> bool {
+    domain: &SigningDomain,
+) -> Result<(), SchnorrVerificationError> {
+    let message = prepare_message(&voting_key, address, domain);
+    let voting_key = check_public_key(voting_key, &signature.0)?;
     let s_point = AffinePoint::generator() * signature.1;
-    let message = prepare_message(&voting_key, address);
-    let voting_key = AffinePoint::from_raw_coordinates(voting_key);
-    assert!(voting_key.is_on_curve());
-    let h = hash_message(&signature.0, &message);
-    let mut h_bytes = [0u8; 32];
-    for (i, h_word) in h.iter().enumerate().take(4) {
-        h_bytes[8 * i..8 * i + 8].copy_from_slice(&h_word.to_bytes());
-    }
-    let h_bits = h_bytes.as_bits::<Lsb0>();
-    // Reconstruct a scalar from the binary sequence of h
-    let h_scalar = Scalar::from_bits(h_bits);
+    let h_scalar = build_transcript(&signature.0, &message).challenge_scalar();
     let h_pubkey_point = voting_key * h_scalar;
     let r_point = AffinePoint::from(s_point + h_pubkey_point);
-    r_point.get_x() == signature.0
+    if r_point.is_identity() || r_point.get_x() != signature.0 {
+        return Err(SchnorrVerificationError::InvalidSignature);
+    }
+    Ok(())
+}
+
+/// Computes a Schnorr signature of a Rescue digest
+pub(crate) fn sign_digest(
+    secret_key: Scalar,
+    digest: &rescue::Hash,
+) -> ([BaseElement; POINT_COORDINATE_WIDTH], Scalar) {
+    let r = Scalar::random(OsRng);
+    let r_point = AffinePoint::from(ecc::mul_generator_constant_time(r));
+    let h_scalar = build_transcript(&r_point.get_x(), &digest.to_elements()).challenge_scalar();
+    (r_point.get_x(), r - secret_key * h_scalar)
+}
+
+/// Verify a Schnorr signature of a Rescue digest
+pub(crate) fn verify_digest_signature(
+    public_key: [BaseElement; AFFINE_POINT_WIDTH],
+    digest: &rescue::Hash,
+    signature: ([BaseElement; POINT_COORDINATE_WIDTH], Scalar),
+) -> Result<(), SchnorrVerificationError> {
+    let public_key = check_public_key(public_key, &signature.0)?;
+    let h_scalar = build_transcript(&signature.0, &digest.to_elements()).challenge_scalar();
+    let r_point = AffinePoint::from(AffinePoint::generator() * signature.1 + public_key * h_scalar);
+    if r_point.is_identity() || r_point.get_x() != signature.0 {
+        return Err(SchnorrVerificationError::InvalidSignature);
+    }
+    Ok(())
+}
+
+/// Check that the coordinates of a public key and of the x coordinate of R
+/// are canonical field elements, and that the public key is a point of the
+/// subgroup generated by G other than the identity
+#[inline]
+fn check_public_key(
+    public_key: [BaseElement; AFFINE_POINT_WIDTH],
+    signature_r: &[BaseElement; POINT_COORDINATE_WIDTH],
+) -> Result<AffinePoint, SchnorrVerificationError> {
+    if !public_key
+        .iter()
+        .chain(signature_r.iter())
+        .all(|element| element.as_int() < BaseElement::MODULUS)
+    {
+        return Err(SchnorrVerificationError::NonCanonicalElement);
+    }
+    let public_key = ecc::validate_point(&public_key).map_err(|error| match error {
+        PointValidationError::NonCanonicalElement => SchnorrVerificationError::NonCanonicalElement,
+        PointValidationError::OffCurve => SchnorrVerificationError::OffCurveKey,
+        PointValidationError::SmallOrderComponent => SchnorrVerificationError::SmallOrderKey,
+    })?;
+    if public_key.is_identity() {
+        return Err(SchnorrVerificationError::IdentityKey);
+    }
+    Ok(public_key)
 }
 
 pub(crate) fn random_key_pairs(
     num_pairs: usize,
 ) -> (Vec<Scalar>, Vec<[BaseElement; AFFINE_POINT_WIDTH]>) {
     let mut rng = OsRng;
-    let secret_keys = (0..num_pairs)
-        .map(|_| Scalar::random(&mut rng))
-        .collect::<Vec<Scalar>>();
-    let voting_keys = secret_keys
-        .iter()
-        .map(|&s| projective_to_elements(ProjectivePoint::generator() * s))
-        .collect::<Vec<[BaseElement; AFFINE_POINT_WIDTH]>>();
-    (secret_keys, voting_keys)
+    (0..num_pairs)
+        .map(|_| random_key_pair_with_rng(&mut rng))
+        .unzip()
 }
 
-#[inline]
-pub(crate) fn projective_to_elements(point: ProjectivePoint) -> [BaseElement; AFFINE_POINT_WIDTH] {
-    let mut result = [BaseElement::ZERO; AFFINE_POINT_WIDTH];
-    result[..POINT_COORDINATE_WIDTH].copy_from_slice(&AffinePoint::from(point).get_x());
-    result[POINT_COORDINATE_WIDTH..AFFINE_POINT_WIDTH]
-        .copy_from_slice(&AffinePoint::from(point).get_y());
-    result
+/// Generate a secret key and its voting key, drawing the secret key
+/// from the given random number generator
+pub fn random_key_pair_with_rng<R: RngCore>(
+    rng: &mut R,
+) -> (Scalar, [BaseElement; AFFINE_POINT_WIDTH]) {
+    let secret_key = Scalar::random(rng);
+    let voting_key = projective_to_elements(ecc::mul_generator_constant_time(secret_key));
+    (secret_key, voting_key)
 }
 
 /// Prepare messages that voters need to sign based
 /// on addresses and voting keys
 #[inline]
 pub(crate) fn prepare_messages(
-    voting_keys: &Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
-    addresses: &Vec<Address>,
+    voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+    addresses: &[Address],
+    domain: &SigningDomain,
 ) -> Vec<[BaseElement; MSG_LENGTH]> {
     let mut messages = Vec::with_capacity(MSG_LENGTH);
 
     for i in 0..voting_keys.len() {
-        messages.push(prepare_message(&voting_keys[i], addresses[i]));
+        messages.push(prepare_message(&voting_keys[i], addresses[i], domain));
     }
 
     messages
 }
 
+/// Prepare the message signed by a voter, i.e. the voting key, the packed address
+/// and the signing domain, padded with zeros. The domain takes the place of padding,
+/// hence unbound messages are the same as before domains were introduced.
 #[inline]
 pub(crate) fn prepare_message(
     voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
     address: Address,
+    domain: &SigningDomain,
 ) -> [BaseElement; MSG_LENGTH] {
+    let mut transcript = Transcript::new();
+    transcript.absorb_point(voting_key);
+    transcript.absorb_bytes(address.as_bytes());
+    domain.absorb_into(&mut transcript);
+    transcript.pad_to_length(MSG_LENGTH);
     let mut message = [BaseElement::ZERO; MSG_LENGTH];
-    // Voting key
-    message[..AFFINE_POINT_WIDTH].copy_from_slice(voting_key);
-    // Ethereum address
-    let address_bytes = address.as_bytes();
-    for i in (0..20).step_by(5) {
-        message[AFFINE_POINT_WIDTH + (i / 5)] = BaseElement::from(u64::from_be_bytes([
-            address_bytes[i],
-            address_bytes[i + 1],
-            address_bytes[i + 2],
-            address_bytes[i + 3],
-            address_bytes[i + 4],
-            0,
-            0,
-            0,
-        ]));
-    }
+    message.copy_from_slice(transcript.message());
     message
 }
 
-fn hash_message(
-    input: &[BaseElement; POINT_COORDINATE_WIDTH],
-    message: &[BaseElement; MSG_LENGTH],
-) -> [BaseElement; HASH_RATE_WIDTH] {
-    let mut h = Rescue63::digest(input);
-    let mut message_chunk = rescue::Hash::new(
-        message[0], message[1], message[2], message[3], message[4], message[5], message[6],
-    );
-    h = Rescue63::merge(&[h, message_chunk]);
-    message_chunk = rescue::Hash::new(
-        message[7],
-        message[8],
-        message[9],
-        message[10],
-        message[11],
-        message[12],
-        message[13],
-    );
-    h = Rescue63::merge(&[h, message_chunk]);
-    message_chunk = rescue::Hash::new(
-        message[14],
-        message[15],
-        message[16],
-        message[17],
-        message[18],
-        message[19],
-        message[20],
-    );
-    h = Rescue63::merge(&[h, message_chunk]);
-    message_chunk = rescue::Hash::new(
-        message[21],
-        message[22],
-        message[23],
-        message[24],
-        message[25],
-        message[26],
-        message[27],
-    );
-    h = Rescue63::merge(&[h, message_chunk]);
-
-    h.to_elements()
+/// Build the transcript of a signature, i.e. the x coordinate of R
+/// padded to a full block, followed by the signed message
+#[inline]
+pub(crate) fn build_transcript(
+    r_x: &[BaseElement; POINT_COORDINATE_WIDTH],
+    message: &[BaseElement],
+) -> Transcript {
+    let mut transcript = Transcript::new();
+    transcript.absorb_elements(r_x);
+    transcript.pad_to_length(HASH_RATE_WIDTH);
+    transcript.absorb_elements(message);
+    transcript
 }
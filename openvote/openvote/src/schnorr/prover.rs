@@ -2,12 +2,11 @@ use super::constants::*;
 use super::trace::*;
 use super::PublicInputs;
 use super::SchnorrAir;
+use super::SigningDomain;
+use crate::compat::curve::Scalar;
+use crate::utils::address::Address;
 use bitvec::{order::Lsb0, view::AsBits};
-use web3::types::Address;
-use winterfell::{
-    math::{curves::curve_f63::Scalar, fields::f63::BaseElement},
-    ProofOptions, Prover, TraceTable,
-};
+use winterfell::{math::fields::f63::BaseElement, ProofOptions, Prover, TraceTable};
 
 #[cfg(feature = "concurrent")]
 use winterfell::iterators::*;
@@ -18,37 +17,61 @@ use alloc::vec::Vec;
 // SCHNORR PROVER
 // ================================================================================================
 
-pub struct SchnorrProver {
+// inputs are borrowed from the caller to avoid cloning them for large elections
+pub struct SchnorrProver<'a> {
     options: ProofOptions,
-    voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
-    addresses: Vec<Address>,
-    signatures: Vec<([BaseElement; POINT_COORDINATE_WIDTH], Scalar)>,
+    voting_keys: &'a [[BaseElement; AFFINE_POINT_WIDTH]],
+    addresses: &'a [Address],
+    signatures: &'a [([BaseElement; POINT_COORDINATE_WIDTH], Scalar)],
+    domain: SigningDomain,
 }
 
-impl SchnorrProver {
+impl<'a> SchnorrProver<'a> {
     pub fn new(
         options: ProofOptions,
-        voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
-        addresses: Vec<Address>,
-        signatures: Vec<([BaseElement; POINT_COORDINATE_WIDTH], Scalar)>,
+        voting_keys: &'a [[BaseElement; AFFINE_POINT_WIDTH]],
+        addresses: &'a [Address],
+        signatures: &'a [([BaseElement; POINT_COORDINATE_WIDTH], Scalar)],
     ) -> Self {
         Self {
             options,
             voting_keys,
             addresses,
             signatures,
+            domain: SigningDomain::default(),
         }
     }
 
+    /// Verify signatures bound to the given signing domain
+    pub fn with_domain(mut self, domain: SigningDomain) -> Self {
+        self.domain = domain;
+        self
+    }
+
     pub fn build_trace(&self) -> TraceTable<BaseElement> {
+        let sig_infos = (0..self.voting_keys.len())
+            .map(|i| {
+                build_sig_info(
+                    &self.voting_keys[i],
+                    self.addresses[i],
+                    &self.signatures[i],
+                    &self.domain,
+                )
+            })
+            .collect::<Vec<SigInfo>>();
+        self.build_trace_from_sig_infos(&sig_infos)
+    }
+
+    /// Build the trace from signature information prepared beforehand,
+    /// e.g. in a precomputation stage shared with other sub-proofs
+    pub fn build_trace_from_sig_infos(&self, sig_infos: &[SigInfo]) -> TraceTable<BaseElement> {
         // allocate memory to hold the trace table
         let trace_length = SIG_CYCLE_LENGTH * self.voting_keys.len();
         let mut trace = TraceTable::new(TRACE_WIDTH, trace_length);
         trace.fragments(SIG_CYCLE_LENGTH).for_each(|mut sig_trace| {
             let i = sig_trace.index();
             let vkey_point = self.voting_keys[i];
-            let (message, s_bytes, h_bytes) =
-                build_sig_info(&vkey_point, self.addresses[i], &self.signatures[i]);
+            let (message, s_bytes, h_bytes) = sig_infos[i];
             let s_bits = s_bytes.as_bits::<Lsb0>();
             let h_bits = h_bytes.as_bits::<Lsb0>();
             sig_trace.fill(
@@ -64,7 +87,7 @@ impl SchnorrProver {
     }
 }
 
-impl Prover for SchnorrProver {
+impl<'a> Prover for SchnorrProver<'a> {
     type BaseField = BaseElement;
     type Air = SchnorrAir;
     type Trace = TraceTable<BaseElement>;
@@ -76,9 +99,10 @@ impl Prover for SchnorrProver {
     // where only initial and final Merkle roots are provided to the verifier.
     fn get_pub_inputs(&self, _trace: &Self::Trace) -> PublicInputs {
         PublicInputs {
-            voting_keys: self.voting_keys.clone(),
-            addresses: self.addresses.clone(),
-            signatures: self.signatures.clone(),
+            voting_keys: self.voting_keys.to_vec(),
+            addresses: self.addresses.to_vec(),
+            signatures: self.signatures.to_vec(),
+            domain: self.domain,
         }
     }
 
@@ -0,0 +1,511 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// SEQUENCE HASH VARIANT
+// ================================================================================================
+// Variant of Schnorr signature verification whose public inputs are reduced to a running Rescue
+// hash of the signed (voting key, address, signature) tuples, instead of the tuples themselves.
+// The tuples are moved into the trace, and the AIR absorbs each of them into a sponge carried
+// from one signature cycle to the next. Only the final digest is asserted, so that verification
+// only needs the digest stored by the contract, which absorbs each tuple when it is submitted.
+//
+// Within the cycle of a signature, the tuple is absorbed in SEQUENCE_NUM_BLOCKS blocks: the
+// block i is added to the rate at step 8i + 7, and permuted at steps 8(i + 1)..8(i + 1) + 7.
+// The sponge state is copied on all other steps, including across signature cycles.
+//
+// The scalar s of signatures is not absorbed, as in the Schnorr AIR it is a witness of the
+// verification equation: the AIR only binds the x coordinate of R.
+
+use super::{air, constants::*, prepare_message, rescue, trace::*, Rescue63, SigningDomain};
+use crate::compat::curve::Scalar;
+use crate::utils::{address::Address, are_equal, not, EvaluationResult};
+use bitvec::{order::Lsb0, view::AsBits};
+use winterfell::{
+    math::{fields::f63::BaseElement, FieldElement},
+    Air, AirContext, Assertion, ByteReader, ByteWriter, Deserializable, DeserializationError,
+    EvaluationFrame, ProofOptions, Prover, Serializable, SliceReader, Trace, TraceInfo, TraceTable,
+    TransitionConstraintDegree,
+};
+
+#[cfg(feature = "concurrent")]
+use winterfell::iterators::*;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// Offset of the hash registers among the Schnorr registers
+const SIG_HASH_OFFSET: usize = 2 * PROJECTIVE_POINT_WIDTH + 6;
+/// Offset of the registers holding the tuple of the current signature
+const TUPLE_OFFSET: usize = TRACE_WIDTH;
+/// Offset of the registers of the sequence hash state
+const SEQUENCE_OFFSET: usize = TRACE_WIDTH + SEQUENCE_TUPLE_WIDTH;
+/// Offset of the constraints binding the x coordinate of R to the tuple
+const BINDING_OFFSET: usize = TRACE_WIDTH + SEQUENCE_TUPLE_WIDTH;
+/// Offset of the constraints of the sequence hash
+const SEQUENCE_CONSTRAINT_OFFSET: usize = BINDING_OFFSET + POINT_COORDINATE_WIDTH;
+
+// PUBLIC INPUTS
+// ================================================================================================
+
+/// Public inputs of Schnorr signature verification reduced to the sequence hash
+/// of the signed tuples, as stored by the on-chain verifier.
+///
+/// Fields are serialized in the following order:
+/// | n (u32, little-endian) | digest (DIGEST_SIZE field elements) |
+/// followed by the signing domain when it is bound.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SequencePublicInputs {
+    /// Number of signatures, which must be a power of two greater than 1
+    pub num_signatures: usize,
+    /// Sequence hash of the (voting key, address, signature) tuples
+    pub digest: [BaseElement; DIGEST_SIZE],
+    /// Signing domain of the signatures (not read back), set by the verifier
+    pub domain: SigningDomain,
+}
+
+impl Serializable for SequencePublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u32(self.num_signatures as u32);
+        Serializable::write_batch_into(&self.digest, target);
+        if self.domain.is_bound() {
+            self.domain.write_into(target);
+        }
+    }
+}
+
+impl Deserializable for SequencePublicInputs {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let num_signatures = source.read_u32()? as usize;
+        let mut digest = [BaseElement::ZERO; DIGEST_SIZE];
+        digest.copy_from_slice(&BaseElement::read_batch_from(source, DIGEST_SIZE)?);
+        Ok(Self {
+            num_signatures,
+            digest,
+            domain: SigningDomain::default(),
+        })
+    }
+}
+
+impl SequencePublicInputs {
+    /// Deserialize public inputs from bytes in the format described above
+    pub fn from_bytes(source: &[u8]) -> Result<Self, DeserializationError> {
+        let mut source = SliceReader::new(source);
+        Self::read_from(&mut source)
+    }
+}
+
+// SEQUENCE AIR
+// ================================================================================================
+
+pub struct SchnorrSequenceAir {
+    context: AirContext<BaseElement>,
+    digest: [BaseElement; DIGEST_SIZE],
+    domain: [BaseElement; MSG_DOMAIN_WIDTH],
+}
+
+impl Air for SchnorrSequenceAir {
+    type BaseField = BaseElement;
+    type PublicInputs = SequencePublicInputs;
+
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    fn new(trace_info: TraceInfo, pub_inputs: SequencePublicInputs, options: ProofOptions) -> Self {
+        let degrees = transition_constraint_degrees(pub_inputs.num_signatures);
+        assert_eq!(SEQUENCE_TRACE_WIDTH, trace_info.width());
+        SchnorrSequenceAir {
+            context: AirContext::new(trace_info, degrees, options),
+            digest: pub_inputs.digest,
+            domain: pub_inputs.domain.to_elements(),
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+
+        // Expected state width is SEQUENCE_TRACE_WIDTH field elements
+        debug_assert_eq!(SEQUENCE_TRACE_WIDTH, current.len());
+        debug_assert_eq!(SEQUENCE_TRACE_WIDTH, next.len());
+
+        // Split periodic values
+        let global_mask = periodic_values[0];
+        let scalar_mult_flag = periodic_values[1];
+        let doubling_flag = periodic_values[2];
+        let hash_digest_register_flag = &periodic_values[3..7];
+        let hash_flag = periodic_values[7];
+        let first_step_flag = periodic_values[8];
+        let block_flags = &periodic_values[9..9 + SEQUENCE_NUM_BLOCKS];
+        let sequence_hash_flag = periodic_values[9 + SEQUENCE_NUM_BLOCKS];
+        // Rescue round constants
+        let ark = &periodic_values[10 + SEQUENCE_NUM_BLOCKS..];
+
+        let copy_hash_flag = not(hash_flag) * global_mask;
+        let final_point_addition_flag = not(scalar_mult_flag) * global_mask;
+        let addition_flag = not(doubling_flag) * scalar_mult_flag;
+
+        // The chunks of the message and the blocks of the tuple are both read from
+        // the tuple registers, the message being the first MSG_INPUT_WIDTH elements
+        // followed by the signing domain, which is a public input
+        let tuple = &current[TUPLE_OFFSET..TUPLE_OFFSET + SEQUENCE_TUPLE_WIDTH];
+        let mut message_inputs = [E::ZERO; HASH_RATE_WIDTH];
+        let mut tuple_inputs = [E::ZERO; HASH_RATE_WIDTH];
+        for (i, &flag) in block_flags.iter().enumerate() {
+            for j in 0..HASH_RATE_WIDTH {
+                let k = i * HASH_RATE_WIDTH + j;
+                if k < MSG_INPUT_WIDTH {
+                    message_inputs[j] += flag * tuple[k];
+                } else if k < MSG_INPUT_WIDTH + MSG_DOMAIN_WIDTH {
+                    message_inputs[j] += flag * E::from(self.domain[k - MSG_INPUT_WIDTH]);
+                }
+                if k < SEQUENCE_TUPLE_WIDTH {
+                    tuple_inputs[j] += flag * tuple[k];
+                }
+            }
+        }
+
+        // Schnorr signature verification, with the voting key and the
+        // message read from the tuple registers instead of periodic columns
+        air::evaluate_constraints(
+            &mut result[..TRACE_WIDTH],
+            &current[..TRACE_WIDTH],
+            &next[..TRACE_WIDTH],
+            ark,
+            doubling_flag,
+            addition_flag,
+            hash_digest_register_flag,
+            &tuple[..AFFINE_POINT_WIDTH],
+            final_point_addition_flag,
+            hash_flag,
+            copy_hash_flag,
+            &message_inputs,
+        );
+
+        // The tuple is constant within the cycle of a signature
+        for i in 0..SEQUENCE_TUPLE_WIDTH {
+            result.agg_constraint(
+                TUPLE_OFFSET + i,
+                global_mask,
+                are_equal(current[TUPLE_OFFSET + i], next[TUPLE_OFFSET + i]),
+            );
+        }
+
+        // The x coordinate of R of the tuple initializes the hash of the message,
+        // and matches the x coordinate of s.G + h.P at the end of the cycle
+        for i in 0..POINT_COORDINATE_WIDTH {
+            let signature_r = tuple[MSG_INPUT_WIDTH + i];
+            result.agg_constraint(
+                BINDING_OFFSET + i,
+                first_step_flag,
+                are_equal(current[SIG_HASH_OFFSET + i], signature_r),
+            );
+            result.agg_constraint(
+                BINDING_OFFSET + i,
+                final_point_addition_flag,
+                are_equal(next[i], signature_r),
+            );
+        }
+
+        // Rounds of the sequence hash, and absorption of the blocks of the tuple
+        rescue::enforce_round(
+            &mut result[SEQUENCE_CONSTRAINT_OFFSET..],
+            &current[SEQUENCE_OFFSET..],
+            &next[SEQUENCE_OFFSET..],
+            ark,
+            sequence_hash_flag,
+        );
+        for i in 0..HASH_STATE_WIDTH {
+            let input = if i < HASH_RATE_WIDTH {
+                tuple_inputs[i]
+            } else {
+                E::ZERO
+            };
+            result.agg_constraint(
+                SEQUENCE_CONSTRAINT_OFFSET + i,
+                not(sequence_hash_flag),
+                are_equal(
+                    next[SEQUENCE_OFFSET + i],
+                    current[SEQUENCE_OFFSET + i] + input,
+                ),
+            );
+        }
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        let mut assertions = air::build_initial_point_assertions();
+        assertions.append(&mut air::build_initial_rate_assertions());
+
+        // The sponge starts from the zero state, and its rate
+        // holds the digest of all tuples at the last step
+        let last_step = self.trace_length() - 1;
+        for i in 0..HASH_STATE_WIDTH {
+            assertions.push(Assertion::single(SEQUENCE_OFFSET + i, 0, BaseElement::ZERO));
+        }
+        for (i, &digest_element) in self.digest.iter().enumerate() {
+            assertions.push(Assertion::single(
+                SEQUENCE_OFFSET + i,
+                last_step,
+                digest_element,
+            ));
+        }
+
+        assertions
+    }
+
+    fn get_periodic_column_values(&self) -> Vec<Vec<Self::BaseField>> {
+        let mut columns = air::periodic_columns();
+
+        // Flag for the first step of a signature cycle
+        let mut first_step_flag = vec![BaseElement::ZERO; SIG_CYCLE_LENGTH];
+        first_step_flag[0] = BaseElement::ONE;
+        columns.push(first_step_flag);
+
+        // Flags for feeding the i-th chunk of the message to the hash of the
+        // Schnorr AIR, and the i-th block of the tuple to the sequence hash
+        for i in 0..SEQUENCE_NUM_BLOCKS {
+            let mut block_flag = vec![BaseElement::ZERO; SIG_CYCLE_LENGTH];
+            block_flag[i * HASH_CYCLE_LENGTH + NUM_HASH_ROUNDS] = BaseElement::ONE;
+            columns.push(block_flag);
+        }
+
+        // Flag for performing rounds of the sequence hash
+        let mut sequence_hash_flag = vec![BaseElement::ZERO; HASH_CYCLE_LENGTH];
+        for _ in 0..SEQUENCE_NUM_BLOCKS {
+            sequence_hash_flag.extend_from_slice(&HASH_CYCLE_MASK);
+        }
+        sequence_hash_flag.resize(SIG_CYCLE_LENGTH, BaseElement::ZERO);
+        columns.push(sequence_hash_flag);
+
+        // Append the rescue round constants
+        columns.append(&mut rescue::get_round_constants());
+
+        columns
+    }
+}
+
+fn transition_constraint_degrees(num_signatures: usize) -> Vec<TransitionConstraintDegree> {
+    // The voting key is read from the trace, which has the same degree
+    // as reading it from periodic columns when there are several signatures
+    let mut degrees = air::transition_constraint_degrees(num_signatures, SIG_CYCLE_LENGTH);
+
+    // Tuple registers
+    for _ in 0..SEQUENCE_TUPLE_WIDTH {
+        degrees.push(TransitionConstraintDegree::with_cycles(
+            1,
+            vec![SIG_CYCLE_LENGTH],
+        ));
+    }
+
+    // Binding of the x coordinate of R
+    for _ in 0..POINT_COORDINATE_WIDTH {
+        degrees.push(TransitionConstraintDegree::with_cycles(
+            1,
+            vec![SIG_CYCLE_LENGTH, SIG_CYCLE_LENGTH],
+        ));
+    }
+
+    // Sequence hash
+    for _ in 0..HASH_STATE_WIDTH {
+        degrees.push(TransitionConstraintDegree::with_cycles(
+            3,
+            vec![SIG_CYCLE_LENGTH],
+        ));
+    }
+
+    degrees
+}
+
+// SEQUENCE PROVER
+// ================================================================================================
+
+// inputs are borrowed from the caller to avoid cloning them for large elections
+pub struct SchnorrSequenceProver<'a> {
+    options: ProofOptions,
+    voting_keys: &'a [[BaseElement; AFFINE_POINT_WIDTH]],
+    addresses: &'a [Address],
+    signatures: &'a [([BaseElement; POINT_COORDINATE_WIDTH], Scalar)],
+    domain: SigningDomain,
+}
+
+impl<'a> SchnorrSequenceProver<'a> {
+    pub fn new(
+        options: ProofOptions,
+        voting_keys: &'a [[BaseElement; AFFINE_POINT_WIDTH]],
+        addresses: &'a [Address],
+        signatures: &'a [([BaseElement; POINT_COORDINATE_WIDTH], Scalar)],
+    ) -> Self {
+        Self {
+            options,
+            voting_keys,
+            addresses,
+            signatures,
+            domain: SigningDomain::default(),
+        }
+    }
+
+    /// Verify signatures bound to the given signing domain
+    pub fn with_domain(mut self, domain: SigningDomain) -> Self {
+        self.domain = domain;
+        self
+    }
+
+    pub fn build_trace(&self) -> TraceTable<BaseElement> {
+        let num_signatures = self.voting_keys.len();
+        debug_assert!(
+            num_signatures >= 2,
+            "Number of signatures cannot be less than 2."
+        );
+        debug_assert!(
+            num_signatures.is_power_of_two(),
+            "Number of signatures must be a power of 2."
+        );
+
+        let sig_infos = (0..num_signatures)
+            .map(|i| {
+                build_sig_info(
+                    &self.voting_keys[i],
+                    self.addresses[i],
+                    &self.signatures[i],
+                    &self.domain,
+                )
+            })
+            .collect::<Vec<SigInfo>>();
+        let tuples = (0..num_signatures)
+            .map(|i| sequence_tuple(&self.voting_keys[i], self.addresses[i], &self.signatures[i]))
+            .collect::<Vec<_>>();
+
+        // the sponge is carried across cycles, hence compute its
+        // state at the start of each cycle beforehand
+        let mut sequence_states = Vec::with_capacity(num_signatures);
+        let mut sequence_state = [BaseElement::ZERO; HASH_STATE_WIDTH];
+        for tuple in tuples.iter() {
+            sequence_states.push(sequence_state);
+            for block in tuple.chunks(HASH_RATE_WIDTH) {
+                for (state, &element) in sequence_state.iter_mut().zip(block) {
+                    *state += element;
+                }
+                rescue::apply_permutation(&mut sequence_state);
+            }
+        }
+
+        // allocate memory to hold the trace table
+        let trace_length = SIG_CYCLE_LENGTH * num_signatures;
+        let mut trace = TraceTable::new(SEQUENCE_TRACE_WIDTH, trace_length);
+        trace.fragments(SIG_CYCLE_LENGTH).for_each(|mut sig_trace| {
+            let i = sig_trace.index();
+            let vkey_point = self.voting_keys[i];
+            let (message, s_bytes, h_bytes) = sig_infos[i];
+            let s_bits = s_bytes.as_bits::<Lsb0>();
+            let h_bits = h_bytes.as_bits::<Lsb0>();
+            sig_trace.fill(
+                |state| {
+                    init_sig_verification_state(self.signatures[i], &mut state[..TRACE_WIDTH]);
+                    state[TUPLE_OFFSET..SEQUENCE_OFFSET]
+                        .copy_from_slice(&tuples[i][..SEQUENCE_TUPLE_WIDTH]);
+                    state[SEQUENCE_OFFSET..].copy_from_slice(&sequence_states[i]);
+                },
+                |step, state| {
+                    update_sig_verification_state(
+                        step,
+                        message,
+                        vkey_point,
+                        s_bits,
+                        h_bits,
+                        &mut state[..TRACE_WIDTH],
+                    );
+                    update_sequence_state(step, &tuples[i], &mut state[SEQUENCE_OFFSET..]);
+                },
+            );
+        });
+        trace
+    }
+}
+
+impl<'a> Prover for SchnorrSequenceProver<'a> {
+    type BaseField = BaseElement;
+    type Air = SchnorrSequenceAir;
+    type Trace = TraceTable<BaseElement>;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> SequencePublicInputs {
+        let mut row = [BaseElement::ZERO; SEQUENCE_TRACE_WIDTH];
+        trace.read_row_into(trace.length() - 1, &mut row);
+        let mut digest = [BaseElement::ZERO; DIGEST_SIZE];
+        digest.copy_from_slice(&row[SEQUENCE_OFFSET..SEQUENCE_OFFSET + DIGEST_SIZE]);
+        SequencePublicInputs {
+            num_signatures: self.voting_keys.len(),
+            digest,
+            domain: self.domain,
+        }
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Update the sequence hash state at the given step of a signature cycle
+fn update_sequence_state(
+    step: usize,
+    tuple: &[BaseElement; SEQUENCE_TUPLE_LENGTH],
+    state: &mut [BaseElement],
+) {
+    let block = step / HASH_CYCLE_LENGTH;
+    let rescue_step = step % HASH_CYCLE_LENGTH;
+    if block < SEQUENCE_NUM_BLOCKS && rescue_step == NUM_HASH_ROUNDS {
+        // absorb the next block of the tuple
+        for i in 0..HASH_RATE_WIDTH {
+            state[i] += tuple[block * HASH_RATE_WIDTH + i];
+        }
+    } else if (1..=SEQUENCE_NUM_BLOCKS).contains(&block) && rescue_step < NUM_HASH_ROUNDS {
+        rescue::apply_round(state, step);
+    }
+}
+
+/// Tuple absorbed into the sequence hash for a signature: the voting key, the
+/// packed Ethereum address and the x coordinate of R, padded with zeros
+pub(crate) fn sequence_tuple(
+    voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    address: Address,
+    signature: &([BaseElement; POINT_COORDINATE_WIDTH], Scalar),
+) -> [BaseElement; SEQUENCE_TUPLE_LENGTH] {
+    // the signing domain is not part of the tuple, hence the unbound message
+    let message = prepare_message(voting_key, address, &SigningDomain::default());
+    let mut tuple = [BaseElement::ZERO; SEQUENCE_TUPLE_LENGTH];
+    tuple[..MSG_INPUT_WIDTH].copy_from_slice(&message[..MSG_INPUT_WIDTH]);
+    tuple[MSG_INPUT_WIDTH..SEQUENCE_TUPLE_WIDTH].copy_from_slice(&signature.0);
+    tuple
+}
+
+/// Compute the sequence hash of (voting key, address, signature) tuples,
+/// i.e. the Rescue hash of their concatenation
+pub fn sequence_digest(
+    voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+    addresses: &[Address],
+    signatures: &[([BaseElement; POINT_COORDINATE_WIDTH], Scalar)],
+) -> [BaseElement; DIGEST_SIZE] {
+    let mut hasher = Rescue63::new();
+    for i in 0..voting_keys.len() {
+        hasher.update(&sequence_tuple(
+            &voting_keys[i],
+            addresses[i],
+            &signatures[i],
+        ));
+    }
+    hasher.finalize().to_elements()
+}
@@ -6,7 +6,11 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use winterfell::{FieldExtension, HashFunction, ProofOptions};
+use super::{PublicInputs, SequencePublicInputs, SigningDomain};
+use crate::compat::proof_options;
+use crate::testing::strategies::{address, digest, domain, point, signature};
+use proptest::{collection::vec, prelude::*};
+use winterfell::{FieldExtension, HashFunction, ProofOptions, Serializable};
 
 #[test]
 fn schnorr_test_proof_verification() {
@@ -45,8 +49,291 @@ fn schnorr_test_proof_verification_wrong_signature() {
     assert!(verified.is_err());
 }
 
+#[test]
+fn schnorr_test_pub_inputs_serialization() {
+    let schnorr = super::SchnorrExample::new(build_options(1), 4);
+    let pub_inputs = PublicInputs {
+        voting_keys: schnorr.voting_keys,
+        addresses: schnorr.addresses,
+        signatures: schnorr.signatures,
+        domain: SigningDomain::default(),
+    };
+
+    let bytes = pub_inputs.to_bytes();
+    assert_eq!(bytes.len(), PublicInputs::serialized_len(4));
+    assert_eq!(&bytes[..4], &4u32.to_le_bytes());
+    assert_eq!(PublicInputs::from_bytes(&bytes).unwrap(), pub_inputs);
+}
+
+#[test]
+fn schnorr_test_pub_inputs_deserialization_truncated() {
+    let schnorr = super::SchnorrExample::new(build_options(1), 2);
+    let pub_inputs = PublicInputs {
+        voting_keys: schnorr.voting_keys,
+        addresses: schnorr.addresses,
+        signatures: schnorr.signatures,
+        domain: SigningDomain::default(),
+    };
+
+    let bytes = pub_inputs.to_bytes();
+    assert!(PublicInputs::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn schnorr_test_sequence_proof_verification() {
+    let schnorr = super::SchnorrExample::new(build_options(1), 2);
+    let proof = schnorr.prove_sequence();
+    assert!(schnorr.verify_sequence(proof).is_ok());
+}
+
+#[test]
+fn schnorr_test_sequence_proof_verification_wrong_digest() {
+    use super::{sequence_digest, SchnorrSequenceAir};
+
+    let schnorr = super::SchnorrExample::new(build_options(1), 2);
+    let proof = schnorr.prove_sequence();
+
+    // the digest of the tuples in another order does not match
+    let voting_keys = [schnorr.voting_keys[1], schnorr.voting_keys[0]];
+    let addresses = [schnorr.addresses[1], schnorr.addresses[0]];
+    let signatures = [schnorr.signatures[1], schnorr.signatures[0]];
+    let pub_inputs = SequencePublicInputs {
+        num_signatures: 2,
+        digest: sequence_digest(&voting_keys, &addresses, &signatures),
+        domain: SigningDomain::default(),
+    };
+    assert!(crate::compat::verify::<SchnorrSequenceAir>(proof, pub_inputs).is_err());
+}
+
+#[test]
+fn schnorr_test_sequence_pub_inputs_serialization() {
+    use super::sequence_digest;
+
+    let schnorr = super::SchnorrExample::new(build_options(1), 2);
+    let pub_inputs = SequencePublicInputs {
+        num_signatures: 2,
+        digest: sequence_digest(
+            &schnorr.voting_keys,
+            &schnorr.addresses,
+            &schnorr.signatures,
+        ),
+        domain: SigningDomain::default(),
+    };
+    let bytes = pub_inputs.to_bytes();
+    assert_eq!(
+        SequencePublicInputs::from_bytes(&bytes).unwrap(),
+        pub_inputs
+    );
+    assert!(SequencePublicInputs::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn schnorr_test_signature_verification_errors() {
+    use super::{constants::AFFINE_POINT_WIDTH, verify_signature, SchnorrVerificationError};
+    use crate::compat::curve::{AffinePoint, ProjectivePoint, Scalar};
+    use crate::utils::ecc::projective_to_elements;
+    use winterfell::math::{fields::f63::BaseElement, FieldElement};
+
+    let schnorr = super::SchnorrExample::new(build_options(1), 1);
+    let voting_key = schnorr.voting_keys[0];
+    let address = schnorr.addresses[0];
+    let signature = schnorr.signatures[0];
+    let domain = SigningDomain::default();
+    assert_eq!(
+        verify_signature(voting_key, address, signature, &domain),
+        Ok(())
+    );
+
+    let mut wrong_signature = signature;
+    wrong_signature.1 += Scalar::one();
+    assert_eq!(
+        verify_signature(voting_key, address, wrong_signature, &domain),
+        Err(SchnorrVerificationError::InvalidSignature)
+    );
+
+    // An off-curve voting key is reported instead of panicking
+    let mut off_curve_key = voting_key;
+    off_curve_key[AFFINE_POINT_WIDTH - 1] += BaseElement::ONE;
+    assert_eq!(
+        verify_signature(off_curve_key, address, signature, &domain),
+        Err(SchnorrVerificationError::OffCurveKey)
+    );
+
+    // So is a voting key with a component of small order
+    let small_order_key = projective_to_elements(
+        ProjectivePoint::from(AffinePoint::from_raw_coordinates(voting_key))
+            + crate::testing::small_order_point(),
+    );
+    assert_eq!(
+        verify_signature(small_order_key, address, signature, &domain),
+        Err(SchnorrVerificationError::SmallOrderKey)
+    );
+}
+
+#[test]
+fn schnorr_test_signing_domain() {
+    use super::{
+        constants::{MSG_DOMAIN_WIDTH, MSG_INPUT_WIDTH},
+        prepare_message, sign_message_in_domain, verify_signature, SchnorrVerificationError,
+    };
+    use rand_core::OsRng;
+    use winterfell::math::{fields::f63::BaseElement, FieldElement};
+
+    let schnorr = super::SchnorrExample::new(build_options(1), 1);
+    let (secret_key, voting_key) = super::random_key_pair_with_rng(&mut OsRng);
+    let address = schnorr.addresses[0];
+    let domain = SigningDomain::new(1, [7u8; 16]);
+
+    // the unbound message is the voting key and the address padded with zeros
+    let message = prepare_message(&voting_key, address, &SigningDomain::default());
+    assert!(message[MSG_INPUT_WIDTH..]
+        .iter()
+        .all(|&element| element == BaseElement::ZERO));
+    let bound_message = prepare_message(&voting_key, address, &domain);
+    assert_eq!(message[..MSG_INPUT_WIDTH], bound_message[..MSG_INPUT_WIDTH]);
+    assert_eq!(
+        bound_message[MSG_INPUT_WIDTH..MSG_INPUT_WIDTH + MSG_DOMAIN_WIDTH],
+        domain.to_elements()
+    );
+
+    // a signature bound to a domain is not valid in any other domain
+    let signature = sign_message_in_domain(secret_key, &voting_key, address, &domain, &mut OsRng);
+    assert_eq!(
+        verify_signature(voting_key, address, signature, &domain),
+        Ok(())
+    );
+    for other_domain in [
+        SigningDomain::default(),
+        SigningDomain::new(5, domain.election_id),
+        SigningDomain::new(domain.chain_id, [8u8; 16]),
+    ]
+    .iter()
+    {
+        assert_eq!(
+            verify_signature(voting_key, address, signature, other_domain),
+            Err(SchnorrVerificationError::InvalidSignature)
+        );
+    }
+
+    // domains are serialized in exactly BYTES_PER_SIGNING_DOMAIN bytes
+    let bytes = domain.to_bytes();
+    assert_eq!(bytes.len(), super::constants::BYTES_PER_SIGNING_DOMAIN);
+    assert_eq!(SigningDomain::from_bytes(&bytes).unwrap(), domain);
+    assert!(SigningDomain::from_bytes(&bytes[1..]).is_err());
+    assert!(SigningDomain::from_bytes(&[bytes.clone(), vec![0]].concat()).is_err());
+}
+
+#[test]
+fn schnorr_test_proof_verification_domain() {
+    use super::{
+        random_key_pairs, sequence_digest, sign_message_in_domain, SchnorrAir, SchnorrProver,
+        SchnorrSequenceAir, SchnorrSequenceProver,
+    };
+    use rand_core::OsRng;
+    use winterfell::Prover;
+
+    let schnorr = super::SchnorrExample::new(build_options(1), 2);
+    let (secret_keys, voting_keys) = random_key_pairs(2);
+    let addresses = schnorr.addresses;
+    let domain = SigningDomain::new(1, [7u8; 16]);
+    let signatures = (0..2)
+        .map(|i| {
+            sign_message_in_domain(
+                secret_keys[i],
+                &voting_keys[i],
+                addresses[i],
+                &domain,
+                &mut OsRng,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let prover = SchnorrProver::new(build_options(1), &voting_keys, &addresses, &signatures)
+        .with_domain(domain);
+    let proof = prover.prove(prover.build_trace()).unwrap();
+    let mut pub_inputs = PublicInputs {
+        voting_keys: voting_keys.clone(),
+        addresses: addresses.clone(),
+        signatures: signatures.clone(),
+        domain,
+    };
+    assert!(crate::compat::verify::<SchnorrAir>(proof.clone(), pub_inputs.clone()).is_ok());
+    pub_inputs.domain = SigningDomain::default();
+    assert!(crate::compat::verify::<SchnorrAir>(proof, pub_inputs).is_err());
+
+    // the sequence variant reads the domain from its public inputs
+    let prover =
+        SchnorrSequenceProver::new(build_options(1), &voting_keys, &addresses, &signatures)
+            .with_domain(domain);
+    let proof = prover.prove(prover.build_trace()).unwrap();
+    let mut pub_inputs = SequencePublicInputs {
+        num_signatures: 2,
+        digest: sequence_digest(&voting_keys, &addresses, &signatures),
+        domain,
+    };
+    assert!(crate::compat::verify::<SchnorrSequenceAir>(proof.clone(), pub_inputs.clone()).is_ok());
+    pub_inputs.domain = SigningDomain::default();
+    assert!(crate::compat::verify::<SchnorrSequenceAir>(proof, pub_inputs).is_err());
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn schnorr_test_pub_inputs_roundtrip(
+        signers in vec((point(), address(), signature()), 0..6),
+        domain in domain(),
+    ) {
+        let pub_inputs = PublicInputs {
+            voting_keys: signers.iter().map(|signer| signer.0).collect(),
+            addresses: signers.iter().map(|signer| signer.1).collect(),
+            signatures: signers.iter().map(|signer| signer.2).collect(),
+            domain,
+        };
+        let bytes = pub_inputs.to_bytes();
+        let restored = PublicInputs::from_bytes(&bytes).unwrap();
+
+        // the domain is not read back, and must be set by the verifier
+        let expected = PublicInputs {
+            domain: SigningDomain::default(),
+            ..pub_inputs
+        };
+        prop_assert_eq!(&restored, &expected);
+        prop_assert_eq!(
+            &restored.to_bytes()[..],
+            &bytes[..PublicInputs::serialized_len(signers.len())]
+        );
+    }
+
+    #[test]
+    fn schnorr_test_sequence_pub_inputs_roundtrip(
+        num_signatures in any::<u32>(),
+        digest in digest(),
+        domain in domain(),
+    ) {
+        let pub_inputs = SequencePublicInputs {
+            num_signatures: num_signatures as usize,
+            digest,
+            domain,
+        };
+        let restored = SequencePublicInputs::from_bytes(&pub_inputs.to_bytes()).unwrap();
+        prop_assert_eq!(
+            restored,
+            SequencePublicInputs {
+                domain: SigningDomain::default(),
+                ..pub_inputs
+            }
+        );
+    }
+
+    #[test]
+    fn schnorr_test_signing_domain_roundtrip(domain in domain()) {
+        prop_assert_eq!(SigningDomain::from_bytes(&domain.to_bytes()).unwrap(), domain);
+    }
+}
+
 fn build_options(extension: u8) -> ProofOptions {
-    ProofOptions::new(
+    proof_options(
         42,
         8,
         0,
@@ -6,12 +6,13 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use super::{constants::*, prepare_message};
+use super::{constants::*, prepare_message, SigningDomain};
 use super::{ecc, field, rescue};
+use crate::compat::curve::Scalar;
+use crate::utils::address::Address;
 use bitvec::{order::Lsb0, slice::BitSlice};
 use core::cmp::Ordering;
-use web3::types::Address;
-use winterfell::math::{curves::curve_f63::Scalar, fields::f63::BaseElement, FieldElement};
+use winterfell::math::{fields::f63::BaseElement, FieldElement};
 
 // TRACE INITIALIZATION
 // ================================================================================================
@@ -125,17 +126,17 @@ pub(crate) fn update_sig_verification_state(
 // HELPER FUNCTIONS
 // ================================================================================================
 
+/// Message, and bytes of s and h of a signature, used to fill the trace
+pub(crate) type SigInfo = ([BaseElement; MSG_LENGTH], [u8; 32], [u8; 32]);
+
 pub(crate) fn build_sig_info(
     voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
     address: Address,
     signature: &([BaseElement; POINT_COORDINATE_WIDTH], Scalar),
-) -> ([BaseElement; MSG_LENGTH], [u8; 32], [u8; 32]) {
+    domain: &SigningDomain,
+) -> SigInfo {
     let s_bytes = signature.1.to_bytes();
-    let message = prepare_message(&voting_key, address);
-    let h = super::hash_message(&signature.0, &message);
-    let mut h_bytes = [0u8; 32];
-    for (i, h_word) in h.iter().enumerate().take(4) {
-        h_bytes[8 * i..8 * i + 8].copy_from_slice(&h_word.to_bytes());
-    }
+    let message = prepare_message(&voting_key, address, domain);
+    let h_bytes = super::build_transcript(&signature.0, &message).challenge_bytes();
     (message, s_bytes, h_bytes)
 }
@@ -0,0 +1,421 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// REMOTE PROVING SERVICE
+// ================================================================================================
+// gRPC service generated from proto/prover.proto. Workers run the CDS, Schnorr, Merkle and tally
+// provers on jobs dispatched by the aggregator coordinator, and return the serialized public
+// inputs and STARK proof of each job.
+
+use crate::{
+    aggregator::build_options,
+    cds::{constants::*, CDSProver, ShardInfo},
+    compat::curve::Scalar,
+    manifest::read_manifest_digest,
+    merkle::{constants::TREE_DEPTH, MerkleProver},
+    proto::{elements_to_bytes, invalid_value, read_elements, read_point, read_scalar},
+    schnorr::SchnorrProver,
+    tally::TallyProver,
+    utils::{
+        address::Address,
+        ecc::{compress_point, POINT_COORDINATE_WIDTH},
+        rescue::DIGEST_SIZE,
+    },
+};
+use winterfell::{
+    math::{fields::f63::BaseElement, FieldElement},
+    DeserializationError, ProofOptions, Prover, Serializable,
+};
+
+use tonic::{Request, Response, Status};
+
+#[cfg(test)]
+mod tests;
+
+/// Service and messages generated from the protobuf schema
+#[allow(missing_docs)]
+pub mod rpc {
+    tonic::include_proto!("openvote.prover");
+}
+
+pub use rpc::{
+    prover_worker_client::ProverWorkerClient, prover_worker_server::ProverWorkerServer, CdsJob,
+    MerkleJob, ProofResult, SchnorrJob, TallyJob,
+};
+
+// PROVING WORKER
+// ================================================================================================
+
+/// Worker serving proving jobs over gRPC
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ProvingWorker;
+
+impl ProvingWorker {
+    /// Wrap the worker into a gRPC server
+    pub fn into_server(self) -> ProverWorkerServer<Self> {
+        ProverWorkerServer::new(self)
+    }
+}
+
+#[tonic::async_trait]
+impl rpc::prover_worker_server::ProverWorker for ProvingWorker {
+    async fn prove_cds(&self, request: Request<CdsJob>) -> Result<Response<ProofResult>, Status> {
+        run_job(request, prove_cds).await
+    }
+
+    async fn prove_schnorr(
+        &self,
+        request: Request<SchnorrJob>,
+    ) -> Result<Response<ProofResult>, Status> {
+        run_job(request, prove_schnorr).await
+    }
+
+    async fn prove_merkle(
+        &self,
+        request: Request<MerkleJob>,
+    ) -> Result<Response<ProofResult>, Status> {
+        run_job(request, prove_merkle).await
+    }
+
+    async fn prove_tally(
+        &self,
+        request: Request<TallyJob>,
+    ) -> Result<Response<ProofResult>, Status> {
+        run_job(request, prove_tally).await
+    }
+}
+
+/// Run a proving job on the blocking thread pool, as proof generation
+/// would otherwise stall the executor serving other requests
+async fn run_job<J, F>(request: Request<J>, prove: F) -> Result<Response<ProofResult>, Status>
+where
+    J: Send + 'static,
+    F: FnOnce(J) -> Result<ProofResult, Status> + Send + 'static,
+{
+    let job = request.into_inner();
+    tokio::task::spawn_blocking(move || prove(job))
+        .await
+        .map_err(|err| Status::internal(err.to_string()))?
+        .map(Response::new)
+}
+
+// JOB CONSTRUCTORS
+// ================================================================================================
+
+impl CdsJob {
+    /// Create a job proving the CDS proofs of all the given voters
+    pub fn new(
+        extension: u8,
+        voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+        encrypted_votes: &[[BaseElement; AFFINE_POINT_WIDTH]],
+        proof_points: &[[BaseElement; AFFINE_POINT_WIDTH * PROOF_NUM_POINTS]],
+        proof_scalars: &[[Scalar; PROOF_NUM_SCALARS]],
+    ) -> Self {
+        Self {
+            extension: extension as u32,
+            voting_keys: points_to_bytes(voting_keys.iter().flatten()),
+            encrypted_votes: points_to_bytes(encrypted_votes.iter().flatten()),
+            proof_points: points_to_bytes(proof_points.iter().flatten()),
+            proof_scalars: proof_scalars
+                .iter()
+                .flatten()
+                .map(Serializable::to_bytes)
+                .collect(),
+            shard: None,
+            manifest_digest: vec![],
+        }
+    }
+
+    /// Bind the proof to the digest of an election manifest
+    pub fn with_manifest(mut self, manifest_digest: [BaseElement; DIGEST_SIZE]) -> Self {
+        self.manifest_digest = elements_to_bytes(&manifest_digest);
+        self
+    }
+
+    /// Restrict the job to a shard of voters
+    pub fn with_shard(mut self, shard: ShardInfo) -> Self {
+        self.shard = Some(rpc::Shard {
+            first_voter_index: shard.first_voter_index as u32,
+            initial_blinding_key: compress_point(&shard.initial_blinding_key).to_vec(),
+        });
+        self
+    }
+}
+
+impl SchnorrJob {
+    /// Create a job proving the given Schnorr signatures
+    pub fn new(
+        extension: u8,
+        voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+        addresses: &[Address],
+        signatures: &[([BaseElement; POINT_COORDINATE_WIDTH], Scalar)],
+    ) -> Self {
+        Self {
+            extension: extension as u32,
+            voting_keys: points_to_bytes(voting_keys.iter().flatten()),
+            addresses: addresses
+                .iter()
+                .map(|address| address.as_bytes().to_vec())
+                .collect(),
+            signatures: signatures
+                .iter()
+                .map(|signature| {
+                    let mut bytes = elements_to_bytes(&signature.0);
+                    bytes.extend_from_slice(&Serializable::to_bytes(&signature.1));
+                    bytes
+                })
+                .collect(),
+        }
+    }
+}
+
+impl MerkleJob {
+    /// Create a job proving the membership of the given voting keys
+    pub fn new(
+        extension: u8,
+        tree_root: &[BaseElement; DIGEST_SIZE],
+        voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+        branches: &[[BaseElement; TREE_DEPTH * DIGEST_SIZE]],
+        hash_indices: &[usize],
+    ) -> Self {
+        Self {
+            extension: extension as u32,
+            tree_root: elements_to_bytes(tree_root),
+            voting_keys: points_to_bytes(voting_keys.iter().flatten()),
+            branches: branches
+                .iter()
+                .map(|branch| elements_to_bytes(branch))
+                .collect(),
+            hash_indices: hash_indices.iter().map(|&index| index as u64).collect(),
+        }
+    }
+}
+
+impl TallyJob {
+    /// Create a job proving the tally result of the given encrypted votes
+    pub fn new(
+        extension: u8,
+        encrypted_votes: &[[BaseElement; AFFINE_POINT_WIDTH]],
+        tally_result: u32,
+    ) -> Self {
+        Self {
+            extension: extension as u32,
+            encrypted_votes: points_to_bytes(encrypted_votes.iter().flatten()),
+            tally_result,
+        }
+    }
+}
+
+// PROVING JOBS
+// ================================================================================================
+
+/// Generate the CDS proof of a job
+pub fn prove_cds(job: CdsJob) -> Result<ProofResult, Status> {
+    let num_voters = job.voting_keys.len();
+    if job.encrypted_votes.len() != num_voters
+        || job.proof_points.len() != num_voters * PROOF_NUM_POINTS
+        || job.proof_scalars.len() != num_voters * PROOF_NUM_SCALARS
+    {
+        return Err(Status::invalid_argument(
+            "Inconsistent number of CDS inputs.",
+        ));
+    }
+    let voting_keys = read_points(&job.voting_keys)?;
+    let encrypted_votes = read_points(&job.encrypted_votes)?;
+    let proof_points = read_points(&job.proof_points)?
+        .chunks(PROOF_NUM_POINTS)
+        .map(|points| {
+            let mut proof_points = [BaseElement::ZERO; AFFINE_POINT_WIDTH * PROOF_NUM_POINTS];
+            for (i, point) in points.iter().enumerate() {
+                proof_points[i * AFFINE_POINT_WIDTH..(i + 1) * AFFINE_POINT_WIDTH]
+                    .copy_from_slice(point);
+            }
+            proof_points
+        })
+        .collect::<Vec<_>>();
+    let proof_scalars = job
+        .proof_scalars
+        .chunks(PROOF_NUM_SCALARS)
+        .map(|scalars| {
+            let mut proof_scalars = [Scalar::zero(); PROOF_NUM_SCALARS];
+            for (proof_scalar, bytes) in proof_scalars.iter_mut().zip(scalars.iter()) {
+                *proof_scalar = read_scalar(bytes)?;
+            }
+            Ok(proof_scalars)
+        })
+        .collect::<Result<Vec<_>, DeserializationError>>()
+        .map_err(invalid_argument)?;
+
+    let options = job_options(job.extension);
+    let prover = match job.shard {
+        Some(shard) => CDSProver::new_shard(
+            options,
+            ShardInfo {
+                first_voter_index: shard.first_voter_index as usize,
+                initial_blinding_key: read_point(&shard.initial_blinding_key)
+                    .map_err(invalid_argument)?,
+            },
+            &voting_keys,
+            &encrypted_votes,
+            &proof_points,
+            &proof_scalars,
+        ),
+        None => CDSProver::new(
+            options,
+            &voting_keys,
+            &encrypted_votes,
+            &proof_points,
+            &proof_scalars,
+        ),
+    };
+    let prover = if job.manifest_digest.is_empty() {
+        prover
+    } else {
+        prover.with_manifest(read_manifest_digest(&job.manifest_digest).map_err(invalid_argument)?)
+    };
+    let trace = prover.build_trace();
+    let pub_inputs = prover.get_pub_inputs(&trace);
+    finish_job(pub_inputs, prover.prove(trace))
+}
+
+/// Generate the Schnorr signature proof of a job
+pub fn prove_schnorr(job: SchnorrJob) -> Result<ProofResult, Status> {
+    let num_signatures = job.voting_keys.len();
+    if job.addresses.len() != num_signatures || job.signatures.len() != num_signatures {
+        return Err(Status::invalid_argument(
+            "Inconsistent number of Schnorr inputs.",
+        ));
+    }
+    let voting_keys = read_points(&job.voting_keys)?;
+    let addresses = job
+        .addresses
+        .iter()
+        .map(|bytes| {
+            if bytes.len() != Address::len_bytes() {
+                return Err(Status::invalid_argument("Invalid Ethereum address."));
+            }
+            Ok(Address::from_slice(bytes))
+        })
+        .collect::<Result<Vec<_>, Status>>()?;
+    let signatures = job
+        .signatures
+        .iter()
+        .map(|bytes| {
+            let nbytes_r = POINT_COORDINATE_WIDTH * 8;
+            if bytes.len() < nbytes_r {
+                return Err(invalid_value("Invalid signature length."));
+            }
+            let mut signature_r = [BaseElement::ZERO; POINT_COORDINATE_WIDTH];
+            signature_r
+                .copy_from_slice(&read_elements(&bytes[..nbytes_r], POINT_COORDINATE_WIDTH)?);
+            Ok((signature_r, read_scalar(&bytes[nbytes_r..])?))
+        })
+        .collect::<Result<Vec<_>, DeserializationError>>()
+        .map_err(invalid_argument)?;
+
+    let prover = SchnorrProver::new(
+        job_options(job.extension),
+        &voting_keys,
+        &addresses,
+        &signatures,
+    );
+    let trace = prover.build_trace();
+    let pub_inputs = prover.get_pub_inputs(&trace);
+    finish_job(pub_inputs, prover.prove(trace))
+}
+
+/// Generate the Merkle proof of membership of a job
+pub fn prove_merkle(job: MerkleJob) -> Result<ProofResult, Status> {
+    let num_voters = job.voting_keys.len();
+    if job.branches.len() != num_voters || job.hash_indices.len() != num_voters {
+        return Err(Status::invalid_argument(
+            "Inconsistent number of Merkle inputs.",
+        ));
+    }
+    let mut tree_root = [BaseElement::ZERO; DIGEST_SIZE];
+    tree_root
+        .copy_from_slice(&read_elements(&job.tree_root, DIGEST_SIZE).map_err(invalid_argument)?);
+    let voting_keys = read_points(&job.voting_keys)?;
+    let branches = job
+        .branches
+        .iter()
+        .map(|bytes| {
+            let mut branch = [BaseElement::ZERO; TREE_DEPTH * DIGEST_SIZE];
+            branch.copy_from_slice(&read_elements(bytes, TREE_DEPTH * DIGEST_SIZE)?);
+            Ok(branch)
+        })
+        .collect::<Result<Vec<_>, DeserializationError>>()
+        .map_err(invalid_argument)?;
+    let hash_indices = job
+        .hash_indices
+        .iter()
+        .map(|&index| index as usize)
+        .collect::<Vec<_>>();
+
+    let prover = MerkleProver::new(job_options(job.extension), tree_root, &voting_keys);
+    let trace = prover.build_trace(&branches, &hash_indices);
+    let pub_inputs = prover.get_pub_inputs(&trace);
+    finish_job(pub_inputs, prover.prove(trace))
+}
+
+/// Generate the tally proof of a job
+pub fn prove_tally(job: TallyJob) -> Result<ProofResult, Status> {
+    let encrypted_votes = read_points(&job.encrypted_votes)?;
+    if encrypted_votes.len() < 2 || !encrypted_votes.len().is_power_of_two() {
+        return Err(Status::invalid_argument(
+            "Number of encrypted votes must be a power of two.",
+        ));
+    }
+
+    let prover = TallyProver::new(
+        job_options(job.extension),
+        &encrypted_votes,
+        job.tally_result,
+    );
+    let trace = prover.build_trace();
+    let pub_inputs = prover.get_pub_inputs(&trace);
+    finish_job(pub_inputs, prover.prove(trace))
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn job_options(extension: u32) -> ProofOptions {
+    build_options(extension as u8)
+}
+
+fn invalid_argument(err: DeserializationError) -> Status {
+    Status::invalid_argument(err.to_string())
+}
+
+fn read_points(points: &[Vec<u8>]) -> Result<Vec<[BaseElement; AFFINE_POINT_WIDTH]>, Status> {
+    points
+        .iter()
+        .map(|bytes| read_point(bytes))
+        .collect::<Result<Vec<_>, DeserializationError>>()
+        .map_err(invalid_argument)
+}
+
+fn points_to_bytes<'a, I: Iterator<Item = &'a BaseElement>>(elements: I) -> Vec<Vec<u8>> {
+    let elements = elements.copied().collect::<Vec<BaseElement>>();
+    elements
+        .chunks(AFFINE_POINT_WIDTH)
+        .map(|point| compress_point(point).to_vec())
+        .collect()
+}
+
+fn finish_job<P: Serializable>(
+    pub_inputs: P,
+    proof: Result<winterfell::StarkProof, winterfell::ProverError>,
+) -> Result<ProofResult, Status> {
+    let proof = proof.map_err(|err| Status::internal(err.to_string()))?;
+    Ok(ProofResult {
+        pub_inputs: pub_inputs.to_bytes(),
+        proof: proof.to_bytes(),
+    })
+}
@@ -0,0 +1,62 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::{prove_cds, prove_schnorr, prove_tally, CdsJob, SchnorrJob, TallyJob};
+use crate::compat::verify;
+use crate::{
+    aggregator::build_options,
+    cds::{CDSAir, PublicInputs as CDSPublicInputs},
+    schnorr::{PublicInputs as SchnorrPublicInputs, SchnorrAir, SchnorrExample},
+    tally::TallyExample,
+};
+use winterfell::StarkProof;
+
+#[test]
+fn service_test_cds_job() {
+    let (example, _) = crate::cds::get_example(2);
+    let job = CdsJob::new(
+        1,
+        &example.voting_keys,
+        &example.encrypted_votes,
+        &example.proof_points,
+        &example.proof_scalars,
+    );
+    let result = prove_cds(job).unwrap();
+
+    let pub_inputs = CDSPublicInputs::from_bytes(&result.pub_inputs).unwrap();
+    let proof = StarkProof::from_bytes(&result.proof).unwrap();
+    assert!(verify::<CDSAir>(proof, pub_inputs).is_ok());
+}
+
+#[test]
+fn service_test_schnorr_job() {
+    let example = SchnorrExample::new(build_options(1), 2);
+    let job = SchnorrJob::new(
+        1,
+        &example.voting_keys,
+        &example.addresses,
+        &example.signatures,
+    );
+    let result = prove_schnorr(job).unwrap();
+
+    let pub_inputs = SchnorrPublicInputs::from_bytes(&result.pub_inputs).unwrap();
+    let proof = StarkProof::from_bytes(&result.proof).unwrap();
+    assert!(verify::<SchnorrAir>(proof, pub_inputs).is_ok());
+}
+
+#[test]
+fn service_test_invalid_jobs() {
+    let example = TallyExample::new(build_options(1), 2);
+    let mut job = TallyJob::new(1, &example.encrypted_votes, example.tally_result);
+    assert!(prove_tally(job.clone()).is_ok());
+
+    job.encrypted_votes.pop();
+    assert!(prove_tally(job.clone()).is_err());
+    job.encrypted_votes.push(vec![0u8; 3]);
+    assert!(prove_tally(job).is_err());
+}
@@ -0,0 +1,291 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// ELECTION SIMULATION
+// ================================================================================================
+// Drives the aggregator through all phases of an election with simulated voters, some of whom
+// misbehave or drop out, and reports acceptance statistics and proving times for capacity
+// planning.
+
+use crate::{
+    aggregator::{
+        cast::{CollectorError, EncryptedVote, VoteCollector},
+        register::{Registration, VoterRegistar},
+        tally::VoteTallier,
+    },
+    compat::curve::Scalar,
+    merkle::build_merkle_tree_with_keys,
+    schnorr::{random_key_pairs, sign_messages},
+    utils::address::Address,
+};
+use core::fmt;
+use rand_core::{OsRng, RngCore};
+use std::time::{Duration, Instant};
+
+#[cfg(test)]
+mod tests;
+
+// CONFIGURATION
+// ================================================================================================
+
+/// Number of simulated voters and rates at which they misbehave.
+/// Rates are probabilities between 0 and 1, drawn independently for each voter.
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    /// Number of eligible voters
+    pub num_voters: usize,
+    /// Rate of registrations with an invalid Schnorr signature
+    pub bad_signature_rate: f64,
+    /// Rate of registrations reusing the Ethereum address of another voter
+    pub duplicate_address_rate: f64,
+    /// Rate of encrypted votes with an invalid CDS proof
+    pub invalid_cds_rate: f64,
+    /// Rate of voters dropping out of each phase
+    pub dropout_rate: f64,
+    /// Set to true to generate the proofs of each phase
+    pub prove: bool,
+}
+
+impl SimulationConfig {
+    /// Configuration of an election of num_voters well-behaved voters
+    pub fn new(num_voters: usize) -> Self {
+        Self {
+            num_voters,
+            bad_signature_rate: 0.0,
+            duplicate_address_rate: 0.0,
+            invalid_cds_rate: 0.0,
+            dropout_rate: 0.0,
+            prove: true,
+        }
+    }
+}
+
+// REPORT
+// ================================================================================================
+
+/// Acceptance statistics of the submissions of a phase
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SubmissionStats {
+    /// Number of voters taking part in the phase
+    pub num_voters: usize,
+    /// Number of submissions
+    pub submitted: usize,
+    /// Number of submissions accepted by the aggregator
+    pub accepted: usize,
+    /// Number of submissions rejected by the aggregator
+    pub rejected: usize,
+}
+
+impl SubmissionStats {
+    /// Number of voters who did not submit anything
+    pub fn dropped(&self) -> usize {
+        self.num_voters - self.submitted
+    }
+}
+
+/// Outcome of a simulated election
+#[derive(Debug)]
+pub struct SimulationReport {
+    /// Statistics of the registration phase
+    pub registrations: SubmissionStats,
+    /// Statistics of the casting phase
+    pub votes: SubmissionStats,
+    /// Time spent generating the register proof
+    pub register_proof_time: Option<Duration>,
+    /// Time spent generating the cast proof
+    pub cast_proof_time: Option<Duration>,
+    /// Error raised when generating the cast proof
+    pub cast_proof_error: Option<CollectorError>,
+    /// Time spent tallying votes
+    pub tally_time: Option<Duration>,
+    /// Number of "yes" among accepted votes
+    pub expected_tally_result: u32,
+    /// Tally result, if all registered voters cast a valid vote
+    pub tally_result: Option<u32>,
+}
+
+impl fmt::Display for SubmissionStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} voters, {} submitted, {} accepted, {} rejected, {} dropped",
+            self.num_voters,
+            self.submitted,
+            self.accepted,
+            self.rejected,
+            self.dropped()
+        )
+    }
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "registrations: {}", self.registrations)?;
+        writeln!(f, "votes: {}", self.votes)?;
+        if let Some(time) = self.register_proof_time {
+            writeln!(f, "register proof: {} ms", time.as_millis())?;
+        }
+        match (self.cast_proof_time, &self.cast_proof_error) {
+            (Some(time), _) => writeln!(f, "cast proof: {} ms", time.as_millis())?,
+            (None, Some(error)) => writeln!(f, "cast proof: failed with {:?}", error)?,
+            _ => {}
+        }
+        if let Some(time) = self.tally_time {
+            writeln!(f, "tally: {} ms", time.as_millis())?;
+        }
+        match self.tally_result {
+            Some(tally_result) => write!(
+                f,
+                "tally result: {} (expected {})",
+                tally_result, self.expected_tally_result
+            ),
+            None => write!(f, "tally result: not available"),
+        }
+    }
+}
+
+// SIMULATION
+// ================================================================================================
+
+/// Run a simulated election with the given configuration
+pub fn run(config: &SimulationConfig) -> SimulationReport {
+    let mut rng = OsRng;
+
+    let (secret_keys, voting_keys) = random_key_pairs(config.num_voters);
+    let (tree, hash_indices) = build_merkle_tree_with_keys(&voting_keys);
+    let mut registar = VoterRegistar::new(tree, config.num_voters);
+
+    // REGISTRATION
+    let mut registrations = SubmissionStats {
+        num_voters: config.num_voters,
+        ..SubmissionStats::default()
+    };
+    let mut registered_secret_keys = vec![];
+    for i in 0..config.num_voters {
+        if draw(&mut rng, config.dropout_rate) {
+            continue;
+        }
+        let mut address = Address::random();
+        if i > 0 && draw(&mut rng, config.duplicate_address_rate) {
+            address = registar
+                .addresses
+                .last()
+                .copied()
+                .unwrap_or_else(Address::random);
+        }
+        let mut signature =
+            sign_messages(&vec![voting_keys[i]], &vec![address], &vec![secret_keys[i]])[0];
+        if draw(&mut rng, config.bad_signature_rate) {
+            signature.1 += Scalar::one();
+        }
+        let registration = Registration {
+            voting_key: voting_keys[i],
+            merkle_branch: registar.tree.branch(hash_indices[i]),
+            hash_index: hash_indices[i],
+            signature,
+            address,
+            ownership_signature: None,
+        };
+
+        registrations.submitted += 1;
+        match registar.add_registration(registration) {
+            Ok(()) => {
+                registrations.accepted += 1;
+                registered_secret_keys.push(secret_keys[i]);
+            }
+            Err(_) => registrations.rejected += 1,
+        }
+    }
+
+    let register_proof_time = if config.prove && !registar.voting_keys.is_empty() {
+        let indices = (0..registar.voting_keys.len()).collect::<Vec<usize>>();
+        let now = Instant::now();
+        registar
+            .get_register_proof_for(&indices)
+            .expect("Failed to generate register proof.");
+        Some(now.elapsed())
+    } else {
+        None
+    };
+
+    // CASTING
+    let registered_keys = registar.voting_keys.clone();
+    let mut votes = SubmissionStats {
+        num_voters: registered_keys.len(),
+        ..SubmissionStats::default()
+    };
+    let mut expected_tally_result = 0;
+    let mut collector = VoteCollector::new(registered_keys.clone());
+    for (i, &secret_key) in registered_secret_keys.iter().enumerate() {
+        if draw(&mut rng, config.dropout_rate) {
+            continue;
+        }
+        let vote = rng.next_u32() % 2 == 1;
+        let mut encrypted_vote = EncryptedVote::cast(i, secret_key, &registered_keys, vote);
+        if draw(&mut rng, config.invalid_cds_rate) {
+            encrypted_vote.proof_scalars[0] += Scalar::one();
+        }
+
+        votes.submitted += 1;
+        match collector.add_encrypted_vote(encrypted_vote) {
+            Ok(()) => {
+                votes.accepted += 1;
+                expected_tally_result += vote as u32;
+            }
+            Err(_) => votes.rejected += 1,
+        }
+    }
+
+    // the cast proof and the tally require a valid vote from every registered voter,
+    // and a power of two of them
+    let complete = votes.accepted == votes.num_voters && votes.num_voters.is_power_of_two();
+    let mut cast_proof_time = None;
+    let mut cast_proof_error = None;
+    if config.prove {
+        if complete {
+            let now = Instant::now();
+            match collector.get_cast_proof() {
+                Ok(_) => cast_proof_time = Some(now.elapsed()),
+                Err(error) => cast_proof_error = Some(error),
+            }
+        } else {
+            cast_proof_error = Some(CollectorError::NotEnoughEncryptedVotes);
+        }
+    }
+
+    // TALLY
+    let (tally_time, tally_result) = if complete {
+        let encrypted_votes = collector
+            .encrypted_votes
+            .iter()
+            .map(|encrypted_vote| encrypted_vote.unwrap())
+            .collect();
+        let mut tallier = VoteTallier::new(encrypted_votes);
+        let now = Instant::now();
+        let tally_result = tallier.tally_votes().ok();
+        (Some(now.elapsed()), tally_result)
+    } else {
+        (None, None)
+    };
+
+    SimulationReport {
+        registrations,
+        votes,
+        register_proof_time,
+        cast_proof_time,
+        cast_proof_error,
+        tally_time,
+        expected_tally_result,
+        tally_result,
+    }
+}
+
+/// Return true with probability rate
+fn draw(rng: &mut OsRng, rate: f64) -> bool {
+    (rng.next_u32() as f64) < rate * (u32::MAX as f64)
+}
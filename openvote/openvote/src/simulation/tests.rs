@@ -0,0 +1,47 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+
+#[test]
+fn simulation_test_honest_voters() {
+    let mut config = SimulationConfig::new(4);
+    config.prove = false;
+    let report = run(&config);
+
+    assert_eq!(report.registrations.accepted, 4);
+    assert_eq!(report.votes.accepted, 4);
+    assert_eq!(report.votes.dropped(), 0);
+    assert_eq!(report.tally_result, Some(report.expected_tally_result));
+    assert!(report.register_proof_time.is_none());
+}
+
+#[test]
+fn simulation_test_misbehaving_voters() {
+    let mut config = SimulationConfig::new(4);
+    config.bad_signature_rate = 1.0;
+    let report = run(&config);
+
+    assert_eq!(report.registrations.submitted, 4);
+    assert_eq!(report.registrations.rejected, 4);
+    assert_eq!(report.votes.num_voters, 0);
+    assert_eq!(report.tally_result, None);
+    assert_eq!(
+        report.cast_proof_error,
+        Some(CollectorError::NotEnoughEncryptedVotes)
+    );
+
+    let mut config = SimulationConfig::new(4);
+    config.invalid_cds_rate = 1.0;
+    config.prove = false;
+    let report = run(&config);
+
+    assert_eq!(report.registrations.accepted, 4);
+    assert_eq!(report.votes.rejected, 4);
+    assert_eq!(report.tally_result, None);
+}
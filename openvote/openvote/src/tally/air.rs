@@ -6,11 +6,17 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use super::{constants::*, projective_to_elements};
-use crate::utils::{ecc, not};
-use winterfell::math::curves::curve_f63::AffinePoint;
+use super::constants::*;
+use crate::{
+    chain::{write_phase_commitment, PhaseCommitment},
+    compat::curve::{AffinePoint, Scalar},
+    utils::{
+        ecc::{self, projective_to_elements},
+        not,
+    },
+};
 use winterfell::{
-    math::{curves::curve_f63::Scalar, fields::f63::BaseElement, FieldElement},
+    math::{fields::f63::BaseElement, FieldElement},
     Air, AirContext, Assertion, ByteWriter, EvaluationFrame, ProofOptions, Serializable, TraceInfo,
     TransitionConstraintDegree,
 };
@@ -24,6 +30,8 @@ use alloc::vec::Vec;
 pub struct PublicInputs {
     pub encrypted_votes: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
     pub tally_result: u32,
+    // commitment to the vote casting phase the proof is chained to, if any
+    pub ballot_commitment: Option<PhaseCommitment>,
 }
 
 impl Serializable for PublicInputs {
@@ -32,6 +40,7 @@ impl Serializable for PublicInputs {
         for encrypted_vote in self.encrypted_votes.iter() {
             Serializable::write_batch_into(encrypted_vote, target);
         }
+        write_phase_commitment(self.ballot_commitment, target);
     }
 }
 
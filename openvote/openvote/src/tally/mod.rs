@@ -7,15 +7,15 @@
 // except according to those terms.
 
 use self::constants::*;
-use super::utils::ecc;
+use super::utils::ecc::{self, projective_to_elements};
+use crate::compat::curve::{AffinePoint, ProjectivePoint, Scalar};
+use crate::compat::proof_options;
+use core::convert::TryFrom;
 use rand_core::{OsRng, RngCore};
 use winterfell::{
-    math::{
-        curves::curve_f63::{AffinePoint, ProjectivePoint, Scalar},
-        fields::f63::BaseElement,
-        FieldElement,
-    },
-    FieldExtension, HashFunction, ProofOptions, Prover, StarkProof, VerifierError,
+    math::{fields::f63::BaseElement, FieldElement},
+    DeserializationError, FieldExtension, HashFunction, ProofOptions, Prover, StarkProof,
+    VerifierError,
 };
 
 #[cfg(not(feature = "std"))]
@@ -31,7 +31,7 @@ use winterfell::{math::log2, Trace};
 pub(crate) mod constants;
 
 mod air;
-use air::{PublicInputs, TallyAir};
+pub(crate) use air::{PublicInputs, TallyAir};
 
 mod prover;
 pub(crate) use prover::TallyProver;
@@ -46,7 +46,7 @@ mod tests;
 pub fn get_example(num_signatures: usize) -> TallyExample {
     TallyExample::new(
         // TODO: make it customizable
-        ProofOptions::new(
+        proof_options(
             42,
             8,
             0,
@@ -75,21 +75,29 @@ impl TallyExample {
     pub fn new(options: ProofOptions, num_votes: usize) -> TallyExample {
         // compute the encrypted votes
         let mut rng = OsRng;
-        let tally_result = rng.next_u32() % ((num_votes + 1) as u32);
+        let tally_result = match u32::try_from(num_votes)
+            .ok()
+            .and_then(|num_votes| num_votes.checked_add(1))
+        {
+            Some(num_results) => rng.next_u32() % num_results,
+            None => rng.next_u32(),
+        };
         let mut encrypted_votes = Vec::with_capacity(num_votes);
 
         let d = Scalar::from(tally_result).double() - Scalar::from(num_votes as u64);
         let mut s_sum = Scalar::zero();
 
-        for _ in 0..num_votes - 1 {
+        for _ in 1..num_votes {
             let s = Scalar::random(rng);
             s_sum += s;
             let encrypted_vote = AffinePoint::generator() * s;
             encrypted_votes.push(projective_to_elements(encrypted_vote))
         }
 
-        let encrypted_vote = AffinePoint::generator() * (d - s_sum);
-        encrypted_votes.push(projective_to_elements(encrypted_vote));
+        if num_votes > 0 {
+            let encrypted_vote = AffinePoint::generator() * (d - s_sum);
+            encrypted_votes.push(projective_to_elements(encrypted_vote));
+        }
 
         // verify the tally result
         #[cfg(feature = "std")]
@@ -123,7 +131,7 @@ impl TallyExample {
 
         let prover = TallyProver::new(
             self.options.clone(),
-            self.encrypted_votes.clone(),
+            &self.encrypted_votes,
             self.tally_result,
         );
 
@@ -148,8 +156,9 @@ impl TallyExample {
         let pub_inputs = PublicInputs {
             encrypted_votes: self.encrypted_votes.clone(),
             tally_result: self.tally_result,
+            ballot_commitment: None,
         };
-        winterfell::verify::<TallyAir>(proof, pub_inputs)
+        crate::compat::verify::<TallyAir>(proof, pub_inputs)
     }
 
     #[cfg(test)]
@@ -159,11 +168,12 @@ impl TallyExample {
         let mut pub_inputs = PublicInputs {
             encrypted_votes: self.encrypted_votes.clone(),
             tally_result: self.tally_result,
+            ballot_commitment: None,
         };
         let fault_idx = (rng.next_u32() as usize) % num_votes;
         let fault_position = (rng.next_u32() as usize) % self.encrypted_votes[0].len();
         pub_inputs.encrypted_votes[fault_idx][fault_position] += BaseElement::ONE;
-        winterfell::verify::<TallyAir>(proof, pub_inputs)
+        crate::compat::verify::<TallyAir>(proof, pub_inputs)
     }
 
     #[cfg(test)]
@@ -173,12 +183,46 @@ impl TallyExample {
         let mut pub_inputs = PublicInputs {
             encrypted_votes: self.encrypted_votes.clone(),
             tally_result: self.tally_result,
+            ballot_commitment: None,
         };
         while pub_inputs.tally_result == self.tally_result {
             pub_inputs.tally_result = rng.next_u32() % ((num_votes + 1) as u32);
         }
-        winterfell::verify::<TallyAir>(proof, pub_inputs)
+        crate::compat::verify::<TallyAir>(proof, pub_inputs)
+    }
+}
+
+// SELF-TALLYING
+// ================================================================================================
+
+/// Compute the number of "yes" votes from the encrypted votes, without any secret.
+/// Blinding keys cancel out in the sum of all encrypted votes, which is then
+/// (2 * tally_result - num_votes) * G. Return None if no result in range matches the sum.
+pub fn self_tally(encrypted_votes: &[[BaseElement; AFFINE_POINT_WIDTH]]) -> Option<u32> {
+    let num_votes = u32::try_from(encrypted_votes.len()).ok()?;
+    let mut encrypted_sum = ProjectivePoint::identity();
+    for &encrypted_vote in encrypted_votes.iter() {
+        encrypted_sum += AffinePoint::from_raw_coordinates(encrypted_vote);
+    }
+
+    // recover the discrete log by walking through every possible result
+    let double_generator = ProjectivePoint::generator() + ProjectivePoint::generator();
+    let mut candidate =
+        ProjectivePoint::identity() - ProjectivePoint::generator() * Scalar::from(num_votes);
+    for tally_result in 0..=num_votes {
+        if candidate == encrypted_sum {
+            return Some(tally_result);
+        }
+        candidate += double_generator;
     }
+    None
+}
+
+/// Compute the number of "yes" votes from encrypted votes in the format stored on smart
+/// contract, so that any observer can check the result published by the aggregator
+pub fn self_tally_bytes(encrypted_votes: &[u8]) -> Result<Option<u32>, DeserializationError> {
+    let encrypted_votes = crate::verifier::decode_encrypted_votes(encrypted_votes)?;
+    Ok(self_tally(&encrypted_votes))
 }
 
 // HELPER FUNCTIONS
@@ -191,6 +235,9 @@ pub fn naive_verify_tally_result(
     tally_result: u32,
 ) -> bool {
     let num_votes = encrypted_votes.len();
+    if tally_result as u64 > num_votes as u64 {
+        return false;
+    }
     let mut encrypted_sum = IDENTITY;
     let expected_sum = AffinePoint::generator()
         * (Scalar::from(tally_result).double() - Scalar::from(num_votes as u64));
@@ -211,13 +258,3 @@ pub fn naive_verify_tally_result(
         encrypted_sum == expected_sum
     }
 }
-
-#[inline]
-/// Convert ProjectivePoint to AffinePoint then convert AffinePoint to array of BaseElement
-fn projective_to_elements(point: ProjectivePoint) -> [BaseElement; AFFINE_POINT_WIDTH] {
-    let mut result = [BaseElement::ZERO; AFFINE_POINT_WIDTH];
-    result[..POINT_COORDINATE_WIDTH].copy_from_slice(&AffinePoint::from(point).get_x());
-    result[POINT_COORDINATE_WIDTH..AFFINE_POINT_WIDTH]
-        .copy_from_slice(&AffinePoint::from(point).get_y());
-    result
-}
@@ -1,13 +1,10 @@
 use super::constants::*;
 use super::PublicInputs;
 use super::TallyAir;
-use crate::utils::ecc;
+use crate::compat::curve::{AffinePoint, Scalar};
+use crate::{chain::PhaseCommitment, utils::ecc};
 use winterfell::{
-    math::{
-        curves::curve_f63::{AffinePoint, Scalar},
-        fields::f63::BaseElement,
-        FieldElement, StarkField,
-    },
+    math::{fields::f63::BaseElement, FieldElement, StarkField},
     ProofOptions, Prover, TraceTable,
 };
 
@@ -17,26 +14,36 @@ use alloc::vec::Vec;
 // TALLY PROVER
 // ================================================================================================
 
-pub struct TallyProver {
+// encrypted votes are borrowed from the caller to avoid cloning them
+pub struct TallyProver<'a> {
     options: ProofOptions,
-    encrypted_votes: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+    encrypted_votes: &'a [[BaseElement; AFFINE_POINT_WIDTH]],
     // number of "yes" votes
     tally_result: u32,
+    // commitment to the vote casting phase the proof is chained to, if any
+    ballot_commitment: Option<PhaseCommitment>,
 }
 
-impl TallyProver {
+impl<'a> TallyProver<'a> {
     pub fn new(
         options: ProofOptions,
-        encrypted_votes: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+        encrypted_votes: &'a [[BaseElement; AFFINE_POINT_WIDTH]],
         tally_result: u32,
     ) -> Self {
         Self {
             options,
             encrypted_votes,
             tally_result,
+            ballot_commitment: None,
         }
     }
 
+    /// Chain the proof to the commitment to the vote casting phase
+    pub fn with_ballot_commitment(mut self, ballot_commitment: PhaseCommitment) -> Self {
+        self.ballot_commitment = Some(ballot_commitment);
+        self
+    }
+
     pub fn build_trace(&self) -> TraceTable<BaseElement> {
         // the number of valid encrypted votes is supposed
         // to be a power of two (checked in cds)
@@ -67,7 +74,7 @@ impl TallyProver {
                 }
             },
             |step, state| {
-                if (step as u32) < num_votes - 2 {
+                if (step as u32) + 2 < num_votes {
                     ecc::compute_add_mixed(state, &self.encrypted_votes[step]);
                 } else {
                     ecc::compute_add_mixed(state, &self.encrypted_votes[step]);
@@ -81,7 +88,7 @@ impl TallyProver {
     }
 }
 
-impl Prover for TallyProver {
+impl<'a> Prover for TallyProver<'a> {
     type BaseField = BaseElement;
     type Air = TallyAir;
     type Trace = TraceTable<BaseElement>;
@@ -93,8 +100,9 @@ impl Prover for TallyProver {
     // where only initial and final Merkle roots are provided to the verifier.
     fn get_pub_inputs(&self, _trace: &Self::Trace) -> PublicInputs {
         PublicInputs {
-            encrypted_votes: self.encrypted_votes.clone(),
+            encrypted_votes: self.encrypted_votes.to_vec(),
             tally_result: self.tally_result,
+            ballot_commitment: self.ballot_commitment,
         }
     }
 
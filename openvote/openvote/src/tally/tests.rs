@@ -6,6 +6,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use crate::compat::proof_options;
 use winterfell::{FieldExtension, HashFunction, ProofOptions};
 
 #[test]
@@ -45,8 +46,48 @@ fn tally_test_proof_verification_wrong_tally_result() {
     assert!(verified.is_err());
 }
 
+#[test]
+fn tally_test_self_tally() {
+    let tally = super::TallyExample::new(build_options(1), 8);
+    assert_eq!(
+        super::self_tally(&tally.encrypted_votes),
+        Some(tally.tally_result)
+    );
+
+    // observers get the same result from the encrypted votes stored on smart contract
+    let encrypted_votes = crate::verifier::encode_encrypted_votes(&tally.encrypted_votes);
+    assert_eq!(
+        super::self_tally_bytes(&encrypted_votes),
+        Ok(Some(tally.tally_result))
+    );
+    assert_eq!(
+        crate::verifier::verify_tally_result(&encrypted_votes, tally.tally_result),
+        Ok(true)
+    );
+    assert!(super::self_tally_bytes(&encrypted_votes[1..]).is_err());
+
+    // dropping a vote leaves a sum which is not the encryption of any result
+    assert_eq!(super::self_tally(&tally.encrypted_votes[1..]), None);
+    assert_eq!(super::self_tally(&[]), Some(0));
+}
+
+#[test]
+fn tally_test_result_out_of_range() {
+    // an empty election has no vote to count
+    let tally = super::TallyExample::new(build_options(1), 0);
+    assert!(tally.encrypted_votes.is_empty());
+    assert_eq!(tally.tally_result, 0);
+
+    let tally = super::TallyExample::new(build_options(1), 4);
+    assert!(!super::naive_verify_tally_result(&tally.encrypted_votes, 5));
+    assert!(!super::naive_verify_tally_result(
+        &tally.encrypted_votes,
+        u32::MAX
+    ));
+}
+
 fn build_options(extension: u8) -> ProofOptions {
-    ProofOptions::new(
+    proof_options(
         42,
         8,
         0,
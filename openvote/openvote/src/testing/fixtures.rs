@@ -0,0 +1,130 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// EXAMPLE FILES
+// ================================================================================================
+// Byte files of an example election, as written by the generate-example binary. Files prefixed
+// with a selector are the exact inputs of the STARK verifier precompile, so that the precompile
+// can be tested end-to-end against the same bytes that are submitted on chain.
+
+use crate::{
+    aggregator::AggregatorExample,
+    utils::ecc::{compress_point, GENERATOR},
+};
+use std::{fs::File, io::Write, path::Path};
+use winterfell::{ByteWriter, Serializable};
+
+/// Selector of the register proof verification method of the precompile
+pub const VERIFY_REGISTER_SELECTOR: [u8; 4] = [243, 90, 41, 19];
+/// Selector of the cast proof verification method of the precompile
+pub const VERIFY_CAST_SELECTOR: [u8; 4] = [199, 65, 76, 236];
+
+/// Byte files of an example election
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExampleFiles {
+    /// Serialized generator of the curve
+    pub generator: Vec<u8>,
+    /// Serialized root of the Merkle tree of eligible voters
+    pub elg_root: Vec<u8>,
+    /// Register proof, as accepted by verify_register_proof
+    pub truncated_register_proof: Vec<u8>,
+    /// Register proof prefixed with its selector and the Merkle root
+    pub register_proof: Vec<u8>,
+    /// Cast proof, as accepted by verify_cast_proof
+    pub truncated_cast_proof: Vec<u8>,
+    /// Cast proof prefixed with its selector and the voting keys
+    pub cast_proof: Vec<u8>,
+    /// Voting keys, as accepted by verify_cast_proof
+    pub voting_keys: Vec<u8>,
+    /// Encrypted votes, as accepted by verify_tally_result
+    pub encrypted_votes: Vec<u8>,
+    /// Big-endian tally result
+    pub tally_result: Vec<u8>,
+}
+
+impl ExampleFiles {
+    /// Run an example election with the given number of voters and encode its files
+    pub fn generate(num_voters: usize) -> Self {
+        let mut aggregator = AggregatorExample::new(num_voters);
+        let register_proof = aggregator.voter_registar.get_register_proof().unwrap();
+        let cast_proof = aggregator.vote_collector.get_cast_proof().unwrap();
+        let tally_result = aggregator.vote_tallier.tally_votes().unwrap();
+
+        let mut generator = vec![];
+        Serializable::write_batch_into(&GENERATOR, &mut generator);
+        let mut elg_root = vec![];
+        Serializable::write_batch_into(&aggregator.voter_registar.elg_root, &mut elg_root);
+
+        // | selector | elg_root | register_proof |
+        let mut ext_register_proof = vec![];
+        ext_register_proof.write_u8_slice(&VERIFY_REGISTER_SELECTOR);
+        ext_register_proof.write_u8_slice(&elg_root);
+        ext_register_proof.write_u8_slice(&register_proof);
+
+        let collector = &aggregator.vote_collector;
+        let mut voting_keys = vec![];
+        voting_keys.write_u8_slice(&(collector.voting_keys.len() as u32).to_be_bytes());
+        for voting_key in collector.voting_keys.iter() {
+            voting_keys.write_u8_slice(&compress_point(voting_key));
+        }
+
+        // | selector | num_keys | voting_keys | cast_proof |
+        let mut ext_cast_proof = vec![];
+        ext_cast_proof.write_u8_slice(&VERIFY_CAST_SELECTOR);
+        ext_cast_proof.write_u8_slice(&(collector.num_valid_votes as u32).to_be_bytes());
+        for voting_key in collector.voting_keys.iter() {
+            ext_cast_proof.write_u8_slice(&compress_point(voting_key));
+        }
+        ext_cast_proof.write_u8_slice(&cast_proof);
+
+        Self {
+            generator,
+            elg_root,
+            truncated_register_proof: register_proof,
+            register_proof: ext_register_proof,
+            truncated_cast_proof: cast_proof,
+            cast_proof: ext_cast_proof,
+            voting_keys,
+            encrypted_votes: aggregator.vote_tallier.encrypted_votes_bytes(),
+            tally_result: tally_result.to_be_bytes().to_vec(),
+        }
+    }
+
+    /// Tally result encoded in the tally_result file
+    pub fn tally_result(&self) -> u32 {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(&self.tally_result);
+        u32::from_be_bytes(bytes)
+    }
+
+    /// Names and contents of the files
+    pub fn files(&self) -> [(&'static str, &[u8]); 9] {
+        [
+            ("generator.dat", &self.generator),
+            ("elg_root.dat", &self.elg_root),
+            (
+                "truncated_register_proof.dat",
+                &self.truncated_register_proof,
+            ),
+            ("register_proof.dat", &self.register_proof),
+            ("truncated_cast_proof.dat", &self.truncated_cast_proof),
+            ("cast_proof.dat", &self.cast_proof),
+            ("voting_keys.dat", &self.voting_keys),
+            ("encrypted_votes.dat", &self.encrypted_votes),
+            ("tally_result.dat", &self.tally_result),
+        ]
+    }
+
+    /// Write the files into an existing directory
+    pub fn write_to(&self, dir: &Path) -> std::io::Result<()> {
+        for (name, bytes) in self.files().iter() {
+            File::create(dir.join(name))?.write_all(bytes)?;
+        }
+        Ok(())
+    }
+}
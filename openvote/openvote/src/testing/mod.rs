@@ -0,0 +1,78 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// TESTING PRESETS
+// ================================================================================================
+// Merkle proofs of production depth dominate the proving time of tests. These presets prove
+// membership in much smaller trees, and should be used wherever the depth does not matter.
+
+use crate::compat::curve::{AffinePoint, ProjectivePoint, Scalar};
+use crate::compat::proof_options;
+use crate::merkle::MerkleExample;
+use crate::utils::ecc::{compress_point, decompress_point, GENERATOR};
+use winterfell::{FieldExtension, HashFunction, ProofOptions};
+
+/// Byte files of an example election, as fed to the STARK verifier precompile
+#[cfg(feature = "std")]
+pub mod fixtures;
+
+/// Proptest strategies for the round-trip tests of serialization
+#[cfg(test)]
+pub(crate) mod strategies;
+
+/// Smallest supported depth of Merkle tree, with 4 leaves
+pub const SMALL_TREE_DEPTH: usize = 2;
+
+/// Intermediate supported depth of Merkle tree, with 64 leaves
+pub const MEDIUM_TREE_DEPTH: usize = 6;
+
+/// Fast proof options with the given field extension (1: none, 2: quadratic, 3: cubic)
+pub fn test_options(extension: u8) -> ProofOptions {
+    proof_options(
+        42,
+        8,
+        0,
+        HashFunction::Blake3_192,
+        match extension {
+            2 => FieldExtension::Quadratic,
+            3 => FieldExtension::Cubic,
+            _ => FieldExtension::None,
+        },
+        4,
+        256,
+    )
+}
+
+/// Outputs a new `MerkleExample` with `num_keys` Merkle proofs of membership
+/// in a tree of depth SMALL_TREE_DEPTH, or MEDIUM_TREE_DEPTH if it is too small
+pub fn small_merkle_example(num_keys: usize) -> MerkleExample {
+    let depth = if num_keys <= 1 << SMALL_TREE_DEPTH {
+        SMALL_TREE_DEPTH
+    } else {
+        MEDIUM_TREE_DEPTH
+    };
+    MerkleExample::with_depth(test_options(1), num_keys, depth)
+}
+
+/// Point of the curve of order dividing the cofactor, i.e. with no component in the
+/// subgroup generated by G, to craft voting keys and encrypted votes of small order.
+/// It is obtained as q * P for the first point P found next to G in compressed form.
+pub fn small_order_point() -> ProjectivePoint {
+    let mut compressed = compress_point(&GENERATOR);
+    for byte in 0..=u8::MAX {
+        compressed[0] = byte;
+        if let Some(coordinates) = decompress_point(&compressed) {
+            let point = ProjectivePoint::from(AffinePoint::from_raw_coordinates(coordinates));
+            let small_order_point = point * -Scalar::one() + point;
+            if !small_order_point.is_identity() {
+                return small_order_point;
+            }
+        }
+    }
+    panic!("no point outside of the subgroup generated by G was found");
+}
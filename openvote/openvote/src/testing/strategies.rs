@@ -0,0 +1,71 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// PROPTEST STRATEGIES
+// ================================================================================================
+// Strategies generating the values written by the Serializable implementations of the crate.
+// Points and scalars are derived from a random seed, so that they are always canonical.
+
+use crate::compat::curve::{ProjectivePoint, Scalar};
+use crate::schnorr::{constants::DOMAIN_ELECTION_ID_SIZE, SigningDomain};
+use crate::utils::{
+    address::Address,
+    ecc::{projective_to_elements, AFFINE_POINT_WIDTH, POINT_COORDINATE_WIDTH},
+    rescue::DIGEST_SIZE,
+};
+use proptest::{collection::vec, prelude::*};
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use winterfell::math::{fields::f63::BaseElement, FieldElement};
+
+/// Field element
+pub fn element() -> impl Strategy<Value = BaseElement> {
+    any::<u64>().prop_map(BaseElement::new)
+}
+
+/// Digest of the Rescue hash function
+pub fn digest() -> impl Strategy<Value = [BaseElement; DIGEST_SIZE]> {
+    vec(element(), DIGEST_SIZE).prop_map(|elements| {
+        let mut digest = [BaseElement::ZERO; DIGEST_SIZE];
+        digest.copy_from_slice(&elements);
+        digest
+    })
+}
+
+/// Scalar drawn from a seeded random number generator
+pub fn scalar() -> impl Strategy<Value = Scalar> {
+    any::<u64>().prop_map(|seed| Scalar::random(&mut ChaCha20Rng::seed_from_u64(seed)))
+}
+
+/// Point of the subgroup generated by G, in affine coordinates
+pub fn point() -> impl Strategy<Value = [BaseElement; AFFINE_POINT_WIDTH]> {
+    scalar().prop_map(|scalar| projective_to_elements(ProjectivePoint::generator() * scalar))
+}
+
+/// Ethereum address
+pub fn address() -> impl Strategy<Value = Address> {
+    any::<[u8; 20]>().prop_map(|bytes| Address::from_slice(&bytes))
+}
+
+/// Schnorr signature, which is not valid for any message
+pub fn signature() -> impl Strategy<Value = ([BaseElement; POINT_COORDINATE_WIDTH], Scalar)> {
+    (vec(element(), POINT_COORDINATE_WIDTH), scalar()).prop_map(|(elements, s)| {
+        let mut r = [BaseElement::ZERO; POINT_COORDINATE_WIDTH];
+        r.copy_from_slice(&elements);
+        (r, s)
+    })
+}
+
+/// Signing domain, unbound half of the time
+pub fn domain() -> impl Strategy<Value = SigningDomain> {
+    prop_oneof![
+        Just(SigningDomain::default()),
+        (any::<u64>(), any::<[u8; DOMAIN_ELECTION_ID_SIZE]>())
+            .prop_map(|(chain_id, election_id)| SigningDomain::new(chain_id, election_id)),
+    ]
+}
@@ -0,0 +1,80 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// ABI ENCODING
+// ================================================================================================
+// Minimal Solidity ABI encoder for the outputs of the STARK verifier precompile, which are
+// decoded by contracts with abi.decode. Static values take one 32-byte word in the head, and
+// dynamic values are referenced by an offset in the head and appended to the tail, prefixed
+// with their length and right-padded to a multiple of 32 bytes.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// Size of an ABI word, in bytes
+pub const WORD_SIZE: usize = 32;
+
+/// Value of an ABI-encoded tuple
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token<'a> {
+    /// Solidity bool
+    Bool(bool),
+    /// Solidity uint256 holding a value of at most 64 bits
+    Uint(u64),
+    /// Solidity bytes
+    Bytes(&'a [u8]),
+}
+
+/// Length of the given number of bytes padded to a multiple of the word size,
+/// or None if it overflows
+pub fn padded_len(len: usize) -> Option<usize> {
+    len.checked_add(WORD_SIZE - 1)
+        .map(|len| len / WORD_SIZE * WORD_SIZE)
+}
+
+/// Length of the ABI encoding of the given tuple, or None if it overflows
+pub fn encoded_len(tokens: &[Token]) -> Option<usize> {
+    tokens.iter().try_fold(0usize, |len, token| {
+        let token_len = match token {
+            Token::Bool(_) | Token::Uint(_) => WORD_SIZE,
+            Token::Bytes(bytes) => padded_len(bytes.len())?.checked_add(2 * WORD_SIZE)?,
+        };
+        len.checked_add(token_len)
+    })
+}
+
+/// ABI encoding of the given tuple, i.e. the equivalent of abi.encode
+/// in Solidity, or None if its length overflows
+pub fn encode(tokens: &[Token]) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(encoded_len(tokens)?);
+    let mut tail = Vec::new();
+    let head_len = tokens.len() * WORD_SIZE;
+    for token in tokens.iter() {
+        match token {
+            Token::Bool(value) => write_word(&mut output, *value as u64),
+            Token::Uint(value) => write_word(&mut output, *value),
+            Token::Bytes(bytes) => {
+                write_word(&mut output, (head_len + tail.len()) as u64);
+                write_word(&mut tail, bytes.len() as u64);
+                tail.extend_from_slice(bytes);
+                tail.resize(tail.len() + padded_len(bytes.len())? - bytes.len(), 0);
+            }
+        }
+    }
+    output.extend_from_slice(&tail);
+    Some(output)
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Append a big-endian word holding a value of at most 64 bits
+fn write_word(target: &mut Vec<u8>, value: u64) {
+    target.extend_from_slice(&[0u8; WORD_SIZE - 8]);
+    target.extend_from_slice(&value.to_be_bytes());
+}
@@ -0,0 +1,111 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use core::fmt;
+use rand_core::{OsRng, RngCore};
+use winterfell::{ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable};
+
+/// Number of bytes of an Ethereum address
+pub const ADDRESS_LENGTH: usize = 20;
+
+/// A 20-byte Ethereum address
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Address([u8; ADDRESS_LENGTH]);
+
+impl Address {
+    /// Number of bytes of an address
+    pub const fn len_bytes() -> usize {
+        ADDRESS_LENGTH
+    }
+
+    /// Create an address from exactly 20 bytes
+    ///
+    /// # Panics
+    /// Panics if `bytes` is not 20 bytes long
+    pub fn from_slice(bytes: &[u8]) -> Self {
+        let mut address = [0u8; ADDRESS_LENGTH];
+        address.copy_from_slice(bytes);
+        Self(address)
+    }
+
+    /// Create a random address
+    pub fn random() -> Self {
+        Self::random_with_rng(&mut OsRng)
+    }
+
+    /// Create a random address drawn from the given random number generator
+    pub fn random_with_rng<R: RngCore>(rng: &mut R) -> Self {
+        let mut address = [0u8; ADDRESS_LENGTH];
+        rng.fill_bytes(&mut address);
+        Self(address)
+    }
+
+    /// Bytes of the address
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Bytes of the address as a fixed-size array
+    pub fn as_fixed_bytes(&self) -> &[u8; ADDRESS_LENGTH] {
+        &self.0
+    }
+}
+
+impl From<[u8; ADDRESS_LENGTH]> for Address {
+    fn from(bytes: [u8; ADDRESS_LENGTH]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<Address> for [u8; ADDRESS_LENGTH] {
+    fn from(address: Address) -> Self {
+        address.0
+    }
+}
+
+#[cfg(feature = "web3")]
+impl From<web3::types::Address> for Address {
+    fn from(address: web3::types::Address) -> Self {
+        Self(address.to_fixed_bytes())
+    }
+}
+
+#[cfg(feature = "web3")]
+impl From<Address> for web3::types::Address {
+    fn from(address: Address) -> Self {
+        web3::types::Address::from(address.0)
+    }
+}
+
+impl fmt::Debug for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x")?;
+        for byte in self.0.iter() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl Serializable for Address {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u8_slice(&self.0);
+    }
+}
+
+impl Deserializable for Address {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        Ok(Self::from_slice(&source.read_u8_vec(ADDRESS_LENGTH)?))
+    }
+}
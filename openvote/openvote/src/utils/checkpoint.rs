@@ -0,0 +1,200 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// PROVER CHECKPOINTS
+// ================================================================================================
+// The execution trace and the final proof are written to a checkpoint directory, so that proving
+// resumes from the last completed stage after an interruption. The low-degree extension and FRI
+// layers are internal to winterfell::Prover::prove and are recomputed from the trace on resume.
+// Each file starts with a digest of the prover inputs, so that stale checkpoints are ignored.
+
+use sha3::{Digest, Sha3_256};
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+use winterfell::{
+    math::fields::f63::BaseElement, Air, ByteReader, ByteWriter, Deserializable,
+    DeserializationError, Prover, ProverError, Serializable, SliceReader, StarkProof, Trace,
+    TraceTable,
+};
+
+/// File holding the execution trace
+pub const TRACE_FILE: &str = "trace.ckpt";
+/// File holding the final proof
+pub const PROOF_FILE: &str = "proof.ckpt";
+
+/// Size of the digest of the prover inputs
+pub const BINDING_SIZE: usize = 32;
+
+// ERRORS
+// ================================================================================================
+
+/// Errors raised when proving from a checkpoint
+#[derive(Debug, PartialEq)]
+pub enum CheckpointError {
+    /// This error occurs when a checkpoint file cannot be read or written
+    Io(ErrorKind),
+    /// This error occurs when a checkpoint file bound to the prover inputs is corrupted
+    Malformed(DeserializationError),
+    /// Wrapper for errors raised by the prover
+    Prover(ProverError),
+}
+
+impl From<io::Error> for CheckpointError {
+    fn from(error: io::Error) -> Self {
+        CheckpointError::Io(error.kind())
+    }
+}
+
+// CHECKPOINT
+// ================================================================================================
+
+/// Checkpoint directory of a proving job, bound to the inputs of the prover
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    dir: PathBuf,
+    binding: [u8; BINDING_SIZE],
+}
+
+impl Checkpoint {
+    /// Open the checkpoint directory of a proving job, creating it if needed.
+    /// The binding is a digest of the prover inputs, see compute_binding.
+    pub fn open(dir: &Path, binding: [u8; BINDING_SIZE]) -> Result<Self, CheckpointError> {
+        fs::create_dir_all(dir)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            binding,
+        })
+    }
+
+    /// Load the execution trace, if it was saved for the same prover inputs
+    pub fn load_trace(&self) -> Result<Option<TraceTable<BaseElement>>, CheckpointError> {
+        let bytes = match self.read(TRACE_FILE)? {
+            Some(bytes) => bytes,
+            None => return Ok(None),
+        };
+        let mut source = SliceReader::new(&bytes);
+        let width = source.read_u32().map_err(CheckpointError::Malformed)? as usize;
+        let length = source.read_u32().map_err(CheckpointError::Malformed)? as usize;
+        let mut columns = Vec::with_capacity(width);
+        for _ in 0..width {
+            columns.push(
+                BaseElement::read_batch_from(&mut source, length)
+                    .map_err(CheckpointError::Malformed)?,
+            );
+        }
+        Ok(Some(TraceTable::init(columns)))
+    }
+
+    /// Save the execution trace
+    pub fn save_trace(&self, trace: &TraceTable<BaseElement>) -> Result<(), CheckpointError> {
+        let mut bytes = vec![];
+        bytes.write_u32(trace.width() as u32);
+        bytes.write_u32(trace.length() as u32);
+        for i in 0..trace.width() {
+            Serializable::write_batch_into(trace.get_column(i), &mut bytes);
+        }
+        self.write(TRACE_FILE, &bytes)
+    }
+
+    /// Load the final proof, if it was saved for the same prover inputs
+    pub fn load_proof(&self) -> Result<Option<StarkProof>, CheckpointError> {
+        match self.read(PROOF_FILE)? {
+            Some(bytes) => StarkProof::from_bytes(&bytes)
+                .map(Some)
+                .map_err(CheckpointError::Malformed),
+            None => Ok(None),
+        }
+    }
+
+    /// Save the final proof
+    pub fn save_proof(&self, proof: &StarkProof) -> Result<(), CheckpointError> {
+        self.write(PROOF_FILE, &proof.to_bytes())
+    }
+
+    /// Remove all checkpoint files of the proving job
+    pub fn clear(&self) -> Result<(), CheckpointError> {
+        for name in [TRACE_FILE, PROOF_FILE].iter() {
+            match fs::remove_file(self.dir.join(name)) {
+                Err(error) if error.kind() != ErrorKind::NotFound => return Err(error.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a checkpoint file, returning None if it is missing or bound to other inputs
+    fn read(&self, name: &str) -> Result<Option<Vec<u8>>, CheckpointError> {
+        let mut bytes = match fs::read(self.dir.join(name)) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        if bytes.len() < BINDING_SIZE || bytes[..BINDING_SIZE] != self.binding {
+            return Ok(None);
+        }
+        Ok(Some(bytes.split_off(BINDING_SIZE)))
+    }
+
+    /// Write a checkpoint file atomically, so that an interruption
+    /// never leaves a truncated file behind
+    fn write(&self, name: &str, payload: &[u8]) -> Result<(), CheckpointError> {
+        let path = self.dir.join(name);
+        let tmp_path = path.with_extension("tmp");
+        let mut bytes = Vec::with_capacity(BINDING_SIZE + payload.len());
+        bytes.extend_from_slice(&self.binding);
+        bytes.extend_from_slice(payload);
+        fs::write(&tmp_path, &bytes)?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Compute the digest binding a checkpoint to the serialized inputs of a prover
+pub fn compute_binding(inputs: &[u8]) -> [u8; BINDING_SIZE] {
+    let mut binding = [0u8; BINDING_SIZE];
+    binding.copy_from_slice(&Sha3_256::digest(inputs));
+    binding
+}
+
+/// Prove from the last completed stage saved in the checkpoint. The trace is kept
+/// after the proof is saved, as the public inputs are extracted from it.
+pub(crate) fn prove_resumable<P>(
+    prover: &P,
+    checkpoint: &Checkpoint,
+    build_trace: impl FnOnce() -> TraceTable<BaseElement>,
+) -> Result<(<P::Air as Air>::PublicInputs, StarkProof), CheckpointError>
+where
+    P: Prover<BaseField = BaseElement, Trace = TraceTable<BaseElement>>,
+{
+    let trace = match checkpoint.load_trace()? {
+        Some(trace) => trace,
+        None => {
+            let trace = build_trace();
+            checkpoint.save_trace(&trace)?;
+            trace
+        }
+    };
+    let pub_inputs = prover.get_pub_inputs(&trace);
+
+    let proof = match checkpoint.load_proof()? {
+        Some(proof) => proof,
+        None => {
+            let proof = prover.prove(trace).map_err(CheckpointError::Prover)?;
+            checkpoint.save_proof(&proof)?;
+            proof
+        }
+    };
+
+    Ok((pub_inputs, proof))
+}
@@ -0,0 +1,272 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// PROOF COMPRESSION
+// ================================================================================================
+// Serialized proofs are post-processed for transport and calldata. Registrations repeated in the
+// public inputs of a register proof, e.g. when a subset of registrations is padded to a power of
+// two, are written once and referenced by index. The result may then be compressed with zstd.
+// Compressed proofs start with the wire tag followed by COMPRESSION_MARKER, which no version of
+// the wire format uses, so that the verifier decompresses them transparently.
+
+use super::{
+    ecc::BYTES_PER_COMPRESSED_POINT,
+    wire::{read_varint_usize, varint_len, write_varint, WIRE_TAG},
+};
+use crate::{
+    schnorr::PublicInputs as SchnorrPublicInputs,
+    verifier::{
+        constants::{BYTES_PER_ADDRESS, BYTES_PER_SIGNATURE},
+        ProofKind,
+    },
+};
+use alloc::{borrow::Cow, collections::BTreeMap};
+use winterfell::{ByteReader, ByteWriter, DeserializationError, SliceReader};
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+/// Byte following the wire tag at the start of a compressed proof.
+/// It is greater than any version of the wire format.
+pub const COMPRESSION_MARKER: u8 = 0x80;
+
+/// Number of bytes of the header of a compressed proof:
+/// wire tag, compression marker, proof kind and compressor
+pub const COMPRESSION_HEADER_SIZE: usize = 4 + 1 + 1 + 1;
+
+/// Maximum size of a decompressed proof, bounding the memory
+/// allocated for untrusted compressed proofs
+pub const MAX_DECOMPRESSED_SIZE: usize = 1 << 26;
+
+/// Compression level of zstd
+#[cfg(feature = "zstd")]
+pub const ZSTD_LEVEL: i32 = 19;
+
+/// Number of bytes of a registration in the public inputs of a register proof
+const BYTES_PER_REGISTRATION: usize =
+    BYTES_PER_COMPRESSED_POINT + BYTES_PER_ADDRESS + BYTES_PER_SIGNATURE;
+
+/// General-purpose compressor applied after deduplication
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compressor {
+    /// Deduplication only
+    None,
+    /// Deduplication followed by zstd
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Compressor {
+    fn tag(&self) -> u8 {
+        match self {
+            Compressor::None => 0,
+            #[cfg(feature = "zstd")]
+            Compressor::Zstd => 1,
+        }
+    }
+}
+
+// COMPRESSION
+// ================================================================================================
+
+/// Compress a serialized proof of the given kind, as returned by the aggregator
+pub fn compress_proof(
+    kind: ProofKind,
+    proof: &[u8],
+    compressor: Compressor,
+) -> Result<Vec<u8>, DeserializationError> {
+    let deduplicated = match kind {
+        ProofKind::Register => deduplicate_registrations(proof)?,
+        ProofKind::Cast | ProofKind::Tally => proof.to_vec(),
+    };
+
+    let mut compressed = Vec::with_capacity(COMPRESSION_HEADER_SIZE + deduplicated.len());
+    compressed.write_u32(WIRE_TAG);
+    compressed.write_u8(COMPRESSION_MARKER);
+    compressed.write_u8(kind_tag(kind));
+    compressed.write_u8(compressor.tag());
+    match compressor {
+        Compressor::None => compressed.write_u8_slice(&deduplicated),
+        #[cfg(feature = "zstd")]
+        Compressor::Zstd => {
+            let payload = zstd::bulk::compress(&deduplicated, ZSTD_LEVEL).map_err(|_| {
+                DeserializationError::InvalidValue(String::from("zstd compression failed."))
+            })?;
+            compressed.write_u32(deduplicated.len() as u32);
+            compressed.write_u8_slice(&payload);
+        }
+    }
+    Ok(compressed)
+}
+
+/// Return true if the bytes are a compressed proof
+pub fn is_compressed(bytes: &[u8]) -> bool {
+    bytes.len() >= COMPRESSION_HEADER_SIZE
+        && bytes[..4] == WIRE_TAG.to_le_bytes()
+        && bytes[4] == COMPRESSION_MARKER
+}
+
+/// Decompress a compressed proof, or return the bytes unchanged if they are not compressed
+pub fn decompress_proof(bytes: &[u8]) -> Result<Cow<'_, [u8]>, DeserializationError> {
+    if !is_compressed(bytes) {
+        return Ok(Cow::Borrowed(bytes));
+    }
+    let kind = read_kind(bytes[5])?;
+    let payload = &bytes[COMPRESSION_HEADER_SIZE..];
+    let deduplicated = match bytes[6] {
+        0 => Cow::Borrowed(payload),
+        1 => Cow::Owned(zstd_decompress(payload)?),
+        _ => return Err(invalid("Unknown proof compressor.")),
+    };
+
+    match kind {
+        ProofKind::Register => Ok(Cow::Owned(restore_registrations(&deduplicated)?)),
+        ProofKind::Cast | ProofKind::Tally => Ok(Cow::Owned(deduplicated.into_owned())),
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Write each distinct registration of the public inputs of a register proof once:
+/// | num_regs | num_unique | index of each registration | unique registrations | STARK proof |
+/// Unique registrations are written column by column as in the public inputs.
+fn deduplicate_registrations(proof: &[u8]) -> Result<Vec<u8>, DeserializationError> {
+    let num_regs = SliceReader::new(proof).read_u32()? as usize;
+    if (proof.len() - 4) / BYTES_PER_REGISTRATION < num_regs {
+        return Err(DeserializationError::UnexpectedEOF);
+    }
+    let bound = SchnorrPublicInputs::serialized_len(num_regs);
+
+    let columns = registration_columns(num_regs);
+    let mut unique = BTreeMap::new();
+    let mut unique_rows = Vec::new();
+    let mut indices = Vec::with_capacity(num_regs);
+    for i in 0..num_regs {
+        let row = columns
+            .iter()
+            .flat_map(|&(start, width)| &proof[start + i * width..start + (i + 1) * width])
+            .copied()
+            .collect::<Vec<u8>>();
+        let next_index = unique_rows.len();
+        let index = *unique.entry(row).or_insert_with(|| {
+            unique_rows.push(i);
+            next_index
+        });
+        indices.push(index);
+    }
+
+    let mut deduplicated = Vec::with_capacity(proof.len());
+    deduplicated.write_u32(num_regs as u32);
+    write_varint(&mut deduplicated, unique_rows.len() as u64);
+    for &index in indices.iter() {
+        write_varint(&mut deduplicated, index as u64);
+    }
+    for &(start, width) in columns.iter() {
+        for &i in unique_rows.iter() {
+            deduplicated.write_u8_slice(&proof[start + i * width..start + (i + 1) * width]);
+        }
+    }
+    deduplicated.write_u8_slice(&proof[bound..]);
+    Ok(deduplicated)
+}
+
+/// Inverse of deduplicate_registrations
+fn restore_registrations(deduplicated: &[u8]) -> Result<Vec<u8>, DeserializationError> {
+    let mut source = SliceReader::new(deduplicated);
+    let num_regs = source.read_u32()? as usize;
+    let num_unique = read_varint_usize(&mut source)?;
+    if num_unique > num_regs {
+        return Err(invalid("Invalid number of unique registrations."));
+    }
+    // each index takes at least one byte, and each unique registration a full row
+    let mut consumed = 4 + varint_len(num_unique as u64);
+    if (deduplicated.len() - consumed) < num_regs
+        || (deduplicated.len() - consumed - num_regs) / BYTES_PER_REGISTRATION < num_unique
+    {
+        return Err(DeserializationError::UnexpectedEOF);
+    }
+
+    let mut indices = Vec::with_capacity(num_regs);
+    for _ in 0..num_regs {
+        let index = read_varint_usize(&mut source)?;
+        if index >= num_unique {
+            return Err(invalid("Invalid index of unique registration."));
+        }
+        consumed += varint_len(index as u64);
+        indices.push(index);
+    }
+    if (deduplicated.len() - consumed) / BYTES_PER_REGISTRATION < num_unique {
+        return Err(DeserializationError::UnexpectedEOF);
+    }
+
+    let columns = registration_columns(num_unique);
+    let mut proof = Vec::with_capacity(
+        SchnorrPublicInputs::serialized_len(num_regs) + deduplicated.len() - consumed,
+    );
+    proof.write_u32(num_regs as u32);
+    for &(start, width) in columns.iter() {
+        let column = &deduplicated[consumed + start - 4..];
+        for &index in indices.iter() {
+            proof.write_u8_slice(&column[index * width..(index + 1) * width]);
+        }
+    }
+    consumed += num_unique * BYTES_PER_REGISTRATION;
+    proof.write_u8_slice(&deduplicated[consumed..]);
+    Ok(proof)
+}
+
+/// Offset and width of the voting keys, addresses and signatures of the
+/// given number of registrations in the public inputs of a register proof
+fn registration_columns(num_regs: usize) -> [(usize, usize); 3] {
+    let addresses_start = 4 + num_regs * BYTES_PER_COMPRESSED_POINT;
+    let signatures_start = addresses_start + num_regs * BYTES_PER_ADDRESS;
+    [
+        (4, BYTES_PER_COMPRESSED_POINT),
+        (addresses_start, BYTES_PER_ADDRESS),
+        (signatures_start, BYTES_PER_SIGNATURE),
+    ]
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_decompress(payload: &[u8]) -> Result<Vec<u8>, DeserializationError> {
+    let size = SliceReader::new(payload).read_u32()? as usize;
+    if size > MAX_DECOMPRESSED_SIZE {
+        return Err(invalid("Decompressed proof is too large."));
+    }
+    match zstd::bulk::decompress(&payload[4..], size) {
+        Ok(decompressed) if decompressed.len() == size => Ok(decompressed),
+        _ => Err(invalid("Invalid zstd payload.")),
+    }
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_decompress(_payload: &[u8]) -> Result<Vec<u8>, DeserializationError> {
+    Err(invalid("zstd compression is not supported."))
+}
+
+fn kind_tag(kind: ProofKind) -> u8 {
+    match kind {
+        ProofKind::Register => 0,
+        ProofKind::Cast => 1,
+        ProofKind::Tally => 2,
+    }
+}
+
+fn read_kind(tag: u8) -> Result<ProofKind, DeserializationError> {
+    match tag {
+        0 => Ok(ProofKind::Register),
+        1 => Ok(ProofKind::Cast),
+        2 => Ok(ProofKind::Tally),
+        _ => Err(invalid("Unknown proof kind.")),
+    }
+}
+
+fn invalid(message: &str) -> DeserializationError {
+    DeserializationError::InvalidValue(String::from(message))
+}
@@ -0,0 +1,70 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use winterfell::{
+    math::{fields::f63::BaseElement, FieldElement},
+    Serializable,
+};
+
+// CONSTANT-TIME COMPARISONS
+// ================================================================================================
+
+/// Returns a truthy `Choice` only when the two byte slices are equal.
+/// Slices of different lengths are never equal.
+#[inline]
+pub fn ct_eq_bytes(a: &[u8], b: &[u8]) -> Choice {
+    a.ct_eq(b)
+}
+
+/// Returns a truthy `Choice` only when the two sequences of field elements
+/// are equal. All elements are compared, regardless of where a mismatch occurs.
+pub fn ct_eq_elements(a: &[BaseElement], b: &[BaseElement]) -> Choice {
+    if a.len() != b.len() {
+        return Choice::from(0);
+    }
+    let mut result = Choice::from(1);
+    for (x, y) in a.iter().zip(b.iter()) {
+        result &= x.to_bytes().ct_eq(&y.to_bytes());
+    }
+    result
+}
+
+/// Returns a truthy `Choice` only when all field elements are zero.
+pub fn ct_is_zero(a: &[BaseElement]) -> Choice {
+    let mut result = Choice::from(1);
+    for x in a.iter() {
+        result &= x.to_bytes().ct_eq(&BaseElement::ZERO.to_bytes());
+    }
+    result
+}
+
+// CONSTANT-TIME SEARCH
+// ================================================================================================
+
+/// Returns the index of the first item for which `is_match` holds.
+/// Every item is visited, so the running time does not depend on
+/// the position of the match.
+pub fn ct_position<T, F>(items: &[T], is_match: F) -> Option<usize>
+where
+    F: Fn(&T) -> Choice,
+{
+    let mut found = Choice::from(0);
+    let mut position = 0u64;
+    for (i, item) in items.iter().enumerate() {
+        let first_match = is_match(item) & !found;
+        position.conditional_assign(&(i as u64), first_match);
+        found |= first_match;
+    }
+
+    if bool::from(found) {
+        Some(position as usize)
+    } else {
+        None
+    }
+}
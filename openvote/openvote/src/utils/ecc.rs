@@ -6,8 +6,15 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use super::{are_equal, is_binary, not, EvaluationResult};
-use winterfell::math::{fields::f63::BaseElement, FieldElement};
+use super::{are_equal, constant_time::ct_is_zero, is_binary, not, EvaluationResult};
+use crate::compat::curve::{AffinePoint, ProjectivePoint, Scalar};
+use winterfell::{
+    math::{fields::f63::BaseElement, FieldElement, StarkField},
+    ByteReader, ByteWriter, Deserializable, DeserializationError, SliceReader,
+};
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
 
 // CONSTANTS
 // ================================================================================================
@@ -18,6 +25,10 @@ pub const POINT_COORDINATE_WIDTH: usize = 6;
 pub const AFFINE_POINT_WIDTH: usize = POINT_COORDINATE_WIDTH * 2;
 /// The length of a ProjectivePoint
 pub const PROJECTIVE_POINT_WIDTH: usize = POINT_COORDINATE_WIDTH * 3;
+/// Number of bytes of a compressed point (x coordinate and sign of y)
+pub const BYTES_PER_COMPRESSED_POINT: usize = 48;
+/// Number of bytes of a serialized Scalar
+pub const BYTES_PER_SCALAR: usize = 32;
 
 /// Specifies the affine coordinates of the curve generator G
 pub const GENERATOR: [BaseElement; AFFINE_POINT_WIDTH] = [
@@ -405,6 +416,26 @@ pub(crate) fn enforce_point_addition_mixed_reduce_affine<E: FieldElement + From<
 
 // HELPER FUNCTIONS
 // ================================================================================================
+
+/// Convert a point in projective coordinates to the array of its affine coordinates [X, Y]
+#[inline]
+pub fn projective_to_elements(point: ProjectivePoint) -> [BaseElement; AFFINE_POINT_WIDTH] {
+    let point = AffinePoint::from(point);
+    let mut result = [BaseElement::ZERO; AFFINE_POINT_WIDTH];
+    result[..POINT_COORDINATE_WIDTH].copy_from_slice(&point.get_x());
+    result[POINT_COORDINATE_WIDTH..].copy_from_slice(&point.get_y());
+    result
+}
+
+/// Concatenate the affine coordinates of a sequence of points in projective coordinates
+pub fn projective_points_to_elements(points: &[ProjectivePoint]) -> Vec<BaseElement> {
+    let mut result = Vec::with_capacity(points.len() * AFFINE_POINT_WIDTH);
+    for &point in points.iter() {
+        result.extend_from_slice(&projective_to_elements(point));
+    }
+    result
+}
+
 /// Reduce a point in projective coordinates to affine coordinates, returned as [X, Y]
 #[inline(always)]
 pub(crate) fn reduce_to_affine(point: &[BaseElement]) -> [BaseElement; AFFINE_POINT_WIDTH] {
@@ -419,6 +450,52 @@ pub(crate) fn reduce_to_affine(point: &[BaseElement]) -> [BaseElement; AFFINE_PO
     result
 }
 
+/// Reduce points in projective coordinates to affine coordinates with a single
+/// field inversion, using Montgomery's batch inversion trick. Points at infinity
+/// are skipped in the inversion and reduced to the encoding of the identity.
+pub(crate) fn batch_reduce_to_affine(
+    points: &[[BaseElement; PROJECTIVE_POINT_WIDTH]],
+) -> Vec<[BaseElement; AFFINE_POINT_WIDTH]> {
+    let mut one = [BaseElement::ZERO; POINT_COORDINATE_WIDTH];
+    one[0] = BaseElement::ONE;
+    let is_identity = |point: &[BaseElement; PROJECTIVE_POINT_WIDTH]| {
+        point[AFFINE_POINT_WIDTH..PROJECTIVE_POINT_WIDTH]
+            .iter()
+            .all(|&x| x == BaseElement::ZERO)
+    };
+
+    // prefix products of the non-zero z coordinates
+    let mut products = Vec::with_capacity(points.len());
+    let mut product = one;
+    for point in points.iter() {
+        products.push(product);
+        if !is_identity(point) {
+            product = mul_fp6(&product, &point[AFFINE_POINT_WIDTH..PROJECTIVE_POINT_WIDTH]);
+        }
+    }
+
+    let identity = projective_to_elements(ProjectivePoint::identity());
+    let mut product_inv = invert_fp6(&product);
+    let mut result = vec![identity; points.len()];
+    for (i, point) in points.iter().enumerate().rev() {
+        if is_identity(point) {
+            continue;
+        }
+        let z_inv = mul_fp6(&product_inv, &products[i]);
+        product_inv = mul_fp6(
+            &product_inv,
+            &point[AFFINE_POINT_WIDTH..PROJECTIVE_POINT_WIDTH],
+        );
+        result[i][..POINT_COORDINATE_WIDTH]
+            .copy_from_slice(&mul_fp6(&point[..POINT_COORDINATE_WIDTH], &z_inv));
+        result[i][POINT_COORDINATE_WIDTH..AFFINE_POINT_WIDTH].copy_from_slice(&mul_fp6(
+            &point[POINT_COORDINATE_WIDTH..AFFINE_POINT_WIDTH],
+            &z_inv,
+        ));
+    }
+    result
+}
+
 /// Compute the negation of a point in affine coordinates, returned as [X, -Y]
 #[inline(always)]
 pub(crate) fn compute_negation_affine<E: FieldElement + From<BaseElement>>(
@@ -917,3 +994,176 @@ pub(crate) fn neg_fp6<E: FieldElement + From<BaseElement>>(a: &[E]) -> [E; POINT
         a[5].neg(),
     ]
 }
+
+// POINT VALIDATION
+// ================================================================================================
+
+/// Errors raised when an externally supplied point is not a valid point of the curve
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointValidationError {
+    /// A coordinate of the point is not a canonical field element
+    NonCanonicalElement,
+    /// The point is not on the curve
+    OffCurve,
+    /// The point has a component of small order, i.e. it is not in the
+    /// subgroup of prime order generated by G
+    SmallOrderComponent,
+}
+
+/// Check that the coordinates of an externally supplied point are canonical field
+/// elements, and that the point is on the curve and in the subgroup generated by G.
+/// Points with a small-order component are rejected rather than cleared, as clearing
+/// the cofactor would break the relation between a voting key and its secret key.
+pub fn validate_point(
+    point: &[BaseElement; AFFINE_POINT_WIDTH],
+) -> Result<AffinePoint, PointValidationError> {
+    if !point
+        .iter()
+        .all(|element| element.as_int() < BaseElement::MODULUS)
+    {
+        return Err(PointValidationError::NonCanonicalElement);
+    }
+    let point = AffinePoint::from_raw_coordinates(*point);
+    if !point.is_on_curve() {
+        return Err(PointValidationError::OffCurve);
+    }
+    if !is_in_prime_subgroup(ProjectivePoint::from(point)) {
+        return Err(PointValidationError::SmallOrderComponent);
+    }
+    Ok(point)
+}
+
+/// Return true if q * point is the identity, where q is the order of G.
+/// q * point is computed as (q - 1) * point + point.
+pub fn is_in_prime_subgroup(point: ProjectivePoint) -> bool {
+    (point * -Scalar::one() + point).is_identity()
+}
+
+// CONSTANT-TIME SCALAR MULTIPLICATION
+// ================================================================================================
+
+/// Multiply a point by a secret scalar with a Montgomery ladder. The same sequence of
+/// field operations is performed for all scalars, so this should be used instead of
+/// the multiplication of curve points whenever the scalar is secret.
+pub fn mul_constant_time(point: ProjectivePoint, scalar: Scalar) -> ProjectivePoint {
+    // the point is public, only the scalar needs to be protected
+    if point.is_identity() {
+        return ProjectivePoint::identity();
+    }
+    let mut r0 = IDENTITY;
+    let mut r1 = [BaseElement::ZERO; PROJECTIVE_POINT_WIDTH];
+    r1[..AFFINE_POINT_WIDTH].copy_from_slice(&projective_to_elements(point));
+    r1[AFFINE_POINT_WIDTH] = BaseElement::ONE;
+
+    // invariant: r1 = r0 + point
+    let scalar_bytes = scalar.to_bytes();
+    for i in (0..BYTES_PER_SCALAR * 8).rev() {
+        let bit = BaseElement::from((scalar_bytes[i / 8] >> (i % 8)) & 1);
+        conditional_swap(&mut r0, &mut r1, bit);
+        compute_add(&mut r1, &r0);
+        compute_double(&mut r0);
+        conditional_swap(&mut r0, &mut r1, bit);
+    }
+
+    if bool::from(ct_is_zero(&r0[AFFINE_POINT_WIDTH..])) {
+        return ProjectivePoint::identity();
+    }
+    ProjectivePoint::from(AffinePoint::from_raw_coordinates(reduce_to_affine(&r0)))
+}
+
+/// Multiply the curve generator by a secret scalar in constant time
+pub fn mul_generator_constant_time(scalar: Scalar) -> ProjectivePoint {
+    mul_constant_time(ProjectivePoint::generator(), scalar)
+}
+
+/// Swap a and b when bit is one, and leave them unchanged when bit is zero,
+/// without branching on bit
+#[inline(always)]
+fn conditional_swap(a: &mut [BaseElement], b: &mut [BaseElement], bit: BaseElement) {
+    for (x, y) in a.iter_mut().zip(b.iter_mut()) {
+        let diff = bit * (*x - *y);
+        *x -= diff;
+        *y += diff;
+    }
+}
+
+// SERIALIZATION
+// ================================================================================================
+
+/// Compress a point in affine coordinates into its x coordinate and the sign of y
+pub fn compress_point(point: &[BaseElement]) -> [u8; BYTES_PER_COMPRESSED_POINT] {
+    let mut coordinates = [BaseElement::ZERO; AFFINE_POINT_WIDTH];
+    coordinates.copy_from_slice(&point[..AFFINE_POINT_WIDTH]);
+    AffinePoint::from_raw_coordinates(coordinates).to_compressed()
+}
+
+/// Decompress a point into affine coordinates, recovering y from x and its sign.
+/// Returns None if the bytes do not encode a point of the curve.
+pub fn decompress_point(
+    bytes: &[u8; BYTES_PER_COMPRESSED_POINT],
+) -> Option<[BaseElement; AFFINE_POINT_WIDTH]> {
+    let point: Option<AffinePoint> = AffinePoint::from_compressed(bytes).into();
+    point.map(|point| {
+        let mut result = [BaseElement::ZERO; AFFINE_POINT_WIDTH];
+        result[..POINT_COORDINATE_WIDTH].copy_from_slice(&point.get_x());
+        result[POINT_COORDINATE_WIDTH..].copy_from_slice(&point.get_y());
+        result
+    })
+}
+
+/// Write a sequence of points in affine coordinates in compressed form
+pub(crate) fn write_compressed_points<W: ByteWriter>(points: &[BaseElement], target: &mut W) {
+    for point in points.chunks(AFFINE_POINT_WIDTH) {
+        target.write_u8_slice(&compress_point(point));
+    }
+}
+
+/// Read a point in compressed form and return its affine coordinates
+pub(crate) fn read_compressed_point<R: ByteReader>(
+    source: &mut R,
+) -> Result<[BaseElement; AFFINE_POINT_WIDTH], DeserializationError> {
+    let mut bytes = [0u8; BYTES_PER_COMPRESSED_POINT];
+    bytes.copy_from_slice(&source.read_u8_vec(BYTES_PER_COMPRESSED_POINT)?);
+    decompress_point(&bytes).ok_or_else(|| {
+        DeserializationError::InvalidValue(String::from("Invalid compressed curve point."))
+    })
+}
+
+/// Read num_points points in compressed form and return their affine coordinates
+pub(crate) fn read_compressed_points<R: ByteReader>(
+    source: &mut R,
+    num_points: usize,
+) -> Result<Vec<BaseElement>, DeserializationError> {
+    let mut result = Vec::with_capacity(num_points * AFFINE_POINT_WIDTH);
+    for _ in 0..num_points {
+        result.extend_from_slice(&read_compressed_point(source)?);
+    }
+    Ok(result)
+}
+
+/// Read a scalar, rejecting encodings that are not the canonical encoding of
+/// their value, so that signatures and CDS proofs cannot be made malleable
+pub(crate) fn read_canonical_scalar<R: ByteReader>(
+    source: &mut R,
+) -> Result<Scalar, DeserializationError> {
+    let bytes = source.read_u8_vec(BYTES_PER_SCALAR)?;
+    let scalar = Scalar::read_from(&mut SliceReader::new(&bytes))?;
+    if scalar.to_bytes()[..] != bytes[..] {
+        return Err(DeserializationError::InvalidValue(String::from(
+            "Non-canonical scalar encoding.",
+        )));
+    }
+    Ok(scalar)
+}
+
+/// Read num_scalars scalars, rejecting non-canonical encodings
+pub(crate) fn read_canonical_scalars<R: ByteReader>(
+    source: &mut R,
+    num_scalars: usize,
+) -> Result<Vec<Scalar>, DeserializationError> {
+    let mut result = Vec::with_capacity(num_scalars);
+    for _ in 0..num_scalars {
+        result.push(read_canonical_scalar(source)?);
+    }
+    Ok(result)
+}
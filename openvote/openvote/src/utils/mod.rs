@@ -11,18 +11,38 @@ use winterfell::math::FieldElement;
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 
+/// A Solidity ABI encoding utility module
+pub mod abi;
+/// An Ethereum address utility module
+pub mod address;
+/// A prover checkpointing utility module
+#[cfg(feature = "std")]
+pub mod checkpoint;
+/// A proof compression utility module
+pub mod compression;
+/// A constant-time comparison utility module
+pub mod constant_time;
 /// An elliptic curve group operation utility module
 pub mod ecc;
 /// A field operation utility module
 pub(crate) mod field;
+/// A voting key ordering utility module
+pub mod ordering;
 /// A periodic values utility module
 pub(crate) mod periodic_columns;
+/// A content-addressed proof store utility module
+#[cfg(feature = "std")]
+pub mod proof_store;
 /// The Rescue-Prime utility module
 // Public for benchmarking purposes
 pub mod rescue;
+/// A Fiat-Shamir transcript utility module
+pub mod transcript;
+/// A compact wire encoding utility module
+pub mod wire;
 
-// #[cfg(test)]
-// mod tests;
+#[cfg(test)]
+mod tests;
 
 // CONSTRAINT EVALUATION HELPERS
 // ================================================================================================
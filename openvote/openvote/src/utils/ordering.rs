@@ -0,0 +1,87 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// VOTING KEY ORDERING
+// ================================================================================================
+// Voting keys are listed by the public inputs of register and cast proofs in the order in which
+// voters registered, unless the election uses the canonical order, in which they are sorted by
+// the bytes of the Rescue digest of their affine coordinates. The canonical order only depends
+// on the set of registered keys, so that it can be recomputed and cross-checked on chain.
+
+use super::{ecc::AFFINE_POINT_WIDTH, rescue::Rescue63};
+use winterfell::{math::fields::f63::BaseElement, ByteWriter};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// Order of the voting keys in the public inputs of register and cast proofs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyOrder {
+    /// Voting keys are listed in the order in which voters registered
+    Submission,
+    /// Voting keys are sorted by the digest of their affine coordinates
+    Canonical,
+}
+
+impl Default for KeyOrder {
+    fn default() -> Self {
+        KeyOrder::Submission
+    }
+}
+
+impl KeyOrder {
+    /// Return true if the voting keys are listed in this order. Voting keys
+    /// are always listed in submission order.
+    pub fn is_ordered(&self, voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]]) -> bool {
+        match self {
+            KeyOrder::Submission => true,
+            KeyOrder::Canonical => voting_keys
+                .windows(2)
+                .all(|pair| key_hash(&pair[0]) < key_hash(&pair[1])),
+        }
+    }
+
+    /// Position at which a new voting key is inserted in a list of voting keys
+    /// already in this order
+    pub fn position(
+        &self,
+        voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]],
+        voting_key: &[BaseElement; AFFINE_POINT_WIDTH],
+    ) -> usize {
+        match self {
+            KeyOrder::Submission => voting_keys.len(),
+            KeyOrder::Canonical => {
+                let hash = key_hash(voting_key);
+                voting_keys.partition_point(|other| key_hash(other) < hash)
+            }
+        }
+    }
+
+    /// Permutation sorting the voting keys in this order, i.e. the index
+    /// of the voting key listed at each position
+    pub fn permutation(&self, voting_keys: &[[BaseElement; AFFINE_POINT_WIDTH]]) -> Vec<usize> {
+        let mut permutation = (0..voting_keys.len()).collect::<Vec<usize>>();
+        if *self == KeyOrder::Canonical {
+            permutation.sort_by_cached_key(|&i| key_hash(&voting_keys[i]));
+        }
+        permutation
+    }
+
+    /// Bind the order into public inputs. Nothing is written for the
+    /// submission order, so that the encoding of public inputs stays unchanged.
+    pub fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        if *self == KeyOrder::Canonical {
+            target.write_u8(1);
+        }
+    }
+}
+
+/// Sort key of a voting key in the canonical order
+pub fn key_hash(voting_key: &[BaseElement; AFFINE_POINT_WIDTH]) -> [u8; 112] {
+    Rescue63::digest(voting_key).to_bytes()
+}
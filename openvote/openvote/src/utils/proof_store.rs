@@ -0,0 +1,137 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// PROOF STORE
+// ================================================================================================
+// Serialized proofs are stored once under the Blake3 digest of their bytes, in the blobs
+// subdirectory. The index subdirectory maps the digest of the inputs of a prover to the digest
+// of the proof generated from them, so that a proof is reused after the process is restarted.
+// Identical proofs generated from different inputs share a single blob.
+
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+use winterfell::{
+    crypto::{hashers::Blake3_256, Digest, Hasher},
+    math::fields::f63::BaseElement,
+};
+
+/// Subdirectory holding serialized proofs, named after their digest
+pub const BLOBS_DIR: &str = "blobs";
+/// Subdirectory mapping digests of prover inputs to digests of proofs
+pub const INDEX_DIR: &str = "index";
+
+/// Size of the digests of proofs and of prover inputs
+pub const BYTES_PER_STORE_DIGEST: usize = 32;
+
+/// Blake3 digest of a serialized proof or of the inputs of a prover
+pub type StoreDigest = [u8; BYTES_PER_STORE_DIGEST];
+
+/// Content-addressed store of serialized proofs in a directory
+#[derive(Debug, Clone)]
+pub struct ProofStore {
+    dir: PathBuf,
+}
+
+impl ProofStore {
+    /// Open the store in the given directory, creating it if needed
+    pub fn open(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir.join(BLOBS_DIR))?;
+        fs::create_dir_all(dir.join(INDEX_DIR))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    /// Store a serialized proof, unless an identical proof is already
+    /// stored, and return its digest
+    pub fn put(&self, proof: &[u8]) -> io::Result<StoreDigest> {
+        let digest = store_digest(proof);
+        let path = self.blob_path(&digest);
+        if !path.exists() {
+            write_atomic(&path, proof)?;
+        }
+        Ok(digest)
+    }
+
+    /// Serialized proof with the given digest, if it is stored and not corrupted
+    pub fn get(&self, digest: &StoreDigest) -> io::Result<Option<Vec<u8>>> {
+        let proof = match fs::read(self.blob_path(digest)) {
+            Ok(proof) => proof,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        if store_digest(&proof) != *digest {
+            return Ok(None);
+        }
+        Ok(Some(proof))
+    }
+
+    /// Return true if a proof with the given digest is stored
+    pub fn contains(&self, digest: &StoreDigest) -> bool {
+        self.blob_path(digest).exists()
+    }
+
+    /// Store the proof generated from the prover inputs with the given digest
+    /// and return the digest of the proof, see inputs_key
+    pub fn insert(&self, inputs_key: &StoreDigest, proof: &[u8]) -> io::Result<StoreDigest> {
+        let digest = self.put(proof)?;
+        write_atomic(&self.index_path(inputs_key), &digest)?;
+        Ok(digest)
+    }
+
+    /// Serialized proof generated from the prover inputs with the given digest, if any
+    pub fn lookup(&self, inputs_key: &StoreDigest) -> io::Result<Option<Vec<u8>>> {
+        let digest = match fs::read(self.index_path(inputs_key)) {
+            Ok(digest) if digest.len() == BYTES_PER_STORE_DIGEST => {
+                let mut proof_digest = [0u8; BYTES_PER_STORE_DIGEST];
+                proof_digest.copy_from_slice(&digest);
+                proof_digest
+            }
+            Ok(_) => return Ok(None),
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        self.get(&digest)
+    }
+
+    fn blob_path(&self, digest: &StoreDigest) -> PathBuf {
+        self.dir.join(BLOBS_DIR).join(hex::encode(digest))
+    }
+
+    fn index_path(&self, inputs_key: &StoreDigest) -> PathBuf {
+        self.dir.join(INDEX_DIR).join(hex::encode(inputs_key))
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Blake3 digest of the given bytes
+pub fn store_digest(bytes: &[u8]) -> StoreDigest {
+    Blake3_256::<BaseElement>::hash(bytes).as_bytes()
+}
+
+/// Digest of the inputs of a prover, made of a domain tag
+/// and of the serialized inputs
+pub fn inputs_key(tag: &[u8], inputs: &[u8]) -> StoreDigest {
+    let mut bytes = Vec::with_capacity(1 + tag.len() + inputs.len());
+    bytes.push(tag.len() as u8);
+    bytes.extend_from_slice(tag);
+    bytes.extend_from_slice(inputs);
+    store_digest(&bytes)
+}
+
+/// Write a file atomically, so that an interruption never leaves a truncated file behind
+fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, bytes)?;
+    fs::rename(&tmp_path, path)
+}
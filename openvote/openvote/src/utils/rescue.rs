@@ -33,6 +33,9 @@ pub const DIGEST_SIZE: usize = 7;
 /// computed using algorithm 7 from <https://eprint.iacr.org/2020/1143.pdf>
 pub const NUM_HASH_ROUNDS: usize = 7;
 
+/// Number of bytes packed into each field element when digesting byte strings.
+pub const BYTES_PER_DIGEST_ELEMENT: usize = 7;
+
 /// Minimum cycle length required to describe Rescue permutation.
 pub const HASH_CYCLE_LENGTH: usize = 8;
 
@@ -323,6 +326,18 @@ pub fn get_round_constants() -> Vec<Vec<BaseElement>> {
 // HELPER FUNCTIONS
 // ================================================================================================
 
+/// Returns the digest of a byte string, prefixed with its length.
+pub fn digest_bytes(bytes: &[u8]) -> Hash {
+    let mut hasher = Rescue63::new();
+    hasher.update(&[BaseElement::from(bytes.len() as u64)]);
+    for chunk in bytes.chunks(BYTES_PER_DIGEST_ELEMENT) {
+        let mut element_bytes = [0u8; 8];
+        element_bytes[..chunk.len()].copy_from_slice(chunk);
+        hasher.update(&[BaseElement::from(u64::from_le_bytes(element_bytes))]);
+    }
+    hasher.finalize()
+}
+
 #[inline(always)]
 #[allow(clippy::needless_range_loop)]
 fn apply_sbox<E: FieldElement>(state: &mut [E]) {
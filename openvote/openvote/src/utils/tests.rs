@@ -0,0 +1,209 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::{
+    abi::{self, Token},
+    ecc::{
+        batch_reduce_to_affine, is_in_prime_subgroup, mul_constant_time, mul_fp6,
+        mul_generator_constant_time, projective_points_to_elements, projective_to_elements,
+        validate_point, PointValidationError, AFFINE_POINT_WIDTH, POINT_COORDINATE_WIDTH,
+        PROJECTIVE_POINT_WIDTH,
+    },
+    rescue::{self, Rescue63, RATE_WIDTH},
+    transcript::Transcript,
+};
+use crate::compat::curve::{AffinePoint, ProjectivePoint, Scalar};
+use rand_core::OsRng;
+use winterfell::{
+    crypto::Hasher,
+    math::{fields::f63::BaseElement, FieldElement},
+};
+
+#[test]
+fn ecc_test_constant_time_mul_matches_mul() {
+    let point = ProjectivePoint::generator() * Scalar::random(OsRng);
+    for _ in 0..8 {
+        let scalar = Scalar::random(OsRng);
+        assert_eq!(mul_constant_time(point, scalar), point * scalar);
+        assert_eq!(
+            mul_generator_constant_time(scalar),
+            ProjectivePoint::generator() * scalar
+        );
+    }
+}
+
+#[test]
+fn ecc_test_constant_time_mul_edge_cases() {
+    let point = ProjectivePoint::generator() * Scalar::random(OsRng);
+    assert_eq!(mul_constant_time(point, Scalar::one()), point);
+    assert_eq!(
+        mul_constant_time(point, -Scalar::one()),
+        ProjectivePoint::identity() - point
+    );
+    assert!(mul_constant_time(point, Scalar::zero()).is_identity());
+    assert!(mul_constant_time(ProjectivePoint::identity(), Scalar::random(OsRng)).is_identity());
+}
+
+#[test]
+fn ecc_test_projective_to_elements() {
+    let points: Vec<ProjectivePoint> = (0..3)
+        .map(|_| ProjectivePoint::generator() * Scalar::random(OsRng))
+        .collect();
+    for point in points.iter() {
+        let affine = AffinePoint::from(*point);
+        let elements = projective_to_elements(*point);
+        assert_eq!(elements[..AFFINE_POINT_WIDTH / 2], affine.get_x());
+        assert_eq!(elements[AFFINE_POINT_WIDTH / 2..], affine.get_y());
+    }
+
+    let concatenated = projective_points_to_elements(&points);
+    assert_eq!(concatenated.len(), points.len() * AFFINE_POINT_WIDTH);
+    for (chunk, point) in concatenated.chunks(AFFINE_POINT_WIDTH).zip(points.iter()) {
+        assert_eq!(chunk, projective_to_elements(*point));
+    }
+}
+
+#[test]
+fn ecc_test_batch_reduce_to_affine() {
+    let points: Vec<ProjectivePoint> = (0..3)
+        .map(|_| ProjectivePoint::generator() * Scalar::random(OsRng))
+        .collect();
+
+    // scale the affine coordinates by distinct z coordinates, and put
+    // a point at infinity in the middle of the batch
+    let mut projective = Vec::new();
+    for (i, point) in points.iter().enumerate() {
+        let affine = projective_to_elements(*point);
+        let mut z = [BaseElement::ZERO; POINT_COORDINATE_WIDTH];
+        z[0] = BaseElement::from(i as u64 + 2);
+        z[1] = BaseElement::from(i as u64 + 7);
+        let mut elements = [BaseElement::ZERO; PROJECTIVE_POINT_WIDTH];
+        elements[..POINT_COORDINATE_WIDTH]
+            .copy_from_slice(&mul_fp6(&affine[..POINT_COORDINATE_WIDTH], &z));
+        elements[POINT_COORDINATE_WIDTH..AFFINE_POINT_WIDTH]
+            .copy_from_slice(&mul_fp6(&affine[POINT_COORDINATE_WIDTH..], &z));
+        elements[AFFINE_POINT_WIDTH..].copy_from_slice(&z);
+        projective.push(elements);
+    }
+    let mut infinity = [BaseElement::ZERO; PROJECTIVE_POINT_WIDTH];
+    infinity[POINT_COORDINATE_WIDTH] = BaseElement::ONE;
+    projective.insert(1, infinity);
+
+    let reduced = batch_reduce_to_affine(&projective);
+    assert_eq!(reduced.len(), 4);
+    assert_eq!(reduced[0], projective_to_elements(points[0]));
+    assert_eq!(
+        reduced[1],
+        projective_to_elements(ProjectivePoint::identity())
+    );
+    assert_eq!(reduced[2], projective_to_elements(points[1]));
+    assert_eq!(reduced[3], projective_to_elements(points[2]));
+}
+
+#[test]
+fn ecc_test_validate_point() {
+    let point = ProjectivePoint::generator() * Scalar::random(OsRng);
+    let elements = projective_to_elements(point);
+    assert_eq!(validate_point(&elements), Ok(AffinePoint::from(point)));
+    assert!(is_in_prime_subgroup(ProjectivePoint::identity()));
+
+    let mut off_curve = elements;
+    off_curve[AFFINE_POINT_WIDTH - 1] += BaseElement::ONE;
+    assert_eq!(
+        validate_point(&off_curve),
+        Err(PointValidationError::OffCurve)
+    );
+
+    // points of small order, and points with a component of small order, are rejected
+    let small_order_point = crate::testing::small_order_point();
+    for &crafted in [small_order_point, point + small_order_point].iter() {
+        assert!(!is_in_prime_subgroup(crafted));
+        assert_eq!(
+            validate_point(&projective_to_elements(crafted)),
+            Err(PointValidationError::SmallOrderComponent)
+        );
+    }
+}
+
+#[test]
+fn transcript_test_chained_digest() {
+    let elements = (0..2 * RATE_WIDTH as u64)
+        .map(BaseElement::new)
+        .collect::<Vec<_>>();
+    let mut transcript = Transcript::new();
+    transcript.absorb_elements(&elements);
+
+    let block = &elements[RATE_WIDTH..];
+    let expected = Rescue63::merge(&[
+        Rescue63::digest(&elements[..RATE_WIDTH]),
+        rescue::Hash::new(
+            block[0], block[1], block[2], block[3], block[4], block[5], block[6],
+        ),
+    ]);
+    assert_eq!(transcript.digest(), expected);
+
+    // a partial last block is padded with zeros
+    let mut padded = Transcript::new();
+    padded.absorb_elements(&elements[..RATE_WIDTH + 3]);
+    let mut expected = padded.clone();
+    expected.pad_to_length(2 * RATE_WIDTH);
+    assert_eq!(padded.digest(), expected.digest());
+}
+
+#[test]
+fn transcript_test_absorb_bytes() {
+    let mut transcript = Transcript::new();
+    transcript.absorb_bytes(&[1, 2, 3, 4, 5, 6]);
+    assert_eq!(
+        transcript.message(),
+        &[
+            BaseElement::from(0x0102030405000000u64),
+            BaseElement::from(0x0600000000000000u64),
+        ]
+    );
+
+    let scalar = Scalar::random(OsRng);
+    let mut scalar_transcript = Transcript::new();
+    scalar_transcript.absorb_scalar(&scalar);
+    assert_eq!(scalar_transcript.message().len(), 7);
+    assert_ne!(
+        scalar_transcript.challenge_scalar(),
+        transcript.challenge_scalar()
+    );
+}
+
+#[test]
+fn abi_test_encode() {
+    let mut expected = vec![0u8; 32 * 4];
+    expected[31] = 1;
+    expected[63] = 0x40;
+    expected[95] = 2;
+    expected[96..98].copy_from_slice(&[1, 0]);
+    assert_eq!(
+        abi::encode(&[Token::Bool(true), Token::Bytes(&[1, 0])]).unwrap(),
+        expected
+    );
+
+    // data is padded to a multiple of the word size, and not beyond
+    for (len, padded) in [(0, 0), (1, 32), (31, 32), (32, 32), (33, 64), (64, 64)] {
+        let bytes = vec![1u8; len];
+        let tokens = [Token::Bool(false), Token::Bytes(&bytes)];
+        let encoding = abi::encode(&tokens).unwrap();
+        assert_eq!(abi::padded_len(len), Some(padded));
+        assert_eq!(encoding.len(), 32 * 3 + padded);
+        assert_eq!(abi::encoded_len(&tokens), Some(encoding.len()));
+        assert!(encoding[96 + len..].iter().all(|&b| b == 0));
+    }
+
+    let encoding = abi::encode(&[Token::Bool(true), Token::Uint(0x01020304)]).unwrap();
+    assert_eq!(encoding.len(), 64);
+    assert_eq!(encoding[60..64], [1, 2, 3, 4]);
+
+    // lengths overflowing usize are rejected
+    assert_eq!(abi::padded_len(usize::MAX), None);
+}
@@ -0,0 +1,124 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::ecc::AFFINE_POINT_WIDTH;
+use super::rescue::{self, Rescue63, RATE_WIDTH};
+use crate::compat::curve::Scalar;
+use bitvec::{order::Lsb0, view::AsBits};
+use winterfell::{
+    crypto::Hasher,
+    math::{fields::f63::BaseElement, FieldElement},
+};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Number of bytes packed into a single field element when absorbing bytes
+pub const BYTES_PER_PACKED_ELEMENT: usize = 5;
+
+/// Number of bytes of a challenge, taken from the first four elements of the digest
+pub const BYTES_PER_CHALLENGE: usize = 32;
+
+// TRANSCRIPT
+// ================================================================================================
+
+/// Fiat-Shamir transcript of a proof or signature.
+///
+/// The absorbed elements form the hash message laid out in the periodic columns of the AIRs.
+/// The challenge is derived from the digest of the first block of `RATE_WIDTH` elements, into
+/// which each following block is merged, as done by the Rescue registers of the traces.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transcript {
+    message: Vec<BaseElement>,
+}
+
+impl Transcript {
+    /// Create an empty transcript
+    pub fn new() -> Self {
+        Self {
+            message: Vec::new(),
+        }
+    }
+
+    /// Absorb a single field element
+    pub fn absorb_element(&mut self, element: BaseElement) {
+        self.message.push(element);
+    }
+
+    /// Absorb a sequence of field elements
+    pub fn absorb_elements(&mut self, elements: &[BaseElement]) {
+        self.message.extend_from_slice(elements);
+    }
+
+    /// Absorb the affine coordinates of a point
+    pub fn absorb_point(&mut self, point: &[BaseElement; AFFINE_POINT_WIDTH]) {
+        self.absorb_elements(point);
+    }
+
+    /// Absorb the canonical encoding of a scalar
+    pub fn absorb_scalar(&mut self, scalar: &Scalar) {
+        self.absorb_bytes(&scalar.to_bytes());
+    }
+
+    /// Absorb bytes, packed by chunks of `BYTES_PER_PACKED_ELEMENT` into the most
+    /// significant bytes of a u64, so that each chunk fits in a field element
+    pub fn absorb_bytes(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(BYTES_PER_PACKED_ELEMENT) {
+            let mut word = [0u8; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.absorb_element(BaseElement::from(u64::from_be_bytes(word)));
+        }
+    }
+
+    /// Pad the transcript with zeros up to the given number of elements
+    pub fn pad_to_length(&mut self, length: usize) {
+        debug_assert!(
+            self.message.len() <= length,
+            "Transcript is longer than the padded length."
+        );
+        self.message.resize(length, BaseElement::ZERO);
+    }
+
+    /// Elements absorbed so far
+    pub fn message(&self) -> &[BaseElement] {
+        &self.message
+    }
+
+    /// Digest of the transcript. The last block is padded with zeros.
+    pub fn digest(&self) -> rescue::Hash {
+        let mut blocks = self.message.chunks(RATE_WIDTH).map(|chunk| {
+            let mut block = [BaseElement::ZERO; RATE_WIDTH];
+            block[..chunk.len()].copy_from_slice(chunk);
+            block
+        });
+        let first_block = blocks.next().unwrap_or([BaseElement::ZERO; RATE_WIDTH]);
+        blocks.fold(Rescue63::digest(&first_block), |h, block| {
+            let block = rescue::Hash::new(
+                block[0], block[1], block[2], block[3], block[4], block[5], block[6],
+            );
+            Rescue63::merge(&[h, block])
+        })
+    }
+
+    /// Bytes of the challenge, i.e. of the first four elements of the digest
+    pub fn challenge_bytes(&self) -> [u8; BYTES_PER_CHALLENGE] {
+        let mut bytes = [0u8; BYTES_PER_CHALLENGE];
+        for (chunk, element) in bytes.chunks_mut(8).zip(self.digest().to_elements().iter()) {
+            chunk.copy_from_slice(&element.to_bytes());
+        }
+        bytes
+    }
+
+    /// Challenge scalar of the transcript
+    pub fn challenge_scalar(&self) -> Scalar {
+        Scalar::from_bits(self.challenge_bytes().as_bits::<Lsb0>())
+    }
+}
@@ -0,0 +1,188 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::rescue::{self, DIGEST_SIZE};
+use winterfell::{ByteReader, ByteWriter, Deserializable, DeserializationError, SliceReader};
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Tag written in place of the leading u32 count of legacy encodings. No legacy
+/// encoding starts with it, as it would describe 2^32 - 1 elements.
+pub const WIRE_TAG: u32 = u32::MAX;
+
+/// Current version of the compact wire format. Version 2 drops the Merkle
+/// branches and indices of registrations from VoterRegistar objects.
+pub const WIRE_VERSION: u8 = 2;
+
+/// Maximum number of bytes of a varint-encoded u64
+pub const MAX_VARINT_LENGTH: usize = 10;
+
+/// Marker of an optional trailing section of a serialized object. Trailing digests are
+/// written without marker, and the marker is not the encoding of a canonical field
+/// element, so that it cannot be mistaken for the start of a digest.
+pub const SECTION_MARKER: [u8; 8] = [0xff; 8];
+
+// WIRE FORMAT
+// ================================================================================================
+
+/// Encoding used for counts and indices of a serialized object
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WireFormat {
+    /// Fixed-width encoding: u32 counts and u64 indices, without header
+    Legacy,
+    /// Varint encoding of counts and indices, preceded by a versioned header
+    Compact,
+}
+
+/// Header of a serialized object
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WireHeader {
+    /// Legacy object, carrying the u32 count read in place of the header
+    Legacy(u32),
+    /// Compact object of the given version
+    Compact(u8),
+}
+
+impl WireHeader {
+    /// Encoding used by the object
+    pub fn format(&self) -> WireFormat {
+        match self {
+            WireHeader::Legacy(_) => WireFormat::Legacy,
+            WireHeader::Compact(_) => WireFormat::Compact,
+        }
+    }
+}
+
+impl WireFormat {
+    /// Read a count, written as u32 in legacy format
+    pub fn read_count<R: ByteReader>(&self, source: &mut R) -> Result<usize, DeserializationError> {
+        match self {
+            WireFormat::Legacy => Ok(source.read_u32()? as usize),
+            WireFormat::Compact => read_varint_usize(source),
+        }
+    }
+
+    /// Read an index, written as u64 in legacy format
+    pub fn read_index<R: ByteReader>(&self, source: &mut R) -> Result<usize, DeserializationError> {
+        match self {
+            WireFormat::Legacy => Ok(source.read_u64()? as usize),
+            WireFormat::Compact => read_varint_usize(source),
+        }
+    }
+
+    /// Read the first count of an object, which legacy objects store in place of the header
+    pub fn read_first_count<R: ByteReader>(
+        &self,
+        header: WireHeader,
+        source: &mut R,
+    ) -> Result<usize, DeserializationError> {
+        match header {
+            WireHeader::Legacy(count) => Ok(count as usize),
+            WireHeader::Compact(_) => self.read_count(source),
+        }
+    }
+}
+
+/// Write the header of the current compact wire format
+pub fn write_header<W: ByteWriter>(target: &mut W) {
+    target.write_u32(WIRE_TAG);
+    target.write_u8(WIRE_VERSION);
+}
+
+/// Read the header of a serialized object, falling back to the legacy
+/// format when the object does not start with the wire tag
+pub fn read_header<R: ByteReader>(source: &mut R) -> Result<WireHeader, DeserializationError> {
+    let tag = source.read_u32()?;
+    if tag != WIRE_TAG {
+        return Ok(WireHeader::Legacy(tag));
+    }
+    let version = source.read_u8()?;
+    if version == 0 || version > WIRE_VERSION {
+        return Err(DeserializationError::InvalidValue(String::from(
+            "Unsupported wire format version.",
+        )));
+    }
+    Ok(WireHeader::Compact(version))
+}
+
+// TRAILING SECTIONS
+// ================================================================================================
+
+/// Write the marker of a trailing section
+pub fn write_section_marker<W: ByteWriter>(target: &mut W) {
+    target.write_u8_slice(&SECTION_MARKER);
+}
+
+/// Read a trailing digest written without marker, or return None if the next
+/// bytes are a section marker, which is consumed
+pub fn read_digest_or_marker<R: ByteReader>(
+    source: &mut R,
+) -> Result<Option<rescue::Hash>, DeserializationError> {
+    let mut bytes = source.read_u8_vec(SECTION_MARKER.len())?;
+    if bytes[..] == SECTION_MARKER[..] {
+        return Ok(None);
+    }
+    bytes.extend_from_slice(&source.read_u8_vec(DIGEST_SIZE * 8 - SECTION_MARKER.len())?);
+    rescue::Hash::read_from(&mut SliceReader::new(&bytes)).map(Some)
+}
+
+// VARINT ENCODING
+// ================================================================================================
+
+/// Write value as an unsigned LEB128 varint: 7 bits per byte, least
+/// significant group first, with the MSB of each byte set if more bytes follow
+pub fn write_varint<W: ByteWriter>(target: &mut W, mut value: u64) {
+    while value >= 0x80 {
+        target.write_u8((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    target.write_u8(value as u8);
+}
+
+/// Read an unsigned LEB128 varint, rejecting overlong and overflowing encodings
+pub fn read_varint<R: ByteReader>(source: &mut R) -> Result<u64, DeserializationError> {
+    let mut value = 0u64;
+    for i in 0..MAX_VARINT_LENGTH {
+        let byte = source.read_u8()?;
+        let group = (byte & 0x7f) as u64;
+        if i == MAX_VARINT_LENGTH - 1 && group > 1 {
+            break;
+        }
+        value |= group << (7 * i);
+        if byte & 0x80 == 0 {
+            if byte == 0 && i > 0 {
+                break;
+            }
+            return Ok(value);
+        }
+    }
+    Err(DeserializationError::InvalidValue(String::from(
+        "Invalid varint encoding.",
+    )))
+}
+
+/// Read an unsigned LEB128 varint that must fit in a usize
+pub fn read_varint_usize<R: ByteReader>(source: &mut R) -> Result<usize, DeserializationError> {
+    let value = read_varint(source)?;
+    if value > usize::MAX as u64 {
+        return Err(DeserializationError::InvalidValue(String::from(
+            "Varint does not fit in usize.",
+        )));
+    }
+    Ok(value as usize)
+}
+
+/// Number of bytes of the varint encoding of value
+pub fn varint_len(value: u64) -> usize {
+    let bits = 64 - (value | 1).leading_zeros() as usize;
+    (bits + 6) / 7
+}
@@ -0,0 +1,145 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// BLOB ENCODING
+// ================================================================================================
+// Encoding of proofs for EIP-4844 blobs, which are much cheaper than calldata. The proof body is
+// carried by the blobs of the transaction, and a short header in calldata holds the Merkle root
+// of eligible voters, the length of the proof and a Blake3 commitment to it. Blobs are vectors
+// of 4096 elements of the BLS12-381 scalar field, so each 32-byte element carries 31 bytes of
+// the proof behind a zero byte, which keeps it below the field modulus.
+
+use super::{constants::BYTES_PER_DIGEST, verify_register_proof};
+use winterfell::{
+    crypto::{hashers::Blake3_256, Digest, Hasher},
+    math::fields::f63::BaseElement,
+    DeserializationError,
+};
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+/// Number of field elements in a blob
+pub const FIELD_ELEMENTS_PER_BLOB: usize = 4096;
+
+/// Size of a field element of a blob, in bytes
+pub const BYTES_PER_FIELD_ELEMENT: usize = 32;
+
+/// Size of a blob, in bytes
+pub const BYTES_PER_BLOB: usize = FIELD_ELEMENTS_PER_BLOB * BYTES_PER_FIELD_ELEMENT;
+
+/// Number of proof bytes carried by a field element of a blob
+pub const PAYLOAD_BYTES_PER_FIELD_ELEMENT: usize = BYTES_PER_FIELD_ELEMENT - 1;
+
+/// Maximum number of blobs per transaction
+pub const MAX_BLOBS_PER_TRANSACTION: usize = 6;
+
+/// Maximum size of a proof carried by blobs, in bytes
+pub const MAX_BLOB_PAYLOAD_SIZE: usize =
+    MAX_BLOBS_PER_TRANSACTION * FIELD_ELEMENTS_PER_BLOB * PAYLOAD_BYTES_PER_FIELD_ELEMENT;
+
+/// Size of the calldata header of a proof carried by blobs, in bytes:
+/// Merkle root, little-endian u32 length of the proof and Blake3 commitment
+pub const BYTES_PER_BLOB_HEADER: usize = BYTES_PER_DIGEST + 4 + 32;
+
+/// Encode a register proof into a calldata header and blobs, or return None
+/// if the proof does not fit in the blobs of a transaction
+pub fn encode_register_proof_blob(
+    elg_root_bytes: &[u8],
+    register_proof: &[u8],
+) -> Option<(Vec<u8>, Vec<u8>)> {
+    assert_eq!(elg_root_bytes.len(), BYTES_PER_DIGEST);
+    let blobs = encode_blobs(register_proof)?;
+    let mut header = Vec::with_capacity(BYTES_PER_BLOB_HEADER);
+    header.extend_from_slice(elg_root_bytes);
+    header.extend_from_slice(&(register_proof.len() as u32).to_le_bytes());
+    header.extend_from_slice(&commit_payload(register_proof));
+    Some((header, blobs))
+}
+
+/// Verify a register proof carried by blobs. header is the calldata header built by
+/// encode_register_proof_blob, and blob_bytes the concatenation of the blobs.
+pub fn verify_register_proof_blob(
+    header: &[u8],
+    blob_bytes: &[u8],
+) -> Result<bool, DeserializationError> {
+    if header.len() != BYTES_PER_BLOB_HEADER {
+        return Err(DeserializationError::InvalidValue(String::from(
+            "Invalid length of blob header.",
+        )));
+    }
+    let elg_root_bytes = &header[..BYTES_PER_DIGEST];
+    let mut proof_len = [0u8; 4];
+    proof_len.copy_from_slice(&header[BYTES_PER_DIGEST..BYTES_PER_DIGEST + 4]);
+    let register_proof = decode_blobs(blob_bytes, u32::from_le_bytes(proof_len) as usize)?;
+    if commit_payload(&register_proof)[..] != header[BYTES_PER_DIGEST + 4..] {
+        return Err(DeserializationError::InvalidValue(String::from(
+            "Blobs do not match the commitment of the header.",
+        )));
+    }
+    verify_register_proof(elg_root_bytes, &register_proof)
+}
+
+/// Encode a payload into the minimum number of blobs, or return None if it
+/// does not fit in the blobs of a transaction
+pub fn encode_blobs(payload: &[u8]) -> Option<Vec<u8>> {
+    if payload.len() > MAX_BLOB_PAYLOAD_SIZE {
+        return None;
+    }
+    let num_elements =
+        (payload.len() + PAYLOAD_BYTES_PER_FIELD_ELEMENT - 1) / PAYLOAD_BYTES_PER_FIELD_ELEMENT;
+    let num_blobs = core::cmp::max(
+        1,
+        (num_elements + FIELD_ELEMENTS_PER_BLOB - 1) / FIELD_ELEMENTS_PER_BLOB,
+    );
+    let mut blobs = Vec::with_capacity(num_blobs * BYTES_PER_BLOB);
+    for chunk in payload.chunks(PAYLOAD_BYTES_PER_FIELD_ELEMENT) {
+        blobs.push(0);
+        blobs.extend_from_slice(chunk);
+    }
+    blobs.resize(num_blobs * BYTES_PER_BLOB, 0);
+    Some(blobs)
+}
+
+/// Decode a payload of the given length from blobs, checking that the blobs
+/// are canonical, i.e. that all other bytes are zero
+pub fn decode_blobs(
+    blob_bytes: &[u8],
+    payload_len: usize,
+) -> Result<Vec<u8>, DeserializationError> {
+    let num_blobs = blob_bytes.len() / BYTES_PER_BLOB;
+    if blob_bytes.len() % BYTES_PER_BLOB != 0
+        || num_blobs == 0
+        || num_blobs > MAX_BLOBS_PER_TRANSACTION
+        || payload_len > num_blobs * FIELD_ELEMENTS_PER_BLOB * PAYLOAD_BYTES_PER_FIELD_ELEMENT
+    {
+        return Err(DeserializationError::InvalidValue(String::from(
+            "Invalid number of blobs.",
+        )));
+    }
+    let mut payload = Vec::with_capacity(payload_len);
+    for element in blob_bytes.chunks(BYTES_PER_FIELD_ELEMENT) {
+        let num_bytes =
+            core::cmp::min(payload_len - payload.len(), PAYLOAD_BYTES_PER_FIELD_ELEMENT);
+        if element[0] != 0 || element[1 + num_bytes..].iter().any(|&b| b != 0) {
+            return Err(DeserializationError::InvalidValue(String::from(
+                "Non-canonical blob encoding.",
+            )));
+        }
+        payload.extend_from_slice(&element[1..1 + num_bytes]);
+    }
+    Ok(payload)
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Blake3 commitment to the payload of blobs
+fn commit_payload(payload: &[u8]) -> [u8; 32] {
+    Blake3_256::<BaseElement>::hash(payload).as_bytes()
+}
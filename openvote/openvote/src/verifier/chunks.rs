@@ -0,0 +1,155 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// CHUNKED PROOF SUBMISSION
+// ================================================================================================
+// STARK proofs may exceed the calldata limit of a transaction, in which case they are submitted
+// in chunks over several transactions. Chunks are chained by their Blake3 digest, starting from
+// the zero digest, and a proof is keyed by the chain digest of all its chunks. It is complete
+// when the chain digest of the chunks received so far reaches its key, and only then verified.
+
+use super::{verify_cast_proof as verify_cast, verify_register_proof as verify_register};
+use winterfell::{
+    crypto::{hashers::Blake3_256, Digest, Hasher},
+    math::fields::f63::BaseElement,
+    DeserializationError,
+};
+
+#[cfg(not(feature = "std"))]
+use alloc::{collections::BTreeMap, vec::Vec};
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+/// Digest of a chain of chunks
+pub type ChunkDigest = [u8; 32];
+
+/// Chain digest of an empty sequence of chunks
+pub const EMPTY_CHUNK_DIGEST: ChunkDigest = [0u8; 32];
+
+/// Maximum size of an assembled proof, in bytes
+pub const MAX_ASSEMBLED_SIZE: usize = 1 << 24;
+
+// ERRORS
+// ================================================================================================
+
+/// Errors raised when assembling a proof from chunks
+#[derive(Debug, PartialEq)]
+pub enum ChunkError {
+    /// This error occurs when no chunk was received for a proof
+    UnknownProof,
+    /// This error occurs when the chunks received for a proof do not chain up to its key yet
+    Incomplete,
+    /// This error occurs when a chunk is received for a proof that is already complete
+    AlreadyComplete,
+    /// This error occurs when the assembled proof would exceed MAX_ASSEMBLED_SIZE
+    TooLarge,
+    /// This error occurs when the assembled proof cannot be deserialized
+    Deserialization(DeserializationError),
+}
+
+// CHUNK ASSEMBLER
+// ================================================================================================
+
+/// Chunks received so far for a proof
+#[derive(Debug, Clone)]
+struct PendingProof {
+    digest: ChunkDigest,
+    bytes: Vec<u8>,
+}
+
+/// Store of proof fragments, keyed by the chain digest of their chunks
+#[derive(Debug, Clone, Default)]
+pub struct ChunkAssembler {
+    pending: BTreeMap<ChunkDigest, PendingProof>,
+}
+
+impl ChunkAssembler {
+    /// Create an assembler without pending proofs
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of proofs with chunks received so far
+    pub fn num_pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Append the next chunk of the proof with the given key, and return true if
+    /// the proof is complete
+    pub fn append(&mut self, key: ChunkDigest, chunk: &[u8]) -> Result<bool, ChunkError> {
+        let proof = self.pending.entry(key).or_insert(PendingProof {
+            digest: EMPTY_CHUNK_DIGEST,
+            bytes: Vec::new(),
+        });
+        if proof.digest == key {
+            return Err(ChunkError::AlreadyComplete);
+        }
+        if proof.bytes.len() + chunk.len() > MAX_ASSEMBLED_SIZE {
+            return Err(ChunkError::TooLarge);
+        }
+        proof.digest = chain_digest(&proof.digest, chunk);
+        proof.bytes.extend_from_slice(chunk);
+        Ok(proof.digest == key)
+    }
+
+    /// Remove a complete proof from the assembler and return its bytes
+    pub fn take(&mut self, key: &ChunkDigest) -> Result<Vec<u8>, ChunkError> {
+        match self.pending.get(key) {
+            None => Err(ChunkError::UnknownProof),
+            Some(proof) if proof.digest != *key => Err(ChunkError::Incomplete),
+            Some(_) => Ok(self.pending.remove(key).unwrap().bytes),
+        }
+    }
+
+    /// Drop the chunks received for a proof, e.g. after a submission was aborted
+    pub fn discard(&mut self, key: &ChunkDigest) -> bool {
+        self.pending.remove(key).is_some()
+    }
+
+    /// Verify a complete register proof, see verifier::verify_register_proof
+    pub fn verify_register_proof(
+        &mut self,
+        key: &ChunkDigest,
+        elg_root_bytes: &[u8],
+    ) -> Result<bool, ChunkError> {
+        let register_proof = self.take(key)?;
+        verify_register(elg_root_bytes, &register_proof).map_err(ChunkError::Deserialization)
+    }
+
+    /// Verify a complete cast proof, see verifier::verify_cast_proof
+    pub fn verify_cast_proof(
+        &mut self,
+        key: &ChunkDigest,
+        voting_keys: &[u8],
+    ) -> Result<bool, ChunkError> {
+        let cast_proof = self.take(key)?;
+        verify_cast(voting_keys, &cast_proof).map_err(ChunkError::Deserialization)
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Chain digest of the chunks of a proof following the given chain digest
+pub fn chain_digest(previous: &ChunkDigest, chunk: &[u8]) -> ChunkDigest {
+    let mut bytes = Vec::with_capacity(previous.len() + chunk.len());
+    bytes.extend_from_slice(previous);
+    bytes.extend_from_slice(chunk);
+    Blake3_256::<BaseElement>::hash(&bytes).as_bytes()
+}
+
+/// Split a proof into chunks of at most chunk_size bytes, and return
+/// the chunks along with the key of the proof
+pub fn split_proof(proof: &[u8], chunk_size: usize) -> (ChunkDigest, Vec<&[u8]>) {
+    assert!(chunk_size > 0, "Chunks must not be empty.");
+    let chunks = proof.chunks(chunk_size).collect::<Vec<&[u8]>>();
+    let key = chunks.iter().fold(EMPTY_CHUNK_DIGEST, |digest, chunk| {
+        chain_digest(&digest, chunk)
+    });
+    (key, chunks)
+}
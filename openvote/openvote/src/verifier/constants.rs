@@ -12,9 +12,6 @@ pub const BYTES_PER_AFFINE: usize = AFFINE_POINT_WIDTH * BYTES_PER_ELEMENT;
 /// Number of bytes of an Ethereum address
 pub const BYTES_PER_ADDRESS: usize = 20;
 
-/// Number of bytes of a serialized Scalar
-pub const BYTES_PER_SCALAR: usize = 32;
-
 /// Number of bytes of a serialized Schnorr signature
 pub const BYTES_PER_SIGNATURE: usize =
     POINT_COORDINATE_WIDTH * BYTES_PER_ELEMENT + BYTES_PER_SCALAR;
@@ -22,5 +19,6 @@ pub const BYTES_PER_SIGNATURE: usize =
 /// Number of bytes of a Rescue digest
 pub const BYTES_PER_DIGEST: usize = DIGEST_SIZE * BYTES_PER_ELEMENT;
 
-/// Number of bytes of a CDS output
-pub const BYTES_PER_OUTPUT: usize = BYTES_PER_AFFINE * 5;
+/// Number of bytes of the compact public inputs of a single cast
+/// (compressed encrypted vote and CDS proof points)
+pub const BYTES_PER_CAST_PUB_INPUTS: usize = (1 + PROOF_NUM_POINTS) * BYTES_PER_COMPRESSED_POINT;
@@ -0,0 +1,194 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// VERIFICATION COST ESTIMATES
+// ================================================================================================
+// Estimates the work of verifying a register proof, a cast proof or a tally result from the
+// headers of its serialized form, without performing the verification, so that contracts and
+// front ends can check beforehand that it fits in a gas budget. Hash counts are upper bounds,
+// as the verifier batches the Merkle authentication paths of queries.
+
+use super::constants::*;
+use crate::{
+    cds::{read_validity_bitmap, validity_bitmap_size},
+    schnorr::PublicInputs as SchnorrPublicInputs,
+    utils::compression::decompress_proof,
+};
+use winterfell::{ByteReader, DeserializationError, SliceReader, StarkProof};
+
+/// Gas charged for a base cost of any verification
+pub const GAS_BASE: u64 = 3_000;
+
+/// Gas charged per hash function invocation
+pub const GAS_PER_HASH: u64 = 60;
+
+/// Gas charged per point decompression or point addition
+pub const GAS_PER_POINT_OPERATION: u64 = 150;
+
+/// Gas charged per scalar multiplication
+pub const GAS_PER_SCALAR_MUL: u64 = 6_000;
+
+/// Number of scalar multiplications of the verification of a CDS proof
+const SCALAR_MULS_PER_CDS_PROOF: usize = 2 * PROOF_NUM_POINTS;
+
+/// Number of scalar multiplications to verify the signature of a ballot by its voter
+const SCALAR_MULS_PER_SIGNATURE: usize = 2;
+
+/// Verification whose cost is estimated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofKind {
+    /// Register proof, as verified by verify_register_proof
+    Register,
+    /// Cast proof, as verified by verify_cast_proof
+    Cast,
+    /// Encrypted votes, as verified by verify_tally_result
+    Tally,
+}
+
+/// Estimated work of a verification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct VerificationEstimate {
+    /// Number of FRI queries of the STARK proof, 0 if there is none
+    pub num_queries: usize,
+    /// Number of hash function invocations
+    pub num_hashes: usize,
+    /// Number of point decompressions and point additions
+    pub num_point_operations: usize,
+    /// Number of scalar multiplications
+    pub num_scalar_muls: usize,
+}
+
+impl VerificationEstimate {
+    /// Estimated gas charged for the verification
+    pub fn gas(&self) -> u64 {
+        GAS_BASE
+            + self.num_hashes as u64 * GAS_PER_HASH
+            + self.num_point_operations as u64 * GAS_PER_POINT_OPERATION
+            + self.num_scalar_muls as u64 * GAS_PER_SCALAR_MUL
+    }
+}
+
+/// Estimate the work of verifying a register proof, a cast proof or serialized encrypted
+/// votes, in the format passed to the corresponding verification function. Compressed
+/// proofs are estimated after decompression.
+pub fn estimate(
+    kind: ProofKind,
+    bytes: &[u8],
+) -> Result<VerificationEstimate, DeserializationError> {
+    let bytes = decompress_proof(bytes)?;
+    match kind {
+        ProofKind::Register => estimate_register_proof(&bytes),
+        ProofKind::Cast => estimate_cast_proof(&bytes),
+        ProofKind::Tally => estimate_tally_result(&bytes),
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn estimate_register_proof(
+    register_proof: &[u8],
+) -> Result<VerificationEstimate, DeserializationError> {
+    let num_regs = SliceReader::new(register_proof).read_u32()? as usize;
+    let bound = SchnorrPublicInputs::serialized_len(num_regs);
+    if register_proof.len() < bound {
+        return Err(DeserializationError::UnexpectedEOF);
+    }
+
+    let mut estimate = estimate_stark_proof(&register_proof[bound..])?;
+    estimate.num_point_operations += num_regs;
+    Ok(estimate)
+}
+
+fn estimate_cast_proof(cast_proof: &[u8]) -> Result<VerificationEstimate, DeserializationError> {
+    let num_proofs = SliceReader::new(cast_proof).read_u32()? as usize;
+    let mut bound = skip(cast_proof, 4, num_proofs, BYTES_PER_CAST_PUB_INPUTS)?;
+
+    // CDS proofs of ballots flagged as invalid are verified outside of the STARK proof
+    let num_excluded =
+        read_validity_bitmap(&mut SliceReader::new(&cast_proof[bound..]), num_proofs)?
+            .iter()
+            .filter(|&&is_valid| !is_valid)
+            .count();
+    bound = skip(
+        cast_proof,
+        bound + validity_bitmap_size(num_proofs),
+        num_excluded,
+        PROOF_NUM_SCALARS * BYTES_PER_SCALAR + BYTES_PER_SIGNATURE,
+    )?;
+    let num_commitments = SliceReader::new(&cast_proof[bound..]).read_u32()? as usize;
+    bound = skip(cast_proof, bound + 4, num_commitments, BYTES_PER_COMMITMENT)?;
+
+    let mut estimate = estimate_stark_proof(&cast_proof[bound..])?;
+    estimate.num_point_operations += num_proofs * (1 + PROOF_NUM_POINTS);
+    if num_excluded > 0 {
+        // blinding keys are computed for all voters
+        estimate.num_point_operations += 2 * num_proofs;
+        // each excluded ballot is hashed for its CDS challenge and its signature
+        estimate.num_hashes += 2 * num_excluded;
+        estimate.num_scalar_muls +=
+            num_excluded * (SCALAR_MULS_PER_CDS_PROOF + SCALAR_MULS_PER_SIGNATURE);
+    }
+    estimate.num_hashes += num_commitments;
+    Ok(estimate)
+}
+
+fn estimate_tally_result(
+    encrypted_votes: &[u8],
+) -> Result<VerificationEstimate, DeserializationError> {
+    let num_votes = SliceReader::new(encrypted_votes).read_u32()? as usize;
+    Ok(VerificationEstimate {
+        num_queries: 0,
+        num_hashes: 0,
+        num_point_operations: 2 * num_votes,
+        num_scalar_muls: 1,
+    })
+}
+
+/// Estimate the work of verifying a STARK proof from its options and domain size
+fn estimate_stark_proof(proof_bytes: &[u8]) -> Result<VerificationEstimate, DeserializationError> {
+    let proof = StarkProof::from_bytes(proof_bytes)?;
+    let options = proof.options();
+    let num_queries = options.num_queries();
+    let lde_domain_size = proof.lde_domain_size();
+    let fri_options = options.to_fri_options();
+    let folding_factor = fri_options.folding_factor();
+
+    // each query opens the trace and constraint commitments, then each FRI layer
+    let mut hashes_per_query = 2 * (1 + log2(lde_domain_size));
+    let mut domain_size = lde_domain_size;
+    for _ in 0..fri_options.num_fri_layers(lde_domain_size) {
+        domain_size /= folding_factor;
+        hashes_per_query += 1 + log2(domain_size);
+    }
+
+    Ok(VerificationEstimate {
+        num_queries,
+        num_hashes: num_queries * hashes_per_query,
+        num_point_operations: 0,
+        num_scalar_muls: 0,
+    })
+}
+
+/// Offset following count items of item_len bytes starting at offset start of source.
+/// The check is performed without multiplying count, which is untrusted.
+fn skip(
+    source: &[u8],
+    start: usize,
+    count: usize,
+    item_len: usize,
+) -> Result<usize, DeserializationError> {
+    if source.len() < start || (source.len() - start) / item_len < count {
+        return Err(DeserializationError::UnexpectedEOF);
+    }
+    Ok(start + count * item_len)
+}
+
+fn log2(n: usize) -> usize {
+    n.trailing_zeros() as usize
+}
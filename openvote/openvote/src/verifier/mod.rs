@@ -1,102 +1,1030 @@
 use self::constants::*;
 use crate::{
-    cds::{CDSAir, PublicInputs as CDSPublicInputs},
-    merkle::{MerkleAir, PublicInputs as MerklePublicInputs},
-    schnorr::{PublicInputs as SchnorrPublicInputs, SchnorrAir},
+    cds::{
+        compute_blinding_key, compute_blinding_keys, compute_vote_commitment, election_context,
+        read_ballot_signature, read_validity_bitmap, signed_ballot_digest, validity_bitmap_size,
+        verify_cds_proof_bound, CDSAir, PublicInputs as CDSPublicInputs, ShardInfo,
+    },
+    chain::{ballot_commitment, read_phase_commitment, register_commitment, PhaseCommitment},
+    compat::curve::{AffinePoint, ProjectivePoint, Scalar},
+    compat::verify,
+    merkle::MerkleTree,
+    register::{PublicInputs as RegisterPublicInputs, RegisterAir},
+    schnorr::{
+        verify_digest_signature, PublicInputs as SchnorrPublicInputs, SchnorrSequenceAir,
+        SequencePublicInputs, SigningDomain,
+    },
+    tally::{PublicInputs as TallyPublicInputs, TallyAir},
+    utils::{
+        compression::decompress_proof,
+        ecc::{compress_point, read_canonical_scalars, read_compressed_point},
+        ordering::KeyOrder,
+        wire::{self, WireFormat},
+    },
 };
 use winterfell::{
-    math::{
-        curves::curve_f63::{AffinePoint, ProjectivePoint, Scalar},
-        fields::f63::BaseElement,
-        FieldElement,
-    },
-    verify, Deserializable, DeserializationError, SliceReader, StarkProof,
+    math::{fields::f63::BaseElement, FieldElement},
+    Air, ByteReader, ByteWriter, DeserializationError, SliceReader, StarkProof,
 };
 
+#[cfg(feature = "accumulator")]
+use self::options::AccumulatorMembership;
+#[cfg(feature = "accumulator")]
+use crate::eligibility::accumulator::verify_membership;
+
 /// constants for verifier
 pub mod constants;
+/// Encoding of proofs for EIP-4844 blobs
+pub mod blob;
+pub use self::blob::verify_register_proof_blob;
+/// Assembly of proofs submitted in chunks
+pub mod chunks;
+pub use self::chunks::{ChunkAssembler, ChunkError};
+/// Policies on the field extension and FRI parameters of STARK proofs
+pub mod policy;
+use self::policy::{extension_degree, ExtensionPolicy};
+use crate::planner::FriConfig;
+/// Estimates of the cost of verifications
+pub mod cost;
+/// Options of the verifications of register and cast proofs
+pub mod options;
+pub use self::options::{CastVerification, RegisterVerification};
+/// Structured outcomes of verifications
+pub mod report;
+pub use self::cost::{estimate, ProofKind, VerificationEstimate};
+use self::report::{CastReport, ElectionReport, ProofError, RegisterReport, TallyReport};
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(test)]
+mod security_tests;
 
 /// Verify register proof submitted by off-chain aggregator in voter registration phase
-/// elg_root_bytes should be stored on smart contract
+/// elg_root_bytes should be stored on smart contract, and must be exactly
+/// BYTES_PER_DIGEST bytes long
 pub fn verify_register_proof(
     elg_root_bytes: &[u8],
     register_proof: &[u8],
 ) -> Result<bool, DeserializationError> {
-    // Deserialize Merkle public inputs
-    let mut tmp = [0u8; 4];
-    tmp.copy_from_slice(&register_proof[..4]);
-    let num_regs = u32::from_le_bytes(tmp) as usize;
-    let mut bound = 4 + BYTES_PER_AFFINE * num_regs;
-    let merkle_pub_inputs_bytes = [&elg_root_bytes, &register_proof[..bound]].concat();
-    let merkle_pub_inputs = MerklePublicInputs::from_bytes(&merkle_pub_inputs_bytes)?;
-    // Deserialize Schnorr public inputs
-    bound += (BYTES_PER_ADDRESS + BYTES_PER_SIGNATURE) * num_regs;
-    let schnorr_pub_inputs = SchnorrPublicInputs::from_bytes(&register_proof[..bound])?;
-    // Deserialize proofs
-    tmp.copy_from_slice(&register_proof[bound..bound + 4]);
-    let merkle_proof_nbytes = u32::from_le_bytes(tmp) as usize;
-    bound += 4;
-    let merkle_proof = StarkProof::from_bytes(&register_proof[bound..bound + merkle_proof_nbytes])?;
-    let schnorr_proof = StarkProof::from_bytes(&register_proof[bound + merkle_proof_nbytes..])?;
-
-    // Verify STARK proofs
-    Ok(verify::<MerkleAir>(merkle_proof, merkle_pub_inputs).is_ok()
-        && verify::<SchnorrAir>(schnorr_proof, schnorr_pub_inputs).is_ok())
+    let elg_root = read_elg_root(elg_root_bytes)?;
+    verify_register_proof_with(&elg_root, &RegisterVerification::new(), register_proof)
+        .into_outcome()
+}
+
+/// Verify register proof against an eligibility root already deserialized, e.g. with
+/// read_elg_root when the root is stored on smart contract, and the given options.
+/// Report whether its public inputs or its STARK proof were rejected.
+pub fn verify_register_proof_with(
+    elg_root: &[BaseElement; DIGEST_SIZE],
+    options: &RegisterVerification,
+    register_proof: &[u8],
+) -> RegisterReport {
+    // Decompress the proof if it was compressed, and deserialize
+    // public inputs of the combined registration AIR
+    let register_proof = match decompress_proof(register_proof) {
+        Ok(register_proof) => register_proof,
+        Err(error) => {
+            return RegisterReport {
+                public_inputs: Err(error),
+                stark_proof: None,
+            }
+        }
+    };
+    let mut register_pub_inputs = match read_register_pub_inputs(*elg_root, &register_proof) {
+        Ok(register_pub_inputs) => register_pub_inputs,
+        Err(error) => {
+            return RegisterReport {
+                public_inputs: Err(error),
+                stark_proof: None,
+            }
+        }
+    };
+    if !options
+        .key_order
+        .is_ordered(&register_pub_inputs.voting_keys)
+    {
+        return RegisterReport {
+            public_inputs: Err(unordered_keys()),
+            stark_proof: None,
+        };
+    }
+    register_pub_inputs.manifest_digest = options.manifest_digest;
+    register_pub_inputs.domain = options.domain;
+    register_pub_inputs.key_order = options.key_order;
+    let num_regs = register_pub_inputs.signatures.len();
+    if !options.leaf_indices.is_empty() && options.leaf_indices.len() != num_regs {
+        return RegisterReport {
+            public_inputs: Err(DeserializationError::InvalidValue(String::from(
+                "Number of leaf indices does not match number of registrations.",
+            ))),
+            stark_proof: None,
+        };
+    }
+    if !options.groups.is_empty() && options.groups.len() != num_regs {
+        return RegisterReport {
+            public_inputs: Err(DeserializationError::InvalidValue(String::from(
+                "Number of groups does not match number of registrations.",
+            ))),
+            stark_proof: None,
+        };
+    }
+    #[cfg(feature = "accumulator")]
+    if let Some(accumulator) = options.accumulator.as_ref() {
+        if let Err(error) = verify_accumulator_membership(accumulator, &register_pub_inputs) {
+            return RegisterReport {
+                public_inputs: Err(error),
+                stark_proof: None,
+            };
+        }
+    }
+    register_pub_inputs.leaf_indices = options.leaf_indices.clone();
+    register_pub_inputs.groups = options.groups.clone();
+    let bound = SchnorrPublicInputs::serialized_len(num_regs);
+
+    // Verify STARK proof
+    RegisterReport {
+        public_inputs: Ok(()),
+        stark_proof: Some(if is_provable_count(num_regs) {
+            verify_stark_proof::<RegisterAir>(
+                &register_proof[bound..],
+                register_pub_inputs,
+                options.policy,
+            )
+        } else {
+            Err(ProofError::Count(num_regs))
+        }),
+    }
+}
+
+/// Check that each voting key listed by a register proof is a member of the accumulator
+#[cfg(feature = "accumulator")]
+fn verify_accumulator_membership(
+    accumulator: &AccumulatorMembership,
+    register_pub_inputs: &RegisterPublicInputs,
+) -> Result<(), DeserializationError> {
+    if accumulator.witnesses.len() != register_pub_inputs.voting_keys.len() {
+        return Err(DeserializationError::InvalidValue(String::from(
+            "Number of accumulator witnesses does not match number of registrations.",
+        )));
+    }
+    for (voting_key, witness) in register_pub_inputs
+        .voting_keys
+        .iter()
+        .zip(accumulator.witnesses.iter())
+    {
+        if !verify_membership(
+            &accumulator.modulus,
+            &accumulator.value,
+            voting_key,
+            witness,
+        ) {
+            return Err(DeserializationError::InvalidValue(String::from(
+                "Voting key is not a member of the accumulator.",
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Deserialize an eligibility root as stored on smart contract, rejecting
+/// any encoding which is not exactly BYTES_PER_DIGEST bytes long
+pub fn read_elg_root(
+    elg_root_bytes: &[u8],
+) -> Result<[BaseElement; DIGEST_SIZE], DeserializationError> {
+    let mut source = SliceReader::new(elg_root_bytes);
+    let mut elg_root = [BaseElement::ZERO; DIGEST_SIZE];
+    elg_root.copy_from_slice(&BaseElement::read_batch_from(&mut source, DIGEST_SIZE)?);
+    if source.has_more_bytes() {
+        return Err(DeserializationError::UnconsumedBytes);
+    }
+    Ok(elg_root)
+}
+
+/// Verify a proof of the Schnorr signatures of registrations whose public inputs
+/// are reduced to the sequence hash of the (voting key, address, signature) tuples.
+/// sequence_digest should be stored on smart contract, which absorbs each registration
+/// when it is submitted. First 4 bytes of the proof are the little-endian number of signatures.
+pub fn verify_signature_sequence_proof(
+    sequence_digest: &[u8],
+    sequence_proof: &[u8],
+) -> Result<bool, DeserializationError> {
+    verify_signature_sequence_proof_with_policy(
+        sequence_digest,
+        ExtensionPolicy::ANY,
+        sequence_proof,
+    )
+}
+
+/// Verify signature sequence proof as verify_signature_sequence_proof does,
+/// rejecting it if its field extension does not meet the given policy
+pub fn verify_signature_sequence_proof_with_policy(
+    sequence_digest: &[u8],
+    policy: ExtensionPolicy,
+    sequence_proof: &[u8],
+) -> Result<bool, DeserializationError> {
+    verify_signature_sequence_proof_bound(
+        sequence_digest,
+        SigningDomain::default(),
+        policy,
+        sequence_proof,
+    )
+}
+
+/// Verify signature sequence proof whose signatures are bound to a signing domain,
+/// see RegisterVerification::with_domain
+pub fn verify_signature_sequence_proof_in_domain(
+    sequence_digest: &[u8],
+    domain: &[u8],
+    sequence_proof: &[u8],
+) -> Result<bool, DeserializationError> {
+    let domain = SigningDomain::from_bytes(domain)?;
+    verify_signature_sequence_proof_bound(
+        sequence_digest,
+        domain,
+        ExtensionPolicy::ANY,
+        sequence_proof,
+    )
+}
+
+fn verify_signature_sequence_proof_bound(
+    sequence_digest: &[u8],
+    domain: SigningDomain,
+    policy: ExtensionPolicy,
+    sequence_proof: &[u8],
+) -> Result<bool, DeserializationError> {
+    if sequence_proof.len() < 4 {
+        return Err(DeserializationError::UnexpectedEOF);
+    }
+    let mut pub_inputs =
+        SequencePublicInputs::from_bytes(&[&sequence_proof[..4], sequence_digest].concat())?;
+    pub_inputs.domain = domain;
+    if !is_provable_count(pub_inputs.num_signatures) {
+        return Ok(false);
+    }
+    let sequence_proof = StarkProof::from_bytes(&sequence_proof[4..])?;
+
+    Ok(policy.accepts(&sequence_proof)
+        && verify::<SchnorrSequenceAir>(sequence_proof, pub_inputs).is_ok())
 }
 
 /// voting_keys should be stored on smart contract
 /// First 4 bytes of voting_keys are little-endian representation of voting_keys.len()
+/// The validity of each CDS proof is asserted by the AIR, except for the ballots flagged
+/// as invalid in the public inputs, which are checked to be invalid by the verifier.
 pub fn verify_cast_proof(
     voting_keys: &[u8],
     cast_proof: &[u8],
 ) -> Result<bool, DeserializationError> {
-    // Deserialize CDS public inputs and proof
-    let mut tmp = [0u8; 4];
-    tmp.copy_from_slice(&cast_proof[..4]);
-    let num_proofs = u32::from_le_bytes(tmp) as usize;
-    tmp.copy_from_slice(&voting_keys[..4]);
-    tmp.reverse();
-    if num_proofs != (u32::from_le_bytes(tmp) as usize) {
+    verify_cast_proof_with(voting_keys, &CastVerification::new(), cast_proof).into_outcome()
+}
+
+/// Verify cast proof as verify_cast_proof does with the given options, and report whether
+/// its public inputs, the ballots it flags as invalid, its commitments or its STARK proof
+/// were rejected. The report lists the trackers of the encrypted votes in the order of
+/// voters, so that voters can check that their ballot is included.
+pub fn verify_cast_proof_with(
+    voting_keys: &[u8],
+    options: &CastVerification,
+    cast_proof: &[u8],
+) -> CastReport {
+    let mut report = CastReport {
+        public_inputs: Ok(()),
+        excluded_ballots: None,
+        commitments: None,
+        stark_proof: None,
+        trackers: Vec::new(),
+    };
+
+    // Decompress the proof if it was compressed, and deserialize CDS public inputs and proof
+    let cast_proof = match decompress_proof(cast_proof) {
+        Ok(cast_proof) => cast_proof,
+        Err(error) => {
+            report.public_inputs = Err(error);
+            return report;
+        }
+    };
+    let (cds_pub_inputs, bound, commitments_valid, excluded_valid) =
+        match read_cast_pub_inputs(voting_keys, options, &cast_proof) {
+            Ok(result) => result,
+            Err(error) => {
+                report.public_inputs = Err(error);
+                return report;
+            }
+        };
+    report.excluded_ballots = Some(excluded_valid);
+    report.commitments = Some(commitments_valid);
+    report.trackers = cds_pub_inputs.trackers();
+
+    // Verify STARK proof
+    let num_voters = cds_pub_inputs.encrypted_votes.len();
+    report.stark_proof = Some(if is_provable_count(num_voters) {
+        verify_stark_proof::<CDSAir>(&cast_proof[bound..], cds_pub_inputs, options.policy)
+    } else {
+        Err(ProofError::Count(num_voters))
+    });
+    report
+}
+
+/// Deserialize the registrations listed by a register proof, without copying them
+/// next to the eligibility root, so that the offsets of the proof do not depend on
+/// the encoding of the root
+fn read_register_pub_inputs(
+    elg_root: [BaseElement; DIGEST_SIZE],
+    register_proof: &[u8],
+) -> Result<RegisterPublicInputs, DeserializationError> {
+    let num_regs = read_count_at(register_proof, 0)?;
+    let bound = SchnorrPublicInputs::serialized_len(num_regs);
+    let pub_inputs = read_bytes_at(register_proof, 0, bound)?;
+    RegisterPublicInputs::from_tree_root_and_bytes(elg_root, pub_inputs)
+}
+
+/// Deserialize the public inputs of a cast proof, check the ballots flagged as invalid
+/// and the commitments to encrypted votes, and return (public inputs, offset of the
+/// STARK proof, commitments are valid, excluded ballots are valid)
+fn read_cast_pub_inputs(
+    voting_keys: &[u8],
+    options: &CastVerification,
+    cast_proof: &[u8],
+) -> Result<(CDSPublicInputs, usize, bool, bool), DeserializationError> {
+    let num_proofs = read_count_at(cast_proof, 0)?;
+    let num_voters = read_num_voting_keys(voting_keys)?;
+    if num_proofs != num_voters {
         return Err(DeserializationError::InvalidValue(String::from(
             "Number of CDS proofs submitted does not match number of voting keys.",
         )));
     }
-    let cds_pub_inputs =
-        CDSPublicInputs::from_bytes(&[&tmp, &voting_keys[4..], &cast_proof[4..]].concat())?;
-    let bound = 4 + num_proofs * (2 * 5 * AFFINE_POINT_WIDTH * BYTES_PER_ELEMENT);
-    let cds_proof = StarkProof::from_bytes(&cast_proof[bound..])?;
+    // check the length before allocating, as the number of proofs is untrusted
+    if (cast_proof.len() - 4) / BYTES_PER_CAST_PUB_INPUTS < num_proofs {
+        return Err(DeserializationError::UnexpectedEOF);
+    }
+    let mut cds_pub_inputs = CDSPublicInputs::from_bytes(
+        &[
+            &(num_proofs as u32).to_le_bytes(),
+            &voting_keys[4..],
+            &cast_proof[4..],
+        ]
+        .concat(),
+    )?;
+    cds_pub_inputs.manifest_digest = options.manifest_digest;
+    if !options.key_order.is_ordered(&cds_pub_inputs.voting_keys) {
+        return Err(unordered_keys());
+    }
+    cds_pub_inputs.ballot_context = options.ballot_context;
+    cds_pub_inputs.register_commitment = options.register_commitment;
+    cds_pub_inputs.key_order = options.key_order;
+    let mut bound = 4 + num_proofs * BYTES_PER_CAST_PUB_INPUTS;
+    let excluded_valid = verify_excluded_ballots(&mut cds_pub_inputs, cast_proof, &mut bound)?;
+    if let Some(commitments) = options.commitments.as_ref() {
+        if commitments.len() != num_voters {
+            return Err(mismatched_commitments());
+        }
+    }
+    let commitments_valid = verify_vote_commitments(
+        &cds_pub_inputs,
+        0,
+        options.commitments.as_deref(),
+        cast_proof,
+        &mut bound,
+    )?;
 
-    // Verify STARK proof
-    Ok(verify::<CDSAir>(cds_proof, cds_pub_inputs).is_ok())
+    Ok((cds_pub_inputs, bound, commitments_valid, excluded_valid))
+}
+
+/// Voting keys listed by a register proof, in the format of verify_cast_proof
+pub(crate) fn read_registered_voting_keys(
+    register_proof: &[u8],
+) -> Result<Vec<u8>, DeserializationError> {
+    let num_regs = SliceReader::new(register_proof).read_u32()? as usize;
+    if (register_proof.len() - 4) / BYTES_PER_COMPRESSED_POINT < num_regs {
+        return Err(DeserializationError::UnexpectedEOF);
+    }
+    let mut voting_keys = Vec::with_capacity(4 + num_regs * BYTES_PER_COMPRESSED_POINT);
+    voting_keys.write_u8_slice(&(num_regs as u32).to_be_bytes());
+    voting_keys.write_u8_slice(&register_proof[4..4 + num_regs * BYTES_PER_COMPRESSED_POINT]);
+    Ok(voting_keys)
+}
+
+/// Encrypted votes of the ballots of a cast proof not flagged as invalid, in the order of voters
+pub(crate) fn read_valid_encrypted_votes(
+    cast_proof: &[u8],
+) -> Result<Vec<[BaseElement; AFFINE_POINT_WIDTH]>, DeserializationError> {
+    let num_proofs = SliceReader::new(cast_proof).read_u32()? as usize;
+    if (cast_proof.len() - 4) / BYTES_PER_CAST_PUB_INPUTS < num_proofs {
+        return Err(DeserializationError::UnexpectedEOF);
+    }
+    let bound = 4 + num_proofs * BYTES_PER_CAST_PUB_INPUTS;
+    let validity = read_validity_bitmap(&mut SliceReader::new(&cast_proof[bound..]), num_proofs)?;
+    let mut encrypted_votes = Vec::with_capacity(num_proofs);
+    for (i, &is_valid) in validity.iter().enumerate() {
+        if is_valid {
+            let offset = 4 + i * BYTES_PER_CAST_PUB_INPUTS;
+            encrypted_votes.push(read_compressed_point(&mut SliceReader::new(
+                &cast_proof[offset..],
+            ))?);
+        }
+    }
+    Ok(encrypted_votes)
+}
+
+/// Number of registrations listed by a register proof, which may be compressed
+pub(crate) fn read_num_registrations(
+    register_proof: &[u8],
+) -> Result<usize, DeserializationError> {
+    let register_proof = decompress_proof(register_proof)?;
+    read_count_at(&register_proof, 0)
+}
+
+/// Check that the ballots flagged as invalid in the public inputs of a cast proof are
+/// indeed invalid, from their CDS proof scalars serialized after the validity bitmap.
+/// The AIR does not constrain the flags, so the scalars of each excluded ballot must be
+/// signed by the voting key of its voter, which prevents the aggregator from excluding a
+/// valid ballot with scalars of its choosing. The validity of the other ballots is asserted
+/// by the AIR. Advance bound past the validity bitmap, the scalars and the signatures.
+fn verify_excluded_ballots(
+    cds_pub_inputs: &mut CDSPublicInputs,
+    cast_proof: &[u8],
+    bound: &mut usize,
+) -> Result<bool, DeserializationError> {
+    let num_proofs = cds_pub_inputs.voting_keys.len();
+    *bound += validity_bitmap_size(num_proofs);
+    if cds_pub_inputs.validity.iter().all(|&is_valid| is_valid) {
+        return Ok(true);
+    }
+
+    // Blinding keys are reused by the AIR
+    cds_pub_inputs.blinding_keys =
+        compute_blinding_keys(&cds_pub_inputs.voting_keys, cds_pub_inputs.shard);
+    let first_voter_index = cds_pub_inputs
+        .shard
+        .map_or(0, |shard| shard.first_voter_index);
+    let context = election_context(
+        cds_pub_inputs
+            .ballot_context
+            .or(cds_pub_inputs.manifest_digest),
+    );
+    let mut reader = SliceReader::new(
+        cast_proof
+            .get(*bound..)
+            .ok_or(DeserializationError::UnexpectedEOF)?,
+    );
+    let mut proof_scalars = [Scalar::zero(); PROOF_NUM_SCALARS];
+    let mut excluded_valid = true;
+    for i in 0..num_proofs {
+        if cds_pub_inputs.validity[i] {
+            continue;
+        }
+        proof_scalars.copy_from_slice(&read_canonical_scalars(&mut reader, PROOF_NUM_SCALARS)?);
+        let signature = read_ballot_signature(&mut reader)?;
+        *bound += PROOF_NUM_SCALARS * BYTES_PER_SCALAR + BYTES_PER_SIGNATURE;
+        let digest = signed_ballot_digest(
+            first_voter_index + i,
+            &cds_pub_inputs.encrypted_votes[i],
+            &cds_pub_inputs.cds_proofs[i],
+            &proof_scalars,
+            &context,
+        );
+        excluded_valid &=
+            verify_digest_signature(cds_pub_inputs.voting_keys[i], &digest, signature).is_ok();
+        excluded_valid &= verify_cds_proof_bound(
+            first_voter_index + i,
+            &cds_pub_inputs.voting_keys[i],
+            &cds_pub_inputs.blinding_keys[i],
+            &cds_pub_inputs.encrypted_votes[i],
+            &cds_pub_inputs.cds_proofs[i],
+            &proof_scalars,
+            &context,
+        )
+        .is_err();
+    }
+    Ok(excluded_valid)
+}
+
+/// Check the commitments to encrypted votes listed by a cast proof, starting at the voter of
+/// the given index, against the commitments submitted on smart contract during the commit
+/// phase, if any. The commitments listed by the proof are chosen by the aggregator, so they
+/// bind nothing on their own, and the proof must list all of the submitted commitments.
+fn verify_vote_commitments(
+    cds_pub_inputs: &CDSPublicInputs,
+    first_voter_index: usize,
+    commitments: Option<&[[u8; BYTES_PER_COMMITMENT]]>,
+    cast_proof: &[u8],
+    bound: &mut usize,
+) -> Result<bool, DeserializationError> {
+    let num_proofs = cds_pub_inputs.encrypted_votes.len();
+    let num_commitments = read_count_at(cast_proof, *bound)?;
+    *bound += 4;
+    if num_commitments != 0 && num_commitments != num_proofs {
+        return Err(mismatched_commitments());
+    }
+    let listed = read_bytes_at(cast_proof, *bound, num_commitments * BYTES_PER_COMMITMENT)?;
+    *bound += listed.len();
+
+    let commitments = match commitments {
+        Some(commitments) => commitments,
+        None => return Ok(true),
+    };
+    // the commit phase cannot be skipped by leaving the commitments out of the proof
+    if num_commitments != num_proofs {
+        return Ok(false);
+    }
+    Ok(commitments
+        .iter()
+        .zip(listed.chunks(BYTES_PER_COMMITMENT))
+        .enumerate()
+        .all(|(i, (commitment, listed))| {
+            let revealed = compute_vote_commitment(
+                first_voter_index + i,
+                &cds_pub_inputs.encrypted_votes[i],
+                &cds_pub_inputs.cds_proofs[i],
+            );
+            listed == &commitment[..] && revealed == *commitment
+        }))
+}
+
+/// voting_keys should be stored on smart contract.
+/// stitched_proof contains the shard proofs generated by different aggregators,
+/// which must cover all voters with consecutive shards in increasing order.
+/// The blinding key each shard starts from is derived by the verifier from the
+/// global list of voting keys, so that the blinding-key chain is consistent
+/// across shard boundaries.
+pub fn verify_cast_proofs_sharded(
+    voting_keys: &[u8],
+    stitched_proof: &[u8],
+) -> Result<bool, DeserializationError> {
+    verify_cast_proofs_sharded_with(voting_keys, &CastVerification::new(), stitched_proof)
+}
+
+/// Verify stitched shard proofs as verify_cast_proofs_sharded does with the given options.
+/// Shard proofs cannot be chained to the registration phase, and list voting keys in
+/// submission order, so options requiring otherwise are rejected.
+pub fn verify_cast_proofs_sharded_with(
+    voting_keys: &[u8],
+    options: &CastVerification,
+    stitched_proof: &[u8],
+) -> Result<bool, DeserializationError> {
+    if options.register_commitment.is_some() || options.key_order != KeyOrder::Submission {
+        return Err(DeserializationError::InvalidValue(String::from(
+            "Sharded cast proofs are neither chained nor in canonical order.",
+        )));
+    }
+    let stitched_proof = decompress_proof(stitched_proof)?;
+    let stitched_proof: &[u8] = &stitched_proof;
+
+    // Deserialize global voting keys
+    let num_voters = read_num_voting_keys(voting_keys)?;
+    // check the length before allocating, as the number of voters is untrusted
+    if (voting_keys.len() - 4) / BYTES_PER_COMPRESSED_POINT < num_voters {
+        return Err(DeserializationError::UnexpectedEOF);
+    }
+    if let Some(commitments) = options.commitments.as_ref() {
+        if commitments.len() != num_voters {
+            return Err(mismatched_commitments());
+        }
+    }
+    let mut reader = SliceReader::new(&voting_keys[4..]);
+    let mut global_voting_keys = Vec::with_capacity(num_voters);
+    for _ in 0..num_voters {
+        global_voting_keys.push(read_compressed_point(&mut reader)?);
+    }
+    let voting_key_nbytes = BYTES_PER_COMPRESSED_POINT;
+
+    // Stitched proofs are either legacy (u32 counts) or compact (varint counts)
+    let mut reader = SliceReader::new(stitched_proof);
+    let header = wire::read_header(&mut reader)?;
+    let format = header.format();
+    let num_shards = format.read_first_count(header, &mut reader)?;
+    let mut next_voter_index = 0;
+    let mut proofs_valid = true;
+
+    for _ in 0..num_shards {
+        let shard_nbytes = format.read_count(&mut reader)?;
+        let shard_proof = reader.read_u8_vec(shard_nbytes)?;
+
+        // Shards must be consecutive and within the list of voters
+        let first_voter_index = format.read_count(&mut SliceReader::new(&shard_proof))?;
+        let index_nbytes = match format {
+            WireFormat::Legacy => 4,
+            WireFormat::Compact => wire::varint_len(first_voter_index as u64),
+        };
+        let cast_proof = shard_proof
+            .get(index_nbytes..)
+            .ok_or(DeserializationError::UnexpectedEOF)?;
+        let num_proofs = read_count_at(cast_proof, 0)?;
+        if !is_provable_count(num_proofs)
+            || first_voter_index != next_voter_index
+            || first_voter_index
+                .checked_add(num_proofs)
+                .map_or(true, |end| end > num_voters)
+        {
+            return Ok(false);
+        }
+        next_voter_index += num_proofs;
+
+        // Deserialize CDS public inputs of the shard
+        let mut cds_pub_inputs = CDSPublicInputs::from_bytes(
+            &[
+                &cast_proof[..4],
+                &voting_keys[4 + first_voter_index * voting_key_nbytes
+                    ..4 + (first_voter_index + num_proofs) * voting_key_nbytes],
+                &cast_proof[4..],
+            ]
+            .concat(),
+        )?;
+        cds_pub_inputs.shard = Some(ShardInfo {
+            first_voter_index,
+            initial_blinding_key: compute_blinding_key(&global_voting_keys, first_voter_index),
+        });
+        cds_pub_inputs.manifest_digest = options.manifest_digest;
+        cds_pub_inputs.ballot_context = options.ballot_context;
+        let mut shard_bound = 4 + num_proofs * BYTES_PER_CAST_PUB_INPUTS;
+        proofs_valid &= verify_excluded_ballots(&mut cds_pub_inputs, cast_proof, &mut shard_bound)?;
+
+        let commitments = options
+            .commitments
+            .as_ref()
+            .map(|commitments| &commitments[first_voter_index..first_voter_index + num_proofs]);
+        proofs_valid &= verify_vote_commitments(
+            &cds_pub_inputs,
+            first_voter_index,
+            commitments,
+            cast_proof,
+            &mut shard_bound,
+        )?;
+        let cds_proof = StarkProof::from_bytes(&cast_proof[shard_bound..])?;
+
+        // Verify STARK proof of the shard
+        proofs_valid &= options.policy.accepts(&cds_proof)
+            && verify::<CDSAir>(cds_proof, cds_pub_inputs).is_ok();
+    }
+
+    // Shards must cover all voters
+    Ok(proofs_valid && next_voter_index == num_voters)
 }
 
-/// encrypted_votes should be stored on smart contract
+// ENCRYPTED VOTES
+// ================================================================================================
+
+/// Serialize encrypted votes in the format stored on smart contract for the tally phase:
+/// the little-endian number of votes followed by the compressed encrypted votes
+pub fn encode_encrypted_votes(encrypted_votes: &[[BaseElement; AFFINE_POINT_WIDTH]]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(4 + encrypted_votes.len() * BYTES_PER_COMPRESSED_POINT);
+    bytes.write_u32(encrypted_votes.len() as u32);
+    for encrypted_vote in encrypted_votes.iter() {
+        bytes.write_u8_slice(&compress_point(encrypted_vote));
+    }
+    bytes
+}
+
+/// Deserialize encrypted votes serialized with encode_encrypted_votes,
+/// rejecting any trailing bytes
+pub fn decode_encrypted_votes(
+    encrypted_votes: &[u8],
+) -> Result<Vec<[BaseElement; AFFINE_POINT_WIDTH]>, DeserializationError> {
+    let num_votes = read_num_encrypted_votes(encrypted_votes)? as usize;
+    // check the length before allocating, as the number of votes is untrusted
+    if (encrypted_votes.len() - 4) / BYTES_PER_COMPRESSED_POINT < num_votes {
+        return Err(DeserializationError::UnexpectedEOF);
+    }
+    let mut source = SliceReader::new(&encrypted_votes[4..]);
+    let mut result = Vec::with_capacity(num_votes);
+    for _ in 0..num_votes {
+        result.push(read_compressed_point(&mut source)?);
+    }
+    if source.has_more_bytes() {
+        return Err(DeserializationError::UnconsumedBytes);
+    }
+    Ok(result)
+}
+
+/// Read the number of votes of encrypted votes serialized with encode_encrypted_votes
+fn read_num_encrypted_votes(encrypted_votes: &[u8]) -> Result<u32, DeserializationError> {
+    SliceReader::new(encrypted_votes).read_u32()
+}
+
+// TALLY RESULT
+// ================================================================================================
+
+/// encrypted_votes should be stored on smart contract, as serialized by encode_encrypted_votes
 pub fn verify_tally_result(
     encrypted_votes: &[u8],
     tally_result: u32,
 ) -> Result<bool, DeserializationError> {
-    let mut tmp = [0u8; 4];
-    tmp.copy_from_slice(&encrypted_votes[..4]);
-    let num_votes = u32::from_le_bytes(tmp);
+    verify_tally_result_report(encrypted_votes, tally_result).into_outcome()
+}
 
-    let diff = if tally_result * 2 > num_votes {
-        Scalar::from(tally_result * 2 - num_votes)
-    } else {
-        -Scalar::from(num_votes - tally_result * 2)
+/// Verify tally result as verify_tally_result does, and report whether the encrypted
+/// votes were rejected, the tally result is out of range or does not match their sum
+pub fn verify_tally_result_report(encrypted_votes: &[u8], tally_result: u32) -> TallyReport {
+    let mut report = TallyReport {
+        public_inputs: Ok(()),
+        in_range: false,
+        sum: None,
+    };
+    let num_votes = match read_num_encrypted_votes(encrypted_votes) {
+        Ok(num_votes) => num_votes,
+        Err(error) => {
+            report.public_inputs = Err(error);
+            return report;
+        }
+    };
+    report.in_range = tally_result <= num_votes;
+    if !report.in_range {
+        return report;
+    }
+
+    // The sum of encrypted votes is (2 * tally_result - num_votes) * G. The difference is
+    // computed over u64 so that it cannot wrap around for adversarial values.
+    let yes_minus_no = 2 * tally_result as u64;
+    let diff = match yes_minus_no.checked_sub(num_votes as u64) {
+        Some(diff) => Scalar::from(diff),
+        None => -Scalar::from(num_votes as u64 - yes_minus_no),
     };
     let expected = ProjectivePoint::generator() * diff;
     let mut actual = ProjectivePoint::identity();
 
-    let mut encrypted_vote = [BaseElement::ZERO; AFFINE_POINT_WIDTH];
-    let mut encrypted_votes = SliceReader::new(&encrypted_votes[4..]);
+    match decode_encrypted_votes(encrypted_votes) {
+        Ok(encrypted_votes) => {
+            for encrypted_vote in encrypted_votes {
+                actual += AffinePoint::from_raw_coordinates(encrypted_vote);
+            }
+        }
+        Err(error) => {
+            report.public_inputs = Err(error);
+            return report;
+        }
+    }
 
-    for _ in 0..num_votes {
-        encrypted_vote.copy_from_slice(&BaseElement::read_batch_from(
-            &mut encrypted_votes,
-            AFFINE_POINT_WIDTH,
-        )?);
-        actual += AffinePoint::from_raw_coordinates(encrypted_vote);
+    // A tie sums to the identity, which has no unique projective representation
+    report.sum = Some(if expected.is_identity() {
+        actual.is_identity()
+    } else {
+        expected == actual
+    });
+    report
+}
+
+// TALLY PROOF
+// ================================================================================================
+
+/// Verify a STARK proof of the tally result, as generated by VoteTallier::get_tally_proof.
+/// encrypted_votes should be stored on smart contract, as serialized by encode_encrypted_votes
+pub fn verify_tally_proof(
+    encrypted_votes: &[u8],
+    tally_result: u32,
+    tally_proof: &[u8],
+) -> Result<bool, DeserializationError> {
+    let encrypted_votes = decode_encrypted_votes(encrypted_votes)?;
+    verify_tally_proof_bound(encrypted_votes, tally_result, None, tally_proof)
+}
+
+/// Verify tally proof chained to the commitment to the vote casting phase, see
+/// chain::ballot_commitment. ballot_commitment should be stored on smart contract,
+/// and must be exactly BYTES_PER_PHASE_COMMITMENT bytes long.
+pub fn verify_tally_proof_chained(
+    encrypted_votes: &[u8],
+    tally_result: u32,
+    ballot_commitment: &[u8],
+    tally_proof: &[u8],
+) -> Result<bool, DeserializationError> {
+    let encrypted_votes = decode_encrypted_votes(encrypted_votes)?;
+    let ballot_commitment = read_phase_commitment(ballot_commitment)?;
+    verify_tally_proof_bound(
+        encrypted_votes,
+        tally_result,
+        Some(ballot_commitment),
+        tally_proof,
+    )
+}
+
+/// Verify tally proof against the encrypted votes, rejecting numbers of votes
+/// and tally results that cannot be proven
+fn verify_tally_proof_bound(
+    encrypted_votes: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+    tally_result: u32,
+    ballot_commitment: Option<PhaseCommitment>,
+    tally_proof: &[u8],
+) -> Result<bool, DeserializationError> {
+    let num_votes = encrypted_votes.len();
+    if !is_provable_count(num_votes) || tally_result as usize > num_votes {
+        return Ok(false);
+    }
+    let tally_proof = decompress_proof(tally_proof)?;
+    let pub_inputs = TallyPublicInputs {
+        encrypted_votes,
+        tally_result,
+        ballot_commitment,
+    };
+    match verify_stark_proof::<TallyAir>(&tally_proof, pub_inputs, ExtensionPolicy::ANY) {
+        Ok(()) => Ok(true),
+        Err(ProofError::Deserialization(error)) => Err(error),
+        Err(_) => Ok(false),
+    }
+}
+
+// ELECTION
+// ================================================================================================
+
+/// Verify all phases of an election, and that they are consistent with each other: the cast
+/// proof is verified against the voting keys proven registered by the register proof, and
+/// the encrypted votes tallied must be those of the valid ballots of the cast proof.
+/// elg_root_bytes and encrypted_votes are in the formats of verify_register_proof and
+/// verify_tally_result.
+pub fn verify_election(
+    elg_root_bytes: &[u8],
+    register_proof: &[u8],
+    cast_proof: &[u8],
+    encrypted_votes: &[u8],
+    tally_result: u32,
+) -> Result<bool, DeserializationError> {
+    verify_election_report(
+        elg_root_bytes,
+        register_proof,
+        cast_proof,
+        encrypted_votes,
+        tally_result,
+    )
+    .into_outcome()
+}
+
+/// Verify an election as verify_election does, and report the outcome
+/// of the checks of each phase and of their consistency
+pub fn verify_election_report(
+    elg_root_bytes: &[u8],
+    register_proof: &[u8],
+    cast_proof: &[u8],
+    encrypted_votes: &[u8],
+    tally_result: u32,
+) -> ElectionReport {
+    let register = match read_elg_root(elg_root_bytes) {
+        Ok(elg_root) => {
+            verify_register_proof_with(&elg_root, &RegisterVerification::new(), register_proof)
+        }
+        Err(error) => RegisterReport {
+            public_inputs: Err(error),
+            stark_proof: None,
+        },
+    };
+    let mut report = ElectionReport {
+        register,
+        cast: None,
+        tally: verify_tally_result_report(encrypted_votes, tally_result),
+        encrypted_votes: None,
+    };
+
+    let voting_keys = match decompress_proof(register_proof)
+        .and_then(|register_proof| read_registered_voting_keys(&register_proof))
+    {
+        Ok(voting_keys) => voting_keys,
+        Err(_) => return report,
+    };
+    let cast = verify_cast_proof_with(&voting_keys, &CastVerification::new(), cast_proof);
+    if cast.public_inputs.is_ok() {
+        let cast_votes = decompress_proof(cast_proof)
+            .and_then(|cast_proof| read_valid_encrypted_votes(&cast_proof));
+        if let (Ok(cast_votes), Ok(tallied_votes)) =
+            (cast_votes, decode_encrypted_votes(encrypted_votes))
+        {
+            report.encrypted_votes = Some(cast_votes == tallied_votes);
+        }
     }
+    report.cast = Some(cast);
+    report
+}
 
-    Ok(expected == actual)
+/// Verify an election whose proofs are chained by phase commitments, see chain: the cast
+/// proof must be chained to the registration set proven by the register proof, and the
+/// tally proof to the valid encrypted votes of the cast proof. Arguments are in the formats
+/// of verify_register_proof, verify_cast_proof and verify_tally_proof.
+pub fn verify_election_chained(
+    elg_root_bytes: &[u8],
+    register_proof: &[u8],
+    cast_proof: &[u8],
+    encrypted_votes: &[u8],
+    tally_result: u32,
+    tally_proof: &[u8],
+) -> Result<bool, DeserializationError> {
+    if !verify_register_proof(elg_root_bytes, register_proof)? {
+        return Ok(false);
+    }
+    let elg_root = read_elg_root(elg_root_bytes)?;
+    let register_proof = decompress_proof(register_proof)?;
+    let registered = read_register_pub_inputs(elg_root, &register_proof)?;
+    let register_commitment = register_commitment(&elg_root, &registered.voting_keys);
+
+    let voting_keys = read_registered_voting_keys(&register_proof)?;
+    let options = CastVerification::new().with_register_commitment(register_commitment);
+    let cast = verify_cast_proof_with(&voting_keys, &options, cast_proof);
+    if !cast.into_outcome()? {
+        return Ok(false);
+    }
+    let cast_votes = read_valid_encrypted_votes(&decompress_proof(cast_proof)?)?;
+    let tallied_votes = decode_encrypted_votes(encrypted_votes)?;
+    if cast_votes != tallied_votes {
+        return Ok(false);
+    }
+
+    let ballot_commitment = ballot_commitment(&register_commitment, &cast_votes);
+    verify_tally_proof_bound(
+        tallied_votes,
+        tally_result,
+        Some(ballot_commitment),
+        tally_proof,
+    )
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Read num_bytes bytes at the given offset, without overflowing on adversarial offsets
+fn read_bytes_at(
+    source: &[u8],
+    offset: usize,
+    num_bytes: usize,
+) -> Result<&[u8], DeserializationError> {
+    offset
+        .checked_add(num_bytes)
+        .and_then(|end| source.get(offset..end))
+        .ok_or(DeserializationError::UnexpectedEOF)
+}
+
+/// Read the little-endian count at the given offset of a proof
+fn read_count_at(source: &[u8], offset: usize) -> Result<usize, DeserializationError> {
+    let mut count = [0u8; 4];
+    count.copy_from_slice(read_bytes_at(source, offset, 4)?);
+    Ok(u32::from_le_bytes(count) as usize)
+}
+
+/// Read the leaf indices recorded with registrations on smart contract,
+/// each 4 bytes little-endian, see RegisterVerification::with_leaf_indices
+pub fn read_leaf_indices(leaf_indices: &[u8]) -> Result<Vec<usize>, DeserializationError> {
+    if leaf_indices.len() % 4 != 0 {
+        return Err(DeserializationError::UnexpectedEOF);
+    }
+    let mut result = Vec::with_capacity(leaf_indices.len() / 4);
+    for offset in (0..leaf_indices.len()).step_by(4) {
+        let leaf_index = read_count_at(leaf_indices, offset)?;
+        if leaf_index >= MerkleTree::NUM_LEAVES {
+            return Err(DeserializationError::InvalidValue(String::from(
+                "Invalid Merkle leaf index.",
+            )));
+        }
+        result.push(leaf_index);
+    }
+    Ok(result)
+}
+
+/// Read the group labels recorded with registrations on smart contract,
+/// each 4 bytes little-endian, see RegisterVerification::with_groups
+pub fn read_groups(groups: &[u8]) -> Result<Vec<u32>, DeserializationError> {
+    if groups.len() % 4 != 0 {
+        return Err(DeserializationError::UnexpectedEOF);
+    }
+    (0..groups.len())
+        .step_by(4)
+        .map(|offset| read_count_at(groups, offset).map(|group| group as u32))
+        .collect()
+}
+
+/// Read the number of voting keys stored on smart contract, which is big-endian
+fn read_num_voting_keys(voting_keys: &[u8]) -> Result<usize, DeserializationError> {
+    let mut count = [0u8; 4];
+    count.copy_from_slice(read_bytes_at(voting_keys, 0, 4)?);
+    Ok(u32::from_be_bytes(count) as usize)
+}
+
+/// Error raised when the number of commitments to encrypted votes is not the number of voters
+fn mismatched_commitments() -> DeserializationError {
+    DeserializationError::InvalidValue(String::from(
+        "Number of commitments does not match number of CDS proofs.",
+    ))
+}
+
+/// Error raised when the voting keys of a proof are not listed in the expected order
+fn unordered_keys() -> DeserializationError {
+    DeserializationError::InvalidValue(String::from("Voting keys are not in the expected order."))
+}
+
+/// Return true if a STARK proof can cover the given number of voters, i.e. if it is
+/// a power of two greater than 1. Smaller elections are rejected before their proof is
+/// deserialized, as execution traces of fewer than two voters are not defined.
+fn is_provable_count(num_voters: usize) -> bool {
+    num_voters >= 2 && num_voters.is_power_of_two()
+}
+
+/// Deserialize a STARK proof and verify it against the public inputs, if its field
+/// extension and FRI parameters meet the policy
+fn verify_stark_proof<AIR: Air>(
+    proof_bytes: &[u8],
+    pub_inputs: AIR::PublicInputs,
+    policy: ExtensionPolicy,
+) -> Result<(), ProofError> {
+    let proof = StarkProof::from_bytes(proof_bytes).map_err(ProofError::Deserialization)?;
+    if extension_degree(&proof) < policy.min_degree() {
+        return Err(ProofError::Extension(extension_degree(&proof)));
+    }
+    if !policy.accepts_fri(&proof) {
+        return Err(ProofError::Fri(FriConfig::of(proof.options())));
+    }
+    verify::<AIR>(proof, pub_inputs).map_err(ProofError::Verifier)
 }
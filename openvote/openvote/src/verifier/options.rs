@@ -0,0 +1,190 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::policy::ExtensionPolicy;
+use crate::{
+    cds::constants::BYTES_PER_COMMITMENT,
+    chain::PhaseCommitment,
+    schnorr::SigningDomain,
+    utils::{ordering::KeyOrder, rescue::DIGEST_SIZE},
+};
+use winterfell::math::fields::f63::BaseElement;
+
+#[cfg(feature = "accumulator")]
+use num_bigint::BigUint;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+// REGISTER VERIFICATION
+// ================================================================================================
+
+/// Values a register proof is checked against in addition to the eligibility root.
+/// The default options are those of verify_register_proof.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RegisterVerification {
+    pub(crate) manifest_digest: Option<[BaseElement; DIGEST_SIZE]>,
+    pub(crate) domain: SigningDomain,
+    pub(crate) key_order: KeyOrder,
+    pub(crate) leaf_indices: Vec<usize>,
+    pub(crate) groups: Vec<u32>,
+    pub(crate) policy: ExtensionPolicy,
+    #[cfg(feature = "accumulator")]
+    pub(crate) accumulator: Option<AccumulatorMembership>,
+}
+
+/// RSA accumulator of eligible voting keys a register proof is checked against,
+/// with a witness of membership for each registration
+#[cfg(feature = "accumulator")]
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AccumulatorMembership {
+    pub(crate) modulus: BigUint,
+    pub(crate) value: BigUint,
+    pub(crate) witnesses: Vec<BigUint>,
+}
+
+impl RegisterVerification {
+    /// Create options verifying a register proof as verify_register_proof does
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require the proof to be bound to the digest of the election manifest shown to
+    /// voters, which should be stored on smart contract, see manifest::read_manifest_digest
+    pub fn with_manifest(mut self, manifest_digest: [BaseElement; DIGEST_SIZE]) -> Self {
+        self.manifest_digest = Some(manifest_digest);
+        self
+    }
+
+    /// Require signatures to be bound to the signing domain of the election, i.e. the
+    /// chain id of the verifier and the election id, see SigningDomain::from_bytes
+    pub fn with_domain(mut self, domain: SigningDomain) -> Self {
+        self.domain = domain;
+        self
+    }
+
+    /// Require voting keys to be listed in the given order
+    pub fn with_key_order(mut self, key_order: KeyOrder) -> Self {
+        self.key_order = key_order;
+        self
+    }
+
+    /// Require the proof to bind the leaf index of each voting key in the tree of eligible
+    /// voters, as recorded on smart contract with the registrations listed by the proof,
+    /// in the same order, see VoterRegistar::bind_leaf_indices and read_leaf_indices
+    pub fn with_leaf_indices(mut self, leaf_indices: Vec<usize>) -> Self {
+        self.leaf_indices = leaf_indices;
+        self
+    }
+
+    /// Require the proof to bind the group label committed in the leaf of each voting key,
+    /// as recorded on smart contract with the registrations listed by the proof, in the
+    /// same order, see VoterRegistar::bind_groups and read_groups
+    pub fn with_groups(mut self, groups: Vec<u32>) -> Self {
+        self.groups = groups;
+        self
+    }
+
+    /// Reject the proof if its field extension does not meet the given policy
+    pub fn with_policy(mut self, policy: ExtensionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Require each voting key listed by the proof to be a member of the RSA accumulator
+    /// of the given modulus and value, which should be stored on smart contract, with the
+    /// witnesses published by the aggregator for the registrations, in the same order, see
+    /// accumulator::RsaAccumulator. This is checked in addition to the eligibility root.
+    #[cfg(feature = "accumulator")]
+    pub fn with_accumulator(
+        mut self,
+        modulus: BigUint,
+        value: BigUint,
+        witnesses: Vec<BigUint>,
+    ) -> Self {
+        self.accumulator = Some(AccumulatorMembership {
+            modulus,
+            value,
+            witnesses,
+        });
+        self
+    }
+}
+
+// CAST VERIFICATION
+// ================================================================================================
+
+/// Values a cast proof is checked against in addition to the voting keys.
+/// The default options are those of verify_cast_proof.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CastVerification {
+    pub(crate) manifest_digest: Option<[BaseElement; DIGEST_SIZE]>,
+    pub(crate) ballot_context: Option<[BaseElement; DIGEST_SIZE]>,
+    pub(crate) register_commitment: Option<PhaseCommitment>,
+    pub(crate) key_order: KeyOrder,
+    pub(crate) policy: ExtensionPolicy,
+    pub(crate) commitments: Option<Vec<[u8; BYTES_PER_COMMITMENT]>>,
+}
+
+impl CastVerification {
+    /// Create options verifying a cast proof as verify_cast_proof does
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require the proof to be bound to the digest of the election manifest,
+    /// see RegisterVerification::with_manifest
+    pub fn with_manifest(mut self, manifest_digest: [BaseElement; DIGEST_SIZE]) -> Self {
+        self.manifest_digest = Some(manifest_digest);
+        self
+    }
+
+    /// Require the proof to be bound to an amended election manifest, see
+    /// ElectionManifest::amend. manifest_digest is the digest of the amended manifest,
+    /// and ballot_digest the one of the manifest in effect when casting started,
+    /// which CDS proofs are bound to.
+    pub fn with_amended_manifest(
+        mut self,
+        ballot_digest: [BaseElement; DIGEST_SIZE],
+        manifest_digest: [BaseElement; DIGEST_SIZE],
+    ) -> Self {
+        self.ballot_context = Some(ballot_digest);
+        self.manifest_digest = Some(manifest_digest);
+        self
+    }
+
+    /// Require the proof to be chained to the commitment to the registration phase,
+    /// which should be stored on smart contract, see chain::read_phase_commitment.
+    /// Sharded cast proofs cannot be chained.
+    pub fn with_register_commitment(mut self, register_commitment: PhaseCommitment) -> Self {
+        self.register_commitment = Some(register_commitment);
+        self
+    }
+
+    /// Require voting keys to be listed in the given order.
+    /// Sharded cast proofs list voting keys in submission order.
+    pub fn with_key_order(mut self, key_order: KeyOrder) -> Self {
+        self.key_order = key_order;
+        self
+    }
+
+    /// Reject the proof if its field extension does not meet the given policy
+    pub fn with_policy(mut self, policy: ExtensionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Require the proof to list the commitments to encrypted votes submitted on smart
+    /// contract during the commit phase, in the order of voters, and each encrypted vote
+    /// to match its commitment. Without them, commitments listed by the proof are not
+    /// checked, as they are chosen by the aggregator.
+    pub fn with_commitments(mut self, commitments: Vec<[u8; BYTES_PER_COMMITMENT]>) -> Self {
+        self.commitments = Some(commitments);
+        self
+    }
+}
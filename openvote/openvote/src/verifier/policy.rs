@@ -0,0 +1,86 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::planner::FriConfig;
+use winterfell::{DeserializationError, StarkProof};
+
+// EXTENSION POLICY
+// ================================================================================================
+
+/// Degree of the field extension a STARK proof was generated with:
+/// 1 for no extension, 2 for the quadratic and 3 for the cubic extension
+pub fn extension_degree(proof: &StarkProof) -> u32 {
+    proof.options().field_extension().degree()
+}
+
+/// Degree of the field extension of a serialized STARK proof
+pub fn extension_degree_of(proof_bytes: &[u8]) -> Result<u32, DeserializationError> {
+    Ok(extension_degree(&StarkProof::from_bytes(proof_bytes)?))
+}
+
+/// Minimum degree of the field extension required from the STARK proofs of an election.
+/// Proofs over a smaller extension have a lower soundness and are rejected by the
+/// verification functions taking a policy. The policy may also pin the FRI parameters
+/// of the proofs, which determine their size and verification cost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtensionPolicy {
+    min_degree: u32,
+    fri: Option<FriConfig>,
+}
+
+impl ExtensionPolicy {
+    /// Policy accepting proofs over any field extension
+    pub const ANY: Self = Self {
+        min_degree: 1,
+        fri: None,
+    };
+
+    /// Policy requiring proofs over an extension of degree at least min_degree
+    pub fn at_least(min_degree: u32) -> Self {
+        Self {
+            min_degree,
+            fri: None,
+        }
+    }
+
+    /// Same policy, additionally requiring proofs generated with the given FRI parameters
+    pub fn with_fri(self, fri: FriConfig) -> Self {
+        Self {
+            fri: Some(fri),
+            ..self
+        }
+    }
+
+    /// Minimum degree of the field extension
+    pub fn min_degree(&self) -> u32 {
+        self.min_degree
+    }
+
+    /// FRI parameters required from the proofs, if any
+    pub fn fri(&self) -> Option<FriConfig> {
+        self.fri
+    }
+
+    /// Returns true if the proof was generated with a large enough field extension
+    /// and with the required FRI parameters, if any
+    pub fn accepts(&self, proof: &StarkProof) -> bool {
+        extension_degree(proof) >= self.min_degree && self.accepts_fri(proof)
+    }
+
+    /// Returns true if the proof was generated with the required FRI parameters, if any
+    pub fn accepts_fri(&self, proof: &StarkProof) -> bool {
+        self.fri
+            .map_or(true, |fri| FriConfig::of(proof.options()) == fri)
+    }
+}
+
+impl Default for ExtensionPolicy {
+    fn default() -> Self {
+        Self::ANY
+    }
+}
@@ -0,0 +1,181 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::constants::BYTES_PER_TRACKER;
+use crate::planner::FriConfig;
+use winterfell::{DeserializationError, VerifierError};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+// PROOF ERRORS
+// ================================================================================================
+
+/// Reason a STARK proof was rejected
+#[derive(Debug, PartialEq)]
+pub enum ProofError {
+    /// This error occurs when the STARK proof cannot be deserialized
+    Deserialization(DeserializationError),
+    /// This error occurs when the number of voters is not a power of two greater than 1,
+    /// which no STARK proof can cover
+    Count(usize),
+    /// This error occurs when the STARK proof was generated over a field extension
+    /// of smaller degree than required by the extension policy
+    Extension(u32),
+    /// This error occurs when the STARK proof was generated with FRI parameters
+    /// other than those required by the extension policy
+    Fri(FriConfig),
+    /// This error occurs when the STARK proof does not verify against the public inputs
+    Verifier(VerifierError),
+}
+
+// REGISTER REPORT
+// ================================================================================================
+
+/// Outcome of each check of a register proof
+#[derive(Debug, PartialEq)]
+pub struct RegisterReport {
+    /// Outcome of the deserialization of the public inputs, i.e. the voting keys,
+    /// addresses and Schnorr signatures of registrations
+    pub public_inputs: Result<(), DeserializationError>,
+    /// Outcome of the verification of the STARK proof of Merkle proofs of membership and
+    /// Schnorr signatures, which are proven together by the registration AIR.
+    /// None if the public inputs could not be deserialized.
+    pub stark_proof: Option<Result<(), ProofError>>,
+}
+
+impl RegisterReport {
+    /// Return true if the register proof was verified
+    pub fn is_valid(&self) -> bool {
+        self.public_inputs.is_ok() && matches!(self.stark_proof, Some(Ok(())))
+    }
+
+    /// Outcome as returned by verify_register_proof, where deserialization
+    /// failures are errors and any other failure is Ok(false)
+    pub fn into_outcome(self) -> Result<bool, DeserializationError> {
+        self.public_inputs?;
+        match self.stark_proof {
+            Some(Err(ProofError::Deserialization(error))) => Err(error),
+            stark_proof => Ok(matches!(stark_proof, Some(Ok(())))),
+        }
+    }
+}
+
+// CAST REPORT
+// ================================================================================================
+
+/// Outcome of each check of a cast proof
+#[derive(Debug, PartialEq)]
+pub struct CastReport {
+    /// Outcome of the deserialization of the public inputs, i.e. the encrypted votes,
+    /// CDS proof points, validity bitmap, scalars of invalid ballots and commitments
+    pub public_inputs: Result<(), DeserializationError>,
+    /// Set to false if a ballot flagged as invalid has a valid CDS proof.
+    /// None if the public inputs could not be deserialized.
+    pub excluded_ballots: Option<bool>,
+    /// Set to false if the commitments submitted on smart contract, if any, are not listed
+    /// or do not match their encrypted vote. None if the public inputs could not be deserialized.
+    pub commitments: Option<bool>,
+    /// Outcome of the verification of the STARK proof of CDS proofs.
+    /// None if the public inputs could not be deserialized.
+    pub stark_proof: Option<Result<(), ProofError>>,
+    /// Trackers of the encrypted votes listed by the public inputs, in the order of voters
+    pub trackers: Vec<[u8; BYTES_PER_TRACKER]>,
+}
+
+impl CastReport {
+    /// Return true if the cast proof was verified
+    pub fn is_valid(&self) -> bool {
+        self.public_inputs.is_ok()
+            && self.excluded_ballots == Some(true)
+            && self.commitments == Some(true)
+            && matches!(self.stark_proof, Some(Ok(())))
+    }
+
+    /// Outcome as returned by verify_cast_proof, where deserialization
+    /// failures are errors and any other failure is Ok(false)
+    pub fn into_outcome(self) -> Result<bool, DeserializationError> {
+        let valid = self.is_valid();
+        self.public_inputs?;
+        match self.stark_proof {
+            Some(Err(ProofError::Deserialization(error))) => Err(error),
+            _ => Ok(valid),
+        }
+    }
+}
+
+// TALLY REPORT
+// ================================================================================================
+
+/// Outcome of each check of a tally result
+#[derive(Debug, PartialEq)]
+pub struct TallyReport {
+    /// Outcome of the deserialization of the encrypted votes
+    pub public_inputs: Result<(), DeserializationError>,
+    /// Set to false if the tally result exceeds the number of votes
+    pub in_range: bool,
+    /// Set to false if the sum of encrypted votes does not match the tally result.
+    /// None if the encrypted votes could not be deserialized or the tally result
+    /// is out of range.
+    pub sum: Option<bool>,
+}
+
+impl TallyReport {
+    /// Return true if the tally result was verified
+    pub fn is_valid(&self) -> bool {
+        self.public_inputs.is_ok() && self.in_range && self.sum == Some(true)
+    }
+
+    /// Outcome as returned by verify_tally_result, where deserialization
+    /// failures are errors and any other failure is Ok(false)
+    pub fn into_outcome(self) -> Result<bool, DeserializationError> {
+        let valid = self.is_valid();
+        self.public_inputs.map(|_| valid)
+    }
+}
+
+// ELECTION REPORT
+// ================================================================================================
+
+/// Outcome of each check of a complete election
+#[derive(Debug, PartialEq)]
+pub struct ElectionReport {
+    /// Outcome of the checks of the register proof
+    pub register: RegisterReport,
+    /// Outcome of the checks of the cast proof, verified against the voting keys proven
+    /// registered, so that it is rejected if it was generated for other voting keys.
+    /// None if the registrations could not be deserialized.
+    pub cast: Option<CastReport>,
+    /// Outcome of the checks of the tally result
+    pub tally: TallyReport,
+    /// Set to false if the encrypted votes tallied differ from those of the valid
+    /// ballots of the cast proof. None if either could not be deserialized.
+    pub encrypted_votes: Option<bool>,
+}
+
+impl ElectionReport {
+    /// Return true if all phases and their consistency were verified
+    pub fn is_valid(&self) -> bool {
+        self.register.is_valid()
+            && self.cast.as_ref().map_or(false, CastReport::is_valid)
+            && self.tally.is_valid()
+            && self.encrypted_votes == Some(true)
+    }
+
+    /// Outcome as returned by verify_election, where deserialization
+    /// failures of any phase are errors and any other failure is Ok(false)
+    pub fn into_outcome(self) -> Result<bool, DeserializationError> {
+        let valid = self.is_valid();
+        self.register.into_outcome()?;
+        if let Some(cast) = self.cast {
+            cast.into_outcome()?;
+        }
+        self.tally.into_outcome()?;
+        Ok(valid)
+    }
+}
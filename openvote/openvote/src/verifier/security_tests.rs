@@ -0,0 +1,265 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// SECURITY TESTS
+// ================================================================================================
+// Each test mutates a valid signature or CDS proof in a way that must not be accepted. Any
+// mutation accepted by the verification functions is a test failure.
+
+use super::constants::{AFFINE_POINT_WIDTH, BYTES_PER_SCALAR, PROOF_NUM_POINTS, PROOF_NUM_SCALARS};
+use crate::{
+    cds::{compute_blinding_key, verify_cds_proof, CDSExample, CdsVerificationError},
+    compat::curve::{AffinePoint, ProjectivePoint, Scalar},
+    schnorr::{
+        sign_digest, verify_digest_signature, verify_signature, SchnorrExample,
+        SchnorrVerificationError, SigningDomain,
+    },
+    testing::test_options,
+    utils::{
+        address::Address,
+        ecc::{projective_to_elements, read_canonical_scalar},
+        rescue,
+    },
+};
+use rand_core::OsRng;
+use winterfell::{
+    math::{fields::f63::BaseElement, FieldElement},
+    SliceReader,
+};
+
+// SCHNORR SIGNATURES
+// ------------------------------------------------------------------------------------------------
+
+#[test]
+fn security_test_schnorr_negated_response() {
+    let schnorr = SchnorrExample::new(test_options(1), 1);
+    let (voting_key, address, signature) = (
+        schnorr.voting_keys[0],
+        schnorr.addresses[0],
+        schnorr.signatures[0],
+    );
+    let domain = SigningDomain::default();
+    assert_eq!(
+        verify_signature(voting_key, address, signature, &domain),
+        Ok(())
+    );
+
+    let negated = (signature.0, -signature.1);
+    assert_eq!(
+        verify_signature(voting_key, address, negated, &domain),
+        Err(SchnorrVerificationError::InvalidSignature)
+    );
+}
+
+#[test]
+fn security_test_schnorr_response_plus_group_order() {
+    let schnorr = SchnorrExample::new(test_options(1), 1);
+    let s_bytes = schnorr.signatures[0].1.to_bytes();
+    let canonical = read_canonical_scalar(&mut SliceReader::new(&s_bytes)).unwrap();
+    assert_eq!(canonical, schnorr.signatures[0].1);
+
+    // s + q encodes the same scalar, and must be rejected on deserialization
+    let shifted = add_group_order(&s_bytes);
+    assert!(read_canonical_scalar(&mut SliceReader::new(&shifted)).is_err());
+}
+
+#[test]
+fn security_test_schnorr_replay_on_other_message() {
+    let schnorr = SchnorrExample::new(test_options(1), 2);
+    let signature = schnorr.signatures[0];
+    let domain = SigningDomain::default();
+
+    // the signature of a voting key does not carry over to another address or key
+    assert!(verify_signature(
+        schnorr.voting_keys[0],
+        Address::random(),
+        signature,
+        &domain
+    )
+    .is_err());
+    assert!(verify_signature(
+        schnorr.voting_keys[1],
+        schnorr.addresses[0],
+        signature,
+        &domain
+    )
+    .is_err());
+
+    // nor to another chain or election
+    let other_domain = SigningDomain::new(1, [0u8; 16]);
+    assert!(verify_signature(
+        schnorr.voting_keys[0],
+        schnorr.addresses[0],
+        signature,
+        &other_domain
+    )
+    .is_err());
+}
+
+#[test]
+fn security_test_schnorr_identity_key() {
+    // with the identity as public key, R = s * G for any message
+    let k = Scalar::random(OsRng);
+    let r_point = AffinePoint::from(AffinePoint::generator() * k);
+    let forged = (r_point.get_x(), k);
+    let identity_key = projective_to_elements(ProjectivePoint::identity());
+
+    assert!(verify_signature(
+        identity_key,
+        Address::random(),
+        forged,
+        &SigningDomain::default()
+    )
+    .is_err());
+    assert!(verify_digest_signature(identity_key, &build_digest(), forged).is_err());
+}
+
+#[test]
+fn security_test_schnorr_digest_signature_mutations() {
+    let secret_key = Scalar::random(OsRng);
+    let public_key = projective_to_elements(ProjectivePoint::generator() * secret_key);
+    let digest = build_digest();
+    let signature = sign_digest(secret_key, &digest);
+    assert_eq!(
+        verify_digest_signature(public_key, &digest, signature),
+        Ok(())
+    );
+
+    let negated = (signature.0, -signature.1);
+    assert!(verify_digest_signature(public_key, &digest, negated).is_err());
+
+    let mut other_r = signature;
+    other_r.0[0] += BaseElement::ONE;
+    assert!(verify_digest_signature(public_key, &digest, other_r).is_err());
+}
+
+// CDS PROOFS
+// ------------------------------------------------------------------------------------------------
+
+#[test]
+fn security_test_cds_negated_responses() {
+    let (cds, _) = CDSExample::new(test_options(1), 2);
+    let blinding_key = compute_blinding_key(&cds.voting_keys, 0);
+    let verify = |scalars: &[Scalar; PROOF_NUM_SCALARS]| {
+        verify_cds_proof(
+            0,
+            &cds.voting_keys[0],
+            &blinding_key,
+            &cds.encrypted_votes[0],
+            &cds.proof_points[0],
+            scalars,
+        )
+    };
+    assert_eq!(verify(&cds.proof_scalars[0]), Ok(()));
+
+    for i in 0..cds.proof_scalars[0].len() {
+        let mut scalars = cds.proof_scalars[0];
+        scalars[i] = -scalars[i];
+        assert!(
+            verify(&scalars).is_err(),
+            "Negated scalar {} was accepted.",
+            i
+        );
+    }
+}
+
+#[test]
+fn security_test_cds_swapped_branches() {
+    let (cds, _) = CDSExample::new(test_options(1), 2);
+    let blinding_key = compute_blinding_key(&cds.voting_keys, 0);
+    let [d1, d2, r1, r2] = cds.proof_scalars[0];
+    let half = PROOF_NUM_POINTS / 2 * AFFINE_POINT_WIDTH;
+    let mut swapped_points = cds.proof_points[0];
+    swapped_points.rotate_left(half);
+
+    // swapping (d1, r1, a1, b1) with (d2, r2, a2, b2) proves the opposite vote
+    assert!(verify_cds_proof(
+        0,
+        &cds.voting_keys[0],
+        &blinding_key,
+        &cds.encrypted_votes[0],
+        &swapped_points,
+        &[d2, d1, r2, r1],
+    )
+    .is_err());
+    assert!(verify_cds_proof(
+        0,
+        &cds.voting_keys[0],
+        &blinding_key,
+        &cds.encrypted_votes[0],
+        &cds.proof_points[0],
+        &[d2, d1, r1, r2],
+    )
+    .is_err());
+}
+
+#[test]
+fn security_test_cds_response_plus_group_order() {
+    let (cds, _) = CDSExample::new(test_options(1), 2);
+    for scalar in cds.proof_scalars[0].iter() {
+        let shifted = add_group_order(&scalar.to_bytes());
+        assert!(read_canonical_scalar(&mut SliceReader::new(&shifted)).is_err());
+    }
+}
+
+#[test]
+fn security_test_cds_replay_at_other_index() {
+    let (cds, _) = CDSExample::new(test_options(1), 2);
+    let blinding_key = compute_blinding_key(&cds.voting_keys, 0);
+
+    // indices that agree on their low byte must not share challenges
+    for &voter_index in [1, 256, 512].iter() {
+        assert_eq!(
+            verify_cds_proof(
+                voter_index,
+                &cds.voting_keys[0],
+                &blinding_key,
+                &cds.encrypted_votes[0],
+                &cds.proof_points[0],
+                &cds.proof_scalars[0],
+            ),
+            Err(CdsVerificationError::InvalidChallenge),
+            "Proof of voter 0 was accepted for voter {}.",
+            voter_index
+        );
+    }
+}
+
+// HELPER FUNCTIONS
+// ------------------------------------------------------------------------------------------------
+
+/// Add the order of the scalar field to the little-endian encoding of a scalar
+fn add_group_order(bytes: &[u8; BYTES_PER_SCALAR]) -> [u8; BYTES_PER_SCALAR] {
+    // q = (q - 1) + 1, where q - 1 is the canonical encoding of -1
+    let minus_one = (-Scalar::one()).to_bytes();
+    let mut result = [0u8; BYTES_PER_SCALAR];
+    let mut carry = 1u16;
+    for (byte, (&a, &b)) in result.iter_mut().zip(bytes.iter().zip(minus_one.iter())) {
+        let sum = a as u16 + b as u16 + carry;
+        *byte = sum as u8;
+        carry = sum >> 8;
+    }
+    assert_eq!(
+        carry, 0,
+        "s + q does not fit in {} bytes.",
+        BYTES_PER_SCALAR
+    );
+    result
+}
+
+fn build_digest() -> rescue::Hash {
+    rescue::Hash::new(
+        BaseElement::new(1),
+        BaseElement::new(2),
+        BaseElement::new(3),
+        BaseElement::new(4),
+        BaseElement::new(5),
+        BaseElement::new(6),
+        BaseElement::new(7),
+    )
+}
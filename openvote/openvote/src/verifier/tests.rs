@@ -0,0 +1,348 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::{
+    blob::{
+        decode_blobs, encode_blobs, encode_register_proof_blob, BYTES_PER_BLOB,
+        BYTES_PER_BLOB_HEADER, MAX_BLOB_PAYLOAD_SIZE,
+    },
+    chunks::{split_proof, MAX_ASSEMBLED_SIZE},
+    constants::BYTES_PER_COMPRESSED_POINT,
+    cost::{GAS_BASE, GAS_PER_POINT_OPERATION, GAS_PER_SCALAR_MUL},
+    decode_encrypted_votes, encode_encrypted_votes, estimate,
+    policy::{extension_degree, extension_degree_of, ExtensionPolicy},
+    verify_cast_proof, verify_cast_proofs_sharded, verify_register_proof_blob, verify_tally_result,
+    verify_tally_result_report, ChunkAssembler, ChunkError, ProofKind,
+};
+use crate::{
+    aggregator::{build_options, register::VoterRegistar, tally::VoteTallier},
+    cds::CDSExample,
+    compat::curve::{AffinePoint, Scalar},
+    schnorr::SchnorrExample,
+    tally::TallyExample,
+    utils::ecc::projective_to_elements,
+};
+use rand_chacha::ChaCha20Rng;
+use rand_core::{OsRng, RngCore, SeedableRng};
+use winterfell::{DeserializationError, Serializable};
+
+#[test]
+fn verifier_test_tally_result_all_yes() {
+    let encrypted_votes = build_encrypted_votes(8, 8);
+    assert!(verify_tally_result(&encrypted_votes, 8).unwrap());
+    assert!(!verify_tally_result(&encrypted_votes, 7).unwrap());
+}
+
+#[test]
+fn verifier_test_tally_result_all_no() {
+    let encrypted_votes = build_encrypted_votes(8, 0);
+    assert!(verify_tally_result(&encrypted_votes, 0).unwrap());
+    assert!(!verify_tally_result(&encrypted_votes, 1).unwrap());
+}
+
+#[test]
+fn verifier_test_tally_result_tie() {
+    // a tie sums to the identity
+    let encrypted_votes = build_encrypted_votes(8, 4);
+    assert!(verify_tally_result(&encrypted_votes, 4).unwrap());
+    assert!(!verify_tally_result(&encrypted_votes, 3).unwrap());
+    assert!(!verify_tally_result(&encrypted_votes, 5).unwrap());
+}
+
+#[test]
+fn verifier_test_tally_result_out_of_range() {
+    let encrypted_votes = build_encrypted_votes(8, 8);
+    assert!(!verify_tally_result(&encrypted_votes, 9).unwrap());
+    assert!(!verify_tally_result(&encrypted_votes, u32::MAX).unwrap());
+}
+
+#[test]
+fn verifier_test_tally_result_truncated() {
+    let encrypted_votes = build_encrypted_votes(8, 3);
+    assert!(matches!(
+        verify_tally_result(&encrypted_votes[..2], 3),
+        Err(DeserializationError::UnexpectedEOF)
+    ));
+    assert!(verify_tally_result(&encrypted_votes[..encrypted_votes.len() - 1], 3).is_err());
+}
+
+#[test]
+fn verifier_test_count_overflows() {
+    // adversarial counts are rejected without overflowing or allocating
+    let mut max_count = u32::MAX.to_le_bytes().to_vec();
+    max_count.extend_from_slice(&[0u8; 64]);
+    assert!(decode_encrypted_votes(&max_count).is_err());
+    let mut max_keys = u32::MAX.to_be_bytes().to_vec();
+    max_keys.extend_from_slice(&[0u8; 64]);
+    assert!(verify_cast_proof(&max_keys, &max_count).is_err());
+    assert!(verify_cast_proofs_sharded(&max_keys, &max_count).is_err());
+
+    // truncated or random inputs return an error instead of panicking
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    for _ in 0..64 {
+        let mut voting_keys = vec![0u8; (rng.next_u32() % 128) as usize];
+        let mut cast_proof = vec![0u8; (rng.next_u32() % 512) as usize];
+        rng.fill_bytes(&mut voting_keys);
+        rng.fill_bytes(&mut cast_proof);
+        if voting_keys.len() >= 4 && cast_proof.len() >= 4 {
+            // make counts match half of the time, to reach deeper offsets
+            let count = rng.next_u32() % 4;
+            if count % 2 == 0 {
+                voting_keys[..4].copy_from_slice(&count.to_be_bytes());
+                cast_proof[..4].copy_from_slice(&count.to_le_bytes());
+            }
+        }
+        assert_ne!(verify_cast_proof(&voting_keys, &cast_proof), Ok(true));
+        assert_ne!(
+            verify_cast_proofs_sharded(&voting_keys, &cast_proof),
+            Ok(true)
+        );
+        assert_ne!(verify_tally_result(&cast_proof, rng.next_u32()), Ok(true));
+    }
+}
+
+#[test]
+fn verifier_test_encrypted_votes_encoding() {
+    let (tallier, _) = VoteTallier::get_example(4);
+    let bytes = encode_encrypted_votes(&tallier.encrypted_votes);
+    assert_eq!(bytes.len(), 4 + 4 * BYTES_PER_COMPRESSED_POINT);
+    assert_eq!(bytes[..4], 4u32.to_le_bytes());
+    assert_eq!(
+        decode_encrypted_votes(&bytes).unwrap(),
+        tallier.encrypted_votes
+    );
+    assert_eq!(
+        decode_encrypted_votes(&encode_encrypted_votes(&[])),
+        Ok(vec![])
+    );
+
+    // truncated, extended and oversized lists are rejected
+    assert_eq!(
+        decode_encrypted_votes(&bytes[..bytes.len() - 1]),
+        Err(DeserializationError::UnexpectedEOF)
+    );
+    assert_eq!(
+        decode_encrypted_votes(&[&bytes[..], &[0]].concat()),
+        Err(DeserializationError::UnconsumedBytes)
+    );
+    let mut oversized = bytes.clone();
+    oversized[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(
+        decode_encrypted_votes(&oversized),
+        Err(DeserializationError::UnexpectedEOF)
+    );
+    assert_eq!(
+        decode_encrypted_votes(&bytes[..3]),
+        Err(DeserializationError::UnexpectedEOF)
+    );
+}
+
+#[test]
+fn verifier_test_estimate_tally_result() {
+    let encrypted_votes = build_encrypted_votes(8, 3);
+    let tally_estimate = estimate(ProofKind::Tally, &encrypted_votes).unwrap();
+    assert_eq!(tally_estimate.num_queries, 0);
+    assert_eq!(tally_estimate.num_point_operations, 16);
+    assert_eq!(
+        tally_estimate.gas(),
+        GAS_BASE + 16 * GAS_PER_POINT_OPERATION + GAS_PER_SCALAR_MUL
+    );
+
+    // only the header is read
+    assert_eq!(
+        estimate(ProofKind::Tally, &encrypted_votes[..4]),
+        Ok(tally_estimate)
+    );
+    assert_eq!(
+        estimate(ProofKind::Tally, &encrypted_votes[..3]),
+        Err(DeserializationError::UnexpectedEOF)
+    );
+}
+
+#[test]
+fn verifier_test_tally_result_report() {
+    let encrypted_votes = build_encrypted_votes(8, 5);
+    let report = verify_tally_result_report(&encrypted_votes, 5);
+    assert!(report.is_valid());
+    assert_eq!(report.sum, Some(true));
+
+    let report = verify_tally_result_report(&encrypted_votes, 4);
+    assert!(report.in_range && report.public_inputs.is_ok());
+    assert_eq!(report.sum, Some(false));
+
+    let report = verify_tally_result_report(&encrypted_votes, 9);
+    assert!(!report.in_range);
+    assert_eq!(report.sum, None);
+
+    let report = verify_tally_result_report(&encrypted_votes[..encrypted_votes.len() - 1], 5);
+    assert!(report.public_inputs.is_err());
+    assert_eq!(report.sum, None);
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Serialize num_votes random encrypted votes summing to (2 * tally_result - num_votes) * G
+fn build_encrypted_votes(num_votes: usize, tally_result: u32) -> Vec<u8> {
+    let d = Scalar::from(tally_result).double() - Scalar::from(num_votes as u64);
+    let mut s_sum = Scalar::zero();
+
+    let mut encrypted_votes = Vec::with_capacity(num_votes);
+    for _ in 0..num_votes - 1 {
+        let s = Scalar::random(OsRng);
+        s_sum += s;
+        encrypted_votes.push(projective_to_elements(AffinePoint::generator() * s));
+    }
+    encrypted_votes.push(projective_to_elements(
+        AffinePoint::generator() * (d - s_sum),
+    ));
+
+    encode_encrypted_votes(&encrypted_votes)
+}
+
+#[test]
+fn verifier_test_extension_degree() {
+    for extension in 1..=3u8 {
+        let options = build_options(extension);
+
+        // registration, casting and tallying phases
+        let schnorr_proof = SchnorrExample::new(options.clone(), 2).prove();
+        let (cds, _) = CDSExample::new(options.clone(), 2);
+        let (_, cds_proof) = cds.prove();
+        let tally_proof = TallyExample::new(options, 2).prove();
+
+        for proof in [schnorr_proof, cds_proof, tally_proof].iter() {
+            assert_eq!(extension_degree(proof), extension as u32);
+            assert_eq!(extension_degree_of(&proof.to_bytes()), Ok(extension as u32));
+            for min_degree in 1..=3 {
+                assert_eq!(
+                    ExtensionPolicy::at_least(min_degree).accepts(proof),
+                    extension as u32 >= min_degree
+                );
+            }
+            assert!(ExtensionPolicy::default().accepts(proof));
+        }
+    }
+    assert!(extension_degree_of(&[0u8; 4]).is_err());
+}
+
+#[test]
+fn verifier_test_chunk_assembler() {
+    let mut registar = VoterRegistar::get_example(2);
+    let register_proof = registar.get_register_proof().unwrap();
+    let mut elg_root_bytes = vec![];
+    Serializable::write_batch_into(&registar.elg_root, &mut elg_root_bytes);
+    let (key, chunks) = split_proof(&register_proof, 1000);
+    assert!(chunks.len() > 2);
+    assert_eq!(chunks.concat(), register_proof);
+
+    let mut assembler = ChunkAssembler::new();
+    assert_eq!(
+        assembler.verify_register_proof(&key, &elg_root_bytes),
+        Err(ChunkError::UnknownProof)
+    );
+    for (i, chunk) in chunks.iter().enumerate() {
+        let expected = if i == 0 {
+            ChunkError::UnknownProof
+        } else {
+            ChunkError::Incomplete
+        };
+        assert_eq!(assembler.take(&key), Err(expected));
+        assert_eq!(assembler.append(key, chunk), Ok(i == chunks.len() - 1));
+    }
+    assert_eq!(
+        assembler.append(key, chunks[0]),
+        Err(ChunkError::AlreadyComplete)
+    );
+    assert_eq!(
+        assembler.verify_register_proof(&key, &elg_root_bytes),
+        Ok(true)
+    );
+    assert_eq!(assembler.num_pending(), 0);
+
+    // chunks submitted out of order never complete the proof
+    assembler.append(key, chunks[1]).unwrap();
+    assembler.append(key, chunks[0]).unwrap();
+    for chunk in chunks[2..].iter() {
+        assert_eq!(assembler.append(key, chunk), Ok(false));
+    }
+    assert_eq!(assembler.take(&key), Err(ChunkError::Incomplete));
+    assert!(assembler.discard(&key));
+
+    // a complete proof which does not deserialize
+    let (key, chunks) = split_proof(&register_proof[..100], 64);
+    for chunk in chunks.iter() {
+        assembler.append(key, chunk).unwrap();
+    }
+    assert!(matches!(
+        assembler.verify_register_proof(&key, &elg_root_bytes),
+        Err(ChunkError::Deserialization(_))
+    ));
+
+    // oversized submissions are rejected
+    let chunk = vec![0u8; MAX_ASSEMBLED_SIZE / 2 + 1];
+    assert_eq!(assembler.append([1u8; 32], &chunk), Ok(false));
+    assert_eq!(
+        assembler.append([1u8; 32], &chunk),
+        Err(ChunkError::TooLarge)
+    );
+}
+
+#[test]
+fn verifier_test_register_proof_blob() {
+    let mut registar = VoterRegistar::get_example(2);
+    let register_proof = registar.get_register_proof().unwrap();
+    let mut elg_root_bytes = vec![];
+    Serializable::write_batch_into(&registar.elg_root, &mut elg_root_bytes);
+
+    let (header, blobs) = encode_register_proof_blob(&elg_root_bytes, &register_proof).unwrap();
+    assert_eq!(header.len(), BYTES_PER_BLOB_HEADER);
+    assert_eq!(blobs.len() % BYTES_PER_BLOB, 0);
+    assert!(blobs.chunks(32).all(|element| element[0] == 0));
+    assert_eq!(verify_register_proof_blob(&header, &blobs), Ok(true));
+
+    // tampered blobs do not match the commitment of the header
+    let mut tampered = blobs.clone();
+    tampered[1] ^= 1;
+    assert!(verify_register_proof_blob(&header, &tampered).is_err());
+    let mut wrong_root = header.clone();
+    wrong_root[0] ^= 1;
+    assert!(!matches!(
+        verify_register_proof_blob(&wrong_root, &blobs),
+        Ok(true)
+    ));
+
+    // non-canonical encodings are rejected
+    let mut tampered = blobs.clone();
+    tampered[32] = 1;
+    assert!(verify_register_proof_blob(&header, &tampered).is_err());
+    let mut tampered = blobs.clone();
+    *tampered.last_mut().unwrap() = 1;
+    assert!(verify_register_proof_blob(&header, &tampered).is_err());
+    assert!(verify_register_proof_blob(&header, &blobs[1..]).is_err());
+    assert!(verify_register_proof_blob(&header[1..], &blobs).is_err());
+}
+
+#[test]
+fn verifier_test_blob_encoding() {
+    for len in [0, 1, 30, 31, 32, 4096 * 31, 4096 * 31 + 1] {
+        let payload = (0..len).map(|i| i as u8).collect::<Vec<u8>>();
+        let blobs = encode_blobs(&payload).unwrap();
+        let num_blobs = std::cmp::max(1, (len + 4096 * 31 - 1) / (4096 * 31));
+        assert_eq!(blobs.len(), num_blobs * BYTES_PER_BLOB);
+        assert_eq!(decode_blobs(&blobs, len).unwrap(), payload);
+        // the length must cover all non-zero bytes
+        if len > 0 && payload[len - 1] != 0 {
+            assert!(decode_blobs(&blobs, len - 1).is_err());
+        }
+    }
+    assert!(encode_blobs(&vec![0u8; MAX_BLOB_PAYLOAD_SIZE]).is_some());
+    assert!(encode_blobs(&vec![0u8; MAX_BLOB_PAYLOAD_SIZE + 1]).is_none());
+    assert!(decode_blobs(&[], 0).is_err());
+    assert!(decode_blobs(&vec![0u8; BYTES_PER_BLOB], 4096 * 31 + 1).is_err());
+}
@@ -0,0 +1,771 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// VOTER PRE-FLIGHT CHECKS
+// ================================================================================================
+// Voters run the checks of the aggregator on their submissions against the public state of the
+// election, so that they can fix issues before the submission deadlines. Registrations are
+// checked with the same function as the one used by VoterRegistar. Before encrypting, voters can
+// recompute the blinding keys published by the aggregator from the ordered voting keys. Voters
+// can also audit the ballots prepared by their device before casting one of them.
+//
+// Eligible voters receive a registration kit sealed to their voting key, which they open and
+// check before building their registration from it.
+
+use crate::{
+    aggregator::{
+        cast::{CdsVerificationError, CollectorError, EncryptedVote, VoteCollector},
+        kit::RegistrationKit,
+        ownership::verify_ownership,
+        register::{RegistarError, Registration, VoterRegistar},
+    },
+    cds::{
+        compute_all_blinding_keys, compute_blinding_key, compute_tracker, compute_vote_commitment,
+        concat_proof_points,
+        constants::{AUDIT_DOMAIN, BYTES_PER_COMMITMENT, BYTES_PER_TRACKER},
+        election_context, verify_cds_proof_projective,
+    },
+    compat::curve::{AffinePoint, ProjectivePoint, Scalar},
+    merkle::{constants::*, verify_grouped_merkle_proof, verify_merlke_proof},
+    schnorr::{sign_message_in_domain, verify_signature, SchnorrVerificationError, SigningDomain},
+    utils::{
+        address::Address,
+        constant_time::{ct_eq_bytes, ct_eq_elements, ct_position},
+        ecc::{
+            mul_constant_time, mul_generator_constant_time, projective_to_elements,
+            read_canonical_scalars,
+        },
+        transcript::Transcript,
+    },
+};
+use rand_core::OsRng;
+use winterfell::{
+    math::fields::f63::BaseElement, ByteReader, ByteWriter, Deserializable, DeserializationError,
+    Serializable, SliceReader,
+};
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+#[cfg(test)]
+mod tests;
+
+// REGISTRATION
+// ================================================================================================
+
+/// Public state of the registration phase against which registrations are checked
+#[derive(Debug, Clone)]
+pub struct ElectionParams {
+    /// Merkle root of tree built from list of eligible voters
+    pub elg_root: [BaseElement; DIGEST_SIZE],
+    /// Number of eligible voters
+    pub num_elg_voters: usize,
+    /// Set to true if voters must prove ownership of their Ethereum address
+    pub require_ownership: bool,
+    /// Chain and election to which registration signatures must be bound
+    pub domain: SigningDomain,
+    /// Digest of the election manifest to which ownership signatures must be bound, if any
+    pub manifest_digest: Option<[BaseElement; DIGEST_SIZE]>,
+    /// Voting keys of registered voters
+    pub voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+    /// Ethereum addresses of registered voters
+    pub addresses: Vec<Address>,
+    /// Group label committed in each leaf of the tree of eligible
+    /// voters, empty if voters are not grouped
+    pub leaf_groups: Vec<u32>,
+}
+
+impl ElectionParams {
+    /// Create the parameters of an election in which no voter has registered yet
+    pub fn new(elg_root: [BaseElement; DIGEST_SIZE], num_elg_voters: usize) -> Self {
+        Self {
+            elg_root,
+            num_elg_voters,
+            require_ownership: false,
+            domain: SigningDomain::default(),
+            manifest_digest: None,
+            voting_keys: vec![],
+            addresses: vec![],
+            leaf_groups: vec![],
+        }
+    }
+
+    /// Borrow the state against which registrations are checked
+    pub(crate) fn registration_state(&self) -> RegistrationState<'_> {
+        RegistrationState {
+            elg_root: &self.elg_root,
+            num_elg_voters: self.num_elg_voters,
+            require_ownership: self.require_ownership,
+            domain: &self.domain,
+            manifest_digest: self.manifest_digest.as_ref(),
+            voting_keys: &self.voting_keys,
+            addresses: &self.addresses,
+            leaf_groups: &self.leaf_groups,
+        }
+    }
+}
+
+/// Borrowed public state of the registration phase, shared by the pre-flight
+/// checks of voters and the checks of VoterRegistar, see ElectionParams
+pub(crate) struct RegistrationState<'a> {
+    pub(crate) elg_root: &'a [BaseElement; DIGEST_SIZE],
+    pub(crate) num_elg_voters: usize,
+    pub(crate) require_ownership: bool,
+    pub(crate) domain: &'a SigningDomain,
+    pub(crate) manifest_digest: Option<&'a [BaseElement; DIGEST_SIZE]>,
+    pub(crate) voting_keys: &'a [[BaseElement; AFFINE_POINT_WIDTH]],
+    pub(crate) addresses: &'a [Address],
+    pub(crate) leaf_groups: &'a [u32],
+}
+
+impl From<&VoterRegistar> for ElectionParams {
+    fn from(registar: &VoterRegistar) -> Self {
+        Self {
+            elg_root: registar.elg_root,
+            num_elg_voters: registar.num_elg_voters,
+            require_ownership: registar.require_ownership,
+            domain: registar.domain,
+            manifest_digest: registar.manifest_digest,
+            voting_keys: registar.voting_keys.clone(),
+            addresses: registar.addresses.clone(),
+            leaf_groups: registar.leaf_groups.clone(),
+        }
+    }
+}
+
+/// Outcome of each check run by the aggregator on a registration
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegistrationDiagnostics {
+    /// Set to true if the Ethereum address is registered under another voting key
+    pub duplicated_address: bool,
+    /// Set to true if the Merkle proof of membership is valid
+    pub valid_merkle_proof: bool,
+    /// Set to true if the Schnorr signature is valid
+    pub valid_schnorr_signature: bool,
+    /// Reason why the Schnorr signature is invalid, if it is
+    pub schnorr_signature_error: Option<SchnorrVerificationError>,
+    /// Validity of the ownership signature, None if ownership is not checked
+    pub valid_ownership_signature: Option<bool>,
+    /// Index of the registration replaced by this one, if the voter has already registered.
+    /// Unless it is the same registration, the replacement must be chained to it, see
+    /// VoterRegistar::replace_registration.
+    pub replaced_registration: Option<usize>,
+    /// Set to true if all eligible voters have already registered
+    pub too_many_registrations: bool,
+}
+
+impl RegistrationDiagnostics {
+    /// Error returned by the aggregator on this registration, if any
+    pub fn error(&self) -> Option<RegistarError> {
+        if self.duplicated_address {
+            Some(RegistarError::DuplicatedEthAddress)
+        } else if !self.valid_merkle_proof {
+            Some(RegistarError::InvalidMerkleProof)
+        } else if !self.valid_schnorr_signature {
+            Some(RegistarError::InvalidSchnorrSig)
+        } else if self.valid_ownership_signature == Some(false) {
+            Some(RegistarError::InvalidOwnershipSig)
+        } else if self.too_many_registrations {
+            Some(RegistarError::TooManyRegistrations)
+        } else {
+            None
+        }
+    }
+
+    /// Return true if the aggregator will accept this registration
+    pub fn is_ok(&self) -> bool {
+        self.error().is_none()
+    }
+}
+
+/// Run the checks of the aggregator on a registration before submitting it
+pub fn preflight(registration: &Registration, params: &ElectionParams) -> RegistrationDiagnostics {
+    diagnose_registration(registration, &params.registration_state())
+}
+
+/// Run all checks on a registration. Lookups are constant-time to avoid
+/// leaking which voter matched. If voters are grouped, the Merkle branch
+/// is checked against the leaf committing to the group of its index.
+pub(crate) fn diagnose_registration(
+    registration: &Registration,
+    state: &RegistrationState<'_>,
+) -> RegistrationDiagnostics {
+    let RegistrationState {
+        elg_root,
+        num_elg_voters,
+        require_ownership,
+        domain,
+        manifest_digest,
+        voting_keys,
+        addresses,
+        leaf_groups,
+    } = *state;
+
+    // Two voters cannot share one Ethereum address
+    let duplicated_address = match ct_position(addresses, |a| {
+        ct_eq_bytes(a.as_bytes(), registration.address.as_bytes())
+    }) {
+        Some(idx) => !bool::from(ct_eq_elements(&voting_keys[idx], &registration.voting_key)),
+        None => false,
+    };
+
+    let valid_merkle_proof = if leaf_groups.is_empty() {
+        verify_merlke_proof(
+            elg_root,
+            &registration.voting_key,
+            &registration.merkle_branch,
+            registration.hash_index,
+        )
+    } else {
+        match leaf_groups.get(registration.hash_index) {
+            Some(&group) => verify_grouped_merkle_proof(
+                elg_root,
+                &registration.voting_key,
+                group,
+                &registration.merkle_branch,
+                registration.hash_index,
+            ),
+            None => false,
+        }
+    };
+
+    let schnorr_signature_error = verify_signature(
+        registration.voting_key,
+        registration.address,
+        registration.signature,
+        domain,
+    )
+    .err();
+
+    // Check if the owner of the Ethereum address signed the voting key for this
+    // election. Ownership cannot be proven until a manifest is bound.
+    let valid_ownership_signature = if require_ownership {
+        Some(match (registration.ownership_signature, manifest_digest) {
+            (Some(signature), Some(manifest_digest)) => verify_ownership(
+                &registration.voting_key,
+                manifest_digest,
+                registration.address,
+                &signature,
+            ),
+            _ => false,
+        })
+    } else {
+        None
+    };
+
+    // A new registration of a voter replaces their old registration
+    let replaced_registration = ct_position(voting_keys, |vk| {
+        ct_eq_elements(vk, &registration.voting_key)
+    });
+
+    RegistrationDiagnostics {
+        duplicated_address,
+        valid_merkle_proof,
+        valid_schnorr_signature: schnorr_signature_error.is_none(),
+        schnorr_signature_error,
+        valid_ownership_signature,
+        replaced_registration,
+        too_many_registrations: replaced_registration.is_none()
+            && voting_keys.len() >= num_elg_voters,
+    }
+}
+
+// REGISTRATION KITS
+// ================================================================================================
+
+/// Open the sealed registration kit of a voter with their secret key, and check that
+/// its Merkle branch proves membership of their voting key in the eligibility root
+pub fn load_registration_kit(
+    sealed: &[u8],
+    secret_key: Scalar,
+) -> Result<RegistrationKit, DeserializationError> {
+    let kit = RegistrationKit::open(sealed, secret_key)?;
+    let voting_key = projective_to_elements(mul_generator_constant_time(secret_key));
+    if !verify_merlke_proof(
+        &kit.elg_root,
+        &voting_key,
+        &kit.merkle_branch,
+        kit.hash_index,
+    ) {
+        return Err(DeserializationError::InvalidValue(String::from(
+            "Registration kit does not prove eligibility of the voting key.",
+        )));
+    }
+    Ok(kit)
+}
+
+/// Build the registration of a voter from their registration kit, signing
+/// their voting key and address in the signing domain of the kit.
+/// The ownership signature must be added if the kit requires it.
+pub fn registration_from_kit(
+    kit: &RegistrationKit,
+    secret_key: Scalar,
+    address: Address,
+) -> Registration {
+    let voting_key = projective_to_elements(mul_generator_constant_time(secret_key));
+    Registration {
+        voting_key,
+        merkle_branch: kit.merkle_branch,
+        hash_index: kit.hash_index,
+        signature: sign_message_in_domain(
+            secret_key,
+            &voting_key,
+            address,
+            &kit.domain,
+            &mut OsRng,
+        ),
+        address,
+        ownership_signature: None,
+    }
+}
+
+impl From<&RegistrationKit> for ElectionParams {
+    fn from(kit: &RegistrationKit) -> Self {
+        Self {
+            require_ownership: kit.require_ownership,
+            domain: kit.domain,
+            manifest_digest: kit.manifest_digest,
+            ..Self::new(kit.elg_root, kit.num_elg_voters)
+        }
+    }
+}
+
+// VOTE CASTING
+// ================================================================================================
+
+/// Public state of the casting phase against which encrypted votes are checked
+#[derive(Debug, Clone)]
+pub struct CastParams {
+    /// Voting keys of registered voters
+    pub voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+    /// Set to true if voters must commit to their encrypted votes before revealing them
+    pub commit_phase: bool,
+    /// Commitments to encrypted votes submitted during commit phase
+    pub commitments: Vec<Option<[u8; BYTES_PER_COMMITMENT]>>,
+    /// Set to true for voters who have already submitted a valid encrypted vote
+    pub submitted: Vec<bool>,
+    /// Digest of the election manifest bound into CDS proofs, if any
+    pub manifest_digest: Option<[BaseElement; DIGEST_SIZE]>,
+}
+
+impl CastParams {
+    /// Create the parameters of a casting phase without commitments
+    /// in which no voter has submitted an encrypted vote yet
+    pub fn new(voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>) -> Self {
+        let num_voters = voting_keys.len();
+        Self {
+            voting_keys,
+            commit_phase: false,
+            commitments: vec![None; num_voters],
+            submitted: vec![false; num_voters],
+            manifest_digest: None,
+        }
+    }
+}
+
+impl From<&VoteCollector> for CastParams {
+    fn from(collector: &VoteCollector) -> Self {
+        Self {
+            voting_keys: collector.voting_keys.clone(),
+            commit_phase: collector.commit_phase,
+            commitments: collector.commitments.clone(),
+            submitted: collector
+                .encrypted_votes
+                .iter()
+                .map(Option::is_some)
+                .collect(),
+            manifest_digest: collector.ballot_context.or(collector.manifest_digest),
+        }
+    }
+}
+
+/// Outcome of each check run by the aggregator on an encrypted vote
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoteDiagnostics {
+    /// Set to true if the voter index refers to a registered voter
+    pub valid_voter_index: bool,
+    /// Whether the encrypted vote matches the commitment of the voter,
+    /// None if there is no commit phase
+    pub matches_commitment: Option<bool>,
+    /// Result of the verification of the CDS proof
+    pub cds_proof: Result<(), CdsVerificationError>,
+    /// Set to true if the voter has already submitted a valid encrypted vote
+    pub already_submitted: bool,
+}
+
+impl VoteDiagnostics {
+    /// Error returned by the aggregator on this encrypted vote, if any
+    pub fn error(&self) -> Option<CollectorError> {
+        if !self.valid_voter_index {
+            Some(CollectorError::InvalidEncryptedVote)
+        } else if self.matches_commitment == Some(false) {
+            Some(CollectorError::InvalidReveal)
+        } else if self.cds_proof.is_err() || self.already_submitted {
+            Some(CollectorError::InvalidEncryptedVote)
+        } else {
+            None
+        }
+    }
+
+    /// Return true if the aggregator will accept this encrypted vote
+    pub fn is_ok(&self) -> bool {
+        self.error().is_none()
+    }
+}
+
+/// Run the checks of the aggregator on an encrypted vote before submitting it
+pub fn preflight_vote(encrypted_vote: &EncryptedVote, params: &CastParams) -> VoteDiagnostics {
+    let voter_index = encrypted_vote.voter_index;
+    if voter_index >= params.voting_keys.len() {
+        return VoteDiagnostics {
+            valid_voter_index: false,
+            matches_commitment: None,
+            cds_proof: Err(CdsVerificationError::InvalidVoterIndex),
+            already_submitted: false,
+        };
+    }
+
+    let matches_commitment = if params.commit_phase {
+        Some(match params.commitments[voter_index] {
+            Some(commitment) => {
+                let revealed = compute_vote_commitment(
+                    voter_index,
+                    &projective_to_elements(encrypted_vote.encrypted_vote),
+                    &concat_proof_points(&encrypted_vote.proof_points),
+                );
+                bool::from(ct_eq_bytes(&commitment, &revealed))
+            }
+            None => false,
+        })
+    } else {
+        None
+    };
+
+    let voting_key = ProjectivePoint::from(AffinePoint::from_raw_coordinates(
+        params.voting_keys[voter_index],
+    ));
+    let blinding_key = ProjectivePoint::from(AffinePoint::from_raw_coordinates(
+        compute_blinding_key(&params.voting_keys, voter_index),
+    ));
+    let cds_proof = verify_cds_proof_projective(
+        voter_index,
+        voting_key,
+        blinding_key,
+        encrypted_vote.encrypted_vote,
+        &encrypted_vote.proof_points,
+        &encrypted_vote.proof_scalars,
+        &election_context(params.manifest_digest),
+    );
+
+    VoteDiagnostics {
+        valid_voter_index: true,
+        matches_commitment,
+        cds_proof,
+        already_submitted: params.submitted[voter_index],
+    }
+}
+
+// BLINDING KEYS
+// ================================================================================================
+
+/// Errors raised when checking the blinding keys published by the aggregator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlindingKeyError {
+    /// This error occurs when the number of blinding keys differs from the number of voting keys
+    LengthMismatch,
+    /// This error occurs when the voter index does not refer to a registered voter
+    InvalidVoterIndex,
+    /// This error occurs when the blinding key of the voter at the given index is not the sum
+    /// of the voting keys of the voters before them minus those of the voters after them
+    InvalidBlindingKey(usize),
+}
+
+/// Ordered voting keys of registered voters and blinding keys published by the aggregator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishedBlindingKeys {
+    /// Voting keys of registered voters, in order of registration
+    pub voting_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+    /// Blinding key of each voter
+    pub blinding_keys: Vec<[BaseElement; AFFINE_POINT_WIDTH]>,
+}
+
+impl PublishedBlindingKeys {
+    /// Check that every published blinding key is derived from the ordered voting keys
+    pub fn verify(&self) -> Result<(), BlindingKeyError> {
+        if self.blinding_keys.len() != self.voting_keys.len() {
+            return Err(BlindingKeyError::LengthMismatch);
+        }
+        let expected = compute_all_blinding_keys(&self.voting_keys);
+        match self
+            .blinding_keys
+            .iter()
+            .zip(expected.iter())
+            .position(|(published, expected)| published != expected)
+        {
+            Some(index) => Err(BlindingKeyError::InvalidBlindingKey(index)),
+            None => Ok(()),
+        }
+    }
+
+    /// Check only the blinding key of the voter at the given index, which is the one
+    /// used to encrypt their vote
+    pub fn verify_voter(&self, voter_index: usize) -> Result<(), BlindingKeyError> {
+        if self.blinding_keys.len() != self.voting_keys.len() {
+            return Err(BlindingKeyError::LengthMismatch);
+        }
+        if voter_index >= self.voting_keys.len() {
+            return Err(BlindingKeyError::InvalidVoterIndex);
+        }
+        if compute_blinding_key(&self.voting_keys, voter_index) != self.blinding_keys[voter_index] {
+            return Err(BlindingKeyError::InvalidBlindingKey(voter_index));
+        }
+        Ok(())
+    }
+}
+
+impl From<&VoteCollector> for PublishedBlindingKeys {
+    fn from(collector: &VoteCollector) -> Self {
+        Self {
+            voting_keys: collector.voting_keys.clone(),
+            blinding_keys: collector
+                .blinding_keys
+                .iter()
+                .map(|&blinding_key| projective_to_elements(blinding_key))
+                .collect(),
+        }
+    }
+}
+
+// BALLOT AUDIT
+// ================================================================================================
+
+/// Errors raised when verifying an audited ballot
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuditError {
+    /// This error occurs when the voter index does not refer to a registered voter
+    InvalidVoterIndex,
+    /// This error occurs when the ballot is not proven to encrypt the intended vote
+    VoteMismatch,
+    /// This error occurs when the CDS proof of the ballot is invalid
+    InvalidProof(CdsVerificationError),
+}
+
+/// Encrypted vote prepared by the voting device, which the voter then either
+/// casts or audits (Benaloh challenge). Both operations consume the ballot.
+/// The device proves the vote encrypted by the ballot when preparing it, so that
+/// the ballot does not hold the secret key of the voter.
+pub struct PreparedBallot {
+    encrypted_vote: EncryptedVote,
+    vote: bool,
+    opening: [Scalar; 2],
+}
+
+impl PreparedBallot {
+    /// Encrypt the vote of the voter at voter_index against the public state of the
+    /// casting phase, binding the CDS proof to the election context of the collector
+    pub fn new(voter_index: usize, secret_key: Scalar, params: &CastParams, vote: bool) -> Self {
+        let context = election_context(params.manifest_digest);
+        let encrypted_vote = EncryptedVote::cast_with_manifest(
+            voter_index,
+            secret_key,
+            &params.voting_keys,
+            vote,
+            &context,
+        );
+        let blinding_key = ProjectivePoint::from(AffinePoint::from_raw_coordinates(
+            compute_blinding_key(&params.voting_keys, voter_index),
+        ));
+
+        // prove that ev - G (resp. ev + G) is the blinding key multiplied by the secret key
+        let nonce = Scalar::random(&mut OsRng);
+        let commitments = [
+            mul_generator_constant_time(nonce),
+            mul_constant_time(blinding_key, nonce),
+        ];
+        let challenge = opening_challenge(
+            &encrypted_vote,
+            mul_generator_constant_time(secret_key),
+            blinding_key,
+            vote,
+            &commitments,
+            &context,
+        );
+
+        Self {
+            encrypted_vote,
+            vote,
+            opening: [challenge, nonce - secret_key * challenge],
+        }
+    }
+
+    /// Tracker of the ballot, which the voter records before choosing to cast or audit it
+    pub fn tracker(&self) -> [u8; BYTES_PER_TRACKER] {
+        compute_tracker(
+            self.encrypted_vote.voter_index,
+            &projective_to_elements(self.encrypted_vote.encrypted_vote),
+            &concat_proof_points(&self.encrypted_vote.proof_points),
+        )
+    }
+
+    /// Release the encrypted vote for submission to the aggregator
+    pub fn cast(self) -> EncryptedVote {
+        self.encrypted_vote
+    }
+
+    /// Open the ballot for audit on another device instead of casting it
+    pub fn audit(self) -> BallotAudit {
+        BallotAudit {
+            encrypted_vote: self.encrypted_vote,
+            vote: self.vote,
+            opening: self.opening,
+        }
+    }
+}
+
+/// Opening of an audited ballot, i.e. the ballot, its vote and a Chaum-Pedersen proof
+/// that the ballot encrypts this vote, which does not reveal the secret key of the voter.
+/// Votes are encrypted deterministically, so the opening also reveals the vote of the
+/// ballot the voter casts, and must only be verified on a device trusted by the voter.
+pub struct BallotAudit {
+    encrypted_vote: EncryptedVote,
+    vote: bool,
+    opening: [Scalar; 2],
+}
+
+impl BallotAudit {
+    /// Tracker of the audited ballot, to compare with the one shown before the audit
+    pub fn tracker(&self) -> [u8; BYTES_PER_TRACKER] {
+        compute_tracker(
+            self.encrypted_vote.voter_index,
+            &projective_to_elements(self.encrypted_vote.encrypted_vote),
+            &concat_proof_points(&self.encrypted_vote.proof_points),
+        )
+    }
+
+    /// Deserialize an opening transferred to the auditing device, rejecting any trailing bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DeserializationError> {
+        let mut source = SliceReader::new(bytes);
+        let audit = Self::read_from(&mut source)?;
+        if source.has_more_bytes() {
+            return Err(DeserializationError::UnconsumedBytes);
+        }
+        Ok(audit)
+    }
+}
+
+impl Serializable for BallotAudit {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.encrypted_vote.write_into(target);
+        target.write_u8(self.vote as u8);
+        Serializable::write_batch_into(&self.opening, target);
+    }
+}
+
+impl Deserializable for BallotAudit {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let encrypted_vote = EncryptedVote::read_from(source)?;
+        let vote = match source.read_u8()? {
+            0 => false,
+            1 => true,
+            _ => {
+                return Err(DeserializationError::InvalidValue(String::from(
+                    "Invalid vote of audited ballot.",
+                )))
+            }
+        };
+        let mut opening = [Scalar::zero(); 2];
+        opening.copy_from_slice(&read_canonical_scalars(source, 2)?);
+        Ok(Self {
+            encrypted_vote,
+            vote,
+            opening,
+        })
+    }
+}
+
+/// Check that an audited ballot encrypts the vote intended by the voter under
+/// their voting key, and that its CDS proof is valid in the election context
+pub fn verify_ballot_audit(
+    audit: &BallotAudit,
+    params: &CastParams,
+    intended_vote: bool,
+) -> Result<(), AuditError> {
+    let encrypted_vote = &audit.encrypted_vote;
+    let voter_index = encrypted_vote.voter_index;
+    if voter_index >= params.voting_keys.len() {
+        return Err(AuditError::InvalidVoterIndex);
+    }
+    let context = election_context(params.manifest_digest);
+    let voting_key = ProjectivePoint::from(AffinePoint::from_raw_coordinates(
+        params.voting_keys[voter_index],
+    ));
+    let blinding_key = ProjectivePoint::from(AffinePoint::from_raw_coordinates(
+        compute_blinding_key(&params.voting_keys, voter_index),
+    ));
+    verify_cds_proof_projective(
+        voter_index,
+        voting_key,
+        blinding_key,
+        encrypted_vote.encrypted_vote,
+        &encrypted_vote.proof_points,
+        &encrypted_vote.proof_scalars,
+        &context,
+    )
+    .map_err(AuditError::InvalidProof)?;
+
+    // the vote is encrypted as bk * x + G for "yes" and bk * x - G for "no"
+    if audit.vote != intended_vote {
+        return Err(AuditError::VoteMismatch);
+    }
+    let vote_key = if intended_vote {
+        encrypted_vote.encrypted_vote - ProjectivePoint::generator()
+    } else {
+        encrypted_vote.encrypted_vote + ProjectivePoint::generator()
+    };
+    let [challenge, response] = audit.opening;
+    let commitments = [
+        ProjectivePoint::generator() * response + voting_key * challenge,
+        blinding_key * response + vote_key * challenge,
+    ];
+    let expected = opening_challenge(
+        encrypted_vote,
+        voting_key,
+        blinding_key,
+        intended_vote,
+        &commitments,
+        &context,
+    );
+    if expected != challenge {
+        return Err(AuditError::VoteMismatch);
+    }
+    Ok(())
+}
+
+/// Challenge of the proof opening a ballot to the given vote
+fn opening_challenge(
+    encrypted_vote: &EncryptedVote,
+    voting_key: ProjectivePoint,
+    blinding_key: ProjectivePoint,
+    vote: bool,
+    commitments: &[ProjectivePoint; 2],
+    context: &[BaseElement; DIGEST_SIZE],
+) -> Scalar {
+    let mut transcript = Transcript::new();
+    transcript.absorb_bytes(AUDIT_DOMAIN);
+    transcript.absorb_element(BaseElement::from(encrypted_vote.voter_index as u64));
+    transcript.absorb_element(BaseElement::from(vote as u8));
+    transcript.absorb_point(&projective_to_elements(voting_key));
+    transcript.absorb_point(&projective_to_elements(blinding_key));
+    transcript.absorb_point(&projective_to_elements(encrypted_vote.encrypted_vote));
+    transcript.absorb_elements(&concat_proof_points(&encrypted_vote.proof_points));
+    for &commitment in commitments.iter() {
+        transcript.absorb_point(&projective_to_elements(commitment));
+    }
+    transcript.absorb_elements(context);
+    transcript.challenge_scalar()
+}
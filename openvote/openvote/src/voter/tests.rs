@@ -0,0 +1,261 @@
+// Copyright (c) 2021-2022 Toposware, Inc.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+use crate::{
+    aggregator::{
+        cast::{CollectorError, EncryptedVote, VoteCollector},
+        register::{RegistarError, Registration, VoterRegistar},
+    },
+    compat::curve::Scalar,
+    manifest::{ElectionManifest, Schedule},
+    schnorr::random_key_pairs,
+    utils::ecc::BYTES_PER_SCALAR,
+};
+
+#[test]
+fn voter_test_preflight_registration() {
+    let example = VoterRegistar::get_example(2);
+    let mut registar = VoterRegistar::new(example.tree.clone(), 2);
+    let mut registration = Registration {
+        voting_key: example.voting_keys[0],
+        merkle_branch: example.merkle_branch(0),
+        hash_index: example.hash_indices[0],
+        signature: example.signatures[0],
+        address: example.addresses[0],
+        ownership_signature: None,
+    };
+
+    let diagnostics = preflight(&registration, &ElectionParams::from(&registar));
+    assert!(diagnostics.is_ok());
+    assert_eq!(diagnostics.replaced_registration, None);
+    assert_eq!(registar.add_registration(registration), Ok(()));
+
+    // a second registration of the voter replaces the first one
+    let diagnostics = preflight(&registration, &ElectionParams::from(&registar));
+    assert_eq!(diagnostics.replaced_registration, Some(0));
+
+    // another voter cannot use the same address
+    let mut other = registration;
+    other.voting_key = example.voting_keys[1];
+    other.merkle_branch = example.merkle_branch(1);
+    other.hash_index = example.hash_indices[1];
+    let diagnostics = preflight(&other, &ElectionParams::from(&registar));
+    assert!(diagnostics.duplicated_address);
+    assert!(!diagnostics.valid_schnorr_signature);
+    assert_eq!(
+        diagnostics.error(),
+        registar.add_registration(other).err(),
+        "Pre-flight checks should report the error of the aggregator."
+    );
+
+    // all failing checks are reported
+    registration.hash_index ^= 1;
+    registration.signature.1 += Scalar::one();
+    let diagnostics = preflight(&registration, &ElectionParams::new(example.elg_root, 2));
+    assert!(!diagnostics.valid_merkle_proof);
+    assert!(!diagnostics.valid_schnorr_signature);
+    assert_eq!(diagnostics.error(), Some(RegistarError::InvalidMerkleProof));
+}
+
+#[test]
+fn voter_test_preflight_vote() {
+    let (secret_keys, voting_keys) = random_key_pairs(4);
+    let mut collector = VoteCollector::new(voting_keys.clone());
+    let encrypted_vote = EncryptedVote::cast(1, secret_keys[1], &voting_keys, true);
+
+    let diagnostics = preflight_vote(&encrypted_vote, &CastParams::new(voting_keys.clone()));
+    assert!(diagnostics.is_ok());
+    assert_eq!(diagnostics.matches_commitment, None);
+    collector.add_encrypted_vote(encrypted_vote).unwrap();
+
+    // a voter cannot submit twice
+    let diagnostics = preflight_vote(&encrypted_vote, &CastParams::from(&collector));
+    assert!(diagnostics.already_submitted);
+    assert_eq!(
+        diagnostics.error(),
+        Some(CollectorError::InvalidEncryptedVote)
+    );
+
+    // a vote encrypted with the key of another voter has an invalid proof
+    let encrypted_vote = EncryptedVote::cast(2, secret_keys[3], &voting_keys, false);
+    let diagnostics = preflight_vote(&encrypted_vote, &CastParams::from(&collector));
+    assert!(diagnostics.cds_proof.is_err());
+    assert_eq!(
+        diagnostics.error(),
+        collector.add_encrypted_vote(encrypted_vote).err()
+    );
+
+    // unknown voters are reported
+    let params = CastParams::new(voting_keys[..2].to_vec());
+    let encrypted_vote = EncryptedVote::cast(3, secret_keys[3], &voting_keys, false);
+    assert!(!preflight_vote(&encrypted_vote, &params).valid_voter_index);
+}
+
+#[test]
+fn voter_test_blinding_keys() {
+    let (_, voting_keys) = random_key_pairs(4);
+    let collector = VoteCollector::new(voting_keys.clone());
+    let published = PublishedBlindingKeys::from(&collector);
+    assert_eq!(published.voting_keys, voting_keys);
+    assert_eq!(published.verify(), Ok(()));
+    for voter_index in 0..4 {
+        assert_eq!(published.verify_voter(voter_index), Ok(()));
+    }
+    assert_eq!(
+        published.verify_voter(4),
+        Err(BlindingKeyError::InvalidVoterIndex)
+    );
+
+    // a blinding key swapped with the one of another voter is detected
+    let mut forged = published.clone();
+    forged.blinding_keys.swap(1, 2);
+    assert_eq!(
+        forged.verify(),
+        Err(BlindingKeyError::InvalidBlindingKey(1))
+    );
+    assert_eq!(forged.verify_voter(0), Ok(()));
+    assert_eq!(
+        forged.verify_voter(2),
+        Err(BlindingKeyError::InvalidBlindingKey(2))
+    );
+
+    // reordering the voting keys changes all blinding keys but the ones of the ends
+    let mut reordered = published.clone();
+    reordered.voting_keys.swap(1, 2);
+    assert_eq!(
+        reordered.verify(),
+        Err(BlindingKeyError::InvalidBlindingKey(1))
+    );
+
+    let mut truncated = published;
+    truncated.blinding_keys.pop();
+    assert_eq!(truncated.verify(), Err(BlindingKeyError::LengthMismatch));
+}
+
+#[test]
+fn voter_test_ballot_audit() {
+    let (secret_keys, voting_keys) = random_key_pairs(4);
+    let params = CastParams::new(voting_keys.clone());
+
+    // an audited ballot encrypts the intended vote
+    let ballot = PreparedBallot::new(2, secret_keys[2], &params, true);
+    let tracker = ballot.tracker();
+    let audit = ballot.audit();
+    assert_eq!(audit.tracker(), tracker);
+    assert_eq!(verify_ballot_audit(&audit, &params, true), Ok(()));
+    assert_eq!(
+        verify_ballot_audit(&audit, &params, false),
+        Err(AuditError::VoteMismatch)
+    );
+    assert_eq!(
+        verify_ballot_audit(&audit, &CastParams::new(voting_keys[..2].to_vec()), true),
+        Err(AuditError::InvalidVoterIndex)
+    );
+
+    // the opening is checked on another device, and does not reveal the secret key
+    let bytes = audit.to_bytes();
+    assert!(!bytes
+        .windows(BYTES_PER_SCALAR)
+        .any(|window| window == &secret_keys[2].to_bytes()[..]));
+    let restored = BallotAudit::from_bytes(&bytes).unwrap();
+    assert_eq!(verify_ballot_audit(&restored, &params, true), Ok(()));
+    let mut tampered = bytes.clone();
+    let last = tampered.len() - 1;
+    tampered[last - BYTES_PER_SCALAR] ^= 1;
+    assert!(BallotAudit::from_bytes(&tampered)
+        .map(|audit| verify_ballot_audit(&audit, &params, true).is_err())
+        .unwrap_or(true));
+    assert!(BallotAudit::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+    // a device encrypting with another key is caught
+    let ballot = PreparedBallot::new(2, secret_keys[3], &params, false);
+    assert!(matches!(
+        verify_ballot_audit(&ballot.audit(), &params, false),
+        Err(AuditError::InvalidProof(_))
+    ));
+
+    // a fresh ballot is prepared to be cast
+    let mut collector = VoteCollector::new(voting_keys.clone());
+    let ballot = PreparedBallot::new(2, secret_keys[2], &params, true);
+    let tracker = ballot.tracker();
+    collector.add_encrypted_vote(ballot.cast()).unwrap();
+    assert_eq!(collector.tracker_for(2), Some(tracker));
+
+    // ballots are bound to the election context of the collector
+    let manifest = ElectionManifest::new("Adopt the proposal?", Schedule::default());
+    let mut collector = VoteCollector::new(voting_keys);
+    collector.bind_manifest(&manifest);
+    let params = CastParams::from(&collector);
+    let ballot = PreparedBallot::new(1, secret_keys[1], &params, false);
+    let audit = PreparedBallot::new(1, secret_keys[1], &params, false).audit();
+    assert_eq!(verify_ballot_audit(&audit, &params, false), Ok(()));
+    assert!(matches!(
+        verify_ballot_audit(&audit, &CastParams::new(params.voting_keys.clone()), false),
+        Err(AuditError::InvalidProof(_))
+    ));
+    assert_eq!(collector.add_encrypted_vote(ballot.cast()), Ok(()));
+}
+
+#[test]
+fn voter_test_registration_kit() {
+    use crate::{
+        aggregator::kit::KIT_MAGIC,
+        merkle::build_merkle_tree_with_keys,
+        utils::{
+            address::Address,
+            ecc::{compress_point, BYTES_PER_COMPRESSED_POINT},
+        },
+    };
+    use rand_core::RngCore;
+
+    let (secret_keys, voting_keys) = random_key_pairs(3);
+    let (tree, _) = build_merkle_tree_with_keys(&voting_keys[..2]);
+    let mut registar = VoterRegistar::new(tree, 2);
+    registar.bind_domain(SigningDomain::new(1, [7u8; 16]));
+
+    // the kit is only opened with the secret key of its voter
+    let kit = registar.registration_kit(&voting_keys[0]).unwrap();
+    assert!(registar.registration_kit(&voting_keys[2]).is_none());
+    let sealed = kit.seal(&voting_keys[0]);
+    assert_eq!(load_registration_kit(&sealed, secret_keys[0]).unwrap(), kit);
+    assert!(load_registration_kit(&sealed, secret_keys[1]).is_err());
+    let mut tampered = sealed.clone();
+    tampered[sealed.len() / 2] ^= 1;
+    assert!(load_registration_kit(&tampered, secret_keys[0]).is_err());
+
+    // the ephemeral key is rejected if it is the identity
+    let mut tampered = sealed.clone();
+    tampered[KIT_MAGIC.len()..KIT_MAGIC.len() + BYTES_PER_COMPRESSED_POINT].copy_from_slice(
+        &compress_point(&projective_to_elements(ProjectivePoint::identity())),
+    );
+    assert!(load_registration_kit(&tampered, secret_keys[0]).is_err());
+
+    // a kit sealed to another voter does not prove eligibility of the key opening it
+    let mut other_kit = registar.registration_kit(&voting_keys[1]).unwrap();
+    other_kit.hash_index = kit.hash_index;
+    assert!(load_registration_kit(&other_kit.seal(&voting_keys[0]), secret_keys[0]).is_err());
+
+    // the registration built from the kit is accepted
+    let registration = registration_from_kit(&kit, secret_keys[0], Address::random());
+    assert!(preflight(&registration, &ElectionParams::from(&kit)).is_ok());
+    assert_eq!(registar.add_registration(registration), Ok(()));
+
+    // kits are exported for eligible voters only
+    let dir = std::env::temp_dir().join(format!("openvote-kits-{}", OsRng.next_u32()));
+    assert_eq!(
+        registar
+            .export_registration_kits(&voting_keys, &dir)
+            .unwrap(),
+        2
+    );
+    let file = dir.join(format!("{}.kit", kit.hash_index));
+    let sealed = std::fs::read(file).unwrap();
+    assert_eq!(load_registration_kit(&sealed, secret_keys[0]).unwrap(), kit);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -1,7 +1,11 @@
 use crate::{Error, Precompile, PrecompileAddress, PrecompileResult, StandardPrecompileFn};
 use openvote::{
+    manifest::{read_manifest_digest, BYTES_PER_MANIFEST_DIGEST},
+    utils::abi::{self, Token},
     verifier::{
-        verify_register_proof, verify_cast_proof, verify_tally_result,
+        chunks::{chain_digest, ChunkDigest, EMPTY_CHUNK_DIGEST},
+        read_elg_root, verify_register_proof_with, verify_cast_proof, verify_tally_result,
+        verify_cast_proof_with, CastVerification, RegisterVerification,
         constants::*,
     },
 };
@@ -11,6 +15,13 @@ pub const CHECK_GENERATOR_SELECTOR: [u8; 4] = [248, 243, 181, 56];
 pub const VERIFY_REGISTER_SELECTOR: [u8; 4] = [243, 90, 41, 19];
 pub const VERIFY_CAST_SELECTOR: [u8; 4] = [199, 65, 76, 236];
 pub const VERIFY_TALLY_SELECTOR: [u8; 4] = [151, 84, 187, 55];
+pub const CHECK_PARAMS_SELECTOR: [u8; 4] = [51, 5, 197, 235];
+pub const APPEND_CHUNK_SELECTOR: [u8; 4] = [247, 79, 227, 82];
+pub const FINALIZE_CHUNKS_SELECTOR: [u8; 4] = [151, 193, 49, 99];
+
+/// Phases of the proofs accepted by CHECK_PARAMS_SELECTOR
+pub const PARAMS_PHASE_REGISTER: u8 = 0;
+pub const PARAMS_PHASE_CAST: u8 = 1;
 
 pub const STARK_VERIFIER: PrecompileAddress = PrecompileAddress(
     [168, 178, 124, 96, 75, 85, 83, 81, 210, 209, 180, 146, 95, 104, 203, 67, 41, 196, 176, 242],
@@ -18,6 +29,9 @@ pub const STARK_VERIFIER: PrecompileAddress = PrecompileAddress(
 );
 
 fn stark_verifier_run(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    if input.len() < 4 {
+        return Err(Error::InvalidMethod);
+    }
     let mut selector = [0u8; 4];
     selector.copy_from_slice(&input[..4]);
 
@@ -27,6 +41,9 @@ fn stark_verifier_run(input: &[u8], gas_limit: u64) -> PrecompileResult {
         VERIFY_REGISTER_SELECTOR => { verify_register_proof_run(&input[4..], gas_limit) },
         VERIFY_CAST_SELECTOR => { verify_cast_proof_run(&input[4..], gas_limit) },
         VERIFY_TALLY_SELECTOR => { verify_tally_result_run(&input[4..], gas_limit) },
+        CHECK_PARAMS_SELECTOR => { check_params_run(&input[4..], gas_limit) },
+        APPEND_CHUNK_SELECTOR => { append_chunk_run(&input[4..], gas_limit) },
+        FINALIZE_CHUNKS_SELECTOR => { finalize_chunks_run(&input[4..], gas_limit) },
         _ => { Err(Error::InvalidMethod) }
     }
 }
@@ -45,8 +62,7 @@ fn check_generator_run(input: &[u8], gas_limit: u64) -> PrecompileResult {
             return Err(Error::DeserializationError);
         }
         let generator = generator.unwrap();
-        let mut output = vec![0u8; 32];
-        output[31] = (generator == GENERATOR) as u8;
+        let output = encode_output(&[Token::Bool(generator == GENERATOR)])?;
         Ok((cost, output))
     }
 }
@@ -57,21 +73,27 @@ fn verify_register_proof_run(input: &[u8], gas_limit: u64) -> PrecompileResult {
         Err(Error::OutOfGas)
     } else {
         // separate inputs
-        let elg_root_bytes = &input[..BYTES_PER_DIGEST];
+        if input.len() < BYTES_PER_DIGEST + 4 {
+            return Err(Error::DeserializationError);
+        }
+        let elg_root = match read_elg_root(&input[..BYTES_PER_DIGEST]) {
+            Ok(elg_root) => elg_root,
+            Err(_) => return Err(Error::DeserializationError),
+        };
         let register_proof = &input[BYTES_PER_DIGEST..];
-        let verify_result = verify_register_proof(
-            elg_root_bytes, register_proof
-        );
+        let verify_result = verify_register_proof_with(
+            &elg_root, &RegisterVerification::new(), register_proof
+        ).into_outcome();
         if verify_result.is_err() {
             return Err(Error::DeserializationError);
         }
-        // get big-endian repr. of no. registrations
+        // no. registrations is little-endian in the register proof
         let mut num_regs = [0u8; 4];
         num_regs.copy_from_slice(&register_proof[..4]);
-        num_regs.reverse();
-        let mut output = vec![0u8; 64];
-        output[31] = verify_result.unwrap() as u8;
-        output[60..64].copy_from_slice(&num_regs);
+        let output = encode_output(&[
+            Token::Bool(verify_result.unwrap()),
+            Token::Uint(u32::from_le_bytes(num_regs) as u64),
+        ])?;
 
         Ok((cost, output))
     }
@@ -84,10 +106,16 @@ fn verify_cast_proof_run(input: &[u8], gas_limit: u64) -> PrecompileResult {
         Err(Error::OutOfGas)
     } else {
         // separate inputs
+        if input.len() < 4 {
+            return Err(Error::DeserializationError);
+        }
         let mut num_keys_bytes: [u8; 4] = [0u8; 4];
         num_keys_bytes.copy_from_slice(&input[..4]);
         let num_keys = u32::from_be_bytes(num_keys_bytes) as usize;
-        let key_end = 4 + num_keys * BYTES_PER_AFFINE;
+        let key_end = match read_key_end(input, num_keys) {
+            Some(key_end) => key_end,
+            None => return Err(Error::DeserializationError),
+        };
         let voting_keys = &input[..key_end];
         let cast_proof = &input[key_end..];
         let verify_result = verify_cast_proof(
@@ -97,20 +125,20 @@ fn verify_cast_proof_run(input: &[u8], gas_limit: u64) -> PrecompileResult {
             return Err(Error::DeserializationError);
         }
 
-        let output_length = 32 * 3 + num_keys + (32 - (num_keys % 32));
-        let mut output = vec![0u8; output_length];
-        output[31] = verify_result.unwrap() as u8;
+        let verify_result = verify_result.unwrap();
 
-        // ABI encode the dynamic array of outputs
-        output[63] = 0x40;
-        output[92..96].copy_from_slice(&num_keys_bytes);
-        let cds_output_start = 4 + num_keys * BYTES_PER_AFFINE * (PROOF_NUM_POINTS + 1);
-        
-        for (i, j) in (cds_output_start..cds_output_start + num_keys * BYTES_PER_OUTPUT).step_by(BYTES_PER_OUTPUT).zip(96..num_keys + 96) {
-            let cds_output = &cast_proof[i..i + BYTES_PER_OUTPUT];
-            output[j] = cds_output.iter().all(|&b| b == 0) as u8;
-        }
+        // per-ballot results, one byte each, read from the validity
+        // bitmap following the compact public inputs
+        let bitmap_start = 4 + num_keys * BYTES_PER_CAST_PUB_INPUTS;
+        let bitmap = match cast_proof.get(bitmap_start..bitmap_start + (num_keys + 7) / 8) {
+            Some(bitmap) => bitmap,
+            None => return Err(Error::DeserializationError),
+        };
+        let ballot_results = (0..num_keys)
+            .map(|i| (verify_result && (bitmap[i / 8] >> (i % 8)) & 1 == 1) as u8)
+            .collect::<Vec<u8>>();
 
+        let output = encode_output(&[Token::Bool(verify_result), Token::Bytes(&ballot_results)])?;
         Ok((cost, output))
     }
 }
@@ -120,27 +148,513 @@ fn verify_tally_result_run(input: &[u8], gas_limit: u64) -> PrecompileResult {
     if cost > gas_limit {
         Err(Error::OutOfGas)
     } else {
-        let mut output = vec![0u8; 32];
-        
-        // separate inputs
+        // separate inputs, encrypted votes are encoded with encode_encrypted_votes
+        if input.len() < 4 {
+            return Err(Error::DeserializationError);
+        }
         let mut tmp = [0u8; 4];
         tmp.copy_from_slice(&input[..4]);
         let tally_result = u32::from_be_bytes(tmp);
-        tmp.copy_from_slice(&input[4..8]);
-        let num_voters = u32::from_le_bytes(tmp);
 
-        if tally_result > num_voters {
-            output[31] = 0;
-            return Ok((cost, output))
-        }
-        
+        // out of range results are rejected before reading encrypted votes
         let verify_result = verify_tally_result(&input[4..], tally_result);
 
         if verify_result.is_err() {
             return Err(Error::DeserializationError);
         }
 
-        output[31] = verify_result.unwrap() as u8;
+        let output = encode_output(&[Token::Bool(verify_result.unwrap())])?;
+        Ok((cost, output))
+    }
+}
+
+/// Check that a register or cast proof is valid and bound to an election manifest.
+/// The input holds a 32-byte word with the phase of the proof, the manifest digest
+/// right-padded with zeros to 64 bytes as in ABI encoding, and then the input of
+/// VERIFY_REGISTER_SELECTOR or VERIFY_CAST_SELECTOR.
+fn check_params_run(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    let cost = 0;
+    if cost > gas_limit {
+        Err(Error::OutOfGas)
+    } else {
+        // separate inputs
+        if input.len() < 96 {
+            return Err(Error::DeserializationError);
+        }
+        let (phase, digest) = (&input[..32], &input[32..96]);
+        if phase[..31].iter().any(|&b| b != 0)
+            || digest[BYTES_PER_MANIFEST_DIGEST..].iter().any(|&b| b != 0)
+        {
+            return Err(Error::DeserializationError);
+        }
+        let manifest_digest = match read_manifest_digest(&digest[..BYTES_PER_MANIFEST_DIGEST]) {
+            Ok(manifest_digest) => manifest_digest,
+            Err(_) => return Err(Error::DeserializationError),
+        };
+        let proof_input = &input[96..];
+
+        let verify_result = match phase[31] {
+            PARAMS_PHASE_REGISTER => {
+                if proof_input.len() < BYTES_PER_DIGEST + 4 {
+                    return Err(Error::DeserializationError);
+                }
+                let elg_root = match read_elg_root(&proof_input[..BYTES_PER_DIGEST]) {
+                    Ok(elg_root) => elg_root,
+                    Err(_) => return Err(Error::DeserializationError),
+                };
+                verify_register_proof_with(
+                    &elg_root,
+                    &RegisterVerification::new().with_manifest(manifest_digest),
+                    &proof_input[BYTES_PER_DIGEST..],
+                )
+                .into_outcome()
+            }
+            PARAMS_PHASE_CAST => {
+                if proof_input.len() < 4 {
+                    return Err(Error::DeserializationError);
+                }
+                let mut num_keys_bytes = [0u8; 4];
+                num_keys_bytes.copy_from_slice(&proof_input[..4]);
+                let num_keys = u32::from_be_bytes(num_keys_bytes) as usize;
+                let key_end = match read_key_end(proof_input, num_keys) {
+                    Some(key_end) => key_end,
+                    None => return Err(Error::DeserializationError),
+                };
+                verify_cast_proof_with(
+                    &proof_input[..key_end],
+                    &CastVerification::new().with_manifest(manifest_digest),
+                    &proof_input[key_end..],
+                )
+                .into_outcome()
+            }
+            _ => return Err(Error::DeserializationError),
+        };
+        if verify_result.is_err() {
+            return Err(Error::DeserializationError);
+        }
+
+        let output = encode_output(&[Token::Bool(verify_result.unwrap())])?;
         Ok((cost, output))
     }
 }
+
+/// Chain the digest of a chunk of an input submitted over several transactions.
+/// The input holds the chain digest of the previous chunks, zero for the first
+/// chunk, followed by the chunk. The output is the new chain digest, as bytes32.
+fn append_chunk_run(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    let cost = 0;
+    if cost > gas_limit {
+        Err(Error::OutOfGas)
+    } else {
+        if input.len() < 32 {
+            return Err(Error::DeserializationError);
+        }
+        let mut previous = EMPTY_CHUNK_DIGEST;
+        previous.copy_from_slice(&input[..32]);
+        let output = chain_digest(&previous, &input[32..]).to_vec();
+        Ok((cost, output))
+    }
+}
+
+/// Run a method on an input submitted over several transactions, once all its chunks
+/// were received. The input holds the expected chain digest of the chunks, a 32-byte
+/// word with the size of the chunks, the selector of the method and its input, which
+/// is split into chunks of that size. The chain digest of the chunks must match.
+fn finalize_chunks_run(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    // separate inputs
+    if input.len() < 68 {
+        return Err(Error::DeserializationError);
+    }
+    let mut key: ChunkDigest = EMPTY_CHUNK_DIGEST;
+    key.copy_from_slice(&input[..32]);
+    if input[32..56].iter().any(|&b| b != 0) {
+        return Err(Error::DeserializationError);
+    }
+    let mut chunk_size = [0u8; 8];
+    chunk_size.copy_from_slice(&input[56..64]);
+    let chunk_size = u64::from_be_bytes(chunk_size) as usize;
+    if chunk_size == 0 {
+        return Err(Error::DeserializationError);
+    }
+    let method_input = &input[64..];
+    if method_input[..4] == APPEND_CHUNK_SELECTOR || method_input[..4] == FINALIZE_CHUNKS_SELECTOR {
+        return Err(Error::InvalidMethod);
+    }
+
+    let digest = method_input[4..]
+        .chunks(chunk_size)
+        .fold(EMPTY_CHUNK_DIGEST, |digest, chunk| chain_digest(&digest, chunk));
+    if digest != key {
+        return Err(Error::DeserializationError);
+    }
+    stark_verifier_run(method_input, gas_limit)
+}
+
+/// End of the voting keys in the input of VERIFY_CAST_SELECTOR, if the input holds num_keys keys
+fn read_key_end(input: &[u8], num_keys: usize) -> Option<usize> {
+    num_keys
+        .checked_mul(BYTES_PER_COMPRESSED_POINT)
+        .and_then(|len| len.checked_add(4))
+        .filter(|&key_end| key_end <= input.len())
+}
+
+/// ABI-encoded output of a method, as decoded by abi.decode in Solidity
+fn encode_output(tokens: &[Token]) -> Result<Vec<u8>, Error> {
+    abi::encode(tokens).ok_or(Error::DeserializationError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethabi::{ParamType, Token as AbiToken};
+    use once_cell::sync::OnceCell;
+    use openvote::{
+        aggregator::register::VoterRegistar,
+        manifest::{ElectionManifest, Schedule},
+        testing::fixtures::{self, ExampleFiles},
+        verifier::chunks::split_proof,
+    };
+    use std::{fs, path::PathBuf};
+
+    const GAS_LIMIT: u64 = 1_000_000;
+
+    /// Directory holding the files written by generate-example, generated once for all tests
+    fn example_dir() -> &'static PathBuf {
+        static DIR: OnceCell<PathBuf> = OnceCell::new();
+        DIR.get_or_init(|| {
+            let dir =
+                std::env::temp_dir().join(format!("openvote-precompile-{}", std::process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            ExampleFiles::generate(2).write_to(&dir).unwrap();
+            dir
+        })
+    }
+
+    fn example_file(name: &str) -> Vec<u8> {
+        fs::read(example_dir().join(name)).unwrap()
+    }
+
+    fn with_selector(selector: [u8; 4], input: &[u8]) -> Vec<u8> {
+        [&selector[..], input].concat()
+    }
+
+    /// Return true if the precompile succeeds and accepts its input
+    fn is_accepted(result: PrecompileResult) -> bool {
+        matches!(result, Ok((_, output)) if output[..31].iter().all(|&b| b == 0) && output[31] == 1)
+    }
+
+    #[test]
+    fn test_selectors_match_example_files() {
+        assert_eq!(fixtures::VERIFY_REGISTER_SELECTOR, VERIFY_REGISTER_SELECTOR);
+        assert_eq!(fixtures::VERIFY_CAST_SELECTOR, VERIFY_CAST_SELECTOR);
+        assert_eq!(
+            example_file("register_proof.dat")[..4],
+            VERIFY_REGISTER_SELECTOR
+        );
+        assert_eq!(example_file("cast_proof.dat")[..4], VERIFY_CAST_SELECTOR);
+    }
+
+    #[test]
+    fn test_malformed_prefix() {
+        for input in [&[][..], &[248][..], &CHECK_GENERATOR_SELECTOR[..3]] {
+            assert!(matches!(
+                stark_verifier_run(input, GAS_LIMIT),
+                Err(Error::InvalidMethod)
+            ));
+        }
+
+        // unknown selector in front of a valid input
+        let mut input = example_file("register_proof.dat");
+        input[0] ^= 1;
+        assert!(matches!(
+            stark_verifier_run(&input, GAS_LIMIT),
+            Err(Error::InvalidMethod)
+        ));
+
+        // valid input behind the selector of another method
+        let mut input = example_file("register_proof.dat");
+        input[..4].copy_from_slice(&VERIFY_TALLY_SELECTOR);
+        assert!(!is_accepted(stark_verifier_run(&input, GAS_LIMIT)));
+        input[..4].copy_from_slice(&VERIFY_CAST_SELECTOR);
+        assert!(!is_accepted(stark_verifier_run(&input, GAS_LIMIT)));
+    }
+
+    #[test]
+    fn test_check_generator() {
+        let generator = example_file("generator.dat");
+        let input = with_selector(CHECK_GENERATOR_SELECTOR, &generator);
+        assert!(is_accepted(stark_verifier_run(&input, GAS_LIMIT)));
+
+        let mut input = with_selector(CHECK_GENERATOR_SELECTOR, &generator);
+        input[4] ^= 1;
+        assert!(!is_accepted(stark_verifier_run(&input, GAS_LIMIT)));
+
+        let mut input = with_selector(CHECK_GENERATOR_SELECTOR, &generator);
+        input.push(0);
+        assert!(matches!(
+            stark_verifier_run(&input, GAS_LIMIT),
+            Err(Error::UnconsumedBytes)
+        ));
+        input.truncate(input.len() - 2);
+        assert!(stark_verifier_run(&input, GAS_LIMIT).is_err());
+    }
+
+    #[test]
+    fn test_verify_register_proof() {
+        let input = example_file("register_proof.dat");
+        let (_, output) = stark_verifier_run(&input, GAS_LIMIT).unwrap();
+        assert_eq!(output.len(), 64);
+        assert_eq!(output[31], 1);
+        assert_eq!(output[60..64], 2u32.to_be_bytes());
+
+        // the extended file is the Merkle root and the truncated proof behind the selector
+        let truncated = [
+            example_file("elg_root.dat"),
+            example_file("truncated_register_proof.dat"),
+        ]
+        .concat();
+        assert_eq!(input[4..], truncated[..]);
+
+        // inputs too short to hold the Merkle root and the number of registrations
+        let short_input = &input[..4 + BYTES_PER_DIGEST + 3];
+        assert!(matches!(
+            stark_verifier_run(short_input, GAS_LIMIT),
+            Err(Error::DeserializationError)
+        ));
+
+        // oversized number of registrations
+        let mut oversized = input.clone();
+        oversized[4 + BYTES_PER_DIGEST..8 + BYTES_PER_DIGEST]
+            .copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(!is_accepted(stark_verifier_run(&oversized, GAS_LIMIT)));
+
+        // proof verified against another Merkle root
+        let mut wrong_root = input.clone();
+        wrong_root[4..4 + BYTES_PER_DIGEST]
+            .copy_from_slice(&example_file("generator.dat")[..BYTES_PER_DIGEST]);
+        assert!(!is_accepted(stark_verifier_run(&wrong_root, GAS_LIMIT)));
+    }
+
+    #[test]
+    fn test_verify_cast_proof() {
+        let input = example_file("cast_proof.dat");
+        let (_, output) = stark_verifier_run(&input, GAS_LIMIT).unwrap();
+        assert_eq!(output[31], 1);
+        assert_eq!(output[63], 0x40);
+        assert_eq!(output[92..96], 2u32.to_be_bytes());
+        assert_eq!(output[96..98], [1, 1]);
+
+        // the extended file is the voting keys and the truncated proof behind the selector
+        let truncated = [
+            example_file("voting_keys.dat"),
+            example_file("truncated_cast_proof.dat"),
+        ]
+        .concat();
+        assert_eq!(input[4..], truncated[..]);
+
+        // oversized numbers of voting keys are rejected without reading past the input
+        for num_keys in [
+            (input.len() / BYTES_PER_COMPRESSED_POINT + 1) as u32,
+            u32::MAX,
+        ] {
+            let mut oversized = input.clone();
+            oversized[4..8].copy_from_slice(&num_keys.to_be_bytes());
+            assert!(matches!(
+                stark_verifier_run(&oversized, GAS_LIMIT),
+                Err(Error::DeserializationError)
+            ));
+        }
+        let mut oversized = input.clone();
+        oversized[4..8].copy_from_slice(&3u32.to_be_bytes());
+        assert!(!is_accepted(stark_verifier_run(&oversized, GAS_LIMIT)));
+        assert!(matches!(
+            stark_verifier_run(&input[..6], GAS_LIMIT),
+            Err(Error::DeserializationError)
+        ));
+
+        // fewer voting keys than covered by the proof
+        let mut undersized = with_selector(VERIFY_CAST_SELECTOR, &1u32.to_be_bytes());
+        undersized.extend_from_slice(&input[8..]);
+        assert!(!is_accepted(stark_verifier_run(&undersized, GAS_LIMIT)));
+    }
+
+    #[test]
+    fn test_verify_tally_result() {
+        let tally_result = example_file("tally_result.dat");
+        let encrypted_votes = example_file("encrypted_votes.dat");
+        let input = with_selector(
+            VERIFY_TALLY_SELECTOR,
+            &[tally_result.clone(), encrypted_votes.clone()].concat(),
+        );
+        assert!(is_accepted(stark_verifier_run(&input, GAS_LIMIT)));
+
+        let mut wrong_result = tally_result.clone();
+        wrong_result[3] ^= 1;
+        let input = with_selector(
+            VERIFY_TALLY_SELECTOR,
+            &[wrong_result, encrypted_votes.clone()].concat(),
+        );
+        assert!(!is_accepted(stark_verifier_run(&input, GAS_LIMIT)));
+
+        // results exceeding the number of votes are rejected
+        let oversized = [u32::MAX.to_be_bytes().to_vec(), encrypted_votes].concat();
+        let input = with_selector(VERIFY_TALLY_SELECTOR, &oversized);
+        assert!(!is_accepted(stark_verifier_run(&input, GAS_LIMIT)));
+
+        let input = with_selector(VERIFY_TALLY_SELECTOR, &tally_result[..3]);
+        assert!(matches!(
+            stark_verifier_run(&input, GAS_LIMIT),
+            Err(Error::DeserializationError)
+        ));
+    }
+
+    #[test]
+    fn test_chunked_register_proof() {
+        let input = example_file("register_proof.dat");
+        let (key, chunks) = split_proof(&input[4..], 1000);
+
+        // chain digests computed chunk by chunk reach the key of the input
+        let mut digest = EMPTY_CHUNK_DIGEST;
+        for chunk in chunks.iter() {
+            let append_input = [&APPEND_CHUNK_SELECTOR[..], &digest, chunk].concat();
+            let (_, output) = stark_verifier_run(&append_input, GAS_LIMIT).unwrap();
+            digest.copy_from_slice(&output);
+        }
+        assert_eq!(digest, key);
+
+        let finalize_input = |key: &[u8], chunk_size: u64, method_input: &[u8]| {
+            let mut chunk_size_word = [0u8; 32];
+            chunk_size_word[24..].copy_from_slice(&chunk_size.to_be_bytes());
+            [
+                &FINALIZE_CHUNKS_SELECTOR[..],
+                key,
+                &chunk_size_word,
+                method_input,
+            ]
+            .concat()
+        };
+        let (_, output) =
+            stark_verifier_run(&finalize_input(&key, 1000, &input), GAS_LIMIT).unwrap();
+        assert_eq!(output, stark_verifier_run(&input, GAS_LIMIT).unwrap().1);
+
+        // the chunks must match the key
+        for (chunk_key, chunk_size) in [(key, 999), (key, 0), ([1u8; 32], 1000)] {
+            let finalize_input = finalize_input(&chunk_key, chunk_size, &input);
+            assert!(matches!(
+                stark_verifier_run(&finalize_input, GAS_LIMIT),
+                Err(Error::DeserializationError)
+            ));
+        }
+        let mut tampered = input.clone();
+        tampered[100] ^= 1;
+        assert!(stark_verifier_run(&finalize_input(&key, 1000, &tampered), GAS_LIMIT).is_err());
+
+        // chunk methods cannot be nested
+        let nested = finalize_input(&key, 1000, &input);
+        let nested = finalize_input(&split_proof(&nested[4..], 1000).0, 1000, &nested);
+        assert!(matches!(
+            stark_verifier_run(&nested, GAS_LIMIT),
+            Err(Error::InvalidMethod)
+        ));
+        assert!(matches!(
+            stark_verifier_run(&finalize_input(&key, 1000, &[]), GAS_LIMIT),
+            Err(Error::DeserializationError)
+        ));
+    }
+
+    #[test]
+    fn test_output_abi_decoding() {
+        // outputs decode with ethabi, and match its encoding, for any number of voters
+        for num_voters in 0..100 {
+            let results = (0..num_voters)
+                .map(|i| (i % 3 != 0) as u8)
+                .collect::<Vec<u8>>();
+            let output = encode_output(&[Token::Bool(true), Token::Bytes(&results)]).unwrap();
+            let decoded = ethabi::decode(&[ParamType::Bool, ParamType::Bytes], &output).unwrap();
+            assert_eq!(
+                decoded,
+                vec![AbiToken::Bool(true), AbiToken::Bytes(results.clone())]
+            );
+            assert_eq!(output, ethabi::encode(&decoded));
+        }
+        let output = encode_output(&[Token::Bool(false), Token::Uint(u32::MAX as u64)]).unwrap();
+        assert_eq!(
+            ethabi::decode(&[ParamType::Bool, ParamType::Uint(32)], &output).unwrap(),
+            vec![AbiToken::Bool(false), AbiToken::Uint(u32::MAX.into())]
+        );
+
+        // outputs of cast proofs of elections with various numbers of voters
+        for num_voters in [1, 3] {
+            let files = ExampleFiles::generate(num_voters);
+            let (_, output) = stark_verifier_run(&files.cast_proof, GAS_LIMIT).unwrap();
+            let decoded = ethabi::decode(&[ParamType::Bool, ParamType::Bytes], &output).unwrap();
+            assert_eq!(
+                decoded,
+                vec![AbiToken::Bool(true), AbiToken::Bytes(vec![1; num_voters])]
+            );
+            assert_eq!(output, ethabi::encode(&decoded));
+        }
+        let (_, output) =
+            stark_verifier_run(&example_file("register_proof.dat"), GAS_LIMIT).unwrap();
+        assert_eq!(
+            ethabi::decode(&[ParamType::Bool, ParamType::Uint(32)], &output).unwrap(),
+            vec![AbiToken::Bool(true), AbiToken::Uint(2u32.into())]
+        );
+    }
+
+    #[test]
+    fn test_check_params() {
+        let manifest = ElectionManifest::new("Adopt the proposal?", Schedule::default());
+        let other_manifest = ElectionManifest::new("Reject the proposal?", Schedule::default());
+        let mut registar = VoterRegistar::get_example(2);
+        registar.bind_manifest(&manifest);
+        let register_proof = registar.get_register_proof().unwrap();
+        let mut elg_root = vec![];
+        winterfell::Serializable::write_batch_into(&registar.elg_root, &mut elg_root);
+
+        let params_input = |phase: u8, manifest: &ElectionManifest, proof_input: &[u8]| {
+            let mut input = with_selector(CHECK_PARAMS_SELECTOR, &[0u8; 96]);
+            input[35] = phase;
+            input[36..36 + BYTES_PER_MANIFEST_DIGEST].copy_from_slice(&manifest.digest_to_bytes());
+            input.extend_from_slice(proof_input);
+            input
+        };
+        let register_input = [elg_root, register_proof].concat();
+        let input = params_input(PARAMS_PHASE_REGISTER, &manifest, &register_input);
+        assert!(is_accepted(stark_verifier_run(&input, GAS_LIMIT)));
+        let input = params_input(PARAMS_PHASE_REGISTER, &other_manifest, &register_input);
+        assert!(!is_accepted(stark_verifier_run(&input, GAS_LIMIT)));
+
+        // proofs of the example files are not bound to any manifest
+        let input = params_input(
+            PARAMS_PHASE_REGISTER,
+            &manifest,
+            &example_file("register_proof.dat")[4..],
+        );
+        assert!(!is_accepted(stark_verifier_run(&input, GAS_LIMIT)));
+        let cast_input = example_file("cast_proof.dat");
+        let input = params_input(PARAMS_PHASE_CAST, &manifest, &cast_input[4..]);
+        assert!(!is_accepted(stark_verifier_run(&input, GAS_LIMIT)));
+
+        // malformed phase, padding and counts
+        let valid_input = params_input(PARAMS_PHASE_REGISTER, &manifest, &register_input);
+        for position in [4, 35, 4 + 32 + BYTES_PER_MANIFEST_DIGEST] {
+            let mut input = valid_input.clone();
+            input[position] = 7;
+            assert!(matches!(
+                stark_verifier_run(&input, GAS_LIMIT),
+                Err(Error::DeserializationError)
+            ));
+        }
+        assert!(matches!(
+            stark_verifier_run(&valid_input[..100], GAS_LIMIT),
+            Err(Error::DeserializationError)
+        ));
+        let mut oversized = params_input(PARAMS_PHASE_CAST, &manifest, &cast_input[4..]);
+        oversized[100..104].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(matches!(
+            stark_verifier_run(&oversized, GAS_LIMIT),
+            Err(Error::DeserializationError)
+        ));
+    }
+}